@@ -0,0 +1,89 @@
+import * as wingCompiler from "./wingc";
+
+const WINGC_GET_AST_DIFF = "wingc_get_ast_diff";
+
+/**
+ * A declaration that was added, removed or modified between two versions of a Wing file.
+ */
+export interface WingDeclarationChange {
+  /** The path of the declaration in its file, e.g. "Bucket.put" */
+  path: string;
+  kind:
+    | "class"
+    | "interface"
+    | "struct"
+    | "enum"
+    | "variable"
+    | "function"
+    | "field"
+    | "method"
+    | "initializer"
+    | "enumValue";
+  change: "added" | "removed" | "modified";
+  oldSpan?: wingCompiler.WingSpan;
+  newSpan?: wingCompiler.WingSpan;
+  oldSignature?: string;
+  newSignature?: string;
+  /** Whether the signature of a modified declaration changed (otherwise only its implementation did) */
+  signatureChanged: boolean;
+  /** Whether code using the old version of the file from another library could break with the new one */
+  breaking: boolean;
+}
+
+export interface GetAstDiffOutput {
+  changes: WingDeclarationChange[];
+  diagnostics: wingCompiler.WingDiagnostic[];
+}
+
+/**
+ * Compares the declarations of two versions of a Wing file, e.g. to detect breaking changes
+ * in the public API of a library.
+ */
+export async function getAstDiff(props: {
+  path: string;
+  oldSource: string;
+  newSource: string;
+  log?: (...args: any[]) => void;
+}): Promise<GetAstDiffOutput> {
+  const wingc = await wingCompiler.load({
+    env: {
+      RUST_BACKTRACE: "full",
+    },
+    imports: {
+      env: {
+        send_diagnostic,
+      },
+    },
+  });
+
+  const diagnostics: wingCompiler.WingDiagnostic[] = [];
+
+  function send_diagnostic(data_ptr: number, data_len: number) {
+    const data_buf = Buffer.from(
+      (wingc.exports.memory as WebAssembly.Memory).buffer,
+      data_ptr,
+      data_len
+    );
+    const data_str = new TextDecoder().decode(data_buf);
+    diagnostics.push(JSON.parse(data_str));
+  }
+
+  const arg = JSON.stringify({
+    path: props.path,
+    oldSource: props.oldSource,
+    newSource: props.newSource,
+  });
+  props.log?.(`invoking %s for: "%s"`, WINGC_GET_AST_DIFF, props.path);
+  let changes: WingDeclarationChange[] = [];
+  const result = wingCompiler.invoke(wingc, WINGC_GET_AST_DIFF, arg);
+  if (typeof result === "number") {
+    // This is a bug in the compiler, the details should be part of the diagnostics.
+  } else {
+    changes = JSON.parse(result);
+  }
+
+  return {
+    changes,
+    diagnostics,
+  };
+}
@@ -9,6 +9,7 @@ import { BuiltinPlatform } from "./constants";
 import { PreflightError } from "./errors";
 import { readFile } from "fs/promises";
 import { fork } from "child_process";
+import { COMPILER_FEATURES, LintLevel, applyLintLevels, loadCompilerConfig } from "./config";
 
 // increase the stack trace limit to 50, useful for debugging Rust panics
 // (not setting the limit too high in case of infinite recursion)
@@ -18,6 +19,7 @@ Error.stackTraceLimit = 50;
 const WINGC_COMPILE = "wingc_compile";
 const WINGC_PREFLIGHT = "preflight.cjs";
 const DOT_WING = ".wing";
+const COMPILER_VERSION: string = require("../package.json").version;
 
 const BUILTIN_PLATFORMS = [
   BuiltinPlatform.SIM,
@@ -67,6 +69,82 @@ export interface CompileOptions {
 
   // overrides the target directory for the output files
   readonly output?: string;
+
+  // compiler dumps to write into the work directory (ast, typed-ast, lifts, ir), for debugging the compiler
+  readonly emit?: string[];
+
+  // emit the preflight code as a single self-contained file
+  readonly bundle?: boolean;
+
+  // configures the JS the compiler emits for inflight code
+  readonly target?: CompileTarget;
+
+  // report compiler warnings as errors
+  readonly strict?: boolean;
+
+  // compiler feature flags to enable (e.g. "dead-code-elimination")
+  readonly features?: string[];
+
+  // how to report compiler warnings, by the code of their message (e.g. { W0001: "allow" })
+  readonly lints?: Record<string, LintLevel>;
+
+  /**
+   * How many errors and warnings to report per file before summarizing the rest (e.g. "and 37 more errors in
+   * this file"). 0 reports all of them. Defaults to 100.
+   */
+  readonly maxErrorsPerFile?: number;
+
+  /**
+   * String representing a regex pattern used to selectively filter which tests to run.
+   * Tests whose names don't match are still type checked, but left out of the compiled program.
+   * Look-arounds and backreferences aren't supported.
+   */
+  readonly testFilter?: string;
+
+  /**
+   * Instrument the generated code with coverage counters. The compiler maps the counters to the
+   * Wing code they count in `.wing/coverage-map.json`, and the program writes the counts to
+   * `.wing/coverage/` when it runs.
+   */
+  readonly coverage?: boolean;
+
+  /**
+   * Whether `expect.snapshot` calls record the values they're passed, so the test runner can store
+   * them: "record" stores the snapshots that are missing, and "update" ignores the stored snapshots
+   * so all of them are rewritten. The calls are listed in `.wing/snapshots.json`.
+   */
+  readonly expectSnapshots?: "record" | "update";
+
+  /**
+   * Lock the public API of a library in its `wing.api.json` file: "check" reports the breaking
+   * changes since the API was locked (unless the major version of the library was bumped) and locks
+   * the current API if there are none, and "update" locks the current API whatever changed.
+   */
+  readonly apiLockMode?: "check" | "update";
+
+  /**
+   * Called with updates on the progress of the compiler, e.g. to show it on large projects
+   */
+  readonly onProgress?: (progress: wingCompiler.WingCompileProgress) => void;
+
+  /**
+   * Contents of Wing files to compile instead of their contents on disk, by path (e.g. the unsaved
+   * changes of an editor, or the changes of a speculative compilation). The files must still exist
+   * on disk.
+   */
+  readonly overlays?: Record<string, string>;
+}
+
+/**
+ * Requirements of the environment inflight code runs in.
+ */
+export interface CompileTarget {
+  /**
+   * The Node.js version inflight code must run on (e.g. "16"). Syntax that this version doesn't support is
+   * rewritten, or reported as an error if it can't be.
+   * @default - the latest Node.js version
+   */
+  readonly node?: string;
 }
 
 /**
@@ -133,6 +211,35 @@ export interface CompileOutput {
   readonly wingcErrors: wingCompiler.WingDiagnostic[];
   // Error that occurred during preflight execution
   readonly preflightError?: PreflightError;
+  // The test blocks of the compiled project (empty for TypeScript entrypoints)
+  readonly tests: TestBlock[];
+  // The files and directories the compilation depended on, so watch mode can watch just these
+  // (empty for TypeScript entrypoints)
+  readonly inputs: CompilationInput[];
+}
+
+/**
+ * A `test` block found by the compiler.
+ */
+export interface TestBlock {
+  /** The name of the test */
+  readonly name: string;
+  /** The file the test is defined in */
+  readonly file: string;
+  /** The span of the test block */
+  readonly span: wingCompiler.WingSpan;
+  /** Whether the test's body runs inflight */
+  readonly inflight: boolean;
+}
+
+/**
+ * A file or directory a compilation depended on.
+ */
+export interface CompilationInput {
+  /** The path of the file or directory */
+  readonly path: string;
+  /** What the file or directory is */
+  readonly kind: "wingFile" | "wingDirectory" | "extern" | "wingToml" | "packageJson" | "jsiiManifest";
 }
 
 /**
@@ -161,6 +268,12 @@ export async function compile(entrypoint: string, options: CompileOptions): Prom
   let wingcErrors: wingCompiler.WingDiagnostic[] = [];
   let failed = false;
 
+  // options passed explicitly take precedence over the [compiler] section of the project's wing.toml
+  const compilerConfig = loadCompilerConfig(entrypointFile);
+  log?.("compiler config: %s", compilerConfig.path);
+  const config = compilerConfig.config;
+  const strict = options.strict ?? config.strict ?? false;
+
   const nearestNodeModules = (dir: string): string => {
     let nodeModules = join(dir, "node_modules");
     while (!existsSync(nodeModules)) {
@@ -187,10 +300,25 @@ export async function compile(entrypoint: string, options: CompileOptions): Prom
     projectDir,
     synthDir,
     color: options.color,
+    emit: options.emit?.length ? options.emit : config.emit,
+    bundle: options.bundle ?? config.bundle,
+    nodeTarget: options.target?.node ?? config.target?.node,
+    features: options.features ?? config.features,
+    maxErrorsPerFile: options.maxErrorsPerFile ?? config.maxErrorsPerFile,
+    testFilter: options.testFilter,
+    coverage: options.coverage,
+    expectSnapshots: options.expectSnapshots,
+    apiLockMode: options.apiLockMode,
+    onProgress: options.onProgress,
+    overlays: options.overlays,
     log,
   });
-  wingcErrors = compileForPreflightResult.diagnostics;
-  if (compileForPreflightResult.diagnostics.map((d) => d.severity).includes("error")) {
+  wingcErrors = applyLintLevels(
+    [...compilerConfig.diagnostics, ...compileForPreflightResult.diagnostics],
+    { ...config.lints, ...options.lints },
+    strict
+  );
+  if (wingcErrors.map((d) => d.severity).includes("error")) {
     failed = true;
   }
 
@@ -241,6 +369,8 @@ export async function compile(entrypoint: string, options: CompileOptions): Prom
     outputDir: !failed ? synthDir : undefined,
     wingcErrors,
     preflightError,
+    tests: compileForPreflightResult.compilerOutput?.tests ?? [],
+    inputs: compileForPreflightResult.compilerOutput?.inputs ?? [],
   };
 }
 
@@ -259,6 +389,8 @@ interface CompileForPreflightResult {
   readonly preflightEntrypoint: string;
   readonly compilerOutput?: {
     imported_namespaces: string[];
+    tests: TestBlock[];
+    inputs: CompilationInput[];
   };
   readonly diagnostics: wingCompiler.WingDiagnostic[];
 }
@@ -269,6 +401,17 @@ async function compileForPreflight(props: {
   projectDir: string;
   synthDir: string;
   color?: boolean;
+  emit?: string[];
+  bundle?: boolean;
+  nodeTarget?: string;
+  features?: string[];
+  maxErrorsPerFile?: number;
+  testFilter?: string;
+  coverage?: boolean;
+  expectSnapshots?: "record" | "update";
+  apiLockMode?: "check" | "update";
+  onProgress?: (progress: wingCompiler.WingCompileProgress) => void;
+  overlays?: Record<string, string>;
   log?: (...args: any[]) => void;
 }): Promise<CompileForPreflightResult> {
   if (props.entrypointFile.endsWith(".ts")) {
@@ -324,21 +467,64 @@ async function compileWingForPreflight(props: {
   projectDir: string;
   synthDir: string;
   color?: boolean;
+  emit?: string[];
+  bundle?: boolean;
+  nodeTarget?: string;
+  features?: string[];
+  maxErrorsPerFile?: number;
+  testFilter?: string;
+  coverage?: boolean;
+  expectSnapshots?: "record" | "update";
+  apiLockMode?: "check" | "update";
+  onProgress?: (progress: wingCompiler.WingCompileProgress) => void;
+  overlays?: Record<string, string>;
   log?: (...args: any[]) => void;
 }) {
   let env: Record<string, string> = {
     RUST_BACKTRACE: "full",
     WING_SYNTH_DIR: normalPath(props.synthDir),
+    WING_COMPILER_VERSION: COMPILER_VERSION,
   };
   if (props.color !== undefined) {
     env.CLICOLOR = props.color ? "1" : "0";
   }
+  if (props.emit?.length) {
+    env.WING_EMIT = props.emit.join(",");
+  }
+  if (props.bundle) {
+    env.WING_BUNDLE = "true";
+  }
+  if (props.nodeTarget) {
+    env.WING_NODE_TARGET = props.nodeTarget;
+  }
+  if (props.maxErrorsPerFile !== undefined) {
+    env.WING_MAX_ERRORS_PER_FILE = props.maxErrorsPerFile.toString();
+  }
+  if (props.testFilter) {
+    env.WING_TEST_FILTER = props.testFilter;
+  }
+  if (props.coverage) {
+    env.WING_COVERAGE = "true";
+  }
+  if (props.expectSnapshots) {
+    env.WING_EXPECT_SNAPSHOTS = props.expectSnapshots;
+  }
+  if (props.apiLockMode) {
+    env.WING_API_LOCK = props.apiLockMode;
+  }
+  for (const feature of props.features ?? []) {
+    const featureEnv = COMPILER_FEATURES[feature];
+    if (featureEnv) {
+      env[featureEnv] = "true";
+    }
+  }
 
   const wingc = await wingCompiler.load({
     env,
     imports: {
       env: {
         send_diagnostic,
+        send_progress,
       },
     },
   });
@@ -355,7 +541,27 @@ async function compileWingForPreflight(props: {
     diagnostics.push(JSON.parse(data_str));
   }
 
-  const arg = `${normalPath(props.entrypointFile)};${normalPath(props.workDir)}`;
+  function send_progress(data_ptr: number, data_len: number) {
+    if (!props.onProgress) {
+      return;
+    }
+    const data_buf = Buffer.from(
+      (wingc.exports.memory as WebAssembly.Memory).buffer,
+      data_ptr,
+      data_len
+    );
+    const progress = JSON.parse(new TextDecoder().decode(data_buf));
+    props.onProgress({ ...progress, file: progress.file ?? undefined });
+  }
+
+  let arg = `${normalPath(props.entrypointFile)};${normalPath(props.workDir)}`;
+  if (props.overlays) {
+    // the overlays go last, since their contents can contain `;`
+    const overlays = Object.fromEntries(
+      Object.entries(props.overlays).map(([path, text]) => [normalPath(path), text])
+    );
+    arg += `;${JSON.stringify(overlays)}`;
+  }
   props.log?.(`invoking %s with: "%s"`, WINGC_COMPILE, arg);
   let compilerOutput: string | number = "";
   try {
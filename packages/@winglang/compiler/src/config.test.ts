@@ -0,0 +1,158 @@
+import { describe, test, expect } from "vitest";
+import { join } from "path";
+import { mkdtemp, mkdir, writeFile } from "fs/promises";
+import { tmpdir } from "os";
+import { applyLintLevels, findWingToml, parseCompilerConfig } from "./config";
+import type { WingDiagnostic } from "./wingc";
+
+describe("wing.toml compiler config", () => {
+  test("reads the [compiler] section", () => {
+    const { config, diagnostics } = parseCompilerConfig(
+      `
+[tf-aws]
+vpc = "new"
+
+[compiler]
+strict = true # fail on warnings
+output-format = "bundle"
+target-node = "16"
+emit = [
+  "ast",
+  'lifts', # trailing commas and comments are fine
+]
+features = ["dead-code-elimination"]
+max-errors-per-file = 20
+
+[compiler.lints]
+W0001 = "allow"
+W0004 = "error"
+`,
+      "wing.toml"
+    );
+
+    expect(diagnostics).toEqual([]);
+    expect(config).toEqual({
+      strict: true,
+      bundle: true,
+      target: { node: "16" },
+      emit: ["ast", "lifts"],
+      features: ["dead-code-elimination"],
+      maxErrorsPerFile: 20,
+      lints: { W0001: "allow", W0004: "error" },
+    });
+  });
+
+  test("reads quoted strings with commas", () => {
+    const { config, diagnostics } = parseCompilerConfig(
+      `[compiler]
+emit = ["ast,lifts"]
+`,
+      "wing.toml"
+    );
+
+    expect(config.emit).toBeUndefined();
+    expect(diagnostics.map((d) => d.message)).toEqual([
+      '"emit" must be a list of: "ast", "typed-ast", "lifts", "ir"',
+    ]);
+  });
+
+  test("reports invalid lints", () => {
+    const { config, diagnostics } = parseCompilerConfig(
+      `[compiler]
+lints = { W0001 = "ignore", unused = "allow", W0002 = "warn" }
+`,
+      "wing.toml"
+    );
+
+    expect(config.lints).toEqual({ W0002: "warn" });
+    expect(diagnostics.map((d) => [d.message, d.span?.start])).toEqual([
+      ['The level of lint "W0001" must be one of: "allow", "warn", "error"', { line: 1, col: 8 }],
+      ['Unknown lint "unused"', { line: 1, col: 0 }],
+    ]);
+  });
+
+  test("reports TOML syntax errors", () => {
+    const { config, diagnostics } = parseCompilerConfig(
+      `[compiler]
+strict = yes
+`,
+      "wing.toml"
+    );
+
+    expect(config).toEqual({});
+    expect(diagnostics).toHaveLength(1);
+    expect(diagnostics[0].message).toMatch(/^Invalid wing.toml: /);
+    expect(diagnostics[0].span?.start.line).toEqual(1);
+  });
+
+  test("reports unknown keys and invalid values", () => {
+    const { config, diagnostics } = parseCompilerConfig(
+      `[compiler]
+strictness = true
+output-format = "zip"
+target-node = "latest"
+`,
+      "wing.toml"
+    );
+
+    expect(config).toEqual({});
+    expect(diagnostics.map((d) => [d.message, d.span?.start, d.span?.end])).toEqual([
+      [
+        'Unknown key "strictness" in the [compiler] section',
+        { line: 1, col: 0 },
+        { line: 1, col: 10 },
+      ],
+      [
+        '"output-format" must be one of: "files", "bundle"',
+        { line: 2, col: 16 },
+        { line: 2, col: 21 },
+      ],
+      [
+        '"target-node" must be a Node.js version (e.g. "18")',
+        { line: 3, col: 14 },
+        { line: 3, col: 22 },
+      ],
+    ]);
+    expect(diagnostics.every((d) => d.span?.file_id === "wing.toml")).toBeTruthy();
+  });
+
+  test("finds the wing.toml of the entrypoint's project", async () => {
+    const projectDir = await mkdtemp(join(tmpdir(), "-wing-config-test"));
+    await writeFile(join(projectDir, "wing.toml"), "[compiler]\n");
+    await mkdir(join(projectDir, "src"));
+
+    expect(findWingToml(join(projectDir, "src", "main.w"))).toEqual(join(projectDir, "wing.toml"));
+  });
+
+  test("applies lint levels to warnings", () => {
+    const diagnostic = (severity: "error" | "warning", code?: string): WingDiagnostic => ({
+      message: code ?? severity,
+      annotations: [],
+      hints: [],
+      severity,
+      code,
+    });
+    const diagnostics = [
+      diagnostic("warning", "W0001"),
+      diagnostic("warning", "W0002"),
+      diagnostic("warning", "W0004"),
+      diagnostic("warning"),
+      diagnostic("error", "E2001"),
+    ];
+    const lints = { W0001: "allow", W0002: "error", W0004: "warn" } as const;
+
+    expect(applyLintLevels(diagnostics, lints, false).map((d) => [d.message, d.severity])).toEqual([
+      ["W0002", "error"],
+      ["W0004", "warning"],
+      ["warning", "warning"],
+      ["E2001", "error"],
+    ]);
+    // in strict mode, only warnings explicitly set to "warn" stay warnings
+    expect(applyLintLevels(diagnostics, lints, true).map((d) => [d.message, d.severity])).toEqual([
+      ["W0002", "error"],
+      ["W0004", "warning"],
+      ["warning", "error"],
+      ["E2001", "error"],
+    ]);
+  });
+});
@@ -0,0 +1,365 @@
+import { existsSync, readFileSync } from "fs";
+import { dirname, join, resolve } from "path";
+import * as toml from "toml";
+import type { CompileOptions } from "./compile";
+import type { WingDiagnostic, WingSpan } from "./wingc";
+
+const WING_TOML = "wing.toml";
+const COMPILER_SECTION = "compiler";
+const LINTS_TABLE = "lints";
+
+/** Output formats supported by the `output-format` key */
+const OUTPUT_FORMATS = ["files", "bundle"];
+
+/** Compiler feature flags supported by the `features` key, and the environment variables that enable them */
+export const COMPILER_FEATURES: Record<string, string> = {
+  "dead-code-elimination": "WING_DEAD_CODE_ELIMINATION",
+  openapi: "WING_OPENAPI",
+  "validate-ast": "WING_VALIDATE_AST",
+};
+
+/** Compiler dumps supported by the `emit` key */
+const DUMP_KINDS = ["ast", "typed-ast", "lifts", "ir"];
+
+/**
+ * How a compiler warning is reported: not at all, as a warning (the default) or as an error.
+ */
+export type LintLevel = "allow" | "warn" | "error";
+
+const LINT_LEVELS: LintLevel[] = ["allow", "warn", "error"];
+
+/** Lints are named by the code of their warning in the compiler's message catalog */
+const LINT_CODE = /^W\d{4}$/;
+
+/**
+ * Compile options that can be set in the `[compiler]` section of a project's `wing.toml`.
+ */
+export type CompilerConfig = Pick<
+  CompileOptions,
+  "strict" | "bundle" | "target" | "emit" | "features" | "maxErrorsPerFile" | "lints"
+>;
+
+export interface LoadCompilerConfigResult {
+  /** The path of the `wing.toml` file the configuration was read from, if there is one */
+  readonly path?: string;
+  readonly config: CompilerConfig;
+  readonly diagnostics: WingDiagnostic[];
+}
+
+/**
+ * Finds the `wing.toml` of the project the entrypoint belongs to, the same way the compiler finds the project's root:
+ * by searching the entrypoint's directory and its parents for a `wing.toml`, or a `package.json` with a "wing" field.
+ */
+export function findWingToml(entrypoint: string): string | undefined {
+  let dir = resolve(dirname(entrypoint));
+  while (true) {
+    const wingToml = join(dir, WING_TOML);
+    if (existsSync(wingToml)) {
+      return wingToml;
+    }
+    const packageJson = join(dir, "package.json");
+    if (existsSync(packageJson)) {
+      try {
+        if (JSON.parse(readFileSync(packageJson, "utf-8")).wing !== undefined) {
+          return undefined;
+        }
+      } catch {
+        // not a project root we can read, keep looking
+      }
+    }
+    const parent = dirname(dir);
+    if (parent === dir) {
+      return undefined;
+    }
+    dir = parent;
+  }
+}
+
+/**
+ * Reads the `[compiler]` section of the project's `wing.toml`, if there is one.
+ * Unknown keys and invalid values are reported as diagnostics pointing into the file.
+ */
+export function loadCompilerConfig(entrypoint: string): LoadCompilerConfigResult {
+  const path = findWingToml(entrypoint);
+  if (!path) {
+    return { config: {}, diagnostics: [] };
+  }
+  return { path, ...parseCompilerConfig(readFileSync(path, "utf-8"), path) };
+}
+
+/**
+ * Parses the `[compiler]` section of a `wing.toml` file. Other sections are left to the platforms that read them.
+ */
+export function parseCompilerConfig(
+  content: string,
+  path: string
+): { config: CompilerConfig; diagnostics: WingDiagnostic[] } {
+  const diagnostics: WingDiagnostic[] = [];
+  const config: {
+    strict?: boolean;
+    bundle?: boolean;
+    target?: { node?: string };
+    emit?: string[];
+    features?: string[];
+    maxErrorsPerFile?: number;
+    lints?: Record<string, LintLevel>;
+  } = {};
+  const locations = new TomlLocations(content, path);
+
+  const error = (message: string, span: WingSpan | undefined, hints: string[] = []) => {
+    diagnostics.push({ message, span, annotations: [], hints, severity: "error" });
+  };
+
+  let document: Record<string, unknown>;
+  try {
+    document = toml.parse(content);
+  } catch (e: any) {
+    // the parser reports 1-based positions
+    const line = typeof e.line === "number" ? e.line - 1 : 0;
+    const col = typeof e.column === "number" ? e.column - 1 : 0;
+    error(`Invalid ${WING_TOML}: ${e.message}`, locations.at(line, col));
+    return { config, diagnostics };
+  }
+
+  const section = document[COMPILER_SECTION];
+  if (section === undefined) {
+    return { config, diagnostics };
+  }
+  if (!isTable(section)) {
+    error(`"${COMPILER_SECTION}" must be a table`, locations.value([], COMPILER_SECTION));
+    return { config, diagnostics };
+  }
+
+  for (const [key, value] of Object.entries(section)) {
+    const keySpan = locations.key([COMPILER_SECTION], key);
+    const valueSpan = locations.value([COMPILER_SECTION], key);
+
+    const expectBoolean = (): boolean | undefined => {
+      if (typeof value !== "boolean") {
+        error(`"${key}" must be true or false`, valueSpan);
+        return undefined;
+      }
+      return value;
+    };
+    const expectOneOf = (allowed: string[]): string | undefined => {
+      if (typeof value !== "string" || !allowed.includes(value)) {
+        error(`"${key}" must be one of: ${allowed.map((a) => `"${a}"`).join(", ")}`, valueSpan);
+        return undefined;
+      }
+      return value;
+    };
+    const expectListOf = (allowed: string[]): string[] | undefined => {
+      const items: unknown = typeof value === "string" ? [value] : value;
+      if (
+        !Array.isArray(items) ||
+        items.some((item) => typeof item !== "string" || !allowed.includes(item))
+      ) {
+        error(
+          `"${key}" must be a list of: ${allowed.map((a) => `"${a}"`).join(", ")}`,
+          valueSpan
+        );
+        return undefined;
+      }
+      return items;
+    };
+
+    switch (key) {
+      case "strict":
+        config.strict = expectBoolean();
+        break;
+      case "output-format": {
+        const format = expectOneOf(OUTPUT_FORMATS);
+        if (format) {
+          config.bundle = format === "bundle";
+        }
+        break;
+      }
+      case "target-node": {
+        const node = typeof value === "number" ? value.toString() : value;
+        if (typeof node !== "string" || !/^v?\d+(\.\d+){0,2}$/.test(node)) {
+          error(`"${key}" must be a Node.js version (e.g. "18")`, valueSpan);
+        } else {
+          config.target = { node };
+        }
+        break;
+      }
+      case "emit":
+        config.emit = expectListOf(DUMP_KINDS);
+        break;
+      case "features":
+        config.features = expectListOf(Object.keys(COMPILER_FEATURES));
+        break;
+      case "max-errors-per-file":
+        if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+          error(`"${key}" must be a number of errors (0 reports all of them)`, valueSpan);
+        } else {
+          config.maxErrorsPerFile = value;
+        }
+        break;
+      case LINTS_TABLE:
+        config.lints = parseLints(value, locations, error);
+        break;
+      case "version":
+        // the compiler versions a library supports, checked by the compiler when the library is brought
+        if (typeof value !== "string") {
+          error(`"${key}" must be a version range (e.g. ">=0.80.0")`, valueSpan);
+        }
+        break;
+      default:
+        error(`Unknown key "${key}" in the [${COMPILER_SECTION}] section`, keySpan, [
+          "Supported keys are: strict, output-format, target-node, emit, features, max-errors-per-file, lints, version",
+        ]);
+    }
+  }
+
+  return { config, diagnostics };
+}
+
+/**
+ * Parses the `[compiler.lints]` table, which maps warning codes to the level they're reported at.
+ */
+function parseLints(
+  value: unknown,
+  locations: TomlLocations,
+  error: (message: string, span: WingSpan | undefined, hints?: string[]) => void
+): Record<string, LintLevel> | undefined {
+  const table = [COMPILER_SECTION, LINTS_TABLE];
+  if (!isTable(value)) {
+    const span = locations.value([COMPILER_SECTION], LINTS_TABLE);
+    error(`"${LINTS_TABLE}" must be a table of warning codes and levels`, span);
+    return undefined;
+  }
+
+  const lints: Record<string, LintLevel> = {};
+  for (const [code, level] of Object.entries(value)) {
+    if (!LINT_CODE.test(code)) {
+      error(`Unknown lint "${code}"`, locations.key(table, code), [
+        'Lints are named by the code of their warning (e.g. "W0001")',
+      ]);
+      continue;
+    }
+    if (typeof level !== "string" || !LINT_LEVELS.includes(level as LintLevel)) {
+      error(
+        `The level of lint "${code}" must be one of: ${LINT_LEVELS.map((l) => `"${l}"`).join(", ")}`,
+        locations.value(table, code)
+      );
+      continue;
+    }
+    lints[code] = level as LintLevel;
+  }
+  return lints;
+}
+
+/**
+ * Applies lint levels to the compiler's diagnostics: warnings whose lint is set to "allow" are dropped and the ones set
+ * to "error" are reported as errors. In strict mode, the other warnings are reported as errors too, unless their lint
+ * is explicitly set to "warn" or "allow".
+ */
+export function applyLintLevels(
+  diagnostics: WingDiagnostic[],
+  lints: Record<string, LintLevel>,
+  strict: boolean
+): WingDiagnostic[] {
+  return diagnostics.flatMap((d) => {
+    if (d.severity !== "warning") {
+      return [d];
+    }
+    const level = (d.code !== undefined ? lints[d.code] : undefined) ?? (strict ? "error" : "warn");
+    switch (level) {
+      case "allow":
+        return [];
+      case "warn":
+        return [d];
+      case "error":
+        return [{ ...d, severity: "error" as const }];
+    }
+  });
+}
+
+function isTable(value: unknown): value is Record<string, unknown> {
+  return typeof value === "object" && value !== null && !Array.isArray(value) && !(value instanceof Date);
+}
+
+/**
+ * Finds where keys are defined in a TOML file, so diagnostics can point at them. The `toml` parser doesn't keep the
+ * positions of what it parses, so the lines of the file are searched for `key = value` pairs under table headers.
+ * Keys of inline tables can't be found this way, and are located at the key of the inline table instead.
+ */
+class TomlLocations {
+  private readonly lines: string[];
+  private readonly lineOffsets: number[] = [];
+  /** Where each `table.key` is defined: its line, and the columns of its key and of its value */
+  private readonly keys = new Map<string, { line: number; key: [number, number]; value: [number, number] }>();
+
+  constructor(content: string, private readonly path: string) {
+    this.lines = content.split("\n");
+    let offset = 0;
+    let table: string[] = [];
+    for (const [index, line] of this.lines.entries()) {
+      this.lineOffsets.push(offset);
+      offset += line.length + 1;
+
+      const header = line.match(/^(\s*\[\s*)([^\][]+?)\s*\]/);
+      if (header) {
+        const [, open, name] = header;
+        table = name.split(".").map((part) => unquote(part.trim()));
+        const nameSpan: [number, number] = [open.length, open.length + name.length];
+        this.keys.set(table.join("."), { line: index, key: nameSpan, value: nameSpan });
+        continue;
+      }
+      const pair = line.match(/^(\s*)("[^"]*"|'[^']*'|[A-Za-z0-9_-]+)\s*=\s*/);
+      if (pair) {
+        const [match, indent, key] = pair;
+        const valueEnd = line.replace(/\s+#[^"']*$/, "").trimEnd().length;
+        this.keys.set([...table, unquote(key)].join("."), {
+          line: index,
+          key: [indent.length, indent.length + key.length],
+          value: [match.length, Math.max(valueEnd, match.length)],
+        });
+      }
+    }
+  }
+
+  /** The span of a key, or of the closest table it's in that could be found */
+  key(table: string[], key: string): WingSpan | undefined {
+    return this.find(table, key, "key");
+  }
+
+  /** The span of a key's value (only its first line for values that span multiple lines) */
+  value(table: string[], key: string): WingSpan | undefined {
+    return this.find(table, key, "value");
+  }
+
+  /** A span of a single character */
+  at(line: number, col: number): WingSpan {
+    return this.span(line, col, col + 1);
+  }
+
+  private find(table: string[], key: string, part: "key" | "value"): WingSpan | undefined {
+    const path = [...table, key];
+    while (path.length > 0) {
+      const location = this.keys.get(path.join("."));
+      if (location) {
+        const [start, end] = location[part];
+        return this.span(location.line, start, end);
+      }
+      path.pop();
+    }
+    return undefined;
+  }
+
+  private span(line: number, start: number, end: number): WingSpan {
+    const lineOffset = this.lineOffsets[line] ?? 0;
+    return {
+      start: { line, col: start },
+      end: { line, col: end },
+      file_id: this.path,
+      start_offset: lineOffset + start,
+      end_offset: lineOffset + end,
+    };
+  }
+}
+
+function unquote(key: string): string {
+  return /^(".*"|'.*')$/.test(key) ? key.slice(1, -1) : key;
+}
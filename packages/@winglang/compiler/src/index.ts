@@ -1,4 +1,6 @@
+export * from "./astDiff";
 export * from "./compile";
+export * from "./config";
 export * from "./constants";
 export * from "./errors";
 export * from "./generateDocs";
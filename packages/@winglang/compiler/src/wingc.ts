@@ -8,23 +8,31 @@ import { promisify } from "util";
 export type WingCompilerFunction =
   | "wingc_compile"
   | "wingc_generate_docs"
+  | "wingc_get_ast_diff"
   | "wingc_on_did_open_text_document"
   | "wingc_on_did_change_text_document"
+  | "wingc_on_did_change_text_document_syntax"
+  | "wingc_on_check_text_document"
+  | "wingc_on_did_close_text_document"
   | "wingc_on_completion"
   | "wingc_on_signature_help"
   | "wingc_on_goto_definition"
   | "wingc_on_document_symbol"
+  | "wingc_on_code_lens"
   | "wingc_on_rename"
   | "wingc_on_prepare_rename"
   | "wingc_on_semantic_tokens"
   | "wingc_on_hover"
-  | "wingc_on_code_action";
+  | "wingc_on_code_action"
+  | "wingc_on_did_change_watched_files";
 
 export interface WingCompilerLoadOptions {
   /**
    * Additional imports to pass to the WASI instance. Imports objects/functions that WASM code can invoke.
    *
-   * @default `{ wasi_snapshot_preview1: wasi.wasiImport, env: { send_diagnostic: () => {} }`
+   * The functions in `env` are added to the default ones.
+   *
+   * @default `{ wasi_snapshot_preview1: wasi.wasiImport, env: { send_diagnostic: () => {}, send_progress: () => {} }`
    */
   imports?: Record<string, any>;
 
@@ -189,11 +197,14 @@ export async function load(options: WingCompilerLoadOptions) {
 
   const importObject = {
     wasi_snapshot_preview1: wasi.wasiImport,
+    ...(options.imports ?? {}),
     env: {
       // This function is used only by the lsp
       send_diagnostic: () => {},
+      // Called by `wingc_compile` with updates on its progress
+      send_progress: () => {},
+      ...(options.imports?.env ?? {}),
     },
-    ...(options.imports ?? {}),
   } as any;
 
   log?.("compiling wingc WASM module");
@@ -222,6 +233,16 @@ export async function load(options: WingCompilerLoadOptions) {
 const LOW_MASK = 2n ** 32n - 1n;
 const HIGH_MASK = BigInt(32);
 
+// From progress.rs
+export interface WingCompileProgress {
+  /** The current phase of the compilation (e.g. "type-checking") */
+  phase: string;
+  /** The file the current phase is processing, if it processes files one by one */
+  file?: string;
+  /** How much of the compilation is done, from 0 to 100 */
+  percent: number;
+}
+
 // From diagnostic.rs
 export interface WingDiagnostic {
   message: string;
@@ -232,6 +253,8 @@ export interface WingDiagnostic {
   }[];
   hints: string[];
   severity: "error" | "warning";
+  /** The code of the message in the compiler's message catalog, if it has one */
+  code?: string;
 }
 
 export interface WingSpan {
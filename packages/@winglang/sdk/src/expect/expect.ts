@@ -5,6 +5,9 @@ import { Regex } from "../std";
 /**
  * The Util class provides methods for making assertions in tests,
  * such as comparing two strings for equality with rich error messages.
+ *
+ * When an assertion fails, its error shows the Wing code of the call and the values
+ * of the expressions in its arguments.
  * @inflight
  */
 export class Util {
@@ -97,6 +100,21 @@ export class Util {
     }
   }
 
+  /**
+   * Compares a value with its snapshot, which is stored as JSON in `<file>.snap.json` next to the
+   * Wing file of the test. The snapshot is created the first time the test runs, and can be
+   * updated with `wing test --update-snapshots`.
+   * @param actual The value to test.
+   * @throws Will throw an error if the value does not match its snapshot.
+   * @returns void
+   */
+  public static snapshot(actual: unknown): void {
+    // the compiler turns `expect.snapshot()` calls in Wing tests into calls that know where their snapshot is
+    throw new Error(
+      `expect.snapshot(${JSON.stringify(actual)}) can only be used in the tests of a Wing project`
+    );
+  }
+
   /**
    * Marks a test as failed.
    * @param message An optional message to include with the failure.
@@ -1,8 +1,9 @@
 // Code in this file will be automatically included in all inflight code bundles,
 // so avoid importing anything heavy here.
-import { notDeepStrictEqual } from "node:assert";
+import { AssertionError, notDeepStrictEqual } from "node:assert";
 import fs from "node:fs";
 import * as path from "node:path";
+import { inspect } from "node:util";
 import type { Construct } from "constructs";
 import { parse } from "dotenv";
 import { expand } from "dotenv-expand";
@@ -30,12 +31,300 @@ export function neq(a: any, b: any): boolean {
   }
 }
 
+// Marks the standard library's classes whose instances are values, and are hashed by their fields
+const VALUE_CLASS = Symbol.for("@winglang/sdk.std.valueClass");
+const objectIds = new WeakMap<object, number>();
+let nextObjectId = 0;
+
+function objectId(value: object): number {
+  let id = objectIds.get(value);
+  if (id === undefined) {
+    id = nextObjectId++;
+    objectIds.set(value, id);
+  }
+  return id;
+}
+
+/**
+ * Returns a string that's the same for any two values with equal contents. Objects that aren't values
+ * (instances of classes) are represented by their identity.
+ */
+export function stableHash(value: any): string {
+  if (value === undefined || value === null) {
+    return "nil";
+  }
+  if (typeof value !== "object") {
+    return `${typeof value}:${String(value)}`;
+  }
+  if (value instanceof Date) {
+    return `date:${value.toISOString()}`;
+  }
+  if (Array.isArray(value)) {
+    return `[${value.map(stableHash).join(",")}]`;
+  }
+  if (value instanceof Set) {
+    return `set{${[...value].map(stableHash).sort().join(",")}}`;
+  }
+  if (value instanceof Map) {
+    const entries = [...value].map(([k, v]) => `${stableHash(k)}=>${stableHash(v)}`);
+    return `map{${entries.sort().join(",")}}`;
+  }
+  const proto = Object.getPrototypeOf(value);
+  const isPlain = proto === Object.prototype || proto === null;
+  if (isPlain || proto.constructor?.[VALUE_CLASS] === true) {
+    const fields = Object.keys(value)
+      .filter((k) => value[k] !== undefined)
+      .sort()
+      .map((k) => `${JSON.stringify(k)}:${stableHash(value[k])}`);
+    return `${isPlain ? "" : `class#${objectId(proto.constructor)}`}{${fields.join(",")}}`;
+  }
+  return `object#${objectId(value)}`;
+}
+
+/**
+ * Hashes an object by its `hash` method (see `std.IHashable`), which is async inflight. Objects of
+ * different classes never have the same hash.
+ */
+export function hashByMethod(value: any): string | Promise<string> {
+  if (value === undefined || value === null) {
+    return "nil";
+  }
+  const prefix = `class#${objectId(value.constructor)}#`;
+  const hash = value.hash();
+  return hash instanceof Promise ? hash.then((h: string) => prefix + h) : prefix + hash;
+}
+
+type Hasher = (value: any) => string | Promise<string>;
+const NOT_FOUND = Symbol("notFound");
+
+function then<T, U>(value: T | Promise<T>, f: (value: T) => U | Promise<U>): U | Promise<U> {
+  return value instanceof Promise ? value.then(f) : f(value);
+}
+
+// Returns the member of `set` with the same hash as `value`, or `NOT_FOUND`
+function findSetMember(set: Set<any>, value: any, hash: Hasher): any {
+  if (set.has(value)) {
+    return value;
+  }
+  // primitives only have the same hash as themselves
+  if (value === null || typeof value !== "object") {
+    return NOT_FOUND;
+  }
+  const members = [...set];
+  const find = (target: string, i: number): any => {
+    for (; i < members.length; i++) {
+      const h = hash(members[i]);
+      if (h instanceof Promise) {
+        return h.then((x) => (x === target ? members[i] : find(target, i + 1)));
+      }
+      if (h === target) {
+        return members[i];
+      }
+    }
+    return NOT_FOUND;
+  };
+  return then(hash(value), (target) => find(target, 0));
+}
+
+/**
+ * Whether `set` has a member with the same hash as `value`. The hash is the value's `stableHash`
+ * unless given.
+ */
+export function setHas(
+  set: Set<any> | undefined,
+  value: any,
+  hash: Hasher = stableHash
+): boolean | undefined | Promise<boolean> {
+  if (set === undefined || set === null) {
+    return undefined;
+  }
+  return then(findSetMember(set, value, hash), (member) => member !== NOT_FOUND);
+}
+
+/**
+ * Adds `value` to `set` unless it already has a member with the same hash.
+ */
+export function setAdd<T>(
+  set: Set<T> | undefined,
+  value: T,
+  hash: Hasher = stableHash
+): Set<T> | undefined | Promise<Set<T>> {
+  if (set === undefined || set === null) {
+    return undefined;
+  }
+  return then(findSetMember(set, value, hash), (member) => {
+    if (member === NOT_FOUND) {
+      set.add(value);
+    }
+    return set;
+  });
+}
+
+/**
+ * Removes the member of `set` with the same hash as `value`, returning whether there was one.
+ */
+export function setDelete(
+  set: Set<any> | undefined,
+  value: any,
+  hash: Hasher = stableHash
+): boolean | undefined | Promise<boolean> {
+  if (set === undefined || set === null) {
+    return undefined;
+  }
+  return then(findSetMember(set, value, hash), (member) => member !== NOT_FOUND && set.delete(member));
+}
+
+/**
+ * Creates a `Set` of the given members, skipping those with the same hash as an earlier one.
+ */
+export function toSet<T>(members: T[], hash: Hasher = stableHash): Set<T> | Promise<Set<T>> {
+  const set = new Set<T>();
+  return members.reduce<Set<T> | Promise<Set<T>>>(
+    (acc, member) => then(acc, () => setAdd(set, member, hash) as Set<T> | Promise<Set<T>>),
+    set
+  );
+}
+
 export function assert(condition: any, message: string): asserts condition {
   if (!condition) {
     throw new Error("assertion failed: " + message);
   }
 }
 
+/**
+ * A sub-expression of the arguments of an `expect` call whose value is recorded.
+ */
+export interface RecordedExpr {
+  /** The Wing source of the sub-expression */
+  readonly text: string;
+  /** The column (relative to the start of the call) to show the value under, if the call is on a single line */
+  readonly column: number | null;
+}
+
+/**
+ * Records the values of the sub-expressions of an `expect` call's arguments.
+ */
+export class ExpectValues {
+  public readonly values = new Map<number, unknown>();
+
+  public record<T>(index: number, value: T): T {
+    this.values.set(index, value);
+    return value;
+  }
+}
+
+/**
+ * Runs an `expect` call, adding the values of the sub-expressions of its arguments to the
+ * message of the error it throws if the assertion fails, e.g.:
+ * ```
+ * expect.equal(x.length, y)
+ *                |       |
+ *                3       4
+ * ```
+ */
+export function expectValues<T>(
+  call: (values: ExpectValues) => T,
+  source: string,
+  exprs: RecordedExpr[]
+): T {
+  const values = new ExpectValues();
+  const explain = (error: any): never => {
+    if (error instanceof Error) {
+      const message = error.message;
+      error.message = `${message}\n\n${renderExpectValues(source, exprs, values)}`;
+      error.stack = error.stack?.replace(message, error.message);
+    }
+    throw error;
+  };
+  try {
+    const result = call(values);
+    if (result instanceof Promise) {
+      return result.catch(explain) as T;
+    }
+    return result;
+  } catch (error) {
+    return explain(error);
+  }
+}
+
+function renderExpectValues(
+  source: string,
+  exprs: RecordedExpr[],
+  values: ExpectValues
+): string {
+  const recorded = exprs
+    .map((expr, index) => ({ ...expr, index }))
+    .filter((expr) => values.values.has(expr.index));
+  const render = (value: unknown) => {
+    const text = inspect(value, { depth: 2, breakLength: Infinity });
+    return text.length > 80 ? `${text.slice(0, 77)}...` : text;
+  };
+
+  // list the values when the call spans multiple lines
+  if (source.includes("\n") || recorded.some((expr) => expr.column === null)) {
+    return [
+      source,
+      ...recorded.map((expr) => `  ${expr.text} = ${render(values.values.get(expr.index))}`),
+    ].join("\n");
+  }
+
+  // otherwise, show each value under its sub-expression, starting from the rightmost one
+  const columns = (maxColumn: number) =>
+    [
+      ...new Set(
+        recorded.map((expr) => expr.column!).filter((column) => column < maxColumn)
+      ),
+    ].sort((a, b) => a - b);
+  const pipes = (cols: number[]) =>
+    cols.reduce((line, column) => line.padEnd(column) + "|", "");
+
+  const lines = [source, pipes(columns(Infinity))];
+  const byColumn = [...recorded].sort((a, b) => b.column! - a.column!);
+  for (const expr of byColumn) {
+    lines.push(
+      pipes(columns(expr.column!)).padEnd(expr.column!) + render(values.values.get(expr.index))
+    );
+  }
+  return lines.join("\n");
+}
+
+/**
+ * Information the compiler embeds in an `expect.snapshot` call.
+ */
+export interface SnapshotInfo {
+  /** The index of the call in the snapshot manifest of the program */
+  readonly index: number;
+  /** The key of the snapshot in its snapshot file */
+  readonly id: string;
+  /** The directory to record the value in for the test runner, if it's recorded */
+  readonly dir?: string;
+  /** The stored snapshot (missing if there's none yet, or snapshots are being updated) */
+  readonly expected?: unknown;
+}
+
+/**
+ * Implements `expect.snapshot(value)`: compares the value with its stored snapshot and records it,
+ * so the test runner can create or update the snapshot.
+ */
+export function snapshot(actual: unknown, info: SnapshotInfo): void {
+  // snapshots are stored as JSON, so compare what would be stored
+  const value = actual === undefined ? null : JSON.parse(JSON.stringify(actual));
+  if (info.dir) {
+    fs.mkdirSync(info.dir, { recursive: true });
+    fs.writeFileSync(path.join(info.dir, `${info.index}.json`), JSON.stringify(value));
+  }
+
+  if ("expected" in info && !eq(value, info.expected)) {
+    const error = new AssertionError({
+      actual: value,
+      expected: info.expected,
+      operator: "deepStrictEqual",
+    });
+    error.message = `Snapshot "${info.id}" doesn't match the stored value (run "wing test --update-snapshots" to update it)\n\n${error.message}`;
+    throw error;
+  }
+}
+
 export function range(start: number, end: number, inclusive: boolean) {
   function* iterator() {
     let i = start;
@@ -63,6 +352,77 @@ export function unwrap<T>(value: T): T | never {
   throw new Error("Unexpected nil");
 }
 
+/**
+ * The type an inline JavaScript block declares for its result, as described by the compiler.
+ * `null` stands for types whose values aren't checked (e.g. classes and structs).
+ */
+export type InlineJsType =
+  | "str"
+  | "num"
+  | "bool"
+  | { readonly optional: InlineJsType }
+  | { readonly array: InlineJsType }
+  | { readonly map: InlineJsType }
+  | { readonly set: InlineJsType }
+  | null;
+
+/**
+ * Checks that the result of an `unsafe js` block is a value of the type the block declares, since
+ * the compiler can't check the JavaScript code itself.
+ */
+export function checkInlineJsResult<T>(
+  value: T,
+  type: InlineJsType,
+  typeName: string
+): T {
+  if (value instanceof Promise) {
+    return value.then((v) => checkInlineJsResult(v, type, typeName)) as T;
+  }
+  if (!isOfInlineJsType(value, type)) {
+    const found = inspect(value, { depth: 2, breakLength: Infinity });
+    throw new TypeError(
+      `Inline JavaScript returned ${found}, which is not a value of type "${typeName}"`
+    );
+  }
+  return value;
+}
+
+function isOfInlineJsType(value: unknown, type: InlineJsType): boolean {
+  if (type === null) {
+    return true;
+  }
+  switch (type) {
+    case "str":
+      return typeof value === "string";
+    case "num":
+      return typeof value === "number";
+    case "bool":
+      return typeof value === "boolean";
+  }
+  if ("optional" in type) {
+    return value == null || isOfInlineJsType(value, type.optional);
+  }
+  if ("array" in type) {
+    return (
+      Array.isArray(value) &&
+      value.every((item) => isOfInlineJsType(item, type.array))
+    );
+  }
+  if ("set" in type) {
+    return (
+      value instanceof Set &&
+      [...value].every((item) => isOfInlineJsType(item, type.set))
+    );
+  }
+  return (
+    typeof value === "object" &&
+    value !== null &&
+    !Array.isArray(value) &&
+    !(value instanceof Set) &&
+    Object.values(value).every((item) => isOfInlineJsType(item, type.map))
+  );
+}
+
 export function lookup(obj: any, index: string | number): any {
   checkIndex(index);
 
@@ -84,6 +444,14 @@ export function lookup(obj: any, index: string | number): any {
   return obj[index];
 }
 
+export function lookupOptional(obj: any, index: string | number): any {
+  if (obj == null) {
+    return undefined;
+  }
+
+  return lookup(obj, index);
+}
+
 export function assign(
   obj: any,
   index: string | number,
@@ -287,3 +655,53 @@ export function loadEnvVariables(
 
   return expandedEnvVariables.parsed;
 }
+
+/**
+ * The coverage counters of a program compiled with coverage instrumentation.
+ */
+export interface CoverageCounters {
+  /** Increments the counter with the given id */
+  hit(id: number): void;
+}
+
+/**
+ * Returns the coverage counters instrumented code increments. The counts are written to a file
+ * of `dir` (one per process) shortly after they change and when the process exits.
+ */
+export function coverage(dir: string): CoverageCounters {
+  // all the files of a program (and all the programs in a process) share the counters of a directory
+  const all: Record<string, CoverageCounters> = ((globalThis as any).$wingCoverage ??= {});
+  if (all[dir]) {
+    return all[dir];
+  }
+
+  const hits: Record<number, number> = {};
+  const file = path.join(
+    dir,
+    `hits-${process.pid}-${Math.random().toString(36).slice(2)}.json`
+  );
+  let scheduled = false;
+  const flush = () => {
+    scheduled = false;
+    try {
+      fs.mkdirSync(dir, { recursive: true });
+      fs.writeFileSync(file, JSON.stringify(hits));
+    } catch {
+      // coverage is best effort, don't fail the program
+    }
+  };
+  process.once("exit", flush);
+
+  const counters: CoverageCounters = {
+    hit(id: number) {
+      hits[id] = (hits[id] ?? 0) + 1;
+      // processes running inflight code may be killed without exiting, so don't wait for the exit
+      if (!scheduled) {
+        scheduled = true;
+        setImmediate(flush).unref();
+      }
+    },
+  };
+  all[dir] = counters;
+  return counters;
+}
@@ -20,6 +20,19 @@ export class Array {
 
   private constructor() {}
 
+  /**
+   * Create an ImmutableArray from the given values, the type of its elements is inferred from them
+   *
+   * @macro Array.of(...$args$)
+   *
+   * @param values the values of the array
+   * @returns an ImmutableArray with the given values
+   */
+  public static of(...values: T1[]): Array {
+    values;
+    throw new Error("Macro");
+  }
+
   /**
    * The length of the array
    * @returns the length of the array
@@ -157,6 +170,19 @@ export class MutArray {
 
   private constructor() {}
 
+  /**
+   * Create a MutableArray from the given values, the type of its elements is inferred from them
+   *
+   * @macro Array.of(...$args$)
+   *
+   * @param values the values of the array
+   * @returns a MutableArray with the given values
+   */
+  public static of(...values: T1[]): MutArray {
+    values;
+    throw new Error("Macro");
+  }
+
   /**
    * The length of the array
    * @returns the length of the array
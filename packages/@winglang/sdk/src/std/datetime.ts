@@ -245,3 +245,6 @@ export class Datetime implements ILiftable {
     return new Date(this.timestampMs).toISOString();
   }
 }
+
+// datetimes are values, so sets hash them by their fields
+(Datetime as any)[Symbol.for("@winglang/sdk.std.valueClass")] = true;
@@ -156,3 +156,6 @@ export class Duration implements ILiftable {
     }))`;
   }
 }
+
+// durations are values, so sets hash them by their fields
+(Duration as any)[Symbol.for("@winglang/sdk.std.valueClass")] = true;
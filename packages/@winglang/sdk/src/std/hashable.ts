@@ -0,0 +1,12 @@
+/**
+ * An object that's hashed by its `hash` method when it's a member of a `Set`, so that two members
+ * with the same hash are the same member.
+ *
+ * Only preflight classes can implement it.
+ */
+export interface IHashable {
+  /**
+   * Returns a string that's the same for any two objects that are the same set member.
+   */
+  hash(): string;
+}
@@ -3,6 +3,7 @@ export * from "./bool";
 export * from "./datetime";
 export * from "./duration";
 export * from "./generics";
+export * from "./hashable";
 export * from "./json";
 export * from "./json_schema";
 export * from "./map";
@@ -2,7 +2,17 @@ import { mkdtemp, writeFile } from "fs/promises";
 import { tmpdir } from "os";
 import { join } from "path";
 import { describe, expect, it } from "vitest";
-import { loadEnvVariables } from "../src/helpers.ts";
+import {
+  checkInlineJsResult,
+  expectValues,
+  hashByMethod,
+  loadEnvVariables,
+  setAdd,
+  setDelete,
+  setHas,
+  stableHash,
+  toSet,
+} from "../src/helpers.ts";
 
 describe("loadEnvVariables", () => {
   it("should load env file", async () => {
@@ -61,3 +71,158 @@ describe("loadEnvVariables", () => {
     expect(loaded.TEST6).toBe("6");
   });
 });
+
+describe("expectValues", () => {
+  it("shows the values of sub-expressions under the call", () => {
+    const x = [1, 2, 3];
+    expect(() =>
+      expectValues(
+        (v) => {
+          if (v.record(0, v.record(1, x).length) !== v.record(2, 4)) {
+            throw new Error("not equal");
+          }
+        },
+        "expect.equal(x.length, y)",
+        [
+          { text: "x.length", column: 15 },
+          { text: "x", column: 13 },
+          { text: "y", column: 23 },
+        ]
+      )
+    ).toThrowError(
+      [
+        "not equal",
+        "",
+        "expect.equal(x.length, y)",
+        "             | |       |",
+        "             | |       4",
+        "             | 3",
+        "             [ 1, 2, 3 ]",
+      ].join("\n")
+    );
+  });
+
+  it("lists the values of calls that span multiple lines", async () => {
+    await expect(
+      expectValues(
+        async (v) => {
+          v.record(0, "a");
+          throw new Error("failed");
+        },
+        "expect.equal(\n  y,\n  z)",
+        [
+          { text: "y", column: null },
+          { text: "z", column: null },
+        ]
+      )
+    ).rejects.toThrowError("failed\n\nexpect.equal(\n  y,\n  z)\n  y = 'a'");
+  });
+});
+
+describe("set members", () => {
+  it("are the same member if they have equal contents", () => {
+    const set = toSet([{ name: "Mittens", tags: ["cat"] }]) as Set<any>;
+    expect(setHas(set, { tags: ["cat"], name: "Mittens" })).toBe(true);
+    expect(setHas(set, { name: "Simba", tags: ["cat"] })).toBe(false);
+    setAdd(set, { tags: ["cat"], name: "Mittens" });
+    expect(set.size).toBe(1);
+    expect(setDelete(set, { name: "Mittens", tags: ["cat"] })).toBe(true);
+    expect(set.size).toBe(0);
+  });
+
+  it("are found in sets that weren't created by the helpers", () => {
+    const set = new Set<any>([[1, 2], "a"]);
+    expect(setHas(set, [1, 2])).toBe(true);
+    expect(setHas(set, "a")).toBe(true);
+    expect(setHas(set, [2, 1])).toBe(false);
+  });
+
+  it("hashes objects that aren't values by identity", () => {
+    class Animal {
+      constructor(public readonly name: string) {}
+    }
+    const a = new Animal("Rex");
+    const b = new Animal("Rex");
+    expect(stableHash(a)).toBe(stableHash(a));
+    expect(stableHash(a)).not.toBe(stableHash(b));
+    expect(stableHash({ pet: a })).not.toBe(stableHash({ pet: b }));
+  });
+
+  it("are hashed by their hash method", async () => {
+    class Email {
+      constructor(public readonly address: string) {}
+      public hash() {
+        return this.address.toLowerCase();
+      }
+    }
+    class Name {
+      constructor(public readonly address: string) {}
+      public hash() {
+        return this.address.toLowerCase();
+      }
+    }
+    const set = toSet([new Email("A@x.com"), new Email("a@x.com")], hashByMethod) as Set<any>;
+    expect(set.size).toBe(1);
+    expect(setHas(set, new Email("a@X.com"), hashByMethod)).toBe(true);
+    expect(setHas(set, new Name("a@x.com"), hashByMethod)).toBe(false);
+
+    class AsyncEmail {
+      constructor(public readonly address: string) {}
+      public async hash() {
+        return this.address.toLowerCase();
+      }
+    }
+    const asyncSet = await toSet([new AsyncEmail("a@x.com")], hashByMethod);
+    expect(await setHas(asyncSet, new AsyncEmail("A@x.com"), hashByMethod)).toBe(true);
+  });
+
+  it("ignores the order of set members", () => {
+    expect(stableHash(new Set([1, 2]))).toBe(stableHash(new Set([2, 1])));
+  });
+});
+
+describe("checkInlineJsResult", () => {
+  it("returns values of the declared type", () => {
+    expect(checkInlineJsResult("a", "str", "str")).toBe("a");
+    expect(checkInlineJsResult(undefined, { optional: "num" }, "num?")).toBe(
+      undefined
+    );
+    const nested = [{ a: new Set([true]) }];
+    expect(
+      checkInlineJsResult(
+        nested,
+        { array: { map: { set: "bool" } } },
+        "Array<Map<Set<bool>>>"
+      )
+    ).toBe(nested);
+  });
+
+  it("throws for values of other types", () => {
+    expect(() => checkInlineJsResult("1", "num", "num")).toThrow(
+      `Inline JavaScript returned '1', which is not a value of type "num"`
+    );
+    expect(() =>
+      checkInlineJsResult([1, "2"], { array: "num" }, "Array<num>")
+    ).toThrow(TypeError);
+    expect(() =>
+      checkInlineJsResult(["a"], { map: "str" }, "Map<str>")
+    ).toThrow(TypeError);
+  });
+
+  it("doesn't check values of unchecked types", () => {
+    const value = { anything: 1 };
+    expect(checkInlineJsResult(value, null, "MyClass")).toBe(value);
+    expect(
+      checkInlineJsResult([value], { array: null }, "Array<MyClass>")
+    ).toEqual([value]);
+  });
+
+  it("checks the resolved value of promises", async () => {
+    await expect(
+      checkInlineJsResult(Promise.resolve(1), "num", "num")
+    ).resolves.toBe(1);
+    await expect(
+      checkInlineJsResult(Promise.resolve(1), "str", "str")
+    ).rejects.toThrow(TypeError);
+  });
+});
@@ -25,7 +25,11 @@ module.exports = grammar({
 
   word: ($) => $.identifier,
 
-  externals: ($) => [$.AUTOMATIC_SEMICOLON, $.AUTOMATIC_BLOCK],
+  externals: ($) => [
+    $.AUTOMATIC_SEMICOLON,
+    $.AUTOMATIC_BLOCK,
+    $.inline_js_code,
+  ],
 
   precedences: ($) => [
     // Handle ambiguity in case of empty literal: `a = {}`
@@ -41,7 +45,12 @@ module.exports = grammar({
 
     // These modifier conflicts should be solved through GLR parsing
     [$.field_modifiers, $.method_modifiers],
-    [$.class_modifiers, $.closure_modifiers, $.interface_modifiers],
+    [
+      $.class_modifiers,
+      $.closure_modifiers,
+      $.interface_modifiers,
+      $.function_modifiers,
+    ],
   ],
 
   supertypes: ($) => [$.expression, $._literal],
@@ -68,7 +77,8 @@ module.exports = grammar({
       choice(
         $.nested_identifier,
         $._reference_identifier,
-        $.structured_access_expression
+        $.structured_access_expression,
+        $.super_member
       ),
 
     identifier: ($) => /([A-Za-z_$][A-Za-z_$0-9]*|[A-Z][A-Z0-9_]*)/,
@@ -96,7 +106,9 @@ module.exports = grammar({
               $.expression,
               // This is required because of ambiguity with using Json keyword for both instantiation of Json
               // and Identifier for static methods.
-              $.json_container_type
+              $.json_container_type,
+              // Same as above, for static methods of builtin containers (e.g. `Array.of(1, 2)`)
+              $.container_static_type
             )
           ),
           field("accessor_type", $.accessor),
@@ -115,12 +127,14 @@ module.exports = grammar({
         $.import_statement,
         $.expression_statement,
         $.variable_definition_statement,
+        $.function_definition,
         $.variable_assignment_statement,
         $.return_statement,
         $.class_definition,
         $.interface_definition,
         $.for_in_loop,
         $.while_statement,
+        $.while_let_statement,
         $.break_statement,
         $.continue_statement,
         $.if_statement,
@@ -147,7 +161,14 @@ module.exports = grammar({
         "struct",
         field("name", $.identifier),
         optional(seq("extends", commaSep(field("extends", $.custom_type)))),
-        braced(repeat(field("field", $.struct_field)))
+        braced(
+          repeat(
+            choice(
+              field("field", $.struct_field),
+              field("method", $.method_definition)
+            )
+          )
+        )
       ),
     struct_field: ($) =>
       seq(field("name", $.identifier), $._type_annotation, $._semicolon),
@@ -155,9 +176,16 @@ module.exports = grammar({
     enum_definition: ($) =>
       seq(
         optional(field("access_modifier", $.access_modifier)),
-        "enum",
+        choice("enum", field("flags", alias($._flags_enum_keyword, "flags enum"))),
         field("enum_name", $.identifier),
-        braced(commaSep(alias($.identifier, $.enum_field)))
+        braced(commaSep($.enum_field))
+      ),
+    // A single token, so `flags` can still be used as an identifier
+    _flags_enum_keyword: ($) => token(seq("flags", /\s+/, "enum")),
+    enum_field: ($) =>
+      seq(
+        field("name", $.identifier),
+        optional(seq("=", field("value", $.string)))
       ),
 
     return_statement: ($) =>
@@ -204,6 +232,7 @@ module.exports = grammar({
 
     variable_definition_statement: ($) =>
       seq(
+        optional(field("access_modifier", $.access_modifier)),
         "let",
         optional(field("reassignable", $.reassignable)),
         field("name", $.identifier),
@@ -215,6 +244,20 @@ module.exports = grammar({
 
     _type_annotation: ($) => seq(":", field("type", $._type)),
 
+    function_modifiers: ($) =>
+      repeat1(choice($.access_modifier, $.phase_specifier)),
+
+    // Modifiers are required so `fn` can still be used as an identifier at the start of a statement
+    function_definition: ($) =>
+      seq(
+        field("modifiers", $.function_modifiers),
+        "fn",
+        field("name", $.identifier),
+        field("parameter_list", $.parameter_list),
+        optional($._return_type),
+        field("block", $.block)
+      ),
+
     // Classes
 
     class_modifiers: ($) =>
@@ -278,6 +321,7 @@ module.exports = grammar({
       seq(
         "for",
         field("iterator", $.identifier),
+        optional(seq(",", field("second_iterator", $.identifier))),
         "in",
         field("iterable", choice($.expression, $.loop_range)),
         field("block", $.block)
@@ -286,6 +330,17 @@ module.exports = grammar({
     while_statement: ($) =>
       seq("while", field("condition", $.expression), field("block", $.block)),
 
+    while_let_statement: ($) =>
+      seq(
+        "while",
+        "let",
+        optional(field("reassignable", $.reassignable)),
+        field("name", $.identifier),
+        "=",
+        field("value", $.expression),
+        field("block", $.block)
+      ),
+
     break_statement: ($) => seq("break", $._semicolon),
     _super: ($) => "super",
     super_constructor_statement: ($) =>
@@ -301,6 +356,10 @@ module.exports = grammar({
         field("name", $.identifier),
         "=",
         field("value", $.expression),
+        // Further bindings and guards: `if let a = x, let b = y, a > b {}`
+        repeat(
+          seq(",", field("condition", choice($.if_let_binding, $.expression)))
+        ),
         field("block", $.block),
         repeat(
           choice(
@@ -311,6 +370,15 @@ module.exports = grammar({
         optional(seq("else", field("else_block", $.block)))
       ),
 
+    if_let_binding: ($) =>
+      seq(
+        "let",
+        optional(field("reassignable", $.reassignable)),
+        field("name", $.identifier),
+        "=",
+        field("value", $.expression)
+      ),
+
     else_if_let_block: ($) =>
       seq(
         "else",
@@ -378,7 +446,8 @@ module.exports = grammar({
         $.json_literal,
         $.struct_literal,
         $.optional_unwrap,
-        $.intrinsic
+        $.intrinsic,
+        $.inline_js
       ),
 
     intrinsic: ($) =>
@@ -466,12 +535,14 @@ module.exports = grammar({
       prec.left(
         PREC.CALL,
         seq(
-          field("caller", choice($.expression, $.super_call)),
+          field("caller", $.expression),
+          optional(field("optional_accessor", "?")),
           field("args", $.argument_list)
         )
       ),
 
-    super_call: ($) => seq($._super, ".", field("method", $.identifier)),
+    // A member of the parent class: `super.method()` or `super.field`
+    super_member: ($) => seq($._super, ".", field("member", $._member_identifier)),
 
     argument_list: ($) =>
       seq(
@@ -495,12 +566,20 @@ module.exports = grammar({
     new_expression: ($) =>
       prec.right(
         seq(
+          // `inflight new Foo()` creates a factory that instantiates the inflight class when called
+          optional(field("phase", $.phase_specifier)),
           "new",
           field("class", choice($.custom_type, $.mutable_container_type)),
           // While "args" is optional in this grammar, upstream parsing will fail if it is not present
           field("args", optional($.argument_list)),
           optional(seq("as", field("id", $.expression))),
-          optional(seq("in", field("scope", $.expression)))
+          // Binds tighter than the `in` operator so `as "id" in scope` isn't parsed as a binary expression
+          optional(
+            prec.right(
+              PREC.RELATIONAL + 1,
+              seq("in", field("scope", $.expression))
+            )
+          )
         )
       ),
 
@@ -511,6 +590,7 @@ module.exports = grammar({
         $._builtin_container_type,
         $.json_container_type,
         $.function_type,
+        $.literal_type,
         $.optional,
         $._parenthesized_type
       ),
@@ -530,6 +610,14 @@ module.exports = grammar({
 
     parameter_type_list: ($) => seq("(", commaSep($._type), ")"),
 
+    literal_type: ($) =>
+      prec.right(
+        seq(
+          field("value", choice($.string, $.number)),
+          repeat(seq("|", field("value", choice($.string, $.number))))
+        )
+      ),
+
     builtin_type: ($) =>
       choice(
         "num",
@@ -539,7 +627,9 @@ module.exports = grammar({
         "void",
         "duration",
         "datetime",
-        "regex"
+        "regex",
+        "never",
+        "unknown"
       ),
 
     initializer: ($) =>
@@ -601,6 +691,9 @@ module.exports = grammar({
         )
       ),
 
+    container_static_type: ($) =>
+      choice("Array", "Set", "Map", "MutArray", "MutSet", "MutMap"),
+
     _builtin_container_type: ($) =>
       choice($.immutable_container_type, $.mutable_container_type),
 
@@ -641,15 +734,16 @@ module.exports = grammar({
         ["**", PREC.POWER, "left"],
         ["||", PREC.LOGICAL_OR, "left"],
         ["&&", PREC.LOGICAL_AND, "left"],
-        //['|', PREC.INCLUSIVE_OR, "left"],
+        ["|", PREC.INCLUSIVE_OR, "left"],
         //['^', PREC.EXCLUSIVE_OR, "left"],
-        //['&', PREC.BITWISE_AND, "left"],
+        ["&", PREC.BITWISE_AND, "left"],
         ["==", PREC.EQUAL, "left"],
         ["!=", PREC.EQUAL, "left"],
         [">", PREC.RELATIONAL, "left"],
         [">=", PREC.RELATIONAL, "left"],
         ["<=", PREC.RELATIONAL, "left"],
         ["<", PREC.RELATIONAL, "left"],
+        ["in", PREC.RELATIONAL, "left"],
         //['<<', PREC.SHIFT, "left"],
         //['>>', PREC.SHIFT, "left"],
         //['>>>', PREC.SHIFT, "left"],
@@ -686,6 +780,20 @@ module.exports = grammar({
     defer_expression: ($) => prec.right(seq("defer", $.expression)),
     parenthesized_expression: ($) => seq("(", $.expression, ")"),
 
+    // Raw JavaScript embedded in inflight code, e.g. `unsafe js (a, b): num { return a + b; }`
+    inline_js: ($) =>
+      seq(
+        $._inline_js_keyword,
+        optional(field("inputs", $.inline_js_inputs)),
+        optional($._type_annotation),
+        "{",
+        field("code", $.inline_js_code),
+        "}"
+      ),
+    // A single token, so `unsafe` and `js` can still be used as identifiers (e.g. `fromJson(x, unsafe: true)`)
+    _inline_js_keyword: ($) => token(seq("unsafe", /\s+/, "js")),
+    inline_js_inputs: ($) => seq("(", commaSep($._reference_identifier), ")"),
+
     _collection_literal: ($) => choice($.array_literal, $.map_literal),
     array_literal: ($) =>
       seq(
@@ -709,7 +817,13 @@ module.exports = grammar({
     structured_access_expression: ($) =>
       prec.right(
         PREC.STRUCTURED_ACCESS,
-        seq($.expression, "[", $.expression, "]")
+        seq(
+          $.expression,
+          optional(field("optional_accessor", "?")),
+          "[",
+          $.expression,
+          "]"
+        )
       ),
 
     json_literal: ($) =>
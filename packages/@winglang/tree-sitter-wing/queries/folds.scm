@@ -12,4 +12,5 @@
   (enum_definition)
   (try_catch_statement)
   (method_definition)
+  (function_definition)
 ] @fold
@@ -17,6 +17,9 @@
   name: (identifier) @function.method)
 
 ; Functions
+(function_definition
+  name: (identifier) @function)
+
 (keyword_argument_key) @variable.parameter
 
 (call
@@ -41,6 +44,8 @@
 
 (json_container_type) @type.builtin
 
+(container_static_type) @type.builtin
+
 ; Special
 (comment) @comment @spell
 
@@ -68,6 +73,8 @@
   "&&"
   "??"
   "||"
+  "|"
+  "&"
 ] @operator
 
 [
@@ -80,6 +87,7 @@
   "as"
   "bring"
   "class"
+  "fn"
   "let"
   "new"
   (phase_specifier)
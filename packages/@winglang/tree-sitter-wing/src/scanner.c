@@ -1,14 +1,16 @@
 // Adapted from https://github.com/tree-sitter/tree-sitter-javascript/blob/master/src/scanner.c
 // Removed all the other tokens and only kept the AUTOMATIC_SEMICOLON
-// Added comments
+// Added comments, AUTOMATIC_BLOCK and INLINE_JS_CODE
 
 #include "tree_sitter/parser.h"
 
+#include <string.h>
 #include <wctype.h>
 
 enum TokenType {
   AUTOMATIC_SEMICOLON,
   AUTOMATIC_BLOCK,
+  INLINE_JS_CODE,
 };
 
 void * tree_sitter_wing_external_scanner_create() {
@@ -218,6 +220,175 @@ static bool scan_automatic_block(TSLexer * lexer) {
   return true;
 }
 
+/**
+ * Advance through a JavaScript string or template literal, including its closing quote.
+ * Assumes the lexer is at the opening quote.
+ */
+static void advance_js_string(TSLexer * lexer) {
+  int32_t quote = lexer -> lookahead;
+  lexer -> advance(lexer, false);
+  while (lexer -> lookahead != 0) {
+    if (lexer -> lookahead == '\\') {
+      lexer -> advance(lexer, false);
+      if (lexer -> lookahead != 0) {
+        lexer -> advance(lexer, false);
+      }
+    } else if (lexer -> lookahead == quote) {
+      lexer -> advance(lexer, false);
+      return;
+    } else {
+      lexer -> advance(lexer, false);
+    }
+  }
+}
+
+/**
+ * Advance through a JavaScript regular expression literal, including its closing `/` (but not its flags).
+ * Assumes the lexer is past the opening `/`. Stops at the end of the line if the literal isn't closed.
+ */
+static void advance_js_regex(TSLexer * lexer) {
+  bool in_class = false;
+  while (lexer -> lookahead != 0 && lexer -> lookahead != '\n') {
+    if (lexer -> lookahead == '\\') {
+      lexer -> advance(lexer, false);
+      if (lexer -> lookahead != 0 && lexer -> lookahead != '\n') {
+        lexer -> advance(lexer, false);
+      }
+      continue;
+    }
+    if (lexer -> lookahead == '[') {
+      in_class = true;
+    } else if (lexer -> lookahead == ']') {
+      in_class = false;
+    } else if (lexer -> lookahead == '/' && !in_class) {
+      lexer -> advance(lexer, false);
+      return;
+    }
+    lexer -> advance(lexer, false);
+  }
+}
+
+static bool is_js_identifier_char(int32_t c) {
+  return iswalnum(c) || c == '_' || c == '$';
+}
+
+/**
+ * Whether a `/` that follows the given code starts a regular expression literal rather than being a division.
+ * Like other JavaScript tokenizers, this looks at the previous token: a regular expression can't follow a value (an
+ * identifier, a number, a string, or a closing `)` or `]`), except for keywords like `return` that are followed by
+ * expressions.
+ *
+ * @param prev the last character before the `/` that isn't whitespace, or 0 if there's none
+ * @param word the identifier or keyword that ends with `prev`, if any
+ */
+static bool js_regex_allowed(int32_t prev, const char * word) {
+  static const char * const keywords[] = {
+    "return", "typeof", "instanceof", "in", "of", "new", "delete", "void", "throw", "case", "do", "else", "yield",
+    "await",
+  };
+
+  if (prev == 0) {
+    return true;
+  }
+  if (!is_js_identifier_char(prev)) {
+    // Closing quotes end string literals, which are values too
+    return prev != ')' && prev != ']' && prev != '"' && prev != '\'' && prev != '`';
+  }
+  for (unsigned i = 0; i < sizeof(keywords) / sizeof(keywords[0]); i++) {
+    if (strcmp(word, keywords[i]) == 0) {
+      return true;
+    }
+  }
+  return false;
+}
+
+/**
+ * Scan the raw JavaScript code of an inline js block, up to (but not including) the `}` that closes the block.
+ * Braces are matched, skipping over strings, template literals, regular expression literals and comments.
+ *
+ * Braces inside `${...}` substitutions of template literals are not matched, they are part of the literal.
+ *
+ * @return true if the closing `}` was found, false if the file ended first
+ */
+static bool scan_inline_js_code(TSLexer * lexer) {
+  lexer -> result_symbol = INLINE_JS_CODE;
+  unsigned depth = 0;
+  // The last character that isn't whitespace or part of a comment, and the identifier it ends (only the start of
+  // longer identifiers is kept, which is enough to tell keywords apart)
+  int32_t prev = 0;
+  char word[16] = "";
+  unsigned word_length = 0;
+  bool in_word = false;
+
+  for (;;) {
+    int32_t c = lexer -> lookahead;
+    if (is_js_identifier_char(c)) {
+      if (!in_word) {
+        word_length = 0;
+      }
+      if (word_length < sizeof(word) - 1) {
+        word[word_length++] = c < 128 ? (char)c : '?';
+      }
+      word[word_length] = '\0';
+    }
+    in_word = is_js_identifier_char(c);
+
+    switch (c) {
+      case 0:
+        return false;
+      case '{':
+        depth++;
+        lexer -> advance(lexer, false);
+        break;
+      case '}':
+        if (depth == 0) {
+          lexer -> mark_end(lexer);
+          return true;
+        }
+        depth--;
+        lexer -> advance(lexer, false);
+        break;
+      case '"':
+      case '\'':
+      case '`':
+        advance_js_string(lexer);
+        break;
+      case '/':
+        lexer -> advance(lexer, false);
+        if (lexer -> lookahead == '/') {
+          while (lexer -> lookahead != 0 && lexer -> lookahead != '\n') {
+            lexer -> advance(lexer, false);
+          }
+          continue;
+        } else if (lexer -> lookahead == '*') {
+          lexer -> advance(lexer, false);
+          while (lexer -> lookahead != 0) {
+            if (lexer -> lookahead == '*') {
+              lexer -> advance(lexer, false);
+              if (lexer -> lookahead == '/') {
+                lexer -> advance(lexer, false);
+                break;
+              }
+            } else {
+              lexer -> advance(lexer, false);
+            }
+          }
+          continue;
+        } else if (js_regex_allowed(prev, word)) {
+          advance_js_regex(lexer);
+        }
+        break;
+      default:
+        lexer -> advance(lexer, false);
+        break;
+    }
+
+    if (!iswspace(c)) {
+      prev = c;
+    }
+  }
+}
+
 /**
  * Entrypoint of the external scanner.
  *
@@ -231,6 +402,8 @@ bool tree_sitter_wing_external_scanner_scan(void * payload, TSLexer * lexer,
   } else if (valid_symbols[AUTOMATIC_BLOCK]) {
     bool ret = scan_automatic_block(lexer);
     return ret;
+  } else if (valid_symbols[INLINE_JS_CODE]) {
+    return scan_inline_js_code(lexer);
   }
 
   return false;
@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(
+	init: {
+		// Type checking needs the Wing SDK's JSII manifest, which is looked up relative to the working directory by default
+		if std::env::var("WINGSDK_MANIFEST_ROOT").is_err() {
+			std::env::set_var("WINGSDK_MANIFEST_ROOT", concat!(env!("CARGO_MANIFEST_DIR"), "/../../sdk"));
+		}
+	},
+	|data: &[u8]| {
+		// Any panic in the compiler (which would be reported as a "Compiler bug" diagnostic) aborts the fuzzer
+		if let Ok(source) = std::str::from_utf8(data) {
+			let _ = wingc::compile_str(source);
+		}
+	}
+);
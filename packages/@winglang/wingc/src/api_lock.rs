@@ -0,0 +1,511 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use camino::Utf8Path;
+use serde::{Deserialize, Serialize};
+
+use crate::ast::{AccessModifier, Phase};
+use crate::diagnostic::{report_diagnostic, Diagnostic, DiagnosticSeverity, WingSpan};
+use crate::parser::STRUCT_STATICS_CLASS_PREFIX;
+use crate::type_check::{
+	ClassLike, FunctionSignature, Namespace, SymbolEnvOrNamespace, SymbolKind, Type, TypeRef, Types, VariableInfo,
+	VariableKind,
+};
+
+/// Name of the environment variable that turns on the API lock of a library (see `ApiLockMode`)
+pub const API_LOCK_ENV: &str = "WING_API_LOCK";
+
+/// The file (in the root of a library) with the public API of its last release
+pub const API_LOCK_FILE_NAME: &str = "wing.api.json";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApiLockMode {
+	/// Report the breaking changes between the locked API and the current one, unless the major version of the
+	/// library was bumped, and lock the current API if there are none
+	Check,
+	/// Lock the current API, whatever changed
+	Update,
+}
+
+impl ApiLockMode {
+	/// Reads the mode from `API_LOCK_ENV` ("check" or "update"), reporting an error if it's not a valid mode
+	pub fn from_env() -> Option<Self> {
+		match std::env::var(API_LOCK_ENV).ok().as_deref() {
+			None | Some("") => None,
+			Some("check") => Some(Self::Check),
+			Some("update") => Some(Self::Update),
+			Some(value) => {
+				report_diagnostic(Diagnostic {
+					message: format!("Invalid API lock mode \"{value}\" in {API_LOCK_ENV}, expected \"check\" or \"update\""),
+					span: None,
+					annotations: vec![],
+					hints: vec![],
+					severity: DiagnosticSeverity::Error,
+					code: None,
+				});
+				None
+			}
+		}
+	}
+}
+
+/// The contents of the API lock file
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct ApiLock {
+	/// The version of the library the API was locked at
+	version: Option<String>,
+	/// The signature of each public declaration of the library, keyed by its path (e.g. "Bucket.put")
+	api: BTreeMap<String, String>,
+}
+
+/// A public declaration of the library
+struct ApiEntry {
+	signature: String,
+	span: WingSpan,
+	/// Whether adding the declaration to an existing type breaks code using the type (e.g. a method of an interface,
+	/// which its implementations don't have, or a required field of a struct)
+	breaking_when_added: bool,
+}
+
+/// A breaking change between the locked API of a library and its current one
+#[derive(Debug, PartialEq)]
+pub enum BreakingChange {
+	Removed { path: String, signature: String },
+	Changed { path: String, old: String, new: String },
+	Added { path: String, signature: String },
+}
+
+impl BreakingChange {
+	fn message(&self) -> String {
+		match self {
+			BreakingChange::Removed { path, signature } => {
+				format!("Breaking change: \"{path}\" was removed from the public API (was \"{signature}\")")
+			}
+			BreakingChange::Changed { path, old, new } => {
+				format!("Breaking change: the signature of \"{path}\" changed from \"{old}\" to \"{new}\"")
+			}
+			BreakingChange::Added { path, signature } => {
+				format!("Breaking change: \"{signature}\" was added to \"{path}\", which existing code doesn't provide")
+			}
+		}
+	}
+
+	fn path(&self) -> &str {
+		match self {
+			BreakingChange::Removed { path, .. }
+			| BreakingChange::Changed { path, .. }
+			| BreakingChange::Added { path, .. } => path,
+		}
+	}
+}
+
+/// Compares the public API of the library in `project_dir` with its lock file. In `Check` mode, breaking changes are
+/// reported as errors unless the library's major version was bumped since the API was locked. The lock file is
+/// (re)written when there are no errors and `write` is set.
+pub fn check_api_lock(types: &Types, project_dir: &Utf8Path, mode: ApiLockMode, write: bool) {
+	let Some(SymbolEnvOrNamespace::Namespace(root)) = types.source_file_envs.get(project_dir) else {
+		return;
+	};
+	let mut api = BTreeMap::new();
+	collect_namespace_api(root, "", &mut api);
+
+	let lock_file = project_dir.join(API_LOCK_FILE_NAME);
+	let version = read_package_version(project_dir);
+	let locked = read_api_lock(&lock_file);
+
+	if mode == ApiLockMode::Check {
+		if let Some(locked) = &locked {
+			if !is_major_bump(locked.version.as_deref(), version.as_deref()) {
+				let changes = find_breaking_changes(&locked.api, &api);
+				for change in &changes {
+					report_diagnostic(Diagnostic {
+						message: change.message(),
+						span: api.get(change.path()).map(|entry| entry.span.clone()),
+						annotations: vec![],
+						hints: vec![
+							"bump the major version of the library, or run \"wing compile --update-api-lock\" to accept the change"
+								.to_string(),
+						],
+						severity: DiagnosticSeverity::Error,
+						code: None,
+					});
+				}
+				if !changes.is_empty() {
+					return;
+				}
+			}
+		}
+	}
+
+	let lock = ApiLock {
+		version,
+		api: api.into_iter().map(|(path, entry)| (path, entry.signature)).collect(),
+	};
+	if !write || locked.as_ref() == Some(&lock) {
+		return;
+	}
+	let content = serde_json::to_string_pretty(&lock).expect("API lock serialization failed") + "\n";
+	if let Err(err) = fs::write(&lock_file, content) {
+		report_diagnostic(Diagnostic {
+			message: format!("Failed to write the API lock file \"{lock_file}\": {err}"),
+			span: None,
+			annotations: vec![],
+			hints: vec![],
+			severity: DiagnosticSeverity::Error,
+			code: None,
+		});
+	}
+}
+
+fn find_breaking_changes(locked: &BTreeMap<String, String>, api: &BTreeMap<String, ApiEntry>) -> Vec<BreakingChange> {
+	let mut changes = vec![];
+	for (path, signature) in locked {
+		match api.get(path) {
+			None => changes.push(BreakingChange::Removed {
+				path: path.clone(),
+				signature: signature.clone(),
+			}),
+			// widening the access of a member from protected to public is fine
+			Some(entry)
+				if entry.signature != *signature && signature.strip_prefix("protected ") != Some(entry.signature.as_str()) =>
+			{
+				changes.push(BreakingChange::Changed {
+					path: path.clone(),
+					old: signature.clone(),
+					new: entry.signature.clone(),
+				})
+			}
+			Some(_) => {}
+		}
+	}
+	for (path, entry) in api {
+		let Some((parent, _)) = path.rsplit_once('.') else {
+			continue;
+		};
+		if entry.breaking_when_added && !locked.contains_key(path) && locked.contains_key(parent) {
+			changes.push(BreakingChange::Added {
+				path: path.clone(),
+				signature: entry.signature.clone(),
+			});
+		}
+	}
+	changes
+}
+
+/// Whether `version` has a greater major version than `locked` (or a greater minor version, before 1.0.0), which
+/// means breaking changes are expected
+fn is_major_bump(locked: Option<&str>, version: Option<&str>) -> bool {
+	let (Some(locked), Some(version)) = (locked.and_then(parse_version), version.and_then(parse_version)) else {
+		return false;
+	};
+	match (locked.0, version.0) {
+		(0, 0) => version.1 > locked.1,
+		(locked_major, major) => major > locked_major,
+	}
+}
+
+fn parse_version(version: &str) -> Option<(u64, u64)> {
+	let mut parts = version.split('.');
+	let major = parts.next()?.parse().ok()?;
+	let minor = parts.next()?.parse().ok()?;
+	Some((major, minor))
+}
+
+fn read_package_version(project_dir: &Utf8Path) -> Option<String> {
+	let package_json = fs::read_to_string(project_dir.join("package.json")).ok()?;
+	let package_json: serde_json::Value = serde_json::from_str(&package_json).ok()?;
+	package_json.get("version")?.as_str().map(|v| v.to_string())
+}
+
+fn read_api_lock(lock_file: &Utf8Path) -> Option<ApiLock> {
+	let content = fs::read_to_string(lock_file).ok()?;
+	match serde_json::from_str(&content) {
+		Ok(lock) => Some(lock),
+		Err(err) => {
+			report_diagnostic(Diagnostic {
+				message: format!("Invalid API lock file \"{lock_file}\": {err}"),
+				span: None,
+				annotations: vec![],
+				hints: vec!["delete the file to lock the current API".to_string()],
+				severity: DiagnosticSeverity::Error,
+				code: None,
+			});
+			None
+		}
+	}
+}
+
+fn collect_namespace_api(ns: &Namespace, prefix: &str, api: &mut BTreeMap<String, ApiEntry>) {
+	for env in ns.envs.iter() {
+		for (name, entry) in env.symbol_map.iter() {
+			// Struct static methods are part of their struct's class, which isn't public
+			if entry.access != AccessModifier::Public || name.starts_with(STRUCT_STATICS_CLASS_PREFIX) {
+				continue;
+			}
+			let path = format!("{prefix}{name}");
+			match &entry.kind {
+				SymbolKind::Type(typ) => collect_type_api(*typ, &path, &entry.span, api),
+				SymbolKind::Variable(var) => {
+					let var_ = if var.reassignable { "var " } else { "" };
+					add(
+						api,
+						path,
+						format!("let {var_}{name}: {}", var.type_),
+						&entry.span,
+						false,
+					);
+				}
+				SymbolKind::Namespace(child) => collect_namespace_api(child, &format!("{path}."), api),
+			}
+		}
+	}
+}
+
+fn collect_type_api(typ: TypeRef, path: &str, span: &WingSpan, api: &mut BTreeMap<String, ApiEntry>) {
+	match &*typ {
+		Type::Class(class) => {
+			let parent = class.parent.map(|p| format!(" extends {p}")).unwrap_or_default();
+			let abstract_ = if class.is_abstract { "abstract " } else { "" };
+			add(
+				api,
+				path.to_string(),
+				format!(
+					"{}{abstract_}class {}{parent}{}",
+					phase_prefix(class.phase),
+					class.name,
+					type_list(" impl ", &class.implements)
+				),
+				span,
+				false,
+			);
+			for (_, constructor) in class.constructors(false) {
+				if constructor.access == AccessModifier::Public {
+					let sig = constructor
+						.type_
+						.as_function_sig()
+						.expect("Constructor is not a function");
+					let name = if sig.phase == Phase::Inflight {
+						"inflight new"
+					} else {
+						"new"
+					};
+					add(
+						api,
+						format!("{path}.{name}"),
+						callable(name, sig, false),
+						&constructor.name.span,
+						false,
+					);
+				}
+			}
+			collect_members_api(class, path, false, api);
+		}
+		Type::Interface(interface) => {
+			add(
+				api,
+				path.to_string(),
+				format!(
+					"{}interface {}{}",
+					phase_prefix(interface.phase),
+					interface.name,
+					type_list(" extends ", &interface.extends)
+				),
+				span,
+				false,
+			);
+			collect_members_api(interface, path, true, api);
+		}
+		Type::Struct(struct_) => {
+			add(
+				api,
+				path.to_string(),
+				format!("struct {}{}", struct_.name, type_list(" extends ", &struct_.extends)),
+				span,
+				false,
+			);
+			for (name, field) in struct_.fields(false) {
+				let required = !field.type_.is_option();
+				add(
+					api,
+					format!("{path}.{name}"),
+					format!("{name}: {}", field.type_),
+					&field.name.span,
+					required,
+				);
+			}
+		}
+		Type::Enum(enum_) => {
+			add(api, path.to_string(), format!("enum {}", enum_.name), span, false);
+			for value in enum_.values.keys() {
+				add(api, format!("{path}.{value}"), value.name.clone(), &value.span, false);
+			}
+		}
+		_ => {}
+	}
+}
+
+/// Adds the public and protected fields and methods of a class or interface. Every member of an interface has to be
+/// provided by its implementations, so adding one is a breaking change.
+fn collect_members_api(class: &impl ClassLike, path: &str, is_interface: bool, api: &mut BTreeMap<String, ApiEntry>) {
+	for (name, field) in class.fields(false) {
+		if let Some(access) = member_access_prefix(&field) {
+			let static_ = if field.kind == VariableKind::StaticMember {
+				"static "
+			} else {
+				""
+			};
+			let var_ = if field.reassignable { "var " } else { "" };
+			add(
+				api,
+				format!("{path}.{name}"),
+				format!(
+					"{access}{static_}{}{var_}{name}: {}",
+					phase_prefix(field.phase),
+					field.type_
+				),
+				&field.name.span,
+				is_interface,
+			);
+		}
+	}
+	for (name, method) in class.methods(false) {
+		if let Some(access) = member_access_prefix(&method) {
+			let sig = method.type_.as_function_sig().expect("Method is not a function");
+			let is_static = method.kind == VariableKind::StaticMember;
+			add(
+				api,
+				format!("{path}.{name}"),
+				format!("{access}{}", callable(&name, sig, is_static)),
+				&method.name.span,
+				is_interface,
+			);
+		}
+	}
+}
+
+fn add(
+	api: &mut BTreeMap<String, ApiEntry>,
+	path: String,
+	signature: String,
+	span: &WingSpan,
+	breaking_when_added: bool,
+) {
+	api.insert(
+		path,
+		ApiEntry {
+			signature,
+			span: span.clone(),
+			breaking_when_added,
+		},
+	);
+}
+
+/// Members that other libraries can use: public ones, and protected ones in their subclasses
+fn member_access_prefix(member: &VariableInfo) -> Option<&'static str> {
+	match member.access {
+		AccessModifier::Public => Some(""),
+		AccessModifier::Protected => Some("protected "),
+		AccessModifier::Private | AccessModifier::Internal => None,
+	}
+}
+
+/// The signature of a method, e.g. "static inflight get(key: str): str"
+fn callable(name: &str, sig: &FunctionSignature, is_static: bool) -> String {
+	let static_ = if is_static { "static " } else { "" };
+	let parameters = sig
+		.parameters
+		.iter()
+		.map(|p| {
+			let variadic = if p.variadic { "..." } else { "" };
+			format!("{variadic}{}: {}", p.name, p.typeref)
+		})
+		.collect::<Vec<_>>()
+		.join(", ");
+	format!(
+		"{static_}{}{name}({parameters}): {}",
+		phase_prefix(sig.phase),
+		sig.return_type
+	)
+}
+
+fn phase_prefix(phase: Phase) -> &'static str {
+	match phase {
+		Phase::Inflight => "inflight ",
+		Phase::Preflight => "",
+		Phase::Independent => "unphased ",
+	}
+}
+
+fn type_list(keyword: &str, types: &[TypeRef]) -> String {
+	if types.is_empty() {
+		return String::new();
+	}
+	format!(
+		"{keyword}{}",
+		types.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", ")
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+
+	use super::{find_breaking_changes, is_major_bump, ApiEntry, BreakingChange};
+
+	fn entry(signature: &str, breaking_when_added: bool) -> ApiEntry {
+		ApiEntry {
+			signature: signature.to_string(),
+			span: Default::default(),
+			breaking_when_added,
+		}
+	}
+
+	#[test]
+	fn finds_breaking_changes() {
+		let locked = BTreeMap::from([
+			("Bucket".to_string(), "class Bucket".to_string()),
+			("Bucket.get".to_string(), "get(key: str): str".to_string()),
+			("Bucket.put".to_string(), "put(key: str): void".to_string()),
+			("Bucket.list".to_string(), "protected list(): Array<str>".to_string()),
+			("IStore".to_string(), "interface IStore".to_string()),
+		]);
+		let api = BTreeMap::from([
+			("Bucket".to_string(), entry("class Bucket", false)),
+			(
+				"Bucket.get".to_string(),
+				entry("get(key: str, default: str): str", false),
+			),
+			("Bucket.list".to_string(), entry("list(): Array<str>", false)),
+			("Bucket.delete".to_string(), entry("delete(key: str): void", false)),
+			("IStore".to_string(), entry("interface IStore", false)),
+			("IStore.size".to_string(), entry("size(): num", true)),
+			("Queue".to_string(), entry("class Queue", false)),
+			("Options".to_string(), entry("struct Options", false)),
+			("Options.name".to_string(), entry("name: str", true)),
+		]);
+
+		assert_eq!(
+			find_breaking_changes(&locked, &api),
+			vec![
+				BreakingChange::Changed {
+					path: "Bucket.get".to_string(),
+					old: "get(key: str): str".to_string(),
+					new: "get(key: str, default: str): str".to_string(),
+				},
+				BreakingChange::Removed {
+					path: "Bucket.put".to_string(),
+					signature: "put(key: str): void".to_string(),
+				},
+				BreakingChange::Added {
+					path: "IStore.size".to_string(),
+					signature: "size(): num".to_string(),
+				},
+			]
+		);
+
+		assert!(is_major_bump(Some("1.4.2"), Some("2.0.0")));
+		assert!(!is_major_bump(Some("1.4.2"), Some("1.5.0")));
+		assert!(is_major_bump(Some("0.3.1"), Some("0.4.0")));
+		assert!(!is_major_bump(None, Some("2.0.0")));
+	}
+}
@@ -10,9 +10,35 @@ use crate::diagnostic::WingSpan;
 
 use crate::type_check::CLOSURE_CLASS_HANDLE_METHOD;
 
-static EXPR_COUNTER: AtomicUsize = AtomicUsize::new(0);
-static SCOPE_COUNTER: AtomicUsize = AtomicUsize::new(0);
-static ARGLIST_COUNTER: AtomicUsize = AtomicUsize::new(0);
+/// Allocates the identifiers of the nodes in an AST.
+///
+/// Identifiers are only unique among nodes created with the same context, so all the ASTs that are type checked
+/// together must share one. The counters are atomic so the context can be shared between threads parsing different
+/// files of the same compilation.
+#[derive(Debug, Default)]
+pub struct AstContext {
+	expr_counter: AtomicUsize,
+	scope_counter: AtomicUsize,
+	arglist_counter: AtomicUsize,
+}
+
+impl AstContext {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	fn next_expr_id(&self) -> ExprId {
+		self.expr_counter.fetch_add(1, Ordering::Relaxed)
+	}
+
+	fn next_scope_id(&self) -> ScopeId {
+		self.scope_counter.fetch_add(1, Ordering::Relaxed)
+	}
+
+	fn next_arglist_id(&self) -> ArgListId {
+		self.arglist_counter.fetch_add(1, Ordering::Relaxed)
+	}
+}
 
 #[derive(Debug, Eq, Clone)]
 pub struct Symbol {
@@ -132,6 +158,8 @@ pub enum TypeAnnotationKind {
 	Datetime,
 	Regex,
 	Void,
+	Never,
+	Unknown,
 	Json,
 	MutJson,
 	Optional(Box<TypeAnnotation>),
@@ -143,6 +171,25 @@ pub enum TypeAnnotationKind {
 	MutSet(Box<TypeAnnotation>),
 	Function(FunctionSignature),
 	UserDefined(UserDefinedType),
+	/// A union of literal values, e.g. `"GET" | "POST"`
+	Literal(Vec<LiteralTypeValue>),
+}
+
+/// A single value of a literal type annotation
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiteralTypeValue {
+	/// The string as written in the source, including its quotes
+	String(String),
+	Number(f64),
+}
+
+impl Display for LiteralTypeValue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			LiteralTypeValue::String(s) => write!(f, "{}", s),
+			LiteralTypeValue::Number(n) => write!(f, "{}", n),
+		}
+	}
 }
 
 // In the future this may be an enum for type-alias, class, etc. For now its just a nested name.
@@ -207,6 +254,8 @@ impl Display for TypeAnnotationKind {
 			TypeAnnotationKind::Duration => write!(f, "duration"),
 			TypeAnnotationKind::Datetime => write!(f, "datetime"),
 			TypeAnnotationKind::Regex => write!(f, "regex"),
+			TypeAnnotationKind::Never => write!(f, "never"),
+			TypeAnnotationKind::Unknown => write!(f, "unknown"),
 			TypeAnnotationKind::Void => write!(f, "void"),
 			TypeAnnotationKind::Json => write!(f, "Json"),
 			TypeAnnotationKind::MutJson => write!(f, "MutJson"),
@@ -219,6 +268,7 @@ impl Display for TypeAnnotationKind {
 			TypeAnnotationKind::MutSet(t) => write!(f, "MutSet<{}>", t),
 			TypeAnnotationKind::Function(t) => write!(f, "{}", t),
 			TypeAnnotationKind::UserDefined(user_defined_type) => write!(f, "{}", user_defined_type),
+			TypeAnnotationKind::Literal(values) => write!(f, "{}", values.iter().join(" | ")),
 		}
 	}
 }
@@ -398,8 +448,8 @@ impl Class {
 #[derive(Debug)]
 pub struct Interface {
 	pub name: Symbol,
-	// Each method has a symbol, a signature, and an optional documentation string
-	pub methods: Vec<(Symbol, FunctionSignature, Option<String>)>,
+	// Each method has a symbol, a signature, an optional documentation string and whether it's static
+	pub methods: Vec<(Symbol, FunctionSignature, Option<String>, bool)>,
 	pub extends: Vec<UserDefinedType>,
 	pub access: AccessModifier,
 	pub phase: Phase,
@@ -418,6 +468,10 @@ pub struct Enum {
 	pub name: Symbol,
 	// Each value has a symbol and an optional documenation string
 	pub values: IndexMap<Symbol, Option<String>>,
+	/// Values with an explicit backing string (`A = "a"`), values without one are backed by their name
+	pub backing_values: IndexMap<Symbol, String>,
+	/// A `flags enum`, whose values are distinct bits that can be combined with `|` and `&`
+	pub is_flags: bool,
 	pub access: AccessModifier,
 }
 
@@ -429,6 +483,8 @@ pub enum BringSource {
 	/// The name of the library, and the path to the library (usually inside node_modules)
 	WingLibrary(Symbol, Utf8PathBuf),
 	JsiiModule(Symbol),
+	/// A plain npm package without JSII type information (`bring "npm:lodash" as _`), its exports are untyped
+	NpmModule(Symbol),
 	/// Refers to a relative path to a file
 	WingFile(Utf8PathBuf),
 	/// Refers to a relative path to a directory
@@ -447,11 +503,26 @@ pub struct IfLet {
 	pub reassignable: bool,
 	pub var_name: Symbol,
 	pub value: Expr,
+	/// Further bindings and guards that must all hold for `statements` to run
+	pub conditions: Vec<IfLetCondition>,
 	pub statements: Scope,
 	pub else_if_statements: Vec<ElseIfs>,
 	pub else_statements: Option<Scope>,
 }
 
+/// A clause following the initial binding of an `if let` chain: `if let a = x, let b = y, a > b { ... }`
+#[derive(Debug)]
+pub enum IfLetCondition {
+	/// `let b = y`, binds `b` only if `y` has a value
+	Let {
+		reassignable: bool,
+		var_name: Symbol,
+		value: Expr,
+	},
+	/// A boolean expression that can refer to all preceding bindings
+	Guard(Expr),
+}
+
 #[derive(Debug)]
 pub enum ElseIfs {
 	ElseIfBlock(ElseIfBlock),
@@ -472,9 +543,13 @@ pub enum StmtKind {
 		var_name: Symbol,
 		initial_value: Expr,
 		type_: Option<TypeAnnotation>,
+		/// Only module-level definitions can be made visible outside of their file
+		access: AccessModifier,
 	},
 	ForLoop {
 		iterator: Symbol,
+		/// The index in `for item, i in arr` or the value in `for key, value in map`
+		second_iterator: Option<Symbol>,
 		iterable: Expr,
 		statements: Scope,
 	},
@@ -482,6 +557,13 @@ pub enum StmtKind {
 		condition: Expr,
 		statements: Scope,
 	},
+	/// `while let x = y { ... }`, runs while `y` has a value and binds it to `x` for each iteration
+	WhileLet {
+		reassignable: bool,
+		var_name: Symbol,
+		value: Expr,
+		statements: Scope,
+	},
 	IfLet(IfLet),
 	If {
 		condition: Expr,
@@ -558,6 +640,18 @@ pub enum AccessModifier {
 	Internal,
 }
 
+impl AccessModifier {
+	/// The keyword declaring this access modifier (private is the default, so it has none)
+	pub fn keyword(&self) -> Option<&'static str> {
+		match self {
+			AccessModifier::Private => None,
+			AccessModifier::Public => Some("pub"),
+			AccessModifier::Protected => Some("protected"),
+			AccessModifier::Internal => Some("internal"),
+		}
+	}
+}
+
 impl Display for AccessModifier {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
@@ -652,6 +746,8 @@ pub enum ExprKind {
 	Call {
 		callee: CalleeKind,
 		arg_list: ArgList,
+		/// Whether the call uses optional chaining: `f?()`
+		optional_accessor: bool,
 	},
 	Unary {
 		// TODO: Split to LogicalUnary, NumericUnary
@@ -689,6 +785,17 @@ pub enum ExprKind {
 		element: Box<Expr>,
 	},
 	FunctionClosure(FunctionDefinition),
+	/// Raw JavaScript embedded in inflight code: `unsafe js (a, b): num { return a + b; }`.
+	/// The code can use the inputs by their names, and its return value is trusted to be of the declared type.
+	InlineJs {
+		/// References to the variables passed to the code
+		inputs: Vec<Expr>,
+		return_type: Option<TypeAnnotation>,
+		code: String,
+	},
+	/// Placeholder for an expression that couldn't be parsed. An error was already reported for it, so the
+	/// rest of the statement can still be compiled without reporting more errors.
+	Error,
 }
 
 #[derive(Debug)]
@@ -724,8 +831,8 @@ pub struct Expr {
 }
 
 impl Expr {
-	pub fn new(kind: ExprKind, span: WingSpan) -> Self {
-		let id = EXPR_COUNTER.fetch_add(1, Ordering::SeqCst);
+	pub fn new(ctx: &AstContext, kind: ExprKind, span: WingSpan) -> Self {
+		let id = ctx.next_expr_id();
 		Self { id, kind, span }
 	}
 }
@@ -749,17 +856,17 @@ pub struct ArgList {
 }
 
 impl ArgList {
-	pub fn new(pos_args: Vec<Expr>, named_args: IndexMap<Symbol, Expr>, span: WingSpan) -> Self {
+	pub fn new(ctx: &AstContext, pos_args: Vec<Expr>, named_args: IndexMap<Symbol, Expr>, span: WingSpan) -> Self {
 		ArgList {
 			pos_args,
 			named_args,
 			span,
-			id: ARGLIST_COUNTER.fetch_add(1, Ordering::Relaxed),
+			id: ctx.next_arglist_id(),
 		}
 	}
 
-	pub fn new_empty(span: WingSpan) -> Self {
-		Self::new(vec![], IndexMap::new(), span)
+	pub fn new_empty(ctx: &AstContext, span: WingSpan) -> Self {
+		Self::new(ctx, vec![], IndexMap::new(), span)
 	}
 }
 
@@ -796,16 +903,16 @@ pub struct Scope {
 }
 
 impl Scope {
-	pub fn empty() -> Self {
+	pub fn empty(ctx: &AstContext) -> Self {
 		Self {
-			id: SCOPE_COUNTER.fetch_add(1, Ordering::SeqCst),
+			id: ctx.next_scope_id(),
 			statements: vec![],
 			span: WingSpan::default(),
 		}
 	}
 
-	pub fn new(statements: Vec<Stmt>, span: WingSpan) -> Self {
-		let id = SCOPE_COUNTER.fetch_add(1, Ordering::SeqCst);
+	pub fn new(ctx: &AstContext, statements: Vec<Stmt>, span: WingSpan) -> Self {
+		let id = ctx.next_scope_id();
 		Self { id, statements, span }
 	}
 }
@@ -835,6 +942,12 @@ pub enum BinaryOperator {
 	LogicalAnd,
 	LogicalOr,
 	UnwrapOr,
+	/// Membership test: `key in map`, `item in set`, `item in array` or `substr in str`
+	In,
+	/// Union of the values of a flags enum: `a | b`
+	BitwiseOr,
+	/// Intersection of the values of a flags enum: `a & b`
+	BitwiseAnd,
 }
 
 #[derive(Debug)]
@@ -850,12 +963,19 @@ pub enum Reference {
 	/// A reference to an accessed member of an object `expression[x]`
 	///
 	/// TODO: should this be a separate type of Expr? (this would require changing how `Assignment` statements are modeled)
-	ElementAccess { object: Box<Expr>, index: Box<Expr> },
+	ElementAccess {
+		object: Box<Expr>,
+		index: Box<Expr>,
+		/// Whether the access uses optional chaining: `expression?[x]`
+		optional_accessor: bool,
+	},
 	/// A reference to a member inside a type: `MyType.x` or `MyEnum.A`
 	TypeMember {
 		type_name: UserDefinedType,
 		property: Symbol,
 	},
+	/// A reference to a member of the parent class: `super.x` (calls to `super.x()` are `CalleeKind::SuperCall`)
+	SuperMember { property: Symbol },
 }
 
 impl Clone for Reference {
@@ -868,6 +988,9 @@ impl Clone for Reference {
 				property: property.clone(),
 			},
 			Reference::ElementAccess { .. } => panic!("Unable to clone reference to element access"),
+			Reference::SuperMember { property } => Reference::SuperMember {
+				property: property.clone(),
+			},
 		}
 	}
 }
@@ -882,7 +1005,8 @@ impl Spanned for Reference {
 				optional_accessor: _,
 			} => object.span().merge(&property.span()),
 			Reference::TypeMember { type_name, property } => type_name.span().merge(&property.span()),
-			Reference::ElementAccess { object, index } => {
+			Reference::SuperMember { property } => property.span(),
+			Reference::ElementAccess { object, index, .. } => {
 				let mut span = object.span().merge(&index.span());
 				// Add one to include the closing bracket.
 				// TODO: store a dedicated span field?
@@ -915,6 +1039,7 @@ impl Display for Reference {
 			Reference::ElementAccess { .. } => {
 				write!(f, "element access") // TODO!
 			}
+			Reference::SuperMember { property } => write!(f, "super.{}", property.name),
 		}
 	}
 }
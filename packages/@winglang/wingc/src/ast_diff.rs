@@ -0,0 +1,566 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::ast::{
+	AccessModifier, AstContext, Class, ExprKind, FunctionBody, FunctionDefinition, FunctionSignature, Phase, Stmt,
+	StmtKind, TypeAnnotationKind, UserDefinedType,
+};
+use crate::diagnostic::WingSpan;
+use crate::file_graph::File;
+use crate::parser::Parser;
+use crate::type_check::{CLASS_INFLIGHT_INIT_NAME, CLASS_INIT_NAME};
+use crate::wasm_util::extern_json_fn;
+use crate::workspace::WorkspaceCache;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AstDiffParams {
+	/// The path of the file, used for the spans of the diff
+	pub path: String,
+	pub old_source: String,
+	pub new_source: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+	Added,
+	Removed,
+	Modified,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DeclarationKind {
+	Class,
+	Interface,
+	Struct,
+	Enum,
+	Variable,
+	Function,
+	Field,
+	Method,
+	Initializer,
+	EnumValue,
+}
+
+/// A declaration that was added, removed or modified between two versions of a file
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeclarationChange {
+	/// The path of the declaration in its file, e.g. "Bucket.put" for the `put` method of the `Bucket` class
+	pub path: String,
+	pub kind: DeclarationKind,
+	pub change: ChangeKind,
+	pub old_span: Option<WingSpan>,
+	pub new_span: Option<WingSpan>,
+	pub old_signature: Option<String>,
+	pub new_signature: Option<String>,
+	/// Whether the signature of a modified declaration changed (otherwise only its implementation did)
+	pub signature_changed: bool,
+	/// Whether code using the old version of the file from another library could break with the new one
+	pub breaking: bool,
+}
+
+/// A declaration of a file, or a member of one of its types
+struct Declaration {
+	kind: DeclarationKind,
+	/// Access modifier, phase, name and type of the declaration, e.g. "pub inflight get(key: str): str"
+	signature: String,
+	/// The source of the declaration with its whitespace normalized, for telling whether its implementation changed.
+	/// Types only use their signature, since the changes to their members are reported separately.
+	text: String,
+	span: WingSpan,
+	/// Whether the declaration can be used from another library
+	visible: bool,
+	/// Whether adding the declaration to an existing type can break code using the type from another library (e.g.
+	/// a method of a public interface or a required field of a public struct)
+	breaking_when_added: bool,
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wingc_get_ast_diff(ptr: u32, len: u32) -> u64 {
+	extern_json_fn(ptr, len, get_ast_diff)
+}
+
+/// Compares the declarations of two versions of a file. Changes of the members of types that were added or removed
+/// aren't reported separately.
+pub fn get_ast_diff(params: AstDiffParams) -> Vec<DeclarationChange> {
+	let old = parse_declarations(&params.path, &params.old_source);
+	let new = parse_declarations(&params.path, &params.new_source);
+
+	let is_member_of = |path: &str, declarations: &IndexMap<String, Declaration>| {
+		path
+			.split_once('.')
+			.map_or(false, |(parent, _)| !declarations.contains_key(parent))
+	};
+
+	let mut changes = vec![];
+	for (path, old_decl) in &old {
+		match new.get(path) {
+			None => {
+				if is_member_of(path, &new) {
+					continue;
+				}
+				changes.push(DeclarationChange {
+					path: path.clone(),
+					kind: old_decl.kind,
+					change: ChangeKind::Removed,
+					old_span: Some(old_decl.span.clone()),
+					new_span: None,
+					old_signature: Some(old_decl.signature.clone()),
+					new_signature: None,
+					signature_changed: false,
+					breaking: old_decl.visible,
+				});
+			}
+			Some(new_decl) => {
+				let signature_changed = old_decl.signature != new_decl.signature;
+				if !signature_changed && old_decl.text == new_decl.text {
+					continue;
+				}
+				changes.push(DeclarationChange {
+					path: path.clone(),
+					kind: new_decl.kind,
+					change: ChangeKind::Modified,
+					old_span: Some(old_decl.span.clone()),
+					new_span: Some(new_decl.span.clone()),
+					old_signature: Some(old_decl.signature.clone()),
+					new_signature: Some(new_decl.signature.clone()),
+					signature_changed,
+					breaking: signature_changed && old_decl.visible,
+				});
+			}
+		}
+	}
+	for (path, new_decl) in &new {
+		if old.contains_key(path) || is_member_of(path, &old) {
+			continue;
+		}
+		changes.push(DeclarationChange {
+			path: path.clone(),
+			kind: new_decl.kind,
+			change: ChangeKind::Added,
+			old_span: None,
+			new_span: Some(new_decl.span.clone()),
+			old_signature: None,
+			new_signature: Some(new_decl.signature.clone()),
+			signature_changed: false,
+			breaking: new_decl.breaking_when_added,
+		});
+	}
+	changes
+}
+
+fn parse_declarations(path: &str, source: &str) -> IndexMap<String, Declaration> {
+	let language = tree_sitter_wing::language();
+	let mut tree_sitter_parser = tree_sitter::Parser::new();
+	tree_sitter_parser.set_language(&language).unwrap();
+	let Some(tree) = tree_sitter_parser.parse(source.as_bytes(), None) else {
+		panic!("Error parsing source file with tree-sitter: {path}");
+	};
+
+	let ast_ctx = AstContext::new();
+	let workspaces = WorkspaceCache::default();
+	let parser = Parser::new(source.as_bytes(), File::new(path, "root"), &ast_ctx, &workspaces);
+	let (scope, _, _) = parser.parse(&tree.root_node());
+
+	let mut collector = DeclarationCollector {
+		source,
+		declarations: IndexMap::new(),
+	};
+	for stmt in &scope.statements {
+		collector.add_stmt(stmt);
+	}
+	collector.declarations
+}
+
+struct DeclarationCollector<'a> {
+	source: &'a str,
+	declarations: IndexMap<String, Declaration>,
+}
+
+impl<'a> DeclarationCollector<'a> {
+	fn add(&mut self, path: String, kind: DeclarationKind, signature: String, span: &WingSpan, visible: bool) {
+		self.add_member(path, kind, signature, span, visible, false);
+	}
+
+	fn add_member(
+		&mut self,
+		path: String,
+		kind: DeclarationKind,
+		signature: String,
+		span: &WingSpan,
+		visible: bool,
+		breaking_when_added: bool,
+	) {
+		let text = match kind {
+			DeclarationKind::Function
+			| DeclarationKind::Variable
+			| DeclarationKind::Method
+			| DeclarationKind::Initializer => self.normalized_text(span),
+			_ => signature.clone(),
+		};
+		self.declarations.insert(
+			path,
+			Declaration {
+				kind,
+				signature,
+				text,
+				span: span.clone(),
+				visible,
+				breaking_when_added,
+			},
+		);
+	}
+
+	fn normalized_text(&self, span: &WingSpan) -> String {
+		self
+			.source
+			.get(span.start_offset..span.end_offset)
+			.unwrap_or_default()
+			.split_whitespace()
+			.collect::<Vec<_>>()
+			.join(" ")
+	}
+
+	fn add_stmt(&mut self, stmt: &Stmt) {
+		match &stmt.kind {
+			StmtKind::Let {
+				reassignable,
+				var_name,
+				initial_value,
+				type_,
+				access,
+			} => {
+				let var = if *reassignable { "var " } else { "" };
+				let (kind, signature) = match (&initial_value.kind, type_) {
+					(ExprKind::FunctionClosure(def), None) => (
+						DeclarationKind::Function,
+						format!("{}let {var}{var_name} = {}", access_prefix(*access), def.signature),
+					),
+					(_, Some(type_)) => (
+						DeclarationKind::Variable,
+						format!("{}let {var}{var_name}: {type_}", access_prefix(*access)),
+					),
+					(_, None) => (
+						DeclarationKind::Variable,
+						format!("{}let {var}{var_name}", access_prefix(*access)),
+					),
+				};
+				self.add(var_name.name.clone(), kind, signature, &stmt.span, is_exported(*access));
+			}
+			StmtKind::Class(class) => self.add_class(class, &stmt.span),
+			StmtKind::Interface(interface) => {
+				let visible = is_exported(interface.access);
+				let extends = type_list(" extends ", &interface.extends);
+				self.add(
+					interface.name.name.clone(),
+					DeclarationKind::Interface,
+					format!(
+						"{}{}interface {}{extends}",
+						access_prefix(interface.access),
+						phase_prefix(interface.phase),
+						interface.name
+					),
+					&stmt.span,
+					visible,
+				);
+				for (name, signature, _, is_static) in &interface.methods {
+					let static_ = if *is_static { "static " } else { "" };
+					self.add_member(
+						format!("{}.{name}", interface.name),
+						DeclarationKind::Method,
+						format!("{static_}{}", callable_signature(&name.name, signature)),
+						&name.span,
+						visible,
+						// classes implementing the interface don't have the method yet
+						visible,
+					);
+				}
+			}
+			StmtKind::Struct(struct_) => {
+				let visible = is_exported(struct_.access);
+				let extends = type_list(" extends ", &struct_.extends);
+				self.add(
+					struct_.name.name.clone(),
+					DeclarationKind::Struct,
+					format!("{}struct {}{extends}", access_prefix(struct_.access), struct_.name),
+					&stmt.span,
+					visible,
+				);
+				for field in &struct_.fields {
+					let required = !matches!(field.member_type.kind, TypeAnnotationKind::Optional(_));
+					self.add_member(
+						format!("{}.{}", struct_.name, field.name),
+						DeclarationKind::Field,
+						format!("{}: {}", field.name, field.member_type),
+						&field.name.span,
+						visible,
+						visible && required,
+					);
+				}
+			}
+			StmtKind::Enum(enum_) => {
+				let visible = is_exported(enum_.access);
+				self.add(
+					enum_.name.name.clone(),
+					DeclarationKind::Enum,
+					format!("{}enum {}", access_prefix(enum_.access), enum_.name),
+					&stmt.span,
+					visible,
+				);
+				for value in enum_.values.keys() {
+					self.add(
+						format!("{}.{value}", enum_.name),
+						DeclarationKind::EnumValue,
+						value.name.clone(),
+						&value.span,
+						visible,
+					);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn add_class(&mut self, class: &Class, span: &WingSpan) {
+		let class_visible = is_exported(class.access);
+		let parent = class
+			.parent
+			.as_ref()
+			.map(|parent| format!(" extends {parent}"))
+			.unwrap_or_default();
+		let implements = type_list(" impl ", &class.implements);
+		self.add(
+			class.name.name.clone(),
+			DeclarationKind::Class,
+			format!(
+				"{}{}class {}{parent}{implements}",
+				access_prefix(class.access),
+				phase_prefix(class.phase),
+				class.name
+			),
+			span,
+			class_visible,
+		);
+
+		for field in &class.fields {
+			let var = if field.reassignable { "var " } else { "" };
+			let static_ = if field.is_static { "static " } else { "" };
+			self.add(
+				format!("{}.{}", class.name, field.name),
+				DeclarationKind::Field,
+				format!(
+					"{}{static_}{}{var}{}: {}",
+					access_prefix(field.access),
+					phase_prefix(field.phase),
+					field.name,
+					field.member_type
+				),
+				&field.name.span,
+				class_visible && is_member_visible(field.access),
+			);
+		}
+
+		for (initializer, name) in [
+			(&class.initializer, CLASS_INIT_NAME),
+			(&class.inflight_initializer, CLASS_INFLIGHT_INIT_NAME),
+		] {
+			// default initializers have the span of the class name and no source of their own
+			let is_default = initializer.span == class.name.span;
+			let path = format!("{}.{name}", class.name);
+			// initializers return the class, so the return type is left out
+			let signature = format!(
+				"{}new({})",
+				phase_prefix(initializer.signature.phase),
+				parameter_list(&initializer.signature)
+			);
+			self.add(
+				path.clone(),
+				DeclarationKind::Initializer,
+				signature,
+				&initializer.span,
+				class_visible,
+			);
+			if is_default {
+				self.declarations.get_mut(&path).unwrap().text.clear();
+			}
+		}
+
+		for (name, method) in &class.methods {
+			self.add(
+				format!("{}.{name}", class.name),
+				DeclarationKind::Method,
+				method_signature(name.name.as_str(), method),
+				&method.span,
+				class_visible && is_member_visible(method.access),
+			);
+		}
+	}
+}
+
+fn method_signature(name: &str, method: &FunctionDefinition) -> String {
+	let static_ = if method.is_static { "static " } else { "" };
+	let extern_ = match &method.body {
+		FunctionBody::External(_) => "extern ",
+		FunctionBody::Statements(_) => "",
+	};
+	format!(
+		"{}{extern_}{static_}{}",
+		access_prefix(method.access),
+		callable_signature(name, &method.signature)
+	)
+}
+
+/// The signature of a method, e.g. "inflight get(key: str): str"
+fn callable_signature(name: &str, signature: &FunctionSignature) -> String {
+	format!(
+		"{}{name}({}): {}",
+		phase_prefix(signature.phase),
+		parameter_list(signature),
+		signature.return_type
+	)
+}
+
+fn parameter_list(signature: &FunctionSignature) -> String {
+	signature
+		.parameters
+		.iter()
+		.map(|p| format!("{}: {}", p.name, p.type_annotation))
+		.collect::<Vec<_>>()
+		.join(", ")
+}
+
+fn access_prefix(access: AccessModifier) -> &'static str {
+	match access {
+		AccessModifier::Private => "",
+		AccessModifier::Public => "pub ",
+		AccessModifier::Protected => "protected ",
+		AccessModifier::Internal => "internal ",
+	}
+}
+
+fn phase_prefix(phase: Phase) -> &'static str {
+	match phase {
+		Phase::Inflight => "inflight ",
+		Phase::Preflight => "",
+		Phase::Independent => "unphased ",
+	}
+}
+
+fn type_list(keyword: &str, types: &[UserDefinedType]) -> String {
+	if types.is_empty() {
+		return String::new();
+	}
+	format!(
+		"{keyword}{}",
+		types.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", ")
+	)
+}
+
+/// Whether a module-level declaration can be brought into another library
+fn is_exported(access: AccessModifier) -> bool {
+	access == AccessModifier::Public
+}
+
+/// Whether a member of an exported type can be used by another library (protected members can be used by its
+/// subclasses)
+fn is_member_visible(access: AccessModifier) -> bool {
+	matches!(access, AccessModifier::Public | AccessModifier::Protected)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{get_ast_diff, AstDiffParams, ChangeKind};
+
+	#[test]
+	fn reports_declaration_changes() {
+		let old_source = r#"
+pub class Bucket {
+  pub put(key: str, value: str) {
+    log(key);
+  }
+  pub get(key: str): str {
+    return key;
+  }
+  helper() {}
+  pub remove(key: str) {}
+}
+pub struct Options {
+  name: str;
+}
+pub interface IStore {
+  inflight get(): str;
+}
+internal enum Color { RED }
+"#;
+		let new_source = r#"
+pub class Bucket {
+  pub put(key: str, value: str) {
+    log(value);
+  }
+  pub get(key: str, default: str?): str {
+    return key;
+  }
+  helper(x: num) {}
+  pub list(): Array<str> {
+    return [];
+  }
+}
+pub struct Options {
+  name: str;
+  region: str;
+  tags: Map<str>?;
+}
+pub interface IStore {
+  inflight get(): str;
+  inflight set(value: str): void;
+}
+internal enum Color { RED, GREEN }
+pub class Queue {
+  pub push() {}
+}
+"#;
+
+		let changes = get_ast_diff(AstDiffParams {
+			path: "lib.w".to_string(),
+			old_source: old_source.to_string(),
+			new_source: new_source.to_string(),
+		});
+		let summary = changes
+			.iter()
+			.map(|change| {
+				(
+					change.path.as_str(),
+					change.change,
+					change.signature_changed,
+					change.breaking,
+				)
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(
+			summary,
+			vec![
+				("Bucket.put", ChangeKind::Modified, false, false),
+				("Bucket.get", ChangeKind::Modified, true, true),
+				("Bucket.helper", ChangeKind::Modified, true, false),
+				("Bucket.remove", ChangeKind::Removed, false, true),
+				("Bucket.list", ChangeKind::Added, false, false),
+				("Options.region", ChangeKind::Added, false, true),
+				("Options.tags", ChangeKind::Added, false, false),
+				("IStore.set", ChangeKind::Added, false, true),
+				("Color.GREEN", ChangeKind::Added, false, false),
+				("Queue", ChangeKind::Added, false, false),
+			]
+		);
+
+		let get = &changes[1];
+		assert_eq!(get.old_signature.as_deref(), Some("pub get(key: str): str"));
+		assert_eq!(
+			get.new_signature.as_deref(),
+			Some("pub get(key: str, default: str?): str")
+		);
+		assert_eq!(get.new_span.as_ref().unwrap().start.line, 5);
+	}
+}
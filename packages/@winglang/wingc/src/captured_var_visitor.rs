@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use crate::{
+	ast::{FunctionBody, FunctionDefinition, Phase, Reference, Scope, Stmt, StmtKind, Symbol},
+	diagnostic::{Diagnostic, WingSpan},
+	type_check::{symbol_env::LookupResult, Types, VariableInfo, VariableKind},
+	visit::{self, Visit},
+	visit_context::{VisitContext, VisitorWithContext},
+};
+
+/// This visitor reports preflight `var` variables that are reassigned after being captured by inflight code.
+///
+/// Inflight code gets a copy of the value the variable had when it was lifted, so it will never observe the
+/// reassignment. This is a common source of confusion, so we don't allow it.
+pub struct CapturedVarVisitor<'a> {
+	types: &'a Types,
+	ctx: VisitContext,
+	/// The first inflight capture of each preflight variable, keyed by the span of the variable's definition
+	captures: HashMap<WingSpan, WingSpan>,
+}
+
+impl<'a> CapturedVarVisitor<'a> {
+	pub fn new(types: &'a Types) -> Self {
+		Self {
+			types,
+			ctx: VisitContext::new(),
+			captures: HashMap::new(),
+		}
+	}
+
+	pub fn check(&mut self, scope: &Scope) {
+		self.visit_scope(scope);
+	}
+
+	/// Returns the preflight reassignable variable the symbol refers to, if any
+	fn lookup_preflight_var(&self, symbol: &Symbol) -> Option<VariableInfo> {
+		let env = self.ctx.current_env()?;
+		let LookupResult::Found(kind, _) = env.lookup_ext(symbol, Some(self.ctx.current_stmt_idx())) else {
+			return None;
+		};
+		let var = kind.as_variable()?;
+		if var.reassignable && var.phase == Phase::Preflight && matches!(var.kind, VariableKind::Free) {
+			Some(var.clone())
+		} else {
+			None
+		}
+	}
+}
+
+impl<'a> Visit<'a> for CapturedVarVisitor<'_> {
+	fn visit_scope(&mut self, node: &'a Scope) {
+		self.ctx.push_env(self.types.get_scope_env(node));
+		visit::visit_scope(self, node);
+		self.ctx.pop_env();
+	}
+
+	fn visit_stmt(&mut self, node: &'a Stmt) {
+		self.with_stmt(node, |v| {
+			if let StmtKind::Assignment {
+				variable: Reference::Identifier(symbol),
+				..
+			} = &node.kind
+			{
+				if v.ctx.current_phase() == Phase::Preflight {
+					if let Some(var) = v.lookup_preflight_var(symbol) {
+						if let Some(capture) = v.captures.get(&var.name.span) {
+							Diagnostic::new(
+								format!("Variable \"{symbol}\" is reassigned after it was captured by inflight code"),
+								symbol,
+							)
+							.annotate("captured here", capture.clone())
+							.hint("Inflight code sees the value the variable had when it was lifted, not later reassignments")
+							.report();
+						}
+					}
+				}
+			}
+			visit::visit_stmt(v, node);
+		});
+	}
+
+	fn visit_reference(&mut self, node: &'a Reference) {
+		if let Reference::Identifier(symbol) = node {
+			if self.ctx.current_phase() == Phase::Inflight {
+				if let Some(var) = self.lookup_preflight_var(symbol) {
+					self.captures.entry(var.name.span).or_insert(symbol.span.clone());
+				}
+			}
+		}
+		visit::visit_reference(self, node);
+	}
+
+	fn visit_function_definition(&mut self, node: &'a FunctionDefinition) {
+		match &node.body {
+			FunctionBody::Statements(scope) => self.with_function_def(
+				node.name.as_ref(),
+				&node.signature,
+				node.is_static,
+				self.types.get_scope_env(scope),
+				&node.span,
+				|v| visit::visit_function_definition(v, node),
+			),
+			FunctionBody::External(_) => visit::visit_function_definition(self, node),
+		}
+	}
+}
+
+impl VisitorWithContext for CapturedVarVisitor<'_> {
+	fn ctx(&mut self) -> &mut VisitContext {
+		&mut self.ctx
+	}
+}
@@ -1,12 +1,16 @@
+use std::collections::HashMap;
+
 use indexmap::IndexMap;
 
 use crate::{
 	ast::{
-		AccessModifier, ArgList, AssignmentKind, CalleeKind, Class, ClassField, Expr, ExprKind, FunctionBody,
-		FunctionDefinition, FunctionParameter, FunctionSignature, Literal, New, Phase, Reference, Scope, Stmt, StmtKind,
-		Symbol, TypeAnnotation, TypeAnnotationKind, UserDefinedType,
+		AccessModifier, ArgList, AssignmentKind, AstContext, CalleeKind, Class, ClassField, Expr, ExprId, ExprKind,
+		FunctionBody, FunctionDefinition, FunctionParameter, FunctionSignature, Literal, New, Phase, Reference, Scope,
+		Stmt, StmtKind, Symbol, TypeAnnotation, TypeAnnotationKind, UserDefinedType,
 	},
+	content_hash::short_content_hash,
 	diagnostic::WingSpan,
+	files::Files,
 	fold::{self, Fold},
 	type_check::{CLASS_INFLIGHT_INIT_NAME, CLASS_INIT_NAME, CLOSURE_CLASS_HANDLE_METHOD},
 };
@@ -18,7 +22,10 @@ pub const PARENT_THIS_NAME: &str = "__parent_this";
 ///
 /// This is done by wrapping the closure's code in a preflight class with a single method,
 /// and replacing the closure with a reference to an instance of that class.
-/// The class is given a unique name to avoid collisions.
+/// The class is given a unique name to avoid collisions. The name is derived from the closure's source code,
+/// so it stays the same when other code in the file changes. It also includes a display name taken from where the
+/// closure is defined (the variable, argument or field it is assigned to, and the enclosing method), so the class
+/// can be recognized in simulator logs, stack traces and the construct tree.
 ///
 /// For example, the following code:
 ///
@@ -33,22 +40,30 @@ pub const PARENT_THIS_NAME: &str = "__parent_this";
 ///
 /// ```wing
 /// let b = new cloud.Bucket();
-/// class $Closure1 {
+/// class $Closure_f_1a2b3c4d {
 ///   new() {}
 ///   inflight handle(message: str) {
 ///     b.put("file.txt", message);
 ///   }
 /// }
-/// let f = new $Closure1();
+/// let f = new $Closure_f_1a2b3c4d();
 /// ```
-pub struct ClosureTransformer {
+pub struct ClosureTransformer<'a> {
+	// The source code of the files being transformed, used to name the generated classes
+	files: &'a Files,
+	// Allocates the identifiers of the generated AST nodes
+	ast_ctx: &'a AstContext,
 	// Whether the transformer is inside a preflight or inflight scope.
 	// Only inflight closures defined in preflight scopes need to be transformed.
 	phase: Phase,
 	// Whether the transformer is inside a scope where "this" is valid.
 	inside_scope_with_this: bool,
-	// Helper state for generating unique class names
-	closure_counter: usize,
+	// Helper state for generating unique class names: the number of closures seen so far with each source hash
+	closure_hashes: HashMap<String, usize>,
+	// Display names for the closures found in the expressions and statements visited so far, by expression id
+	display_names: HashMap<ExprId, String>,
+	// Names of the classes, methods and named preflight closures we're inside, innermost last
+	enclosing_names: Vec<String>,
 	// Stores the list of class definitions that need to be added to the nearest scope
 	class_statements: Vec<Stmt>,
 	// Track the statement index of the nearest statement we're inside so that
@@ -56,19 +71,149 @@ pub struct ClosureTransformer {
 	nearest_stmt_idx: usize,
 }
 
-impl ClosureTransformer {
-	pub fn new() -> Self {
+impl<'a> ClosureTransformer<'a> {
+	pub fn new(files: &'a Files, ast_ctx: &'a AstContext) -> Self {
 		Self {
+			files,
+			ast_ctx,
 			phase: Phase::Preflight,
 			inside_scope_with_this: false,
-			closure_counter: 0,
+			closure_hashes: HashMap::new(),
+			display_names: HashMap::new(),
+			enclosing_names: vec![],
 			class_statements: vec![],
 			nearest_stmt_idx: 0,
 		}
 	}
 }
 
-impl Fold for ClosureTransformer {
+impl ClosureTransformer<'_> {
+	/// Returns a suffix for the names generated for a closure, based on a hash of its source code.
+	/// Identical closures in the same file are told apart by the order they appear in.
+	fn closure_suffix(&mut self, span: &WingSpan) -> String {
+		let source = self
+			.files
+			.get_file(&span.file_id)
+			.and_then(|content| content.get(span.start_offset..span.end_offset))
+			.unwrap_or_default();
+		let hash = short_content_hash(source);
+		let count = self.closure_hashes.entry(hash.clone()).or_default();
+		*count += 1;
+		if *count == 1 {
+			hash
+		} else {
+			format!("{hash}_{count}")
+		}
+	}
+
+	/// Records `name` as the display name of `expr` if it is a closure.
+	fn name_closure(&mut self, expr: &Expr, name: &str) {
+		if matches!(expr.kind, ExprKind::FunctionClosure(_)) {
+			// Keys of JSON literals can be any string, but the name ends up in an identifier
+			let name = name
+				.chars()
+				.map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+				.collect();
+			self.display_names.insert(expr.id, name);
+		}
+	}
+
+	/// Names closures passed as arguments after the named argument they're passed to, or after the callee otherwise.
+	fn name_closure_args(&mut self, arg_list: &ArgList, callee_name: Option<&str>) {
+		if let Some(callee_name) = callee_name {
+			for arg in &arg_list.pos_args {
+				self.name_closure(arg, callee_name);
+			}
+		}
+		for (name, arg) in &arg_list.named_args {
+			self.name_closure(arg, &name.name);
+		}
+	}
+
+	/// Returns the display name of a closure: the name it was given where it's defined, qualified by the name of the
+	/// enclosing method or closure (e.g. `setConsumer_handler`).
+	fn display_name(&mut self, id: ExprId) -> Option<String> {
+		let name = self.display_names.remove(&id);
+		match (self.enclosing_names.last(), name) {
+			(Some(enclosing), Some(name)) if *enclosing != name => Some(format!("{enclosing}_{name}")),
+			(_, Some(name)) => Some(name),
+			(Some(enclosing), None) => Some(enclosing.clone()),
+			(None, None) => None,
+		}
+	}
+}
+
+/// Returns the name of the variable or member a reference refers to.
+fn reference_name(reference: &Reference) -> Option<&str> {
+	match reference {
+		Reference::Identifier(symbol) => Some(&symbol.name),
+		Reference::InstanceMember { property, .. }
+		| Reference::TypeMember { property, .. }
+		| Reference::SuperMember { property } => Some(&property.name),
+		Reference::ElementAccess { .. } => None,
+	}
+}
+
+/// Returns the display name of a closure class, as generated by `ClosureTransformer`, if it has one.
+///
+/// For example, the display name of `$Closure_setConsumer_handler_1a2b3c4d` is `setConsumer_handler`.
+pub fn closure_display_name(class_name: &str) -> Option<&str> {
+	let rest = class_name.strip_prefix(CLOSURE_CLASS_PREFIX)?.strip_prefix('_')?;
+	// Strip the source hash, and the counter of identical closures that may follow it
+	let is_hash = |part: &str| part.len() == 8 && part.chars().all(|c| c.is_ascii_hexdigit());
+	let (rest, last) = rest.rsplit_once('_')?;
+	let rest = if is_hash(last) {
+		rest
+	} else {
+		let (rest, hash) = rest.rsplit_once('_')?;
+		is_hash(hash).then_some(rest)?
+	};
+	(!rest.is_empty()).then_some(rest)
+}
+
+impl Fold for ClosureTransformer<'_> {
+	fn fold_stmt(&mut self, node: Stmt) -> Stmt {
+		match &node.kind {
+			StmtKind::Let {
+				var_name,
+				initial_value,
+				..
+			} => self.name_closure(initial_value, &var_name.name),
+			StmtKind::Assignment { variable, value, .. } => {
+				if let Some(name) = reference_name(variable) {
+					self.name_closure(value, name);
+				}
+			}
+			StmtKind::Bring { .. }
+			| StmtKind::SuperConstructor { .. }
+			| StmtKind::ForLoop { .. }
+			| StmtKind::While { .. }
+			| StmtKind::WhileLet { .. }
+			| StmtKind::IfLet(_)
+			| StmtKind::If { .. }
+			| StmtKind::Break
+			| StmtKind::Continue
+			| StmtKind::Return(_)
+			| StmtKind::Throw(_)
+			| StmtKind::Expression(_)
+			| StmtKind::Scope(_)
+			| StmtKind::Class(_)
+			| StmtKind::Interface(_)
+			| StmtKind::Struct(_)
+			| StmtKind::Enum(_)
+			| StmtKind::TryCatch { .. }
+			| StmtKind::ExplicitLift(_) => {}
+		}
+		fold::fold_stmt(self, node)
+	}
+
+	fn fold_class(&mut self, node: Class) -> Class {
+		self.enclosing_names.push(node.name.name.clone());
+		let new_node = fold::fold_class(self, node);
+		self.enclosing_names.pop();
+		new_node
+	}
+
 	fn fold_scope(&mut self, node: Scope) -> Scope {
 		let mut statements = vec![];
 
@@ -105,7 +250,19 @@ impl Fold for ClosureTransformer {
 		if !node.is_static {
 			self.inside_scope_with_this = true;
 		}
+		// Initializers are named after their class, which is already on the stack
+		let method_name = node
+			.name
+			.as_ref()
+			.map(|name| name.name.clone())
+			.filter(|name| name != CLASS_INIT_NAME && name != CLASS_INFLIGHT_INIT_NAME);
+		if let Some(method_name) = &method_name {
+			self.enclosing_names.push(method_name.clone());
+		}
 		let new_node = fold::fold_function_definition(self, node);
+		if method_name.is_some() {
+			self.enclosing_names.pop();
+		}
 		self.inside_scope_with_this = prev_inside_scope_with_this;
 		self.phase = prev_phase;
 		new_node
@@ -123,18 +280,66 @@ impl Fold for ClosureTransformer {
 		// in case its body contains any inflight closures.
 		if let ExprKind::FunctionClosure(ref func_def) = expr.kind {
 			if func_def.signature.phase != Phase::Inflight {
-				return fold::fold_expr(self, expr);
+				let display_name = self.display_name(expr.id);
+				if let Some(display_name) = &display_name {
+					self.enclosing_names.push(display_name.clone());
+				}
+				let new_expr = fold::fold_expr(self, expr);
+				if display_name.is_some() {
+					self.enclosing_names.pop();
+				}
+				return new_expr;
 			}
 		}
 
+		// Name the closures this expression is made of before folding them
+		match &expr.kind {
+			ExprKind::Call { callee, arg_list, .. } => {
+				let callee_name = match callee {
+					CalleeKind::Expr(callee) => match &callee.kind {
+						ExprKind::Reference(reference) => reference_name(reference),
+						_ => None,
+					},
+					CalleeKind::SuperCall(method) => Some(method.name.as_str()),
+				};
+				self.name_closure_args(arg_list, callee_name);
+			}
+			ExprKind::New(new) => {
+				let class_name = new.class.fields.last().unwrap_or(&new.class.root);
+				self.name_closure_args(&new.arg_list, Some(&class_name.name));
+			}
+			ExprKind::StructLiteral { fields, .. } | ExprKind::JsonMapLiteral { fields } => {
+				for (name, value) in fields {
+					self.name_closure(value, &name.name);
+				}
+			}
+			ExprKind::Literal(_)
+			| ExprKind::Range { .. }
+			| ExprKind::Reference(_)
+			| ExprKind::Intrinsic(_)
+			| ExprKind::Unary { .. }
+			| ExprKind::Binary { .. }
+			| ExprKind::ArrayLiteral { .. }
+			| ExprKind::MapLiteral { .. }
+			| ExprKind::SetLiteral { .. }
+			| ExprKind::JsonLiteral { .. }
+			| ExprKind::FunctionClosure(_)
+			| ExprKind::InlineJs { .. }
+			| ExprKind::Error => {}
+		}
+
 		match expr.kind {
 			ExprKind::FunctionClosure(func_def) => {
-				self.closure_counter += 1;
+				let suffix = self.closure_suffix(&expr.span);
+				let class_suffix = match self.display_name(expr.id) {
+					Some(display_name) => format!("{display_name}_{suffix}"),
+					None => suffix.clone(),
+				};
 
 				let file_id = &expr.span.file_id;
 
 				let new_class_name = Symbol {
-					name: format!("{}{}", CLOSURE_CLASS_PREFIX, self.closure_counter),
+					name: format!("{}_{}", CLOSURE_CLASS_PREFIX, class_suffix),
 					span: WingSpan::for_file(file_id),
 				};
 				let handle_name = Symbol {
@@ -156,12 +361,12 @@ impl Fold for ClosureTransformer {
 				let class_fields: Vec<ClassField> = vec![];
 				let class_init_params: Vec<FunctionParameter> = vec![];
 
-				let parent_this = format!("{}_{}", PARENT_THIS_NAME, self.closure_counter);
+				let parent_this = format!("{}_{}", PARENT_THIS_NAME, suffix);
 				let mut this_transform = RenameThisTransformer::new(&parent_this.as_str());
 				let new_func_def = if self.inside_scope_with_this {
 					// If we are inside a class, we transform inflight closures with an extra
-					// `let __parent_this_${CLOSURE_COUNT} = this;` statement before the class definition, and replace references
-					// to `this` with `__parent_this_${CLOSURE_COUNT}` so that they can access the parent class's fields.
+					// `let __parent_this_${CLOSURE_SUFFIX} = this;` statement before the class definition, and replace references
+					// to `this` with `__parent_this_${CLOSURE_SUFFIX}` so that they can access the parent class's fields.
 					this_transform.fold_function_definition(func_def)
 				} else {
 					func_def
@@ -185,16 +390,21 @@ impl Fold for ClosureTransformer {
 				// nodeof(this).hidden = true;
 				// ```
 				let std_display_of_this = Expr::new(
+					self.ast_ctx,
 					ExprKind::Call {
 						callee: CalleeKind::Expr(Box::new(Expr::new(
+							self.ast_ctx,
 							ExprKind::Reference(Reference::Identifier(Symbol::new(
 								"nodeof",
 								WingSpan::for_file(file_id),
 							))),
 							WingSpan::for_file(file_id),
 						))),
+						optional_accessor: false,
 						arg_list: ArgList::new(
+							self.ast_ctx,
 							vec![Expr::new(
+								self.ast_ctx,
 								ExprKind::Reference(Reference::Identifier(Symbol::new("this", WingSpan::for_file(file_id)))),
 								WingSpan::for_file(file_id),
 							)],
@@ -214,13 +424,17 @@ impl Fold for ClosureTransformer {
 							optional_accessor: false,
 						},
 
-						value: Expr::new(ExprKind::Literal(Literal::Boolean(true)), WingSpan::for_file(file_id)),
+						value: Expr::new(
+							self.ast_ctx,
+							ExprKind::Literal(Literal::Boolean(true)),
+							WingSpan::for_file(file_id),
+						),
 					},
 					span: WingSpan::for_file(file_id),
 					doc: None,
 				}];
 
-				// If we are inside a scope with "this", add define `let __parent_this_${CLOSURE_COUNT} = this` which can be
+				// If we are inside a scope with "this", add define `let __parent_this_${CLOSURE_SUFFIX} = this` which can be
 				// used by the newly-created preflight classes
 				if self.inside_scope_with_this && this_transform.performed_renames {
 					let parent_this_name = Symbol::new(parent_this, WingSpan::for_file(file_id));
@@ -230,13 +444,15 @@ impl Fold for ClosureTransformer {
 							reassignable: false,
 							var_name: parent_this_name,
 							initial_value: Expr::new(
+								self.ast_ctx,
 								ExprKind::Reference(Reference::Identifier(this_name)),
 								WingSpan::for_file(file_id),
 							),
 							type_: None,
+							access: AccessModifier::Private,
 						},
 						span: WingSpan::for_file(file_id),
-						idx: 0,
+						idx: self.nearest_stmt_idx,
 						doc: None,
 					};
 					self.class_statements.push(parent_this_def);
@@ -264,7 +480,7 @@ impl Fold for ClosureTransformer {
 								phase: Phase::Preflight,
 							},
 							is_static: true,
-							body: FunctionBody::Statements(Scope::new(class_init_body, WingSpan::for_file(file_id))),
+							body: FunctionBody::Statements(Scope::new(self.ast_ctx, class_init_body, WingSpan::for_file(file_id))),
 							span: WingSpan::for_file(file_id),
 							access: AccessModifier::Public,
 							doc: None,
@@ -284,7 +500,7 @@ impl Fold for ClosureTransformer {
 								phase: Phase::Inflight,
 							},
 							is_static: false,
-							body: FunctionBody::Statements(Scope::new(vec![], WingSpan::for_file(file_id))),
+							body: FunctionBody::Statements(Scope::new(self.ast_ctx, vec![], WingSpan::for_file(file_id))),
 							span: WingSpan::for_file(file_id),
 							access: AccessModifier::Public,
 							doc: None,
@@ -302,9 +518,10 @@ impl Fold for ClosureTransformer {
 				// new <new_class_name>();
 				// ```
 				let new_class_instance = Expr::new(
+					self.ast_ctx,
 					ExprKind::New(New {
 						class: class_udt,
-						arg_list: ArgList::new_empty(WingSpan::for_file(file_id)),
+						arg_list: ArgList::new_empty(self.ast_ctx, WingSpan::for_file(file_id)),
 						obj_id: None,
 						obj_scope: None,
 					}),
@@ -315,7 +532,23 @@ impl Fold for ClosureTransformer {
 
 				new_class_instance
 			}
-			_ => fold::fold_expr(self, expr),
+			// Listed explicitly (rather than with `_`) so any new kind of closure must be handled here too
+			ExprKind::New(_)
+			| ExprKind::Literal(_)
+			| ExprKind::Range { .. }
+			| ExprKind::Reference(_)
+			| ExprKind::Intrinsic(_)
+			| ExprKind::InlineJs { .. }
+			| ExprKind::Call { .. }
+			| ExprKind::Unary { .. }
+			| ExprKind::Binary { .. }
+			| ExprKind::ArrayLiteral { .. }
+			| ExprKind::StructLiteral { .. }
+			| ExprKind::JsonMapLiteral { .. }
+			| ExprKind::MapLiteral { .. }
+			| ExprKind::SetLiteral { .. }
+			| ExprKind::JsonLiteral { .. }
+			| ExprKind::Error => fold::fold_expr(self, expr),
 		}
 	}
 }
@@ -357,9 +590,10 @@ impl<'a> Fold for RenameThisTransformer<'a> {
 					Reference::Identifier(ident)
 				}
 			}
-			Reference::InstanceMember { .. } | Reference::TypeMember { .. } | Reference::ElementAccess { .. } => {
-				fold::fold_reference(self, node)
-			}
+			Reference::InstanceMember { .. }
+			| Reference::TypeMember { .. }
+			| Reference::ElementAccess { .. }
+			| Reference::SuperMember { .. } => fold::fold_reference(self, node),
 		}
 	}
 }
@@ -3,6 +3,7 @@ use std::{
 	cell::RefCell,
 };
 
+use camino::Utf8Path;
 use strum::{Display, EnumString};
 
 use crate::diagnostic::{report_diagnostic, Diagnostic, DiagnosticSeverity, WingSpan};
@@ -21,6 +22,7 @@ pub enum CompilationPhase {
 	TypeChecking,
 	Lifting,
 	Jsifying,
+	Dtsifying,
 }
 
 pub struct CompilationContext {
@@ -83,6 +85,72 @@ pub fn set_custom_panic_hook() {
 			annotations: vec![],
 			hints: vec![],
 			severity: DiagnosticSeverity::Error,
+			code: None,
 		})
 	}));
 }
+
+/// Runs `phase` of the compilation of a single file, so a compiler bug in it doesn't take down the compilation of
+/// the rest of the project (which matters most for the LSP, where one broken file would otherwise break every feature).
+///
+/// If `f` panics, the panic (reported as a diagnostic by the custom panic hook) is caught, the file is marked with an
+/// error saying its diagnostics may be incomplete, and `None` is returned.
+///
+/// This only works in native builds. The compiler and the language server ship as wasm32-wasi, where panics abort
+/// the whole instance and can't be caught, so there `f` is simply called and a compiler bug still ends the
+/// compilation.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn catch_file_panic<R>(phase: CompilationPhase, file: &Utf8Path, f: impl FnOnce() -> R) -> Option<R> {
+	// Attribute panics to this file until the phase narrows them down to a span
+	CompilationContext::set(phase, &WingSpan::for_file(file.as_str()));
+
+	match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+		Ok(result) => Some(result),
+		Err(_) => {
+			report_diagnostic(Diagnostic {
+				message: format!(
+					"Compiler bug during {} of this file, its diagnostics may be incomplete",
+					phase
+				),
+				span: Some(WingSpan::for_file(file.as_str())),
+				annotations: vec![],
+				hints: vec![],
+				severity: DiagnosticSeverity::Error,
+				code: None,
+			});
+			None
+		}
+	}
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn catch_file_panic<R>(phase: CompilationPhase, file: &Utf8Path, f: impl FnOnce() -> R) -> Option<R> {
+	CompilationContext::set(phase, &WingSpan::for_file(file.as_str()));
+	Some(f())
+}
+
+#[cfg(test)]
+mod tests {
+	use camino::Utf8Path;
+
+	use super::{catch_file_panic, CompilationPhase};
+	use crate::diagnostic::{found_errors, get_diagnostics, reset_diagnostics};
+
+	#[test]
+	fn catches_panics_of_a_file() {
+		reset_diagnostics();
+		let phase = CompilationPhase::TypeChecking;
+
+		assert_eq!(catch_file_panic(phase, Utf8Path::new("ok.w"), || 42), Some(42));
+		assert!(!found_errors());
+
+		let broken: Option<()> = catch_file_panic(phase, Utf8Path::new("broken.w"), || panic!("oops"));
+		assert_eq!(broken, None);
+
+		let diagnostics = get_diagnostics();
+		let marker = diagnostics.last().expect("the broken file should be marked");
+		assert_eq!(marker.span.as_ref().unwrap().file_id, "broken.w");
+		assert!(marker.message.starts_with("Compiler bug during type-checking"));
+		reset_diagnostics();
+	}
+}
@@ -0,0 +1,157 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::dtsify::extern_dtsify::is_extern_file;
+use crate::file_graph::File;
+
+/// What a file or directory the compilation depended on is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CompilationInputKind {
+	WingFile,
+	/// A directory of Wing files brought as a whole (or the root of a Wing library), which changes when files are added
+	/// to it or removed from it
+	WingDirectory,
+	Extern,
+	WingToml,
+	PackageJson,
+	JsiiManifest,
+}
+
+/// A file or directory the compilation depended on. Changing it can change the output, so watch mode only has to
+/// watch these instead of the whole project.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CompilationInput {
+	pub path: String,
+	pub kind: CompilationInputKind,
+}
+
+/// Lists the inputs of a compilation, sorted by path:
+/// - the Wing files and directories that were parsed
+/// - the extern files that were brought
+/// - the `wing.toml` and `package.json` of the project and of every Wing library (if they exist)
+/// - the `package.json` and JSII manifest of every JSII module that was loaded
+pub fn compilation_inputs<'a>(
+	files: &[File],
+	externs: impl Iterator<Item = &'a Utf8PathBuf>,
+	library_roots: &IndexMap<String, Utf8PathBuf>,
+	jsii_manifest_files: &[Utf8PathBuf],
+) -> Vec<CompilationInput> {
+	let mut inputs = vec![];
+	let mut add = |path: &Utf8Path, kind| {
+		inputs.push(CompilationInput {
+			path: path.to_string(),
+			kind,
+		})
+	};
+
+	for file in files {
+		if file.path.is_dir() {
+			add(&file.path, CompilationInputKind::WingDirectory);
+		} else {
+			add(&file.path, CompilationInputKind::WingFile);
+		}
+	}
+	for extern_path in externs.filter(|path| is_extern_file(path)) {
+		add(extern_path, CompilationInputKind::Extern);
+	}
+	for root in library_roots.values() {
+		for (name, kind) in [
+			("wing.toml", CompilationInputKind::WingToml),
+			("package.json", CompilationInputKind::PackageJson),
+		] {
+			let path = root.join(name);
+			if path.exists() {
+				add(&path, kind);
+			}
+		}
+	}
+	for path in jsii_manifest_files {
+		if path.file_name() == Some("package.json") {
+			add(path, CompilationInputKind::PackageJson);
+		} else {
+			add(path, CompilationInputKind::JsiiManifest);
+		}
+	}
+
+	// A file is listed once even if it's reached in several ways (e.g. the `package.json` of a JSII Wing library)
+	inputs.sort();
+	inputs.dedup_by(|a, b| a.path == b.path);
+	inputs
+}
+
+#[cfg(test)]
+mod tests {
+	use camino::{Utf8Path, Utf8PathBuf};
+	use indexmap::IndexMap;
+
+	use super::{compilation_inputs, CompilationInputKind};
+	use crate::file_graph::File;
+
+	#[test]
+	fn lists_the_inputs_of_a_compilation() {
+		let project_dir = tempfile::tempdir().unwrap();
+		let project_dir = Utf8Path::from_path(project_dir.path()).unwrap();
+		let lib_dir = project_dir.join("node_modules/lib");
+		std::fs::create_dir_all(&lib_dir).unwrap();
+		for path in [
+			project_dir.join("main.w"),
+			project_dir.join("util.js"),
+			project_dir.join("wing.toml"),
+			lib_dir.join("package.json"),
+			lib_dir.join("lib.w"),
+		] {
+			std::fs::write(path, "").unwrap();
+		}
+		let jsii_dir = project_dir.join("node_modules/@winglang/sdk");
+
+		let files = [
+			File::new(&lib_dir.join("lib.w"), "lib".to_string()),
+			File::new(&lib_dir, "lib".to_string()),
+			File::new(&project_dir.join("main.w"), "rootpkg".to_string()),
+		];
+		let externs = [project_dir.join("util.js"), project_dir.join("main.w")];
+		let library_roots = IndexMap::from([
+			("rootpkg".to_string(), project_dir.to_owned()),
+			("lib".to_string(), lib_dir.clone()),
+		]);
+		let jsii_manifest_files = [jsii_dir.join("package.json"), jsii_dir.join(".jsii")];
+
+		let inputs = compilation_inputs(&files, externs.iter(), &library_roots, &jsii_manifest_files)
+			.into_iter()
+			.map(|input| {
+				let path = Utf8Path::new(&input.path).strip_prefix(project_dir).unwrap().to_owned();
+				(path, input.kind)
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(
+			inputs,
+			vec![
+				(Utf8PathBuf::from("main.w"), CompilationInputKind::WingFile),
+				(
+					Utf8PathBuf::from("node_modules/@winglang/sdk/.jsii"),
+					CompilationInputKind::JsiiManifest
+				),
+				(
+					Utf8PathBuf::from("node_modules/@winglang/sdk/package.json"),
+					CompilationInputKind::PackageJson
+				),
+				(
+					Utf8PathBuf::from("node_modules/lib"),
+					CompilationInputKind::WingDirectory
+				),
+				(
+					Utf8PathBuf::from("node_modules/lib/lib.w"),
+					CompilationInputKind::WingFile
+				),
+				(
+					Utf8PathBuf::from("node_modules/lib/package.json"),
+					CompilationInputKind::PackageJson
+				),
+				(Utf8PathBuf::from("util.js"), CompilationInputKind::Extern),
+				(Utf8PathBuf::from("wing.toml"), CompilationInputKind::WingToml),
+			]
+		);
+	}
+}
@@ -0,0 +1,235 @@
+use std::fs;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use serde_json::Value;
+
+/// Name of the environment variable with the version of the Wing compiler (e.g. "0.85.3"). It's unset (or "0.0.0")
+/// for development builds, which are assumed to be compatible with every library.
+pub const COMPILER_VERSION_ENV: &str = "WING_COMPILER_VERSION";
+
+/// The version of development builds of the compiler
+const DEV_VERSION: &str = "0.0.0";
+
+/// A library whose declared compiler version range doesn't include the current compiler
+#[derive(Debug)]
+pub struct IncompatibleLibrary {
+	/// The compiler version range the library declares (e.g. ">=0.80.0")
+	pub range: String,
+	/// The version of the current compiler
+	pub compiler_version: String,
+	/// The file the range was read from
+	pub declared_in: Utf8PathBuf,
+}
+
+/// Checks that the Wing library in `library_dir` supports the current compiler.
+///
+/// Libraries declare the compiler versions they support in the `engines.wing` field of their package.json, or in the
+/// `version` key of the `[compiler]` section of their wing.toml. Ranges use the npm syntax (e.g. ">=0.80.0 <1.0.0",
+/// "^0.85" or "0.84.x || 0.85.x"). Ranges we don't understand are assumed to be satisfied, so they never block a
+/// library from being used.
+pub fn check_library_compiler_version(library_dir: &Utf8Path) -> Result<(), IncompatibleLibrary> {
+	let Some(compiler_version) = std::env::var(COMPILER_VERSION_ENV)
+		.ok()
+		.filter(|version| version != DEV_VERSION)
+	else {
+		return Ok(());
+	};
+	let Some(version) = Version::parse(&compiler_version) else {
+		return Ok(());
+	};
+	let Some((range, declared_in)) = declared_compiler_range(library_dir) else {
+		return Ok(());
+	};
+
+	if range_satisfies(&range, &version) == Some(false) {
+		return Err(IncompatibleLibrary {
+			range,
+			compiler_version,
+			declared_in,
+		});
+	}
+	Ok(())
+}
+
+/// Returns the compiler version range a library declares, along with the file it was read from
+fn declared_compiler_range(library_dir: &Utf8Path) -> Option<(String, Utf8PathBuf)> {
+	let package_json_path = library_dir.join("package.json");
+	if let Some(range) = fs::read_to_string(&package_json_path)
+		.ok()
+		.and_then(|package_json| serde_json::from_str::<Value>(&package_json).ok())
+		.and_then(|package_json| package_json.get("engines")?.get("wing")?.as_str().map(str::to_string))
+	{
+		return Some((range, package_json_path));
+	}
+
+	let wing_toml_path = library_dir.join("wing.toml");
+	let wing_toml = fs::read_to_string(&wing_toml_path).ok()?;
+	let range = wing_toml_compiler_version(&wing_toml)?;
+	Some((range, wing_toml_path))
+}
+
+/// Reads the `version` key of the `[compiler]` section of a wing.toml file.
+/// The file is fully validated by the compiler's CLI, so we only need to find the key here.
+fn wing_toml_compiler_version(wing_toml: &str) -> Option<String> {
+	let mut in_compiler_section = false;
+	for line in wing_toml.lines() {
+		let line = line.trim();
+		if line.starts_with('[') {
+			in_compiler_section = line.trim_start_matches('[').trim_end_matches(']').trim() == "compiler";
+			continue;
+		}
+		if !in_compiler_section {
+			continue;
+		}
+		let Some((key, value)) = line.split_once('=') else {
+			continue;
+		};
+		if key.trim() != "version" {
+			continue;
+		}
+		// Drop trailing comments (ranges never contain '#')
+		let value = value.split('#').next()?.trim();
+		let unquoted = value
+			.strip_prefix('"')
+			.and_then(|v| v.strip_suffix('"'))
+			.or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))?;
+		return Some(unquoted.to_string());
+	}
+	None
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Version {
+	major: u64,
+	minor: u64,
+	patch: u64,
+}
+
+impl Version {
+	fn new(major: u64, minor: u64, patch: u64) -> Self {
+		Self { major, minor, patch }
+	}
+
+	/// Parses versions like "0.85.3", "v1.2.0" or "1.0.0-rc.1" (pre-release tags are ignored)
+	fn parse(version: &str) -> Option<Self> {
+		let (major, minor, patch) = parse_partial(version)?;
+		Some(Self {
+			major: major?,
+			minor: minor?,
+			patch: patch?,
+		})
+	}
+}
+
+/// Parses a possibly partial version like "1", "1.2", "1.x" or "1.2.3". Missing and wildcard parts are `None`.
+fn parse_partial(version: &str) -> Option<(Option<u64>, Option<u64>, Option<u64>)> {
+	let version = version.trim();
+	let version = version.strip_prefix('v').unwrap_or(version);
+	let version = version.split(['-', '+']).next()?;
+	let mut parts = version.split('.').map(|part| match part {
+		"x" | "X" | "*" => Ok(None),
+		_ => part.parse::<u64>().map(Some),
+	});
+	let major = parts.next().unwrap_or(Ok(None)).ok()?;
+	let minor = parts.next().unwrap_or(Ok(None)).ok()?;
+	let patch = parts.next().unwrap_or(Ok(None)).ok()?;
+	if parts.next().is_some() {
+		return None;
+	}
+	Some((major, minor, patch))
+}
+
+/// Checks whether an npm style version range includes a version, or returns `None` if the range can't be parsed
+fn range_satisfies(range: &str, version: &Version) -> Option<bool> {
+	let mut satisfied = false;
+	for alternative in range.split("||") {
+		let mut all = true;
+		for comparator in alternative.split_whitespace() {
+			all &= comparator_satisfies(comparator, version)?;
+		}
+		satisfied |= all;
+	}
+	Some(satisfied)
+}
+
+fn comparator_satisfies(comparator: &str, version: &Version) -> Option<bool> {
+	let (operator, partial) = ["<=", ">=", "<", ">", "=", "^", "~"]
+		.into_iter()
+		.find_map(|op| comparator.strip_prefix(op).map(|rest| (op, rest)))
+		.unwrap_or(("", comparator));
+	if partial.is_empty() || partial == "*" || partial == "x" {
+		return Some(true);
+	}
+	let (major, minor, patch) = parse_partial(partial)?;
+	let Some(major) = major else {
+		return Some(true);
+	};
+	let lower = Version {
+		major,
+		minor: minor.unwrap_or(0),
+		patch: patch.unwrap_or(0),
+	};
+	let next_major = Version::new(major + 1, 0, 0);
+	let next_minor = Version::new(major, lower.minor + 1, 0);
+	// The first version that's no longer matched when the missing parts are wildcards (e.g. "1.2" is "<1.3.0")
+	let wildcard_upper = match (minor, patch) {
+		(None, _) => next_major,
+		(Some(_), None) => next_minor,
+		(Some(_), Some(_)) => Version::new(major, lower.minor, lower.patch + 1),
+	};
+
+	Some(match operator {
+		">=" => *version >= lower,
+		">" => *version >= wildcard_upper,
+		"<" => *version < lower,
+		"<=" => *version < wildcard_upper,
+		"" | "=" => *version >= lower && *version < wildcard_upper,
+		// "~" allows patch level changes if a minor version is given, and minor level changes otherwise
+		"~" => *version >= lower && *version < if minor.is_some() { next_minor } else { next_major },
+		// "^" allows changes that don't modify the left-most non-zero part
+		"^" => {
+			let upper = if major > 0 || minor.is_none() {
+				next_major
+			} else if lower.minor > 0 || patch.is_none() {
+				next_minor
+			} else {
+				Version::new(0, 0, lower.patch + 1)
+			};
+			*version >= lower && *version < upper
+		}
+		_ => return None,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{range_satisfies, wing_toml_compiler_version, Version};
+
+	fn satisfies(range: &str, version: &str) -> Option<bool> {
+		range_satisfies(range, &Version::parse(version).unwrap())
+	}
+
+	#[test]
+	fn matches_npm_ranges() {
+		assert_eq!(satisfies("*", "0.85.3"), Some(true));
+		assert_eq!(satisfies(">=0.80.0", "0.85.3"), Some(true));
+		assert_eq!(satisfies(">=0.86", "0.85.3"), Some(false));
+		assert_eq!(satisfies(">=0.80.0 <0.85.0", "0.85.3"), Some(false));
+		assert_eq!(satisfies("^0.85.1", "0.85.3"), Some(true));
+		assert_eq!(satisfies("^0.84", "0.85.3"), Some(false));
+		assert_eq!(satisfies("^1.2.0", "1.9.0"), Some(true));
+		assert_eq!(satisfies("~0.85.4", "0.85.3"), Some(false));
+		assert_eq!(satisfies("~0.85", "0.85.3"), Some(true));
+		assert_eq!(satisfies("0.84.x || 0.85.x", "0.85.3"), Some(true));
+		assert_eq!(satisfies("0.84", "0.85.3"), Some(false));
+		assert_eq!(satisfies("<=0.85", "0.85.3"), Some(true));
+		assert_eq!(satisfies("latest", "0.85.3"), None);
+	}
+
+	#[test]
+	fn reads_the_version_from_wing_toml() {
+		let wing_toml = "[tf-aws]\nversion = \"1\"\n\n[compiler]\nstrict = true\nversion = \">=0.80.0\" # tested on 0.80\n";
+		assert_eq!(wing_toml_compiler_version(wing_toml), Some(">=0.80.0".to_string()));
+		assert_eq!(wing_toml_compiler_version("[compiler]\nstrict = true\n"), None);
+	}
+}
@@ -0,0 +1,230 @@
+//! Metadata about the construct tree of a program: the preflight classes it declares and the objects it creates, with
+//! their source spans, doc comments and lifts. The Wing Console reads it to show where each construct in the tree comes
+//! from, which the synthesized tree itself can't tell since it's only built when the program runs.
+
+use std::collections::HashMap;
+
+use camino::Utf8PathBuf;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::ast::{Class, Expr, ExprId, ExprKind, Literal, Phase, Scope, Stmt, StmtKind};
+use crate::diagnostic::WingSpan;
+use crate::type_check::{lifts::Lifts, SymbolKind, Types};
+use crate::visit::{self, Visit};
+use crate::visit_context::VisitContext;
+
+/// Emitted next to the preflight entrypoint
+pub const CONSTRUCT_TREE_METADATA_FILE_NAME: &str = "tree.meta.json";
+
+#[derive(Serialize)]
+struct ConstructTreeMetadata {
+	classes: Vec<ClassMetadata>,
+	constructs: Vec<ConstructMetadata>,
+}
+
+/// A preflight class declaration
+#[derive(Serialize)]
+struct ClassMetadata {
+	name: String,
+	span: WingSpan,
+	doc: Option<String>,
+	lifts: Vec<LiftEdge>,
+}
+
+/// A preflight `new` expression
+#[derive(Serialize)]
+struct ConstructMetadata {
+	/// The name of the class being instantiated
+	class: String,
+	/// The fully qualified name of the class, if it isn't declared in Wing code
+	fqn: Option<String>,
+	/// The construct id, unless it's only known when the program runs
+	id: Option<String>,
+	span: WingSpan,
+	/// The doc comment of the statement the object is created by
+	doc: Option<String>,
+	/// The lifts of the object's class
+	lifts: Vec<LiftEdge>,
+}
+
+/// A preflight object (or type) a method of a class lifts, and the operations the method performs on it
+#[derive(Serialize)]
+struct LiftEdge {
+	method: String,
+	/// The preflight code of the lifted object
+	target: String,
+	ops: Vec<String>,
+}
+
+/// The construct tree metadata of a type checked and lifted program, as JSON
+pub fn construct_tree_metadata_json(types: &Types, asts: &IndexMap<Utf8PathBuf, Scope>) -> String {
+	let mut collector = ConstructTreeCollector {
+		types,
+		ctx: VisitContext::new(),
+		docs: HashMap::new(),
+		metadata: ConstructTreeMetadata {
+			classes: vec![],
+			constructs: vec![],
+		},
+	};
+	for scope in asts.values() {
+		collector.visit_scope(scope);
+	}
+	serde_json::to_string_pretty(&collector.metadata).expect("serialize construct tree metadata")
+}
+
+struct ConstructTreeCollector<'a> {
+	types: &'a Types,
+	ctx: VisitContext,
+	/// The doc comments of statements, by the id of the expression that's their value
+	docs: HashMap<ExprId, String>,
+	metadata: ConstructTreeMetadata,
+}
+
+impl ConstructTreeCollector<'_> {
+	fn lift_edges(lifts: Option<&Lifts>) -> Vec<LiftEdge> {
+		let Some(lifts) = lifts else {
+			return vec![];
+		};
+		lifts
+			.lifts_qualifications
+			.iter()
+			.flat_map(|(method, qualifications)| {
+				qualifications.iter().map(|(target, qualification)| LiftEdge {
+					method: method.clone(),
+					target: target.clone(),
+					ops: qualification.ops.iter().cloned().collect(),
+				})
+			})
+			.collect()
+	}
+
+	fn add_class(&mut self, class: &Class, doc: Option<&String>) {
+		if class.phase != Phase::Preflight {
+			return;
+		}
+		let Some(env) = self.ctx.current_env() else {
+			return;
+		};
+		let class_type = match env.lookup(&class.name, None) {
+			Some(SymbolKind::Type(t)) => t.as_class(),
+			_ => None,
+		};
+		self.metadata.classes.push(ClassMetadata {
+			name: class.name.name.clone(),
+			span: class.span.clone(),
+			doc: doc.cloned(),
+			lifts: Self::lift_edges(class_type.and_then(|c| c.lifts.as_ref())),
+		});
+	}
+
+	fn add_construct(&mut self, expr: &Expr) {
+		let ExprKind::New(new) = &expr.kind else {
+			return;
+		};
+		let expr_type = self.types.get_expr_type(expr);
+		if !expr_type.is_preflight_class() {
+			return;
+		}
+		let Some(class) = expr_type.as_class() else {
+			return;
+		};
+		// Like the generated code, the id defaults to the name of the class
+		let id = match &new.obj_id {
+			_ if !class.std_construct_args => None,
+			None => Some(class.name.name.clone()),
+			Some(obj_id) => match &obj_id.kind {
+				ExprKind::Literal(Literal::String(s)) => serde_json::from_str(&s.replace("\\{", "{")).ok(),
+				_ => None,
+			},
+		};
+		self.metadata.constructs.push(ConstructMetadata {
+			class: class.name.name.clone(),
+			fqn: class.fqn.clone(),
+			id,
+			span: expr.span.clone(),
+			doc: self.docs.get(&expr.id).cloned(),
+			lifts: Self::lift_edges(class.lifts.as_ref()),
+		});
+	}
+}
+
+impl<'a> Visit<'a> for ConstructTreeCollector<'a> {
+	fn visit_scope(&mut self, node: &'a Scope) {
+		self.ctx.push_env(self.types.get_scope_env(node));
+		visit::visit_scope(self, node);
+		self.ctx.pop_env();
+	}
+
+	fn visit_stmt(&mut self, node: &'a Stmt) {
+		if let Some(doc) = &node.doc {
+			match &node.kind {
+				StmtKind::Let { initial_value, .. } => {
+					self.docs.insert(initial_value.id, doc.clone());
+				}
+				StmtKind::Expression(expr) => {
+					self.docs.insert(expr.id, doc.clone());
+				}
+				_ => {}
+			}
+		}
+		if let StmtKind::Class(class) = &node.kind {
+			self.add_class(class, node.doc.as_ref());
+		}
+		visit::visit_stmt(self, node);
+	}
+
+	fn visit_expr(&mut self, node: &'a Expr) {
+		self.add_construct(node);
+		visit::visit_expr(self, node);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::CONSTRUCT_TREE_METADATA_FILE_NAME;
+	use crate::test_utils::compile_source;
+
+	#[test]
+	fn maps_classes_and_constructs_to_their_source() {
+		let source = r#"
+bring cloud;
+
+/// Stores the uploads
+class Store {
+  bucket: cloud.Bucket;
+  new() {
+    this.bucket = new cloud.Bucket();
+  }
+  pub inflight put(key: str) {
+    this.bucket.put(key, "");
+  }
+}
+
+/// The main store
+let store = new Store() as "main store";
+"#;
+		let metadata = compile_source(source).read_json(CONSTRUCT_TREE_METADATA_FILE_NAME);
+
+		let store_class = &metadata["classes"][0];
+		assert_eq!(store_class["name"], "Store");
+		assert_eq!(store_class["doc"], "Stores the uploads");
+		assert_eq!(store_class["span"]["start"]["line"], 4);
+		assert!(store_class["lifts"]
+			.as_array()
+			.unwrap()
+			.iter()
+			.any(|lift| lift["method"] == "put" && lift["target"] == "this.bucket" && lift["ops"][0] == "put"));
+
+		let constructs = metadata["constructs"].as_array().unwrap();
+		let bucket = constructs.iter().find(|c| c["class"] == "Bucket").unwrap();
+		assert_eq!(bucket["id"], "Bucket");
+		assert_eq!(bucket["fqn"], "@winglang/sdk.cloud.Bucket");
+		let store = constructs.iter().find(|c| c["class"] == "Store").unwrap();
+		assert_eq!(store["id"], "main store");
+		assert_eq!(store["doc"], "The main store");
+		assert_eq!(store["span"]["start"]["line"], 15);
+		assert_eq!(store["lifts"], store_class["lifts"]);
+	}
+}
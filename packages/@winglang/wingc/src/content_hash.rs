@@ -0,0 +1,30 @@
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// A 64-bit FNV-1a hash of the content.
+///
+/// Unlike `std::collections::hash_map::DefaultHasher`, the result is guaranteed to be the same across compiler
+/// versions and platforms, so it can be used to derive identifiers in generated code.
+pub fn content_hash(content: &str) -> u64 {
+	content.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+		(hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+	})
+}
+
+/// A short hex string derived from the hash of the content, for use in generated names
+pub fn short_content_hash(content: &str) -> String {
+	let hash = content_hash(content);
+	format!("{:08x}", (hash ^ (hash >> 32)) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn content_hash_is_fnv1a() {
+		assert_eq!(content_hash(""), 0xcbf29ce484222325);
+		assert_eq!(content_hash("a"), 0xaf63dc4c8601ec8c);
+		assert_eq!(short_content_hash("a"), "296230c0");
+	}
+}
@@ -8,6 +8,8 @@
 //   "DEBUG=foo:*" to enable all namespaces starting with "foo"
 //   "DEBUG=*,-bar" to enable all namespaces except "bar"
 
+pub mod ast_validator;
+
 extern crate globset;
 extern crate lazy_static;
 use globset::{Glob, GlobMatcher};
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use camino::Utf8Path;
+
+use crate::{
+	ast::{ArgList, ArgListId, Expr, ExprId, ExprKind, Reference, Scope, ScopeId, Stmt},
+	diagnostic::WingSpan,
+	files::Files,
+	type_check::Types,
+	visit::{self, Visit},
+};
+
+/// Set this environment variable to "true" to validate the AST after every phase that transforms it.
+/// Validation always runs in unit tests.
+pub const VALIDATE_AST_ENV: &str = "WING_VALIDATE_AST";
+
+pub fn is_ast_validation_enabled() -> bool {
+	cfg!(test) || std::env::var(VALIDATE_AST_ENV).is_ok_and(|v| v == "true")
+}
+
+/// Validates the structural invariants of an AST after the phase named `after` (e.g. "parsing"), if validation is
+/// enabled. Panics with a description of the first violation found.
+///
+/// Pass `types` once the AST was type checked, to also verify that all references to types were transformed by
+/// `TypeReferenceTransformer`.
+pub fn validate_ast(scope: &Scope, files: &Files, types: Option<&Types>, after: &str) {
+	if !is_ast_validation_enabled() {
+		return;
+	}
+
+	let mut validator = AstValidator {
+		files,
+		types,
+		after,
+		expr_ids: HashMap::new(),
+		scope_ids: HashMap::new(),
+		arg_list_ids: HashMap::new(),
+		scope_spans: vec![],
+	};
+	validator.visit_scope(scope);
+}
+
+/// Checks that:
+/// 1. The ids of all expressions, scopes and argument lists are unique.
+/// 2. All spans are within the bounds of their file.
+/// 3. Statement indices are consistent with the order of the statements in their scope, and scopes and statements
+///    are within the span of the scope they're nested in.
+/// 4. No instance member references to types are left (when `types` is given).
+///
+/// Spans that weren't created from the source (like the ones of desugared code) are only checked against their file's
+/// bounds, and only if they point to a file.
+struct AstValidator<'a> {
+	files: &'a Files,
+	types: Option<&'a Types>,
+	after: &'a str,
+	expr_ids: HashMap<ExprId, WingSpan>,
+	scope_ids: HashMap<ScopeId, WingSpan>,
+	arg_list_ids: HashMap<ArgListId, WingSpan>,
+	/// The spans of the scopes we're currently in, innermost last
+	scope_spans: Vec<WingSpan>,
+}
+
+impl AstValidator<'_> {
+	fn fail(&self, message: String) -> ! {
+		fail(self.after, message);
+	}
+
+	fn check_span(&self, kind: &str, span: &WingSpan) {
+		if span.file_id.is_empty() {
+			return;
+		}
+		let Some(text) = self.files.get_file(Utf8Path::new(&span.file_id)) else {
+			self.fail(format!("{} at {} points to an unknown file", kind, describe_span(span)));
+		};
+		if span.start > span.end || span.start_offset > span.end_offset {
+			self.fail(format!("{} at {} ends before it starts", kind, describe_span(span)));
+		}
+		if span.end_offset > text.len() {
+			self.fail(format!(
+				"{} at {} ends at byte {}, past the end of the file ({} bytes)",
+				kind,
+				describe_span(span),
+				span.end_offset,
+				text.len()
+			));
+		}
+	}
+
+	/// Checks that a node of a scope is within the span of the innermost scope we're in
+	fn check_nested(&self, kind: &str, span: &WingSpan) {
+		let Some(parent) = self.scope_spans.last() else {
+			return;
+		};
+		// Empty spans are usually made up for desugared code, so they can't be compared with real ones
+		if span.file_id != parent.file_id
+			|| span.start_offset == span.end_offset
+			|| parent.start_offset == parent.end_offset
+		{
+			return;
+		}
+		if span.start < parent.start || span.end > parent.end {
+			self.fail(format!(
+				"{} at {} is outside of the scope it's in, at {}",
+				kind,
+				describe_span(span),
+				describe_span(parent)
+			));
+		}
+	}
+}
+
+impl<'a> Visit<'a> for AstValidator<'_> {
+	fn visit_scope(&mut self, node: &'a Scope) {
+		check_unique_id(self.after, &mut self.scope_ids, "Scope", node.id, &node.span);
+		self.check_span("Scope", &node.span);
+		self.check_nested("Scope", &node.span);
+
+		// Statements inserted while desugaring share the index of the statement they were inserted before, so indices
+		// can repeat but must never decrease or be ahead of the statement's position
+		let mut prev_idx = 0;
+		for (position, stmt) in node.statements.iter().enumerate() {
+			if stmt.idx < prev_idx || stmt.idx > position {
+				self.fail(format!(
+					"Statement at {} has index {}, but it's statement {} of its scope and follows a statement with index {}",
+					describe_span(&stmt.span),
+					stmt.idx,
+					position,
+					prev_idx
+				));
+			}
+			prev_idx = stmt.idx;
+		}
+
+		self.scope_spans.push(node.span.clone());
+		visit::visit_scope(self, node);
+		self.scope_spans.pop();
+	}
+
+	fn visit_stmt(&mut self, node: &'a Stmt) {
+		self.check_span("Statement", &node.span);
+		self.check_nested("Statement", &node.span);
+		visit::visit_stmt(self, node);
+	}
+
+	fn visit_expr(&mut self, node: &'a Expr) {
+		check_unique_id(self.after, &mut self.expr_ids, "Expression", node.id, &node.span);
+		self.check_span("Expression", &node.span);
+
+		if let (Some(types), ExprKind::Reference(Reference::InstanceMember { object, property, .. })) =
+			(self.types, &node.kind)
+		{
+			if types.is_type_expression(object.id) {
+				self.fail(format!(
+					"Reference to {} at {} refers to a type but wasn't transformed into a type member",
+					property.name,
+					describe_span(&node.span)
+				));
+			}
+		}
+
+		visit::visit_expr(self, node);
+	}
+
+	fn visit_args(&mut self, node: &'a ArgList) {
+		check_unique_id(self.after, &mut self.arg_list_ids, "Argument list", node.id, &node.span);
+		self.check_span("Argument list", &node.span);
+		visit::visit_args(self, node);
+	}
+}
+
+fn fail(after: &str, message: String) -> ! {
+	panic!("Invalid AST after {}: {}", after, message);
+}
+
+fn check_unique_id(after: &str, ids: &mut HashMap<usize, WingSpan>, kind: &str, id: usize, span: &WingSpan) {
+	if let Some(first) = ids.insert(id, span.clone()) {
+		fail(
+			after,
+			format!(
+				"{} id {} is used both at {} and at {}",
+				kind,
+				id,
+				describe_span(&first),
+				describe_span(span)
+			),
+		);
+	}
+}
+
+fn describe_span(span: &WingSpan) -> String {
+	if span.file_id.is_empty() {
+		"<no location>".to_string()
+	} else {
+		format!(
+			"{}:{}:{}-{}:{}",
+			span.file_id,
+			span.start.line + 1,
+			span.start.col + 1,
+			span.end.line + 1,
+			span.end.col + 1
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::validate_ast;
+	use crate::{
+		ast::{AstContext, Expr, ExprKind, Literal, Scope, Stmt, StmtKind},
+		diagnostic::WingSpan,
+		files::Files,
+	};
+
+	fn number_stmt(ctx: &AstContext, idx: usize) -> Stmt {
+		Stmt {
+			kind: StmtKind::Expression(Expr::new(
+				ctx,
+				ExprKind::Literal(Literal::Number(1.0)),
+				WingSpan::default(),
+			)),
+			span: WingSpan::default(),
+			idx,
+			doc: None,
+		}
+	}
+
+	#[test]
+	fn valid_ast_passes() {
+		let ctx = AstContext::new();
+		let scope = Scope::new(
+			&ctx,
+			vec![number_stmt(&ctx, 0), number_stmt(&ctx, 0), number_stmt(&ctx, 1)],
+			WingSpan::default(),
+		);
+		validate_ast(&scope, &Files::new(), None, "testing");
+	}
+
+	#[test]
+	#[should_panic(expected = "Invalid AST after testing: Expression id 0 is used both")]
+	fn duplicate_expr_ids_are_detected() {
+		// Expressions allocated by different contexts get the same ids
+		let ctx = AstContext::new();
+		let other_ctx = AstContext::new();
+		let scope = Scope::new(
+			&ctx,
+			vec![number_stmt(&ctx, 0), number_stmt(&other_ctx, 1)],
+			WingSpan::default(),
+		);
+		validate_ast(&scope, &Files::new(), None, "testing");
+	}
+
+	#[test]
+	#[should_panic(expected = "has index 0, but it's statement 2 of its scope and follows a statement with index 1")]
+	fn decreasing_stmt_indices_are_detected() {
+		let ctx = AstContext::new();
+		let scope = Scope::new(
+			&ctx,
+			vec![number_stmt(&ctx, 0), number_stmt(&ctx, 1), number_stmt(&ctx, 0)],
+			WingSpan::default(),
+		);
+		validate_ast(&scope, &Files::new(), None, "testing");
+	}
+}
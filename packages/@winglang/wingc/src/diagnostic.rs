@@ -1,6 +1,10 @@
 use camino::Utf8Path;
 use colored::Colorize;
-use std::{cell::RefCell, fmt::Display};
+use std::{
+	cell::{OnceCell, RefCell},
+	collections::{BTreeMap, HashMap, HashSet},
+	fmt::{Debug, Display},
+};
 use tree_sitter::Point;
 
 use lsp_types::{Position, Range};
@@ -8,13 +12,17 @@ use lsp_types::{Position, Range};
 use serde::Serialize;
 
 use crate::ast::Spanned;
+use crate::events::{emit_event, CompilerEvent};
+use crate::messages::{Message, MORE_DIAGNOSTICS_IN_FILE};
 
 pub type FileId = String;
-type Diagnostics = Vec<Diagnostic>;
 pub type DiagnosticResult<T> = Result<T, ()>;
 
-// error constant
-pub const ERR_EXPECTED_SEMICOLON: &str = "Expected ';'";
+/// Environment variable setting how many diagnostics are reported per file before the rest are only counted
+/// (0 reports all of them)
+pub const MAX_DIAGNOSTICS_PER_FILE_ENV: &str = "WING_MAX_ERRORS_PER_FILE";
+/// How many diagnostics are reported per file unless `MAX_DIAGNOSTICS_PER_FILE_ENV` says otherwise
+const DEFAULT_MAX_DIAGNOSTICS_PER_FILE: usize = 100;
 
 /// Line and character location in a UTF8 Wing source file
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize)]
@@ -86,7 +94,7 @@ impl Display for WingLocation {
 }
 
 /// A span of text in a Wing source file
-#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Serialize)]
+#[derive(Default, PartialEq, Eq, Hash, Clone, Serialize)]
 pub struct WingSpan {
 	pub start: WingLocation,
 	pub end: WingLocation,
@@ -259,6 +267,21 @@ impl Display for WingSpan {
 	}
 }
 
+// Spans are part of almost every AST node, so keep them short when debug printing the AST
+impl Debug for WingSpan {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{}:{}:{}-{}:{}",
+			Utf8Path::new(&self.file_id).file_name().unwrap_or("<unknown>"),
+			self.start.line + 1,
+			self.start.col + 1,
+			self.end.line + 1,
+			self.end.col + 1
+		)
+	}
+}
+
 impl Ord for WingSpan {
 	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
 		self
@@ -282,6 +305,10 @@ pub struct Diagnostic {
 	pub span: Option<WingSpan>,
 	pub hints: Vec<String>,
 	pub severity: DiagnosticSeverity,
+	/// The code of the message in the message catalog, if it has one (see `messages`). Tools should identify
+	/// diagnostics by their code, since their message may be translated.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code: Option<&'static str>,
 }
 
 impl Diagnostic {
@@ -292,6 +319,7 @@ impl Diagnostic {
 			annotations: vec![],
 			hints: vec![],
 			severity: DiagnosticSeverity::Error,
+			code: None,
 		}
 	}
 
@@ -323,6 +351,14 @@ impl Diagnostic {
 		self
 	}
 
+	/// A diagnostic with the given message of the message catalog
+	pub fn from_message(message: Message, args: &[(&str, &dyn Display)], span: &impl Spanned) -> Self {
+		Self {
+			code: Some(message.code),
+			..Self::new(message.format(args), span)
+		}
+	}
+
 	pub fn report(self) {
 		report_diagnostic(self);
 	}
@@ -387,21 +423,118 @@ impl PartialOrd for Diagnostic {
 	}
 }
 
+/// The diagnostics reported during a compilation, and what's needed to keep reporting one cheap even when a file has
+/// thousands of errors (e.g. a broken generated file)
+#[derive(Default)]
+struct Diagnostics {
+	list: Vec<Diagnostic>,
+	/// The diagnostics that were reported, including the ones left out of `list` for being over the per-file budget
+	seen: HashSet<Diagnostic>,
+	found_errors: bool,
+	/// How many diagnostics each file has in `list`
+	per_file: HashMap<FileId, usize>,
+	/// How many diagnostics (and whether any of them are errors) were left out of `list` for each file
+	suppressed: BTreeMap<FileId, (usize, bool)>,
+	max_per_file: OnceCell<Option<usize>>,
+}
+
+impl Diagnostics {
+	/// Adds a diagnostic, unless the same one was already added. Returns whether the diagnostic should be reported to
+	/// the host, which it isn't if it's a duplicate or over the budget of its file.
+	fn add(&mut self, diagnostic: &Diagnostic) -> bool {
+		if self.seen.contains(diagnostic) {
+			return false;
+		}
+		self.seen.insert(diagnostic.clone());
+		let is_error = diagnostic.severity == DiagnosticSeverity::Error;
+		self.found_errors |= is_error;
+
+		if let Some(file_id) = diagnostic.span.as_ref().map(|span| &span.file_id) {
+			let max_per_file = *self.max_per_file.get_or_init(max_diagnostics_per_file_from_env);
+			let count = self.per_file.entry(file_id.clone()).or_default();
+			if max_per_file.is_some_and(|max| *count >= max) {
+				let (suppressed, has_errors) = self.suppressed.entry(file_id.clone()).or_default();
+				*suppressed += 1;
+				*has_errors |= is_error;
+				return false;
+			}
+			*count += 1;
+		}
+
+		self.list.push(diagnostic.clone());
+		true
+	}
+}
+
+fn max_diagnostics_per_file_from_env() -> Option<usize> {
+	match std::env::var(MAX_DIAGNOSTICS_PER_FILE_ENV).map(|value| value.parse::<usize>()) {
+		Ok(Ok(0)) => None,
+		Ok(Ok(max)) => Some(max),
+		_ => Some(DEFAULT_MAX_DIAGNOSTICS_PER_FILE),
+	}
+}
+
 thread_local! {
-	static DIAGNOSTICS: RefCell<Diagnostics> = RefCell::new(Diagnostics::new());
+	static DIAGNOSTICS: RefCell<Diagnostics> = RefCell::new(Diagnostics::default());
 }
 
 /// Report a compilation diagnostic
 pub fn report_diagnostic(diagnostic: Diagnostic) {
-	// Add the diagnostic to the list of diagnostics
+	// Add the diagnostic to the list of diagnostics, unless the exact same one was already reported
+	// (e.g. when an expression is checked more than once), or its file already has too many of them
+	let is_new = DIAGNOSTICS.with(|diagnostics| diagnostics.borrow_mut().add(&diagnostic));
+	if !is_new {
+		return;
+	}
+	send_diagnostic_to_host(&diagnostic);
+}
+
+/// Overrides how many diagnostics are reported per file (`None` reports all of them)
+pub fn set_max_diagnostics_per_file(max: Option<usize>) {
 	DIAGNOSTICS.with(|diagnostics| {
-		diagnostics.borrow_mut().push(diagnostic.clone());
+		diagnostics.borrow_mut().max_per_file = OnceCell::from(max);
+	})
+}
+
+/// Reports how many diagnostics were left out of each file that had more than its budget of them (e.g. "and 37 more
+/// errors in this file"). Should be called once the compilation is done.
+pub fn report_suppressed_diagnostics() {
+	let summaries = DIAGNOSTICS.with(|diagnostics| {
+		let mut diagnostics = diagnostics.borrow_mut();
+		let summaries = std::mem::take(&mut diagnostics.suppressed)
+			.into_iter()
+			.map(|(file_id, (count, has_errors))| {
+				let kind = if has_errors { "error" } else { "warning" };
+				let diagnostics = format!("{kind}{}", if count == 1 { "" } else { "s" });
+				Diagnostic {
+					message: MORE_DIAGNOSTICS_IN_FILE.format(&[("count", &count), ("diagnostics", &diagnostics)]),
+					span: Some(WingSpan::for_file(file_id)),
+					annotations: vec![],
+					hints: vec![format!("set {MAX_DIAGNOSTICS_PER_FILE_ENV}=0 to report all of them")],
+					severity: if has_errors {
+						DiagnosticSeverity::Error
+					} else {
+						DiagnosticSeverity::Warning
+					},
+					code: Some(MORE_DIAGNOSTICS_IN_FILE.code),
+				}
+			})
+			.collect::<Vec<_>>();
+		diagnostics.list.extend(summaries.iter().cloned());
+		summaries
 	});
+	for summary in &summaries {
+		send_diagnostic_to_host(summary);
+	}
+}
+
+fn send_diagnostic_to_host(diagnostic: &Diagnostic) {
+	emit_event(CompilerEvent::DiagnosticReported { diagnostic });
 
 	// If we're running in wasm32 then send the diagnostic to the client
 	#[cfg(target_arch = "wasm32")]
 	{
-		match serde_json::to_string(&diagnostic) {
+		match serde_json::to_string(diagnostic) {
 			Ok(json) => {
 				let bytes = json.as_bytes();
 				unsafe {
@@ -423,10 +556,7 @@ extern "C" {
 
 /// Returns whether any errors were found during compilation
 pub fn found_errors() -> bool {
-	DIAGNOSTICS.with(|diagnostics| {
-		let diagnostics = diagnostics.borrow();
-		diagnostics.iter().any(|d| d.severity == DiagnosticSeverity::Error)
-	})
+	DIAGNOSTICS.with(|diagnostics| diagnostics.borrow().found_errors)
 }
 
 #[cfg(test)]
@@ -435,6 +565,7 @@ pub fn assert_no_panics() {
 	let panics = DIAGNOSTICS.with(|diagnostics| {
 		let diagnostics = diagnostics.borrow();
 		diagnostics
+			.list
 			.iter()
 			.filter(|d| d.message.starts_with("Compiler bug"))
 			.cloned()
@@ -446,10 +577,7 @@ pub fn assert_no_panics() {
 
 /// Returns the list of diagnostics
 pub fn get_diagnostics() -> Vec<Diagnostic> {
-	DIAGNOSTICS.with(|diagnostics| {
-		let diagnostics = diagnostics.borrow();
-		diagnostics.clone()
-	})
+	DIAGNOSTICS.with(|diagnostics| diagnostics.borrow().list.clone())
 }
 
 /// Reset diagnostics, this is useful if we perform more than one compilation
@@ -457,7 +585,12 @@ pub fn get_diagnostics() -> Vec<Diagnostic> {
 pub fn reset_diagnostics() {
 	DIAGNOSTICS.with(|diagnostics| {
 		let mut diagnostics = diagnostics.borrow_mut();
-		diagnostics.clear();
+		// The budget set by `set_max_diagnostics_per_file` (or read from the environment) outlives the compilation
+		let max_per_file = std::mem::take(&mut diagnostics.max_per_file);
+		*diagnostics = Diagnostics {
+			max_per_file,
+			..Diagnostics::default()
+		};
 	})
 }
 
@@ -467,6 +600,8 @@ pub struct TypeError {
 	pub span: WingSpan,
 	pub annotations: Vec<DiagnosticAnnotation>,
 	pub hints: Vec<String>,
+	/// The code of the message in the message catalog, if it has one
+	pub code: Option<&'static str>,
 }
 
 impl std::fmt::Display for TypeError {
@@ -479,6 +614,72 @@ impl std::fmt::Display for TypeError {
 mod tests {
 	use super::*;
 
+	#[test]
+	fn identical_diagnostics_are_reported_once() {
+		reset_diagnostics();
+		let diagnostic = Diagnostic {
+			message: "oops".to_string(),
+			span: Some(WingSpan::for_file("test")),
+			annotations: vec![],
+			hints: vec![],
+			severity: DiagnosticSeverity::Error,
+			code: None,
+		};
+		report_diagnostic(diagnostic.clone());
+		report_diagnostic(diagnostic.clone());
+		report_diagnostic(Diagnostic {
+			severity: DiagnosticSeverity::Warning,
+			code: None,
+			..diagnostic
+		});
+
+		assert_eq!(get_diagnostics().len(), 2);
+		reset_diagnostics();
+	}
+
+	#[test]
+	fn diagnostics_over_the_file_budget_are_summarized() {
+		reset_diagnostics();
+		set_max_diagnostics_per_file(Some(2));
+		for index in 0..5 {
+			report_diagnostic(Diagnostic {
+				message: format!("error {index}"),
+				span: Some(WingSpan::for_file("big.w")),
+				annotations: vec![],
+				hints: vec![],
+				severity: DiagnosticSeverity::Error,
+				code: None,
+			});
+		}
+		report_diagnostic(Diagnostic {
+			message: "error".to_string(),
+			span: Some(WingSpan::for_file("small.w")),
+			annotations: vec![],
+			hints: vec![],
+			severity: DiagnosticSeverity::Error,
+			code: None,
+		});
+		report_suppressed_diagnostics();
+
+		let messages = get_diagnostics()
+			.into_iter()
+			.map(|d| format!("{}: {}", d.span.unwrap().file_id, d.message))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			messages,
+			vec![
+				"big.w: error 0",
+				"big.w: error 1",
+				"small.w: error",
+				"big.w: and 3 more errors in this file"
+			]
+		);
+		assert!(found_errors());
+
+		set_max_diagnostics_per_file(None);
+		reset_diagnostics();
+	}
+
 	#[test]
 	fn wingspan_contains_lsp_position() {
 		let span = WingSpan {
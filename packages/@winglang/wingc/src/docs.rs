@@ -21,6 +21,9 @@ pub struct Docs {
 	pub example: Option<String>,
 
 	pub returns: Option<String>,
+	pub throws: Option<String>,
+	/// The documented parameters of a function and their descriptions, in the order they're documented
+	pub params: Vec<(String, String)>,
 	pub deprecated: Option<String>,
 	pub see: Option<String>,
 
@@ -54,6 +57,52 @@ impl Docs {
 		}
 	}
 
+	/// Parses a `///` doc comment. The text before the first tag is the summary, and the tags that follow it are
+	/// `@param name description`, `@returns description`, `@throws description`, `@example code`, `@deprecated`, `@see`
+	/// and custom tags (`@tag value`). A tag's text continues on the following lines until the next tag.
+	pub(crate) fn from_doc_comment(comment: &str) -> Docs {
+		let mut docs = Docs::default();
+		let mut summary = vec![];
+		let mut tags: Vec<(&str, Vec<&str>)> = vec![];
+		for line in comment.lines() {
+			match line.trim_start().strip_prefix('@') {
+				Some(tag) => {
+					let (name, text) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+					tags.push((name, vec![text.trim()]));
+				}
+				None => match tags.last_mut() {
+					Some((_, text)) => text.push(line),
+					None => summary.push(line),
+				},
+			}
+		}
+
+		let text = |lines: Vec<&str>| Some(lines.join("\n").trim().to_string()).filter(|text| !text.is_empty());
+		docs.summary = text(summary);
+		for (tag, lines) in tags {
+			match tag {
+				"param" => {
+					let lines = lines.join("\n");
+					let (name, description) = lines.split_once(char::is_whitespace).unwrap_or((&lines, ""));
+					if !name.is_empty() {
+						docs.params.push((name.to_string(), description.trim().to_string()));
+					}
+				}
+				"returns" | "return" => docs.returns = text(lines),
+				"throws" => docs.throws = text(lines),
+				"example" => docs.example = text(lines),
+				"deprecated" => docs.deprecated = text(lines).or(Some(String::default())),
+				"see" => docs.see = text(lines),
+				_ => {
+					docs
+						.custom
+						.insert(tag.to_string(), text(lines).unwrap_or_else(|| "true".to_string()));
+				}
+			}
+		}
+		docs
+	}
+
 	pub fn as_jsdoc_comment(&self) -> Option<String> {
 		let mut markdown = CodeMaker::default();
 		let mut has_data = false;
@@ -73,11 +122,21 @@ impl Docs {
 			markdown.line(s);
 		}
 
+		for (name, description) in &self.params {
+			has_data = true;
+			markdown.line(format!("@param {name} {description}").trim_end());
+		}
+
 		if let Some(s) = &self.returns {
 			has_data = true;
 			markdown.line(format!("@returns {s}"));
 		}
 
+		if let Some(s) = &self.throws {
+			has_data = true;
+			markdown.line(format!("@throws {s}"));
+		}
+
 		if let Some(s) = &self.deprecated {
 			has_data = true;
 			markdown.line(format!("@deprecated {s}"));
@@ -159,6 +218,9 @@ impl Documented for TypeRef {
 			| Type::Regex
 			| Type::Boolean
 			| Type::Void
+			| Type::Never
+			| Type::Unknown
+			| Type::Literal(_)
 			| Type::Json(_)
 			| Type::Stringable
 			| Type::MutJson
@@ -196,6 +258,9 @@ impl Documented for TypeRef {
 			| Type::Regex
 			| Type::Boolean
 			| Type::Void
+			| Type::Never
+			| Type::Unknown
+			| Type::Literal(_)
 			| Type::Json(_)
 			| Type::MutJson
 			| Type::Nil
@@ -310,12 +375,26 @@ pub fn fix_markdown_links(s: &str) -> String {
 }
 
 fn render_docs(markdown: &mut CodeMaker, docs: &Docs) {
+	if !docs.params.is_empty() {
+		markdown.empty_line();
+		markdown.line("#### Parameters");
+		for (name, description) in &docs.params {
+			markdown.line(format!("- `{name}` {}", fix_markdown_links(description)).trim_end());
+		}
+	}
+
 	if let Some(s) = &docs.returns {
 		markdown.empty_line();
 		markdown.line("#### Returns");
 		markdown.line(fix_markdown_links(s));
 	}
 
+	if let Some(s) = &docs.throws {
+		markdown.empty_line();
+		markdown.line("#### Throws");
+		markdown.line(fix_markdown_links(s));
+	}
+
 	if let Some(s) = &docs.remarks {
 		markdown.empty_line();
 		markdown.line("#### Remarks");
@@ -17,6 +17,11 @@ const DURATION_FQN: &str = formatcp!("{WINGSDK_ASSEMBLY_NAME}.{WINGSDK_DURATION}
 const DATETIME_FQN: &str = formatcp!("{WINGSDK_ASSEMBLY_NAME}.{WINGSDK_DATETIME}");
 const REGEX_FQN: &str = formatcp!("{WINGSDK_ASSEMBLY_NAME}.{WINGSDK_REGEX}");
 
+/// Postfix of the type alias generated for the `handle` signature of closure classes and interfaces
+const TYPE_HANDLE_POSTFIX: &str = "$Handle";
+/// Name of the generic type used for inflight closures that are passed around in preflight code
+const INFLIGHT_CLOSURE_TYPE: &str = "InflightClosure";
+
 /// Generates a self-contained .d.ts file for a given extern file.
 pub struct ExternDTSifier<'a> {
 	/// The type information for any named types seen along the way that need to be hoisted to the top of the file
@@ -108,6 +113,9 @@ impl<'a> ExternDTSifier<'a> {
 			Type::String => "string".to_string(),
 			Type::Boolean => "boolean".to_string(),
 			Type::Void => "void".to_string(),
+			Type::Never => "never".to_string(),
+			Type::Unknown => "unknown".to_string(),
+			Type::Literal(values) => values.iter().join(" | "),
 			Type::Nil => "undefined".to_string(),
 			Type::Json(Some(data)) => match &data.kind {
 				JsonDataKind::Type(inner) => self.dtsify_type(inner.type_, is_inflight),
@@ -165,7 +173,15 @@ impl<'a> ExternDTSifier<'a> {
 			Type::MutMap(t) => format!("Record<string, {}>", self.dtsify_type(*t, is_inflight)),
 			Type::Set(t) => format!("Readonly<Set<{}>>", self.dtsify_type(*t, is_inflight)),
 			Type::MutSet(t) => format!("Set<{}>", self.dtsify_type(*t, is_inflight)),
-			Type::Function(f) => self.dtsify_function_signature(&f, is_inflight),
+			Type::Function(f) => {
+				let signature = self.dtsify_function_signature(&f, is_inflight);
+				if !is_inflight && f.phase == Phase::Inflight {
+					// Preflight code sees inflight closures as objects, only their inflight client can be called
+					format!("{}<{signature}>", self.inflight_closure_type())
+				} else {
+					signature
+				}
+			}
 			Type::Class(_) | Type::Interface(_) | Type::Struct(_) | Type::Enum(_) => {
 				self.resolve_named_type(type_, is_inflight)
 			}
@@ -218,14 +234,7 @@ impl<'a> ExternDTSifier<'a> {
 		if let Some(name) = self.known_types.get(&type_key) {
 			name.clone()
 		} else {
-			let name_counter = self.name_counter.get(&base_name).unwrap_or(&0);
-			let name = if *name_counter == 0 {
-				base_name.to_string()
-			} else {
-				format!("{base_name}{name_counter}")
-			};
-
-			self.name_counter.insert(base_name, name_counter + 1);
+			let name = self.unique_name(&base_name);
 			self.known_types.insert(type_key, name.clone());
 
 			let type_code = match &*type_ {
@@ -236,11 +245,76 @@ impl<'a> ExternDTSifier<'a> {
 				_ => panic!("Not a named type"),
 			};
 			self.hoisted_types.line(type_code);
+			self.hoist_closure_handle(type_, &fqn, &name);
 
 			name
 		}
 	}
 
+	/// Closure classes and interfaces (like `cloud.IFunctionHandler`) only have an inflight `handle` method, which
+	/// their preflight declaration doesn't include. To let extern implementers check the functions they write against
+	/// it, its signature is also declared as a type alias named after the closure type (e.g. `IFunctionHandler$Handle`).
+	fn hoist_closure_handle(&mut self, type_: TypeRef, fqn: &str, type_name: &str) {
+		let Some(sig) = type_.as_deep_function_sig() else {
+			return;
+		};
+		if sig.phase != Phase::Inflight {
+			return;
+		}
+
+		let base_name = format!(
+			"{}{TYPE_HANDLE_POSTFIX}",
+			type_name.trim_end_matches(TYPE_INFLIGHT_POSTFIX)
+		);
+		let type_key = format!("{fqn}|{base_name}");
+		if self.known_types.contains_key(&type_key) {
+			return;
+		}
+		let name = self.unique_name(&base_name);
+		self.known_types.insert(type_key, name.clone());
+
+		let signature = self.dtsify_function_signature(sig, true);
+		self.hoisted_types.line(format!(
+			"/** The signature of the inflight `handle` method of `{}` */",
+			type_name.trim_end_matches(TYPE_INFLIGHT_POSTFIX)
+		));
+		self.hoisted_types.line(format!("export type {name} = {signature};"));
+	}
+
+	/// Returns the name of the generic type of inflight closures passed to (or returned from) preflight code,
+	/// declaring it the first time it's used.
+	fn inflight_closure_type(&mut self) -> String {
+		if let Some(name) = self.known_types.get(INFLIGHT_CLOSURE_TYPE) {
+			return name.clone();
+		}
+		let name = self.unique_name(INFLIGHT_CLOSURE_TYPE);
+		self.known_types.insert(INFLIGHT_CLOSURE_TYPE.to_string(), name.clone());
+
+		self.hoisted_types.line(
+			"/** An inflight closure, as seen from preflight code. Its `handle` method can only be called inflight. */",
+		);
+		self.hoisted_types.open(format!(
+			"export interface {name}<Handle extends (...args: any[]) => Promise<any>> {{"
+		));
+		self
+			.hoisted_types
+			.line("/** Not actually defined, this carries the closure's inflight signature across the phase boundary */");
+		self.hoisted_types.line("readonly $handle?: Handle;");
+		self.hoisted_types.close("}");
+		name
+	}
+
+	/// Returns `base_name`, with a counter appended if a type with the same name was already declared.
+	fn unique_name(&mut self, base_name: &str) -> String {
+		let name_counter = *self.name_counter.get(base_name).unwrap_or(&0);
+		self.name_counter.insert(base_name.to_string(), name_counter + 1);
+		if name_counter == 0 {
+			base_name.to_string()
+		} else {
+			format!("{base_name}{name_counter}")
+		}
+	}
+
 	fn dtsify_function_signature(&mut self, f: &FunctionSignature, is_inflight: bool) -> String {
 		let args = self.dtsify_parameters(&f.parameters, is_inflight);
 
@@ -270,8 +344,12 @@ impl<'a> ExternDTSifier<'a> {
 		}
 		code.open(format!("export enum {} {{", enum_.name.name));
 
-		for (i, variant) in enum_.values.iter().enumerate() {
-			code.line(format!("{} = {i},", variant.0));
+		for variant in enum_.values.keys() {
+			if enum_.is_flags {
+				code.line(format!("{variant} = {},", enum_.flag_value(variant)));
+			} else {
+				code.line(format!("{variant} = \"{}\",", enum_.backing_value(variant)));
+			}
 		}
 
 		code.close("}");
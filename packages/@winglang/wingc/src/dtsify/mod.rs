@@ -1,21 +1,25 @@
 use std::cell::RefCell;
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use indexmap::IndexMap;
 use itertools::Itertools;
+use parcel_sourcemap::utils::make_relative_path;
 
 use crate::{
 	ast::*,
-	diagnostic::report_diagnostic,
+	diagnostic::{report_diagnostic, WingSpan},
+	docs::Docs,
 	file_graph::{File, FileGraph},
 	files::Files,
 	jsify::codemaker::CodeMaker,
-	type_check::Types,
-	WINGSDK_ASSEMBLY_NAME,
+	parser::STRUCT_STATICS_CLASS_PREFIX,
+	type_check::{is_secondary_constructor_name, symbol_env::SymbolEnv, SymbolKind, Type, TypeRef, Types},
+	INTERNAL_DIRECTORY_NAME, WINGSDK_ASSEMBLY_NAME,
 };
 pub mod extern_dtsify;
 
 pub const TYPE_INFLIGHT_POSTFIX: &str = "$Inflight";
+const TYPE_STATIC_POSTFIX: &str = "$Static";
 const TYPE_INTERNAL_NAMESPACE: &str = "$internal";
 const TYPE_STD: &str = "std";
 const EMIT_FILE_EXTENSION: &str = "cjs";
@@ -24,6 +28,8 @@ const EMIT_TYPE_FILE_EXTENSION: &str = "d.cts";
 pub struct DTSifier<'a> {
 	preflight_file_map: &'a IndexMap<Utf8PathBuf, String>,
 	source_file_graph: &'a FileGraph,
+	source_files: &'a Files,
+	out_dir: &'a Utf8Path,
 	pub types: &'a mut Types,
 	pub output_files: RefCell<Files>,
 }
@@ -33,10 +39,14 @@ impl<'a> DTSifier<'a> {
 		types: &'a mut Types,
 		preflight_file_map: &'a IndexMap<Utf8PathBuf, String>,
 		source_file_graph: &'a FileGraph,
+		source_files: &'a Files,
+		out_dir: &'a Utf8Path,
 	) -> Self {
 		Self {
 			preflight_file_map,
 			source_file_graph,
+			source_files,
+			out_dir,
 			output_files: RefCell::new(Files::new()),
 			types,
 		}
@@ -55,6 +65,10 @@ impl<'a> DTSifier<'a> {
 					.expect("no emitted JS file found");
 				if file.path.is_dir() {
 					let directory_name = file.path.file_stem().unwrap();
+					// Internal directories are only usable from Wing code within the same package
+					if directory_name == INTERNAL_DIRECTORY_NAME {
+						continue;
+					}
 					dts.line(format!("export * as {directory_name} from \"./{preflight_file_name}\""));
 				} else {
 					dts.line(format!("export * from \"./{preflight_file_name}\""));
@@ -67,8 +81,9 @@ impl<'a> DTSifier<'a> {
 			dts.line(format!("import {{ {TYPE_STD} }} from \"{WINGSDK_ASSEMBLY_NAME}\""));
 		}
 
+		let env = self.types.get_scope_env(scope);
 		for statement in &scope.statements {
-			dts.add_code(self.dtsify_statement(statement));
+			dts.add_code(self.dtsify_statement(statement, &env));
 		}
 
 		let mut dts_file_name = Utf8PathBuf::from(self.preflight_file_map.get(&source_file.path).unwrap());
@@ -77,6 +92,27 @@ impl<'a> DTSifier<'a> {
 
 		dts_file_name = dts_file_name.with_extension(EMIT_TYPE_FILE_EXTENSION);
 
+		// Emit a declaration map so "go to definition" from TypeScript lands in the Wing source (directories have no
+		// source of their own)
+		let source_content = self.source_files.get_file(&source_file.path);
+		if let Some(source_content) = source_content.filter(|_| !source_file.path.is_dir()) {
+			let declaration_map_file_name = format!("{dts_file_name}.map");
+			dts.line(format!("//# sourceMappingURL={declaration_map_file_name}"));
+			let declaration_map = dts.generate_sourcemap(
+				&make_relative_path(self.out_dir.as_str(), source_file.path.as_str()),
+				source_content,
+				dts_file_name.as_str(),
+			);
+			match self
+				.output_files
+				.borrow_mut()
+				.add_file(declaration_map_file_name, declaration_map)
+			{
+				Ok(()) => {}
+				Err(err) => report_diagnostic(err.into()),
+			}
+		}
+
 		match self.output_files.borrow_mut().add_file(dts_file_name, dts.to_string()) {
 			Ok(()) => {}
 			Err(err) => report_diagnostic(err.into()),
@@ -106,7 +142,7 @@ impl<'a> DTSifier<'a> {
 	}
 
 	fn dtsify_interface(&self, interface: &Interface, as_inflight: bool) -> CodeMaker {
-		let mut code = CodeMaker::default();
+		let mut code = CodeMaker::with_source(&interface.name.span);
 		let interface_name = if as_inflight {
 			format!("{}{TYPE_INFLIGHT_POSTFIX}", &interface.name.name)
 		} else {
@@ -137,22 +173,63 @@ impl<'a> DTSifier<'a> {
 		for method in interface
 			.methods
 			.iter()
+			.filter(|m| !m.3)
 			.filter(|m| !ignore_member_phase(m.1.phase, as_inflight))
 		{
-			code.line(format!(
-				"readonly {}: {};",
-				method.0.name,
-				self.dtsify_function_signature(&method.1, as_inflight)
+			if let Some(doc) = method
+				.2
+				.as_ref()
+				.and_then(|doc| Docs::from_doc_comment(doc).as_jsdoc_comment())
+			{
+				code.line(doc);
+			}
+			code.line(sourced_line(
+				&method.0.span,
+				format!(
+					"readonly {}: {};",
+					method.0.name,
+					self.dtsify_function_signature(&method.1, as_inflight)
+				),
 			));
 		}
 
 		code.close("}");
 
+		// TypeScript interfaces can't declare static members, so they are emitted as a separate interface
+		// describing the implementing class itself (e.g. `const Impl: IFoo$Static = MyClass;`)
+		let static_methods = interface
+			.methods
+			.iter()
+			.filter(|m| m.3)
+			.filter(|m| !ignore_member_phase(m.1.phase, as_inflight))
+			.collect_vec();
+		if !static_methods.is_empty() {
+			code.open(format!("export interface {interface_name}{TYPE_STATIC_POSTFIX} {{"));
+			for method in static_methods {
+				if let Some(doc) = method
+					.2
+					.as_ref()
+					.and_then(|doc| Docs::from_doc_comment(doc).as_jsdoc_comment())
+				{
+					code.line(doc);
+				}
+				code.line(sourced_line(
+					&method.0.span,
+					format!(
+						"readonly {}: {};",
+						method.0.name,
+						self.dtsify_function_signature(&method.1, as_inflight)
+					),
+				));
+			}
+			code.close("}");
+		}
+
 		code
 	}
 
 	fn dtsify_class(&self, class: &Class, as_inflight: bool) -> CodeMaker {
-		let mut code = CodeMaker::default();
+		let mut code = CodeMaker::with_source(&class.name.span);
 		let class_name = if as_inflight {
 			format!("{}{TYPE_INFLIGHT_POSTFIX}", class.name)
 		} else {
@@ -224,15 +301,18 @@ impl<'a> DTSifier<'a> {
 			.filter(|f| matches!(f.access, AccessModifier::Public))
 			.filter(|f| !ignore_member_phase(f.phase, as_inflight))
 		{
-			code.line(format!(
-				"{}{}: {};",
-				field.name,
-				if matches!(field.member_type.kind, TypeAnnotationKind::Optional(_)) {
-					"?"
-				} else {
-					""
-				},
-				self.dtsify_type_annotation(&field.member_type, true)
+			code.line(sourced_line(
+				&field.name.span,
+				format!(
+					"{}{}: {};",
+					field.name,
+					if matches!(field.member_type.kind, TypeAnnotationKind::Optional(_)) {
+						"?"
+					} else {
+						""
+					},
+					self.dtsify_type_annotation(&field.member_type, true)
+				),
 			));
 		}
 		for method in class
@@ -241,11 +321,28 @@ impl<'a> DTSifier<'a> {
 			.filter(|f| matches!(f.1.access, AccessModifier::Public))
 			.filter(|f| !ignore_member_phase(f.1.signature.phase, as_inflight))
 		{
-			code.line(format!(
-				"{}{}: {};",
-				if method.1.is_static { "static " } else { "" },
-				method.0.name,
-				self.dtsify_function_signature(&method.1.signature, as_inflight)
+			let mut docs = method.1.doc.as_ref().map(|doc| Docs::from_doc_comment(doc));
+			// Point secondary constructors back to the `new` expressions that are routed to them
+			if is_secondary_constructor(class, &method.0, &method.1) {
+				let docs = docs.get_or_insert_with(Docs::default);
+				docs.see.get_or_insert_with(|| {
+					format!(
+						"`new {}(...)` in Wing is routed to this secondary constructor when its arguments fit",
+						class.name
+					)
+				});
+			}
+			if let Some(doc) = docs.and_then(|docs| docs.as_jsdoc_comment()) {
+				code.line(doc);
+			}
+			code.line(sourced_line(
+				&method.0.span,
+				format!(
+					"{}{}: {};",
+					if method.1.is_static { "static " } else { "" },
+					method.0.name,
+					self.dtsify_function_signature(&method.1.signature, as_inflight)
+				),
 			));
 		}
 
@@ -253,7 +350,7 @@ impl<'a> DTSifier<'a> {
 		code
 	}
 
-	fn dtsify_statement(&self, stmt: &Stmt) -> CodeMaker {
+	fn dtsify_statement(&self, stmt: &Stmt, env: &SymbolEnv) -> CodeMaker {
 		let mut code = CodeMaker::default();
 		match &stmt.kind {
 			StmtKind::Interface(interface) => {
@@ -261,6 +358,7 @@ impl<'a> DTSifier<'a> {
 				code.line(self.dtsify_interface(interface, true));
 			}
 			StmtKind::Struct(st) => {
+				let mut code = CodeMaker::with_source(&st.name.span);
 				if !st.extends.is_empty() {
 					code.open(format!(
 						"export interface {} extends {} {{",
@@ -272,25 +370,44 @@ impl<'a> DTSifier<'a> {
 				}
 
 				for field in &st.fields {
-					code.line(format!(
-						"readonly {}{}: {};",
-						field.name,
-						if matches!(field.member_type.kind, TypeAnnotationKind::Optional(_)) {
-							"?"
-						} else {
-							""
-						},
-						self.dtsify_type_annotation(&field.member_type, false)
+					if let Some(doc) = field
+						.doc
+						.as_ref()
+						.and_then(|doc| Docs::from_doc_comment(doc).as_jsdoc_comment())
+					{
+						code.line(doc);
+					}
+					code.line(sourced_line(
+						&field.name.span,
+						format!(
+							"readonly {}{}: {};",
+							field.name,
+							if matches!(field.member_type.kind, TypeAnnotationKind::Optional(_)) {
+								"?"
+							} else {
+								""
+							},
+							self.dtsify_type_annotation(&field.member_type, false)
+						),
 					));
 				}
 				code.close("}");
+				return code;
 			}
 			StmtKind::Enum(enu) => {
+				let mut code = CodeMaker::with_source(&enu.name.span);
 				code.open(format!("export enum {} {{", enu.name.name));
-				for value in &enu.values {
-					code.line(format!("{},", value.0.name));
+				// Values are represented by their backing string (or bit, for flags enums) at runtime
+				for (i, value) in enu.values.keys().enumerate() {
+					let backing_value = if enu.is_flags {
+						(1u32 << i).to_string()
+					} else {
+						format!("\"{}\"", enu.backing_values.get(value).unwrap_or(&value.name))
+					};
+					code.line(sourced_line(&value.span, format!("{} = {backing_value},", value.name)));
 				}
 				code.close("}");
+				return code;
 			}
 			StmtKind::Bring { source, identifier } => {
 				let identifier = identifier.as_ref().map(|i| i.name.clone()).unwrap_or("".to_string());
@@ -306,6 +423,8 @@ impl<'a> DTSifier<'a> {
 						code.line(format!("import * as {sym} from \"./{preflight_file_name}\";"))
 					}
 					BringSource::JsiiModule(sym) => code.line(format!("import * as {identifier} from \"{sym}\"")),
+					// npm modules are untyped, so they never appear in the declarations
+					BringSource::NpmModule(_) => {}
 					BringSource::WingFile(path) => {
 						let preflight_file_name = self.preflight_file_map.get(path).unwrap();
 						code.line(format!("import * as {identifier} from \"./{preflight_file_name}\";"))
@@ -316,16 +435,38 @@ impl<'a> DTSifier<'a> {
 					}
 				}
 			}
+			// the synthetic class holding struct statics is an implementation detail of the Wing compiler
+			StmtKind::Class(class) if class.name.name.starts_with(STRUCT_STATICS_CLASS_PREFIX) => {}
 			StmtKind::Class(class) => {
 				code.line(self.dtsify_class(class, false));
 				code.line(self.dtsify_class(class, true));
 			}
 
+			StmtKind::Let {
+				var_name,
+				initial_value,
+				type_,
+				access,
+				..
+			} => {
+				if *access == AccessModifier::Public || *access == AccessModifier::Internal {
+					let type_str = if let Some(type_) = type_ {
+						self.dtsify_type_annotation(type_, false)
+					} else {
+						self.dtsify_type(self.types.get_expr_type(initial_value), env)
+					};
+					code.line(sourced_line(
+						&var_name.span,
+						format!("export declare const {}: {type_str};", var_name.name),
+					));
+				}
+			}
+
 			// No need to emit anything for these
 			StmtKind::SuperConstructor { .. }
-			| StmtKind::Let { .. }
 			| StmtKind::ForLoop { .. }
 			| StmtKind::While { .. }
+			| StmtKind::WhileLet { .. }
 			| StmtKind::IfLet(_)
 			| StmtKind::If { .. }
 			| StmtKind::Break
@@ -366,6 +507,50 @@ impl<'a> DTSifier<'a> {
 		args.join(", ")
 	}
 
+	/// Like `dtsify_type_annotation` but for types that were inferred by the type checker, user defined types are
+	/// qualified the way they would be written in the file whose environment is `env`
+	fn dtsify_type(&self, type_: TypeRef, env: &SymbolEnv) -> String {
+		match &*type_ {
+			Type::Number => "number".to_string(),
+			Type::String => "string".to_string(),
+			Type::Boolean => "boolean".to_string(),
+			Type::Void => "void".to_string(),
+			Type::Never => "never".to_string(),
+			Type::Unknown => "unknown".to_string(),
+			Type::Literal(values) => values.iter().join(" | "),
+			Type::Nil => "undefined".to_string(),
+			Type::Json(_) => format!("Readonly<{TYPE_INTERNAL_NAMESPACE}.Json>"),
+			Type::MutJson => format!("{TYPE_INTERNAL_NAMESPACE}.Json"),
+			Type::Duration => format!("{TYPE_STD}.Duration"),
+			Type::Datetime => format!("{TYPE_STD}.Datetime"),
+			Type::Regex => format!("{TYPE_STD}.Regex"),
+			Type::Optional(t) => format!("({}) | undefined", self.dtsify_type(*t, env)),
+			Type::Array(t) => format!("(readonly ({})[])", self.dtsify_type(*t, env)),
+			Type::MutArray(t) => format!("({})[]", self.dtsify_type(*t, env)),
+			Type::Map(t) => format!("Readonly<Record<string, {}>>", self.dtsify_type(*t, env)),
+			Type::MutMap(t) => format!("Record<string, {}>", self.dtsify_type(*t, env)),
+			Type::Set(t) => format!("Readonly<Set<{}>>", self.dtsify_type(*t, env)),
+			Type::MutSet(t) => format!("Set<{}>", self.dtsify_type(*t, env)),
+			Type::Function(f) => {
+				let args = f
+					.parameters
+					.iter()
+					.map(|p| format!("{}: {}", p.name, self.dtsify_type(p.typeref, env)))
+					.join(", ");
+				format!("({args}) => {}", self.dtsify_type(f.return_type, env))
+			}
+			Type::Class(c) => find_type_path(type_, env, 0).unwrap_or_else(|| c.name.name.clone()),
+			Type::Interface(i) => find_type_path(type_, env, 0).unwrap_or_else(|| i.name.name.clone()),
+			Type::Struct(s) => find_type_path(type_, env, 0).unwrap_or_else(|| s.name.name.clone()),
+			Type::Enum(e) => find_type_path(type_, env, 0).unwrap_or_else(|| e.name.name.clone()),
+			Type::Inferred(id) => self
+				.types
+				.get_inference_by_id(*id)
+				.map_or("any".to_string(), |t| self.dtsify_type(t, env)),
+			Type::Anything | Type::Stringable | Type::Unresolved => "any".to_string(),
+		}
+	}
+
 	fn dtsify_type_annotation(&self, typ: &TypeAnnotation, ignore_phase: bool) -> String {
 		match &typ.kind {
 			TypeAnnotationKind::Inferred => panic!("Should not have any inferred types"),
@@ -373,6 +558,9 @@ impl<'a> DTSifier<'a> {
 			TypeAnnotationKind::String => "string".to_string(),
 			TypeAnnotationKind::Bool => "boolean".to_string(),
 			TypeAnnotationKind::Void => "void".to_string(),
+			TypeAnnotationKind::Never => "never".to_string(),
+			TypeAnnotationKind::Unknown => "unknown".to_string(),
+			TypeAnnotationKind::Literal(values) => values.iter().join(" | "),
 			TypeAnnotationKind::Json => format!("Readonly<{TYPE_INTERNAL_NAMESPACE}.Json>"),
 			TypeAnnotationKind::MutJson => format!("{TYPE_INTERNAL_NAMESPACE}.Json"),
 			TypeAnnotationKind::Duration => format!("{TYPE_STD}.Duration"),
@@ -398,6 +586,48 @@ impl<'a> DTSifier<'a> {
 	}
 }
 
+/// How many namespaces deep to look for a type brought into a file (e.g. `lib.subdir.Type`)
+const MAX_TYPE_PATH_DEPTH: usize = 3;
+
+/// Finds the name of a type as seen from `env`: either a symbol of its own or a path through the namespaces brought
+/// into it
+fn find_type_path(type_: TypeRef, env: &SymbolEnv, depth: usize) -> Option<String> {
+	if depth > MAX_TYPE_PATH_DEPTH {
+		return None;
+	}
+	for (name, kind, _) in env.iter(false) {
+		match kind {
+			SymbolKind::Type(t) if std::ptr::eq(&**t, &*type_) => return Some(name),
+			SymbolKind::Namespace(ns) => {
+				if let Some(path) = ns
+					.envs
+					.iter()
+					.find_map(|ns_env| find_type_path(type_, ns_env, depth + 1))
+				{
+					return Some(format!("{name}.{path}"));
+				}
+			}
+			_ => {}
+		}
+	}
+	None
+}
+
+/// A line of declarations that maps back to the given span of the Wing source
+fn sourced_line(span: &WingSpan, line: String) -> CodeMaker {
+	let mut code = CodeMaker::with_source(span);
+	code.line(line);
+	code
+}
+
+/// Whether a class method is a secondary constructor (see `Class::secondary_constructors`)
+fn is_secondary_constructor(class: &Class, name: &Symbol, method: &FunctionDefinition) -> bool {
+	method.is_static
+		&& matches!(method.access, AccessModifier::Public)
+		&& is_secondary_constructor_name(&name.name)
+		&& matches!(&method.signature.return_type.kind, TypeAnnotationKind::UserDefined(udt) if udt.fields.is_empty() && udt.root.name == class.name.name)
+}
+
 pub fn ignore_member_phase(phase: Phase, is_inflight: bool) -> bool {
 	// If we're an inflight client, we want to ignore preflight members
 	// Or
@@ -471,3 +701,116 @@ pub class ParentClass impl ClassInterface {
 "#
 	)
 }
+
+#[test]
+fn declaration_map_points_to_declarations() {
+	let source = r#"
+pub struct Options {
+  retries: num;
+}
+
+pub class Client {
+  pub name: str;
+  new() {
+    this.name = "client";
+  }
+  pub send(options: Options) {}
+}
+
+pub enum Level {
+  LOW,
+  HIGH,
+}
+"#;
+	std::env::set_current_dir(env!("CARGO_MANIFEST_DIR")).unwrap();
+	let project_dir = tempfile::tempdir().unwrap();
+	let project_dir = Utf8Path::from_path(project_dir.path()).unwrap();
+	let out_dir = project_dir.join("target/lib.out/.wing");
+	std::fs::write(project_dir.join("lib.w"), source).unwrap();
+	assert!(crate::compile(project_dir, None, &out_dir).is_ok());
+
+	let dts = std::fs::read_to_string(out_dir.join("preflight.lib-1.d.cts")).unwrap();
+	assert!(dts.contains("//# sourceMappingURL=preflight.lib-1.d.cts.map"));
+	let declaration_map: serde_json::Value =
+		serde_json::from_str(&std::fs::read_to_string(out_dir.join("preflight.lib-1.d.cts.map")).unwrap()).unwrap();
+	assert_eq!(declaration_map["sources"], serde_json::json!(["../../../lib.w"]));
+	let mut sourcemap = parcel_sourcemap::SourceMap::new(out_dir.as_str());
+	sourcemap
+		.add_vlq_map::<&str>(
+			declaration_map["mappings"].as_str().unwrap().as_bytes(),
+			vec!["../../../lib.w"],
+			vec![],
+			vec![],
+			0,
+			0,
+		)
+		.unwrap();
+	let mappings = sourcemap.get_mappings();
+
+	// Each declaration maps to the name it's declared with in the Wing source
+	let assert_maps_to = |declaration: &str, name: &str| {
+		let generated_line = dts
+			.lines()
+			.position(|line| line.contains(declaration))
+			.unwrap_or_else(|| panic!("no declaration containing \"{declaration}\""));
+		let (line, column) = source
+			.lines()
+			.enumerate()
+			.find_map(|(i, line)| line.find(name).map(|column| (i, column)))
+			.unwrap();
+		let original = mappings
+			.iter()
+			.find(|mapping| mapping.generated_line as usize == generated_line)
+			.and_then(|mapping| mapping.original)
+			.unwrap_or_else(|| panic!("\"{declaration}\" isn't mapped"));
+		assert_eq!(
+			(original.original_line as usize, original.original_column as usize),
+			(line, column),
+			"\"{declaration}\" should map to \"{name}\""
+		);
+	};
+	assert_maps_to("export interface Options", "Options {");
+	assert_maps_to("readonly retries", "retries");
+	assert_maps_to("export class Client", "Client");
+	assert_maps_to("name: string", "name");
+	assert_maps_to("send:", "send");
+	assert_maps_to("export enum Level", "Level");
+	assert_maps_to("HIGH = ", "HIGH");
+}
+
+#[test]
+fn inferred_types_are_qualified() {
+	std::env::set_current_dir(env!("CARGO_MANIFEST_DIR")).unwrap();
+	let project_dir = tempfile::tempdir().unwrap();
+	let project_dir = Utf8Path::from_path(project_dir.path()).unwrap();
+	let out_dir = project_dir.join("target/lib.out/.wing");
+	std::fs::write(
+		project_dir.join("options.w"),
+		r#"
+pub struct Options {
+  retries: num;
+}
+"#,
+	)
+	.unwrap();
+	std::fs::write(
+		project_dir.join("lib.w"),
+		r#"
+bring "./options.w" as opts;
+
+pub enum Level {
+  LOW,
+  HIGH,
+}
+
+pub let DEFAULT_OPTIONS = opts.Options { retries: 3 };
+pub let DEFAULT_LEVELS = [Level.LOW];
+"#,
+	)
+	.unwrap();
+	assert!(crate::compile(project_dir, None, &out_dir).is_ok());
+
+	let dts = std::fs::read_to_string(out_dir.join("preflight.lib-1.d.cts")).unwrap();
+	assert!(dts.contains("export declare const DEFAULT_OPTIONS: opts.Options;"));
+	assert!(dts.contains("export declare const DEFAULT_LEVELS: (readonly (Level)[]);"));
+}
@@ -0,0 +1,247 @@
+use std::{fmt::Write, str::FromStr};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use indexmap::IndexMap;
+use strum::{Display, EnumString};
+
+use crate::{
+	ast::{Expr, Scope},
+	diagnostic::{report_diagnostic, Diagnostic, DiagnosticSeverity},
+	files::Files,
+	ir::{build_ir, IrProgram},
+	type_check::Types,
+	visit::{self, Visit},
+};
+
+/// Comma separated list of compiler dumps to write into the output directory (e.g. "ast,lifts").
+/// See `DumpKind` for the available dumps.
+pub const EMIT_ENV: &str = "WING_EMIT";
+
+/// Human readable dumps of the compiler's intermediate representations, used for debugging the compiler.
+#[derive(EnumString, Display, PartialEq, Eq, Clone, Copy, Debug)]
+#[strum(serialize_all = "kebab-case")]
+pub enum DumpKind {
+	/// The AST right after parsing
+	Ast,
+	/// Every expression with its type and phase, after type checking
+	TypedAst,
+	/// The lifts and captures of every class, after the lifting phase
+	Lifts,
+	/// The program's intermediate representation as JSON, after the lifting phase (see `ir::IrProgram`)
+	Ir,
+}
+
+/// Returns the dumps requested through `EMIT_ENV`. Unknown dump kinds are reported as warnings.
+pub fn requested_dumps() -> Vec<DumpKind> {
+	let Ok(value) = std::env::var(EMIT_ENV) else {
+		return vec![];
+	};
+	parse_dump_kinds(&value)
+}
+
+fn parse_dump_kinds(value: &str) -> Vec<DumpKind> {
+	let mut kinds = vec![];
+	for name in value.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+		match DumpKind::from_str(name) {
+			Ok(kind) => {
+				if !kinds.contains(&kind) {
+					kinds.push(kind);
+				}
+			}
+			Err(_) => report_diagnostic(Diagnostic {
+				message: format!(
+					"Unknown value \"{name}\" in {EMIT_ENV}, expected one of: {}, {}, {}, {}",
+					DumpKind::Ast,
+					DumpKind::TypedAst,
+					DumpKind::Lifts,
+					DumpKind::Ir
+				),
+				span: None,
+				annotations: vec![],
+				hints: vec![],
+				severity: DiagnosticSeverity::Warning,
+				code: None,
+			}),
+		}
+	}
+	kinds
+}
+
+/// Writes the requested dumps into `<out_dir>/<kind>/`, mirroring the layout of the source files.
+pub struct Dumps<'a> {
+	kinds: Vec<DumpKind>,
+	/// Source files under this directory keep their relative path in the dump directory
+	root: &'a Utf8Path,
+	out_dir: &'a Utf8Path,
+}
+
+impl<'a> Dumps<'a> {
+	pub fn new(kinds: Vec<DumpKind>, source_path: &'a Utf8Path, out_dir: &'a Utf8Path) -> Self {
+		let root = if source_path.is_dir() {
+			source_path
+		} else {
+			source_path.parent().unwrap_or(source_path)
+		};
+		Self { kinds, root, out_dir }
+	}
+
+	pub fn is_enabled(&self, kind: DumpKind) -> bool {
+		self.kinds.contains(&kind)
+	}
+
+	pub fn dump_ast(&self, asts: &IndexMap<Utf8PathBuf, Scope>) {
+		if !self.is_enabled(DumpKind::Ast) {
+			return;
+		}
+		self.emit(
+			DumpKind::Ast,
+			asts
+				.iter()
+				.map(|(path, scope)| (path.as_path(), format!("{:#?}\n", scope))),
+		);
+	}
+
+	pub fn dump_typed_ast(&self, path: &Utf8Path, scope: &Scope, types: &Types, files: &Files) {
+		if !self.is_enabled(DumpKind::TypedAst) {
+			return;
+		}
+		let mut printer = TypedAstPrinter {
+			types,
+			source: files.get_file(path).map(String::as_str).unwrap_or_default(),
+			depth: 0,
+			output: String::new(),
+		};
+		printer.visit_scope(scope);
+		self.emit(DumpKind::TypedAst, [(path, printer.output)].into_iter());
+	}
+
+	/// Writes the dumps of the lifting phase. `asts` must be in the order in which the files were type checked.
+	pub fn dump_lifted(&self, asts: &IndexMap<Utf8PathBuf, Scope>, types: &Types) {
+		if !self.is_enabled(DumpKind::Lifts) && !self.is_enabled(DumpKind::Ir) {
+			return;
+		}
+		let program = build_ir(asts, types);
+
+		if self.is_enabled(DumpKind::Lifts) {
+			self.emit(
+				DumpKind::Lifts,
+				program
+					.files
+					.iter()
+					.map(|file| (Utf8Path::new(&file.path), print_lifts(&program, &file.path))),
+			);
+		}
+
+		if self.is_enabled(DumpKind::Ir) {
+			let mut files = Files::new();
+			let json = serde_json::to_string_pretty(&program).expect("IR is serializable");
+			files.update_file("ir.json", json);
+			if let Err(err) = files.emit_files(self.out_dir) {
+				report_diagnostic(err.into());
+			}
+		}
+	}
+
+	fn emit<'b>(&self, kind: DumpKind, dumps: impl Iterator<Item = (&'b Utf8Path, String)>) {
+		let mut files = Files::new();
+		for (path, content) in dumps {
+			let relative_path = match path.strip_prefix(self.root) {
+				Ok(relative_path) if !relative_path.as_str().is_empty() => relative_path,
+				_ => Utf8Path::new(path.file_name().unwrap_or("main.w")),
+			};
+			let dump_path = Utf8Path::new(&kind.to_string()).join(format!("{relative_path}.txt"));
+			files.update_file(dump_path, content);
+		}
+		if let Err(err) = files.emit_files(self.out_dir) {
+			report_diagnostic(err.into());
+		}
+	}
+}
+
+/// Prints every expression on its own line, indented by its nesting level, along with its location, source code,
+/// type and phase.
+struct TypedAstPrinter<'a> {
+	types: &'a Types,
+	source: &'a str,
+	depth: usize,
+	output: String,
+}
+
+impl<'a> Visit<'a> for TypedAstPrinter<'_> {
+	fn visit_expr(&mut self, node: &'a Expr) {
+		let snippet = self
+			.source
+			.get(node.span.start_offset..node.span.end_offset)
+			.unwrap_or_default();
+		let snippet = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+		let type_ = self
+			.types
+			.try_get_expr_type(node.id)
+			.map_or("<unknown>".to_string(), |t| t.to_string());
+		let phase = self
+			.types
+			.get_expr_phase(node)
+			.map_or("<unknown>".to_string(), |p| p.to_string());
+		_ = writeln!(
+			self.output,
+			"{}{}:{} `{}`: {} ({})",
+			"  ".repeat(self.depth),
+			node.span.start.line + 1,
+			node.span.start.col + 1,
+			snippet,
+			type_,
+			phase
+		);
+
+		self.depth += 1;
+		visit::visit_expr(self, node);
+		self.depth -= 1;
+	}
+}
+
+/// Prints the lift qualifications and captures of every class in the given file that has them.
+fn print_lifts(program: &IrProgram, path: &str) -> String {
+	let mut output = String::new();
+	let classes = program
+		.files
+		.iter()
+		.filter(|file| file.path == path)
+		.flat_map(|file| &file.classes);
+	for class in classes {
+		if class.lifts.is_empty() && class.captures.is_empty() {
+			continue;
+		}
+		_ = writeln!(output, "class {} ({})", class.name, class.span);
+		if !class.lifts.is_empty() {
+			_ = writeln!(output, "  lifts:");
+			for (method, qualifications) in &class.lifts {
+				_ = writeln!(output, "    {}:", method);
+				for (code, ops) in qualifications {
+					_ = writeln!(output, "      {} [{}]", code, ops.join(", "));
+				}
+			}
+		}
+		if !class.captures.is_empty() {
+			_ = writeln!(output, "  captures:");
+			for capture in &class.captures {
+				let field = if capture.is_field { " (field)" } else { "" };
+				_ = writeln!(output, "    {} = {}{}", capture.token, capture.code, field);
+			}
+		}
+		_ = writeln!(output);
+	}
+	output
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{parse_dump_kinds, DumpKind};
+
+	#[test]
+	fn parses_dump_kinds() {
+		assert_eq!(
+			parse_dump_kinds(" ast,typed-ast,,lifts,ast,ir "),
+			vec![DumpKind::Ast, DumpKind::TypedAst, DumpKind::Lifts, DumpKind::Ir]
+		);
+	}
+}
@@ -0,0 +1,196 @@
+//! A manifest of the environment variables a program reads with `util.env` and `util.tryEnv`, in preflight and inflight
+//! code. Deployments can check the required ones are configured before the program runs, instead of finding out from
+//! a failed read.
+
+use std::collections::BTreeMap;
+
+use camino::Utf8PathBuf;
+use const_format::formatcp;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::ast::{CalleeKind, Expr, ExprKind, FunctionDefinition, Literal, Phase, Reference, Scope, Stmt};
+use crate::diagnostic::WingSpan;
+use crate::type_check::{resolve_user_defined_type, Types};
+use crate::visit::{self, Visit};
+use crate::visit_context::VisitContext;
+use crate::{UTIL_CLASS_NAME, WINGSDK_ASSEMBLY_NAME, WINGSDK_UTIL_MODULE};
+
+/// Emitted next to the preflight entrypoint
+pub const ENV_MANIFEST_FILE_NAME: &str = "env.manifest.json";
+
+const UTIL_CLASS_FQN: &str = formatcp!("{WINGSDK_ASSEMBLY_NAME}.{WINGSDK_UTIL_MODULE}.{UTIL_CLASS_NAME}");
+
+/// Reads an environment variable and fails if it isn't set
+const REQUIRED_ENV_METHOD: &str = "env";
+/// Reads an environment variable that may not be set
+const OPTIONAL_ENV_METHOD: &str = "tryEnv";
+
+#[derive(Serialize)]
+struct EnvManifest {
+	/// The variables read by name, sorted by name
+	variables: Vec<EnvVariable>,
+	/// Reads of variables whose names are computed, so they can't be listed
+	dynamic: Vec<EnvAccess>,
+}
+
+#[derive(Serialize)]
+struct EnvVariable {
+	name: String,
+	/// Whether the program fails when the variable isn't set (some of its reads use `util.env`)
+	required: bool,
+	accesses: Vec<EnvAccess>,
+}
+
+#[derive(Serialize)]
+struct EnvAccess {
+	span: WingSpan,
+	/// Whether the variable is read when the program is compiled (preflight) or when it runs (inflight)
+	phase: String,
+	required: bool,
+}
+
+/// The environment variable manifest of a type checked program, as JSON
+pub fn env_manifest_json(types: &Types, asts: &IndexMap<Utf8PathBuf, Scope>) -> String {
+	let mut collector = EnvAccessCollector {
+		types,
+		ctx: VisitContext::new(),
+		variables: BTreeMap::new(),
+		dynamic: vec![],
+	};
+	for scope in asts.values() {
+		collector.visit_scope(scope);
+	}
+	let manifest = EnvManifest {
+		variables: collector
+			.variables
+			.into_iter()
+			.map(|(name, accesses)| EnvVariable {
+				name,
+				required: accesses.iter().any(|access| access.required),
+				accesses,
+			})
+			.collect(),
+		dynamic: collector.dynamic,
+	};
+	serde_json::to_string_pretty(&manifest).expect("serialize environment variable manifest")
+}
+
+struct EnvAccessCollector<'a> {
+	types: &'a Types,
+	ctx: VisitContext,
+	variables: BTreeMap<String, Vec<EnvAccess>>,
+	dynamic: Vec<EnvAccess>,
+}
+
+impl EnvAccessCollector<'_> {
+	/// Records the expression if it's a call to `util.env` or `util.tryEnv`
+	fn add_access(&mut self, expr: &Expr) {
+		let ExprKind::Call {
+			callee: CalleeKind::Expr(callee),
+			arg_list,
+			..
+		} = &expr.kind
+		else {
+			return;
+		};
+		let ExprKind::Reference(Reference::TypeMember { type_name, property }) = &callee.kind else {
+			return;
+		};
+		let required = match property.name.as_str() {
+			REQUIRED_ENV_METHOD => true,
+			OPTIONAL_ENV_METHOD => false,
+			_ => return,
+		};
+		let Some(env) = self.ctx.current_env() else {
+			return;
+		};
+		let is_util = resolve_user_defined_type(type_name, env, self.ctx.current_stmt_idx())
+			.is_ok_and(|t| t.as_class().is_some_and(|c| c.fqn.as_deref() == Some(UTIL_CLASS_FQN)));
+		if !is_util {
+			return;
+		}
+
+		let access = EnvAccess {
+			span: expr.span.clone(),
+			phase: self.ctx.current_phase().to_string(),
+			required,
+		};
+		let name = match arg_list.pos_args.first().map(|arg| &arg.kind) {
+			Some(ExprKind::Literal(Literal::String(s))) => serde_json::from_str::<String>(&s.replace("\\{", "{")).ok(),
+			Some(ExprKind::Literal(Literal::NonInterpolatedString(s))) => Some(s.trim_matches('"').to_string()),
+			_ => None,
+		};
+		match name {
+			Some(name) => self.variables.entry(name).or_default().push(access),
+			None => self.dynamic.push(access),
+		}
+	}
+}
+
+impl<'a> Visit<'a> for EnvAccessCollector<'a> {
+	fn visit_scope(&mut self, node: &'a Scope) {
+		self.ctx.push_env(self.types.get_scope_env(node));
+		visit::visit_scope(self, node);
+		self.ctx.pop_env();
+	}
+
+	fn visit_stmt(&mut self, node: &'a Stmt) {
+		self.ctx.push_stmt(node);
+		visit::visit_stmt(self, node);
+		self.ctx.pop_stmt();
+	}
+
+	fn visit_function_definition(&mut self, node: &'a FunctionDefinition) {
+		// Phase independent functions run in the phase they're called from
+		let phase = match node.signature.phase {
+			Phase::Independent => self.ctx.current_phase(),
+			phase => phase,
+		};
+		self.ctx.push_phase(phase);
+		visit::visit_function_definition(self, node);
+		self.ctx.pop_phase();
+	}
+
+	fn visit_expr(&mut self, node: &'a Expr) {
+		self.add_access(node);
+		visit::visit_expr(self, node);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::ENV_MANIFEST_FILE_NAME;
+	use crate::test_utils::compile_source;
+
+	#[test]
+	fn lists_required_and_optional_variables() {
+		let source = r#"
+bring util;
+
+let region = util.env("REGION");
+let name = "TABLE";
+
+inflight () => {
+  util.tryEnv("REGION");
+  util.tryEnv("DEBUG");
+  util.env(name);
+};
+"#;
+		let manifest = compile_source(source).read_json(ENV_MANIFEST_FILE_NAME);
+
+		let debug = &manifest["variables"][0];
+		assert_eq!(debug["name"], "DEBUG");
+		assert_eq!(debug["required"], false);
+		assert_eq!(debug["accesses"][0]["phase"], "inflight");
+
+		let region = &manifest["variables"][1];
+		assert_eq!(region["name"], "REGION");
+		assert_eq!(region["required"], true);
+		assert_eq!(region["accesses"].as_array().unwrap().len(), 2);
+		assert_eq!(region["accesses"][0]["phase"], "preflight");
+		assert_eq!(region["accesses"][0]["span"]["start"]["line"], 3);
+
+		assert_eq!(manifest["dynamic"][0]["span"]["start"]["line"], 9);
+	}
+}
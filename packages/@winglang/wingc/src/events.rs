@@ -0,0 +1,165 @@
+//! Structured events about the progress of a compilation, for the programs embedding the compiler.
+//!
+//! An embedder registers an `EventSink` with `set_event_sink`, and `compile()` and the LSP call it as they go, e.g. to
+//! show a progress bar, open tracing spans for the phases of a compilation, or count cache hits.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use camino::Utf8Path;
+
+pub use crate::comp_ctx::CompilationPhase;
+use crate::diagnostic::Diagnostic;
+
+/// Something that happened during a compilation
+#[derive(Debug)]
+pub enum CompilerEvent<'a> {
+	/// A phase started. Phases may be nested: `Compiling` spans a whole compilation.
+	PhaseStarted { phase: CompilationPhase },
+	/// A phase finished (also when it was cut short by errors)
+	PhaseFinished {
+		phase: CompilationPhase,
+		duration: Duration,
+	},
+	/// The current phase started processing a file, the `index`th of the `total` files it processes
+	FileStarted {
+		path: &'a Utf8Path,
+		index: usize,
+		total: usize,
+	},
+	/// A file went through all the phases of the compilation
+	FileCompiled { path: &'a Utf8Path },
+	/// A diagnostic was reported
+	DiagnosticReported { diagnostic: &'a Diagnostic },
+	/// Work from a previous compilation was reused instead of being redone
+	CacheHit { cache: CacheKind, key: &'a str },
+}
+
+/// The kinds of work that can be reused between compilations (mostly by the LSP, which keeps its project data)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKind {
+	/// The syntax tree of a file was updated incrementally instead of being parsed from scratch (keyed by file path)
+	SyntaxTree,
+	/// A file brought by another one was already parsed (keyed by file path)
+	ParsedFile,
+	/// The types of a JSII module were already imported (keyed by assembly name)
+	JsiiImport,
+}
+
+/// Receives the events of the compilations running on the thread it was registered on
+pub trait EventSink {
+	fn on_event(&self, event: &CompilerEvent<'_>);
+}
+
+thread_local! {
+	static EVENT_SINK: RefCell<Option<Rc<dyn EventSink>>> = RefCell::new(None);
+}
+
+/// Registers the sink that receives the compiler's events on this thread, replacing (and returning) the previous one.
+/// Pass `None` to stop receiving events.
+pub fn set_event_sink(sink: Option<Rc<dyn EventSink>>) -> Option<Rc<dyn EventSink>> {
+	EVENT_SINK.with(|current| current.replace(sink))
+}
+
+/// Sends an event to the registered sink, if there's one
+pub fn emit_event(event: CompilerEvent<'_>) {
+	// The sink is cloned out of the cell first, so it can report diagnostics (which emits events) or replace itself
+	let Some(sink) = EVENT_SINK.with(|sink| sink.borrow().clone()) else {
+		return;
+	};
+	sink.on_event(&event);
+}
+
+/// Emits `PhaseStarted` now and `PhaseFinished` when the returned guard is dropped (or finished)
+pub fn start_phase(phase: CompilationPhase) -> PhaseGuard {
+	emit_event(CompilerEvent::PhaseStarted { phase });
+	PhaseGuard {
+		phase,
+		started_at: Instant::now(),
+	}
+}
+
+#[must_use = "the phase finishes when the guard is dropped"]
+pub struct PhaseGuard {
+	phase: CompilationPhase,
+	started_at: Instant,
+}
+
+impl PhaseGuard {
+	/// Finishes the phase (same as dropping the guard, but reads better between the phases of a function)
+	pub fn finish(self) {}
+}
+
+impl Drop for PhaseGuard {
+	fn drop(&mut self) {
+		emit_event(CompilerEvent::PhaseFinished {
+			phase: self.phase,
+			duration: self.started_at.elapsed(),
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::RefCell;
+	use std::rc::Rc;
+
+	use super::{set_event_sink, start_phase, CompilerEvent, EventSink};
+	use crate::comp_ctx::CompilationPhase;
+	use crate::diagnostic::{report_diagnostic, reset_diagnostics, Diagnostic, DiagnosticSeverity};
+
+	#[derive(Default)]
+	struct RecordingSink {
+		events: RefCell<Vec<String>>,
+	}
+
+	impl EventSink for RecordingSink {
+		fn on_event(&self, event: &CompilerEvent<'_>) {
+			let event = match event {
+				CompilerEvent::PhaseStarted { phase } => format!("start {phase}"),
+				CompilerEvent::PhaseFinished { phase, .. } => format!("finish {phase}"),
+				CompilerEvent::FileStarted { path, index, total } => format!("file {path} {index}/{total}"),
+				CompilerEvent::FileCompiled { path } => format!("file {path}"),
+				CompilerEvent::DiagnosticReported { diagnostic } => format!("diagnostic {}", diagnostic.message),
+				CompilerEvent::CacheHit { cache, key } => format!("cache {cache:?} {key}"),
+			};
+			self.events.borrow_mut().push(event);
+		}
+	}
+
+	#[test]
+	fn sends_events_to_the_registered_sink() {
+		let sink = Rc::new(RecordingSink::default());
+		set_event_sink(Some(sink.clone()));
+
+		let compiling = start_phase(CompilationPhase::Compiling);
+		{
+			let _parsing = start_phase(CompilationPhase::Parsing);
+			report_diagnostic(Diagnostic {
+				message: "oops".to_string(),
+				span: None,
+				annotations: vec![],
+				hints: vec![],
+				severity: DiagnosticSeverity::Error,
+				code: None,
+			});
+		}
+		compiling.finish();
+
+		set_event_sink(None);
+		start_phase(CompilationPhase::Lifting).finish();
+		reset_diagnostics();
+
+		assert_eq!(
+			*sink.events.borrow(),
+			vec![
+				"start compiling",
+				"start parsing",
+				"diagnostic oops",
+				"finish parsing",
+				"finish compiling"
+			]
+		);
+	}
+}
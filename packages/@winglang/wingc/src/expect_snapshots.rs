@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::ast::{BringSource, CalleeKind, Expr, ExprId, ExprKind, Reference, Scope, StmtKind};
+use crate::diagnostic::{report_diagnostic, Diagnostic, DiagnosticSeverity, WingSpan};
+use crate::test_blocks::test_name;
+use crate::visit::{self, Visit};
+use crate::WINGSDK_EXPECT_MODULE;
+
+/// Name of the environment variable with what `expect.snapshot` calls do with the values they're passed, besides
+/// comparing them with the stored snapshots (see `SnapshotMode`)
+pub const SNAPSHOT_MODE_ENV: &str = "WING_EXPECT_SNAPSHOTS";
+
+/// Lists the `expect.snapshot` calls of a program in the order of their indices, for the test runner
+pub const SNAPSHOT_MANIFEST_FILE_NAME: &str = "snapshots.json";
+
+/// The snapshots of a Wing file are stored next to it, in `<file>.snap.json`
+const SNAPSHOT_FILE_SUFFIX: &str = ".snap.json";
+
+const SNAPSHOT_METHOD: &str = "snapshot";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SnapshotMode {
+	/// Only compare values with their stored snapshots (e.g. when tests run in the cloud, where values can't be
+	/// recorded for the test runner)
+	#[default]
+	Compare,
+	/// Compare values with their stored snapshots, and record them so the test runner can store new snapshots
+	Record,
+	/// Ignore the stored snapshots and record all the values, so the test runner rewrites the snapshot files
+	Update,
+}
+
+impl SnapshotMode {
+	/// Reads the mode from `SNAPSHOT_MODE_ENV` ("record" or "update"), reporting an error if it's not a valid mode
+	pub fn from_env() -> Self {
+		match std::env::var(SNAPSHOT_MODE_ENV).ok().as_deref() {
+			None | Some("") => Self::Compare,
+			Some("record") => Self::Record,
+			Some("update") => Self::Update,
+			Some(value) => {
+				report_diagnostic(Diagnostic {
+					message: format!(
+						"Invalid snapshot mode \"{value}\" in {SNAPSHOT_MODE_ENV}, expected \"record\" or \"update\""
+					),
+					span: None,
+					annotations: vec![],
+					hints: vec![],
+					severity: DiagnosticSeverity::Error,
+					code: None,
+				});
+				Self::Compare
+			}
+		}
+	}
+
+	/// Whether `expect.snapshot` calls record the values they're passed
+	pub fn records_values(&self) -> bool {
+		*self != Self::Compare
+	}
+}
+
+/// An `expect.snapshot(value)` call
+#[derive(Serialize)]
+pub struct SnapshotCall {
+	/// The key of the snapshot in its snapshot file. It's the name of the test the call is in, followed by the
+	/// call's position among the snapshots of that test (e.g. "put 1"), so it doesn't change when unrelated code does.
+	pub id: String,
+	/// The file the snapshot is stored in
+	pub snapshot_file: String,
+	/// The span of the call
+	pub span: WingSpan,
+	/// The stored value the snapshot is compared with, if there's one (and snapshots aren't being updated)
+	#[serde(skip)]
+	pub expected: Option<Value>,
+	#[serde(skip)]
+	pub expr_id: ExprId,
+}
+
+/// Finds the `expect.snapshot` calls of a parsed file (before inflight closures are transformed into classes, so the
+/// tests they're in can still be found)
+pub fn find_snapshot_calls(scope: &Scope) -> Vec<SnapshotCall> {
+	let expect_alias = scope.statements.iter().find_map(|stmt| match &stmt.kind {
+		StmtKind::Bring {
+			source: BringSource::BuiltinModule(name),
+			identifier,
+		} if name.name == WINGSDK_EXPECT_MODULE => Some(identifier.as_ref().unwrap_or(name).name.clone()),
+		_ => None,
+	});
+	let Some(expect_alias) = expect_alias else {
+		return vec![];
+	};
+	let mut finder = SnapshotCallFinder {
+		expect_alias,
+		current_test: None,
+		counts: HashMap::new(),
+		calls: vec![],
+	};
+	finder.visit_scope(scope);
+	finder.calls
+}
+
+/// Reads the stored value of each snapshot from its snapshot file, unless snapshots are being updated
+pub fn load_stored_snapshots(calls: &mut [SnapshotCall], mode: SnapshotMode) {
+	if mode == SnapshotMode::Update {
+		return;
+	}
+	let mut snapshot_files: HashMap<String, Option<serde_json::Map<String, Value>>> = HashMap::new();
+	for call in calls.iter_mut() {
+		let stored = snapshot_files
+			.entry(call.snapshot_file.clone())
+			.or_insert_with(|| read_snapshot_file(&call.snapshot_file));
+		call.expected = stored.as_ref().and_then(|stored| stored.get(&call.id).cloned());
+	}
+}
+
+fn read_snapshot_file(path: &str) -> Option<serde_json::Map<String, Value>> {
+	let content = std::fs::read_to_string(path).ok()?;
+	match serde_json::from_str(&content) {
+		Ok(Value::Object(snapshots)) => Some(snapshots),
+		_ => {
+			report_diagnostic(Diagnostic {
+				message: format!("Invalid snapshot file \"{path}\", expected a JSON object"),
+				span: None,
+				annotations: vec![],
+				hints: vec!["delete the file and run the tests again to recreate it".to_string()],
+				severity: DiagnosticSeverity::Error,
+				code: None,
+			});
+			None
+		}
+	}
+}
+
+struct SnapshotCallFinder {
+	expect_alias: String,
+	current_test: Option<String>,
+	/// How many snapshot calls were found in each test so far ("" for calls outside tests)
+	counts: HashMap<String, usize>,
+	calls: Vec<SnapshotCall>,
+}
+
+impl SnapshotCallFinder {
+	fn is_snapshot_call(&self, expr: &Expr) -> bool {
+		let ExprKind::Call {
+			callee: CalleeKind::Expr(callee),
+			..
+		} = &expr.kind
+		else {
+			return false;
+		};
+		let ExprKind::Reference(Reference::InstanceMember { object, property, .. }) = &callee.kind else {
+			return false;
+		};
+		property.name == SNAPSHOT_METHOD
+			&& matches!(&object.kind, ExprKind::Reference(Reference::Identifier(module)) if module.name == self.expect_alias)
+	}
+}
+
+impl<'a> Visit<'a> for SnapshotCallFinder {
+	fn visit_expr(&mut self, node: &'a Expr) {
+		if let Some(name) = test_name(node) {
+			let outer_test = self.current_test.replace(name);
+			visit::visit_expr(self, node);
+			self.current_test = outer_test;
+			return;
+		}
+
+		if self.is_snapshot_call(node) {
+			let test = self.current_test.clone().unwrap_or_default();
+			let count = self.counts.entry(test.clone()).or_default();
+			*count += 1;
+			let id = if test.is_empty() {
+				format!("snapshot {count}")
+			} else {
+				format!("{test} {count}")
+			};
+			self.calls.push(SnapshotCall {
+				id,
+				snapshot_file: format!("{}{SNAPSHOT_FILE_SUFFIX}", node.span.file_id),
+				span: node.span.clone(),
+				expected: None,
+				expr_id: node.id,
+			});
+		}
+		visit::visit_expr(self, node);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use camino::Utf8PathBuf;
+
+	use super::{find_snapshot_calls, load_stored_snapshots, SnapshotMode};
+	use crate::ast::AstContext;
+	use crate::test_utils::parse_source;
+
+	#[test]
+	fn assigns_stable_ids_and_loads_stored_values() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let main = Utf8PathBuf::from_path_buf(temp_dir.path().join("main.test.w")).unwrap();
+		let source = r#"
+bring expect as e;
+e.snapshot("preflight");
+test "put" {
+  e.snapshot(1);
+  if true {
+    e.snapshot(2);
+  }
+}
+test "get" {
+  e.snapshot(3);
+  expect.snapshot(4);
+}
+"#;
+		fs::write(&main, source).unwrap();
+		fs::write(format!("{main}.snap.json"), r#"{ "put 2": [1, 2] }"#).unwrap();
+
+		let ast_ctx = AstContext::new();
+		let ast = parse_source(&main, source, &ast_ctx);
+
+		let mut calls = find_snapshot_calls(&ast);
+		assert_eq!(
+			calls.iter().map(|call| call.id.as_str()).collect::<Vec<_>>(),
+			vec!["snapshot 1", "put 1", "put 2", "get 1"]
+		);
+		assert_eq!(calls[2].span.start.line, 6);
+
+		load_stored_snapshots(&mut calls, SnapshotMode::Record);
+		assert_eq!(calls[2].expected, Some(serde_json::json!([1, 2])));
+		assert_eq!(calls[1].expected, None);
+
+		load_stored_snapshots(&mut calls[2..], SnapshotMode::Update);
+		assert_eq!(calls[2].expected, Some(serde_json::json!([1, 2])));
+	}
+}
@@ -4,6 +4,8 @@ use camino::Utf8PathBuf;
 use indexmap::IndexMap;
 use petgraph::visit::EdgeRef;
 
+use crate::paths::path_key;
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct File {
 	/// The path of the file relative to the entrypoint of the compilation.
@@ -33,7 +35,14 @@ impl File {
 #[derive(Default)]
 pub struct FileGraph {
 	graph: petgraph::stable_graph::StableDiGraph<File, ()>,
-	path_to_node_index: IndexMap<File, petgraph::graph::NodeIndex>,
+	/// The nodes of files, by their package and the key of their path (see `node_key`)
+	path_to_node_index: IndexMap<(String, String), petgraph::graph::NodeIndex>,
+}
+
+/// Files are looked up by the keys of their paths, so different spellings of a path (e.g. in different case on
+/// Windows, or with different separators) are the same file
+fn node_key(file: &File) -> (String, String) {
+	(path_key(&file.path), file.package.clone())
 }
 
 impl FileGraph {
@@ -64,14 +73,14 @@ impl FileGraph {
 
 	/// Returns true if the given file is in the graph
 	pub fn contains_file(&mut self, file: &File) -> bool {
-		self.path_to_node_index.contains_key(file)
+		self.path_to_node_index.contains_key(&node_key(file))
 	}
 
 	/// Returns a list of the direct dependencies of the given file.
 	/// (does not include all transitive dependencies)
 	/// The file path must be relative to the root of the file graph.
 	pub fn dependencies_of(&self, file: &File) -> Vec<&File> {
-		let node_index = self.path_to_node_index.get(file).expect("path not in graph");
+		let node_index = self.path_to_node_index.get(&node_key(file)).expect("path not in graph");
 		self
 			.graph
 			.edges(*node_index)
@@ -108,12 +117,13 @@ impl FileGraph {
 	}
 
 	fn get_or_insert_node_index(&mut self, file: &File) -> petgraph::graph::NodeIndex {
-		if let Some(node_index) = self.path_to_node_index.get(file) {
+		let key = node_key(file);
+		if let Some(node_index) = self.path_to_node_index.get(&key) {
 			return *node_index;
 		}
 
 		let node_index = self.graph.add_node(file.to_owned());
-		self.path_to_node_index.insert(file.to_owned(), node_index);
+		self.path_to_node_index.insert(key, node_index);
 		node_index
 	}
 
@@ -243,6 +253,21 @@ mod tests {
 		assert_eq!(err, ["a", "b", "c"]);
 	}
 
+	#[test]
+	fn windows_paths_are_compared_ignoring_case() {
+		// graph with two nodes, where A depends on B, and B is referenced by different spellings of its path
+		let mut graph = FileGraph::default();
+		let a = File::new(r"C:\project\main.w", "pkg");
+		let b = File::new(r"C:\project\lib\util.w", "pkg");
+		graph.set_file_deps(&a, [&b]);
+		graph.set_file_deps(&File::new("c:/Project/Lib/Util.w", "pkg"), []);
+		assert!(graph.contains_file(&File::new("C:/PROJECT/lib/util.w", "pkg")));
+		assert_eq!(
+			file_paths(&graph.toposort().unwrap()),
+			[r"C:\project\lib\util.w", r"C:\project\main.w"]
+		);
+	}
+
 	#[test]
 	fn toposort_cycle_and_unrelated_component() {
 		// graph with 5 nodes, where A depends on B, and {C,D,E} form a cycle
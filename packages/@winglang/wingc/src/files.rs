@@ -6,10 +6,18 @@ use std::{
 	io::Write,
 };
 
-use camino::{Utf8Path, Utf8PathBuf};
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use itertools::Itertools;
 
 use crate::diagnostic::{Diagnostic, DiagnosticSeverity};
 
+/// The directory (in the output directory) files are written to before they're moved into place
+const STAGING_DIR: &str = ".staging";
+
+/// Written to the output directory with the names of the files emitted to it, so the next compilation knows which of
+/// them it may remove
+pub const EMITTED_FILES_MANIFEST: &str = ".emitted.json";
+
 #[derive(Debug)]
 pub enum FilesError {
 	DuplicateFile(Utf8PathBuf),
@@ -33,6 +41,7 @@ impl From<FilesError> for Diagnostic {
 			annotations: vec![],
 			hints: vec![],
 			severity: DiagnosticSeverity::Error,
+			code: None,
 		}
 	}
 }
@@ -75,20 +84,124 @@ impl Files {
 		self.data.contains_key(path.as_ref())
 	}
 
-	/// Write all files to the given directory.
+	/// Add all files of `other`, returning an error if one of them has the same name as a file that already exists.
+	pub fn extend(&mut self, other: Files) -> Result<(), FilesError> {
+		other
+			.data
+			.into_iter()
+			.try_for_each(|(path, content)| self.add_file(path, content))
+	}
+
+	/// Iterate over all files and their contents, in no particular order.
+	pub fn iter(&self) -> impl Iterator<Item = (&Utf8PathBuf, &String)> {
+		self.data.iter()
+	}
+
+	/// Write all files to the given directory. Files whose content didn't change aren't touched, and the others are
+	/// written to a staging directory first and then moved into place, so readers of the directory never see a
+	/// half-written file. Nothing is changed if a file can't be written to the staging directory, but if moving one of
+	/// them into place fails, the files moved before it stay in place (each file is either fully updated or untouched).
 	pub fn emit_files(&self, out_dir: &Utf8Path) -> Result<(), FilesError> {
-		for (path, content) in &self.data {
-			let full_path = out_dir.join(path);
+		let changed = self
+			.data
+			.iter()
+			.filter(|(path, content)| fs::read(out_dir.join(path)).map_or(true, |existing| existing != content.as_bytes()))
+			.collect::<Vec<_>>();
+		if changed.is_empty() {
+			return Ok(());
+		}
 
-			// create parent directories if they don't exist
-			if let Some(parent) = full_path.parent() {
+		let staging_dir = out_dir.join(STAGING_DIR);
+		let staged = changed.iter().try_for_each(|(path, content)| {
+			let staged_path = staging_dir.join(path);
+			if let Some(parent) = staged_path.parent() {
 				fs::create_dir_all(parent).map_err(FilesError::IoError)?;
 			}
+			write_file(&staged_path, content)
+		});
+		let moved = staged.and_then(|()| {
+			changed.iter().try_for_each(|(path, _)| {
+				let full_path = out_dir.join(path);
+				// create parent directories if they don't exist
+				if let Some(parent) = full_path.parent() {
+					fs::create_dir_all(parent).map_err(FilesError::IoError)?;
+				}
+				fs::rename(staging_dir.join(path), full_path).map_err(FilesError::IoError)
+			})
+		});
+		_ = fs::remove_dir_all(&staging_dir);
+		moved
+	}
 
-			update_file(&full_path, content)?;
+	/// Write all files to the given directory (see `emit_files`), and remove the files a previous call emitted there
+	/// that aren't part of the output anymore. Files in the directory that weren't emitted by this method are left alone.
+	///
+	/// The manifest of emitted files is written before anything else and lists both the previous and the new files, so
+	/// if this fails part way, the next call still removes the files that turn out to be orphans. It only lists the new
+	/// files once all of them are in place and the orphans are removed.
+	pub fn emit_files_removing_orphans(&self, out_dir: &Utf8Path) -> Result<(), FilesError> {
+		let manifest_path = out_dir.join(EMITTED_FILES_MANIFEST);
+		let previous: Vec<String> = fs::read_to_string(&manifest_path)
+			.ok()
+			.and_then(|manifest| serde_json::from_str(&manifest).ok())
+			.unwrap_or_default();
+		let orphans = previous
+			.into_iter()
+			.filter(|path| !self.contains_file(path.as_str()))
+			.collect::<Vec<_>>();
+
+		self.emit_manifest(out_dir, &orphans)?;
+		self.emit_files(out_dir)?;
+
+		let mut remaining = vec![];
+		let mut result = Ok(());
+		for orphan in orphans {
+			if let Err(err) = remove_orphan(out_dir, Utf8Path::new(&orphan)) {
+				remaining.push(orphan);
+				result = result.and(Err(err));
+			}
+		}
+		self.emit_manifest(out_dir, &remaining)?;
+		result
+	}
+
+	/// Write the manifest of emitted files: the files of `self` and the given orphans that still have to be removed
+	fn emit_manifest(&self, out_dir: &Utf8Path, orphans: &[String]) -> Result<(), FilesError> {
+		let emitted = self
+			.data
+			.keys()
+			.map(|path| path.as_str())
+			.chain(orphans.iter().map(|path| path.as_str()))
+			.sorted()
+			.collect::<Vec<_>>();
+		let mut manifest = Files::new();
+		manifest.update_file(
+			EMITTED_FILES_MANIFEST,
+			serde_json::to_string_pretty(&emitted).expect("serialize emitted files"),
+		);
+		manifest.emit_files(out_dir)
+	}
+}
+
+/// Remove a file a previous compilation emitted, and the directories it leaves empty
+fn remove_orphan(out_dir: &Utf8Path, orphan: &Utf8Path) -> Result<(), FilesError> {
+	// Never remove anything outside the output directory, even if the manifest was tampered with
+	if !orphan.components().all(|c| matches!(c, Utf8Component::Normal(_))) {
+		return Ok(());
+	}
+	let orphan_path = out_dir.join(orphan);
+	match fs::remove_file(&orphan_path) {
+		Ok(()) => {}
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+		Err(err) => return Err(FilesError::IoError(err)),
+	}
+	// Removing a directory that isn't empty fails
+	for dir in orphan_path.ancestors().skip(1).take_while(|dir| *dir != out_dir) {
+		if fs::remove_dir(dir).is_err() {
+			break;
 		}
-		Ok(())
 	}
+	Ok(())
 }
 
 /// Write file to disk
@@ -230,6 +343,72 @@ mod tests {
 		let file1_content = fs::read_to_string(file1_path).expect("Failed to read file");
 		assert_eq!(file1_content, "content1");
 	}
+	#[test]
+	fn test_emit_files_removes_orphans() {
+		let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+		let out_dir = Utf8Path::from_path(temp_dir.path()).expect("invalid unicode path");
+
+		write_file(&out_dir.join("unrelated"), "content").expect("Failed to write file");
+
+		let mut files = Files::new();
+		files
+			.add_file("file1", "content1".to_owned())
+			.expect("Failed to add file");
+		files
+			.add_file("subdir/file2", "content2".to_owned())
+			.expect("Failed to add file");
+		assert!(files.emit_files_removing_orphans(out_dir).is_ok());
+		assert!(out_dir.join("subdir/file2").exists());
+
+		let mut files = Files::new();
+		files
+			.add_file("file1", "content1".to_owned())
+			.expect("Failed to add file");
+		assert!(files.emit_files_removing_orphans(out_dir).is_ok());
+
+		// Verify that only the file the first call emitted and the second didn't was removed
+		assert!(out_dir.join("file1").exists());
+		assert!(out_dir.join("unrelated").exists());
+		assert!(!out_dir.join("subdir/file2").exists());
+		assert!(!out_dir.join("subdir").exists());
+		assert!(!out_dir.join(STAGING_DIR).exists());
+	}
+
+	#[test]
+	fn test_emit_files_removes_orphans_after_a_failure() {
+		let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+		let out_dir = Utf8Path::from_path(temp_dir.path()).expect("invalid unicode path");
+
+		let mut files = Files::new();
+		files
+			.add_file("file1", "content1".to_owned())
+			.expect("Failed to add file");
+		assert!(files.emit_files_removing_orphans(out_dir).is_ok());
+
+		// A file where a directory has to be created makes emitting fail after the manifest is written
+		write_file(&out_dir.join("blocked"), "content").expect("Failed to write file");
+		let mut files = Files::new();
+		files
+			.add_file("blocked/file2", "content2".to_owned())
+			.expect("Failed to add file");
+		assert!(files.emit_files_removing_orphans(out_dir).is_err());
+		assert!(out_dir.join("file1").exists());
+
+		fs::remove_file(out_dir.join("blocked")).expect("Failed to remove file");
+		let mut files = Files::new();
+		files
+			.add_file("file3", "content3".to_owned())
+			.expect("Failed to add file");
+		assert!(files.emit_files_removing_orphans(out_dir).is_ok());
+
+		// Verify that the files of both previous calls were removed, even though the second one failed
+		assert!(!out_dir.join("file1").exists());
+		assert!(!out_dir.join("blocked").exists());
+		assert!(out_dir.join("file3").exists());
+		let manifest = fs::read_to_string(out_dir.join(EMITTED_FILES_MANIFEST)).expect("Failed to read file");
+		assert_eq!(serde_json::from_str::<Vec<String>>(&manifest).unwrap(), vec!["file3"]);
+	}
+
 	#[test]
 	fn test_update_file() {
 		let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
@@ -1,13 +1,16 @@
 use crate::ast::{
 	ArgList, BringSource, CalleeKind, CatchBlock, Class, ClassField, ElseIfBlock, ElseIfLetBlock, ElseIfs, Enum,
 	ExplicitLift, Expr, ExprKind, FunctionBody, FunctionDefinition, FunctionParameter, FunctionSignature, IfLet,
-	Interface, InterpolatedString, InterpolatedStringPart, Intrinsic, LiftQualification, Literal, New, Reference, Scope,
-	Stmt, StmtKind, Struct, StructField, Symbol, TypeAnnotation, TypeAnnotationKind, UserDefinedType,
+	IfLetCondition, Interface, InterpolatedString, InterpolatedStringPart, Intrinsic, LiftQualification, Literal, New,
+	Reference, Scope, Stmt, StmtKind, Struct, StructField, Symbol, TypeAnnotation, TypeAnnotationKind, UserDefinedType,
 };
 
 /// Similar to the `visit` module in `wingc` except each method takes ownership of an
 /// AST node instead of a reference to it, and returns a new AST node instance.
 /// This trait can be useful for AST transformations.
+///
+/// Like in `visit`, the default implementations handle every kind of node explicitly, and so should any
+/// transformation that matches on the kind of a node.
 pub trait Fold {
 	fn fold_scope(&mut self, node: Scope) -> Scope {
 		fold_scope(self, node)
@@ -90,6 +93,7 @@ where
 				BringSource::TrustedModule(name, module_dir) => BringSource::TrustedModule(f.fold_symbol(name), module_dir),
 				BringSource::WingLibrary(name, module_dir) => BringSource::WingLibrary(f.fold_symbol(name), module_dir),
 				BringSource::JsiiModule(name) => BringSource::JsiiModule(f.fold_symbol(name)),
+				BringSource::NpmModule(name) => BringSource::NpmModule(f.fold_symbol(name)),
 				BringSource::WingFile(path) => BringSource::WingFile(path),
 				BringSource::Directory(path) => BringSource::Directory(path),
 			},
@@ -100,18 +104,22 @@ where
 			var_name,
 			initial_value,
 			type_,
+			access,
 		} => StmtKind::Let {
 			reassignable,
 			var_name: f.fold_symbol(var_name),
 			initial_value: f.fold_expr(initial_value),
 			type_: type_.map(|type_| f.fold_type_annotation(type_)),
+			access,
 		},
 		StmtKind::ForLoop {
 			iterator,
+			second_iterator,
 			iterable,
 			statements,
 		} => StmtKind::ForLoop {
 			iterator: f.fold_symbol(iterator),
+			second_iterator: second_iterator.map(|s| f.fold_symbol(s)),
 			iterable: f.fold_expr(iterable),
 			statements: f.fold_scope(statements),
 		},
@@ -119,8 +127,20 @@ where
 			condition: f.fold_expr(condition),
 			statements: f.fold_scope(statements),
 		},
+		StmtKind::WhileLet {
+			reassignable,
+			var_name,
+			value,
+			statements,
+		} => StmtKind::WhileLet {
+			reassignable,
+			var_name: f.fold_symbol(var_name),
+			value: f.fold_expr(value),
+			statements: f.fold_scope(statements),
+		},
 		StmtKind::IfLet(IfLet {
 			value,
+			conditions,
 			statements,
 			reassignable,
 			var_name,
@@ -128,6 +148,21 @@ where
 			else_statements,
 		}) => StmtKind::IfLet(IfLet {
 			value: f.fold_expr(value),
+			conditions: conditions
+				.into_iter()
+				.map(|condition| match condition {
+					IfLetCondition::Let {
+						reassignable,
+						var_name,
+						value,
+					} => IfLetCondition::Let {
+						reassignable,
+						var_name: f.fold_symbol(var_name),
+						value: f.fold_expr(value),
+					},
+					IfLetCondition::Guard(guard) => IfLetCondition::Guard(f.fold_expr(guard)),
+				})
+				.collect(),
 			statements: f.fold_scope(statements),
 			reassignable,
 			var_name: f.fold_symbol(var_name),
@@ -281,7 +316,7 @@ where
 		methods: node
 			.methods
 			.into_iter()
-			.map(|(name, sig, doc)| (f.fold_symbol(name), f.fold_function_signature(sig), doc))
+			.map(|(name, sig, doc, is_static)| (f.fold_symbol(name), f.fold_function_signature(sig), doc, is_static))
 			.collect(),
 		extends: node
 			.extends
@@ -316,6 +351,12 @@ where
 	Enum {
 		name: f.fold_symbol(node.name),
 		values: node.values.into_iter().map(|v| (f.fold_symbol(v.0), v.1)).collect(),
+		backing_values: node
+			.backing_values
+			.into_iter()
+			.map(|v| (f.fold_symbol(v.0), v.1))
+			.collect(),
+		is_flags: node.is_flags,
 		access: node.access,
 	}
 }
@@ -338,12 +379,26 @@ where
 			name: f.fold_symbol(intrinsic.name),
 			kind: intrinsic.kind,
 		}),
-		ExprKind::Call { callee, arg_list } => ExprKind::Call {
+		ExprKind::InlineJs {
+			inputs,
+			return_type,
+			code,
+		} => ExprKind::InlineJs {
+			inputs: inputs.into_iter().map(|input| f.fold_expr(input)).collect(),
+			return_type: return_type.map(|t| f.fold_type_annotation(t)),
+			code,
+		},
+		ExprKind::Call {
+			callee,
+			arg_list,
+			optional_accessor,
+		} => ExprKind::Call {
 			callee: match callee {
 				CalleeKind::Expr(expr) => CalleeKind::Expr(Box::new(f.fold_expr(*expr))),
 				CalleeKind::SuperCall(method) => CalleeKind::SuperCall(f.fold_symbol(method)),
 			},
 			arg_list: f.fold_args(arg_list),
+			optional_accessor,
 		},
 		ExprKind::Unary { op, exp } => ExprKind::Unary {
 			op,
@@ -387,6 +442,7 @@ where
 			element: Box::new(f.fold_expr(*element)),
 		},
 		ExprKind::FunctionClosure(def) => ExprKind::FunctionClosure(f.fold_function_definition(def)),
+		ExprKind::Error => ExprKind::Error,
 	};
 	Expr {
 		id: node.id,
@@ -457,9 +513,17 @@ where
 			type_name: f.fold_user_defined_type(type_name),
 			property: f.fold_symbol(property),
 		},
-		Reference::ElementAccess { object, index } => Reference::ElementAccess {
+		Reference::ElementAccess {
+			object,
+			index,
+			optional_accessor,
+		} => Reference::ElementAccess {
 			object: Box::new(f.fold_expr(*object)),
 			index: Box::new(f.fold_expr(*index)),
+			optional_accessor,
+		},
+		Reference::SuperMember { property } => Reference::SuperMember {
+			property: f.fold_symbol(property),
 		},
 	}
 }
@@ -536,6 +600,9 @@ where
 		TypeAnnotationKind::Duration => TypeAnnotationKind::Duration,
 		TypeAnnotationKind::Datetime => TypeAnnotationKind::Datetime,
 		TypeAnnotationKind::Regex => TypeAnnotationKind::Regex,
+		TypeAnnotationKind::Never => TypeAnnotationKind::Never,
+		TypeAnnotationKind::Unknown => TypeAnnotationKind::Unknown,
+		TypeAnnotationKind::Literal(values) => TypeAnnotationKind::Literal(values),
 		TypeAnnotationKind::Void => TypeAnnotationKind::Void,
 		TypeAnnotationKind::Json => TypeAnnotationKind::Json,
 		TypeAnnotationKind::MutJson => TypeAnnotationKind::MutJson,
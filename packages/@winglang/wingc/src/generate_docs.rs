@@ -1,19 +1,24 @@
+use std::{
+	collections::{HashMap, HashSet},
+	fs,
+};
+
 use camino::{Utf8Path, Utf8PathBuf};
 use indexmap::IndexMap;
 use wingii::{fqn::FQN, type_system::TypeSystem};
 
 use crate::{
-	ast::{AccessModifier, Phase, Scope},
+	ast::{AccessModifier, AstContext, Phase, Scope},
 	closure_transform::ClosureTransformer,
 	diagnostic::{found_errors, report_diagnostic, Diagnostic, DiagnosticSeverity},
-	emit_warning_for_unsupported_package_managers,
 	file_graph::{File, FileGraph},
 	files::Files,
 	find_nearest_wing_project_dir,
 	fold::Fold,
 	jsify::JSifier,
 	lifting::LiftVisitor,
-	parser::{as_wing_library, normalize_path, parse_wing_project},
+	parser::{as_wing_library, parse_wing_project, SourceOverlays, STRUCT_STATICS_CLASS_PREFIX},
+	paths::normalize_path,
 	type_check::{
 		type_reference_transform::TypeReferenceTransformer, ClassLike, FunctionSignature, HasFqn, Namespace, NamespaceRef,
 		SymbolEnvOrNamespace, SymbolKind, Type, TypeRef, Types, UnsafeRef, VariableKind,
@@ -35,6 +40,7 @@ pub fn generate_docs(project_dir: &Utf8Path) -> Result<String, ()> {
 			annotations: vec![],
 			hints: vec![],
 			severity: DiagnosticSeverity::Error,
+			code: None,
 		});
 		return Err(());
 	}
@@ -51,9 +57,11 @@ pub fn generate_docs(project_dir: &Utf8Path) -> Result<String, ()> {
 	let mut file_graph = FileGraph::default();
 	let mut tree_sitter_trees = IndexMap::new();
 	let mut asts = IndexMap::new();
+	let ast_ctx = AstContext::new();
 	let topo_sorted_files = parse_wing_project(
 		&source_file,
-		None,
+		&ast_ctx,
+		&SourceOverlays::new(),
 		&mut files,
 		&mut file_graph,
 		&mut library_roots,
@@ -61,15 +69,13 @@ pub fn generate_docs(project_dir: &Utf8Path) -> Result<String, ()> {
 		&mut asts,
 	);
 
-	emit_warning_for_unsupported_package_managers(&project_dir);
-
 	// -- DESUGARING PHASE --
 
 	// Transform all inflight closures defined in preflight into single-method resources
 	let mut asts = asts
 		.into_iter()
 		.map(|(path, scope)| {
-			let mut inflight_transformer = ClosureTransformer::new();
+			let mut inflight_transformer = ClosureTransformer::new(&files, &ast_ctx);
 			let scope = inflight_transformer.fold_scope(scope);
 			(path, scope)
 		})
@@ -149,6 +155,8 @@ const HIDDEN_METHODS: [&str; 6] = [
 
 fn generate_docs_helper(types: &Types, project_dir: &Utf8Path) -> Result<String, ()> {
 	let mut docs = String::new();
+	print_package_overview(project_dir, &mut docs);
+
 	let root_env = types.source_file_envs.get(project_dir).expect("No root env found");
 	let ns = match root_env {
 		SymbolEnvOrNamespace::Namespace(ns) => *ns,
@@ -159,20 +167,72 @@ fn generate_docs_helper(types: &Types, project_dir: &Utf8Path) -> Result<String,
 	docs.push_str("## API Reference\n\n");
 
 	let mut public_types = vec![];
+	let mut struct_statics = HashMap::new();
 	let namespaces = find_documentable_namespaces_recursive(&ns);
 	for ns in namespaces {
 		public_types.extend(find_public_types_in_namespace(&ns));
+		struct_statics.extend(find_struct_statics_in_namespace(&ns));
 	}
 
+	// Type references to any of these get linked to their own section of the docs
+	let documented_fqns = public_types.iter().filter_map(|t| t.fqn()).collect::<HashSet<_>>();
+
 	print_table_of_contents(&public_types, &mut docs);
-	print_classes(&public_types, &mut docs);
-	print_interfaces(&public_types, &mut docs);
-	print_structs(&public_types, &mut docs);
+	print_classes(&public_types, &documented_fqns, &mut docs);
+	print_interfaces(&public_types, &documented_fqns, &mut docs);
+	print_structs(&public_types, &struct_statics, &documented_fqns, &mut docs);
 	print_enums(&public_types, &mut docs);
 
 	Ok(docs)
 }
 
+/// Print a landing section for the package based on its README, or its package.json metadata if there's no README.
+fn print_package_overview(project_dir: &Utf8Path, docs: &mut String) {
+	if let Ok(readme) = fs::read_to_string(project_dir.join("README.md")) {
+		if !readme.trim().is_empty() {
+			docs.push_str(readme.trim());
+			docs.push_str("\n\n");
+			return;
+		}
+	}
+
+	let Some(package_json) = fs::read_to_string(project_dir.join("package.json"))
+		.ok()
+		.and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+	else {
+		return;
+	};
+
+	if let Some(name) = package_json.get("name").and_then(|n| n.as_str()) {
+		docs.push_str("# ");
+		docs.push_str(name);
+		docs.push_str("\n\n");
+	}
+	if let Some(description) = package_json.get("description").and_then(|d| d.as_str()) {
+		docs.push_str(description);
+		docs.push_str("\n\n");
+	}
+
+	// "repository" can either be a url or an object with a "url" field
+	let repository = package_json
+		.get("repository")
+		.and_then(|r| r.as_str().or_else(|| r.get("url").and_then(|u| u.as_str())));
+	let homepage = package_json.get("homepage").and_then(|h| h.as_str());
+	if homepage.is_some() || repository.is_some() {
+		if let Some(homepage) = homepage {
+			docs.push_str("- Homepage: ");
+			docs.push_str(homepage);
+			docs.push_str("\n");
+		}
+		if let Some(repository) = repository {
+			docs.push_str("- Repository: ");
+			docs.push_str(repository);
+			docs.push_str("\n");
+		}
+		docs.push_str("\n");
+	}
+}
+
 /// Return a list of all of the public namespaces directly in this namespace and all of its children, including
 /// this namespace if it has any public elements.
 fn find_documentable_namespaces_recursive(ns: &Namespace) -> Vec<NamespaceRef> {
@@ -196,7 +256,11 @@ fn find_documentable_namespaces_recursive(ns: &Namespace) -> Vec<NamespaceRef> {
 fn find_public_types_in_namespace(ns: &Namespace) -> Vec<TypeRef> {
 	let mut entries = vec![];
 	for env in ns.envs.iter() {
-		for (_name, entry) in env.symbol_map.iter() {
+		for (name, entry) in env.symbol_map.iter() {
+			// Struct static methods are documented as part of their struct
+			if name.starts_with(STRUCT_STATICS_CLASS_PREFIX) {
+				continue;
+			}
 			if entry.access == AccessModifier::Public {
 				if let SymbolKind::Type(typ) = entry.kind {
 					entries.push(typ);
@@ -207,6 +271,27 @@ fn find_public_types_in_namespace(ns: &Namespace) -> Vec<TypeRef> {
 	return entries;
 }
 
+/// Return the classes holding the static methods of public structs in this namespace, keyed by the struct's FQN.
+fn find_struct_statics_in_namespace(ns: &Namespace) -> Vec<(String, TypeRef)> {
+	let mut entries = vec![];
+	for env in ns.envs.iter() {
+		for (name, entry) in env.symbol_map.iter() {
+			let Some(struct_name) = name.strip_prefix(STRUCT_STATICS_CLASS_PREFIX) else {
+				continue;
+			};
+			if entry.access != AccessModifier::Public {
+				continue;
+			}
+			if let SymbolKind::Type(typ) = entry.kind {
+				let fqn = typ.fqn().expect("Type has no FQN");
+				let (prefix, _) = fqn.rsplit_once('.').expect("FQN has no namespace");
+				entries.push((format!("{prefix}.{struct_name}"), typ));
+			}
+		}
+	}
+	entries
+}
+
 fn simplified_fqn(typ: &TypeRef) -> String {
 	let fqn = typ.fqn().expect("Type has no FQN");
 	let fqn = FQN::from(fqn.as_str());
@@ -284,7 +369,7 @@ fn print_table_of_contents(types: &[TypeRef], docs: &mut String) {
 	docs.push_str("\n");
 }
 
-fn print_classes(types: &[TypeRef], docs: &mut String) {
+fn print_classes(types: &[TypeRef], documented_fqns: &HashSet<String>, docs: &mut String) {
 	for typ in types {
 		if let Type::Class(ref class) = **typ {
 			docs.push_str("### ");
@@ -304,13 +389,13 @@ fn print_classes(types: &[TypeRef], docs: &mut String) {
 			docs.push_str("\n\n");
 
 			print_constructors(docs, class);
-			print_properties(docs, class);
-			print_methods(docs, class);
+			print_properties(docs, class, documented_fqns);
+			print_methods(docs, class, documented_fqns);
 		}
 	}
 }
 
-fn print_interfaces(types: &[TypeRef], docs: &mut String) {
+fn print_interfaces(types: &[TypeRef], documented_fqns: &HashSet<String>, docs: &mut String) {
 	for typ in types {
 		if let Type::Interface(ref interface) = **typ {
 			docs.push_str("### ");
@@ -327,13 +412,18 @@ fn print_interfaces(types: &[TypeRef], docs: &mut String) {
 			}
 			docs.push_str("\n\n");
 
-			print_properties(docs, interface);
-			print_methods(docs, interface);
+			print_properties(docs, interface, documented_fqns);
+			print_methods(docs, interface, documented_fqns);
 		}
 	}
 }
 
-fn print_structs(types: &[TypeRef], docs: &mut String) {
+fn print_structs(
+	types: &[TypeRef],
+	struct_statics: &HashMap<String, TypeRef>,
+	documented_fqns: &HashSet<String>,
+	docs: &mut String,
+) {
 	for typ in types {
 		if let Type::Struct(ref struct_) = **typ {
 			docs.push_str("### ");
@@ -350,7 +440,12 @@ fn print_structs(types: &[TypeRef], docs: &mut String) {
 			}
 			docs.push_str("\n\n");
 
-			print_properties(docs, struct_);
+			print_properties(docs, struct_, documented_fqns);
+
+			let statics = struct_statics.get(&typ.fqn().expect("Type has no FQN"));
+			if let Some(class) = statics.and_then(|t| t.as_class()) {
+				print_methods(docs, class, documented_fqns);
+			}
 		}
 	}
 }
@@ -409,7 +504,8 @@ fn print_constructors(docs: &mut String, class: &impl ClassLike) {
 				.type_
 				.as_function_sig()
 				.expect("Constructor is not a function");
-			print_signature(&VariableKind::InstanceMember, "new", sig, docs);
+			// Links aren't rendered inside code blocks
+			print_signature(&VariableKind::InstanceMember, "new", sig, &HashSet::new(), docs);
 			docs.push_str("\n");
 		}
 		docs.push_str("```\n");
@@ -418,7 +514,7 @@ fn print_constructors(docs: &mut String, class: &impl ClassLike) {
 	docs.push_str("\n");
 }
 
-fn print_properties(docs: &mut String, class: &impl ClassLike) {
+fn print_properties(docs: &mut String, class: &impl ClassLike, documented_fqns: &HashSet<String>) {
 	docs.push_str("#### Properties\n\n");
 
 	let mut fields = class.fields(true).collect::<Vec<_>>();
@@ -433,7 +529,7 @@ fn print_properties(docs: &mut String, class: &impl ClassLike) {
 			docs.push_str("| <code>");
 			docs.push_str(&name);
 			docs.push_str("</code> | <code>");
-			docs.push_str(&prop_info.type_.to_string());
+			docs.push_str(&print_type(prop_info.type_, documented_fqns));
 			docs.push_str("</code> | ");
 			let prop_summary = prop_info.docs.and_then(|d| d.summary);
 			if let Some(prop_summary) = prop_summary {
@@ -448,7 +544,7 @@ fn print_properties(docs: &mut String, class: &impl ClassLike) {
 	docs.push_str("\n");
 }
 
-fn print_methods(docs: &mut String, class: &impl ClassLike) {
+fn print_methods(docs: &mut String, class: &impl ClassLike, documented_fqns: &HashSet<String>) {
 	docs.push_str("#### Methods\n\n");
 
 	let mut methods = class.methods(true).collect::<Vec<_>>();
@@ -464,7 +560,7 @@ fn print_methods(docs: &mut String, class: &impl ClassLike) {
 			let sig = method_info.type_.as_function_sig().expect("Method is not a function");
 
 			docs.push_str("| <code>");
-			print_signature(&method_info.kind, &name, sig, docs);
+			print_signature(&method_info.kind, &name, sig, documented_fqns, docs);
 			docs.push_str("</code> | ");
 			let method_summary = method_info.docs.and_then(|d| d.summary);
 			if let Some(method_summary) = method_summary {
@@ -479,7 +575,13 @@ fn print_methods(docs: &mut String, class: &impl ClassLike) {
 	docs.push_str("\n");
 }
 
-fn print_signature(var_kind: &VariableKind, name: &str, sig: &FunctionSignature, docs: &mut String) {
+fn print_signature(
+	var_kind: &VariableKind,
+	name: &str,
+	sig: &FunctionSignature,
+	documented_fqns: &HashSet<String>,
+	docs: &mut String,
+) {
 	if var_kind == &VariableKind::StaticMember {
 		docs.push_str("static ");
 	}
@@ -495,8 +597,27 @@ fn print_signature(var_kind: &VariableKind, name: &str, sig: &FunctionSignature,
 		}
 		docs.push_str(&param.name);
 		docs.push_str(": ");
-		docs.push_str(&param.typeref.to_string());
+		docs.push_str(&print_type(param.typeref, documented_fqns));
 	}
 	docs.push_str("): ");
-	docs.push_str(&sig.return_type.to_string());
+	docs.push_str(&print_type(sig.return_type, documented_fqns));
+}
+
+/// Render a type the same way as its `Display` implementation, linking every type that's documented on this page
+fn print_type(typ: TypeRef, documented_fqns: &HashSet<String>) -> String {
+	match *typ {
+		Type::Optional(inner) if inner.is_closure() => format!("({})?", print_type(inner, documented_fqns)),
+		Type::Optional(inner) => format!("{}?", print_type(inner, documented_fqns)),
+		Type::Array(inner) => format!("Array<{}>", print_type(inner, documented_fqns)),
+		Type::MutArray(inner) => format!("MutArray<{}>", print_type(inner, documented_fqns)),
+		Type::Map(inner) => format!("Map<{}>", print_type(inner, documented_fqns)),
+		Type::MutMap(inner) => format!("MutMap<{}>", print_type(inner, documented_fqns)),
+		Type::Set(inner) => format!("Set<{}>", print_type(inner, documented_fqns)),
+		Type::MutSet(inner) => format!("MutSet<{}>", print_type(inner, documented_fqns)),
+		Type::Class(_) | Type::Interface(_) | Type::Struct(_) | Type::Enum(_) => match typ.fqn() {
+			Some(fqn) if documented_fqns.contains(&fqn) => format!("<a href=\"#{fqn}\">{typ}</a>"),
+			_ => typ.to_string(),
+		},
+		_ => typ.to_string(),
+	}
 }
@@ -0,0 +1,400 @@
+//! A serializable, mid-level intermediate representation (IR) of a compiled Wing program.
+//!
+//! The IR is built after the lifting phase, so it captures everything the type checker and the lifting phase learned
+//! about the program: the type and phase of every expression, the classes (including the ones created from inflight
+//! closures) and what each of them lifts. It's meant for tools that analyze Wing programs or generate code for them
+//! outside of this crate. Use `--emit=ir` to write it to the output directory as `ir.json`.
+//!
+//! The IR is an output of the compiler, not a stage of it: `jsify` still generates JavaScript from the AST, and only
+//! summaries derived from what the program lifts (see `permissions`) are built from the IR.
+//!
+//! The IR is versioned through `IR_VERSION`, which must be bumped whenever its shape changes.
+
+use std::collections::BTreeMap;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::{
+	ast::{
+		BringSource, Class, Expr, ExprKind, FunctionDefinition, FunctionParameter, Literal, Reference, Scope, Stmt,
+		StmtKind,
+	},
+	closure_transform::{closure_display_name, CLOSURE_CLASS_PREFIX},
+	diagnostic::WingSpan,
+	type_check::{SymbolKind, Types},
+	visit::{self, Visit},
+};
+
+/// The version of the IR's format
+pub const IR_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+pub struct IrProgram {
+	pub version: u32,
+	/// The files of the program, ordered so that every file comes after the files it brings
+	pub files: Vec<IrFile>,
+}
+
+#[derive(Serialize)]
+pub struct IrFile {
+	pub path: String,
+	/// The file's top level statements
+	pub statements: Vec<IrNode>,
+	/// All the classes declared in the file, including nested ones
+	pub classes: Vec<IrClass>,
+}
+
+/// A statement, expression, scope, function or function parameter
+#[derive(Serialize)]
+pub struct IrNode {
+	/// The kind of the node in snake case, e.g. `let`, `call` or `function`
+	pub kind: &'static str,
+	/// The name the node declares or refers to, if any
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+	/// The value of literals and the operator of unary and binary expressions
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub value: Option<String>,
+	/// The type of expressions
+	#[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+	pub type_: Option<String>,
+	/// The phase of expressions
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub phase: Option<String>,
+	pub span: WingSpan,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub children: Vec<IrNode>,
+}
+
+#[derive(Serialize)]
+pub struct IrClass {
+	pub name: String,
+	pub phase: String,
+	/// Whether the class was created from an inflight closure
+	pub is_closure: bool,
+	/// The readable name of a closure class, derived from where the closure is defined
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub display_name: Option<String>,
+	pub span: WingSpan,
+	/// Map from method to a map from the lifted preflight code to the operations the method performs on it
+	pub lifts: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+	/// The preflight values the class captures
+	pub captures: Vec<IrCapture>,
+}
+
+#[derive(Serialize)]
+pub struct IrCapture {
+	/// The token inflight code uses to refer to the captured value
+	pub token: String,
+	/// The preflight code of the captured value
+	pub code: String,
+	/// Whether this is a field capture (`this.foo`)
+	pub is_field: bool,
+}
+
+/// Builds the IR of a type checked and lifted program. `asts` must be in the order in which the files were type
+/// checked.
+pub fn build_ir(asts: &IndexMap<Utf8PathBuf, Scope>, types: &Types) -> IrProgram {
+	IrProgram {
+		version: IR_VERSION,
+		files: asts
+			.iter()
+			.map(|(path, scope)| build_ir_file(path, scope, types))
+			.collect(),
+	}
+}
+
+pub fn build_ir_file(path: &Utf8Path, scope: &Scope, types: &Types) -> IrFile {
+	let mut builder = IrBuilder {
+		types,
+		nodes: vec![IrNode::new("scope", scope.span.clone())],
+		scopes: vec![scope],
+		classes: vec![],
+	};
+	visit::visit_scope(&mut builder, scope);
+	let root = builder.nodes.pop().expect("the root node is never popped");
+	IrFile {
+		path: path.to_string(),
+		statements: root.children,
+		classes: builder.classes,
+	}
+}
+
+impl IrNode {
+	fn new(kind: &'static str, span: WingSpan) -> Self {
+		Self {
+			kind,
+			name: None,
+			value: None,
+			type_: None,
+			phase: None,
+			span,
+			children: vec![],
+		}
+	}
+
+	fn named(mut self, name: Option<impl ToString>) -> Self {
+		self.name = name.map(|name| name.to_string());
+		self
+	}
+}
+
+struct IrBuilder<'a> {
+	types: &'a Types,
+	/// The nodes we're currently building, innermost last. Finished nodes are added to the children of their parent.
+	nodes: Vec<IrNode>,
+	/// The scopes we're currently in, innermost last
+	scopes: Vec<&'a Scope>,
+	classes: Vec<IrClass>,
+}
+
+impl<'a> IrBuilder<'a> {
+	fn with_node(&mut self, node: IrNode, visit_children: impl FnOnce(&mut Self)) {
+		self.nodes.push(node);
+		visit_children(self);
+		let node = self.nodes.pop().expect("node was pushed above");
+		self
+			.nodes
+			.last_mut()
+			.expect("the root node is never popped")
+			.children
+			.push(node);
+	}
+
+	fn build_class(&self, class: &Class) -> IrClass {
+		let scope = self.scopes.last().expect("classes are always declared in a scope");
+		let env = self.types.get_scope_env(scope);
+		let lifts = match env.lookup(&class.name, None) {
+			Some(SymbolKind::Type(t)) => t.as_class().and_then(|c| c.lifts.as_ref()),
+			_ => None,
+		};
+
+		IrClass {
+			name: class.name.name.clone(),
+			phase: class.phase.to_string(),
+			is_closure: class.name.name.starts_with(CLOSURE_CLASS_PREFIX),
+			display_name: closure_display_name(&class.name.name).map(str::to_string),
+			span: class.span.clone(),
+			lifts: lifts.map_or(BTreeMap::new(), |lifts| {
+				lifts
+					.lifts_qualifications
+					.iter()
+					.map(|(method, qualifications)| {
+						let qualifications = qualifications
+							.iter()
+							.map(|(code, qualification)| (code.clone(), qualification.ops.iter().cloned().collect()))
+							.collect();
+						(method.clone(), qualifications)
+					})
+					.collect()
+			}),
+			captures: lifts.map_or(vec![], |lifts| {
+				lifts
+					.captures
+					.iter()
+					.map(|(token, capture)| IrCapture {
+						token: token.clone(),
+						code: capture.code.clone(),
+						is_field: capture.is_field,
+					})
+					.collect()
+			}),
+		}
+	}
+}
+
+impl<'a> Visit<'a> for IrBuilder<'a> {
+	fn visit_scope(&mut self, node: &'a Scope) {
+		self.with_node(IrNode::new("scope", node.span.clone()), |builder| {
+			builder.scopes.push(node);
+			visit::visit_scope(builder, node);
+			builder.scopes.pop();
+		});
+	}
+
+	fn visit_stmt(&mut self, node: &'a Stmt) {
+		let ir_node = stmt_node(node);
+		self.with_node(ir_node, |builder| visit::visit_stmt(builder, node));
+	}
+
+	fn visit_class(&mut self, node: &'a Class) {
+		let class = self.build_class(node);
+		self.classes.push(class);
+		visit::visit_class(self, node);
+	}
+
+	fn visit_function_definition(&mut self, node: &'a FunctionDefinition) {
+		let ir_node = IrNode::new("function", node.span.clone()).named(node.name.as_ref());
+		self.with_node(ir_node, |builder| visit::visit_function_definition(builder, node));
+	}
+
+	fn visit_function_parameter(&mut self, node: &'a FunctionParameter) {
+		let ir_node = IrNode::new("parameter", node.name.span.clone()).named(Some(&node.name));
+		self.with_node(ir_node, |builder| visit::visit_function_parameter(builder, node));
+	}
+
+	fn visit_expr(&mut self, node: &'a Expr) {
+		let mut ir_node = expr_node(node);
+		ir_node.type_ = self.types.try_get_expr_type(node.id).map(|t| t.to_string());
+		ir_node.phase = self.types.get_expr_phase(node).map(|p| p.to_string());
+		self.with_node(ir_node, |builder| visit::visit_expr(builder, node));
+	}
+}
+
+fn stmt_node(stmt: &Stmt) -> IrNode {
+	let span = stmt.span.clone();
+	match &stmt.kind {
+		StmtKind::Bring { source, identifier } => {
+			let source = match source {
+				BringSource::BuiltinModule(name) | BringSource::JsiiModule(name) | BringSource::NpmModule(name) => {
+					name.name.clone()
+				}
+				BringSource::TrustedModule(name, _) | BringSource::WingLibrary(name, _) => name.name.clone(),
+				BringSource::WingFile(path) | BringSource::Directory(path) => path.to_string(),
+			};
+			let mut node = IrNode::new("bring", span).named(identifier.as_ref());
+			node.value = Some(source);
+			node
+		}
+		StmtKind::SuperConstructor { .. } => IrNode::new("super_constructor", span),
+		StmtKind::Let { var_name, .. } => IrNode::new("let", span).named(Some(var_name)),
+		StmtKind::ForLoop { iterator, .. } => IrNode::new("for_loop", span).named(Some(iterator)),
+		StmtKind::While { .. } => IrNode::new("while", span),
+		StmtKind::WhileLet { var_name, .. } => IrNode::new("while_let", span).named(Some(var_name)),
+		StmtKind::IfLet(if_let) => IrNode::new("if_let", span).named(Some(&if_let.var_name)),
+		StmtKind::If { .. } => IrNode::new("if", span),
+		StmtKind::Break => IrNode::new("break", span),
+		StmtKind::Continue => IrNode::new("continue", span),
+		StmtKind::Return(_) => IrNode::new("return", span),
+		StmtKind::Throw(_) => IrNode::new("throw", span),
+		StmtKind::Expression(_) => IrNode::new("expression", span),
+		StmtKind::Assignment { variable, .. } => IrNode::new("assignment", span).named(reference_name(variable)),
+		StmtKind::Scope(_) => IrNode::new("block", span),
+		StmtKind::Class(class) => IrNode::new("class", span).named(Some(&class.name)),
+		StmtKind::Interface(interface) => IrNode::new("interface", span).named(Some(&interface.name)),
+		StmtKind::Struct(struct_) => IrNode::new("struct", span).named(Some(&struct_.name)),
+		StmtKind::Enum(enum_) => IrNode::new("enum", span).named(Some(&enum_.name)),
+		StmtKind::TryCatch { .. } => IrNode::new("try_catch", span),
+		StmtKind::ExplicitLift(_) => IrNode::new("explicit_lift", span),
+	}
+}
+
+fn expr_node(expr: &Expr) -> IrNode {
+	let span = expr.span.clone();
+	match &expr.kind {
+		ExprKind::New(_) => IrNode::new("new", span),
+		ExprKind::Literal(literal) => {
+			let (kind, value) = match literal {
+				Literal::NonInterpolatedString(s) | Literal::String(s) => ("string", Some(s.clone())),
+				Literal::InterpolatedString(_) => ("interpolated_string", None),
+				Literal::Number(n) => ("number", Some(n.to_string())),
+				Literal::Boolean(b) => ("boolean", Some(b.to_string())),
+				Literal::Nil => ("nil", None),
+			};
+			let mut node = IrNode::new(kind, span);
+			node.value = value;
+			node
+		}
+		ExprKind::Range { .. } => IrNode::new("range", span),
+		ExprKind::Reference(reference) => IrNode::new("reference", span).named(reference_name(reference)),
+		ExprKind::Intrinsic(intrinsic) => IrNode::new("intrinsic", span).named(Some(&intrinsic.name)),
+		ExprKind::Call { .. } => IrNode::new("call", span),
+		ExprKind::InlineJs { code, .. } => {
+			let mut node = IrNode::new("inline_js", span);
+			node.value = Some(code.clone());
+			node
+		}
+		ExprKind::Unary { op, .. } => {
+			let mut node = IrNode::new("unary", span);
+			node.value = Some(format!("{:?}", op));
+			node
+		}
+		ExprKind::Binary { op, .. } => {
+			let mut node = IrNode::new("binary", span);
+			node.value = Some(format!("{:?}", op));
+			node
+		}
+		ExprKind::ArrayLiteral { .. } => IrNode::new("array_literal", span),
+		ExprKind::StructLiteral { .. } => IrNode::new("struct_literal", span),
+		ExprKind::JsonMapLiteral { .. } => IrNode::new("json_map_literal", span),
+		ExprKind::MapLiteral { .. } => IrNode::new("map_literal", span),
+		ExprKind::SetLiteral { .. } => IrNode::new("set_literal", span),
+		ExprKind::JsonLiteral { .. } => IrNode::new("json_literal", span),
+		ExprKind::FunctionClosure(_) => IrNode::new("function_closure", span),
+		ExprKind::Error => IrNode::new("error", span),
+	}
+}
+
+fn reference_name(reference: &Reference) -> Option<String> {
+	match reference {
+		Reference::Identifier(symbol) => Some(symbol.name.clone()),
+		Reference::InstanceMember { property, .. } => Some(property.name.clone()),
+		Reference::TypeMember { property, .. } => Some(property.name.clone()),
+		Reference::SuperMember { property } => Some(property.name.clone()),
+		Reference::ElementAccess { .. } => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use camino::Utf8Path;
+	use serde_json::json;
+
+	use super::build_ir_file;
+	use crate::{ast::AstContext, test_utils::parse_source, type_check::Types};
+
+	#[test]
+	fn serializes_statements_and_expressions() {
+		let path = Utf8Path::new("/project/main.w");
+		let ast_ctx = AstContext::new();
+		let scope = parse_source(
+			path,
+			r#"
+let x = 1 + 2;
+if x > 2 {
+  log("big");
+}
+"#,
+			&ast_ctx,
+		);
+		// Without type checking, the IR has no types or phases
+		let file = serde_json::to_value(build_ir_file(path, &scope, &Types::new())).unwrap();
+
+		assert_eq!(file["path"], "/project/main.w");
+		assert_eq!(file["classes"], json!([]));
+
+		let statements = file["statements"].as_array().unwrap();
+		assert_eq!(statements.len(), 2);
+
+		let let_stmt = &statements[0];
+		assert_eq!(let_stmt["kind"], "let");
+		assert_eq!(let_stmt["name"], "x");
+		let sum = &let_stmt["children"][0];
+		assert_eq!(sum["kind"], "binary");
+		assert_eq!(sum["value"], "AddOrConcat");
+		assert!(sum.get("type").is_none());
+		assert!(sum.get("phase").is_none());
+		assert_eq!(sum["children"][0]["kind"], "number");
+		assert_eq!(sum["children"][0]["value"], "1");
+		assert_eq!(sum["children"][1]["value"], "2");
+		assert_eq!(sum["span"]["start"], json!({ "line": 1, "col": 8 }));
+
+		let if_stmt = &statements[1];
+		assert_eq!(if_stmt["kind"], "if");
+		let kinds = if_stmt["children"]
+			.as_array()
+			.unwrap()
+			.iter()
+			.map(|child| child["kind"].as_str().unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(kinds, vec!["binary", "scope"]);
+		let call = &if_stmt["children"][1]["children"][0]["children"][0];
+		assert_eq!(call["kind"], "call");
+		assert_eq!(call["children"][0]["kind"], "reference");
+		assert_eq!(call["children"][0]["name"], "log");
+		assert_eq!(call["children"][1]["kind"], "string");
+		assert_eq!(call["children"][1]["value"], "\"big\"");
+	}
+}
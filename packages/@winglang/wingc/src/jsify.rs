@@ -1,5 +1,11 @@
 #[macro_use]
 pub mod codemaker;
+pub mod bundle;
+pub mod coverage;
+pub mod dead_code;
+pub mod expect_values;
+pub mod inflight_deps;
+pub mod target;
 mod tests;
 use aho_corasick::AhoCorasick;
 use camino::{Utf8Path, Utf8PathBuf};
@@ -8,32 +14,53 @@ use indexmap::{IndexMap, IndexSet};
 use itertools::Itertools;
 use parcel_sourcemap::utils::make_relative_path;
 
-use std::{borrow::Borrow, cell::RefCell, cmp::Ordering, collections::BTreeMap, vec};
+use std::{
+	borrow::Borrow,
+	cell::RefCell,
+	cmp::Ordering,
+	collections::{BTreeMap, HashSet},
+	vec,
+};
 
 use crate::{
 	ast::{
 		AccessModifier, ArgList, AssignmentKind, BinaryOperator, BringSource, CalleeKind, Class as AstClass, ElseIfs, Enum,
-		Expr, ExprKind, FunctionBody, FunctionDefinition, IfLet, InterpolatedStringPart, IntrinsicKind, Literal, New,
-		Phase, Reference, Scope, Stmt, StmtKind, Symbol, UnaryOperator, UserDefinedType,
+		Expr, ExprId, ExprKind, FunctionBody, FunctionDefinition, IfLet, IfLetCondition, InterpolatedStringPart,
+		IntrinsicKind, Literal, New, Phase, Reference, Scope, Stmt, StmtKind, Symbol, UnaryOperator, UserDefinedType,
 	},
 	comp_ctx::{CompilationContext, CompilationPhase},
+	construct_tree::{construct_tree_metadata_json, CONSTRUCT_TREE_METADATA_FILE_NAME},
+	content_hash::short_content_hash,
 	diagnostic::{report_diagnostic, Diagnostic, DiagnosticSeverity, WingSpan},
+	env_manifest::{env_manifest_json, ENV_MANIFEST_FILE_NAME},
+	expect_snapshots::{SnapshotCall, SNAPSHOT_MANIFEST_FILE_NAME},
 	file_graph::{File, FileGraph},
 	files::Files,
+	ir::build_ir,
+	openapi::{openapi_document_json, OPENAPI_FILE_NAME},
 	parser::is_entrypoint_file,
+	permissions::{PermissionSummary, PERMISSIONS_JSON_FILE_NAME, PERMISSIONS_MARKDOWN_FILE_NAME},
+	test_blocks::TestFilter,
 	type_check::{
-		is_udt_struct_type,
+		is_super_method, is_udt_struct_type,
 		lifts::{LiftQualification, Liftable, Lifts},
-		resolve_super_method, resolve_user_defined_type,
-		symbol_env::{SymbolEnv, SymbolEnvKind},
-		ClassLike, Type, TypeRef, Types, CLASS_INFLIGHT_INIT_NAME,
+		resolve_super_member, resolve_super_method, resolve_user_defined_type,
+		symbol_env::{LookupResult, SymbolEnv, SymbolEnvKind},
+		ClassLike, SetMemberHashing, Type, TypeRef, Types, CLASS_INFLIGHT_INIT_NAME, COMPARE_TO_METHOD,
+		ENUM_VALUE_PROPERTY,
 	},
 	visit_context::{VisitContext, VisitorWithContext},
 	MACRO_REPLACE_ARGS, MACRO_REPLACE_ARGS_TEXT, MACRO_REPLACE_SELF, WINGSDK_ASSEMBLY_NAME, WINGSDK_AUTOID_RESOURCE,
 	WINGSDK_RESOURCE, WINGSDK_STD_MODULE,
 };
 
+use self::bundle::bundle_files;
 use self::codemaker::CodeMaker;
+use self::coverage::{CounterKind, Coverage, COVERAGE_MAP_FILE_NAME};
+use self::dead_code::find_dead_declarations;
+use self::expect_values::{ExpectValues, EXPECT_UTIL_FQN, EXPECT_VALUES_VAR};
+use self::inflight_deps::{inflight_dependencies_json, InflightDependencies, INFLIGHT_DEPENDENCIES_FILE_NAME};
+use self::target::{extern_min_node_version, NodeTarget};
 
 const PREFLIGHT_FILE_NAME: &str = "preflight.cjs";
 
@@ -63,34 +90,62 @@ const MODULE_PREFLIGHT_TYPES_MAP: &str = "$preflightTypesMap";
 
 const SCOPE_PARAM: &str = "$scope";
 
+/// The directory (in the output directory) the values passed to `expect.snapshot` are recorded in
+const SNAPSHOTS_DIR: &str = "snapshots";
+
 pub struct JSifyContext<'a> {
 	pub lifts: Option<&'a Lifts>,
 	pub visit_ctx: &'a mut VisitContext,
 	pub source_file: Option<&'a File>,
 }
 
+/// What the inflight side of a class uses from outside of it, which its inflight file must require
+#[derive(Default)]
+struct InflightUsage {
+	/// The aliases of the npm modules it references
+	npm_modules: IndexSet<String>,
+	/// The require paths of the extern files it uses
+	externs: IndexSet<String>,
+	/// Whether it calls the SDK's helpers (`$helpers`)
+	helpers: bool,
+	/// Whether it calls the SDK's macros (`$macros`)
+	macros: bool,
+}
+
 pub struct JSifier<'a> {
 	pub types: &'a mut Types,
 	/// Store the output files here.
 	pub output_files: RefCell<Files>,
 	/// Stored struct schemas that are referenced in the code.
 	pub referenced_struct_schemas: RefCell<IndexMap<Utf8PathBuf, BTreeMap<String, CodeMaker>>>,
-	/// Counter for generating unique preflight file names.
-	preflight_file_counter: RefCell<usize>,
-
-	/// Counter for generating unique inflight file names.
-	inflight_file_counter: RefCell<usize>,
-	/// Map from source file IDs to safe counters.
-	inflight_file_map: RefCell<IndexMap<String, usize>>,
-
 	/// Map from source file paths to the JS file names they are emitted to.
-	/// e.g. "bucket.w" -> "preflight.bucket-1.cjs"
+	/// e.g. "bucket.w" -> "preflight.bucket-1a2b3c4d.cjs"
 	pub preflight_file_map: RefCell<IndexMap<Utf8PathBuf, String>>,
 	source_files: &'a Files,
 	source_file_graph: &'a FileGraph,
 	/// The path that compilation started at (file or directory)
 	compilation_init_path: &'a Utf8Path,
 	out_dir: &'a Utf8Path,
+	/// Spans of module-level declarations that are never referenced and are left out of the output
+	dead_declarations: HashSet<WingSpan>,
+	/// The Node.js version inflight code must run on, if it isn't the latest
+	node_target: Option<NodeTarget>,
+	/// The npm modules brought by the file being jsified, from the alias they're brought as to the package name
+	npm_modules: IndexMap<String, String>,
+	/// What the inflight side of the class being jsified uses, recorded as its code is generated
+	inflight_usage: RefCell<InflightUsage>,
+	/// The dependencies of the inflight files emitted so far, by file name
+	inflight_dependencies: RefCell<BTreeMap<String, InflightDependencies>>,
+	/// The coverage counters inserted into the generated code, if it's instrumented
+	coverage: Option<Coverage>,
+	/// The sub-expressions recorded while jsifying the arguments of an `expect` call
+	expect_values: RefCell<Option<ExpectValues>>,
+	/// The `expect.snapshot` calls of the program, by the ids of their expressions
+	snapshot_calls: IndexMap<ExprId, SnapshotCall>,
+	/// Whether the values passed to `expect.snapshot` calls are recorded for the test runner
+	record_snapshots: bool,
+	/// Selects the tests that are generated, if only some of them are
+	test_filter: Option<TestFilter>,
 }
 
 impl VisitorWithContext for JSifyContext<'_> {
@@ -124,14 +179,180 @@ impl<'a> JSifier<'a> {
 			compilation_init_path,
 			out_dir,
 			referenced_struct_schemas: RefCell::new(IndexMap::new()),
-			inflight_file_counter: RefCell::new(0),
-			inflight_file_map: RefCell::new(IndexMap::new()),
-			preflight_file_counter: RefCell::new(0),
 			preflight_file_map: RefCell::new(IndexMap::new()),
 			output_files: RefCell::new(output_files),
+			dead_declarations: HashSet::new(),
+			node_target: None,
+			npm_modules: IndexMap::new(),
+			inflight_usage: RefCell::new(InflightUsage::default()),
+			inflight_dependencies: RefCell::new(BTreeMap::new()),
+			coverage: None,
+			expect_values: RefCell::new(None),
+			snapshot_calls: IndexMap::new(),
+			record_snapshots: false,
+			test_filter: None,
+		}
+	}
+
+	/// Leave declarations that no code path references out of the generated preflight code
+	pub fn eliminate_dead_code(&mut self, asts: &IndexMap<Utf8PathBuf, Scope>) {
+		self.dead_declarations = find_dead_declarations(asts);
+	}
+
+	/// Emit inflight code that runs on the given Node.js version
+	pub fn set_node_target(&mut self, node_target: NodeTarget) {
+		self.node_target = Some(node_target);
+	}
+
+	/// Insert coverage counters for every statement and branch into the generated code
+	pub fn instrument_coverage(&mut self) {
+		self.coverage = Some(Coverage::new(self.out_dir));
+	}
+
+	/// Adds the map of the coverage counters to the output files (see `COVERAGE_MAP_FILE_NAME`)
+	pub fn emit_coverage_map(&self) {
+		let Some(coverage) = &self.coverage else {
+			return;
+		};
+		match self
+			.output_files
+			.borrow_mut()
+			.add_file(COVERAGE_MAP_FILE_NAME, coverage.to_map_json())
+		{
+			Ok(()) => {}
+			Err(err) => report_diagnostic(err.into()),
 		}
 	}
 
+	/// Adds the manifest of the extern files and npm packages each inflight file requires to the output files (see
+	/// `INFLIGHT_DEPENDENCIES_FILE_NAME`)
+	pub fn emit_inflight_dependencies(&self) {
+		match self.output_files.borrow_mut().add_file(
+			INFLIGHT_DEPENDENCIES_FILE_NAME,
+			inflight_dependencies_json(&self.inflight_dependencies.borrow()),
+		) {
+			Ok(()) => {}
+			Err(err) => report_diagnostic(err.into()),
+		}
+	}
+
+	/// Leave the tests that don't match the filter out of the generated code
+	pub fn set_test_filter(&mut self, test_filter: TestFilter) {
+		self.test_filter = Some(test_filter);
+	}
+
+	/// Compare the values passed to these `expect.snapshot` calls with their stored snapshots, and record them for the
+	/// test runner if `record` is set
+	pub fn set_snapshot_calls(&mut self, calls: Vec<SnapshotCall>, record: bool) {
+		self.snapshot_calls = calls.into_iter().map(|call| (call.expr_id, call)).collect();
+		self.record_snapshots = record;
+	}
+
+	/// Adds the list of the program's `expect.snapshot` calls to the output files (see `SNAPSHOT_MANIFEST_FILE_NAME`),
+	/// so the test runner can store the values they were passed
+	pub fn emit_snapshot_manifest(&self) {
+		if !self.record_snapshots || self.snapshot_calls.is_empty() {
+			return;
+		}
+		let manifest = serde_json::to_string(&self.snapshot_calls.values().collect_vec()).expect("serialize snapshots");
+		match self
+			.output_files
+			.borrow_mut()
+			.add_file(SNAPSHOT_MANIFEST_FILE_NAME, manifest)
+		{
+			Ok(()) => {}
+			Err(err) => report_diagnostic(err.into()),
+		}
+	}
+
+	/// Adds the construct tree metadata of the program to the output files (see `CONSTRUCT_TREE_METADATA_FILE_NAME`)
+	pub fn emit_construct_tree_metadata(&self, asts: &IndexMap<Utf8PathBuf, Scope>) {
+		match self.output_files.borrow_mut().add_file(
+			CONSTRUCT_TREE_METADATA_FILE_NAME,
+			construct_tree_metadata_json(self.types, asts),
+		) {
+			Ok(()) => {}
+			Err(err) => report_diagnostic(err.into()),
+		}
+	}
+
+	/// Adds the manifest of the environment variables the program reads to the output files (see
+	/// `ENV_MANIFEST_FILE_NAME`)
+	pub fn emit_env_manifest(&self, asts: &IndexMap<Utf8PathBuf, Scope>) {
+		match self
+			.output_files
+			.borrow_mut()
+			.add_file(ENV_MANIFEST_FILE_NAME, env_manifest_json(self.types, asts))
+		{
+			Ok(()) => {}
+			Err(err) => report_diagnostic(err.into()),
+		}
+	}
+
+	/// Adds the summary of the operations inflight code performs on the preflight objects it lifts to the output files,
+	/// as JSON and as Markdown
+	pub fn emit_permissions_summary(&self, asts: &IndexMap<Utf8PathBuf, Scope>) {
+		let summary = PermissionSummary::new(&build_ir(asts, self.types));
+		let mut output_files = self.output_files.borrow_mut();
+		for (name, content) in [
+			(PERMISSIONS_JSON_FILE_NAME, summary.to_json()),
+			(PERMISSIONS_MARKDOWN_FILE_NAME, summary.to_markdown()),
+		] {
+			match output_files.add_file(name, content) {
+				Ok(()) => {}
+				Err(err) => report_diagnostic(err.into()),
+			}
+		}
+	}
+
+	/// Adds the OpenAPI document of the program's API routes to the output files (see `OPENAPI_FILE_NAME`), if it has
+	/// any
+	pub fn emit_openapi_document(&self, asts: &IndexMap<Utf8PathBuf, Scope>, title: &str) {
+		let Some(document) = openapi_document_json(self.types, asts, title) else {
+			return;
+		};
+		match self.output_files.borrow_mut().add_file(OPENAPI_FILE_NAME, document) {
+			Ok(()) => {}
+			Err(err) => report_diagnostic(err.into()),
+		}
+	}
+
+	/// Returns the body of a branch, starting with a counter of how many times it was taken if the code is instrumented
+	fn jsify_branch(&self, scope: &Scope, ctx: &mut JSifyContext) -> CodeMaker {
+		let mut code = CodeMaker::with_source(&scope.span);
+		if let Some(coverage) = &self.coverage {
+			code.line(coverage.counter(CounterKind::Branch, &scope.span));
+		}
+		code.add_code(self.jsify_scope_body(scope, ctx));
+		code
+	}
+
+	/// Whether optional chaining and nullish coalescing must be lowered to syntax older Node.js versions support
+	fn lower_optional_chaining(&self, ctx: &JSifyContext) -> bool {
+		ctx.visit_ctx.current_phase() == Phase::Inflight
+			&& self
+				.node_target
+				.is_some_and(|target| !target.supports_optional_chaining())
+	}
+
+	/// Whether the identifier refers to an npm module brought by the current file (and not to an inflight symbol
+	/// shadowing it)
+	fn is_npm_module_reference(&self, identifier: &Symbol, ctx: &JSifyContext) -> bool {
+		if !self.npm_modules.contains_key(&identifier.name) {
+			return false;
+		}
+		let Some(env) = ctx.visit_ctx.current_env() else {
+			return false;
+		};
+		matches!(env.lookup_ext(identifier, None), LookupResult::Found(_, info) if info.phase != Phase::Inflight)
+	}
+
+	/// Replaces the generated files with a single preflight file that embeds all of them (see `bundle_files`)
+	pub fn bundle_output_files(&self) {
+		let bundled = bundle_files(&self.output_files.borrow());
+		*self.output_files.borrow_mut() = bundled;
+	}
+
 	pub fn jsify(&mut self, source_file: &File, scope: &Scope) {
 		CompilationContext::set(CompilationPhase::Jsifying, &scope.span);
 		let mut js = CodeMaker::default();
@@ -144,7 +365,23 @@ impl<'a> JSifier<'a> {
 			source_file: Some(source_file),
 		};
 		jsify_context.visit_ctx.push_env(self.types.get_scope_env(&scope));
-		for statement in scope.statements.iter().sorted_by(|a, b| match (&a.kind, &b.kind) {
+		// Classes are jsified before the bring statements, so collect the npm modules their inflight files may require
+		self.npm_modules = scope
+			.statements
+			.iter()
+			.filter_map(|stmt| match &stmt.kind {
+				StmtKind::Bring {
+					source: BringSource::NpmModule(name),
+					identifier: Some(identifier),
+				} => Some((identifier.name.clone(), name.name.clone())),
+				_ => None,
+			})
+			.collect();
+		let live_statements = scope
+			.statements
+			.iter()
+			.filter(|stmt| !self.dead_declarations.contains(&stmt.span));
+		for statement in live_statements.sorted_by(|a, b| match (&a.kind, &b.kind) {
 			// Put type definitions first so JS won't complain of unknown types
 			(StmtKind::Enum(_), StmtKind::Enum(_)) => Ordering::Equal,
 			(StmtKind::Enum(_), _) => Ordering::Less,
@@ -192,6 +429,9 @@ impl<'a> JSifier<'a> {
 		output.line(format!(
 			"const {EXTERN_VAR} = {HELPERS_VAR}.createExternRequire({__DIRNAME});"
 		));
+		if let Some(coverage) = &self.coverage {
+			output.line(coverage.preamble());
+		}
 
 		if is_entrypoint {
 			output.line(format!(
@@ -263,7 +503,7 @@ impl<'a> JSifier<'a> {
 			output.add_code(imports);
 			output.add_code(self.jsify_struct_schemas(source_file));
 			output.add_code(js);
-			let exports = get_exported_symbols(&scope);
+			let exports = get_exported_symbols(&scope, &self.dead_declarations);
 			output.line(format!(
 				"module.exports = {{ {MODULE_PREFLIGHT_TYPES_MAP}, {} }};",
 				exports.iter().map(ToString::to_string).join(", ")
@@ -282,10 +522,12 @@ impl<'a> JSifier<'a> {
 				.chars()
 				.filter(|c| c.is_alphanumeric())
 				.collect::<String>();
-			// add a number to the end to avoid name collisions
-			let mut preflight_file_counter = self.preflight_file_counter.borrow_mut();
-			*preflight_file_counter += 1;
-			format!("preflight.{}-{}.cjs", sanitized_name, preflight_file_counter)
+			// add a suffix to the end to avoid name collisions
+			format!(
+				"preflight.{}-{}.cjs",
+				sanitized_name,
+				self.file_name_suffix(&source_file.path)
+			)
 		};
 
 		// Store the file name in a map so if anyone tries to "bring" it as a module,
@@ -361,19 +603,64 @@ impl<'a> JSifier<'a> {
 		code
 	}
 
+	/// JS only looks up methods through `super`, fields of the parent class are held by the instance itself so they're
+	/// accessed through `this` (Wing classes can't override fields, so this always refers to the parent's field).
+	fn jsify_super_member(&self, member: &Symbol, ctx: &JSifyContext) -> String {
+		let env = ctx.visit_ctx.current_env().expect("an env");
+		let is_method = resolve_super_member(member, env, self.types).is_ok_and(|(_, member)| is_super_method(&member));
+		format!("{}.{}", if is_method { "super" } else { "this" }, member)
+	}
+
+	/// The variable the SDK's helpers are bound to, recording that the code being generated uses them
+	fn helpers(&self) -> &'static str {
+		self.inflight_usage.borrow_mut().helpers = true;
+		HELPERS_VAR
+	}
+
+	/// The variable the SDK's macros are bound to, recording that the code being generated uses them
+	fn macros(&self) -> &'static str {
+		self.inflight_usage.borrow_mut().macros = true;
+		MACROS_VAR
+	}
+
 	pub fn jsify_reference(&self, reference: &Reference, ctx: &mut JSifyContext) -> CodeMaker {
 		match reference {
-			Reference::Identifier(identifier) => new_code!(&identifier.span, &identifier.name),
+			Reference::Identifier(identifier) => {
+				if ctx.visit_ctx.current_phase() == Phase::Inflight && self.is_npm_module_reference(identifier, ctx) {
+					self
+						.inflight_usage
+						.borrow_mut()
+						.npm_modules
+						.insert(identifier.name.clone());
+				}
+				new_code!(&identifier.span, &identifier.name)
+			}
 			Reference::InstanceMember {
 				object,
 				property,
 				optional_accessor,
-			} => new_code!(
-				&property.span,
-				self.jsify_expression(object, ctx),
-				if *optional_accessor { "?." } else { "." },
-				&property.to_string()
-			),
+			} => {
+				// Enum values are represented by their backing string
+				if property.name == ENUM_VALUE_PROPERTY && self.types.get_expr_type(object).maybe_unwrap_option().is_enum() {
+					return self.jsify_expression(object, ctx);
+				}
+				if *optional_accessor && self.lower_optional_chaining(ctx) {
+					return new_code!(
+						&property.span,
+						"(($o) => $o == null ? undefined : $o.",
+						&property.to_string(),
+						")(",
+						self.jsify_expression(object, ctx),
+						")"
+					);
+				}
+				new_code!(
+					&property.span,
+					self.jsify_expression(object, ctx),
+					if *optional_accessor { "?." } else { "." },
+					&property.to_string()
+				)
+			}
 			Reference::TypeMember { type_name, property } => {
 				new_code!(
 					&property.span,
@@ -382,9 +669,19 @@ impl<'a> JSifier<'a> {
 					&property.name
 				)
 			}
-			Reference::ElementAccess { object, index } => new_code!(
+			Reference::SuperMember { property } => new_code!(&property.span, self.jsify_super_member(property, ctx)),
+			Reference::ElementAccess {
+				object,
+				index,
+				optional_accessor,
+			} => new_code!(
 				&object.span,
-				"$helpers.lookup(",
+				self.helpers(),
+				if *optional_accessor {
+					".lookupOptional("
+				} else {
+					".lookup("
+				},
 				self.jsify_expression(object, ctx),
 				", ",
 				self.jsify_expression(index, ctx),
@@ -431,6 +728,50 @@ impl<'a> JSifier<'a> {
 		new_code!(&arg_list.span, args)
 	}
 
+	/// Returns the function that hashes a `Set` member which isn't hashed by its identity, and whether hashing it is
+	/// async. The `$helpers` set functions hash members with `stableHash` unless given another function.
+	fn set_member_hasher(&self, member: &Expr, ctx: &JSifyContext) -> Option<(Option<String>, bool)> {
+		match self.types.set_members.get(&member.id)? {
+			SetMemberHashing::Identity => None,
+			SetMemberHashing::Value => Some((None, false)),
+			SetMemberHashing::Method => Some((
+				Some(format!("{}.hashByMethod", self.helpers())),
+				ctx.visit_ctx.current_phase() == Phase::Inflight,
+			)),
+		}
+	}
+
+	/// Jsifies a call to the `$helpers` function that adds, looks up or removes a `Set` member by its hash, or returns
+	/// `None` if the member is hashed by its identity, so the set's own method can be called
+	fn jsify_set_member_call(
+		&self,
+		helper: &str,
+		set: &Expr,
+		member: &Expr,
+		span: &WingSpan,
+		ctx: &mut JSifyContext,
+	) -> Option<CodeMaker> {
+		let (hasher, is_async) = self.set_member_hasher(member, ctx)?;
+		let js_set = self.jsify_expression(set, ctx);
+		let js_member = self.jsify_expression(member, ctx);
+		let hasher = hasher.map(|h| format!(", {h}")).unwrap_or_default();
+		let auto_await = if is_async { "await " } else { "" };
+		Some(new_code!(
+			span,
+			"(",
+			auto_await,
+			self.helpers(),
+			".",
+			helper,
+			"(",
+			js_set,
+			", ",
+			js_member,
+			hasher,
+			"))"
+		))
+	}
+
 	pub fn jsify_type(typ: &Type) -> Option<String> {
 		match typ {
 			Type::Struct(t) => Some(t.name.name.clone()),
@@ -471,10 +812,42 @@ impl<'a> JSifier<'a> {
 		new_code!(&udt.span, udt.full_path_str())
 	}
 
+	/// Like `jsify_expression`, but a range is left as a generator of its numbers rather than collected into an array
+	fn jsify_iterable(&self, expression: &Expr, ctx: &mut JSifyContext) -> CodeMaker {
+		let ExprKind::Range { start, inclusive, end } = &expression.kind else {
+			return self.jsify_expression(expression, ctx);
+		};
+		new_code!(
+			&expression.span,
+			format!("{}.range(", self.helpers()),
+			self.jsify_expression(start, ctx),
+			",",
+			self.jsify_expression(end, ctx),
+			",",
+			inclusive.unwrap().to_string(),
+			")"
+		)
+	}
+
 	pub fn jsify_expression(&self, expression: &Expr, ctx: &mut JSifyContext) -> CodeMaker {
 		CompilationContext::set(CompilationPhase::Jsifying, &expression.span);
 		let expr_span = &expression.span;
 
+		// record the values of the arguments of an `expect` call (see `jsify_expect_call`)
+		let recorded_index = self
+			.expect_values
+			.borrow_mut()
+			.as_mut()
+			.and_then(|values| values.record(expression, |span| lookup_span(span, self.source_files)));
+		if let Some(index) = recorded_index {
+			return new_code!(
+				expr_span,
+				format!("{EXPECT_VALUES_VAR}.record({index}, "),
+				self.jsify_expression(expression, ctx),
+				")"
+			);
+		}
+
 		// if we are in inflight and there's a lifting/capturing token associated with this expression
 		// then emit the token instead of the expression.
 		if ctx.visit_ctx.current_phase() == Phase::Inflight {
@@ -498,6 +871,7 @@ impl<'a> JSifier<'a> {
 						annotations: vec![],
 						hints: vec![],
 						severity: DiagnosticSeverity::Error,
+						code: None,
 					});
 
 					return new_code!(expr_span, "<ERROR>");
@@ -533,6 +907,32 @@ impl<'a> JSifier<'a> {
 
 				let ctor = self.jsify_user_defined_type(class, ctx);
 
+				// If the arguments were routed to a secondary constructor then this is just a call to that static method
+				if let Some(method_name) = self.types.secondary_constructor_calls.get(&expression.id) {
+					let method_sig = class_type
+						.get_method(&Symbol::global(method_name))
+						.and_then(|v| v.type_.as_function_sig())
+						.expect("a secondary constructor");
+					let scope = if method_sig.implicit_scope_param {
+						let implicit_scope_arg_available = ctx.visit_ctx.current_function_env().map_or(false, |e| {
+							if let SymbolEnvKind::Function { sig, .. } = e.kind {
+								sig.as_function_sig().expect("a function sig").implicit_scope_param
+							} else {
+								false
+							}
+						});
+						Some(if implicit_scope_arg_available {
+							SCOPE_PARAM.to_string()
+						} else {
+							"this".to_string()
+						})
+					} else {
+						None
+					};
+					let args = self.jsify_arg_list(&arg_list, scope, None, ctx);
+					return new_code!(expr_span, "(", auto_await, ctor, ".", method_name, "(", args, "))");
+				}
+
 				let scope = if is_preflight_class && class_type.std_construct_args {
 					if let Some(scope) = obj_scope {
 						Some(self.jsify_expression(scope, ctx).to_string())
@@ -601,15 +1001,20 @@ impl<'a> JSifier<'a> {
 							.get_method(&Symbol::global(CLASS_INFLIGHT_INIT_NAME))
 							.is_some()
 					{
+						let init_call = if self.lower_optional_chaining(ctx) {
+							format!("if (o.{CLASS_INFLIGHT_INIT_NAME}) await o.{CLASS_INFLIGHT_INIT_NAME}();")
+						} else {
+							format!("await o.{CLASS_INFLIGHT_INIT_NAME}?.();")
+						};
 						new_code!(
 							expr_span,
 							"(await (async () => {const o = new ",
 							ctor,
 							"(",
 							args,
-							"); await o.",
-							CLASS_INFLIGHT_INIT_NAME,
-							"?.(); return o; })())"
+							"); ",
+							init_call,
+							" return o; })())"
 						)
 					} else {
 						new_code!(expr_span, "new ", ctor, "(", args, ")")
@@ -665,16 +1070,8 @@ impl<'a> JSifier<'a> {
 				Literal::Number(n) => new_code!(expr_span, n.to_string()),
 				Literal::Boolean(b) => new_code!(expr_span, (if *b { "true" } else { "false" }).to_string()),
 			},
-			ExprKind::Range { start, inclusive, end } => new_code!(
-				expr_span,
-				format!("{HELPERS_VAR}.range("),
-				self.jsify_expression(start, ctx),
-				",",
-				self.jsify_expression(end, ctx),
-				",",
-				inclusive.unwrap().to_string(),
-				")"
-			),
+			// Ranges are arrays, but `for` loops iterate the ones they're given directly without collecting them
+			ExprKind::Range { .. } => new_code!(expr_span, "Array.from(", self.jsify_iterable(expression, ctx), ")"),
 			ExprKind::Reference(_ref) => new_code!(expr_span, self.jsify_reference(&_ref, ctx)),
 			ExprKind::Intrinsic(intrinsic) => match intrinsic.kind {
 				IntrinsicKind::Unknown => new_code!(expr_span, ""),
@@ -693,7 +1090,7 @@ impl<'a> JSifier<'a> {
 					// At runtime, $helpers.resolve will normalize the path for Windows or Unix, and then convert it to an absolute path
 					new_code!(
 						expr_span,
-						HELPERS_VAR,
+						self.helpers(),
 						".resolve(",
 						__DIRNAME,
 						", \"",
@@ -715,7 +1112,7 @@ impl<'a> JSifier<'a> {
 					// At runtime, $helpers.resolve will normalize the path for Windows or Unix, and then convert it to an absolute path
 					new_code!(
 						expr_span,
-						HELPERS_VAR,
+						self.helpers(),
 						".resolve(",
 						__DIRNAME,
 						", \"",
@@ -724,10 +1121,43 @@ impl<'a> JSifier<'a> {
 					)
 				}
 				IntrinsicKind::App => {
-					new_code!(expr_span, HELPERS_VAR, ".nodeof(this).app")
+					new_code!(expr_span, self.helpers(), ".nodeof(this).app")
 				}
 			},
-			ExprKind::Call { callee, arg_list } => {
+			ExprKind::Call {
+				callee,
+				arg_list,
+				optional_accessor,
+			} => {
+				if let Some((index, _, snapshot)) = self.snapshot_calls.get_full(&expression.id) {
+					if self.is_expect_call(callee, ctx) {
+						return self.jsify_snapshot_call(index, snapshot, expression, arg_list, ctx);
+					}
+				}
+				if self.expect_values.borrow().is_none() && self.is_expect_call(callee, ctx) {
+					return self.jsify_expect_call(expression, arg_list, ctx);
+				}
+				// a method must be called on its object, so its value is never recorded by itself
+				if let (CalleeKind::Expr(callee_expr), Some(values)) = (callee, self.expect_values.borrow_mut().as_mut()) {
+					values.skip(callee_expr);
+				}
+				// members of a set which aren't hashed by their identity are added, looked up and removed by their hash
+				if let CalleeKind::Expr(callee_expr) = callee {
+					if let ExprKind::Reference(Reference::InstanceMember { object, property, .. }) = &callee_expr.kind {
+						let helper = match property.name.as_str() {
+							"add" => Some("setAdd"),
+							"has" => Some("setHas"),
+							"delete" => Some("setDelete"),
+							_ => None,
+						};
+						if let (Some(helper), Some(member)) = (helper, arg_list.pos_args.first()) {
+							if let Some(code) = self.jsify_set_member_call(helper, object, member, expr_span, ctx) {
+								return code;
+							}
+						}
+					}
+				}
+
 				let function_type = match callee {
 					CalleeKind::Expr(expr) => self.types.get_expr_type(expr),
 					CalleeKind::SuperCall(method) => {
@@ -742,7 +1172,7 @@ impl<'a> JSifier<'a> {
 
 				let expr_string = match callee {
 					CalleeKind::Expr(expr) => self.jsify_expression(expr, ctx).to_string(),
-					CalleeKind::SuperCall(method) => format!("super.{}", method),
+					CalleeKind::SuperCall(method) => self.jsify_super_member(method, ctx),
 				};
 				let mut args_string = self.jsify_arg_list(&arg_list, None, None, ctx).to_string();
 
@@ -788,7 +1218,7 @@ impl<'a> JSifier<'a> {
 								expr_span,
 								format!(
 									"{}.{}({}, {}, {})",
-									MACROS_VAR,
+									self.macros(),
 									js_override,
 									is_optional.to_string(),
 									self_string,
@@ -796,6 +1226,10 @@ impl<'a> JSifier<'a> {
 								)
 							);
 						} else {
+							// overrides of builtins can call the helpers (e.g. `assert`)
+							if js_override.contains(HELPERS_VAR) {
+								self.helpers();
+							}
 							let patterns = &[MACRO_REPLACE_SELF, MACRO_REPLACE_ARGS, MACRO_REPLACE_ARGS_TEXT];
 							let replace_with = &[self_string, args_string, args_text_string];
 							let ac = AhoCorasick::new(patterns).expect("Failed to create macro pattern");
@@ -829,7 +1263,21 @@ impl<'a> JSifier<'a> {
 					}
 				}
 
-				let optional_access = if is_option { "?." } else { "" };
+				let optional_access = if is_option || *optional_accessor { "?." } else { "" };
+				if !optional_access.is_empty() && self.lower_optional_chaining(ctx) {
+					report_diagnostic(Diagnostic {
+						message: format!(
+							"Calling an optional function requires Node.js {} or later, but inflight code targets Node.js {}",
+							NodeTarget::optional_chaining_version(),
+							self.node_target.map_or(0, |target| target.major)
+						),
+						span: Some(expr_span.clone()),
+						annotations: vec![],
+						hints: vec!["unwrap the function with `if let` before calling it".to_string()],
+						severity: DiagnosticSeverity::Error,
+						code: None,
+					});
+				}
 
 				// NOTE: if the expression is a "handle" class, the object itself is callable (see
 				// `jsify_class_inflight` below), so we can just call it as-is.
@@ -850,7 +1298,7 @@ impl<'a> JSifier<'a> {
 					UnaryOperator::Minus => new_code!(expr_span, "(-", js_exp, ")"),
 					UnaryOperator::Not => new_code!(expr_span, "(!", js_exp, ")"),
 					UnaryOperator::OptionalUnwrap => {
-						new_code!(expr_span, "$helpers.unwrap(", js_exp, ")")
+						new_code!(expr_span, self.helpers(), ".unwrap(", js_exp, ")")
 					}
 				}
 			}
@@ -858,6 +1306,50 @@ impl<'a> JSifier<'a> {
 				let js_left = self.jsify_expression(left, ctx);
 				let js_right = self.jsify_expression(right, ctx);
 
+				// Objects and functions are compared by reference, everything else (including structs and Json) is
+				// compared structurally
+				if matches!(op, BinaryOperator::Equal | BinaryOperator::NotEqual) {
+					let ltype = self.types.get_expr_type(left);
+					let rtype = self.types.get_expr_type(right);
+					let by_reference = (ltype.is_compared_by_reference() && (rtype.is_compared_by_reference() || rtype.is_nil()))
+						|| (ltype.is_nil() && rtype.is_compared_by_reference());
+					if by_reference {
+						let js_op = if matches!(op, BinaryOperator::Equal) {
+							"==="
+						} else {
+							"!=="
+						};
+						return new_code!(expr_span, "(", js_left, " ", js_op, " ", js_right, ")");
+					}
+				}
+
+				// Objects that define an ordering are compared by the sign of their `compareTo` method's result
+				if matches!(
+					op,
+					BinaryOperator::Less | BinaryOperator::LessOrEqual | BinaryOperator::Greater | BinaryOperator::GreaterOrEqual
+				) && self.types.get_expr_type(left).compare_to_sig().is_some()
+				{
+					let js_op = match op {
+						BinaryOperator::Less => "<",
+						BinaryOperator::LessOrEqual => "<=",
+						BinaryOperator::Greater => ">",
+						_ => ">=",
+					};
+					return new_code!(
+						expr_span,
+						"((",
+						auto_await,
+						js_left,
+						".",
+						COMPARE_TO_METHOD,
+						"(",
+						js_right,
+						")) ",
+						js_op,
+						" 0)"
+					);
+				}
+
 				let js_op = match op {
 					BinaryOperator::AddOrConcat => "+",
 					BinaryOperator::Sub => "-",
@@ -870,15 +1362,47 @@ impl<'a> JSifier<'a> {
 					BinaryOperator::GreaterOrEqual => ">=",
 					BinaryOperator::Less => "<",
 					BinaryOperator::LessOrEqual => "<=",
-					BinaryOperator::Equal => return new_code!(expr_span, HELPERS_VAR, ".eq(", js_left, ", ", js_right, ")"),
-					BinaryOperator::NotEqual => return new_code!(expr_span, HELPERS_VAR, ".neq(", js_left, ", ", js_right, ")"),
+					BinaryOperator::Equal => return new_code!(expr_span, self.helpers(), ".eq(", js_left, ", ", js_right, ")"),
+					BinaryOperator::NotEqual => {
+						return new_code!(expr_span, self.helpers(), ".neq(", js_left, ", ", js_right, ")")
+					}
 					BinaryOperator::LogicalAnd => "&&",
 					BinaryOperator::LogicalOr => "||",
+					BinaryOperator::BitwiseOr => "|",
+					BinaryOperator::BitwiseAnd => "&",
 					BinaryOperator::UnwrapOr => {
+						if self.lower_optional_chaining(ctx) {
+							// The right side must only be evaluated when the left side is nil. It runs inside an async function
+							// when it awaits anything.
+							return if js_right.to_string().contains("await") {
+								new_code!(
+									expr_span,
+									"(await (async ($o) => $o != null ? $o : ",
+									js_right,
+									")(",
+									js_left,
+									"))"
+								)
+							} else {
+								new_code!(expr_span, "(($o) => $o != null ? $o : ", js_right, ")(", js_left, ")")
+							};
+						}
 						// Use JS nullish coalescing operator which treats undefined and null the same
 						// this is inline with how wing jsifies optionals
 						"??"
 					}
+					BinaryOperator::In => {
+						// Mirror the std `has`/`contains` macros of the collection on the right side
+						return match **self.types.get_expr_type(right).maybe_unwrap_option() {
+							Type::Set(_) | Type::MutSet(_) => self
+								.jsify_set_member_call("setHas", right, left, expr_span, ctx)
+								.unwrap_or_else(|| new_code!(expr_span, "(", js_right, ").has(", js_left, ")")),
+							Type::Array(_) | Type::MutArray(_) | Type::String => {
+								new_code!(expr_span, "(", js_right, ").includes(", js_left, ")")
+							}
+							_ => new_code!(expr_span, "(", js_left, " in (", js_right, "))"),
+						};
+					}
 				};
 				new_code!(expr_span, "(", js_left, " ", js_op, " ", js_right, ")")
 			}
@@ -923,10 +1447,74 @@ impl<'a> JSifier<'a> {
 				new_code!(expr_span, "({", f, "})")
 			}
 			ExprKind::SetLiteral { items, .. } => {
+				let hasher = items.first().and_then(|item| self.set_member_hasher(item, ctx));
 				let item_list = items.iter().map(|expr| self.jsify_expression(expr, ctx)).collect_vec();
-				new_code!(expr_span, "new Set([", item_list, "])")
+				match hasher {
+					// the items are added by their hash, so the set doesn't have two members with the same one
+					Some((hasher, is_async)) => {
+						let hasher = hasher.map(|h| format!(", {h}")).unwrap_or_default();
+						let auto_await = if is_async { "await " } else { "" };
+						new_code!(
+							expr_span,
+							"(",
+							auto_await,
+							self.helpers(),
+							".toSet([",
+							item_list,
+							"]",
+							hasher,
+							"))"
+						)
+					}
+					None => new_code!(expr_span, "new Set([", item_list, "])"),
+				}
 			}
 			ExprKind::FunctionClosure(func_def) => self.jsify_function(None, func_def, true, ctx),
+			ExprKind::InlineJs { inputs, code, .. } => {
+				// The code runs in a function of its own, so it can `return` its result and see the inputs by the names it
+				// was written with, even when they're lifted
+				let params = inputs
+					.iter()
+					.filter_map(|input| match &input.kind {
+						ExprKind::Reference(Reference::Identifier(name)) => Some(name.name.as_str()),
+						_ => None,
+					})
+					.join(", ");
+				let args = inputs
+					.iter()
+					.map(|input| self.jsify_expression(input, ctx))
+					.collect_vec();
+				let call = new_code!(
+					expr_span,
+					"(",
+					auto_await,
+					"(async (",
+					params,
+					") => {",
+					code.as_str(),
+					"})(",
+					args,
+					"))"
+				);
+				// The code isn't type checked, so its result is checked against the declared type when it's returned
+				let result_type = self.types.get_expr_type(expression);
+				match inline_js_type(result_type) {
+					Some(checked_type) => new_code!(
+						expr_span,
+						self.helpers(),
+						".checkInlineJsResult(",
+						call,
+						", ",
+						checked_type,
+						", ",
+						serde_json::to_string(&result_type.to_string()).expect("a string"),
+						")"
+					),
+					None => call,
+				}
+			}
+			// Compilation stops before jsification if there are parse errors, so this is only reached by the LSP
+			ExprKind::Error => new_code!(expr_span, "undefined"),
 		}
 	}
 
@@ -963,19 +1551,96 @@ impl<'a> JSifier<'a> {
 	// 		 }
 	// 	}
 	// }
+	//
+	// When `detached` is set, the first clause is emitted without its leading "else" so the
+	// chain can be placed in a block of its own (see `if let` chains in jsify_statement()).
+	/// Whether a call is to a function of the `expect` module (e.g. `expect.equal(a, b)`)
+	fn is_expect_call(&self, callee: &CalleeKind, ctx: &JSifyContext) -> bool {
+		let CalleeKind::Expr(expr) = callee else {
+			return false;
+		};
+		let ExprKind::Reference(Reference::TypeMember { type_name, .. }) = &expr.kind else {
+			return false;
+		};
+		let Some(env) = ctx.visit_ctx.current_env() else {
+			return false;
+		};
+		resolve_user_defined_type(type_name, env, ctx.visit_ctx.current_stmt_idx())
+			.ok()
+			.and_then(|t| t.as_class().and_then(|class| class.fqn.clone()))
+			.is_some_and(|fqn| fqn == EXPECT_UTIL_FQN)
+	}
+
+	/// Emits an `expect` call that records the values of the sub-expressions of its arguments, so a failed assertion
+	/// can show them under the Wing source of the call (see `$helpers.expectValues`)
+	fn jsify_expect_call(&self, expression: &Expr, arg_list: &ArgList, ctx: &mut JSifyContext) -> CodeMaker {
+		*self.expect_values.borrow_mut() = Some(ExpectValues::new(&expression.span, &arg_list.span));
+		let call = self.jsify_expression(expression, ctx);
+		let values = self.expect_values.take().expect("expect values");
+
+		let source = escape_javascript_string(&lookup_span(&expression.span, self.source_files));
+		let (open, function) = match ctx.visit_ctx.current_phase() {
+			Phase::Inflight => ("(await ", "async "),
+			_ => ("(", ""),
+		};
+		new_code!(
+			&expression.span,
+			format!(
+				"{open}{}.expectValues({function}({EXPECT_VALUES_VAR}) => ",
+				self.helpers()
+			),
+			call,
+			format!(", \"{source}\", {}))", values.to_json())
+		)
+	}
+
+	/// Emits an `expect.snapshot(value)` call, which compares the value with the stored snapshot (if there's one) and
+	/// may record it for the test runner under the call's index in the snapshot manifest (see `$helpers.snapshot`)
+	fn jsify_snapshot_call(
+		&self,
+		index: usize,
+		snapshot: &SnapshotCall,
+		expression: &Expr,
+		arg_list: &ArgList,
+		ctx: &mut JSifyContext,
+	) -> CodeMaker {
+		let mut info = serde_json::json!({
+			"index": index,
+			"id": snapshot.id,
+		});
+		if self.record_snapshots {
+			info["dir"] = self.out_dir.join(SNAPSHOTS_DIR).as_str().into();
+		}
+		if let Some(expected) = &snapshot.expected {
+			info["expected"] = expected.clone();
+		}
+		let value = match arg_list.pos_args.first() {
+			Some(value) => self.jsify_expression(value, ctx),
+			None => new_code!(&arg_list.span, "undefined"),
+		};
+		new_code!(
+			&expression.span,
+			format!("{}.snapshot(", self.helpers()),
+			value,
+			format!(", {info})")
+		)
+	}
+
 	fn jsify_else_if_statements(
 		&self,
 		code: &mut CodeMaker,
 		else_if_statements: &Vec<ElseIfs>,
 		index: usize,
 		else_statements: &Option<Scope>,
+		detached: bool,
 		ctx: &mut JSifyContext,
 	) {
+		let else_keyword = if detached && index == 0 { "" } else { "else " };
 		match else_if_statements.get(index).unwrap() {
 			ElseIfs::ElseIfLetBlock(else_if_let_to_jsify) => {
 				// Emit a JavaScript "else {" for each Wing "else_if_let_block",
 				// and emit the closing "}" bracket in jsify_statement()'s StmtKind::IfLet match case
-				code.open("else {");
+				code.open(format!("{else_keyword}{{"));
 				let else_if_let_value = "$else_if_let_value";
 
 				let value = format!("{}{}", else_if_let_value, index);
@@ -996,7 +1661,7 @@ impl<'a> JSifier<'a> {
 					code.line(format!("const {} = {};", else_if_let_to_jsify.var_name, value));
 				}
 
-				code.add_code(self.jsify_scope_body(&else_if_let_to_jsify.statements, ctx));
+				code.add_code(self.jsify_branch(&else_if_let_to_jsify.statements, ctx));
 				code.close("}");
 			}
 			ElseIfs::ElseIfBlock(else_if_to_jsify) => {
@@ -1005,19 +1670,20 @@ impl<'a> JSifier<'a> {
 				// technically that shouldn't be a problem, its just ugly
 				code.open(new_code!(
 					&else_if_to_jsify.condition.span,
-					"else if (",
+					else_keyword,
+					"if (",
 					condition,
 					") {"
 				));
-				code.add_code(self.jsify_scope_body(&else_if_to_jsify.statements, ctx));
+				code.add_code(self.jsify_branch(&else_if_to_jsify.statements, ctx));
 				code.close("}");
 			}
 		}
 		if index < else_if_statements.len() - 1 {
-			self.jsify_else_if_statements(code, else_if_statements, index + 1, else_statements, ctx);
+			self.jsify_else_if_statements(code, else_if_statements, index + 1, else_statements, false, ctx);
 		} else if let Some(else_scope) = else_statements {
 			code.open("else {");
-			code.add_code(self.jsify_scope_body(else_scope, ctx));
+			code.add_code(self.jsify_branch(else_scope, ctx));
 			code.close("}");
 		}
 		return;
@@ -1025,9 +1691,19 @@ impl<'a> JSifier<'a> {
 
 	fn jsify_statement(&self, env: &SymbolEnv, statement: &Stmt, ctx: &mut JSifyContext) -> CodeMaker {
 		let mut code = CodeMaker::with_source(&statement.span);
+		if let (StmtKind::Expression(expr), Some(test_filter)) = (&statement.kind, &self.test_filter) {
+			if test_filter.excludes(expr) {
+				return code;
+			}
+		}
 
 		CompilationContext::set(CompilationPhase::Jsifying, &statement.span);
 		ctx.visit_ctx.push_stmt(statement);
+		if let Some(coverage) = &self.coverage {
+			if is_executable_statement(&statement.kind) {
+				code.line(coverage.counter(CounterKind::Statement, &statement.span));
+			}
+		}
 		match &statement.kind {
 			StmtKind::Bring { source, identifier } => match source {
 				BringSource::BuiltinModule(name) => {
@@ -1036,15 +1712,23 @@ impl<'a> JSifier<'a> {
 					code.line(format!("const {var_name} = {STDLIB}.{name};"))
 				}
 				BringSource::TrustedModule(name, module_dir) => {
-					code.append(self.jsify_bring_stmt(module_dir, &Some(identifier.as_ref().unwrap_or(name).clone())));
+					code.append(self.jsify_bring_stmt(
+						self.types.library_dir(module_dir),
+						&Some(identifier.as_ref().unwrap_or(name).clone()),
+					));
 				}
 				BringSource::JsiiModule(name) => {
 					// checked during type checking
 					let var_name = identifier.as_ref().unwrap_or(&name);
 					code.line(format!("const {var_name} = require(\"{name}\");"))
 				}
+				BringSource::NpmModule(name) => {
+					// inflight files require the module themselves (see `emit_inflight_file`)
+					let var_name = identifier.as_ref().unwrap();
+					code.line(format!("const {var_name} = require(\"{name}\");"))
+				}
 				BringSource::WingLibrary(_, module_dir) => {
-					code.append(self.jsify_bring_stmt(module_dir, identifier));
+					code.append(self.jsify_bring_stmt(self.types.library_dir(module_dir), identifier));
 				}
 				BringSource::Directory(path) | BringSource::WingFile(path) => {
 					code.append(self.jsify_bring_stmt(path, identifier));
@@ -1057,24 +1741,42 @@ impl<'a> JSifier<'a> {
 					Phase::Inflight => {
 						// yes- and it's an inflight class
 						if parent_class_phase(ctx) == Phase::Inflight {
-							code.line(new_code!(
-								&arg_list.span,
-								"await this.",
-								SUPER_CLASS_INFLIGHT_INIT_NAME,
-								"?.(",
-								args,
-								");"
-							))
+							if self.lower_optional_chaining(ctx) {
+								code.line(new_code!(
+									&arg_list.span,
+									format!("if (this.{SUPER_CLASS_INFLIGHT_INIT_NAME}) await this.{SUPER_CLASS_INFLIGHT_INIT_NAME}("),
+									args,
+									");"
+								))
+							} else {
+								code.line(new_code!(
+									&arg_list.span,
+									"await this.",
+									SUPER_CLASS_INFLIGHT_INIT_NAME,
+									"?.(",
+									args,
+									");"
+								))
+							}
 						} else {
 							// yes- and it's a preflight class
-							code.line(new_code!(
-								&arg_list.span,
-								"await super.",
-								CLASS_INFLIGHT_INIT_NAME,
-								"?.(",
-								args,
-								");"
-							))
+							if self.lower_optional_chaining(ctx) {
+								code.line(new_code!(
+									&arg_list.span,
+									format!("if (super.{CLASS_INFLIGHT_INIT_NAME}) await super.{CLASS_INFLIGHT_INIT_NAME}("),
+									args,
+									");"
+								))
+							} else {
+								code.line(new_code!(
+									&arg_list.span,
+									"await super.",
+									CLASS_INFLIGHT_INIT_NAME,
+									"?.(",
+									args,
+									");"
+								))
+							}
 						}
 					}
 					Phase::Preflight => code.line(new_code!(
@@ -1096,6 +1798,7 @@ impl<'a> JSifier<'a> {
 				var_name,
 				initial_value,
 				type_: _,
+				access: _,
 			} => {
 				let initial_value = self.jsify_expression(initial_value, ctx);
 				if *reassignable {
@@ -1121,17 +1824,65 @@ impl<'a> JSifier<'a> {
 			}
 			StmtKind::ForLoop {
 				iterator,
+				second_iterator,
 				iterable,
 				statements,
 			} => {
-				code.open(new_code!(
-					&statement.span,
-					"for (const ",
-					jsify_symbol(&iterator),
-					" of ",
-					self.jsify_expression(iterable, ctx),
-					") {"
-				));
+				if let Some(second_iterator) = second_iterator {
+					// Maps are plain objects at runtime, arrays are iterated with their indices, and sets and ranges (which are
+					// generators when they're iterated directly) have to be collected to have indices
+					let iterable_type = self.types.get_expr_type(iterable);
+					let iterable_code = self.jsify_iterable(iterable, ctx);
+					let (bindings, entries) = if matches!(*iterable_type, Type::Map(_) | Type::MutMap(_)) {
+						(
+							new_code!(
+								&statement.span,
+								"[",
+								jsify_symbol(iterator),
+								", ",
+								jsify_symbol(second_iterator),
+								"]"
+							),
+							new_code!(&iterable.span, "Object.entries(", iterable_code, ")"),
+						)
+					} else {
+						let entries = if matches!(iterable.kind, ExprKind::Range { .. })
+							|| matches!(*iterable_type, Type::Set(_) | Type::MutSet(_))
+						{
+							new_code!(&iterable.span, "Array.from(", iterable_code, ").entries()")
+						} else {
+							new_code!(&iterable.span, "(", iterable_code, ").entries()")
+						};
+						(
+							new_code!(
+								&statement.span,
+								"[",
+								jsify_symbol(second_iterator),
+								", ",
+								jsify_symbol(iterator),
+								"]"
+							),
+							entries,
+						)
+					};
+					code.open(new_code!(
+						&statement.span,
+						"for (const ",
+						bindings,
+						" of ",
+						entries,
+						") {"
+					));
+				} else {
+					code.open(new_code!(
+						&statement.span,
+						"for (const ",
+						jsify_symbol(&iterator),
+						" of ",
+						self.jsify_iterable(iterable, ctx),
+						") {"
+					));
+				}
 				code.add_code(self.jsify_scope_body(statements, ctx));
 				code.close("}");
 			}
@@ -1145,11 +1896,53 @@ impl<'a> JSifier<'a> {
 				code.add_code(self.jsify_scope_body(statements, ctx));
 				code.close("}");
 			}
+			StmtKind::WhileLet {
+				reassignable,
+				var_name,
+				value,
+				statements,
+			} => {
+				// The value is re-evaluated on every iteration, and like in `if let` the variable is defined in an inner
+				// block so it can shadow a variable used in the value expression:
+				// while (true) {
+				//   const $while_let_value = queue.pop();
+				//   if ($while_let_value == undefined) {
+				//     break;
+				//   }
+				//   {
+				//     const item = $while_let_value;
+				//     ...
+				//   }
+				// }
+				let while_let_value = "$while_let_value";
+				code.open("while (true) {");
+				code.line(new_code!(
+					&var_name.span,
+					"const ",
+					while_let_value,
+					" = ",
+					self.jsify_expression(value, ctx),
+					";"
+				));
+				code.open(format!("if ({while_let_value} == undefined) {{"));
+				code.line("break;");
+				code.close("}");
+				code.open("{");
+				if *reassignable {
+					code.line(format!("let {} = {};", var_name, while_let_value));
+				} else {
+					code.line(format!("const {} = {};", var_name, while_let_value));
+				}
+				code.add_code(self.jsify_scope_body(statements, ctx));
+				code.close("}");
+				code.close("}");
+			}
 			StmtKind::Break => code.line("break;"),
 			StmtKind::Continue => code.line("continue;"),
 			StmtKind::IfLet(IfLet {
 				reassignable,
 				value,
+				conditions,
 				statements,
 				var_name,
 				else_if_statements,
@@ -1184,6 +1977,34 @@ impl<'a> JSifier<'a> {
 				// do not interfere with each other.
 				code.open("{");
 				let if_let_value = "$if_let_value";
+
+				// When the `if let` has chained bindings or guards, the else branches must run if any of them fails.
+				// We track whether the main block was entered in a flag and check it after all the nested ifs:
+				// {
+				//  let $if_let_matched = false;
+				//  const $if_let_value = x;
+				//  if ($if_let_value != undefined) {
+				//    const a = $if_let_value;
+				//    const $if_let_value0 = y;
+				//    if ($if_let_value0 != undefined) {
+				//      const b = $if_let_value0;
+				//      if (a > b) {
+				//        $if_let_matched = true;
+				//        ...
+				//      }
+				//    }
+				//  }
+				//  if (!$if_let_matched) {
+				//    ...
+				//  }
+				// }
+				let if_let_matched = "$if_let_matched";
+				let has_else = !else_if_statements.is_empty() || else_statements.is_some();
+				let needs_matched_flag = !conditions.is_empty() && has_else;
+				if needs_matched_flag {
+					code.line(format!("let {if_let_matched} = false;"));
+				}
+
 				code.line(new_code!(
 					&var_name.span,
 					"const ",
@@ -1199,11 +2020,57 @@ impl<'a> JSifier<'a> {
 				} else {
 					code.line(format!("const {} = {};", var_name, if_let_value));
 				}
-				code.add_code(self.jsify_scope_body(statements, ctx));
+
+				for (index, condition) in conditions.iter().enumerate() {
+					match condition {
+						IfLetCondition::Let {
+							reassignable,
+							var_name,
+							value,
+						} => {
+							let chained_value = format!("{if_let_value}{index}");
+							code.line(new_code!(
+								&var_name.span,
+								"const ",
+								chained_value.clone(),
+								" = ",
+								self.jsify_expression(value, ctx),
+								";"
+							));
+							code.open(format!("if ({chained_value} != undefined) {{"));
+							if *reassignable {
+								code.line(format!("let {} = {};", var_name, chained_value));
+							} else {
+								code.line(format!("const {} = {};", var_name, chained_value));
+							}
+						}
+						IfLetCondition::Guard(guard) => {
+							code.open(new_code!(&guard.span, "if (", self.jsify_expression(guard, ctx), ") {"));
+						}
+					}
+				}
+				if needs_matched_flag {
+					code.line(format!("{if_let_matched} = true;"));
+				}
+				code.add_code(self.jsify_branch(statements, ctx));
+				for _ in conditions {
+					code.close("}");
+				}
 				code.close("}");
 
+				if needs_matched_flag {
+					code.open(format!("if (!{if_let_matched}) {{"));
+				}
+
 				if else_if_statements.len() > 0 {
-					self.jsify_else_if_statements(&mut code, else_if_statements, 0, else_statements, ctx);
+					self.jsify_else_if_statements(
+						&mut code,
+						else_if_statements,
+						0,
+						else_statements,
+						needs_matched_flag,
+						ctx,
+					);
 					for else_if_statement in else_if_statements {
 						if let ElseIfs::ElseIfLetBlock(_) = else_if_statement {
 							// "else_if_let_block" statements emit "else {" in jsify_else_if_statements(),
@@ -1213,8 +2080,16 @@ impl<'a> JSifier<'a> {
 						}
 					}
 				} else if let Some(else_scope) = else_statements {
-					code.open("else {");
-					code.add_code(self.jsify_scope_body(else_scope, ctx));
+					if needs_matched_flag {
+						code.add_code(self.jsify_branch(else_scope, ctx));
+					} else {
+						code.open("else {");
+						code.add_code(self.jsify_branch(else_scope, ctx));
+						code.close("}");
+					}
+				}
+
+				if needs_matched_flag {
 					code.close("}");
 				}
 
@@ -1232,7 +2107,7 @@ impl<'a> JSifier<'a> {
 					self.jsify_expression(condition, ctx),
 					") {"
 				));
-				code.add_code(self.jsify_scope_body(statements, ctx));
+				code.add_code(self.jsify_branch(statements, ctx));
 				code.close("}");
 
 				for else_if_block in else_if_statements {
@@ -1240,13 +2115,18 @@ impl<'a> JSifier<'a> {
 					// TODO: this puts the "else if" in a separate line from the closing block but
 					// technically that shouldn't be a problem, its just ugly
 					code.open(new_code!(&else_if_block.condition.span, "else if (", condition, ") {"));
-					code.add_code(self.jsify_scope_body(&else_if_block.statements, ctx));
+					code.add_code(self.jsify_branch(&else_if_block.statements, ctx));
 					code.close("}");
 				}
 
 				if let Some(else_scope) = else_statements {
 					code.open("else {");
-					code.add_code(self.jsify_scope_body(else_scope, ctx));
+					code.add_code(self.jsify_branch(else_scope, ctx));
+					code.close("}");
+				} else if let Some(coverage) = &self.coverage {
+					// count how many times the implicit `else` was taken too
+					code.open("else {");
+					code.line(coverage.counter(CounterKind::Branch, &statement.span));
 					code.close("}");
 				}
 			}
@@ -1260,12 +2140,13 @@ impl<'a> JSifier<'a> {
 				};
 
 				match variable {
-					Reference::ElementAccess { object, index } => {
+					Reference::ElementAccess { object, index, .. } => {
 						let object = self.jsify_expression(object, ctx);
 						let index = self.jsify_expression(index, ctx);
 						code.line(new_code!(
 							&statement.span,
-							"$helpers.assign(",
+							self.helpers(),
+							".assign(",
 							object,
 							", ",
 							index,
@@ -1317,13 +2198,8 @@ impl<'a> JSifier<'a> {
 				// Struct schemas are emitted before jsification phase
 			}
 			StmtKind::Enum(enu) => {
-				let Enum {
-					name,
-					values,
-					access: _,
-				} = enu;
-				code.open(format!("const {name} ="));
-				code.add_code(self.jsify_enum(name, values));
+				code.open(format!("const {} =", enu.name));
+				code.add_code(self.jsify_enum(enu));
 				code.close(";");
 			}
 			StmtKind::TryCatch {
@@ -1377,20 +2253,30 @@ impl<'a> JSifier<'a> {
 		code
 	}
 
-	fn jsify_enum(&self, name: &Symbol, values: &IndexMap<Symbol, Option<String>>) -> CodeMaker {
-		let mut code = CodeMaker::with_source(&name.span);
+	fn jsify_enum(&self, enu: &Enum) -> CodeMaker {
+		let mut code = CodeMaker::with_source(&enu.name.span);
 		let mut value_index = 0;
 
 		code.open("(function (tmp) {");
 
-		for value in values.keys() {
+		for value in enu.values.keys() {
+			// Values are represented by their backing string (or bit, for flags enums), so they're converted to and from
+			// Json as is
+			let backing_value = if enu.is_flags {
+				new_code!(&value.span, (1u32 << value_index).to_string())
+			} else {
+				match enu.backing_values.get(value) {
+					Some(backing_value) => new_code!(&value.span, "\"", backing_value.as_str(), "\""),
+					None => new_code!(&value.span, "\"", jsify_symbol(value), "\""),
+				}
+			};
 			code.line(new_code!(
 				&value.span,
 				"tmp[\"",
 				jsify_symbol(value),
-				"\"] = \"",
-				jsify_symbol(value),
-				"\";"
+				"\"] = ",
+				backing_value,
+				";"
 			));
 
 			value_index = value_index + 1;
@@ -1507,6 +2393,10 @@ impl<'a> JSifier<'a> {
 			None => ("".to_string(), " => ".to_string()),
 		};
 
+		// The inflight copy of a phase independent function is emitted as inflight code, so it awaits its calls and
+		// refers to lifted objects through their tokens
+		let emit_inflight = func_def.signature.phase == Phase::Inflight || ctx.visit_ctx.current_phase() == Phase::Inflight;
+
 		let body = match &func_def.body {
 			FunctionBody::Statements(scope) => {
 				let function_env = self.types.get_scope_env(&scope);
@@ -1515,15 +2405,30 @@ impl<'a> JSifier<'a> {
 					&func_def.signature,
 					func_def.is_static,
 					function_env,
-					|ctx| self.jsify_scope_body(scope, ctx),
+					&func_def.span,
+					|ctx| {
+						if emit_inflight {
+							ctx.visit_ctx.push_phase(Phase::Inflight);
+						}
+						let body = self.jsify_scope_body(scope, ctx);
+						if emit_inflight {
+							ctx.visit_ctx.pop_phase();
+						}
+						body
+					},
 				)
 			}
 			FunctionBody::External(extern_path) => {
 				// check if the first part of the path is the node module directory
 				let require_path = self.get_require_path(extern_path, &func_def.span);
 
+				if ctx.visit_ctx.current_phase() == Phase::Inflight {
+					self.check_extern_node_target(extern_path, &func_def.span);
+				}
+
 				if let Some(require_path) = require_path {
 					let require = if ctx.visit_ctx.current_phase() == Phase::Inflight {
+						self.inflight_usage.borrow_mut().externs.insert(require_path.clone());
 						"require"
 					} else {
 						EXTERN_VAR
@@ -1547,7 +2452,7 @@ impl<'a> JSifier<'a> {
 		}
 
 		// if this is "constructor" it cannot be async
-		if name != JS_CONSTRUCTOR && matches!(func_def.signature.phase, Phase::Inflight) {
+		if name != JS_CONSTRUCTOR && emit_inflight {
 			prefix.push("async")
 		}
 
@@ -1572,6 +2477,35 @@ impl<'a> JSifier<'a> {
 		}
 	}
 
+	/// Makes sure an extern file used inflight supports the Node.js version inflight code targets, based on the
+	/// `engines` field of its package.json
+	fn check_extern_node_target(&self, extern_path: &Utf8Path, span: &WingSpan) {
+		let Some(target) = self.node_target else {
+			return;
+		};
+		let Some((min_version, package_json)) = extern_min_node_version(extern_path) else {
+			return;
+		};
+		if min_version > target.major {
+			report_diagnostic(Diagnostic {
+				message: format!(
+					"Extern \"{}\" requires Node.js {} or later, but inflight code targets Node.js {}",
+					extern_path.file_name().unwrap_or(extern_path.as_str()),
+					min_version,
+					target.major
+				),
+				span: Some(span.clone()),
+				annotations: vec![],
+				hints: vec![format!(
+					"the requirement comes from the \"engines\" field of {}",
+					package_json
+				)],
+				severity: DiagnosticSeverity::Error,
+				code: None,
+			});
+		}
+	}
+
 	fn get_require_path(&self, absolute_target: &Utf8PathBuf, span: &WingSpan) -> Option<String> {
 		let entrypoint_is_file = self.compilation_init_path.is_file();
 		let entrypoint_dir = if entrypoint_is_file {
@@ -1591,6 +2525,7 @@ impl<'a> JSifier<'a> {
 					hints: vec![],
 					span: Some(span.clone()),
 					severity: DiagnosticSeverity::Error,
+					code: None,
 				});
 				return None;
 			}
@@ -1655,17 +2590,17 @@ impl<'a> JSifier<'a> {
 				source_file: ctx.source_file,
 			};
 
-			// emit the inflight side of the class into a separate file
-			let inflight_class_code = self.jsify_class_inflight(class_type, &class, ctx);
-
 			// if this is inflight/independent, class, just emit the inflight class code inline and move on
-			// with your life.
+			// with your life (whatever it uses is required by the inflight file of the class it's in).
 			if ctx.visit_ctx.current_phase() != Phase::Preflight {
-				return inflight_class_code;
+				return self.jsify_class_inflight(class_type, &class, ctx);
 			}
 
-			// emit the inflight file
-			self.emit_inflight_file(&class, inflight_class_code, ctx);
+			// emit the inflight side of the class into a separate file, which requires only what the class uses
+			let outer_usage = self.inflight_usage.take();
+			let inflight_class_code = self.jsify_class_inflight(class_type, &class, ctx);
+			let usage = self.inflight_usage.replace(outer_usage);
+			let inflight_filename = self.emit_inflight_file(&class, inflight_class_code, usage, ctx);
 
 			// lets write the code for the preflight side of the class
 			// TODO: why would we want to do this for inflight classes?? maybe return here in that case?
@@ -1721,7 +2656,7 @@ impl<'a> JSifier<'a> {
 
 			// emit the `_toInflight` and `_toInflightType` methods (TODO: renamed to `_liftObject` and
 			// `_liftType`).
-			code.add_code(self.jsify_to_inflight_type_method(&class, ctx));
+			code.add_code(self.jsify_to_inflight_type_method(&class, &inflight_filename, ctx));
 			code.add_code(self.jsify_lifted_state(&class.name, class_type));
 
 			// emit `onLift` and `onLiftType` to bind permissions and environment variables to inflight hosts
@@ -1789,6 +2724,7 @@ impl<'a> JSifier<'a> {
 			&class.initializer.signature,
 			class.initializer.is_static,
 			self.types.get_scope_env(init_statements),
+			&class.initializer.span,
 			|ctx| self.jsify_scope_body(init_statements, ctx),
 		);
 		body_code.add_code(init_code);
@@ -1799,9 +2735,7 @@ impl<'a> JSifier<'a> {
 		code
 	}
 
-	fn jsify_to_inflight_type_method(&self, class: &AstClass, ctx: &JSifyContext) -> CodeMaker {
-		let client_path = self.inflight_filename(class);
-
+	fn jsify_to_inflight_type_method(&self, class: &AstClass, client_path: &str, ctx: &JSifyContext) -> CodeMaker {
 		let mut code = CodeMaker::with_source(&class.name.span);
 
 		code.open("static _toInflightType() {");
@@ -1893,6 +2827,13 @@ impl<'a> JSifier<'a> {
 			class_code.line(self.jsify_function(Some(class_type), def, false, ctx));
 		}
 
+		// phase independent methods (struct statics) are emitted on both sides of the class
+		for def in class.all_methods(false) {
+			if def.signature.phase == Phase::Independent {
+				class_code.line(self.jsify_function(Some(class_type), def, false, ctx));
+			}
+		}
+
 		// emit the $inflight_init function (if it has a body).
 		if let FunctionBody::Statements(s) = &class.inflight_initializer.body {
 			if !s.statements.is_empty() {
@@ -1913,7 +2854,14 @@ impl<'a> JSifier<'a> {
 			.insert(struct_name, schema);
 	}
 
-	fn emit_inflight_file(&self, class: &AstClass, inflight_class_code: CodeMaker, ctx: &mut JSifyContext) {
+	/// Emits the inflight side of a class to its own file, returning the name of the file
+	fn emit_inflight_file(
+		&self,
+		class: &AstClass,
+		inflight_class_code: CodeMaker,
+		usage: InflightUsage,
+		ctx: &mut JSifyContext,
+	) -> String {
 		let name = &class.name.name;
 		let mut code = CodeMaker::with_source(&class.name.span);
 
@@ -1927,18 +2875,50 @@ impl<'a> JSifier<'a> {
 			Default::default()
 		};
 
-		let filename = self.inflight_filename(class);
-		let sourcemap_file = format!("{}.map", filename);
-
+		// Only require the SDK modules the class actually uses, since everything an inflight file requires ends up in
+		// the bundle of every inflight function that lifts the class
+		let InflightUsage {
+			npm_modules,
+			externs,
+			helpers,
+			macros,
+		} = usage;
+		// the coverage counters are created by the helpers
+		let uses_helpers = helpers || self.coverage.is_some();
+
+		let mut packages = IndexSet::new();
 		code.line("\"use strict\";");
-		code.line(format!("const {HELPERS_VAR} = require(\"@winglang/sdk/lib/helpers\");"));
-		code.line(format!("const {MACROS_VAR} = require(\"@winglang/sdk/lib/macros\");"));
+		for alias in npm_modules {
+			let package = &self.npm_modules[&alias];
+			code.line(format!("const {alias} = require(\"{package}\");"));
+			packages.insert(package.clone());
+		}
+		if uses_helpers {
+			code.line(format!("const {HELPERS_VAR} = require(\"@winglang/sdk/lib/helpers\");"));
+		}
+		if macros {
+			code.line(format!("const {MACROS_VAR} = require(\"@winglang/sdk/lib/macros\");"));
+		}
+		if uses_helpers || macros {
+			packages.insert(WINGSDK_ASSEMBLY_NAME.to_string());
+		}
+		if let Some(coverage) = &self.coverage {
+			code.line(coverage.preamble());
+		}
 		code.open(format!("module.exports = function({{ {inputs} }}) {{"));
 		code.add_code(inflight_class_code);
 		code.line(format!("return {name};"));
 		code.close("}");
+
+		let filename = self.inflight_filename(class, &code.to_string());
+		let sourcemap_file = format!("{}.map", filename);
 		code.line(format!("//# sourceMappingURL={sourcemap_file}"));
 
+		self
+			.inflight_dependencies
+			.borrow_mut()
+			.insert(filename.clone(), InflightDependencies::new(name, externs, packages));
+
 		let root_source = ctx.source_file.unwrap().to_string();
 
 		// emit the inflight class to a file
@@ -1962,6 +2942,8 @@ impl<'a> JSifier<'a> {
 			Ok(()) => {}
 			Err(err) => report_diagnostic(err.into()),
 		}
+
+		filename
 	}
 
 	fn jsify_inflight_binding_constructor(&self, class: &AstClass, class_type: TypeRef, class_code: &mut CodeMaker) {
@@ -2063,7 +3045,8 @@ impl<'a> JSifier<'a> {
 			let var_info = method.expect(&format!("method \"{name}\" doesn't exist in {class_name}"));
 
 			let is_static = m.is_static;
-			let is_inflight = var_info.phase == Phase::Inflight;
+			// phase independent methods can be called inflight too
+			let is_inflight = var_info.phase != Phase::Preflight;
 			let filter = match bind_method_kind {
 				BindMethod::Instance => is_inflight && !is_static,
 				BindMethod::Type => is_inflight && is_static && name.name != CLASS_INFLIGHT_INIT_NAME,
@@ -2114,15 +3097,8 @@ impl<'a> JSifier<'a> {
 			for (method_name, method_qual) in lift_qualifications {
 				bind_method.open(format!("\"{method_name}\": [",));
 				for (code, method_lift_qual) in method_qual {
-					let ops = method_lift_qual.ops.iter().join(", ");
-					// To keep the code concise treat no ops, single op and multiple ops differenly here, although the multiple ops is the generic case
-					if method_lift_qual.ops.len() == 0 {
-						bind_method.line(format!("[{code}, []],"));
-					} else if method_lift_qual.ops.len() == 1 {
-						bind_method.line(format!("[{code}, {ops}],"));
-					} else {
-						bind_method.line(format!("[{code}, [].concat({ops})],"));
-					}
+					let ops = method_lift_qual.ops.iter().map(|op| format!("\"{op}\"")).join(", ");
+					bind_method.line(format!("[{code}, [{ops}]],"));
 				}
 				bind_method.close("],");
 			}
@@ -2134,17 +3110,27 @@ impl<'a> JSifier<'a> {
 		bind_method
 	}
 
-	fn inflight_filename(&self, class: &AstClass) -> String {
-		let mut file_map = self.inflight_file_map.borrow_mut();
-		let id: usize = if file_map.contains_key(&class.name.span.file_id) {
-			file_map[&class.name.span.file_id]
+	/// The name of the file the inflight side of a class is emitted to. It's derived from the class, the source file it's
+	/// defined in and the code of the file, so it only changes when the file does, and caches keyed by the names of the
+	/// generated files (e.g. of bundled assets) aren't invalidated by changes to other classes.
+	fn inflight_filename(&self, class: &AstClass, code: &str) -> String {
+		let suffix = self.file_name_suffix(Utf8Path::new(&class.name.span.file_id));
+		let hash = short_content_hash(&format!("{suffix}\n{code}"));
+		format!("inflight.{}-{}.cjs", class.name.name, hash)
+	}
+
+	/// A suffix for the names of the JS files generated for a source file. It's derived from the path of the source
+	/// file relative to where compilation started, so it doesn't change when other files are added or removed.
+	fn file_name_suffix(&self, source_path: &Utf8Path) -> String {
+		let root = if self.compilation_init_path.is_dir() {
+			self.compilation_init_path
 		} else {
-			let mut id = self.inflight_file_counter.borrow_mut();
-			*id += 1;
-			file_map.insert(class.name.span.file_id.clone(), *id);
-			*id
+			self
+				.compilation_init_path
+				.parent()
+				.unwrap_or(self.compilation_init_path)
 		};
-		format!("inflight.{}-{}.cjs", class.name.name, id)
+		short_content_hash(&make_relative_path(root.as_str(), source_path.as_str()))
 	}
 }
 
@@ -2162,6 +3148,56 @@ fn jsify_function_parameters(func_def: &FunctionDefinition) -> CodeMaker {
 	new_code!(&func_def.span, parameter_list)
 }
 
+/// Describes the values of a type for `$helpers.checkInlineJsResult`, or returns None if the type's values aren't
+/// checked. Only primitives and collections are checked, the values of other types are trusted to be of the type.
+fn inline_js_type(t: TypeRef) -> Option<String> {
+	let describe = |t: TypeRef| inline_js_type(t).unwrap_or_else(|| "null".to_string());
+	match &*t {
+		Type::String => Some("\"str\"".to_string()),
+		Type::Number => Some("\"num\"".to_string()),
+		Type::Boolean => Some("\"bool\"".to_string()),
+		Type::Optional(t) => Some(format!("{{ optional: {} }}", describe(*t))),
+		Type::Array(t) | Type::MutArray(t) => Some(format!("{{ array: {} }}", describe(*t))),
+		Type::Map(t) | Type::MutMap(t) => Some(format!("{{ map: {} }}", describe(*t))),
+		Type::Set(t) | Type::MutSet(t) => Some(format!("{{ set: {} }}", describe(*t))),
+		Type::Anything
+		| Type::Duration
+		| Type::Datetime
+		| Type::Regex
+		| Type::Void
+		| Type::Never
+		| Type::Unknown
+		| Type::Json(_)
+		| Type::MutJson
+		| Type::Nil
+		| Type::Unresolved
+		| Type::Inferred(_)
+		| Type::Function(_)
+		| Type::Class(_)
+		| Type::Interface(_)
+		| Type::Struct(_)
+		| Type::Enum(_)
+		| Type::Literal(_)
+		| Type::Stringable => None,
+	}
+}
+
+/// Whether a statement runs code of its own (rather than declaring types or only containing other statements), so
+/// coverage counts it
+fn is_executable_statement(kind: &StmtKind) -> bool {
+	!matches!(
+		kind,
+		StmtKind::Bring { .. }
+			| StmtKind::SuperConstructor { .. }
+			| StmtKind::Scope(_)
+			| StmtKind::Class(_)
+			| StmtKind::Interface(_)
+			| StmtKind::Struct(_)
+			| StmtKind::Enum(_)
+			| StmtKind::ExplicitLift(_)
+	)
+}
+
 fn jsify_symbol(symbol: &Symbol) -> CodeMaker {
 	new_code!(&symbol.span, &symbol.name)
 }
@@ -2190,16 +3226,25 @@ fn parent_class_phase(ctx: &JSifyContext<'_>) -> Phase {
 ///
 /// Symbols that are public or internal are exported, though internal symbols
 /// should be hidden at a library level.
-fn get_exported_symbols(scope: &Scope) -> Vec<Symbol> {
+fn get_exported_symbols(scope: &Scope, dead_declarations: &HashSet<WingSpan>) -> Vec<Symbol> {
 	let mut symbols = Vec::new();
 
-	for stmt in &scope.statements {
+	for stmt in scope
+		.statements
+		.iter()
+		.filter(|stmt| !dead_declarations.contains(&stmt.span))
+	{
 		match &stmt.kind {
 			StmtKind::Bring { .. } => {}
 			StmtKind::SuperConstructor { .. } => {}
-			StmtKind::Let { .. } => {}
+			StmtKind::Let { var_name, access, .. } => {
+				if *access == AccessModifier::Public || *access == AccessModifier::Internal {
+					symbols.push(var_name.clone());
+				}
+			}
 			StmtKind::ForLoop { .. } => {}
 			StmtKind::While { .. } => {}
+			StmtKind::WhileLet { .. } => {}
 			StmtKind::IfLet(IfLet { .. }) => {}
 			StmtKind::If { .. } => {}
 			StmtKind::Break => {}
@@ -0,0 +1,113 @@
+use std::fmt::Write;
+
+use crate::files::Files;
+
+use super::PREFLIGHT_FILE_NAME;
+
+/// Name of the environment variable that makes jsification emit a single, self-contained preflight file
+pub const BUNDLE_ENV: &str = "WING_BUNDLE";
+
+/// The name the program's original preflight entrypoint is embedded under in a bundle
+const BUNDLED_ENTRYPOINT_FILE_NAME: &str = "preflight.entry.cjs";
+
+/// Bundles all the generated files (preflight and inflight code, struct schemas and source maps) into a single
+/// `preflight.cjs` file, so small programs can be deployed (or run by the playground) by moving around one file.
+///
+/// The bundle embeds the other files as strings. When it runs, it writes them next to itself and runs the program's
+/// original entrypoint, so relative paths between the generated files (like the ones inflight clients `require`)
+/// keep working without rewriting them.
+pub fn bundle_files(files: &Files) -> Files {
+	let mut embedded = files
+		.iter()
+		.map(|(path, content)| {
+			if path == PREFLIGHT_FILE_NAME {
+				let content = content.replace(
+					&format!("//# sourceMappingURL={PREFLIGHT_FILE_NAME}.map"),
+					&format!("//# sourceMappingURL={BUNDLED_ENTRYPOINT_FILE_NAME}.map"),
+				);
+				(BUNDLED_ENTRYPOINT_FILE_NAME.to_string(), content)
+			} else if path == format!("{PREFLIGHT_FILE_NAME}.map").as_str() {
+				(format!("{BUNDLED_ENTRYPOINT_FILE_NAME}.map"), content.clone())
+			} else {
+				(path.to_string(), content.clone())
+			}
+		})
+		.collect::<Vec<_>>();
+	// Keep the output deterministic
+	embedded.sort();
+
+	let mut bundle = String::new();
+	_ = writeln!(bundle, "\"use strict\";");
+	_ = writeln!(bundle, "const $bundledFiles = {{");
+	for (path, content) in &embedded {
+		let path = serde_json::to_string(path).expect("strings are serializable");
+		let content = serde_json::to_string(content).expect("strings are serializable");
+		_ = writeln!(bundle, "  {path}: {content},");
+	}
+	_ = writeln!(bundle, "}};");
+	_ = writeln!(bundle, "const $fs = require(\"fs\");");
+	_ = writeln!(bundle, "const $path = require(\"path\");");
+	_ = writeln!(
+		bundle,
+		"for (const [name, content] of Object.entries($bundledFiles)) {{"
+	);
+	_ = writeln!(bundle, "  const filePath = $path.join(__dirname, name);");
+	_ = writeln!(
+		bundle,
+		"  $fs.mkdirSync($path.dirname(filePath), {{ recursive: true }});"
+	);
+	_ = writeln!(bundle, "  $fs.writeFileSync(filePath, content);");
+	_ = writeln!(bundle, "}}");
+	_ = writeln!(bundle, "require(\"./{BUNDLED_ENTRYPOINT_FILE_NAME}\");");
+
+	let mut bundled = Files::new();
+	bundled.update_file(PREFLIGHT_FILE_NAME, bundle);
+	bundled
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{bundle_files, BUNDLED_ENTRYPOINT_FILE_NAME};
+	use crate::{files::Files, test_utils::compile_source_with_options, CompileOptions};
+
+	#[test]
+	fn bundles_all_files_into_the_preflight_entrypoint() {
+		let mut files = Files::new();
+		files.update_file(
+			"preflight.cjs",
+			"require(\"./inflight.Foo-1.cjs\");\n//# sourceMappingURL=preflight.cjs.map".to_string(),
+		);
+		files.update_file("preflight.cjs.map", "{}".to_string());
+		files.update_file("inflight.Foo-1.cjs", "module.exports = \"`quoted`\";".to_string());
+
+		let bundled = bundle_files(&files);
+		assert!(!bundled.contains_file("inflight.Foo-1.cjs"));
+		assert!(!bundled.contains_file("preflight.cjs.map"));
+
+		let bundle = bundled.get_file("preflight.cjs").unwrap();
+		assert!(bundle.contains(r#"  "inflight.Foo-1.cjs": "module.exports = \"`quoted`\";","#));
+		assert!(bundle.contains(
+			r#""preflight.entry.cjs": "require(\"./inflight.Foo-1.cjs\");\n//# sourceMappingURL=preflight.entry.cjs.map","#
+		));
+		assert!(bundle.contains(r#"  "preflight.entry.cjs.map": "{}","#));
+		assert!(bundle.ends_with("require(\"./preflight.entry.cjs\");\n"));
+	}
+
+	#[test]
+	fn bundles_programs_compiled_with_the_bundle_option() {
+		let source = r#"
+bring cloud;
+new cloud.Function(inflight () => {});
+"#;
+		let bundle = |bundle: bool| {
+			let options = CompileOptions {
+				bundle,
+				..Default::default()
+			};
+			compile_source_with_options(source, &options).read("preflight.cjs")
+		};
+
+		assert!(bundle(true).contains(BUNDLED_ENTRYPOINT_FILE_NAME));
+		assert!(!bundle(false).contains(BUNDLED_ENTRYPOINT_FILE_NAME));
+	}
+}
@@ -0,0 +1,106 @@
+use std::cell::RefCell;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Serialize;
+
+use crate::diagnostic::WingSpan;
+
+use super::HELPERS_VAR;
+
+/// Name of the environment variable that makes jsification instrument the generated code with coverage counters
+pub const COVERAGE_ENV: &str = "WING_COVERAGE";
+
+/// Maps each coverage counter to the Wing code it counts
+pub const COVERAGE_MAP_FILE_NAME: &str = "coverage-map.json";
+
+/// The directory (in the output directory) instrumented programs write their counters to, one file per process
+const COVERAGE_HITS_DIR: &str = "coverage";
+
+/// The variable holding the counters in every generated file
+const COVERAGE_VAR: &str = "$coverage";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CounterKind {
+	/// Counts how many times a statement ran
+	Statement,
+	/// Counts how many times a branch of an `if` (including an implicit `else`) was taken
+	Branch,
+}
+
+#[derive(Serialize)]
+struct Counter {
+	kind: CounterKind,
+	span: WingSpan,
+}
+
+/// The coverage counters of a compilation. Counters are numbered across all the generated files (preflight and
+/// inflight), so the counts written by all the processes running the program can be merged using a single map.
+pub struct Coverage {
+	hits_dir: Utf8PathBuf,
+	counters: RefCell<Vec<Counter>>,
+}
+
+impl Coverage {
+	pub fn new(out_dir: &Utf8Path) -> Self {
+		Self {
+			hits_dir: out_dir.join(COVERAGE_HITS_DIR),
+			counters: RefCell::new(vec![]),
+		}
+	}
+
+	/// The statement every instrumented file starts with, which makes the counters available to its code
+	pub fn preamble(&self) -> String {
+		format!(
+			"const {COVERAGE_VAR} = {HELPERS_VAR}.coverage({});",
+			serde_json::to_string(self.hits_dir.as_str()).expect("serialize path")
+		)
+	}
+
+	/// Adds a counter for the code at `span`, returning the statement that increments it
+	pub fn counter(&self, kind: CounterKind, span: &WingSpan) -> String {
+		let mut counters = self.counters.borrow_mut();
+		counters.push(Counter {
+			kind,
+			span: span.clone(),
+		});
+		format!("{COVERAGE_VAR}.hit({});", counters.len() - 1)
+	}
+
+	/// The contents of the coverage map: a list of counters, each with its kind and the span of the code it counts
+	pub fn to_map_json(&self) -> String {
+		serde_json::to_string(&*self.counters.borrow()).expect("serialize coverage map")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use camino::Utf8Path;
+
+	use super::{CounterKind, Coverage};
+	use crate::diagnostic::{WingLocation, WingSpan};
+
+	#[test]
+	fn numbers_counters_and_maps_them_to_spans() {
+		let coverage = Coverage::new(Utf8Path::new("/app/target/main.wsim/.wing"));
+		assert_eq!(
+			coverage.preamble(),
+			"const $coverage = $helpers.coverage(\"/app/target/main.wsim/.wing/coverage\");"
+		);
+
+		let span = |line| WingSpan {
+			start: WingLocation { line, col: 0 },
+			end: WingLocation { line, col: 4 },
+			file_id: "/app/main.w".to_string(),
+			start_offset: 0,
+			end_offset: 0,
+		};
+		assert_eq!(coverage.counter(CounterKind::Statement, &span(1)), "$coverage.hit(0);");
+		assert_eq!(coverage.counter(CounterKind::Branch, &span(2)), "$coverage.hit(1);");
+
+		let map: serde_json::Value = serde_json::from_str(&coverage.to_map_json()).unwrap();
+		assert_eq!(map[1]["kind"], "branch");
+		assert_eq!(map[1]["span"]["start"]["line"], 2);
+		assert_eq!(map[1]["span"]["file_id"], "/app/main.w");
+	}
+}
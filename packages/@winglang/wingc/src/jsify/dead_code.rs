@@ -0,0 +1,401 @@
+use std::collections::HashSet;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use indexmap::IndexMap;
+
+use crate::{
+	ast::{
+		CatchBlock, ElseIfs, ExprKind, FunctionDefinition, IfLet, IfLetCondition, Reference, Scope, Stmt, StmtKind, Symbol,
+		UserDefinedType,
+	},
+	diagnostic::WingSpan,
+	visit::{self, Visit},
+};
+
+/// Name of the environment variable that enables dead code elimination during jsification
+pub const DEAD_CODE_ELIMINATION_ENV: &str = "WING_DEAD_CODE_ELIMINATION";
+
+/// Returns the spans of the module-level declarations that can be left out of the generated preflight code because
+/// nothing that runs ever references them: classes (including the classes generated for inflight closures) and
+/// helper functions defined with `let`.
+///
+/// A declaration is kept if it's referenced by any module-level statement that isn't a declaration like this, or by
+/// a declaration that is itself kept. Names aren't resolved against the type checker's environments, instead a
+/// plain reference (`Foo`, `Foo.bar()`) can only refer to a declaration of the same file unless a local symbol
+/// shadows it, while a member access (`lib.Foo`) can only refer to a declaration of another (brought) file.
+pub fn find_dead_declarations(asts: &IndexMap<Utf8PathBuf, Scope>) -> HashSet<WingSpan> {
+	let mut live = Mentions::default();
+	let mut candidates = vec![];
+
+	for (path, scope) in asts {
+		for stmt in &scope.statements {
+			let Some(name) = removable_declaration_name(stmt) else {
+				// Statements that aren't removable always run
+				live.extend(collect_mentions(path, stmt, None));
+				continue;
+			};
+			candidates.push(Candidate {
+				file: path,
+				name: &name.name,
+				span: stmt.span.clone(),
+				mentions: collect_mentions(path, stmt, Some(name)),
+			});
+		}
+	}
+
+	// Keep marking declarations as live until none of the remaining ones is referenced by a live statement
+	loop {
+		let (kept, dead): (Vec<_>, Vec<_>) = candidates
+			.into_iter()
+			.partition(|candidate| live.refers_to(candidate.file, candidate.name));
+		candidates = dead;
+		if kept.is_empty() {
+			break;
+		}
+		for candidate in kept {
+			live.extend(candidate.mentions);
+		}
+	}
+
+	candidates.into_iter().map(|candidate| candidate.span).collect()
+}
+
+struct Candidate<'a> {
+	file: &'a Utf8Path,
+	name: &'a str,
+	span: WingSpan,
+	mentions: Mentions,
+}
+
+/// The names a statement refers to, with the file they're referenced from
+#[derive(Default)]
+struct Mentions {
+	/// Plain references, which refer to declarations of the same file
+	names: HashSet<(Utf8PathBuf, String)>,
+	/// Member accesses, which might refer to declarations of other files
+	members: HashSet<(Utf8PathBuf, String)>,
+}
+
+impl Mentions {
+	fn extend(&mut self, other: Mentions) {
+		self.names.extend(other.names);
+		self.members.extend(other.members);
+	}
+
+	/// Whether any of the references might refer to the declaration of `name` in `file`
+	fn refers_to(&self, file: &Utf8Path, name: &str) -> bool {
+		self.names.contains(&(file.to_owned(), name.to_string()))
+			|| self
+				.members
+				.iter()
+				.any(|(other_file, member)| member == name && other_file != file)
+	}
+}
+
+/// Returns the name of the declaration if the statement declares something that can be omitted when unused
+fn removable_declaration_name(stmt: &Stmt) -> Option<&Symbol> {
+	match &stmt.kind {
+		StmtKind::Class(class) => Some(&class.name),
+		// Defining a function has no side effects, unlike other initial values
+		StmtKind::Let {
+			var_name,
+			initial_value,
+			reassignable: false,
+			..
+		} if matches!(initial_value.kind, ExprKind::FunctionClosure(_)) => Some(var_name),
+		// Listed explicitly so new kinds of declarations are considered for elimination
+		StmtKind::Let { .. }
+		| StmtKind::Bring { .. }
+		| StmtKind::SuperConstructor { .. }
+		| StmtKind::ForLoop { .. }
+		| StmtKind::While { .. }
+		| StmtKind::WhileLet { .. }
+		| StmtKind::IfLet(_)
+		| StmtKind::If { .. }
+		| StmtKind::Break
+		| StmtKind::Continue
+		| StmtKind::Return(_)
+		| StmtKind::Throw(_)
+		| StmtKind::Expression(_)
+		| StmtKind::Assignment { .. }
+		| StmtKind::Scope(_)
+		| StmtKind::Interface(_)
+		| StmtKind::Struct(_)
+		| StmtKind::Enum(_)
+		| StmtKind::TryCatch { .. }
+		| StmtKind::ExplicitLift(_) => None,
+	}
+}
+
+/// Returns the references made by a module-level statement of `file`, except for the name of the declaration itself
+fn collect_mentions(file: &Utf8Path, stmt: &Stmt, declaration: Option<&Symbol>) -> Mentions {
+	let mut collector = MentionCollector {
+		file,
+		declaration,
+		locals: vec![],
+		mentions: Mentions::default(),
+	};
+	collector.visit_stmt(stmt);
+	collector.mentions
+}
+
+struct MentionCollector<'a> {
+	file: &'a Utf8Path,
+	declaration: Option<&'a Symbol>,
+	/// The names declared in each of the nested scopes being visited, which shadow module-level declarations
+	locals: Vec<HashSet<String>>,
+	mentions: Mentions,
+}
+
+impl MentionCollector<'_> {
+	fn mention(&mut self, name: &Symbol) {
+		if self.declaration.is_some_and(|d| d.span == name.span) {
+			return;
+		}
+		if self.locals.iter().any(|scope| scope.contains(&name.name)) {
+			return;
+		}
+		self.mentions.names.insert((self.file.to_owned(), name.name.clone()));
+	}
+
+	fn mention_member(&mut self, name: &Symbol) {
+		self.mentions.members.insert((self.file.to_owned(), name.name.clone()));
+	}
+
+	/// Declares a local symbol in the innermost scope (module-level declarations aren't locals)
+	fn declare(&mut self, name: &Symbol) {
+		if let Some(scope) = self.locals.last_mut() {
+			scope.insert(name.name.clone());
+		}
+	}
+
+	/// Visits a statement whose bindings are only visible in its nested scopes
+	fn with_bindings<'b>(&mut self, bindings: impl IntoIterator<Item = &'b Symbol>, f: impl FnOnce(&mut Self)) {
+		self
+			.locals
+			.push(bindings.into_iter().map(|binding| binding.name.clone()).collect());
+		f(self);
+		self.locals.pop();
+	}
+}
+
+impl<'ast> Visit<'ast> for MentionCollector<'_> {
+	fn visit_scope(&mut self, node: &'ast Scope) {
+		self.locals.push(HashSet::new());
+		visit::visit_scope(self, node);
+		self.locals.pop();
+	}
+
+	fn visit_stmt(&mut self, node: &'ast Stmt) {
+		match &node.kind {
+			StmtKind::Let { var_name, .. } => {
+				visit::visit_stmt(self, node);
+				self.declare(var_name);
+			}
+			StmtKind::Class(class) => {
+				self.declare(&class.name);
+				visit::visit_stmt(self, node);
+			}
+			StmtKind::ForLoop {
+				iterator,
+				second_iterator,
+				..
+			} => self.with_bindings(std::iter::once(iterator).chain(second_iterator), |v| {
+				visit::visit_stmt(v, node)
+			}),
+			StmtKind::WhileLet { var_name, .. } => self.with_bindings([var_name], |v| visit::visit_stmt(v, node)),
+			StmtKind::IfLet(IfLet {
+				var_name,
+				conditions,
+				else_if_statements,
+				..
+			}) => {
+				let mut bindings = vec![var_name];
+				for condition in conditions {
+					if let IfLetCondition::Let { var_name, .. } = condition {
+						bindings.push(var_name);
+					}
+				}
+				for else_if in else_if_statements {
+					if let ElseIfs::ElseIfLetBlock(block) = else_if {
+						bindings.push(&block.var_name);
+					}
+				}
+				self.with_bindings(bindings, |v| visit::visit_stmt(v, node));
+			}
+			StmtKind::TryCatch {
+				catch_block: Some(CatchBlock {
+					exception_var: Some(exception_var),
+					..
+				}),
+				..
+			} => self.with_bindings([exception_var], |v| visit::visit_stmt(v, node)),
+			StmtKind::TryCatch { .. }
+			| StmtKind::Bring { .. }
+			| StmtKind::SuperConstructor { .. }
+			| StmtKind::While { .. }
+			| StmtKind::If { .. }
+			| StmtKind::Break
+			| StmtKind::Continue
+			| StmtKind::Return(_)
+			| StmtKind::Throw(_)
+			| StmtKind::Expression(_)
+			| StmtKind::Assignment { .. }
+			| StmtKind::Scope(_)
+			| StmtKind::Interface(_)
+			| StmtKind::Struct(_)
+			| StmtKind::Enum(_)
+			| StmtKind::ExplicitLift(_) => visit::visit_stmt(self, node),
+		}
+	}
+
+	fn visit_function_definition(&mut self, node: &'ast FunctionDefinition) {
+		self.with_bindings(node.signature.parameters.iter().map(|p| &p.name), |v| {
+			visit::visit_function_definition(v, node)
+		});
+	}
+
+	fn visit_reference(&mut self, node: &'ast Reference) {
+		match node {
+			Reference::Identifier(symbol) => self.mention(symbol),
+			Reference::InstanceMember { object, property, .. } => {
+				self.visit_expr(object);
+				self.mention_member(property);
+			}
+			Reference::TypeMember { type_name, property } => {
+				self.visit_user_defined_type(type_name);
+				self.mention_member(property);
+			}
+			Reference::ElementAccess { .. } | Reference::SuperMember { .. } => visit::visit_reference(self, node),
+		}
+	}
+
+	fn visit_user_defined_type(&mut self, node: &'ast UserDefinedType) {
+		self.mention(&node.root);
+		for field in &node.fields {
+			self.mention_member(field);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use camino::Utf8Path;
+	use indexmap::IndexMap;
+
+	use super::find_dead_declarations;
+	use crate::ast::{AstContext, StmtKind};
+	use crate::test_utils::parse_source;
+
+	/// Returns the names of the module-level declarations of `source` that would be eliminated
+	fn dead_declarations(source: &str) -> Vec<String> {
+		dead_declarations_of_files(&[("/project/main.w", source)])
+	}
+
+	/// Returns the names of the module-level declarations of all files that would be eliminated
+	fn dead_declarations_of_files(files: &[(&str, &str)]) -> Vec<String> {
+		let ast_ctx = AstContext::new();
+		let asts = files
+			.iter()
+			.map(|(path, source)| {
+				let path = Utf8Path::new(path);
+				(path.to_owned(), parse_source(path, source, &ast_ctx))
+			})
+			.collect::<IndexMap<_, _>>();
+		let dead = find_dead_declarations(&asts);
+		asts
+			.values()
+			.flat_map(|ast| ast.statements.iter())
+			.filter(|stmt| dead.contains(&stmt.span))
+			.map(|stmt| match &stmt.kind {
+				StmtKind::Class(class) => class.name.name.clone(),
+				StmtKind::Let { var_name, .. } => var_name.name.clone(),
+				_ => panic!("unexpected dead statement"),
+			})
+			.collect()
+	}
+
+	#[test]
+	fn drops_unused_declarations() {
+		let dead = dead_declarations(
+			r#"
+class Unused {}
+let unusedHelper = (): num => { return 1; };
+class Used {}
+new Used();
+"#,
+		);
+		assert_eq!(dead, vec!["Unused", "unusedHelper"]);
+	}
+
+	#[test]
+	fn keeps_declarations_used_by_live_declarations() {
+		let dead = dead_declarations(
+			r#"
+let double = (x: num): num => { return x * 2; };
+class Calculator {
+  pub static compute(): num {
+    return double(21);
+  }
+}
+let result = Calculator.compute();
+"#,
+		);
+		assert!(dead.is_empty());
+	}
+
+	#[test]
+	fn local_symbols_shadow_declarations() {
+		let dead = dead_declarations(
+			r#"
+class Helper {}
+let helper = (): num => { return 1; };
+let run = (Helper: num, items: Array<num>): num => {
+  let helper = Helper + 1;
+  for Helper in items {
+    log("{Helper}");
+  }
+  return helper;
+};
+log("{run(1, [])}");
+"#,
+		);
+		assert_eq!(dead, vec!["Helper", "helper"]);
+	}
+
+	#[test]
+	fn member_accesses_dont_keep_declarations_of_the_same_file() {
+		let dead = dead_declarations(
+			r#"
+struct Config {
+  helper: num;
+}
+let helper = (): num => { return 1; };
+let config = Config { helper: 1 };
+log("{config.helper}");
+"#,
+		);
+		assert_eq!(dead, vec!["helper"]);
+	}
+
+	#[test]
+	fn member_accesses_keep_declarations_of_brought_files() {
+		let dead = dead_declarations_of_files(&[
+			(
+				"/project/main.w",
+				r#"
+bring "./lib.w" as lib;
+new lib.Used();
+"#,
+			),
+			(
+				"/project/lib.w",
+				r#"
+pub class Used {}
+pub class Unused {}
+"#,
+			),
+		]);
+		assert_eq!(dead, vec!["Unused"]);
+	}
+}
@@ -0,0 +1,129 @@
+use std::collections::HashSet;
+
+use const_format::formatcp;
+use serde::Serialize;
+
+use crate::ast::{Expr, ExprId, ExprKind, Reference};
+use crate::diagnostic::{WingLocation, WingSpan};
+use crate::{UTIL_CLASS_NAME, WINGSDK_ASSEMBLY_NAME, WINGSDK_EXPECT_MODULE};
+
+/// The class whose static methods are the functions of the `expect` module (e.g. `expect.equal`)
+pub const EXPECT_UTIL_FQN: &str = formatcp!("{WINGSDK_ASSEMBLY_NAME}.{WINGSDK_EXPECT_MODULE}.{UTIL_CLASS_NAME}");
+
+/// The variable that records the values of the sub-expressions of an `expect` call's arguments
+pub const EXPECT_VALUES_VAR: &str = "$expect_values";
+
+#[derive(Serialize)]
+struct RecordedExpr {
+	/// The Wing source of the sub-expression
+	text: String,
+	/// The column (relative to the start of the call) to show the value under, if the call is on a single line
+	column: Option<u32>,
+}
+
+/// The sub-expressions of an `expect` call whose values are shown when the assertion fails, like:
+/// ```text
+/// expect.equal(x.length, y)
+///                |       |
+///                3       4
+/// ```
+pub struct ExpectValues {
+	call_span: WingSpan,
+	args_span: WingSpan,
+	seen: HashSet<ExprId>,
+	recorded: Vec<RecordedExpr>,
+}
+
+impl ExpectValues {
+	pub fn new(call_span: &WingSpan, args_span: &WingSpan) -> Self {
+		Self {
+			call_span: call_span.clone(),
+			args_span: args_span.clone(),
+			seen: HashSet::new(),
+			recorded: vec![],
+		}
+	}
+
+	/// Never record the value of `expr`, e.g. because it's a method that must be called on its object
+	pub fn skip(&mut self, expr: &Expr) {
+		self.seen.insert(expr.id);
+	}
+
+	/// Returns the index `expr`'s value is recorded at, if it's a sub-expression of the arguments worth showing
+	/// (literals are not) and it isn't being recorded already
+	pub fn record(&mut self, expr: &Expr, text: impl FnOnce(&WingSpan) -> String) -> Option<usize> {
+		if !self.args_span.contains_span(&expr.span) || !self.seen.insert(expr.id) {
+			return None;
+		}
+		let marker = match &expr.kind {
+			ExprKind::Reference(Reference::Identifier(_)) | ExprKind::Unary { .. } => expr.span.start,
+			ExprKind::Reference(Reference::InstanceMember { property, .. }) => property.span.start,
+			ExprKind::Reference(Reference::ElementAccess { object, .. }) => object.span.end,
+			ExprKind::Call { arg_list, .. } => arg_list.span.start,
+			// the operator is (usually) right after the left operand
+			ExprKind::Binary { left, .. } => WingLocation {
+				line: left.span.end.line,
+				col: left.span.end.col + 1,
+			},
+			_ => return None,
+		};
+		let column = (self.call_span.start.line == self.call_span.end.line && marker.line == self.call_span.start.line)
+			.then(|| marker.col - self.call_span.start.col);
+		self.recorded.push(RecordedExpr {
+			text: text(&expr.span),
+			column,
+		});
+		Some(self.recorded.len() - 1)
+	}
+
+	/// The recorded sub-expressions, in the order of their indices
+	pub fn to_json(&self) -> String {
+		serde_json::to_string(&self.recorded).expect("serialize recorded expressions")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use camino::Utf8Path;
+
+	use super::ExpectValues;
+	use crate::ast::{AstContext, ExprKind, Reference, StmtKind};
+	use crate::diagnostic::WingSpan;
+	use crate::test_utils::parse_source;
+
+	#[test]
+	fn records_sub_expressions_under_their_columns() {
+		let main = Utf8Path::new("/project/main.test.w");
+		let ast_ctx = AstContext::new();
+		let ast = parse_source(main, "expect.equal(x.length, y + 1);", &ast_ctx);
+		let StmtKind::Expression(call) = &ast.statements[0].kind else {
+			panic!("expected an expression statement");
+		};
+		let ExprKind::Call { arg_list, .. } = &call.kind else {
+			panic!("expected a call");
+		};
+
+		let mut values = ExpectValues::new(&call.span, &arg_list.span);
+		let length = &arg_list.pos_args[0];
+		let ExprKind::Reference(Reference::InstanceMember { object: x, .. }) = &length.kind else {
+			panic!("expected a member access");
+		};
+		let sum = &arg_list.pos_args[1];
+		let ExprKind::Binary { right: one, .. } = &sum.kind else {
+			panic!("expected a binary expression");
+		};
+		let text = |span: &WingSpan| format!("{}:{}", span.start.col, span.end.col);
+
+		assert_eq!(values.record(length, text), Some(0));
+		assert_eq!(values.record(length, text), None);
+		assert_eq!(values.record(x, text), Some(1));
+		assert_eq!(values.record(sum, text), Some(2));
+		// literals aren't worth showing, and the callee is outside the arguments
+		assert_eq!(values.record(one, text), None);
+		assert_eq!(values.record(call, text), None);
+		assert_eq!(
+			values.to_json(),
+			r#"[{"text":"13:21","column":15},{"text":"13:14","column":13},{"text":"23:28","column":25}]"#
+		);
+	}
+}
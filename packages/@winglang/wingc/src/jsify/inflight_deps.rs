@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use indexmap::IndexSet;
+use serde::Serialize;
+
+/// Maps each inflight file to the extern files and npm packages it requires, for bundlers and dependency audits
+pub const INFLIGHT_DEPENDENCIES_FILE_NAME: &str = "inflight.deps.json";
+
+/// The dependencies of an inflight file
+#[derive(Serialize)]
+pub struct InflightDependencies {
+	/// The class defined by the file (inflight closures are classes named `$ClosureN`)
+	class: String,
+	/// The paths the file requires extern files from, as they appear in the generated code (relative to the output
+	/// directory, or package paths for externs in `node_modules`)
+	externs: Vec<String>,
+	/// The npm packages the file requires, either directly or through its externs
+	packages: Vec<String>,
+}
+
+impl InflightDependencies {
+	pub fn new(class: &str, externs: IndexSet<String>, packages: IndexSet<String>) -> Self {
+		let mut packages: Vec<String> = packages
+			.into_iter()
+			.chain(externs.iter().filter_map(|path| package_name(path)))
+			.collect();
+		packages.sort();
+		packages.dedup();
+		Self {
+			class: class.to_string(),
+			externs: externs.into_iter().collect(),
+			packages,
+		}
+	}
+}
+
+/// The dependency manifest of the inflight files of a program, by file name, as JSON
+pub fn inflight_dependencies_json(dependencies: &BTreeMap<String, InflightDependencies>) -> String {
+	serde_json::to_string_pretty(dependencies).expect("serialize inflight dependencies")
+}
+
+/// The npm package a require path points into, unless it's a relative path
+fn package_name(require_path: &str) -> Option<String> {
+	if require_path.starts_with('.') || require_path.starts_with('/') {
+		return None;
+	}
+	// Scoped packages have two components (`@scope/name`)
+	let components = if require_path.starts_with('@') { 2 } else { 1 };
+	Some(require_path.split('/').take(components).collect::<Vec<_>>().join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+	use indexmap::IndexSet;
+
+	use super::{package_name, InflightDependencies};
+
+	#[test]
+	fn finds_packages_of_externs() {
+		assert_eq!(package_name("../../util.js"), None);
+		assert_eq!(package_name("lodash/fp.js"), Some("lodash".to_string()));
+		assert_eq!(
+			package_name("@winglibs/blah/util.js"),
+			Some("@winglibs/blah".to_string())
+		);
+
+		let dependencies = InflightDependencies::new(
+			"$Closure1",
+			IndexSet::from(["@winglibs/blah/util.js".to_string(), "../../util.js".to_string()]),
+			IndexSet::from(["uuid".to_string(), "@winglibs/blah".to_string()]),
+		);
+		assert_eq!(dependencies.externs, vec!["@winglibs/blah/util.js", "../../util.js"]);
+		assert_eq!(dependencies.packages, vec!["@winglibs/blah", "uuid"]);
+	}
+}
@@ -0,0 +1,124 @@
+use std::fs;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use serde_json::Value;
+
+use crate::diagnostic::{report_diagnostic, Diagnostic, DiagnosticSeverity};
+
+/// Name of the environment variable with the Node.js version inflight code must run on (e.g. "16" or "18.4.0")
+pub const NODE_TARGET_ENV: &str = "WING_NODE_TARGET";
+
+/// The oldest Node.js version inflight code can be compiled for. Older versions don't support class fields.
+pub const MIN_NODE_TARGET: u32 = 12;
+
+/// The first Node.js version that supports optional chaining (`?.`) and nullish coalescing (`??`)
+const OPTIONAL_CHAINING_NODE_VERSION: u32 = 14;
+
+/// The Node.js version inflight code is compiled for. Preflight code always runs on the compiler's Node.js version,
+/// so the target only affects inflight code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeTarget {
+	pub major: u32,
+}
+
+impl NodeTarget {
+	/// Reads the target from `NODE_TARGET_ENV`, reporting an error if it's not a supported Node.js version.
+	pub fn from_env() -> Option<Self> {
+		let value = std::env::var(NODE_TARGET_ENV).ok()?;
+		let target = parse_major_version(&value).map(|major| Self { major });
+		match target {
+			Some(target) if target.major >= MIN_NODE_TARGET => Some(target),
+			_ => {
+				report_diagnostic(Diagnostic {
+					message: format!(
+						"Invalid Node.js target \"{value}\" in {NODE_TARGET_ENV}, expected a Node.js version no older than {MIN_NODE_TARGET}"
+					),
+					span: None,
+					annotations: vec![],
+					hints: vec![],
+					severity: DiagnosticSeverity::Error,
+					code: None,
+				});
+				None
+			}
+		}
+	}
+
+	/// Whether the target supports optional chaining (`?.`) and nullish coalescing (`??`)
+	pub fn supports_optional_chaining(&self) -> bool {
+		self.major >= OPTIONAL_CHAINING_NODE_VERSION
+	}
+
+	/// The Node.js version optional chaining requires, for diagnostics
+	pub fn optional_chaining_version() -> u32 {
+		OPTIONAL_CHAINING_NODE_VERSION
+	}
+}
+
+/// Parses the major version out of versions like "18", "v18" or "18.4.0"
+fn parse_major_version(version: &str) -> Option<u32> {
+	let version = version.trim();
+	let version = version.strip_prefix('v').unwrap_or(version);
+	version.split('.').next()?.parse().ok()
+}
+
+/// Returns the oldest Node.js major version an extern file supports, along with the package.json it was read
+/// from, based on the `engines.node` field of the nearest package.json.
+///
+/// Only simple ranges like ">=16", "^18.0.0", "~16" or "16 || 18" are understood. For anything else we don't
+/// know the requirement and return `None`.
+pub fn extern_min_node_version(extern_path: &Utf8Path) -> Option<(u32, Utf8PathBuf)> {
+	let package_json_path = extern_path
+		.ancestors()
+		.skip(1)
+		.map(|dir| dir.join("package.json"))
+		.find(|path| path.exists())?;
+	let package_json: Value = serde_json::from_str(&fs::read_to_string(&package_json_path).ok()?).ok()?;
+	let range = package_json.get("engines")?.get("node")?.as_str()?;
+
+	let min_version = range_min_major_version(range)?;
+	Some((min_version, package_json_path))
+}
+
+/// Returns the oldest major version a semver range like ">=16", "^18.0.0" or "16 || 18" allows
+fn range_min_major_version(range: &str) -> Option<u32> {
+	range
+		.split("||")
+		.map(|alternative| {
+			// The lower bound of a range is its first comparator (e.g. ">=16 <20")
+			let alternative = alternative.trim();
+			if alternative.starts_with('<') {
+				return None;
+			}
+			let version = alternative
+				.trim_start_matches(['>', '=', '^', '~'])
+				.split_whitespace()
+				.next()?;
+			parse_major_version(version)
+		})
+		.collect::<Option<Vec<_>>>()?
+		.into_iter()
+		.min()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{parse_major_version, range_min_major_version};
+
+	#[test]
+	fn parses_node_versions() {
+		assert_eq!(parse_major_version("18"), Some(18));
+		assert_eq!(parse_major_version("v16.3.0"), Some(16));
+		assert_eq!(parse_major_version(" 20.1 "), Some(20));
+		assert_eq!(parse_major_version("latest"), None);
+	}
+
+	#[test]
+	fn finds_the_oldest_version_of_a_range() {
+		assert_eq!(range_min_major_version(">=16"), Some(16));
+		assert_eq!(range_min_major_version(">= 18.0.0 <21"), Some(18));
+		assert_eq!(range_min_major_version("^20 || ~18.2"), Some(18));
+		assert_eq!(range_min_major_version("<20"), None);
+		assert_eq!(range_min_major_version("*"), None);
+	}
+}
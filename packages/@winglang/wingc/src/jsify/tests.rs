@@ -1,5 +1,7 @@
 #[cfg(test)]
-use crate::jsify::escape_javascript_string;
+use crate::jsify::{escape_javascript_string, inline_js_type};
+#[cfg(test)]
+use crate::type_check::{Type, Types};
 
 #[test]
 fn test_escape_javascript_string() {
@@ -13,6 +15,26 @@ fn test_escape_javascript_string() {
 	assert_eq!(escape_javascript_string("hello\0world"), String::from("hello\\0world"));
 }
 
+#[test]
+fn test_inline_js_type() {
+	let mut types = Types::new();
+	assert_eq!(inline_js_type(types.string()).as_deref(), Some("\"str\""));
+	assert_eq!(inline_js_type(types.void()), None);
+	assert_eq!(inline_js_type(types.anything()), None);
+
+	let optional_num = types.add_type(Type::Optional(types.number()));
+	let set_of_optional_num = types.add_type(Type::MutSet(optional_num));
+	let array_of_sets = types.add_type(Type::Array(set_of_optional_num));
+	assert_eq!(
+		inline_js_type(array_of_sets).as_deref(),
+		Some("{ array: { set: { optional: \"num\" } } }")
+	);
+
+	// Values of unchecked types inside collections aren't checked either
+	let map_of_anything = types.add_type(Type::Map(types.anything()));
+	assert_eq!(inline_js_type(map_of_anything).as_deref(), Some("{ map: null }"));
+}
+
 #[test]
 fn free_preflight_object_from_preflight() {
 	assert_compile_ok!(
@@ -687,6 +709,23 @@ fn access_property_on_value_returned_from_collection() {
 	);
 }
 
+#[test]
+fn merges_repeated_lifts() {
+	assert_compile_ok!(
+		r#"
+    bring cloud;
+    let b = new cloud.Bucket();
+
+    test "test" {
+      b.put("hello", "world");
+      b.list();
+      b.put("foo", "bar");
+      b.list();
+    }
+    "#
+	);
+}
+
 #[test]
 fn calls_methods_on_preflight_object() {
 	assert_compile_ok!(
@@ -717,6 +756,28 @@ fn enum_value() {
 	);
 }
 
+#[test]
+fn enum_backing_value() {
+	assert_compile_ok!(
+		r#"
+    enum Status { Active = "active", Unknown }
+    let s = Status.Active;
+    log(s.value);
+    "#
+	);
+}
+
+#[test]
+fn flags_enum() {
+	assert_compile_ok!(
+		r#"
+    flags enum Permission { Read, Write, Execute }
+    let p = Permission.Read | Permission.Write;
+    assert((p & Permission.Write) == Permission.Write);
+    "#
+	);
+}
+
 #[test]
 fn static_on_std_type() {
 	assert_compile_ok!(
@@ -1123,6 +1184,30 @@ fn reference_inflight_class() {
 	);
 }
 
+#[test]
+fn closure_display_names() {
+	assert_compile_ok!(
+		r#"
+    bring cloud;
+    let q = new cloud.Queue();
+
+    let handler = inflight (message: str) => {
+      log(message);
+    };
+    q.setConsumer(handler);
+
+    class Worker {
+      new(q: cloud.Queue) {
+        q.setConsumer(inflight (message: str) => {
+          log(message);
+        });
+      }
+    }
+    new Worker(q);
+    "#
+	);
+}
+
 #[test]
 fn inline_inflight_class() {
 	assert_compile_ok!(
@@ -1607,6 +1692,33 @@ fn base_class_with_fields_preflight() {
 	);
 }
 
+#[test]
+fn super_field_access() {
+	assert_compile_ok!(
+		r#"
+    class Base {
+      protected f: str;
+      new() {
+        this.f = "hello";
+      }
+
+      protected foo() {
+        log(this.f);
+      }
+    }
+
+    class Derived extends Base {
+      new() {}
+
+      protected foo() {
+        super.foo();
+        log(super.f);
+      }
+    }
+    "#
+	);
+}
+
 #[test]
 fn base_class_with_fields_inflight() {
 	assert_compile_ok!(
@@ -2080,3 +2192,130 @@ fn allow_type_def_before_super() {
     "#
 	);
 }
+
+#[test]
+fn secondary_constructor_call() {
+	assert_compile_ok!(
+		r#"
+    class Point {
+      pub x: num;
+      new(x: num) {
+        this.x = x;
+      }
+
+      pub static ofStr(s: str): Point {
+        return new Point(0);
+      }
+    }
+
+    new Point(1);
+    new Point("2");
+    "#
+	);
+}
+
+#[test]
+fn class_instance_equality() {
+	assert_compile_ok!(
+		r#"
+    class Foo {}
+    let a = new Foo() as "a";
+    let b: Foo? = nil;
+    assert(a != b);
+    assert(a == a);
+    "#
+	);
+}
+
+#[test]
+fn compare_with_compare_to() {
+	assert_compile_ok!(
+		r#"
+    class Version {
+      pub n: num;
+      new(n: num) {
+        this.n = n;
+      }
+
+      pub compareTo(other: Version): num {
+        return this.n - other.n;
+      }
+    }
+
+    let a = new Version(1) as "a";
+    let b = new Version(2) as "b";
+    assert(a < b);
+    "#
+	);
+}
+
+#[test]
+fn set_members_hashed_by_value() {
+	assert_compile_ok!(
+		r#"
+    let s = MutSet<Array<num>>[[1, 2], [1, 2]];
+    s.add([3]);
+    assert(s.has([1, 2]));
+    assert([3] in s);
+    "#
+	);
+}
+
+#[test]
+fn inflight_file_names_only_change_with_their_code() {
+	let inflight_file_names = |source: &str| {
+		let project = crate::test_utils::compile_source(source);
+		std::fs::read_dir(&project.out_dir)
+			.unwrap()
+			.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+			.filter(|name| name.starts_with("inflight."))
+			.collect::<std::collections::BTreeSet<_>>()
+	};
+
+	let names = inflight_file_names(
+		r#"
+let a = inflight () => { log("a"); };
+let b = inflight () => { log("b"); };
+"#,
+	);
+	assert_eq!(names.len(), 2);
+
+	// adding a closure adds a file, and leaves the names of the others as they are
+	let with_added = inflight_file_names(
+		r#"
+let c = inflight () => { log("c"); };
+let a = inflight () => { log("a"); };
+let b = inflight () => { log("b"); };
+"#,
+	);
+	assert_eq!(with_added.len(), 3);
+	assert!(with_added.is_superset(&names));
+
+	// editing a closure only renames its own file
+	let with_edited = inflight_file_names(
+		r#"
+let a = inflight () => { log("a"); };
+let b = inflight () => { log("b has changed"); };
+"#,
+	);
+	assert_eq!(with_edited.len(), 2);
+	assert_eq!(with_edited.intersection(&names).count(), 1);
+}
+
+#[test]
+fn struct_schemas_describe_fields_with_their_docs() {
+	let preflight = crate::test_utils::compile_source(
+		r#"
+struct Options {
+  /// How long to wait, in seconds
+  timeout: num;
+  retries: num?;
+}
+Options.fromJson({ timeout: 1 });
+"#,
+	)
+	.read("preflight.cjs");
+
+	assert!(preflight.contains(r#"timeout:{description:"How long to wait, in seconds",type:"number"}"#));
+	assert!(preflight.contains(r#"retries:{type:"number"}"#));
+}
@@ -1,4 +1,7 @@
+use serde_json::{json, Map, Value};
+
 use crate::{
+	ast::LiteralTypeValue,
 	jsify::{codemaker::CodeMaker, JSifier},
 	type_check::{symbol_env::SymbolEnv, Struct, Type, UnsafeRef},
 };
@@ -13,11 +16,13 @@ impl JsonSchemaGenerator {
 	fn get_struct_env_properties(&self, env: &SymbolEnv) -> CodeMaker {
 		let mut code = CodeMaker::default();
 		for (field_name, entry) in env.symbol_map.iter() {
-			code.line(format!(
-				"{}: {},",
-				field_name,
-				self.get_struct_schema_field(&entry.kind.as_variable().unwrap().type_)
-			));
+			let field = entry.kind.as_variable().unwrap();
+			let schema = self.get_struct_schema_field(&field.type_);
+			let schema = match field.docs.as_ref().and_then(|docs| docs.summary.as_ref()) {
+				Some(summary) => with_description(&schema, summary),
+				None => schema,
+			};
+			code.line(format!("{}: {},", field_name, schema));
 		}
 		code
 	}
@@ -80,15 +85,32 @@ impl JsonSchemaGenerator {
 			}
 			Type::Optional(t) => self.get_struct_schema_field(&t),
 			Type::Json(_) => "{ type: [\"object\", \"string\", \"boolean\", \"number\", \"array\"] }".to_string(),
+			// Any combination of the bits of a flags enum is one of its values
+			Type::Enum(ref enu) if enu.is_flags => format!(
+				"{{ type: \"integer\", minimum: 0, maximum: {} }}",
+				(1u64 << enu.values.len()) - 1
+			),
 			Type::Enum(ref enu) => {
 				let choices = enu
 					.values
-					.iter()
-					.map(|(s, _)| format!("\"{}\"", s))
+					.keys()
+					.map(|s| format!("\"{}\"", enu.backing_value(s)))
 					.collect::<Vec<String>>()
 					.join(", ");
 				format!("{{ type: \"string\", enum: [{}] }}", choices)
 			}
+			Type::Literal(ref values) => {
+				let choices = values.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(", ");
+				let all_strings = values.iter().all(|v| matches!(v, LiteralTypeValue::String(_)));
+				let all_numbers = values.iter().all(|v| matches!(v, LiteralTypeValue::Number(_)));
+				if all_strings {
+					format!("{{ type: \"string\", enum: [{}] }}", choices)
+				} else if all_numbers {
+					format!("{{ type: \"number\", enum: [{}] }}", choices)
+				} else {
+					format!("{{ enum: [{}] }}", choices)
+				}
+			}
 			_ => "{ type: \"null\" }".to_string(),
 		}
 	}
@@ -112,8 +134,130 @@ impl JsonSchemaGenerator {
 		// close schema
 		code.close("}");
 
-		let cleaned = code.to_string().replace("\n", "").replace(" ", "");
+		CodeMaker::one_line(remove_whitespace_outside_strings(&code.to_string()))
+	}
+
+	/// The schema of a struct as a JSON value (rather than code), for documents that embed it like OpenAPI's
+	pub fn create_json_from_struct(&self, struct_: &Struct) -> Value {
+		json!({
+			"type": "object",
+			"properties": self.get_struct_env_properties_json(&struct_.env),
+			"required": self.get_struct_required_fields_json(&struct_.env),
+		})
+	}
+
+	fn get_struct_env_properties_json(&self, env: &SymbolEnv) -> Map<String, Value> {
+		let mut properties = Map::new();
+		for (field_name, entry) in env.symbol_map.iter() {
+			let field = entry.kind.as_variable().unwrap();
+			let mut schema = self.get_struct_schema_field_json(&field.type_);
+			if let (Some(summary), Value::Object(schema)) =
+				(field.docs.as_ref().and_then(|docs| docs.summary.as_ref()), &mut schema)
+			{
+				schema.insert("description".to_string(), json!(summary));
+			}
+			properties.insert(field_name.clone(), schema);
+		}
+		properties
+	}
+
+	fn get_struct_required_fields_json(&self, env: &SymbolEnv) -> Vec<String> {
+		env
+			.symbol_map
+			.iter()
+			.filter(|(_, entry)| !matches!(*entry.kind.as_variable().unwrap().type_, Type::Optional(_)))
+			.map(|(field_name, _)| field_name.clone())
+			.collect()
+	}
+
+	/// Like `get_struct_schema_field`, but as a JSON value
+	pub fn get_struct_schema_field_json(&self, typ: &UnsafeRef<Type>) -> Value {
+		match **typ {
+			Type::String | Type::Number | Type::Boolean => json!({ "type": JSifier::jsify_type(typ).unwrap() }),
+			Type::Struct(ref s) => self.create_json_from_struct(s),
+			Type::Array(ref t) => json!({ "type": "array", "items": self.get_struct_schema_field_json(t) }),
+			Type::Set(ref t) => json!({
+				"type": "array",
+				"uniqueItems": true,
+				"items": self.get_struct_schema_field_json(t),
+			}),
+			Type::Map(ref t) => json!({
+				"type": "object",
+				"patternProperties": { ".*": self.get_struct_schema_field_json(t) },
+			}),
+			Type::Optional(t) => self.get_struct_schema_field_json(&t),
+			Type::Json(_) => json!({ "type": ["object", "string", "boolean", "number", "array"] }),
+			Type::Enum(ref enu) if enu.is_flags => json!({
+				"type": "integer",
+				"minimum": 0,
+				"maximum": (1u64 << enu.values.len()) - 1,
+			}),
+			Type::Enum(ref enu) => json!({
+				"type": "string",
+				"enum": enu.values.keys().map(|s| enu.backing_value(s)).collect::<Vec<_>>(),
+			}),
+			Type::Literal(ref values) => {
+				let choices = values
+					.iter()
+					.map(|v| serde_json::from_str(&v.to_string()).unwrap_or(Value::Null))
+					.collect::<Vec<_>>();
+				if values.iter().all(|v| matches!(v, LiteralTypeValue::String(_))) {
+					json!({ "type": "string", "enum": choices })
+				} else if values.iter().all(|v| matches!(v, LiteralTypeValue::Number(_))) {
+					json!({ "type": "number", "enum": choices })
+				} else {
+					json!({ "enum": choices })
+				}
+			}
+			_ => json!({ "type": "null" }),
+		}
+	}
+}
+
+/// Adds a `description` to a field's schema (e.g. the field's doc comment)
+fn with_description(schema: &str, description: &str) -> String {
+	let description = serde_json::to_string(description).expect("strings are always serializable");
+	match schema.strip_prefix('{') {
+		Some(rest) => format!("{{ description: {description},{rest}"),
+		None => schema.to_string(),
+	}
+}
+
+/// Removes the whitespace of the generated schema code, except inside string literals (e.g. descriptions)
+fn remove_whitespace_outside_strings(code: &str) -> String {
+	let mut cleaned = String::with_capacity(code.len());
+	let mut in_string = false;
+	let mut escaped = false;
+	for c in code.chars() {
+		if in_string {
+			cleaned.push(c);
+			if escaped {
+				escaped = false;
+			} else if c == '\\' {
+				escaped = true;
+			} else if c == '"' {
+				in_string = false;
+			}
+		} else if c == '"' {
+			in_string = true;
+			cleaned.push(c);
+		} else if !c.is_whitespace() {
+			cleaned.push(c);
+		}
+	}
+	cleaned
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
 
-		CodeMaker::one_line(cleaned)
+	#[test]
+	fn field_docs_become_descriptions() {
+		let schema = with_description("{ type: \"number\" }", "How long to wait, in \"seconds\"");
+		assert_eq!(
+			remove_whitespace_outside_strings(&schema),
+			r#"{description:"How long to wait, in \"seconds\"",type:"number"}"#
+		);
 	}
 }
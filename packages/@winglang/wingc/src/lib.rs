@@ -7,37 +7,57 @@
 #[macro_use]
 extern crate lazy_static;
 
-use ast::{Scope, Symbol};
+use api_lock::{check_api_lock, ApiLockMode};
+use ast::{AstContext, Scope, Symbol};
 use camino::{Utf8Path, Utf8PathBuf};
+use captured_var_visitor::CapturedVarVisitor;
 use closure_transform::ClosureTransformer;
-use comp_ctx::set_custom_panic_hook;
+use comp_ctx::{catch_file_panic, set_custom_panic_hook};
+use compilation_inputs::{compilation_inputs, CompilationInput};
 use const_format::formatcp;
-use diagnostic::{found_errors, report_diagnostic, Diagnostic, DiagnosticSeverity};
+use debug::ast_validator::validate_ast;
+use diagnostic::{
+	found_errors, report_diagnostic, report_suppressed_diagnostics, reset_diagnostics, Diagnostic, DiagnosticSeverity,
+};
 use dtsify::extern_dtsify::{is_extern_file, ExternDTSifier};
+use dump::{requested_dumps, Dumps};
+use events::{emit_event, set_event_sink, start_phase, CompilationPhase, CompilerEvent};
 use file_graph::{File, FileGraph};
 use files::Files;
 use fold::Fold;
 use generate_docs::generate_docs;
 use indexmap::IndexMap;
+use jsify::bundle::BUNDLE_ENV;
+use jsify::coverage::COVERAGE_ENV;
+use jsify::dead_code::DEAD_CODE_ELIMINATION_ENV;
+use jsify::target::NodeTarget;
 use jsify::JSifier;
 
+use expect_snapshots::{find_snapshot_calls, load_stored_snapshots, SnapshotMode};
 use lifting::LiftVisitor;
-use parser::{as_wing_library, is_entrypoint_file, parse_wing_project};
+use messages::load_message_catalog_from_env;
+use never_reassigned_visitor::NeverReassignedVisitor;
+use openapi::OPENAPI_ENV;
+use parser::{as_wing_library, is_entrypoint_file, parse_wing_project, SourceOverlays};
+use progress::{send_progress_to_host, ProgressReporter};
 use serde::Serialize;
 use serde_json::Value;
 use struct_schema::StructSchemaVisitor;
+use test_blocks::{find_test_blocks, TestBlock, TestFilter, TEST_FILTER_ENV};
 use type_check::jsii_importer::JsiiImportSpec;
 use type_check::symbol_env::SymbolEnvKind;
 use type_check::type_reference_transform::TypeReferenceTransformer;
 use type_check_assert::TypeCheckAssert;
+use unreachable_code_visitor::UnreachableCodeVisitor;
 use valid_json_visitor::ValidJsonVisitor;
 use visit::Visit;
 use wasm_util::{ptr_to_str, string_to_combined_ptr, WASM_RETURN_ERROR};
 use wingii::type_system::TypeSystem;
 
-use crate::parser::normalize_path;
+use crate::paths::normalize_path;
 use std::alloc::{alloc, dealloc, Layout};
 
+use std::rc::Rc;
 use std::{fs, mem};
 
 use crate::ast::Phase;
@@ -48,32 +68,53 @@ use crate::type_check::{SymbolEnvOrNamespace, TypeChecker, Types};
 #[cfg(test)]
 mod test_utils;
 
+mod api_lock;
 pub mod ast;
+mod ast_diff;
+mod captured_var_visitor;
 pub mod closure_transform;
 mod comp_ctx;
+mod compilation_inputs;
+mod compiler_version;
+mod construct_tree;
+mod content_hash;
 pub mod debug;
 pub mod diagnostic;
 mod docs;
 mod dtsify;
+mod dump;
+mod env_manifest;
+pub mod events;
+mod expect_snapshots;
 mod file_graph;
 mod files;
 pub mod fold;
 pub mod generate_docs;
+pub mod ir;
 pub mod jsify;
 pub mod json_schema_generator;
 mod lifting;
 pub mod lsp;
+pub mod messages;
+mod never_reassigned_visitor;
+mod openapi;
 pub mod parser;
+pub mod paths;
+mod permissions;
+mod progress;
 pub mod struct_schema;
+mod test_blocks;
 mod ts_traversal;
 pub mod type_check;
 mod type_check_assert;
+mod unreachable_code_visitor;
 mod valid_json_visitor;
 pub mod visit;
 mod visit_context;
 mod visit_stmt_before_super;
 mod visit_types;
 mod wasm_util;
+mod workspace;
 
 const WINGSDK_ASSEMBLY_NAME: &'static str = "@winglang/sdk";
 
@@ -90,6 +131,9 @@ const WINGSDK_UI_MODULE: &'static str = "ui";
 
 pub const UTIL_CLASS_NAME: &'static str = "Util";
 
+/// Subdirectories with this name are only part of a library's API within the library itself
+pub const INTERNAL_DIRECTORY_NAME: &'static str = "internal";
+
 const WINGSDK_BRINGABLE_MODULES: [&'static str; 9] = [
 	WINGSDK_CLOUD_MODULE,
 	WINGSDK_UTIL_MODULE,
@@ -130,6 +174,13 @@ const WINGSDK_SIM_IRESOURCE_FQN: &'static str = formatcp!(
 	iface = WINGSDK_SIM_IRESOURCE
 );
 
+const WINGSDK_HASHABLE: &'static str = "std.IHashable";
+const WINGSDK_HASHABLE_FQN: &'static str = formatcp!(
+	"{assembly}.{iface}",
+	assembly = WINGSDK_ASSEMBLY_NAME,
+	iface = WINGSDK_HASHABLE
+);
+
 const CONSTRUCT_BASE_CLASS: &'static str = "constructs.Construct";
 const CONSTRUCT_BASE_INTERFACE: &'static str = "constructs.IConstruct";
 const CONSTRUCT_NODE_PROPERTY: &'static str = "node";
@@ -145,6 +196,10 @@ pub const DEFAULT_PACKAGE_NAME: &'static str = "rootpkg";
 #[derive(Serialize)]
 pub struct CompilerOutput {
 	imported_namespaces: Vec<String>,
+	/// The test blocks of the compiled project, so tools can list and filter tests without running the program
+	tests: Vec<TestBlock>,
+	/// The files and directories the compilation depended on, so watch mode only recompiles when one of them changes
+	inputs: Vec<CompilationInput>,
 }
 
 /// Exposes an allocation function to the WASM host
@@ -193,19 +248,40 @@ pub unsafe extern "C" fn wingc_init() {
 pub unsafe extern "C" fn wingc_compile(ptr: u32, len: u32) -> u64 {
 	let args = ptr_to_str(ptr, len);
 
-	let split = args.split(";").collect::<Vec<&str>>();
-	if split.len() != 2 {
+	// The optional third argument is a JSON object with the contents of files to read from memory instead of from disk
+	// (by path). It's last, since the contents can contain `;`. The files still have to exist, see `SourceOverlays`.
+	let split = args.splitn(3, ";").collect::<Vec<&str>>();
+	if split.len() < 2 {
 		report_diagnostic(Diagnostic {
-			message: format!("Expected 2 arguments to wingc_compile, got {}", split.len()),
+			message: format!("Expected 2 or 3 arguments to wingc_compile, got {}", split.len()),
 			span: None,
 			annotations: vec![],
 			hints: vec![],
 			severity: DiagnosticSeverity::Error,
+			code: None,
 		});
 		return WASM_RETURN_ERROR;
 	}
 	let source_path = Utf8Path::new(split[0]);
 	let output_dir = split.get(1).map(|s| Utf8Path::new(s)).expect("output dir not provided");
+	let overlays = match split
+		.get(2)
+		.map(|json| serde_json::from_str::<IndexMap<String, String>>(json))
+	{
+		None => SourceOverlays::new(),
+		Some(Ok(overlays)) => overlays.into_iter().collect(),
+		Some(Err(err)) => {
+			report_diagnostic(Diagnostic {
+				message: format!("Invalid file overlays passed to wingc_compile: {}", err),
+				span: None,
+				annotations: vec![],
+				hints: vec![],
+				severity: DiagnosticSeverity::Error,
+				code: None,
+			});
+			return WASM_RETURN_ERROR;
+		}
+	};
 
 	if !source_path.exists() {
 		report_diagnostic(Diagnostic {
@@ -214,11 +290,15 @@ pub unsafe extern "C" fn wingc_compile(ptr: u32, len: u32) -> u64 {
 			annotations: vec![],
 			hints: vec![],
 			severity: DiagnosticSeverity::Error,
+			code: None,
 		});
 		return WASM_RETURN_ERROR;
 	}
 
-	let results = compile(source_path, None, output_dir);
+	// Let the host show the progress of the compilation
+	let previous_sink = set_event_sink(Some(Rc::new(ProgressReporter::new(send_progress_to_host))));
+	let results = compile_with_options(source_path, &overlays, output_dir, &CompileOptions::from_env());
+	set_event_sink(previous_sink);
 
 	if let Ok(results) = results {
 		string_to_combined_ptr(serde_json::to_string(&results).unwrap())
@@ -240,26 +320,6 @@ pub unsafe extern "C" fn wingc_generate_docs(ptr: u32, len: u32) -> u64 {
 	}
 }
 
-const LOCKFILES: [&'static str; 4] = ["pnpm-lock.yaml", "yarn.lock", "bun.lock", "bun.lockb"];
-
-/// Wing sometimes can't find dependencies if they're installed with pnpm/yarn/bun.
-/// Try to anticipate any issues that may arise from using pnpm/yarn/bun with winglibs
-/// by emitting a warning if dependencies were installed with any of these package managers.
-fn emit_warning_for_unsupported_package_managers(project_dir: &Utf8Path) {
-	for lockfile in &LOCKFILES {
-		let lockfile_path = project_dir.join(lockfile);
-		if lockfile_path.exists() {
-			report_diagnostic(Diagnostic {
-				message: "The current project has a pnpm/yarn/bun lockfile. Wing hasn't been tested with package managers besides npm, so it may be unable to resolve dependencies to Wing libraries when using these tools. See https://github.com/winglang/wing/issues/6129 for more details.".to_string(),
-				span: None,
-				annotations: vec![],
-				hints: vec![],
-				severity: DiagnosticSeverity::Warning,
-			});
-		}
-	}
-}
-
 pub fn type_check_file(
 	scope: &mut Scope,
 	types: &mut Types,
@@ -328,6 +388,110 @@ pub fn find_nearest_wing_project_dir(source_path: &Utf8Path) -> Utf8PathBuf {
 }
 
 pub fn compile(source_path: &Utf8Path, source_text: Option<String>, out_dir: &Utf8Path) -> Result<CompilerOutput, ()> {
+	let overlays = source_text.into_iter().map(|text| (source_path, text)).collect();
+	compile_with_options(source_path, &overlays, out_dir, &CompileOptions::default())
+}
+
+/// Optional outputs and transformations of a compilation. The CLI sets them through environment variables, which
+/// `wingc_compile` reads once with `from_env`, while embedders and tests pass them to `compile_with_options` directly.
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+	/// Write an OpenAPI document describing the routes of the program's APIs (see `openapi`)
+	pub openapi: bool,
+	/// Only generate code for the tests whose names match this JavaScript regular expression (see `TestFilter`)
+	pub test_filter: Option<String>,
+	/// Leave out the declarations the program never uses (see `dead_code`)
+	pub dead_code_elimination: bool,
+	/// Instrument the generated code to record which statements ran (see `coverage`)
+	pub coverage: bool,
+	/// Bundle the program's files into a single file (see `bundle`)
+	pub bundle: bool,
+	/// The Node.js version inflight code is compiled for, or the latest one if `None`
+	pub node_target: Option<NodeTarget>,
+	/// What `expect.snapshot` calls do with the values they're passed
+	pub snapshot_mode: SnapshotMode,
+	/// Whether (and how) a library's public API is checked against its lock file
+	pub api_lock: Option<ApiLockMode>,
+}
+
+impl CompileOptions {
+	/// Reads the options from their environment variables, reporting an error for each invalid value
+	pub fn from_env() -> Self {
+		let is_enabled = |name: &str| std::env::var(name).is_ok_and(|v| v == "true");
+		Self {
+			openapi: is_enabled(OPENAPI_ENV),
+			test_filter: std::env::var(TEST_FILTER_ENV).ok().filter(|filter| !filter.is_empty()),
+			dead_code_elimination: is_enabled(DEAD_CODE_ELIMINATION_ENV),
+			coverage: is_enabled(COVERAGE_ENV),
+			bundle: is_enabled(BUNDLE_ENV),
+			node_target: NodeTarget::from_env(),
+			snapshot_mode: SnapshotMode::from_env(),
+			api_lock: ApiLockMode::from_env(),
+		}
+	}
+}
+
+/// Compiles a project like `compile`, reading the files in `overlays` from memory instead of from disk (e.g. the
+/// unsaved changes of an editor, or the changes of a speculative compilation)
+pub fn compile_with_options(
+	source_path: &Utf8Path,
+	overlays: &SourceOverlays,
+	out_dir: &Utf8Path,
+	options: &CompileOptions,
+) -> Result<CompilerOutput, ()> {
+	compile_project(source_path, overlays, out_dir, options, true)
+}
+
+/// The path `compile_str` pretends its source was read from. It doesn't exist, so the source can't bring any
+/// files next to it.
+const COMPILE_STR_SOURCE_PATH: &str = "/__wing_compile_str__/main.w";
+
+/// Compiles Wing source code from a string through all compiler phases, without reading any project files or
+/// writing any output. Only the Wing SDK and libraries brought by the source are read from disk.
+///
+/// Diagnostics from previous compilations are reset first, so it can be called repeatedly (e.g. by a fuzzer).
+pub fn compile_str(source_text: &str) -> Result<CompilerOutput, ()> {
+	reset_diagnostics();
+
+	let source_path = Utf8Path::new(COMPILE_STR_SOURCE_PATH);
+	// out_dir is only used to compute the paths in the generated code
+	let out_dir = source_path.parent().unwrap().join("target");
+	let overlays = SourceOverlays::from_iter([(source_path, source_text.to_string())]);
+	compile_project(source_path, &overlays, &out_dir, &CompileOptions::default(), false)
+}
+
+/// Runs all compiler phases on a project. If `emit_files` is false, the output is generated but never written.
+fn compile_project(
+	source_path: &Utf8Path,
+	overlays: &SourceOverlays,
+	out_dir: &Utf8Path,
+	options: &CompileOptions,
+	emit_files: bool,
+) -> Result<CompilerOutput, ()> {
+	let _compiling = start_phase(CompilationPhase::Compiling);
+	if let Err(message) = load_message_catalog_from_env() {
+		report_diagnostic(Diagnostic {
+			message,
+			span: None,
+			annotations: vec![],
+			hints: vec![],
+			severity: DiagnosticSeverity::Error,
+			code: None,
+		});
+	}
+	let result = run_compiler_phases(source_path, overlays, out_dir, options, emit_files);
+	// Files with too many diagnostics only had the first ones reported, let the user know how many more there are
+	report_suppressed_diagnostics();
+	result
+}
+
+fn run_compiler_phases(
+	source_path: &Utf8Path,
+	overlays: &SourceOverlays,
+	out_dir: &Utf8Path,
+	options: &CompileOptions,
+	emit_files: bool,
+) -> Result<CompilerOutput, ()> {
 	let project_dir = find_nearest_wing_project_dir(source_path);
 	let source_package = as_wing_library(&project_dir, false).unwrap_or_else(|| DEFAULT_PACKAGE_NAME.to_string());
 	let source_path = normalize_path(source_path, None);
@@ -338,13 +502,16 @@ pub fn compile(source_path: &Utf8Path, source_text: Option<String>, out_dir: &Ut
 	library_roots.insert(source_package, project_dir.to_owned());
 
 	// -- PARSING PHASE --
+	let parsing = start_phase(CompilationPhase::Parsing);
 	let mut files = Files::new();
 	let mut file_graph = FileGraph::default();
 	let mut tree_sitter_trees = IndexMap::new();
 	let mut asts = IndexMap::new();
+	let ast_ctx = AstContext::new();
 	let topo_sorted_files = parse_wing_project(
 		&source_file,
-		source_text,
+		&ast_ctx,
+		overlays,
 		&mut files,
 		&mut file_graph,
 		&mut library_roots,
@@ -352,7 +519,56 @@ pub fn compile(source_path: &Utf8Path, source_text: Option<String>, out_dir: &Ut
 		&mut asts,
 	);
 
-	emit_warning_for_unsupported_package_managers(&project_dir);
+	for scope in asts.values() {
+		validate_ast(scope, &files, None, "parsing");
+	}
+	parsing.finish();
+
+	// Tests of brought libraries are never run, so only the project's own tests are listed
+	let tests = topo_sorted_files
+		.iter()
+		.filter(|file| file.package == source_file.package)
+		.filter_map(|file| asts.get(&file.path))
+		.flat_map(find_test_blocks)
+		.collect::<Vec<_>>();
+
+	// The tests that weren't selected are type checked, but left out of the generated code
+	let test_filter = options
+		.test_filter
+		.as_deref()
+		.and_then(|pattern| match TestFilter::new(pattern) {
+			Ok(test_filter) => Some(test_filter),
+			Err(message) => {
+				report_diagnostic(Diagnostic {
+					message,
+					span: None,
+					annotations: vec![],
+					hints: vec![
+						"Test filters are JavaScript regular expressions without look-arounds or backreferences".to_string(),
+					],
+					severity: DiagnosticSeverity::Error,
+					code: None,
+				});
+				None
+			}
+		});
+
+	// Number the `expect.snapshot` calls of the project's files while tests are still test blocks
+	let mut snapshot_calls = topo_sorted_files
+		.iter()
+		.filter(|file| file.package == source_file.package)
+		.filter_map(|file| asts.get(&file.path))
+		.flat_map(find_snapshot_calls)
+		.collect::<Vec<_>>();
+	load_stored_snapshots(&mut snapshot_calls, options.snapshot_mode);
+
+	// Debugging dumps of the compiler's intermediate representations (see `WING_EMIT`)
+	let dumps = Dumps::new(
+		if emit_files { requested_dumps() } else { vec![] },
+		&source_path,
+		out_dir,
+	);
+	dumps.dump_ast(&asts);
 
 	// -- DESUGARING PHASE --
 
@@ -360,13 +576,15 @@ pub fn compile(source_path: &Utf8Path, source_text: Option<String>, out_dir: &Ut
 	let mut asts = asts
 		.into_iter()
 		.map(|(path, scope)| {
-			let mut inflight_transformer = ClosureTransformer::new();
+			let mut inflight_transformer = ClosureTransformer::new(&files, &ast_ctx);
 			let scope = inflight_transformer.fold_scope(scope);
+			validate_ast(&scope, &files, None, "closure transformation");
 			(path, scope)
 		})
 		.collect::<IndexMap<Utf8PathBuf, Scope>>();
 
 	// -- TYPECHECKING PHASE --
+	let type_checking = start_phase(CompilationPhase::TypeChecking);
 
 	// Create universal types collection (need to keep this alive during entire compilation)
 	let mut types = Types::new();
@@ -377,46 +595,85 @@ pub fn compile(source_path: &Utf8Path, source_text: Option<String>, out_dir: &Ut
 
 	// Type check all files in topological order (start with files that don't bring any other
 	// Wing files, then move on to files that depend on those, and repeat)
-	for file in &topo_sorted_files {
+	for (index, file) in topo_sorted_files.iter().enumerate() {
+		emit_event(CompilerEvent::FileStarted {
+			path: &file.path,
+			index,
+			total: topo_sorted_files.len(),
+		});
 		let mut scope = asts.swap_remove(&file.path).expect("matching AST not found");
-		type_check_file(
-			&mut scope,
-			&mut types,
-			&file,
-			&file_graph,
-			&mut library_roots,
-			&mut jsii_types,
-			&mut jsii_imports,
-		);
-
-		// Make sure all type reference are no longer considered references
-		let mut tr_transformer = TypeReferenceTransformer { types: &mut types };
-		let scope = tr_transformer.fold_scope(scope);
-
-		// Validate the type checker didn't miss anything - see `TypeCheckAssert` for details
-		let mut tc_assert = TypeCheckAssert::new(&types, found_errors());
-		tc_assert.check(&scope);
-
-		// Validate all Json literals to make sure their values are legal
-		let mut json_checker = ValidJsonVisitor::new(&types);
-		json_checker.check(&scope);
+
+		// A compiler bug in one file shouldn't stop the others from being checked (in native builds, see
+		// `catch_file_panic`). The file's AST is kept even if checking it panicked, so later phases (and the LSP) still
+		// find it.
+		let type_checked = catch_file_panic(CompilationPhase::TypeChecking, &file.path, || {
+			type_check_file(
+				&mut scope,
+				&mut types,
+				&file,
+				&file_graph,
+				&mut library_roots,
+				&mut jsii_types,
+				&mut jsii_imports,
+			)
+		});
+		if type_checked.is_some() {
+			// Make sure all type reference are no longer considered references
+			let mut tr_transformer = TypeReferenceTransformer { types: &mut types };
+			scope = tr_transformer.fold_scope(scope);
+			validate_ast(&scope, &files, Some(&types), "type reference transformation");
+
+			catch_file_panic(CompilationPhase::TypeChecking, &file.path, || {
+				// Validate the type checker didn't miss anything - see `TypeCheckAssert` for details
+				let mut tc_assert = TypeCheckAssert::new(&types, found_errors());
+				tc_assert.check(&scope);
+
+				// Validate all Json literals to make sure their values are legal
+				let mut json_checker = ValidJsonVisitor::new(&types);
+				json_checker.check(&scope);
+
+				// Warn about code that can never run
+				let mut unreachable_checker = UnreachableCodeVisitor::new(&types);
+				unreachable_checker.check(&scope);
+
+				// Make sure captured preflight variables aren't reassigned behind the back of inflight code
+				let mut captured_var_checker = CapturedVarVisitor::new(&types);
+				captured_var_checker.check(&scope);
+
+				// Suggest dropping `var` from variables and fields that are never reassigned
+				let mut never_reassigned_checker = NeverReassignedVisitor::new(&types);
+				never_reassigned_checker.check(&scope);
+
+				dumps.dump_typed_ast(&file.path, &scope, &types, &files);
+			});
+		}
 
 		asts.insert(file.path.to_owned(), scope);
 	}
+	type_checking.finish();
 
 	let mut jsifier = JSifier::new(&mut types, &files, &file_graph, &source_path, &out_dir);
 
 	// -- LIFTING PHASE --
+	let lifting = start_phase(CompilationPhase::Lifting);
 
 	let mut asts = asts
 		.into_iter()
 		.map(|(path, scope)| {
-			let mut lift = LiftVisitor::new(&jsifier);
-			lift.visit_scope(&scope);
+			catch_file_panic(CompilationPhase::Lifting, &path, || {
+				let mut lift = LiftVisitor::new(&jsifier);
+				lift.visit_scope(&scope);
+			});
 			(path, scope)
 		})
 		.collect::<IndexMap<Utf8PathBuf, Scope>>();
 
+	// Lifts are only computed for classes in programs that type check
+	if !found_errors() {
+		dumps.dump_lifted(&asts, jsifier.types);
+	}
+	lifting.finish();
+
 	// bail out now (before jsification) if there are errors (no point in jsifying)
 	if found_errors() {
 		return Err(());
@@ -433,33 +690,84 @@ pub fn compile(source_path: &Utf8Path, source_text: Option<String>, out_dir: &Ut
 		})
 		.collect::<IndexMap<Utf8PathBuf, Scope>>();
 
+	// -- DEAD CODE ELIMINATION PHASE --
+	// Libraries must keep all their declarations since we don't know which ones their users need
+	if !source_path.is_dir() && options.dead_code_elimination {
+		jsifier.eliminate_dead_code(&asts);
+	}
+
 	// -- JSIFICATION PHASE --
+	let jsifying = start_phase(CompilationPhase::Jsifying);
+
+	if let Some(node_target) = options.node_target {
+		jsifier.set_node_target(node_target);
+	}
+
+	if options.coverage {
+		jsifier.instrument_coverage();
+	}
+	if let Some(test_filter) = test_filter {
+		jsifier.set_test_filter(test_filter);
+	}
+	jsifier.set_snapshot_calls(snapshot_calls, options.snapshot_mode.records_values());
 
-	for file in &topo_sorted_files {
+	for (index, file) in topo_sorted_files.iter().enumerate() {
+		emit_event(CompilerEvent::FileStarted {
+			path: &file.path,
+			index,
+			total: topo_sorted_files.len(),
+		});
 		let scope = asts.get_mut(&file.path).expect("matching AST not found");
 		jsifier.jsify(&file, &scope);
+		emit_event(CompilerEvent::FileCompiled { path: &file.path });
 	}
-	if !found_errors() {
-		match jsifier.output_files.borrow().emit_files(out_dir) {
-			Ok(()) => {}
-			Err(err) => report_diagnostic(err.into()),
-		}
+
+	// -- BUNDLING PHASE --
+	// Libraries are brought by other programs, so their files are always emitted separately
+	if !source_path.is_dir() && options.bundle {
+		jsifier.bundle_output_files();
+	}
+	// Metadata is read by tools alongside the program, so it's emitted after bundling and never bundled
+	jsifier.emit_coverage_map();
+	jsifier.emit_snapshot_manifest();
+	jsifier.emit_construct_tree_metadata(&asts);
+	jsifier.emit_permissions_summary(&asts);
+	jsifier.emit_env_manifest(&asts);
+	jsifier.emit_inflight_dependencies();
+	if !source_path.is_dir() && options.openapi {
+		jsifier.emit_openapi_document(&asts, project_dir.file_name().unwrap_or("api"));
 	}
 
+	let mut output_files = jsifier.output_files.take();
+	jsifying.finish();
+
 	// -- DTSIFICATION PHASE --
 	if source_path.is_dir() {
+		let _dtsifying = start_phase(CompilationPhase::Dtsifying);
 		let preflight_file_map = jsifier.preflight_file_map.borrow();
-		let dtsifier = dtsify::DTSifier::new(&mut types, &preflight_file_map, &mut file_graph);
+		let dtsifier = dtsify::DTSifier::new(&mut types, &preflight_file_map, &mut file_graph, &files, out_dir);
 		for file in &topo_sorted_files {
 			let scope = asts.get_mut(&file.path).expect("matching AST not found");
 			dtsifier.dtsify(&file, &scope);
 		}
-		if !found_errors() {
-			let output_files = dtsifier.output_files.borrow();
-			match output_files.emit_files(out_dir) {
-				Ok(()) => {}
-				Err(err) => report_diagnostic(err.into()),
-			}
+		// Declarations are emitted with the JavaScript files, so the ones of removed files are removed too
+		if let Err(err) = output_files.extend(dtsifier.output_files.take()) {
+			report_diagnostic(err.into());
+		}
+	}
+
+	if emit_files && !found_errors() {
+		match output_files.emit_files_removing_orphans(out_dir) {
+			Ok(()) => {}
+			Err(err) => report_diagnostic(err.into()),
+		}
+	}
+
+	// -- API LOCK PHASE --
+	// Libraries can lock their public API, so breaking changes are reported until their major version is bumped
+	if source_path.is_dir() && !found_errors() {
+		if let Some(mode) = options.api_lock {
+			check_api_lock(&types, &source_path, mode, emit_files);
 		}
 	}
 
@@ -467,7 +775,7 @@ pub fn compile(source_path: &Utf8Path, source_text: Option<String>, out_dir: &Ut
 	for source_files_env in &types.source_file_envs {
 		if is_extern_file(source_files_env.0) {
 			let mut extern_dtsifier = ExternDTSifier::new(&types);
-			if !found_errors() {
+			if emit_files && !found_errors() {
 				match extern_dtsifier.dtsify(source_files_env.0, source_files_env.1) {
 					Ok(()) => {}
 					Err(err) => report_diagnostic(err.into()),
@@ -489,29 +797,25 @@ pub fn compile(source_path: &Utf8Path, source_text: Option<String>, out_dir: &Ut
 		})
 		.collect::<Vec<String>>();
 
-	Ok(CompilerOutput { imported_namespaces })
-}
-
-pub fn is_absolute_path(path: &Utf8Path) -> bool {
-	if path.starts_with("/") {
-		return true;
-	}
-
-	// Check if this is a Windows path instead by checking if the second char is a colon
-	// Note: Cannot use Utf8Path::is_absolute() because it doesn't work with Windows paths on WASI
-	let chars = path.as_str().chars().collect::<Vec<char>>();
-	if chars.len() < 2 || chars[1] != ':' {
-		return false;
-	}
+	let inputs = compilation_inputs(
+		&topo_sorted_files,
+		types.source_file_envs.keys(),
+		&library_roots,
+		jsii_types.manifest_files(),
+	);
 
-	return true;
+	Ok(CompilerOutput {
+		imported_namespaces,
+		tests,
+		inputs,
+	})
 }
 
 #[cfg(test)]
 mod sanity {
 	use camino::{Utf8Path, Utf8PathBuf};
 
-	use crate::{compile, diagnostic::assert_no_panics};
+	use crate::{compile, compile_str, diagnostic::assert_no_panics, COMPILE_STR_SOURCE_PATH};
 	use std::fs;
 
 	fn get_wing_files<P>(dir: P) -> impl Iterator<Item = Utf8PathBuf>
@@ -572,4 +876,11 @@ mod sanity {
 	fn cannot_compile_invalid_files() {
 		compile_test("../../../tests/invalid", true);
 	}
+
+	#[test]
+	fn compile_str_does_not_write_output() {
+		let _ = compile_str("bring cloud;\nlet b = new cloud.Bucket();\ntest \"put\" { b.put(\"a\", \"b\"); }\n");
+		assert_no_panics();
+		assert!(!Utf8Path::new(COMPILE_STR_SOURCE_PATH).parent().unwrap().exists());
+	}
 }
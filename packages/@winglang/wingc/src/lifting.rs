@@ -1,24 +1,33 @@
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
 use itertools::Itertools;
 
 use crate::{
 	ast::{
-		Class, Expr, ExprKind, FunctionBody, FunctionDefinition, Phase, Reference, Scope, Stmt, StmtKind, Symbol,
-		UserDefinedType,
+		BinaryOperator, Class, Expr, ExprKind, FunctionBody, FunctionDefinition, Literal, Phase, Reference, Scope, Stmt,
+		StmtKind, Symbol, UserDefinedType,
 	},
 	comp_ctx::{CompilationContext, CompilationPhase},
-	diagnostic::{report_diagnostic, Diagnostic, DiagnosticSeverity},
+	diagnostic::{Diagnostic, DiagnosticSeverity, WingSpan},
 	jsify::{JSifier, JSifyContext},
+	messages::{LIFTED_ONLY_TO_READ_DATA, SHADOWED_SYMBOL},
 	type_check::{
 		get_udt_definition_phase,
 		lifts::{Liftable, Lifts},
 		resolve_user_defined_type,
 		symbol_env::LookupResult,
-		ClassLike, ResolveSource, SymbolKind, TypeRef, CLOSURE_CLASS_HANDLE_METHOD,
+		ClassLike, ResolveSource, SetMemberHashing, SymbolKind, TypeRef, CLOSURE_CLASS_HANDLE_METHOD, COMPARE_TO_METHOD,
+		HASH_METHOD,
 	},
 	visit::{self, Visit},
 	visit_context::{PropertyObject, VisitContext, VisitorWithContext},
 };
 
+/// Lifted values that contain preflight objects by method and preflight code, and the data property read on each of
+/// them if that's all it's lifted for
+type DataReads = IndexMap<(String, String), Option<(Symbol, WingSpan)>>;
+
 pub struct LiftVisitor<'a> {
 	ctx: VisitContext,
 	jsify: &'a JSifier<'a>,
@@ -27,6 +36,11 @@ pub struct LiftVisitor<'a> {
 	in_disable_lift_qual_err: usize,
 	// Used during visiting to track whether we're inside an inner inflight class
 	in_inner_inflight_class: usize,
+	// Preflight constants (non-reassignable variables initialized with a literal) by the span of their name, inflight
+	// references to them are inlined instead of lifted
+	constants: HashMap<WingSpan, &'a Expr>,
+	// The data reads of each class being visited
+	data_reads_stack: Vec<DataReads>,
 }
 
 impl<'a> LiftVisitor<'a> {
@@ -37,6 +51,8 @@ impl<'a> LiftVisitor<'a> {
 			lifts_stack: vec![],
 			in_inner_inflight_class: 0,
 			in_disable_lift_qual_err: 0,
+			constants: HashMap::new(),
+			data_reads_stack: vec![],
 		}
 	}
 
@@ -64,17 +80,10 @@ impl<'a> LiftVisitor<'a> {
 		// Solving this will require renaming the symbols in the current scope to avoid the conflict, so the east way is just to adopt
 		// the JS limitation. Will be good to improve on this in the future.
 		if let Some(env) = self.ctx.current_env() {
-			if matches!(
-				env.lookup_ext(symbol, Some(self.ctx.current_stmt_idx())),
-				LookupResult::DefinedLater(_)
-			) {
-				report_diagnostic(Diagnostic {
-					span: Some(symbol.span.clone()),
-					message: format!("Cannot access \"{symbol}\" because it is shadowed by another symbol with the same name"),
-					annotations: vec![],
-					hints: vec![],
-					severity: DiagnosticSeverity::Error,
-				});
+			if let LookupResult::DefinedLater(shadowing_span) = env.lookup_ext(symbol, Some(self.ctx.current_stmt_idx())) {
+				Diagnostic::from_message(SHADOWED_SYMBOL, &[("name", symbol)], symbol)
+					.annotate("shadowed by this definition", shadowing_span)
+					.report();
 			}
 		}
 	}
@@ -122,6 +131,55 @@ impl<'a> LiftVisitor<'a> {
 		return true;
 	}
 
+	/// Returns the literal value of a preflight constant if the expression references one
+	fn constant_value(&self, node: &Expr) -> Option<&'a Expr> {
+		let ExprKind::Reference(Reference::Identifier(symbol)) = &node.kind else {
+			return None;
+		};
+		let env = self.ctx.current_env()?;
+		let LookupResult::Found(kind, _) = env.lookup_ext(symbol, Some(self.ctx.current_stmt_idx())) else {
+			return None;
+		};
+		self.constants.get(&kind.as_variable()?.name.span).copied()
+	}
+
+	/// Records a lift of a value that contains preflight objects by the current method, and the data property read on
+	/// it if that's what it's lifted for
+	fn record_data_read(&mut self, code: &str, data_read: Option<(Symbol, WingSpan)>) {
+		let Some((method, _)) = self.ctx.current_method() else {
+			return;
+		};
+		let Some(data_reads) = self.data_reads_stack.last_mut() else {
+			return;
+		};
+		let entry = data_reads
+			.entry((method.name, code.to_string()))
+			.or_insert(data_read.clone());
+		if data_read.is_none() {
+			*entry = None;
+		}
+	}
+
+	/// Warns about values that are lifted only to read data from them, even though lifting them lifts the preflight
+	/// objects they contain too
+	fn report_data_reads(data_reads: DataReads) {
+		for ((_, object), data_read) in data_reads {
+			let Some((property, span)) = data_read else {
+				continue;
+			};
+			Diagnostic::from_message(
+				LIFTED_ONLY_TO_READ_DATA,
+				&[("object", &object), ("property", &property)],
+				&span,
+			)
+			.hint(format!(
+				"Store \"{object}.{property}\" in a preflight variable and use that instead, so only its value is lifted"
+			))
+			.severity(DiagnosticSeverity::Warning)
+			.report();
+		}
+	}
+
 	fn jsify_expr(&mut self, node: &Expr) -> String {
 		self.ctx.push_phase(Phase::Preflight);
 		let res = self.jsify.jsify_expression(
@@ -180,12 +238,6 @@ impl<'a> LiftVisitor<'a> {
 			udt_js
 		}
 	}
-
-	// Used for generating a js array represtining a lift qualificaiton (an operation done on a lifted preflight object)
-	// lift qualifcations are in array format so multiple ops can be bunched together in some cases.
-	fn jsify_symbol_to_op_array(&self, symb: &Symbol) -> String {
-		format!("[\"{symb}\"]")
-	}
 }
 
 impl<'a> Visit<'a> for LiftVisitor<'a> {
@@ -207,7 +259,7 @@ impl<'a> Visit<'a> for LiftVisitor<'a> {
 				self.verify_defined_in_current_env(symb);
 				visit::visit_reference(self, &node);
 			}
-			Reference::ElementAccess { .. } => {
+			Reference::ElementAccess { .. } | Reference::SuperMember { .. } => {
 				visit::visit_reference(self, &node);
 			}
 		}
@@ -263,7 +315,10 @@ impl<'a> Visit<'a> for LiftVisitor<'a> {
 				let property = match v.ctx.current_property() {
 					Some((PropertyObject::Instance(prop_expr_id), property)) if node.id == prop_expr_id => Some(property),
 					_ => {
-						if expr_type.is_closure() {
+						if v.jsify.types.set_members.get(&node.id) == Some(&SetMemberHashing::Method) {
+							// this preflight object is a set member that's hashed by its `hash` method
+							Some(Symbol::global(HASH_METHOD))
+						} else if expr_type.is_closure() {
 							// this is the case where we are lifting a "closure class" (a preflight class that has an inflight `handle`
 							// method is being called) the reason we might not have "property" set is because closure classes might be
 							// syntheticaly generated by the compiler from closures.
@@ -279,22 +334,53 @@ impl<'a> Visit<'a> for LiftVisitor<'a> {
 					return;
 				}
 
+				// constants are inlined, so there's nothing to lift
+				if let Some(value) = v.constant_value(node) {
+					let code = v.jsify_expr(value);
+					let mut lifts = v.lifts_stack.pop().unwrap();
+					lifts.inline(&Liftable::Expr(node.id), &code);
+					v.lifts_stack.push(lifts);
+					return;
+				}
+
 				// jsify the expression so we can get the preflight code
 				let code = v.jsify_expr(&node);
 
 				let mut lifts = v.lifts_stack.pop().unwrap();
 				let is_field = code.contains("this."); // TODO: starts_with?
-				lifts.lift(
-					v.ctx.current_method().map(|(m, _)| m).expect("a method"),
-					property.map(|p| v.jsify_symbol_to_op_array(&p)),
-					&code,
-				);
+				let ops = property.iter().map(|p| p.name.as_str()).collect_vec();
+				lifts.lift(v.ctx.current_method().map(|(m, _)| m).expect("a method"), &ops, &code);
 				lifts.capture(&Liftable::Expr(node.id), &code, is_field);
 				v.lifts_stack.push(lifts);
+
+				if expr_type.collection_item_type().is_some_and(contains_preflight_objects) {
+					// Reading a property that isn't a method only needs its value
+					let reads_data = v
+						.ctx
+						.parent_expr()
+						.is_some_and(|parent| !v.jsify.types.get_expr_id_type(parent).is_function_sig());
+					let data_read = property.filter(|_| reads_data).map(|p| (p, node.span.clone()));
+					v.record_data_read(&code, data_read);
+				}
 				return;
 			}
 
-			// Before we continue lets dive into this (non-preflight) expression to see if we need to lift any parts of it
+			// Before we continue lets dive into this (non-preflight) expression to see if we need to lift any parts of it.
+			// Comparing an object that defines an ordering calls its `compareTo` method, so qualify its lift with it.
+			if let ExprKind::Binary { op, left, right } = &node.kind {
+				if matches!(
+					op,
+					BinaryOperator::Less | BinaryOperator::LessOrEqual | BinaryOperator::Greater | BinaryOperator::GreaterOrEqual
+				) && v.jsify.types.get_expr_type(left).compare_to_sig().is_some()
+				{
+					v.ctx
+						.push_property(PropertyObject::Instance(left.id), &Symbol::global(COMPARE_TO_METHOD));
+					v.visit_expr(left);
+					v.ctx.pop_property();
+					v.visit_expr(right);
+					return;
+				}
+			}
 			visit::visit_expr(v, node);
 
 			// Check if this is an inflight class defined preflight and if we need to qualify the lift with the current property
@@ -307,7 +393,7 @@ impl<'a> Visit<'a> for LiftVisitor<'a> {
 							// Get preflight code that references the type of the class so we can qualify the lift, note we use a unique
 							// type alias here since we might not have the actual type name available in scope here.
 							let code = &v.jsify.class_singleton(expr_type);
-							lifts.lift(m, Some(v.jsify_symbol_to_op_array(&property)), code);
+							lifts.lift(m, &[property.name.as_str()], code);
 							v.lifts_stack.push(lifts);
 							return;
 						}
@@ -350,9 +436,10 @@ impl<'a> Visit<'a> for LiftVisitor<'a> {
 
 			let property = self.ctx.current_property();
 			let mut lifts = self.lifts_stack.pop().unwrap();
+			let ops = property.iter().map(|(_, p)| p.name.as_str()).collect_vec();
 			lifts.lift(
 				self.ctx.current_method().map(|(m, _)| m).expect("a method"),
-				property.map(|(_, p)| self.jsify_symbol_to_op_array(&p)),
+				&ops,
 				&code,
 			);
 			self.lifts_stack.push(lifts);
@@ -387,6 +474,7 @@ impl<'a> Visit<'a> for LiftVisitor<'a> {
 						&node.signature,
 						node.is_static,
 						self.jsify.types.get_scope_env(&scope),
+						&node.span,
 					);
 				}
 
@@ -410,6 +498,7 @@ impl<'a> Visit<'a> for LiftVisitor<'a> {
 			self.ctx.push_class(node);
 
 			self.lifts_stack.push(Lifts::new());
+			self.data_reads_stack.push(IndexMap::new());
 
 			if let Some(parent) = &node.parent {
 				let mut lifts = self.lifts_stack.pop().unwrap();
@@ -424,6 +513,7 @@ impl<'a> Visit<'a> for LiftVisitor<'a> {
 			self.in_inner_inflight_class -= 1;
 		} else {
 			let lifts = self.lifts_stack.pop().expect("Unable to pop class tokens");
+			Self::report_data_reads(self.data_reads_stack.pop().expect("Unable to pop class data reads"));
 
 			if let Some(env) = self.ctx.current_env() {
 				if let Some(mut t) = resolve_user_defined_type(&UserDefinedType::for_class(node), env, 0).ok() {
@@ -447,6 +537,18 @@ impl<'a> Visit<'a> for LiftVisitor<'a> {
 
 		self.ctx.push_stmt(node);
 
+		if let StmtKind::Let {
+			reassignable: false,
+			var_name,
+			initial_value,
+			..
+		} = &node.kind
+		{
+			if self.ctx.current_phase() == Phase::Preflight && is_constant_literal(initial_value) {
+				self.constants.insert(var_name.span.clone(), initial_value);
+			}
+		}
+
 		// If this is an explicit lift statement then add the explicit lift
 		if let StmtKind::ExplicitLift(explicit_lift) = &node.kind {
 			// Mark that within this scope we should ignore unknown preflight objects
@@ -458,15 +560,13 @@ impl<'a> Visit<'a> for LiftVisitor<'a> {
 				// jsify the reference to the preflight object so we can get the preflight code
 				let preflight_code = self.jsify_expr(&qual.obj);
 
-				let ops_str = format!(
-					"[{}]",
-					qual.ops.iter().map(|op| format!("\"{op}\"")).collect_vec().join(", ")
-				);
+				let ops = qual.ops.iter().map(|op| op.name.as_str()).collect_vec();
 				lifts.lift(
 					self.ctx.current_method().map(|(m, _)| m).expect("a method"),
-					Some(ops_str),
+					&ops,
 					&preflight_code,
 				);
+				self.record_data_read(&preflight_code, None);
 			}
 			self.lifts_stack.push(lifts);
 		}
@@ -496,6 +596,31 @@ fn is_inflight_field(expr: &Expr, expr_type: TypeRef, property: &Option<Symbol>)
 	return false;
 }
 
+/// Check if a type is a preflight object or a collection of them (possibly optional or nested)
+fn contains_preflight_objects(type_: TypeRef) -> bool {
+	let type_ = *type_.maybe_unwrap_option();
+	type_.is_preflight_object_type() || type_.collection_item_type().is_some_and(contains_preflight_objects)
+}
+
+/// Check if an expression is a `str`, `num` or `bool` literal, or an immutable `Json` literal made only of such literals.
+/// The values of these can be inlined into inflight code as is.
+fn is_constant_literal(expr: &Expr) -> bool {
+	match &expr.kind {
+		ExprKind::Literal(lit) => !matches!(lit, Literal::InterpolatedString(_) | Literal::Nil),
+		ExprKind::JsonLiteral { is_mut: false, element } => is_constant_json(element),
+		_ => false,
+	}
+}
+
+fn is_constant_json(expr: &Expr) -> bool {
+	match &expr.kind {
+		ExprKind::Literal(lit) => !matches!(lit, Literal::InterpolatedString(_)),
+		ExprKind::ArrayLiteral { items, .. } => items.iter().all(is_constant_json),
+		ExprKind::JsonMapLiteral { fields } => fields.values().all(is_constant_json),
+		_ => false,
+	}
+}
+
 impl VisitorWithContext for LiftVisitor<'_> {
 	fn ctx(&mut self) -> &mut VisitContext {
 		&mut self.ctx
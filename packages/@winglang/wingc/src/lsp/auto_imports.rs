@@ -0,0 +1,642 @@
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use indexmap::IndexMap;
+use lsp_types::{
+	CompletionItem, CompletionItemKind, DidChangeWatchedFilesParams, FileChangeType, Position, Range, TextEdit,
+};
+use tree_sitter::Node;
+
+use crate::ast::{BringSource, Scope, StmtKind};
+use crate::files::Files;
+use crate::lsp::sync::check_utf8;
+use crate::parser::{as_wing_library, is_entrypoint_file};
+use crate::wasm_util::extern_json_fn;
+use crate::{find_nearest_wing_project_dir, INTERNAL_DIRECTORY_NAME, TRUSTED_LIBRARY_NPM_NAMESPACE};
+
+/// Directories that are never searched for Wing files
+const IGNORED_DIRS: [&str; 2] = ["node_modules", "target"];
+
+/// How many levels of subdirectories are searched for Wing files
+const MAX_SEARCH_DEPTH: usize = 8;
+
+/// The most Wing files indexed in a project or in a library
+const MAX_INDEXED_FILES: usize = 2000;
+
+/// A type exported by a module that the current file hasn't brought yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AutoImport {
+	/// The name of the type
+	name: String,
+	kind: CompletionItemKind,
+	/// The `bring` statement that makes the type available
+	bring: String,
+	/// How the type is referenced once brought (e.g. `myLib.sub.Name`)
+	qualified_name: String,
+}
+
+/// Returns completions for the classes, structs, interfaces and enums exported by other files of the project and by
+/// installed Wing libraries that `file` doesn't bring yet. Accepting one of them also adds the missing `bring`.
+///
+/// The exported types are looked up in the project's index (see `ProjectIndex`), so the file system is only searched
+/// the first time types are completed in a project.
+pub fn get_auto_import_completions(file: &Utf8Path, root_scope: &Scope, files: &Files) -> Vec<CompletionItem> {
+	let brought = BroughtModules::new(root_scope);
+	let project_dir = find_nearest_wing_project_dir(file);
+	let source_dir = file.parent().unwrap_or(&project_dir);
+
+	let auto_imports = with_project_index(&project_dir, files, |index| {
+		let mut auto_imports = project_file_auto_imports(index, file, source_dir, &brought);
+		auto_imports.extend(library_auto_imports(index, &brought));
+		auto_imports
+	});
+
+	let bring_position = bring_insert_position(root_scope);
+	auto_imports
+		.into_iter()
+		.map(|auto_import| CompletionItem {
+			label: auto_import.name,
+			kind: Some(auto_import.kind),
+			detail: Some(auto_import.bring.clone()),
+			insert_text: Some(auto_import.qualified_name),
+			additional_text_edits: Some(vec![TextEdit {
+				range: Range {
+					start: bring_position,
+					end: bring_position,
+				},
+				new_text: format!("{}\n", auto_import.bring),
+			}]),
+			..Default::default()
+		})
+		.collect()
+}
+
+thread_local! {
+	/// The index of each project auto imports were completed in, by the project's directory
+	static PROJECT_INDEXES: RefCell<HashMap<Utf8PathBuf, ProjectIndex>> = RefCell::new(HashMap::new());
+}
+
+/// The types exported by the Wing files of a project and by the Wing libraries it has installed. Each part is built
+/// the first time it's needed, and kept up to date from the file system events the editor sends (see
+/// `on_did_change_watched_files`). Files that are open in the editor are indexed from their unsaved text.
+#[derive(Default)]
+struct ProjectIndex {
+	/// The Wing files of the project and the types they export, including internal types
+	files: Option<IndexMap<Utf8PathBuf, FileTypes>>,
+	/// The Wing libraries installed in the project's node_modules, and the public types their files export
+	libraries: Option<Vec<LibraryIndex>>,
+}
+
+/// The types exported by a Wing file, and a hash of the source text they were found in
+struct FileTypes {
+	source_hash: u64,
+	types: Vec<(String, CompletionItemKind)>,
+}
+
+impl FileTypes {
+	fn new(source: &str, include_internal: bool) -> Self {
+		Self {
+			source_hash: hash_source(source),
+			types: exported_types(source, include_internal),
+		}
+	}
+
+	fn read(path: &Utf8Path, include_internal: bool) -> Option<Self> {
+		fs::read_to_string(path)
+			.ok()
+			.map(|source| Self::new(&source, include_internal))
+	}
+}
+
+struct LibraryIndex {
+	/// The name of the library's package
+	name: String,
+	/// The root directory of the library
+	dir: Utf8PathBuf,
+	/// The library's Wing files (other than entrypoints) and the public types they export
+	files: Vec<(Utf8PathBuf, Vec<(String, CompletionItemKind)>)>,
+}
+
+/// Runs `f` with the index of the project in `project_dir`, building the parts of it that aren't built yet
+fn with_project_index<R>(project_dir: &Utf8Path, files: &Files, f: impl FnOnce(&ProjectIndex) -> R) -> R {
+	PROJECT_INDEXES.with(|indexes| {
+		let mut indexes = indexes.borrow_mut();
+		let index = indexes.entry(project_dir.to_owned()).or_default();
+
+		let project_files = index.files.get_or_insert_with(|| {
+			find_wing_files(project_dir, false)
+				.into_iter()
+				.filter_map(|wing_file| FileTypes::read(&wing_file, true).map(|types| (wing_file, types)))
+				.collect()
+		});
+		// files that are open in the editor may have changes that aren't saved yet
+		for (wing_file, file_types) in project_files.iter_mut() {
+			if let Some(source) = files.get_file(wing_file) {
+				if hash_source(source) != file_types.source_hash {
+					*file_types = FileTypes::new(source, true);
+				}
+			}
+		}
+
+		if index.libraries.is_none() {
+			index.libraries = Some(index_libraries(&project_dir.join("node_modules")));
+		}
+
+		f(index)
+	})
+}
+
+/// Indexes the Wing libraries installed in a node_modules directory
+fn index_libraries(node_modules: &Utf8Path) -> Vec<LibraryIndex> {
+	find_installed_libraries(node_modules)
+		.into_iter()
+		.map(|(name, dir)| {
+			let files = find_wing_files(&dir, true)
+				.into_iter()
+				.filter(|wing_file| !is_entrypoint_file(wing_file))
+				.filter_map(|wing_file| FileTypes::read(&wing_file, false).map(|file_types| (wing_file, file_types.types)))
+				.collect();
+			LibraryIndex { name, dir, files }
+		})
+		.collect()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wingc_on_did_change_watched_files(ptr: u32, len: u32) -> u64 {
+	extern_json_fn(ptr, len, on_did_change_watched_files)
+}
+
+/// Keeps the indexes of auto imports up to date with files that are created, changed or deleted outside of the
+/// editor's open documents (e.g. by switching branches or installing packages).
+pub fn on_did_change_watched_files(params: DidChangeWatchedFilesParams) {
+	PROJECT_INDEXES.with(|indexes| {
+		let mut indexes = indexes.borrow_mut();
+		for change in params.changes {
+			let Ok(path) = change.uri.to_file_path() else {
+				continue;
+			};
+			let path = check_utf8(path);
+			for (project_dir, index) in indexes.iter_mut().filter(|(dir, _)| path.starts_with(dir)) {
+				// installing or removing packages changes which libraries are installed
+				if path.starts_with(project_dir.join("node_modules")) || path == project_dir.join("package.json") {
+					index.libraries = None;
+					continue;
+				}
+				let Some(project_files) = &mut index.files else {
+					continue;
+				};
+				if change.typ == FileChangeType::DELETED {
+					// the path may be a directory
+					project_files.retain(|wing_file, _| !wing_file.starts_with(&path));
+				} else if path.extension() == Some("w") {
+					match FileTypes::read(&path, true) {
+						Some(file_types) => {
+							project_files.insert(path.clone(), file_types);
+						}
+						None => {
+							project_files.shift_remove(&path);
+						}
+					}
+				} else if path.is_dir() {
+					// a directory that was created (or moved) may contain any number of files
+					index.files = None;
+				}
+			}
+		}
+	});
+}
+
+fn hash_source(source: &str) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	source.hash(&mut hasher);
+	hasher.finish()
+}
+
+/// The modules a file already brings, and the names they are brought as
+struct BroughtModules {
+	paths: Vec<Utf8PathBuf>,
+	names: HashSet<String>,
+}
+
+impl BroughtModules {
+	fn new(root_scope: &Scope) -> Self {
+		let mut paths = vec![];
+		let mut names = HashSet::new();
+		for stmt in &root_scope.statements {
+			let StmtKind::Bring { source, identifier } = &stmt.kind else {
+				continue;
+			};
+			match source {
+				BringSource::BuiltinModule(name) | BringSource::TrustedModule(name, _) => {
+					names.insert(name.name.clone());
+				}
+				_ => {}
+			}
+			match source {
+				BringSource::TrustedModule(_, path)
+				| BringSource::WingLibrary(_, path)
+				| BringSource::WingFile(path)
+				| BringSource::Directory(path) => paths.push(path.clone()),
+				_ => {}
+			}
+			if let Some(identifier) = identifier {
+				names.insert(identifier.name.clone());
+			}
+		}
+		Self { paths, names }
+	}
+
+	/// Whether the module at `path` is brought, either directly or as part of a brought directory
+	fn contains(&self, path: &Utf8Path) -> bool {
+		self.paths.iter().any(|brought| path.starts_with(brought))
+	}
+}
+
+/// Types exported by the non-entrypoint Wing files of the project
+fn project_file_auto_imports(
+	index: &ProjectIndex,
+	file: &Utf8Path,
+	source_dir: &Utf8Path,
+	brought: &BroughtModules,
+) -> Vec<AutoImport> {
+	let mut auto_imports = vec![];
+	for (wing_file, file_types) in index.files.iter().flatten() {
+		if wing_file == file || is_entrypoint_file(wing_file) || brought.contains(wing_file) {
+			continue;
+		}
+		let Some(alias) = wing_file.file_stem().and_then(to_identifier) else {
+			continue;
+		};
+		if brought.names.contains(&alias) {
+			continue;
+		}
+		let bring = format!("bring \"{}\" as {alias};", relative_module_path(source_dir, wing_file));
+		// files of the same project can also use internal types
+		for (name, kind) in &file_types.types {
+			auto_imports.push(AutoImport {
+				qualified_name: format!("{alias}.{name}"),
+				name: name.clone(),
+				kind: *kind,
+				bring: bring.clone(),
+			});
+		}
+	}
+	auto_imports
+}
+
+/// Types exported by the Wing libraries installed in the project's node_modules
+fn library_auto_imports(index: &ProjectIndex, brought: &BroughtModules) -> Vec<AutoImport> {
+	let mut auto_imports = vec![];
+	for library in index.libraries.iter().flatten() {
+		if brought.contains(&library.dir) {
+			continue;
+		}
+		let (bring, alias) = match library.name.strip_prefix(&format!("{TRUSTED_LIBRARY_NPM_NAMESPACE}/")) {
+			Some(trusted_name) => (format!("bring {trusted_name};"), trusted_name.to_string()),
+			None => {
+				let Some(alias) = to_identifier(library.name.rsplit('/').next().unwrap_or(&library.name)) else {
+					continue;
+				};
+				(format!("bring \"{}\" as {alias};", library.name), alias)
+			}
+		};
+		if brought.names.contains(&alias) {
+			continue;
+		}
+		for (wing_file, types) in &library.files {
+			// files in subdirectories are brought as namespaces named after the directory
+			let namespace = wing_file
+				.parent()
+				.and_then(|dir| dir.strip_prefix(&library.dir).ok())
+				.map(|dir| dir.components().map(|c| format!("{}.", c.as_str())).collect::<String>())
+				.unwrap_or_default();
+			for (name, kind) in types {
+				auto_imports.push(AutoImport {
+					qualified_name: format!("{alias}.{namespace}{name}"),
+					name: name.clone(),
+					kind: *kind,
+					bring: bring.clone(),
+				});
+			}
+		}
+	}
+	auto_imports
+}
+
+/// Returns the names and directories of the Wing libraries in a node_modules directory (including scoped packages)
+fn find_installed_libraries(node_modules: &Utf8Path) -> Vec<(String, Utf8PathBuf)> {
+	let mut libraries = vec![];
+	for dir in subdirectories(node_modules) {
+		if dir.file_name().map_or(false, |name| name.starts_with('@')) {
+			for scoped_dir in subdirectories(&dir) {
+				if let Some(name) = as_wing_library(&scoped_dir, true) {
+					libraries.push((name, scoped_dir));
+				}
+			}
+		} else if let Some(name) = as_wing_library(&dir, true) {
+			libraries.push((name, dir));
+		}
+	}
+	libraries
+}
+
+/// Returns the Wing files under `dir`. Files in internal directories are skipped if `skip_internal` is set, since
+/// they can't be used from outside of their library.
+///
+/// The search is bounded (see `MAX_SEARCH_DEPTH` and `MAX_INDEXED_FILES`), since a file that isn't part of a project
+/// is indexed with its directory as the project, and that may be a home directory.
+fn find_wing_files(dir: &Utf8Path, skip_internal: bool) -> Vec<Utf8PathBuf> {
+	let mut wing_files = vec![];
+	collect_wing_files(dir, skip_internal, MAX_SEARCH_DEPTH, &mut wing_files);
+	wing_files
+}
+
+fn collect_wing_files(dir: &Utf8Path, skip_internal: bool, depth: usize, wing_files: &mut Vec<Utf8PathBuf>) {
+	let Ok(entries) = dir.read_dir_utf8() else {
+		return;
+	};
+	let mut entries = entries.filter_map(|entry| entry.ok()).collect::<Vec<_>>();
+	entries.sort_by(|a, b| a.file_name().cmp(b.file_name()));
+	for entry in entries {
+		if wing_files.len() >= MAX_INDEXED_FILES {
+			return;
+		}
+		let name = entry.file_name();
+		if name.starts_with('.') || IGNORED_DIRS.contains(&name) {
+			continue;
+		}
+		let is_dir = entry.file_type().map_or(false, |t| t.is_dir());
+		if is_dir {
+			if depth > 0 && !(skip_internal && name == INTERNAL_DIRECTORY_NAME) {
+				collect_wing_files(entry.path(), skip_internal, depth - 1, wing_files);
+			}
+		} else if entry.path().extension() == Some("w") {
+			wing_files.push(entry.into_path());
+		}
+	}
+}
+
+fn subdirectories(dir: &Utf8Path) -> Vec<Utf8PathBuf> {
+	let Ok(entries) = dir.read_dir_utf8() else {
+		return vec![];
+	};
+	let mut subdirs = entries
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| !entry.file_name().starts_with('.'))
+		// packages installed by pnpm and yarn are symlinked
+		.filter(|entry| entry.path().is_dir())
+		.map(|entry| entry.into_path())
+		.collect::<Vec<_>>();
+	subdirs.sort();
+	subdirs
+}
+
+/// Returns the public classes, structs, interfaces and enums declared at the top level of a Wing source file.
+/// Internal types are included if `include_internal` is set.
+fn exported_types(source: &str, include_internal: bool) -> Vec<(String, CompletionItemKind)> {
+	let mut parser = tree_sitter::Parser::new();
+	parser.set_language(&tree_sitter_wing::language()).unwrap();
+	let Some(tree) = parser.parse(source, None) else {
+		return vec![];
+	};
+
+	let is_exported = |access_modifier: Option<Node>| {
+		access_modifier
+			.and_then(|node| node.utf8_text(source.as_bytes()).ok())
+			.map_or(false, |access| {
+				access == "pub" || (include_internal && access == "internal")
+			})
+	};
+
+	let root = tree.root_node();
+	let mut cursor = root.walk();
+	let mut types = vec![];
+	for node in root.named_children(&mut cursor) {
+		let (access_modifier, name, kind) = match node.kind() {
+			"class_definition" | "interface_definition" => {
+				let access_modifier = node.child_by_field_name("modifiers").and_then(|modifiers| {
+					let mut cursor = modifiers.walk();
+					let access_modifier = modifiers
+						.named_children(&mut cursor)
+						.find(|modifier| modifier.kind() == "access_modifier");
+					access_modifier
+				});
+				let kind = if node.kind() == "class_definition" {
+					CompletionItemKind::CLASS
+				} else {
+					CompletionItemKind::INTERFACE
+				};
+				(access_modifier, node.child_by_field_name("name"), kind)
+			}
+			"struct_definition" => (
+				node.child_by_field_name("access_modifier"),
+				node.child_by_field_name("name"),
+				CompletionItemKind::STRUCT,
+			),
+			"enum_definition" => (
+				node.child_by_field_name("access_modifier"),
+				node.child_by_field_name("enum_name"),
+				CompletionItemKind::ENUM,
+			),
+			_ => continue,
+		};
+		if !is_exported(access_modifier) {
+			continue;
+		}
+		if let Some(name) = name.and_then(|name| name.utf8_text(source.as_bytes()).ok()) {
+			types.push((name.to_string(), kind));
+		}
+	}
+	types
+}
+
+/// Returns the position new `bring` statements are inserted at: after the file's last top-level `bring`,
+/// or at the start of the file if it has none.
+fn bring_insert_position(root_scope: &Scope) -> Position {
+	root_scope
+		.statements
+		.iter()
+		.filter(|stmt| matches!(stmt.kind, StmtKind::Bring { .. }))
+		.last()
+		.map_or(Position::new(0, 0), |stmt| Position::new(stmt.span.end.line + 1, 0))
+}
+
+/// Returns the path of a Wing file relative to `source_dir`, as written in a `bring` statement (e.g. "../lib/util.w")
+fn relative_module_path(source_dir: &Utf8Path, wing_file: &Utf8Path) -> String {
+	let common = source_dir
+		.ancestors()
+		.find(|ancestor| wing_file.starts_with(ancestor))
+		.unwrap_or(source_dir);
+	let ups = source_dir
+		.strip_prefix(common)
+		.map_or(0, |rest| rest.components().count());
+	let rest = wing_file.strip_prefix(common).unwrap_or(wing_file);
+	if ups == 0 {
+		format!("./{rest}")
+	} else {
+		format!("{}{rest}", "../".repeat(ups))
+	}
+}
+
+/// Converts a file or package name to a camelCase identifier (e.g. "my-utils" becomes "myUtils")
+fn to_identifier(name: &str) -> Option<String> {
+	let mut identifier = String::new();
+	let mut upper_next = false;
+	for c in name.chars() {
+		if c.is_ascii_alphanumeric() {
+			if identifier.is_empty() && c.is_ascii_digit() {
+				continue;
+			}
+			if upper_next && !identifier.is_empty() {
+				identifier.push(c.to_ascii_uppercase());
+			} else {
+				identifier.push(c);
+			}
+			upper_next = false;
+		} else {
+			upper_next = true;
+		}
+	}
+	(!identifier.is_empty()).then_some(identifier)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use camino::{Utf8Path, Utf8PathBuf};
+	use lsp_types::CompletionItemKind;
+
+	use lsp_types::{DidChangeWatchedFilesParams, FileChangeType, FileEvent, Url};
+
+	use super::{
+		exported_types, find_installed_libraries, find_wing_files, on_did_change_watched_files, relative_module_path,
+		to_identifier, with_project_index, MAX_SEARCH_DEPTH,
+	};
+	use crate::files::Files;
+
+	fn indexed_types(project_dir: &Utf8Path, files: &Files) -> Vec<String> {
+		with_project_index(project_dir, files, |index| {
+			index
+				.files
+				.iter()
+				.flatten()
+				.flat_map(|(_, file_types)| file_types.types.iter().map(|(name, _)| name.clone()))
+				.collect()
+		})
+	}
+
+	fn file_changed(path: &Utf8Path, typ: FileChangeType) {
+		on_did_change_watched_files(DidChangeWatchedFilesParams {
+			changes: vec![FileEvent {
+				uri: Url::from_file_path(path).unwrap(),
+				typ,
+			}],
+		});
+	}
+
+	#[test]
+	fn finds_exported_types() {
+		let source = r#"
+bring cloud;
+pub class Store {}
+internal struct Options { name: str; }
+pub enum Color { Red }
+pub interface IStore {}
+class Helper {}
+let x = 1;
+"#;
+		assert_eq!(
+			exported_types(source, false),
+			vec![
+				("Store".to_string(), CompletionItemKind::CLASS),
+				("Color".to_string(), CompletionItemKind::ENUM),
+				("IStore".to_string(), CompletionItemKind::INTERFACE),
+			]
+		);
+		assert!(exported_types(source, true).contains(&("Options".to_string(), CompletionItemKind::STRUCT)));
+	}
+
+	#[test]
+	fn finds_installed_wing_libraries() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let node_modules = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+		for (dir, package_json) in [
+			("my-lib", r#"{ "name": "my-lib", "wing": true }"#),
+			("@winglibs/redis", r#"{ "name": "@winglibs/redis", "wing": {} }"#),
+			("lodash", r#"{ "name": "lodash" }"#),
+		] {
+			fs::create_dir_all(node_modules.join(dir)).unwrap();
+			fs::write(node_modules.join(dir).join("package.json"), package_json).unwrap();
+		}
+
+		assert_eq!(
+			find_installed_libraries(&node_modules),
+			vec![
+				("@winglibs/redis".to_string(), node_modules.join("@winglibs/redis")),
+				("my-lib".to_string(), node_modules.join("my-lib")),
+			]
+		);
+	}
+
+	#[test]
+	fn builds_bring_paths_and_aliases() {
+		assert_eq!(
+			relative_module_path(Utf8Path::new("/app/src"), Utf8Path::new("/app/src/store.w")),
+			"./store.w"
+		);
+		assert_eq!(
+			relative_module_path(Utf8Path::new("/app/src/api"), Utf8Path::new("/app/lib/util.w")),
+			"../../lib/util.w"
+		);
+		assert_eq!(to_identifier("my-utils"), Some("myUtils".to_string()));
+		assert_eq!(to_identifier("store.types"), Some("storeTypes".to_string()));
+		assert_eq!(to_identifier("--"), None);
+	}
+
+	#[test]
+	fn bounds_the_search_for_wing_files() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+		let too_deep = (0..=MAX_SEARCH_DEPTH).fold(root.clone(), |dir, level| dir.join(format!("d{level}")));
+		fs::create_dir_all(&too_deep).unwrap();
+		fs::write(root.join("d0/shallow.w"), "").unwrap();
+		fs::write(too_deep.join("deep.w"), "").unwrap();
+
+		assert_eq!(find_wing_files(&root, false), vec![root.join("d0/shallow.w")]);
+	}
+
+	#[test]
+	fn keeps_the_index_up_to_date() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+		fs::write(root.join("store.w"), "pub class Store {}").unwrap();
+		let files = Files::new();
+		assert_eq!(indexed_types(&root, &files), vec!["Store"]);
+
+		// the index isn't rebuilt for every completion
+		fs::write(root.join("store.w"), "pub class Table {}").unwrap();
+		fs::write(root.join("queue.w"), "pub class Queue {}").unwrap();
+		assert_eq!(indexed_types(&root, &files), vec!["Store"]);
+
+		// but it's updated by file system events
+		file_changed(&root.join("store.w"), FileChangeType::CHANGED);
+		file_changed(&root.join("queue.w"), FileChangeType::CREATED);
+		assert_eq!(indexed_types(&root, &files), vec!["Table", "Queue"]);
+
+		fs::remove_file(root.join("store.w")).unwrap();
+		file_changed(&root.join("store.w"), FileChangeType::DELETED);
+		assert_eq!(indexed_types(&root, &files), vec!["Queue"]);
+
+		// and unsaved changes of open files are used instead of their contents on disk
+		let mut files = Files::new();
+		files
+			.add_file(root.join("queue.w"), "pub class Topic {}".to_string())
+			.unwrap();
+		assert_eq!(indexed_types(&root, &files), vec!["Topic"]);
+	}
+}
@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use lsp_types::{CompletionItem, CompletionItemKind, CompletionTextEdit, Position, Range, TextEdit};
+
+use crate::file_graph::FileGraph;
+use crate::files::Files;
+use crate::parser::{dir_contains_wing_file_recursive, is_entrypoint_file};
+use crate::paths::normalize_path;
+
+/// Directories that are never offered as modules
+const IGNORED_DIRS: [&str; 2] = ["node_modules", "target"];
+
+/// Returns the relative path typed so far if `line_prefix` (the text of a line up to the cursor) ends inside the
+/// module path of a `bring` statement, like `bring "./lib/u`.
+pub fn bring_path_at_cursor(line_prefix: &str) -> Option<&str> {
+	let rest = line_prefix.trim_start().strip_prefix("bring")?;
+	if !rest.starts_with(char::is_whitespace) {
+		return None;
+	}
+	let path = rest.trim_start().strip_prefix('"')?;
+	if path.contains('"') || !(path.starts_with("./") || path.starts_with("../")) {
+		return None;
+	}
+	Some(path)
+}
+
+/// Returns completions for the Wing files and directories that can be brought from `file`, in the directory
+/// `typed_path` (the path typed so far) points to. Files that don't exist on disk yet but are open in the editor
+/// are included too.
+///
+/// The file itself, entrypoints, and modules that (directly or through other files) bring `file` are left out,
+/// since bringing them would fail.
+pub fn get_bring_path_completions(
+	file: &Utf8Path,
+	typed_path: &str,
+	position: Position,
+	files: &Files,
+	file_graph: &FileGraph,
+) -> Vec<CompletionItem> {
+	let (typed_dir, typed_name) = typed_path.rsplit_once('/').unwrap_or(("", typed_path));
+	let dir = normalize_path(Utf8Path::new(&format!("{typed_dir}/")), Some(file));
+	let dependents = files_that_bring(file, file_graph);
+	let brings_file = |path: &Utf8Path| path == file || dependents.iter().any(|dependent| dependent.starts_with(path));
+
+	let mut entries = vec![];
+	if let Ok(dir_entries) = dir.read_dir_utf8() {
+		for entry in dir_entries.filter_map(|entry| entry.ok()) {
+			let name = entry.file_name();
+			if name.starts_with('.') || IGNORED_DIRS.contains(&name) {
+				continue;
+			}
+			let path = entry.path();
+			if path.is_dir() {
+				// a directory that contains this file brings it as well
+				if !file.starts_with(path) && !brings_file(path) && dir_contains_wing_file_recursive(path) {
+					entries.push((name.to_string(), CompletionItemKind::FOLDER));
+				}
+			} else if path.extension() == Some("w") && !is_entrypoint_file(path) && !brings_file(path) {
+				entries.push((name.to_string(), CompletionItemKind::FILE));
+			}
+		}
+	}
+	for (path, _) in files.iter() {
+		if path.parent() != Some(dir.as_path()) || path.exists() {
+			continue;
+		}
+		if path.extension() == Some("w") && !is_entrypoint_file(path) && !brings_file(path) {
+			if let Some(name) = path.file_name() {
+				entries.push((name.to_string(), CompletionItemKind::FILE));
+			}
+		}
+	}
+	entries.sort_by(|a, b| a.0.cmp(&b.0));
+	entries.dedup_by(|a, b| a.0 == b.0);
+
+	// replace the part of the path after the last "/", so editors don't need to know paths are made of several words
+	let range = Range {
+		start: Position {
+			line: position.line,
+			character: position
+				.character
+				.saturating_sub(typed_name.encode_utf16().count() as u32),
+		},
+		end: position,
+	};
+	entries
+		.into_iter()
+		.map(|(name, kind)| CompletionItem {
+			label: name.clone(),
+			kind: Some(kind),
+			text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text: name })),
+			..Default::default()
+		})
+		.collect()
+}
+
+/// Returns the files that bring `file`, directly or through other files
+fn files_that_bring(file: &Utf8Path, file_graph: &FileGraph) -> HashSet<Utf8PathBuf> {
+	let mut dependents = HashSet::new();
+	for candidate in file_graph.iter_files() {
+		let mut visited = HashSet::new();
+		let mut stack = vec![candidate];
+		while let Some(current) = stack.pop() {
+			if !visited.insert(&current.path) {
+				continue;
+			}
+			let dependencies = file_graph.dependencies_of(current);
+			if dependencies.iter().any(|dependency| dependency.path == file) {
+				dependents.insert(candidate.path.clone());
+				break;
+			}
+			stack.extend(dependencies);
+		}
+	}
+	dependents
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use camino::Utf8PathBuf;
+	use lsp_types::{CompletionItemKind, Position};
+
+	use super::{bring_path_at_cursor, get_bring_path_completions};
+	use crate::file_graph::{File, FileGraph};
+	use crate::files::Files;
+
+	#[test]
+	fn detects_bring_paths() {
+		assert_eq!(bring_path_at_cursor("bring \"./lib/u"), Some("./lib/u"));
+		assert_eq!(bring_path_at_cursor("  bring \"../"), Some("../"));
+		assert_eq!(bring_path_at_cursor("bring \"./util.w\" as "), None);
+		assert_eq!(bring_path_at_cursor("bring \"cdktf"), None);
+		assert_eq!(bring_path_at_cursor("let x = \"./"), None);
+	}
+
+	#[test]
+	fn completes_files_and_directories() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+		fs::create_dir_all(root.join("lib/inner")).unwrap();
+		fs::create_dir_all(root.join("assets")).unwrap();
+		for file in [
+			"main.w",
+			"store.w",
+			"api.w",
+			"other.test.w",
+			"lib/inner/util.w",
+			"assets/logo.png",
+		] {
+			fs::write(root.join(file), "").unwrap();
+		}
+		let main = root.join("main.w");
+
+		// api.w brings store.w, which brings main.w, so neither can be brought from main.w
+		let mut file_graph = FileGraph::default();
+		file_graph.set_file_deps(
+			&File::new(root.join("api.w"), "app"),
+			&[File::new(root.join("store.w"), "app")],
+		);
+		file_graph.set_file_deps(&File::new(root.join("store.w"), "app"), &[File::new(&main, "app")]);
+		let mut files = Files::new();
+		files.add_file(root.join("draft.w"), String::new()).unwrap();
+		fs::write(root.join("models.w"), "").unwrap();
+
+		let completions = get_bring_path_completions(&main, "./", Position::new(0, 9), &files, &file_graph);
+		assert_eq!(
+			completions
+				.iter()
+				.map(|c| (c.label.as_str(), c.kind.unwrap()))
+				.collect::<Vec<_>>(),
+			vec![
+				("draft.w", CompletionItemKind::FILE),
+				("lib", CompletionItemKind::FOLDER),
+				("models.w", CompletionItemKind::FILE),
+			]
+		);
+
+		let completions = get_bring_path_completions(&main, "./lib/in", Position::new(0, 15), &files, &file_graph);
+		assert_eq!(completions.len(), 1);
+		assert_eq!(completions[0].label, "inner");
+	}
+}
@@ -1,14 +1,20 @@
+use itertools::Itertools;
 use lsp_types::{
 	CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionTriggerKind, Diagnostic,
-	DiagnosticSeverity, TextEdit, Url, WorkspaceEdit,
+	DiagnosticSeverity, NumberOrString, Range, TextEdit, Url, WorkspaceEdit,
 };
 use std::collections::HashMap;
+use tree_sitter::{Node, Point, TreeCursor};
 
-use crate::diagnostic::{get_diagnostics, ERR_EXPECTED_SEMICOLON};
+use crate::diagnostic::{get_diagnostics, WingLocation};
+use crate::lsp::sync::{check_unchecked_documents, check_utf8, PROJECT_DATA};
+use crate::messages::{ALL_MESSAGES, EXPECTED_SEMICOLON, FIELD_NEVER_REASSIGNED, VARIABLE_NEVER_REASSIGNED};
+use crate::type_check::suggestions::{parse_access_modifier_hint, parse_did_you_mean_hint, parse_missing_fields_hint};
 use crate::wasm_util::extern_json_fn;
 
 #[no_mangle]
 pub unsafe extern "C" fn wingc_on_code_action(ptr: u32, len: u32) -> u64 {
+	check_unchecked_documents();
 	extern_json_fn(ptr, len, on_code_action)
 }
 
@@ -19,7 +25,7 @@ pub fn on_code_action(params: CodeActionParams) -> Vec<CodeActionOrCommand> {
 
 	if matches!(context.trigger_kind, Some(CodeActionTriggerKind::INVOKED)) && !context.diagnostics.is_empty() {
 		for diagnostic in context.diagnostics {
-			if let Some(mut action) = get_fix_for_diagnostic(uri.clone(), diagnostic) {
+			for mut action in get_fixes_for_diagnostic(uri.clone(), diagnostic) {
 				if let CodeActionOrCommand::CodeAction(action) = &mut action {
 					if action.kind == Some(CodeActionKind::SOURCE_FIX_ALL) {
 						action.kind = Some(CodeActionKind::QUICKFIX);
@@ -44,16 +50,19 @@ pub fn on_code_action(params: CodeActionParams) -> Vec<CodeActionOrCommand> {
 			continue;
 		}
 
-		let action = get_fix_for_diagnostic(
+		let actions = get_fixes_for_diagnostic(
 			uri.clone(),
 			Diagnostic {
 				message: original_diagnostic.message.clone(),
 				range: original_diagnostic.span.clone().unwrap_or_default().into(),
 				severity: Some(DiagnosticSeverity::ERROR),
+				code: original_diagnostic
+					.code
+					.map(|code| NumberOrString::String(code.to_string())),
 				..Default::default()
 			},
 		);
-		if let Some(mut action) = action {
+		for mut action in actions {
 			if let CodeActionOrCommand::CodeAction(action) = &mut action {
 				if action.title == "Insert ';'" {
 					let mut add_autofix = true;
@@ -118,9 +127,124 @@ pub fn on_code_action(params: CodeActionParams) -> Vec<CodeActionOrCommand> {
 	action_list
 }
 
+fn get_fixes_for_diagnostic(file: Url, diagnostic: Diagnostic) -> Vec<CodeActionOrCommand> {
+	let suggestions = did_you_mean_suggestions(&diagnostic);
+	if !suggestions.is_empty() {
+		return suggestions
+			.into_iter()
+			.filter_map(|suggestion| {
+				let range = find_misspelled_name(&file, &diagnostic.range, &suggestion)?;
+				let mut change_hashmap = HashMap::new();
+				change_hashmap.insert(
+					file.clone(),
+					vec![TextEdit {
+						range,
+						new_text: suggestion.clone(),
+					}],
+				);
+				Some(CodeActionOrCommand::CodeAction(CodeAction {
+					title: format!("Change to \"{suggestion}\""),
+					kind: Some(CodeActionKind::QUICKFIX),
+					diagnostics: Some(vec![diagnostic.clone()]),
+					edit: Some(WorkspaceEdit {
+						changes: Some(change_hashmap),
+						..Default::default()
+					}),
+					..Default::default()
+				}))
+			})
+			.collect();
+	}
+
+	let access_modifier_fixes = access_modifier_fixes(&diagnostic);
+	if !access_modifier_fixes.is_empty() {
+		return access_modifier_fixes;
+	}
+
+	let missing_fields_fixes = missing_fields_fixes(&file, &diagnostic);
+	if !missing_fields_fixes.is_empty() {
+		return missing_fields_fixes;
+	}
+
+	get_fix_for_diagnostic(file, diagnostic).into_iter().collect()
+}
+
+/// Fixes giving a declaration that can't be accessed where the diagnostic is the access modifier the compiler suggests
+/// for it. The declaration is the first thing the compiler's diagnostic points at besides the access itself, and may be
+/// in another file.
+fn access_modifier_fixes(diagnostic: &Diagnostic) -> Vec<CodeActionOrCommand> {
+	compiler_diagnostics_like(diagnostic)
+		.flat_map(|original| {
+			let Some(declaration) = original.annotations.first().map(|annotation| annotation.span.clone()) else {
+				return vec![];
+			};
+			original
+				.hints
+				.iter()
+				.filter_map(|hint| {
+					let (name, keyword) = parse_access_modifier_hint(hint)?;
+					let file = Url::from_file_path(&declaration.file_id).ok()?;
+					let edit = access_modifier_edit(&file, &(&declaration).into(), &keyword)?;
+					let mut change_hashmap = HashMap::new();
+					change_hashmap.insert(file, vec![edit]);
+					Some(CodeActionOrCommand::CodeAction(CodeAction {
+						title: format!("Declare \"{name}\" as \"{keyword}\""),
+						kind: Some(CodeActionKind::QUICKFIX),
+						diagnostics: Some(vec![diagnostic.clone()]),
+						edit: Some(WorkspaceEdit {
+							changes: Some(change_hashmap),
+							..Default::default()
+						}),
+						..Default::default()
+					}))
+				})
+				.collect::<Vec<_>>()
+		})
+		.collect()
+}
+
+/// Fixes adding the fields a struct literal is missing, initialized with the placeholders the compiler suggests
+fn missing_fields_fixes(file: &Url, diagnostic: &Diagnostic) -> Vec<CodeActionOrCommand> {
+	let initializers = compiler_diagnostics_like(diagnostic)
+		.flat_map(|original| {
+			original
+				.hints
+				.iter()
+				.flat_map(|hint| parse_missing_fields_hint(hint))
+				.collect::<Vec<_>>()
+		})
+		.unique()
+		.collect_vec();
+	if initializers.is_empty() {
+		return vec![];
+	}
+	let Some(edit) = missing_fields_edit(file, &diagnostic.range, &initializers) else {
+		return vec![];
+	};
+	let mut change_hashmap = HashMap::new();
+	change_hashmap.insert(file.clone(), vec![edit]);
+	vec![CodeActionOrCommand::CodeAction(CodeAction {
+		title: if initializers.len() == 1 {
+			"Add missing field".to_string()
+		} else {
+			"Add missing fields".to_string()
+		},
+		kind: Some(CodeActionKind::QUICKFIX),
+		diagnostics: Some(vec![diagnostic.clone()]),
+		edit: Some(WorkspaceEdit {
+			changes: Some(change_hashmap),
+			..Default::default()
+		}),
+		is_preferred: Some(true),
+		..Default::default()
+	})]
+}
+
 fn get_fix_for_diagnostic(file: Url, diagnostic: Diagnostic) -> Option<CodeActionOrCommand> {
-	match diagnostic.message.as_str() {
-		ERR_EXPECTED_SEMICOLON => {
+	// Diagnostics are identified by their code, since their message may be translated
+	let code = diagnostic_code(&diagnostic)?;
+	match code {
+		code if code == EXPECTED_SEMICOLON.code => {
 			let mut change_hashmap = HashMap::new();
 			change_hashmap.insert(
 				file,
@@ -142,10 +266,268 @@ fn get_fix_for_diagnostic(file: Url, diagnostic: Diagnostic) -> Option<CodeActio
 				..Default::default()
 			}))
 		}
+		code if code == VARIABLE_NEVER_REASSIGNED.code || code == FIELD_NEVER_REASSIGNED.code => {
+			let range = find_reassignable_modifier(&file, &diagnostic.range)?;
+			let mut change_hashmap = HashMap::new();
+			change_hashmap.insert(
+				file,
+				vec![TextEdit {
+					range,
+					new_text: "".to_string(),
+				}],
+			);
+			Some(CodeActionOrCommand::CodeAction(CodeAction {
+				title: "Remove \"var\"".to_string(),
+				kind: Some(CodeActionKind::QUICKFIX),
+				diagnostics: Some(vec![diagnostic.clone()]),
+				edit: Some(WorkspaceEdit {
+					changes: Some(change_hashmap),
+					..Default::default()
+				}),
+
+				is_preferred: Some(true),
+				..Default::default()
+			}))
+		}
 		_ => None,
 	}
 }
 
+/// The code of the given diagnostic, or of the compiler's own copy of it if the client didn't keep its code
+fn diagnostic_code(diagnostic: &Diagnostic) -> Option<&'static str> {
+	if let Some(NumberOrString::String(code)) = &diagnostic.code {
+		return ALL_MESSAGES
+			.iter()
+			.map(|message| message.code)
+			.find(|known_code| known_code == code);
+	}
+	compiler_diagnostics_like(diagnostic).find_map(|original| original.code)
+}
+
+/// The names suggested by the compiler for a misspelled name in the given diagnostic (matched with the compiler's own
+/// diagnostic, since the client's copy might not have its hints)
+fn did_you_mean_suggestions(diagnostic: &Diagnostic) -> Vec<String> {
+	compiler_diagnostics_like(diagnostic)
+		.flat_map(|original| {
+			original
+				.hints
+				.iter()
+				.flat_map(|hint| parse_did_you_mean_hint(hint))
+				.collect::<Vec<_>>()
+		})
+		.unique()
+		.collect()
+}
+
+/// The compiler's own diagnostics that the client's diagnostic is a copy of
+fn compiler_diagnostics_like(diagnostic: &Diagnostic) -> impl Iterator<Item = crate::diagnostic::Diagnostic> + '_ {
+	get_diagnostics().into_iter().filter(|original| {
+		// The client's copy of the message may have the hints appended to it
+		diagnostic.message.starts_with(&original.message)
+			&& original.span.as_ref().is_some_and(|span| {
+				let original_range: Range = span.into();
+				original_range == diagnostic.range
+			})
+	})
+}
+
+/// Finds the name within a diagnostic's range that a suggested name should replace: the range itself if it's just
+/// the name, otherwise the closest name to the suggestion in it (e.g. the misspelled field of a struct literal)
+fn find_misspelled_name(file: &Url, range: &Range, suggestion: &str) -> Option<Range> {
+	PROJECT_DATA.with(|project_data| {
+		let project_data = project_data.borrow();
+		let file = check_utf8(file.to_file_path().ok()?);
+		let source = project_data.files.get_file(&file)?;
+		let start: Point = WingLocation::from(range.start).into();
+		let end: Point = WingLocation::from(range.end).into();
+		let root = project_data.trees.get(&file)?.root_node();
+		let node = root.descendant_for_point_range(start, end)?;
+
+		let mut names = vec![];
+		let mut cursor = node.walk();
+		collect_names(&mut cursor, &mut names);
+		names
+			.into_iter()
+			.filter(|name| name.start_position() >= start && name.end_position() <= end)
+			.filter_map(|name| Some((name.utf8_text(source.as_bytes()).ok()?, name)))
+			.filter(|(text, _)| *text != suggestion)
+			.min_by_key(|(text, _)| strsim::damerau_levenshtein(&text.to_lowercase(), &suggestion.to_lowercase()))
+			.map(|(_, name)| Range {
+				start: WingLocation::from(name.start_position()).into(),
+				end: WingLocation::from(name.end_position()).into(),
+			})
+	})
+}
+
+/// Collects the identifiers under the cursor's node (including itself)
+fn collect_names<'a>(cursor: &mut TreeCursor<'a>, names: &mut Vec<Node<'a>>) {
+	let node = cursor.node();
+	if node.child_count() == 0 && node.kind().ends_with("identifier") {
+		names.push(node);
+	}
+	if cursor.goto_first_child() {
+		loop {
+			collect_names(cursor, names);
+			if !cursor.goto_next_sibling() {
+				break;
+			}
+		}
+		cursor.goto_parent();
+	}
+}
+
+/// Finds the range of the `var` keyword (and the whitespace following it) in the declaration of the variable
+/// or field whose name is at the given range
+fn find_reassignable_modifier(file: &Url, name_range: &Range) -> Option<Range> {
+	PROJECT_DATA.with(|project_data| {
+		let project_data = project_data.borrow();
+		let file = check_utf8(file.to_file_path().ok()?);
+		let start: Point = WingLocation::from(name_range.start).into();
+		let end: Point = WingLocation::from(name_range.end).into();
+		let name = project_data
+			.trees
+			.get(&file)?
+			.root_node()
+			.named_descendant_for_point_range(start, end)?;
+		let declaration = name.parent()?;
+		let reassignable = match declaration.kind() {
+			"variable_definition_statement" => declaration.child_by_field_name("reassignable")?,
+			"class_field" => {
+				let modifiers = declaration.child_by_field_name("modifiers")?;
+				let mut cursor = modifiers.walk();
+				let reassignable = modifiers
+					.children(&mut cursor)
+					.find(|modifier| modifier.kind() == "reassignable")?;
+				reassignable
+			}
+			_ => return None,
+		};
+		// The `var` keyword is followed by either another modifier or the name
+		let next_start = reassignable.next_sibling().unwrap_or(name).start_position();
+		Some(Range {
+			start: WingLocation::from(reassignable.start_position()).into(),
+			end: WingLocation::from(next_start).into(),
+		})
+	})
+}
+
+/// The edit replacing the access modifier of the declaration whose name is at the given range with the given keyword,
+/// or adding it to the declaration if it doesn't have one (i.e. it's private)
+fn access_modifier_edit(file: &Url, name_range: &Range, keyword: &str) -> Option<TextEdit> {
+	PROJECT_DATA.with(|project_data| {
+		let project_data = project_data.borrow();
+		let file = check_utf8(file.to_file_path().ok()?);
+		let start: Point = WingLocation::from(name_range.start).into();
+		let end: Point = WingLocation::from(name_range.end).into();
+		let name = project_data
+			.trees
+			.get(&file)?
+			.root_node()
+			.named_descendant_for_point_range(start, end)?;
+		let declaration = name.parent()?;
+		if !matches!(
+			declaration.kind(),
+			"variable_definition_statement"
+				| "function_definition"
+				| "class_definition"
+				| "interface_definition"
+				| "struct_definition"
+				| "enum_definition"
+				| "class_field"
+				| "method_definition"
+		) {
+			return None;
+		}
+		let access_modifier = declaration.child_by_field_name("access_modifier").or_else(|| {
+			let modifiers = declaration.child_by_field_name("modifiers")?;
+			let mut cursor = modifiers.walk();
+			let access_modifier = modifiers
+				.children(&mut cursor)
+				.find(|modifier| modifier.kind() == "access_modifier");
+			access_modifier
+		});
+		Some(match access_modifier {
+			Some(access_modifier) => TextEdit {
+				range: Range {
+					start: WingLocation::from(access_modifier.start_position()).into(),
+					end: WingLocation::from(access_modifier.end_position()).into(),
+				},
+				new_text: keyword.to_string(),
+			},
+			None => {
+				let start = WingLocation::from(declaration.start_position()).into();
+				TextEdit {
+					range: Range { start, end: start },
+					new_text: format!("{keyword} "),
+				}
+			}
+		})
+	})
+}
+
+/// The edit adding the given field initializers to the struct literal at the given range. In a literal spanning several
+/// lines each field goes on its own line after the last one, otherwise they're all added after the last field.
+fn missing_fields_edit(file: &Url, literal_range: &Range, initializers: &[String]) -> Option<TextEdit> {
+	PROJECT_DATA.with(|project_data| {
+		let project_data = project_data.borrow();
+		let file = check_utf8(file.to_file_path().ok()?);
+		let source = project_data.files.get_file(&file)?;
+		let start: Point = WingLocation::from(literal_range.start).into();
+		let end: Point = WingLocation::from(literal_range.end).into();
+		let mut literal = project_data
+			.trees
+			.get(&file)?
+			.root_node()
+			.named_descendant_for_point_range(start, end)?;
+		while literal.kind() != "struct_literal" || literal.start_position() != start || literal.end_position() != end {
+			literal = literal.parent()?;
+		}
+		let closing_brace = literal.child(literal.child_count().checked_sub(1)?)?;
+
+		let mut cursor = literal.walk();
+		let last_field = literal.children_by_field_name("fields", &mut cursor).last();
+		let (position, new_text) = match last_field {
+			Some(last_field) => {
+				let trailing_comma = last_field.next_sibling().filter(|sibling| sibling.kind() == ",");
+				if closing_brace.start_position().row > last_field.end_position().row {
+					let line = source.lines().nth(last_field.start_position().row)?;
+					let indent = &line[..line.len() - line.trim_start().len()];
+					let mut fields = initializers.iter().map(|i| format!("\n{indent}{i}"));
+					match trailing_comma {
+						Some(comma) => (comma.end_position(), fields.map(|f| format!("{f},")).join("")),
+						None => (last_field.end_position(), format!(",{}", fields.join(","))),
+					}
+				} else {
+					(
+						last_field.end_position(),
+						initializers.iter().map(|i| format!(", {i}")).join(""),
+					)
+				}
+			}
+			None => {
+				let opening_brace = closing_brace.prev_sibling()?;
+				let separator = if opening_brace.end_position() == closing_brace.start_position() {
+					" "
+				} else {
+					""
+				};
+				(
+					opening_brace.end_position(),
+					format!(" {}{separator}", initializers.join(", ")),
+				)
+			}
+		};
+		let position = WingLocation::from(position).into();
+		Some(TextEdit {
+			range: Range {
+				start: position,
+				end: position,
+			},
+			new_text,
+		})
+	})
+}
+
 #[cfg(test)]
 mod tests {
 	use crate::lsp::code_actions::*;
@@ -225,4 +607,78 @@ mod tests {
 			panic!("Expected CodeAction");
 		}
 	);
+
+	/// Returns the edits of the "Remove \"var\"" quick fixes for the given program
+	fn remove_var_edits(code: &str) -> Vec<TextEdit> {
+		// NOTE: this is needed for debugging to work regardless of where you run the test
+		std::env::set_current_dir(env!("CARGO_MANIFEST_DIR")).unwrap();
+
+		let text_document_position = load_file_with_contents(code);
+		let actions = on_code_action(CodeActionParams {
+			text_document: text_document_position.text_document.clone(),
+			range: Default::default(),
+			context: Default::default(),
+			work_done_progress_params: Default::default(),
+			partial_result_params: Default::default(),
+		});
+
+		actions
+			.into_iter()
+			.filter_map(|action| match action {
+				CodeActionOrCommand::CodeAction(action) if action.title == "Remove \"var\"" => {
+					assert!(action.kind == Some(CodeActionKind::QUICKFIX));
+					Some(action.edit?.changes?.into_values().flatten().collect_vec())
+				}
+				_ => None,
+			})
+			.flatten()
+			.collect()
+	}
+
+	fn range(line: u32, start: u32, end: u32) -> Range {
+		Range {
+			start: lsp_types::Position { line, character: start },
+			end: lsp_types::Position { line, character: end },
+		}
+	}
+
+	#[test]
+	fn remove_var_from_never_reassigned_variable() {
+		let edits = remove_var_edits(
+			r#"let var x = 1;
+log("{x}");
+"#,
+		);
+
+		assert_eq!(edits.len(), 1);
+		assert_eq!(edits[0].range, range(0, 4, 8));
+		assert_eq!(edits[0].new_text, "");
+	}
+
+	#[test]
+	fn remove_var_from_never_reassigned_field() {
+		let edits = remove_var_edits(
+			r#"class Counter {
+  var count: num;
+  new() {
+    this.count = 0;
+  }
+}
+"#,
+		);
+
+		assert_eq!(edits.len(), 1);
+		assert_eq!(edits[0].range, range(1, 2, 6));
+	}
+
+	#[test]
+	fn keep_var_of_reassigned_variable() {
+		let edits = remove_var_edits(
+			r#"let var x = 1;
+x = 2;
+"#,
+		);
+
+		assert!(edits.is_empty());
+	}
 }
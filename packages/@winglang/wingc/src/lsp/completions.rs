@@ -3,7 +3,6 @@ use lsp_types::{
 	Command, CompletionItem, CompletionItemKind, CompletionResponse, Documentation, InsertTextFormat, MarkupContent,
 	MarkupKind, Position, Range, TextEdit,
 };
-use std::cmp::max;
 use tree_sitter::{Node, Point};
 
 use crate::ast::{
@@ -12,12 +11,15 @@ use crate::ast::{
 use crate::closure_transform::{CLOSURE_CLASS_PREFIX, PARENT_THIS_NAME};
 use crate::diagnostic::{WingLocation, WingSpan};
 use crate::docs::Documented;
-use crate::lsp::sync::{JSII_TYPES, PROJECT_DATA, WING_TYPES};
+use crate::lsp::auto_imports::get_auto_import_completions;
+use crate::lsp::bring_paths::{bring_path_at_cursor, get_bring_path_completions};
+use crate::lsp::sync::{byte_column, check_unchecked_documents, JSII_TYPES, PROJECT_DATA, WING_TYPES};
+use crate::parser::STRUCT_STATICS_CLASS_PREFIX;
 use crate::type_check::jsii_importer::is_construct_base;
 use crate::type_check::symbol_env::{LookupResult, StatementIdx, SymbolEnvKind};
 use crate::type_check::{
-	fully_qualify_std_type, import_udt_from_jsii, resolve_super_method, ClassLike, Namespace, Struct, SymbolKind, Type,
-	TypeRef, Types, UnsafeRef, VariableKind, CLASS_INFLIGHT_INIT_NAME, CLASS_INIT_NAME,
+	fully_qualify_std_type, import_udt_from_jsii, resolve_super_method, ClassLike, FunctionSignature, Namespace, Struct,
+	SymbolKind, Type, TypeRef, Types, UnsafeRef, VariableKind, CLASS_INFLIGHT_INIT_NAME, CLASS_INIT_NAME,
 };
 use crate::visit::{visit_expr, visit_type_annotation, Visit};
 use crate::wasm_util::extern_json_fn;
@@ -30,6 +32,7 @@ const BUILTIN_GENERICS: [&str; 6] = ["Array", "Map", "MutArray", "MutMap", "MutS
 
 #[no_mangle]
 pub unsafe extern "C" fn wingc_on_completion(ptr: u32, len: u32) -> u64 {
+	check_unchecked_documents();
 	extern_json_fn(ptr, len, on_completion)
 }
 
@@ -113,10 +116,15 @@ pub fn on_completion(params: lsp_types::CompletionParams) -> CompletionResponse
 			let root_scope = project_data.asts.get(&file).expect("ast not found");
 			let source_package = project_data.find_source_package(&file);
 
-			let true_point = Point::new(
-				params.text_document_position.position.line as usize,
-				params.text_document_position.position.character as usize,
-			);
+			let contents = project_data.files.get_file(&file).expect("file not found");
+			let line_text = contents
+				.lines()
+				.nth(params.text_document_position.position.line as usize)
+				.unwrap_or_default();
+			// LSP columns are counted in UTF-16 code units, tree-sitter columns and string slices in bytes
+			let column = byte_column(line_text, params.text_document_position.position.character);
+
+			let true_point = Point::new(params.text_document_position.position.line as usize, column);
 			// This is the exact position the completion is requested for
 			let true_node = root_ts_node
 				.named_descendant_for_point_range(true_point, true_point)
@@ -126,8 +134,6 @@ pub fn on_completion(params: lsp_types::CompletionParams) -> CompletionResponse
 				return vec![];
 			}
 
-			let contents = project_data.files.get_file(&file).expect("file not found");
-
 			// get all character from file_data.contents up to the current position
 			let preceding_text = contents
 				.lines()
@@ -135,7 +141,7 @@ pub fn on_completion(params: lsp_types::CompletionParams) -> CompletionResponse
 				.take_while(|(i, _)| *i <= params.text_document_position.position.line as usize)
 				.map(|(i, s)| {
 					if i == params.text_document_position.position.line as usize {
-						&s[..params.text_document_position.position.character as usize].trim_end()
+						s[..column].trim_end()
 					} else {
 						s
 					}
@@ -143,10 +149,25 @@ pub fn on_completion(params: lsp_types::CompletionParams) -> CompletionResponse
 				.join("\n");
 			let last_char_is_colon = preceding_text.ends_with(':');
 
+			// inside the module path of a `bring` statement, e.g. `bring "./lib/`
+			if let Some(typed_path) = bring_path_at_cursor(&line_text[..column]) {
+				return get_bring_path_completions(
+					&file,
+					typed_path,
+					params.text_document_position.position,
+					&project_data.files,
+					&project_data.file_graph,
+				);
+			}
+
 			let node_to_complete = nearest_interesting_sibling(
 				Point::new(
 					params.text_document_position.position.line as usize,
-					max(params.text_document_position.position.character as i64 - 1, 0) as usize,
+					// the character before the cursor
+					line_text[..column]
+						.char_indices()
+						.next_back()
+						.map_or(0, |(offset, _)| offset),
 				),
 				&root_ts_node,
 			);
@@ -392,6 +413,7 @@ pub fn on_completion(params: lsp_types::CompletionParams) -> CompletionResponse
 							&scope_visitor,
 							&node_to_complete,
 							&preceding_text,
+							get_auto_import_completions(&file, root_scope, &project_data.files),
 						));
 					}
 
@@ -446,7 +468,7 @@ pub fn on_completion(params: lsp_types::CompletionParams) -> CompletionResponse
 				}
 			} else if !last_char_is_colon && matches!(nearest_non_reference.kind(), "argument_list" | "positional_argument") {
 				if let Some(callish_expr) = scope_visitor.expression_trail.iter().rev().find_map(|e| match &e.kind {
-					ExprKind::Call { arg_list, callee } => Some((
+					ExprKind::Call { arg_list, callee, .. } => Some((
 						match callee {
 							CalleeKind::Expr(expr) => types.get_expr_type(expr),
 							CalleeKind::SuperCall(method) => {
@@ -465,9 +487,23 @@ pub fn on_completion(params: lsp_types::CompletionParams) -> CompletionResponse
 						Some("keyword_argument")
 					) {
 						// We haven't run into any keyword args yet, so let's show options for positional args (expressions)
-						completions = get_current_scope_completions(&types, &scope_visitor, &node_to_complete, &preceding_text);
+						completions = get_current_scope_completions(
+							&types,
+							&scope_visitor,
+							&node_to_complete,
+							&preceding_text,
+							get_auto_import_completions(&file, root_scope, &project_data.files),
+						);
 					}
 
+					// Index of the positional argument being completed
+					let arg_index = callish_expr
+						.1
+						.pos_args
+						.iter()
+						.filter(|a| a.span.end_offset <= node_to_complete.start_byte())
+						.count();
+
 					let arg_list_strings = &callish_expr
 						.1
 						.named_args
@@ -475,6 +511,10 @@ pub fn on_completion(params: lsp_types::CompletionParams) -> CompletionResponse
 						.map(|(k, _)| k.name.clone())
 						.collect::<Vec<_>>();
 
+					if let Some(func) = callish_expr.0.maybe_unwrap_option().as_function_sig() {
+						completions.extend(get_literal_type_completions(func, arg_index));
+					}
+
 					// if we're in a function, get the struct expansion
 					if let Some(structy) = callish_expr.0.get_function_struct_arg() {
 						let func = callish_expr.0.maybe_unwrap_option().as_function_sig().unwrap();
@@ -483,7 +523,8 @@ pub fn on_completion(params: lsp_types::CompletionParams) -> CompletionResponse
 							.pos_args
 							.iter()
 							.filter(|a| !types.get_expr_type(a).is_unresolved())
-							.count() == func.parameters.len() - 1
+							.count()
+							== func.parameters.len() - 1
 						{
 							completions.extend(get_inner_struct_completions(structy, arg_list_strings));
 						}
@@ -499,6 +540,7 @@ pub fn on_completion(params: lsp_types::CompletionParams) -> CompletionResponse
 						};
 						if let Some(init_method) = init_method {
 							let func = init_method.type_.maybe_unwrap_option().as_function_sig().unwrap();
+							completions.extend(get_literal_type_completions(func, arg_index));
 							if callish_expr
 								.1
 								.pos_args
@@ -520,7 +562,13 @@ pub fn on_completion(params: lsp_types::CompletionParams) -> CompletionResponse
 			}
 
 			// fallback: no special completions, just get stuff from the current scope
-			get_current_scope_completions(&types, &scope_visitor, &nearest_non_reference, &preceding_text)
+			get_current_scope_completions(
+				&types,
+				&scope_visitor,
+				&nearest_non_reference,
+				&preceding_text,
+				get_auto_import_completions(&file, root_scope, &project_data.files),
+			)
 		});
 
 		final_completions = final_completions
@@ -536,12 +584,13 @@ pub fn on_completion(params: lsp_types::CompletionParams) -> CompletionResponse
 	})
 }
 
-/// Get symbols in the current scope as completion items
+/// Get symbols in the current scope as completion items, along with types that can be brought automatically
 fn get_current_scope_completions(
 	types: &Types,
 	scope_visitor: &ScopeVisitor,
 	node_to_complete: &Node,
 	preceding_text: &str,
+	auto_imports: Vec<CompletionItem>,
 ) -> Vec<CompletionItem> {
 	let mut completions = vec![];
 
@@ -703,6 +752,13 @@ fn get_current_scope_completions(
 		}
 	}
 
+	// types from modules that aren't brought yet, unless a symbol in scope already has the same name
+	for completion in auto_imports {
+		if completions.iter().all(|c| c.label != completion.label) {
+			completions.push(completion);
+		}
+	}
+
 	if !in_type {
 		if node_to_complete.kind() == "source" {
 			// we're at the root scope, let's add some typical completions
@@ -920,6 +976,26 @@ fn get_inner_struct_completions(struct_: &Struct, existing_fields: &Vec<String>)
 	return completions;
 }
 
+/// Create completions for the allowed values of a parameter with a literal type (e.g. `"GET" | "POST"`)
+fn get_literal_type_completions(func: &FunctionSignature, arg_index: usize) -> Vec<CompletionItem> {
+	let Some(param) = func.parameters.get(arg_index) else {
+		return vec![];
+	};
+	let Type::Literal(values) = &**param.typeref.maybe_unwrap_option() else {
+		return vec![];
+	};
+
+	values
+		.iter()
+		.map(|value| CompletionItem {
+			label: value.to_string(),
+			detail: Some(param.typeref.to_string()),
+			kind: Some(CompletionItemKind::VALUE),
+			..Default::default()
+		})
+		.collect()
+}
+
 #[derive(Debug)]
 enum ObjectAccessContext {
 	Outside,
@@ -1133,6 +1209,9 @@ fn format_symbol_kind_as_completion(name: &str, symbol_kind: &SymbolKind) -> Opt
 				| Type::Regex
 				| Type::Boolean
 				| Type::Void
+				| Type::Never
+				| Type::Unknown
+				| Type::Literal(_)
 				| Type::Json(_)
 				| Type::MutJson
 				| Type::Nil
@@ -1190,7 +1269,10 @@ fn format_symbol_kind_as_completion(name: &str, symbol_kind: &SymbolKind) -> Opt
 }
 
 fn should_exclude_symbol(symbol: &str) -> bool {
-	symbol == WINGSDK_STD_MODULE || symbol.starts_with(CLOSURE_CLASS_PREFIX) || symbol.starts_with(PARENT_THIS_NAME)
+	symbol == WINGSDK_STD_MODULE
+		|| symbol.starts_with(CLOSURE_CLASS_PREFIX)
+		|| symbol.starts_with(PARENT_THIS_NAME)
+		|| symbol.starts_with(STRUCT_STATICS_CLASS_PREFIX)
 }
 
 fn is_abstract_class(symbol_kind: &SymbolKind) -> bool {
@@ -1707,6 +1789,22 @@ Foo { x: "hi", }
 "#
 	);
 
+	test_completion_list!(
+		struct_literal_field_docs,
+		r#"
+struct Foo {
+	/// The name of the foo
+	x: str;
+	y: num;
+}
+
+Foo {  }
+    //^
+"#,
+		assert!(struct_literal_field_docs.iter().any(|item| item.label == "x"
+			&& matches!(&item.documentation, Some(Documentation::MarkupContent(doc)) if doc.value.contains("The name of the foo"))))
+	);
+
 	test_completion_list!(
 		struct_literal_empty_nospace,
 		r#"
@@ -2161,4 +2259,29 @@ let x = @dir
          //^
 		"#,
 	);
+
+	#[test]
+	fn completes_after_non_ascii_text() {
+		std::env::set_current_dir(env!("CARGO_MANIFEST_DIR")).unwrap();
+
+		// "é" is one UTF-16 code unit (which is how LSP columns are counted) but two bytes
+		let text_document_position = load_file_with_contents(
+			r#"
+let s = "héllo"; s.
+                 //^
+"#,
+		);
+		let CompletionResponse::Array(completions) = on_completion(CompletionParams {
+			context: None,
+			text_document_position,
+			work_done_progress_params: Default::default(),
+			partial_result_params: Default::default(),
+		}) else {
+			panic!("Expected array of completions");
+		};
+
+		assert!(completions.iter().any(|c| c.label == "length"));
+		assert!(completions.iter().any(|c| c.label == "contains"));
+		assert!(!completions.iter().any(|c| c.label == "s"));
+	}
 }
@@ -1,22 +1,46 @@
 use crate::ast::*;
 use crate::closure_transform::{CLOSURE_CLASS_PREFIX, PARENT_THIS_NAME};
-use crate::lsp::sync::PROJECT_DATA;
+use crate::diagnostic::WingSpan;
+use crate::lsp::sync::{check_unchecked_documents, PROJECT_DATA};
+use crate::test_blocks::test_name;
 use crate::visit::Visit;
 use crate::wasm_util::extern_json_fn;
-use lsp_types::{DocumentSymbol, SymbolKind};
+use lsp_types::{CodeLens, Command, DocumentSymbol, SymbolKind, Url};
 
 use super::sync::check_utf8;
 
+/// Command for running a single test, with the file's URI and the test's name as arguments
+pub const COMMAND_RUN_TEST: &str = "wing.runTest";
+/// Command for showing the lifts of a class, with the file's URI and the class name as arguments
+pub const COMMAND_SHOW_LIFTS: &str = "wing.showLifts";
+
 pub struct DocumentSymbolVisitor {
 	pub document_symbols: Vec<DocumentSymbol>,
+	/// Code lenses for the tests and preflight classes of the document
+	pub code_lenses: Vec<CodeLens>,
+	uri: Url,
 }
 
 impl DocumentSymbolVisitor {
-	pub fn new() -> Self {
+	pub fn new(uri: Url) -> Self {
 		Self {
 			document_symbols: vec![],
+			code_lenses: vec![],
+			uri,
 		}
 	}
+
+	fn add_code_lens(&mut self, span: &WingSpan, title: &str, command: &str, name: &str) {
+		self.code_lenses.push(CodeLens {
+			range: span.into(),
+			command: Some(Command {
+				title: title.to_string(),
+				command: command.to_string(),
+				arguments: Some(vec![self.uri.to_string().into(), name.into()]),
+			}),
+			data: None,
+		});
+	}
 }
 
 impl Visit<'_> for DocumentSymbolVisitor {
@@ -44,6 +68,7 @@ impl Visit<'_> for DocumentSymbolVisitor {
 						// so we don't need to add a symbol for the module itself
 						BringSource::WingLibrary(_, _) => {}
 						BringSource::JsiiModule(_) => {}
+						BringSource::NpmModule(_) => {}
 						BringSource::WingFile(_) => {}
 						BringSource::Directory(_) => {}
 					};
@@ -55,17 +80,35 @@ impl Visit<'_> for DocumentSymbolVisitor {
 					.document_symbols
 					.push(create_document_symbol(symbol, SymbolKind::VARIABLE));
 			}
-			StmtKind::ForLoop { iterator, .. } => {
+			StmtKind::ForLoop {
+				iterator,
+				second_iterator,
+				..
+			} => {
 				let symbol = iterator;
 				self
 					.document_symbols
 					.push(create_document_symbol(symbol, SymbolKind::VARIABLE));
+				if let Some(symbol) = second_iterator {
+					self
+						.document_symbols
+						.push(create_document_symbol(symbol, SymbolKind::VARIABLE));
+				}
 			}
 			StmtKind::Class(c) => {
 				let symbol = &c.name;
 				self
 					.document_symbols
 					.push(create_document_symbol(symbol, SymbolKind::CLASS));
+
+				if c.phase == Phase::Preflight && !symbol.name.starts_with(CLOSURE_CLASS_PREFIX) {
+					self.add_code_lens(&symbol.span, "Show lifts", COMMAND_SHOW_LIFTS, &symbol.name);
+				}
+			}
+			StmtKind::Expression(expr) => {
+				if let Some(test_name) = test_name(expr) {
+					self.add_code_lens(&statement.span, "Run test", COMMAND_RUN_TEST, &test_name);
+				}
 			}
 			StmtKind::Struct(st) => {
 				let symbol = &st.name;
@@ -87,6 +130,7 @@ impl Visit<'_> for DocumentSymbolVisitor {
 
 #[no_mangle]
 pub unsafe extern "C" fn wingc_on_document_symbol(ptr: u32, len: u32) -> u64 {
+	check_unchecked_documents();
 	extern_json_fn(ptr, len, on_document_symbols)
 }
 
@@ -97,7 +141,7 @@ pub fn on_document_symbols(params: lsp_types::DocumentSymbolParams) -> Vec<Docum
 		let file = check_utf8(uri.to_file_path().expect("LSP only works on real filesystems"));
 		let scope = project_data.asts.get(&file).unwrap();
 
-		let mut visitor = DocumentSymbolVisitor::new();
+		let mut visitor = DocumentSymbolVisitor::new(uri);
 		visitor.visit_scope(scope);
 
 		visitor
@@ -108,6 +152,29 @@ pub fn on_document_symbols(params: lsp_types::DocumentSymbolParams) -> Vec<Docum
 	})
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn wingc_on_code_lens(ptr: u32, len: u32) -> u64 {
+	check_unchecked_documents();
+	extern_json_fn(ptr, len, on_code_lens)
+}
+
+/// Code lenses for running the tests of a document and inspecting its preflight classes.
+/// They are computed in the same pass as the document's symbols.
+pub fn on_code_lens(params: lsp_types::CodeLensParams) -> Vec<CodeLens> {
+	PROJECT_DATA.with(|project_data| {
+		let project_data = project_data.borrow();
+		let uri = params.text_document.uri;
+		let file = check_utf8(uri.to_file_path().expect("LSP only works on real filesystems"));
+		let Some(scope) = project_data.asts.get(&file) else {
+			return vec![];
+		};
+
+		let mut visitor = DocumentSymbolVisitor::new(uri);
+		visitor.visit_scope(scope);
+		visitor.code_lenses
+	})
+}
+
 fn filter_symbol(symbol: &DocumentSymbol) -> bool {
 	!{ symbol.name.starts_with(CLOSURE_CLASS_PREFIX) || symbol.name.starts_with(PARENT_THIS_NAME) }
 }
@@ -1,5 +1,5 @@
 use crate::lsp::symbol_locator::SymbolLocator;
-use crate::lsp::sync::PROJECT_DATA;
+use crate::lsp::sync::{check_unchecked_documents, PROJECT_DATA};
 use crate::type_check::symbol_env::LookupResult;
 use crate::visit::Visit;
 use crate::wasm_util::extern_json_fn;
@@ -10,6 +10,7 @@ use super::sync::{check_utf8, WING_TYPES};
 
 #[no_mangle]
 pub unsafe extern "C" fn wingc_on_goto_definition(ptr: u32, len: u32) -> u64 {
+	check_unchecked_documents();
 	extern_json_fn(ptr, len, on_goto_definition)
 }
 
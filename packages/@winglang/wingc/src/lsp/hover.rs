@@ -1,8 +1,14 @@
+use crate::ast::{Class, Expr, ExprId, Scope};
+use crate::closure_transform::{closure_display_name, CLOSURE_CLASS_PREFIX};
+use crate::diagnostic::WingLocation;
 use crate::docs::Documented;
-use crate::lsp::sync::PROJECT_DATA;
+use crate::lsp::sync::{check_unchecked_documents, PROJECT_DATA};
+use crate::type_check::lifts::Liftable;
 use crate::type_check::symbol_env::LookupResult;
-use crate::visit::Visit;
+use crate::type_check::{SymbolKind, TypeRef, Types};
+use crate::visit::{self, Visit};
 use crate::wasm_util::extern_json_fn;
+use itertools::Itertools;
 use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind};
 
 use super::symbol_locator::{SymbolLocator, SymbolLocatorResult};
@@ -10,6 +16,7 @@ use super::sync::{check_utf8, WING_TYPES};
 
 #[no_mangle]
 pub unsafe extern "C" fn wingc_on_hover(ptr: u32, len: u32) -> u64 {
+	check_unchecked_documents();
 	extern_json_fn(ptr, len, on_hover)
 }
 pub fn on_hover(params: lsp_types::HoverParams) -> Option<Hover> {
@@ -26,8 +33,16 @@ pub fn on_hover(params: lsp_types::HoverParams) -> Option<Hover> {
 
 			if let Some(lookup) = symbol_finder.lookup_located_symbol() {
 				if let LookupResult::Found(symbol_info, ..) = &lookup {
-					let docs = symbol_info.render_docs();
+					let mut docs = symbol_info.render_docs();
 					let span = symbol_finder.located_span()?;
+
+					// Preflight objects used inflight also show what the inflight code does with them
+					let mut lift_locator = LiftLocator::new(&types, params.text_document_position_params.position.into());
+					lift_locator.visit_scope(root_scope);
+					if let Some(lift_docs) = lift_locator.render_lift() {
+						docs = format!("{docs}\n---\n{lift_docs}");
+					}
+
 					return Some(Hover {
 						contents: HoverContents::Markup(MarkupContent {
 							kind: MarkupKind::Markdown,
@@ -59,6 +74,104 @@ pub fn on_hover(params: lsp_types::HoverParams) -> Option<Hover> {
 	})
 }
 
+/// Finds the preflight object lifted into inflight code at a given location, along with the inflight method it's
+/// lifted into (based on the lifts computed by `LiftVisitor`).
+struct LiftLocator<'a> {
+	types: &'a Types,
+	location: WingLocation,
+	scopes: Vec<&'a Scope>,
+	/// The innermost class with lifts that contains the location, and the name of its method that contains it
+	class: Option<(TypeRef, String)>,
+	/// The expressions that contain the location, from the outermost to the innermost
+	exprs: Vec<ExprId>,
+}
+
+impl<'a> LiftLocator<'a> {
+	fn new(types: &'a Types, location: WingLocation) -> Self {
+		Self {
+			types,
+			location,
+			scopes: vec![],
+			class: None,
+			exprs: vec![],
+		}
+	}
+
+	/// Renders the operations the located preflight object is lifted with as markdown
+	fn render_lift(&self) -> Option<String> {
+		let (class_type, method) = self.class.as_ref()?;
+		let class = class_type.as_class()?;
+		let lifts = class.lifts.as_ref()?;
+		let code = self.exprs.iter().rev().find_map(|expr_id| {
+			let token = lifts.token_for_liftable.get(&Liftable::Expr(*expr_id))?;
+			lifts.captures.get(token).map(|capture| &capture.code)
+		})?;
+		let qualification = lifts.lifts_qualifications.get(method)?.get(code)?;
+
+		let ops = qualification.ops.iter().map(|op| format!("`{op}`")).collect_vec();
+
+		let lifted_into = if class.name.name.starts_with(CLOSURE_CLASS_PREFIX) {
+			match closure_display_name(&class.name.name) {
+				Some(display_name) => format!("the inflight closure `{display_name}`"),
+				None => "an inflight closure".to_string(),
+			}
+		} else {
+			format!("`{}.{method}`", class.name)
+		};
+		Some(if ops.is_empty() {
+			format!("Preflight object lifted into {lifted_into}")
+		} else {
+			format!(
+				"Preflight object lifted into {lifted_into}, which uses: {}",
+				ops.join(", ")
+			)
+		})
+	}
+}
+
+impl<'a> Visit<'a> for LiftLocator<'a> {
+	fn visit_scope(&mut self, node: &'a Scope) {
+		self.scopes.push(node);
+		visit::visit_scope(self, node);
+		self.scopes.pop();
+	}
+
+	fn visit_class(&mut self, node: &'a Class) {
+		if !node.span.contains_location(&self.location) {
+			return;
+		}
+
+		let method = node
+			.all_methods(true)
+			.into_iter()
+			.find(|method| method.span.contains_location(&self.location))
+			.and_then(|method| method.name.as_ref());
+		let class_type =
+			self
+				.scopes
+				.last()
+				.and_then(|scope| match self.types.get_scope_env(scope).lookup(&node.name, None) {
+					Some(SymbolKind::Type(t)) => Some(*t),
+					_ => None,
+				});
+		// classes defined inflight don't have lifts of their own, they are lifted into their outer class
+		if let (Some(method), Some(class_type)) = (method, class_type) {
+			if class_type.as_class().map_or(false, |class| class.lifts.is_some()) {
+				self.class = Some((class_type, method.name.clone()));
+			}
+		}
+
+		visit::visit_class(self, node);
+	}
+
+	fn visit_expr(&mut self, node: &'a Expr) {
+		if node.span.contains_location(&self.location) {
+			self.exprs.push(node.id);
+		}
+		visit::visit_expr(self, node);
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use crate::lsp::hover::*;
@@ -647,4 +760,33 @@ Json.stringify({});
   //^
   "#,
 	);
+
+	#[test]
+	fn preflight_object_used_inflight_shows_lift_ops() {
+		std::env::set_current_dir(env!("CARGO_MANIFEST_DIR")).unwrap();
+
+		let text_document_position_params = load_file_with_contents(
+			r#"
+bring cloud;
+let bucket = new cloud.Bucket();
+inflight () => {
+  bucket.put("a", "b");
+//^
+  bucket.get("a");
+};
+"#,
+		);
+		let hover = on_hover(HoverParams {
+			text_document_position_params,
+			work_done_progress_params: Default::default(),
+		})
+		.expect("Expected hover data");
+
+		let HoverContents::Markup(contents) = hover.contents else {
+			panic!("Expected markdown hover contents");
+		};
+		assert!(contents
+			.value
+			.ends_with("Preflight object lifted into an inflight closure, which uses: `put`, `get`"));
+	}
 }
@@ -1,3 +1,5 @@
+mod auto_imports;
+mod bring_paths;
 mod code_actions;
 mod completions;
 mod document_symbols;
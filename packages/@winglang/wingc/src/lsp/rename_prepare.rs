@@ -1,4 +1,4 @@
-use crate::lsp::sync::PROJECT_DATA;
+use crate::lsp::sync::{check_unchecked_documents, PROJECT_DATA};
 use crate::visit::Visit;
 use crate::wasm_util::extern_json_fn;
 use lsp_types::{PrepareRenameResponse, TextDocumentPositionParams};
@@ -8,6 +8,7 @@ use super::sync::{check_utf8, WING_TYPES};
 
 #[no_mangle]
 pub unsafe extern "C" fn wingc_on_prepare_rename(ptr: u32, len: u32) -> u64 {
+	check_unchecked_documents();
 	extern_json_fn(ptr, len, on_prepare_rename)
 }
 
@@ -177,7 +177,7 @@ impl<'a> Visit<'a> for RenameVisitor<'a> {
 					}
 				}
 			}
-			ExprKind::Call { arg_list, callee } => {
+			ExprKind::Call { arg_list, callee, .. } => {
 				let Some(env) = self.ctx.current_env() else {
 					// usually the env will exist, if not- we cannot use it to resolve the super method.
 					// it's the same as used here: https://github.com/winglang/wing/blob/main/packages/@winglang/wingc/src/lsp/symbol_locator.rs#L374-L376
@@ -225,7 +225,23 @@ impl<'a> Visit<'a> for RenameVisitor<'a> {
 
 	fn visit_stmt(&mut self, stmt: &'a Stmt) {
 		match &stmt.kind {
-			StmtKind::IfLet(IfLet { var_name, .. }) => self.linked_symbols.push(LinkedSymbol {
+			StmtKind::IfLet(IfLet {
+				var_name, conditions, ..
+			}) => {
+				self.linked_symbols.push(LinkedSymbol {
+					symbol: var_name.clone(),
+					references: vec![],
+				});
+				for condition in conditions {
+					if let IfLetCondition::Let { var_name, .. } = condition {
+						self.linked_symbols.push(LinkedSymbol {
+							symbol: var_name.clone(),
+							references: vec![],
+						});
+					}
+				}
+			}
+			StmtKind::WhileLet { var_name, .. } => self.linked_symbols.push(LinkedSymbol {
 				symbol: var_name.clone(),
 				references: vec![],
 			}),
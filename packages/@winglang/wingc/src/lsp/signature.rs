@@ -7,8 +7,8 @@ use lsp_types::{
 use crate::ast::{CalleeKind, Class, Expr, ExprKind, New, Stmt, StmtKind, Symbol};
 use crate::docs::{render_summary, Documented};
 use crate::jsify::codemaker::CodeMaker;
-use crate::lsp::sync::PROJECT_DATA;
 use crate::lsp::sync::WING_TYPES;
+use crate::lsp::sync::{check_unchecked_documents, PROJECT_DATA};
 
 use crate::type_check::symbol_env::SymbolEnvRef;
 use crate::type_check::{
@@ -21,6 +21,7 @@ use super::sync::check_utf8;
 
 #[no_mangle]
 pub unsafe extern "C" fn wingc_on_signature_help(ptr: u32, len: u32) -> u64 {
+	check_unchecked_documents();
 	extern_json_fn(ptr, len, on_signature_help)
 }
 
@@ -82,7 +83,7 @@ pub fn on_signature_help(params: lsp_types::SignatureHelpParams) -> Option<Signa
 
 						(init_lookup?.as_variable()?.type_, arg_list)
 					}
-					ExprKind::Call { callee, arg_list } => {
+					ExprKind::Call { callee, arg_list, .. } => {
 						let t = match callee {
 							CalleeKind::Expr(expr) => types.get_expr_type(expr),
 							CalleeKind::SuperCall(method) => resolve_super_method(method, &env, &types)
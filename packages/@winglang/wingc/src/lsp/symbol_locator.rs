@@ -122,6 +122,8 @@ impl<'a> SymbolLocator<'a> {
 			Type::Optional(_)
 			| Type::Anything
 			| Type::Void
+			| Type::Never
+			| Type::Unknown
 			| Type::Nil
 			| Type::Unresolved
 			| Type::Inferred(_)
@@ -142,7 +144,8 @@ impl<'a> SymbolLocator<'a> {
 			| Type::Duration
 			| Type::Datetime
 			| Type::Regex
-			| Type::Boolean => {
+			| Type::Boolean
+			| Type::Literal(_) => {
 				if let Some((std_type, ..)) = self.types.get_std_class(&type_) {
 					if let Some(t) = std_type.as_type_ref() {
 						if let Some(c) = t.as_class() {
@@ -258,10 +261,23 @@ impl<'a> Visit<'a> for SymbolLocator<'a> {
 				self.ctx.pop_env();
 			}
 			StmtKind::ForLoop {
-				iterator, statements, ..
+				iterator,
+				second_iterator,
+				statements,
+				..
 			} => {
 				self.push_scope_env(&statements);
 				self.visit_symbol(iterator);
+				if let Some(second_iterator) = second_iterator {
+					self.visit_symbol(second_iterator);
+				}
+				self.ctx.pop_env();
+			}
+			StmtKind::WhileLet {
+				var_name, statements, ..
+			} => {
+				self.push_scope_env(&statements);
+				self.visit_symbol(var_name);
 				self.ctx.pop_env();
 			}
 			StmtKind::TryCatch { catch_block, .. } => {
@@ -275,12 +291,18 @@ impl<'a> Visit<'a> for SymbolLocator<'a> {
 			}
 			StmtKind::IfLet(IfLet {
 				var_name,
+				conditions,
 				statements,
 				else_if_statements,
 				..
 			}) => {
 				self.push_scope_env(&statements);
 				self.visit_symbol(var_name);
+				for condition in conditions {
+					if let IfLetCondition::Let { var_name, .. } = condition {
+						self.visit_symbol(var_name);
+					}
+				}
 				self.ctx.pop_env();
 
 				for else_if in else_if_statements {
@@ -365,7 +387,7 @@ impl<'a> Visit<'a> for SymbolLocator<'a> {
 					});
 				}
 			}
-			ExprKind::Call { arg_list, callee } => {
+			ExprKind::Call { arg_list, callee, .. } => {
 				let x = arg_list
 					.named_args
 					.iter()
@@ -516,6 +538,7 @@ impl<'a> Visit<'a> for SymbolLocator<'a> {
 			Reference::ElementAccess { object, .. } => {
 				self.visit_expr(object);
 			}
+			Reference::SuperMember { .. } => {}
 		}
 
 		visit_reference(self, node);
@@ -1,27 +1,41 @@
 use camino::{Utf8Path, Utf8PathBuf};
-use indexmap::IndexMap;
-use lsp_types::{DidChangeTextDocumentParams, DidOpenTextDocumentParams};
+use indexmap::{IndexMap, IndexSet};
+use lsp_types::{
+	DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams, Position, Range,
+	TextDocumentContentChangeEvent, TextDocumentIdentifier,
+};
+use serde::{Deserialize, Serialize};
 use wingii::type_system::TypeSystem;
 
 use std::cell::RefCell;
 use std::path::{Path, PathBuf};
-use tree_sitter::Tree;
+use tree_sitter::{InputEdit, Point, Tree};
 
+use crate::captured_var_visitor::CapturedVarVisitor;
 use crate::closure_transform::ClosureTransformer;
-use crate::diagnostic::{found_errors, reset_diagnostics};
+use crate::comp_ctx::catch_file_panic;
+use crate::diagnostic::{found_errors, report_suppressed_diagnostics, reset_diagnostics};
+use crate::events::{emit_event, start_phase, CompilationPhase, CompilerEvent};
 use crate::file_graph::{File, FileGraph};
 use crate::files::Files;
 use crate::fold::Fold;
 use crate::jsify::JSifier;
 use crate::lifting::LiftVisitor;
-use crate::parser::{normalize_path, parse_wing_project};
+use crate::never_reassigned_visitor::NeverReassignedVisitor;
+use crate::parser::{dir_contains_wing_file_recursive, parse_wing_file_syntax, parse_wing_project, SourceOverlays};
+use crate::paths::normalize_path;
 use crate::type_check::jsii_importer::JsiiImportSpec;
 use crate::type_check::type_reference_transform::TypeReferenceTransformer;
 use crate::type_check_assert::TypeCheckAssert;
+use crate::unreachable_code_visitor::UnreachableCodeVisitor;
 use crate::valid_json_visitor::ValidJsonVisitor;
 use crate::visit::Visit;
 use crate::wasm_util::extern_json_fn;
-use crate::{ast::Scope, type_check::Types};
+use crate::workspace::Workspace;
+use crate::{
+	ast::{AstContext, Scope},
+	type_check::Types,
+};
 use crate::{find_nearest_wing_project_dir, type_check_file, DEFAULT_PACKAGE_NAME};
 
 /// The output of compiling a Wing project with one or more files
@@ -36,9 +50,18 @@ pub struct ProjectData {
 	pub trees: IndexMap<Utf8PathBuf, Tree>,
 	/// AST for each file
 	pub asts: IndexMap<Utf8PathBuf, Scope>,
+	/// Allocates the identifiers of AST nodes. Shared by all compilations so that reused ASTs never collide with
+	/// newly parsed ones.
+	pub ast_ctx: AstContext,
 	/// The JSII imports for the file. This is saved so we can load JSII types (for autocompletion for example)
 	/// which don't exist explicitly in the source.
 	pub jsii_imports: Vec<JsiiImportSpec>,
+	/// Documents whose changes were only checked for syntax errors, so their ASTs and types are out of date until
+	/// they're fully compiled (see `check_unchecked_documents`)
+	pub unchecked_documents: IndexSet<Utf8PathBuf>,
+	/// Documents that are open in the editor. Their latest text (in `files`) may not be saved, so it's compiled instead
+	/// of the one on disk. Like the files of any overlay, they still have to exist on disk to be brought.
+	pub open_documents: IndexSet<Utf8PathBuf>,
 }
 
 impl ProjectData {
@@ -49,7 +72,10 @@ impl ProjectData {
 			library_roots: IndexMap::new(),
 			trees: IndexMap::new(),
 			asts: IndexMap::new(),
+			ast_ctx: AstContext::new(),
 			jsii_imports: Vec::new(),
+			unchecked_documents: IndexSet::new(),
+			open_documents: IndexSet::new(),
 		}
 	}
 
@@ -85,12 +111,17 @@ pub fn on_document_did_open(params: DidOpenTextDocumentParams) {
 				let uri_path = uri.to_file_path().unwrap();
 				let source_text = params.text_document.text;
 
+				// The text of the opened document might not match the last time it was parsed, so its old tree can't be reused
+				let mut project_data = project_data.borrow_mut();
+				project_data.trees.swap_remove(&document_path(&uri_path));
+				project_data.open_documents.insert(document_path(&uri_path));
+
 				partial_compile(
 					&uri_path,
 					source_text,
 					&mut wing_types.borrow_mut(),
 					&mut jsii_types.borrow_mut(),
-					&mut project_data.borrow_mut(),
+					&mut project_data,
 				);
 			});
 		});
@@ -98,11 +129,49 @@ pub fn on_document_did_open(params: DidOpenTextDocumentParams) {
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn wingc_on_did_change_text_document(ptr: u32, len: u32) {
-	extern_json_fn(ptr, len, on_document_did_change);
+pub unsafe extern "C" fn wingc_on_did_close_text_document(ptr: u32, len: u32) {
+	extern_json_fn(ptr, len, on_document_did_close);
+}
+
+/// Compiles the document from disk again, since the files that bring it would otherwise keep seeing its discarded
+/// changes (files are only parsed again when they're compiled themselves)
+pub fn on_document_did_close(params: DidCloseTextDocumentParams) {
+	let uri_path = params.text_document.uri.to_file_path().unwrap();
+	let was_compiled = PROJECT_DATA.with(|project_data| {
+		let mut project_data = project_data.borrow_mut();
+		let path = document_path(&uri_path);
+		project_data.open_documents.swap_remove(&path);
+		project_data.unchecked_documents.swap_remove(&path);
+		if !project_data.files.contains_file(&path) {
+			return false;
+		}
+		let Ok(saved_text) = std::fs::read_to_string(&path) else {
+			return false;
+		};
+		project_data.trees.swap_remove(&path);
+		project_data.files.update_file(&path, saved_text);
+		true
+	});
+	if was_compiled {
+		check_document(&uri_path);
+	}
+}
+
+/// The result of applying the changes of a document
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentChangeResult {
+	/// The changes edit a range of a document whose text isn't known, so they couldn't be applied. The host must open
+	/// the document again with its full text.
+	pub needs_full_text: bool,
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wingc_on_did_change_text_document(ptr: u32, len: u32) -> u64 {
+	extern_json_fn(ptr, len, on_document_did_change)
 }
 
-pub fn on_document_did_change(params: DidChangeTextDocumentParams) {
+pub fn on_document_did_change(params: DidChangeTextDocumentParams) -> DocumentChangeResult {
 	let DidChangeTextDocumentParams {
 		text_document,
 		content_changes,
@@ -113,18 +182,192 @@ pub fn on_document_did_change(params: DidChangeTextDocumentParams) {
 			PROJECT_DATA.with(|project_data| {
 				let uri = text_document.uri;
 				let uri_path = uri.to_file_path().unwrap();
-				let source_text = content_changes.into_iter().next().unwrap().text;
+				let path = document_path(&uri_path);
+				let mut project_data = project_data.borrow_mut();
+
+				let Some(source_text) = apply_content_changes(&mut project_data, &path, content_changes) else {
+					return DocumentChangeResult { needs_full_text: true };
+				};
 
 				partial_compile(
 					&uri_path,
 					source_text,
 					&mut wing_types.borrow_mut(),
 					&mut jsii_types.borrow_mut(),
-					&mut project_data.borrow_mut(),
+					&mut project_data,
 				);
-			});
+				DocumentChangeResult { needs_full_text: false }
+			})
 		})
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wingc_on_did_change_text_document_syntax(ptr: u32, len: u32) -> u64 {
+	extern_json_fn(ptr, len, on_document_did_change_syntax)
+}
+
+/// Applies the changes to the document and only reports the errors found without type checking: the syntax errors
+/// of tree-sitter and the errors the parser reports while building the AST (see `parse_wing_file_syntax`). The
+/// previous ASTs and types are left in place, so the document is marked as unchecked, and it's fully compiled before
+/// any request is answered (see `check_unchecked_documents`), unless `on_document_check` does it first.
+pub fn on_document_did_change_syntax(params: DidChangeTextDocumentParams) -> DocumentChangeResult {
+	let DidChangeTextDocumentParams {
+		text_document,
+		content_changes,
+	} = params;
+
+	PROJECT_DATA.with(|project_data| {
+		let uri_path = text_document.uri.to_file_path().unwrap();
+		let path = document_path(&uri_path);
+		let mut project_data = project_data.borrow_mut();
+
+		reset_diagnostics();
+
+		let Some(source_text) = apply_content_changes(&mut project_data, &path, content_changes) else {
+			return DocumentChangeResult { needs_full_text: true };
+		};
+		let source_file = File::new(path.clone(), project_data.find_source_package(&path));
+		let tree = parse_wing_file_syntax(&source_file, &source_text, project_data.trees.get(&path));
+
+		project_data.trees.insert(path.clone(), tree);
+		project_data.files.update_file(&path, source_text);
+		project_data.unchecked_documents.insert(path);
+		DocumentChangeResult { needs_full_text: false }
+	})
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckTextDocumentParams {
+	pub text_document: TextDocumentIdentifier,
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wingc_on_check_text_document(ptr: u32, len: u32) {
+	extern_json_fn(ptr, len, on_document_check);
+}
+
+/// Fully compiles the latest text of a document, after its changes were applied with `on_document_did_change_syntax`
+pub fn on_document_check(params: CheckTextDocumentParams) {
+	check_document(&params.text_document.uri.to_file_path().unwrap());
+}
+
+/// Fully compiles the documents that were only checked for syntax errors since they last changed, so requests are
+/// never answered from their out of date ASTs and types. Called before answering any request.
+pub fn check_unchecked_documents() {
+	let unchecked = PROJECT_DATA.with(|project_data| {
+		let mut project_data = project_data.borrow_mut();
+		project_data.unchecked_documents.drain(..).collect::<Vec<_>>()
 	});
+	for path in unchecked {
+		check_document(path.as_std_path());
+	}
+}
+
+fn check_document(uri_path: &Path) {
+	WING_TYPES.with(|wing_types| {
+		JSII_TYPES.with(|jsii_types| {
+			PROJECT_DATA.with(|project_data| {
+				let mut project_data = project_data.borrow_mut();
+				let Some(source_text) = project_data.files.get_file(&document_path(uri_path)).cloned() else {
+					return;
+				};
+
+				partial_compile(
+					uri_path,
+					source_text,
+					&mut wing_types.borrow_mut(),
+					&mut jsii_types.borrow_mut(),
+					&mut project_data,
+				);
+			});
+		});
+	});
+}
+
+/// Applies the changes to the stored text of a document and edits its stored tree-sitter tree to match.
+/// Returns the new text, without storing it, or `None` if a change edits a range of a document whose text isn't
+/// known (editing an empty text instead would silently corrupt it).
+fn apply_content_changes(
+	project_data: &mut ProjectData,
+	path: &Utf8Path,
+	content_changes: Vec<TextDocumentContentChangeEvent>,
+) -> Option<String> {
+	let mut source_text = project_data.files.get_file(path).cloned();
+	for change in content_changes {
+		match change.range {
+			Some(range) => {
+				let source_text = source_text.as_mut()?;
+				// Keep the old tree in sync with the text so the file can be reparsed incrementally
+				let edit = edit_text(source_text, range, &change.text);
+				if let Some(tree) = project_data.trees.get_mut(path) {
+					tree.edit(&edit);
+				}
+			}
+			None => {
+				// The whole document was replaced, so the old tree doesn't match the text anymore
+				project_data.trees.swap_remove(path);
+				source_text = Some(change.text);
+			}
+		}
+	}
+	source_text
+}
+
+/// The path a document is stored under in `ProjectData`
+fn document_path(uri_path: &Path) -> Utf8PathBuf {
+	normalize_path(Utf8Path::from_path(uri_path).expect("invalid unicode path"), None)
+}
+
+/// Replaces the given range of the text and returns the matching edit for the text's tree-sitter tree.
+fn edit_text(text: &mut String, range: Range, new_text: &str) -> InputEdit {
+	let start_byte = byte_offset(text, range.start);
+	let old_end_byte = byte_offset(text, range.end).max(start_byte);
+	let start_position = point_at(text, start_byte);
+	let old_end_position = point_at(text, old_end_byte);
+
+	text.replace_range(start_byte..old_end_byte, new_text);
+	let new_end_byte = start_byte + new_text.len();
+
+	InputEdit {
+		start_byte,
+		old_end_byte,
+		new_end_byte,
+		start_position,
+		old_end_position,
+		new_end_position: point_at(text, new_end_byte),
+	}
+}
+
+/// Returns the byte offset of an LSP position, whose columns are counted in UTF-16 code units
+fn byte_offset(text: &str, position: Position) -> usize {
+	let line_start: usize = text
+		.split_inclusive('\n')
+		.take(position.line as usize)
+		.map(str::len)
+		.sum();
+	line_start + byte_column(&text[line_start..], position.character)
+}
+
+/// Returns the byte offset in a line of an LSP column, which is counted in UTF-16 code units. Columns past the end of
+/// the line are clamped to its end.
+pub fn byte_column(line: &str, character: u32) -> usize {
+	let mut column = 0;
+	for (offset, c) in line.char_indices() {
+		if column >= character as usize || c == '\n' {
+			return offset;
+		}
+		column += c.len_utf16();
+	}
+	line.len()
+}
+
+/// Returns the tree-sitter point (where columns are counted in bytes) of a byte offset
+fn point_at(text: &str, byte_offset: usize) -> Point {
+	let before = &text[..byte_offset];
+	let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+	Point::new(before.matches('\n').count(), byte_offset - line_start)
 }
 
 /// Runs several phases of the wing compiler on a file, including: parsing, type checking, and lifting
@@ -138,22 +381,33 @@ fn partial_compile(
 ) {
 	// Reset diagnostics before new compilation (`partial_compile` can be called multiple times)
 	reset_diagnostics();
+	let _compiling = start_phase(CompilationPhase::Compiling);
 
 	let source_path = Utf8Path::from_path(source_path).expect("invalid unicode path");
 	let project_dir = find_nearest_wing_project_dir(source_path);
 	let source_path = normalize_path(source_path, None);
+	project_data.unchecked_documents.swap_remove(&source_path);
+
+	let workspace = Workspace::find(&project_dir);
 
 	let source_package = if let Some(file) = project_data
 		.file_graph
 		.iter_files()
 		.find(|file| file.path == source_path)
 	{
-		&file.package
+		file.package.clone()
+	} else if let Some(package) = workspace
+		.as_ref()
+		.and_then(|workspace| workspace.package_of(&source_path))
+	{
+		// Files of a workspace belong to the workspace package that contains them
+		package.to_string()
 	} else {
 		// If this is our first time seeing the file, we assume it's from the user's root package.
 		// This could be wrong if the user is opening a file inside node_modules, for example.
-		DEFAULT_PACKAGE_NAME
+		DEFAULT_PACKAGE_NAME.to_string()
 	};
+	let source_package = source_package.as_str();
 	let source_file = File::new(source_path, source_package);
 
 	// If this is a file from a project or package we haven't seen before,
@@ -174,21 +428,54 @@ fn partial_compile(
 			.insert(source_package.to_string(), project_dir);
 	}
 
+	let parsing = start_phase(CompilationPhase::Parsing);
+
+	// The other open documents may have unsaved changes too
+	let mut overlays = project_data
+		.open_documents
+		.iter()
+		.filter_map(|path| Some((path, project_data.files.get_file(path)?.clone())))
+		.collect::<SourceOverlays>();
+	overlays.insert(&source_file.path, source_text);
+
+	// Index all the packages of the workspace, so their symbols are known even before they're brought
+	if let Some(workspace) = &workspace {
+		for (name, package_dir) in &workspace.packages {
+			project_data.library_roots.insert(name.clone(), package_dir.clone());
+			let package_file = File::new(package_dir, name);
+			if project_data.file_graph.contains_file(&package_file) || !dir_contains_wing_file_recursive(package_dir) {
+				continue;
+			}
+			parse_wing_project(
+				&package_file,
+				&project_data.ast_ctx,
+				&overlays,
+				&mut project_data.files,
+				&mut project_data.file_graph,
+				&mut project_data.library_roots,
+				&mut project_data.trees,
+				&mut project_data.asts,
+			);
+		}
+	}
+
 	let topo_sorted_files = parse_wing_project(
 		&source_file,
-		Some(source_text),
+		&project_data.ast_ctx,
+		&overlays,
 		&mut project_data.files,
 		&mut project_data.file_graph,
 		&mut project_data.library_roots,
 		&mut project_data.trees,
 		&mut project_data.asts,
 	);
+	parsing.finish();
 
 	// -- DESUGARING PHASE --
 
 	// Transform all inflight closures defined in preflight into single-method resources
 	for file in &topo_sorted_files {
-		let mut inflight_transformer = ClosureTransformer::new();
+		let mut inflight_transformer = ClosureTransformer::new(&project_data.files, &project_data.ast_ctx);
 		let scope = project_data.asts.swap_remove(&file.path).unwrap();
 		let new_scope = inflight_transformer.fold_scope(scope);
 		project_data.asts.insert(file.path.clone(), new_scope);
@@ -199,6 +486,7 @@ fn partial_compile(
 	project_data.jsii_imports.clear();
 
 	// -- TYPECHECKING PHASE --
+	let type_checking = start_phase(CompilationPhase::TypeChecking);
 
 	// Type check all files in topological order (start with files that don't require any other
 	// Wing files, then move on to files that depend on those, etc.)
@@ -207,32 +495,52 @@ fn partial_compile(
 			.asts
 			.swap_remove(&file.path)
 			.expect("matching AST not found");
-		type_check_file(
-			&mut scope,
-			&mut types,
-			&file,
-			&project_data.file_graph,
-			&mut project_data.library_roots,
-			jsii_types,
-			&mut project_data.jsii_imports,
-		);
-
-		// Make sure all type reference are no longer considered references
-		let mut tr_transformer = TypeReferenceTransformer { types: &mut types };
-		let scope = tr_transformer.fold_scope(scope);
-
-		// Validate the type checker didn't miss anything - see `TypeCheckAssert` for details
-		let mut tc_assert = TypeCheckAssert::new(&types, found_errors());
-		tc_assert.check(&scope);
-
-		// Validate all Json literals to make sure their values are legal
-		let mut json_checker = ValidJsonVisitor::new(&types);
-		json_checker.check(&scope);
+		// Keep serving the other files (and this file's AST) if the compiler has a bug in this one
+		let type_checked = catch_file_panic(CompilationPhase::TypeChecking, &file.path, || {
+			type_check_file(
+				&mut scope,
+				&mut types,
+				&file,
+				&project_data.file_graph,
+				&mut project_data.library_roots,
+				jsii_types,
+				&mut project_data.jsii_imports,
+			)
+		});
+		if type_checked.is_some() {
+			// Make sure all type reference are no longer considered references
+			let mut tr_transformer = TypeReferenceTransformer { types: &mut types };
+			scope = tr_transformer.fold_scope(scope);
+
+			catch_file_panic(CompilationPhase::TypeChecking, &file.path, || {
+				// Validate the type checker didn't miss anything - see `TypeCheckAssert` for details
+				let mut tc_assert = TypeCheckAssert::new(&types, found_errors());
+				tc_assert.check(&scope);
+
+				// Validate all Json literals to make sure their values are legal
+				let mut json_checker = ValidJsonVisitor::new(&types);
+				json_checker.check(&scope);
+
+				// Warn about code that can never run
+				let mut unreachable_checker = UnreachableCodeVisitor::new(&types);
+				unreachable_checker.check(&scope);
+
+				// Make sure captured preflight variables aren't reassigned behind the back of inflight code
+				let mut captured_var_checker = CapturedVarVisitor::new(&types);
+				captured_var_checker.check(&scope);
+
+				// Suggest dropping `var` from variables and fields that are never reassigned
+				let mut never_reassigned_checker = NeverReassignedVisitor::new(&types);
+				never_reassigned_checker.check(&scope);
+			});
+		}
 
 		project_data.asts.insert(file.path.clone(), scope);
 	}
+	type_checking.finish();
 
 	// -- LIFTING PHASE --
+	let lifting = start_phase(CompilationPhase::Lifting);
 
 	let jsifier = JSifier::new(
 		&mut types,
@@ -248,11 +556,15 @@ fn partial_compile(
 			.asts
 			.swap_remove(&file.path)
 			.expect("matching AST not found");
-		lift.visit_scope(&scope);
+		catch_file_panic(CompilationPhase::Lifting, &file.path, || lift.visit_scope(&scope));
 		project_data.asts.insert(file.path.clone(), scope);
+		emit_event(CompilerEvent::FileCompiled { path: &file.path });
 	}
+	lifting.finish();
 
 	// no need to JSify in the LSP
+
+	report_suppressed_diagnostics();
 }
 
 pub fn check_utf8(path: PathBuf) -> Utf8PathBuf {
@@ -370,3 +682,160 @@ pub mod test_utils {
 		ranges
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use lsp_types::{
+		DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams, Position, Range,
+		TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem, Url, VersionedTextDocumentIdentifier,
+	};
+	use tree_sitter::Point;
+
+	use super::{
+		byte_column, document_path, edit_text, on_document_did_change_syntax, on_document_did_close, on_document_did_open,
+		DocumentChangeResult, PROJECT_DATA,
+	};
+	use crate::diagnostic::found_errors;
+
+	fn parse(text: &str, old_tree: Option<&tree_sitter::Tree>) -> tree_sitter::Tree {
+		let mut parser = tree_sitter::Parser::new();
+		parser.set_language(&tree_sitter_wing::language()).unwrap();
+		parser.parse(text, old_tree).unwrap()
+	}
+
+	#[test]
+	fn edit_text_counts_columns_in_utf16() {
+		let mut text = "let a = \"é\";\nlet b = 1;\n".to_string();
+		// "é" is one UTF-16 code unit but two bytes
+		let edit = edit_text(
+			&mut text,
+			Range::new(Position::new(0, 11), Position::new(1, 5)),
+			"; let c",
+		);
+
+		assert_eq!(text, "let a = \"é\"; let c = 1;\n");
+		assert_eq!(edit.start_byte, 12);
+		assert_eq!(edit.old_end_byte, 19);
+		assert_eq!(edit.new_end_byte, 19);
+		assert_eq!(edit.start_position, Point::new(0, 12));
+		assert_eq!(edit.old_end_position, Point::new(1, 5));
+		assert_eq!(edit.new_end_position, Point::new(0, 19));
+	}
+
+	#[test]
+	fn byte_column_counts_utf16_code_units() {
+		// "é" is one UTF-16 code unit and two bytes, "💡" is two UTF-16 code units and four bytes
+		let line = "let a = \"é💡\"; a.";
+		assert_eq!(byte_column(line, 9), 9);
+		assert_eq!(byte_column(line, 10), 11);
+		assert_eq!(byte_column(line, 12), 15);
+		assert_eq!(byte_column(line, 17), line.len());
+		assert_eq!(byte_column(line, 100), line.len());
+		assert_eq!(byte_column("a.\nb", 5), 2);
+	}
+
+	#[test]
+	fn incremental_parse_matches_full_parse() {
+		let mut text = "let a = 1;\nlet b = a + 2;\n".to_string();
+		let mut tree = parse(&text, None);
+
+		let edit = edit_text(
+			&mut text,
+			Range::new(Position::new(1, 12), Position::new(1, 13)),
+			"[1, 2].length",
+		);
+		tree.edit(&edit);
+
+		let incremental_tree = parse(&text, Some(&tree));
+		assert_eq!(
+			incremental_tree.root_node().to_sexp(),
+			parse(&text, None).root_node().to_sexp()
+		);
+	}
+
+	#[test]
+	fn syntax_only_change_reports_errors_without_compiling() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let file_path = temp_dir.path().join("main.w");
+		let uri = Url::from_file_path(&file_path).unwrap();
+		let change = |range, text: &str| DidChangeTextDocumentParams {
+			text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 0),
+			content_changes: vec![TextDocumentContentChangeEvent {
+				range,
+				range_length: None,
+				text: text.to_string(),
+			}],
+		};
+
+		on_document_did_change_syntax(change(None, "let x = 1;\n"));
+		assert!(!found_errors());
+
+		on_document_did_change_syntax(change(Some(Range::new(Position::new(0, 9), Position::new(0, 9))), " +"));
+		assert!(found_errors());
+
+		PROJECT_DATA.with(|project_data| {
+			let project_data = project_data.borrow();
+			let path = document_path(&file_path);
+			assert_eq!(project_data.files.get_file(&path).unwrap(), "let x = 1 +;\n");
+			assert!(project_data.trees.contains_key(&path));
+			assert!(!project_data.asts.contains_key(&path));
+			assert!(project_data.unchecked_documents.contains(&path));
+		});
+
+		// Errors the parser reports while building the AST are found too, not just the syntax errors of tree-sitter
+		on_document_did_change_syntax(change(None, "break;\n"));
+		assert!(found_errors());
+	}
+
+	#[test]
+	fn range_changes_to_unknown_documents_ask_for_the_full_text() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let uri = Url::from_file_path(temp_dir.path().join("main.w")).unwrap();
+		let result = on_document_did_change_syntax(DidChangeTextDocumentParams {
+			text_document: VersionedTextDocumentIdentifier::new(uri, 0),
+			content_changes: vec![TextDocumentContentChangeEvent {
+				range: Some(Range::new(Position::new(0, 0), Position::new(0, 0))),
+				range_length: None,
+				text: "let x = 1;".to_string(),
+			}],
+		});
+
+		assert_eq!(result, DocumentChangeResult { needs_full_text: true });
+		PROJECT_DATA.with(|project_data| {
+			let path = document_path(&temp_dir.path().join("main.w"));
+			assert!(project_data.borrow().files.get_file(&path).is_none());
+		});
+	}
+
+	#[test]
+	fn unsaved_changes_of_open_documents_are_compiled() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let lib_path = temp_dir.path().join("lib.w");
+		let main_path = temp_dir.path().join("main.w");
+		std::fs::write(&lib_path, "pub class Saved {}\n").unwrap();
+		std::fs::write(&main_path, "").unwrap();
+		let open = |path: &std::path::Path, text: &str| {
+			on_document_did_open(DidOpenTextDocumentParams {
+				text_document: TextDocumentItem::new(
+					Url::from_file_path(path).unwrap(),
+					"wing".to_string(),
+					0,
+					text.to_string(),
+				),
+			})
+		};
+		let lib_text =
+			|| PROJECT_DATA.with(|project_data| project_data.borrow().files.get_file(&document_path(&lib_path)).cloned());
+
+		open(&lib_path, "pub class Unsaved {}\n");
+		open(&main_path, "bring \"./lib.w\" as lib;\n");
+		assert_eq!(lib_text().unwrap(), "pub class Unsaved {}\n");
+
+		// once closed, the unsaved changes are discarded
+		on_document_did_close(DidCloseTextDocumentParams {
+			text_document: TextDocumentIdentifier::new(Url::from_file_path(&lib_path).unwrap()),
+		});
+		open(&main_path, "bring \"./lib.w\" as lib;\n");
+		assert_eq!(lib_text().unwrap(), "pub class Saved {}\n");
+	}
+}
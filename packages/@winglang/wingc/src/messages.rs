@@ -0,0 +1,224 @@
+//! The wording of the compiler's diagnostic messages.
+//!
+//! Each message is a template keyed by a code, with `{placeholders}` for the values it mentions. The templates here are
+//! the default (English) wording. An alternate catalog, e.g. a translation, can replace any of them, either with
+//! `set_message_catalog` or by pointing the `WING_MESSAGE_CATALOG` environment variable at a JSON file mapping codes to
+//! templates (`{ "E2001": "Symbole inconnu \"{name}\"" }`).
+//!
+//! Diagnostics made from a message carry its code, so tools can tell them apart regardless of their wording.
+//!
+//! Only the messages below are in the catalog: the ones tools need to recognize (lint levels, code actions) and the most
+//! common errors. The rest of the compiler's diagnostics are still worded where they're reported, have no code and
+//! can't be reworded. A message is moved here when it needs a code, rather than all at once.
+
+use std::{
+	cell::RefCell,
+	collections::HashMap,
+	fmt::{Display, Write},
+};
+
+use camino::Utf8Path;
+
+/// Environment variable with the path of a JSON file that replaces the wording of some messages (see `messages`)
+pub const MESSAGE_CATALOG_ENV: &str = "WING_MESSAGE_CATALOG";
+
+/// A diagnostic message, identified by its code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Message {
+	pub code: &'static str,
+	template: &'static str,
+}
+
+impl Message {
+	const fn new(code: &'static str, template: &'static str) -> Self {
+		Self { code, template }
+	}
+
+	/// The default wording of the message
+	pub fn default_template(&self) -> &'static str {
+		self.template
+	}
+
+	/// The message in the wording of the current catalog, with its placeholders replaced by the given values.
+	/// Placeholders without a value are left as they are, and values are never searched for placeholders themselves.
+	pub fn format(&self, args: &[(&str, &dyn Display)]) -> String {
+		let template = CATALOG.with(|catalog| catalog.borrow().get(self.code).cloned());
+		let template = template.as_deref().unwrap_or(self.template);
+		let mut message = String::with_capacity(template.len());
+		let mut rest = template;
+		while let Some(start) = rest.find('{') {
+			message.push_str(&rest[..start]);
+			rest = &rest[start + 1..];
+			let arg = rest
+				.find('}')
+				.and_then(|end| Some((end, args.iter().find(|(name, _)| *name == &rest[..end])?.1)));
+			if let Some((end, value)) = arg {
+				_ = write!(message, "{value}");
+				rest = &rest[end + 1..];
+			} else {
+				message.push('{');
+			}
+		}
+		message.push_str(rest);
+		message
+	}
+
+	/// The message in the wording of the current catalog, for messages without placeholders
+	pub fn text(&self) -> String {
+		self.format(&[])
+	}
+}
+
+// Parser
+pub const EXPECTED_SEMICOLON: Message = Message::new("E1001", "Expected ';'");
+
+// Type checker
+pub const UNKNOWN_SYMBOL: Message = Message::new("E2001", "Unknown symbol \"{name}\"");
+pub const TYPE_MISMATCH: Message =
+	Message::new("E2002", "Expected type to be {expected}, but got \"{actual}\" instead");
+pub const NOT_REASSIGNABLE: Message = Message::new("E2003", "Variable is not reassignable");
+pub const CANNOT_CALL_INTO_PHASE: Message =
+	Message::new("E2004", "Cannot call into {callee_phase} phase while {phase}");
+pub const INVALID_OBJECT_SCOPE: Message = Message::new(
+	"E2005",
+	"Expected scope to be a preflight object, instead found \"{actual}\"",
+);
+pub const INFLIGHT_OBJECT_ID: Message = Message::new(
+	"E2006",
+	"The id of a preflight object must be known in preflight, it can't use inflight values",
+);
+
+// Lifting
+pub const SHADOWED_SYMBOL: Message = Message::new(
+	"E3001",
+	"Cannot access \"{name}\" because it is shadowed by another symbol with the same name",
+);
+
+// Warnings
+pub const VARIABLE_NEVER_REASSIGNED: Message = Message::new(
+	"W0001",
+	"Variable \"{name}\" is never reassigned, consider removing \"var\"",
+);
+pub const FIELD_NEVER_REASSIGNED: Message = Message::new(
+	"W0002",
+	"Field \"{name}\" is never reassigned, consider removing \"var\"",
+);
+pub const MORE_DIAGNOSTICS_IN_FILE: Message = Message::new("W0003", "and {count} more {diagnostics} in this file");
+pub const LIFTED_ONLY_TO_READ_DATA: Message = Message::new(
+	"W0004",
+	"\"{object}\" is lifted only to read \"{property}\", which also lifts every preflight object it contains",
+);
+
+/// Every message, e.g. to review their wording or to list what a translation has to cover
+pub const ALL_MESSAGES: &[Message] = &[
+	EXPECTED_SEMICOLON,
+	UNKNOWN_SYMBOL,
+	TYPE_MISMATCH,
+	NOT_REASSIGNABLE,
+	CANNOT_CALL_INTO_PHASE,
+	INVALID_OBJECT_SCOPE,
+	INFLIGHT_OBJECT_ID,
+	SHADOWED_SYMBOL,
+	VARIABLE_NEVER_REASSIGNED,
+	FIELD_NEVER_REASSIGNED,
+	MORE_DIAGNOSTICS_IN_FILE,
+	LIFTED_ONLY_TO_READ_DATA,
+];
+
+thread_local! {
+	static CATALOG: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+}
+
+/// Replaces the wording of the messages whose codes are in `catalog` (the others keep their default wording).
+/// Fails without changing anything if the catalog has unknown codes, or templates with placeholders their message
+/// doesn't have.
+pub fn set_message_catalog(catalog: HashMap<String, String>) -> Result<(), String> {
+	for (code, template) in &catalog {
+		let Some(message) = ALL_MESSAGES.iter().find(|message| message.code == code) else {
+			return Err(format!("Unknown message code \"{code}\""));
+		};
+		if let Some(placeholder) = placeholders(template).find(|p| !placeholders(message.template).any(|q| q == *p)) {
+			return Err(format!(
+				"Message \"{code}\" has no \"{{{placeholder}}}\" placeholder (its default wording is: {})",
+				message.template
+			));
+		}
+	}
+	CATALOG.with(|current| *current.borrow_mut() = catalog);
+	Ok(())
+}
+
+/// Sets the message catalog to the JSON file `WING_MESSAGE_CATALOG` points at, if it's set
+pub fn load_message_catalog_from_env() -> Result<(), String> {
+	let Ok(path) = std::env::var(MESSAGE_CATALOG_ENV) else {
+		return Ok(());
+	};
+	if path.is_empty() {
+		return Ok(());
+	}
+	let path = Utf8Path::new(&path);
+	let json = std::fs::read_to_string(path).map_err(|err| format!("Cannot read message catalog {path}: {err}"))?;
+	let catalog = serde_json::from_str(&json).map_err(|err| format!("Invalid message catalog {path}: {err}"))?;
+	set_message_catalog(catalog).map_err(|err| format!("Invalid message catalog {path}: {err}"))
+}
+
+/// The names of the `{placeholders}` in a template
+fn placeholders(template: &str) -> impl Iterator<Item = &str> {
+	template
+		.split('{')
+		.skip(1)
+		.filter_map(|part| part.split_once('}').map(|(name, _)| name))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+
+	use super::*;
+
+	#[test]
+	fn formats_messages_with_the_current_catalog() {
+		assert_eq!(UNKNOWN_SYMBOL.format(&[("name", &"foo")]), "Unknown symbol \"foo\"");
+
+		set_message_catalog(HashMap::from([(
+			UNKNOWN_SYMBOL.code.to_string(),
+			"Symbole inconnu \"{name}\"".to_string(),
+		)]))
+		.unwrap();
+		assert_eq!(UNKNOWN_SYMBOL.format(&[("name", &"foo")]), "Symbole inconnu \"foo\"");
+		assert_eq!(NOT_REASSIGNABLE.text(), "Variable is not reassignable");
+
+		assert!(set_message_catalog(HashMap::from([("E9999".to_string(), "?".to_string())])).is_err());
+		assert!(set_message_catalog(HashMap::from([(
+			UNKNOWN_SYMBOL.code.to_string(),
+			"Symbole inconnu \"{nom}\"".to_string()
+		)]))
+		.is_err());
+
+		set_message_catalog(HashMap::new()).unwrap();
+	}
+
+	#[test]
+	fn replaces_each_placeholder_once() {
+		// values that look like placeholders are left as they are
+		assert_eq!(
+			TYPE_MISMATCH.format(&[("expected", &"\"{actual}\""), ("actual", &"str")]),
+			"Expected type to be \"{actual}\", but got \"str\" instead"
+		);
+		assert_eq!(
+			MORE_DIAGNOSTICS_IN_FILE.format(&[("count", &3)]),
+			"and 3 more {diagnostics} in this file"
+		);
+	}
+
+	#[test]
+	fn message_codes_are_unique() {
+		for (index, message) in ALL_MESSAGES.iter().enumerate() {
+			assert!(
+				!ALL_MESSAGES[..index].iter().any(|other| other.code == message.code),
+				"duplicate message code {}",
+				message.code
+			);
+		}
+	}
+}
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+use crate::{
+	ast::{AccessModifier, Class, ExprKind, FunctionBody, FunctionDefinition, Reference, Scope, Stmt, StmtKind, Symbol},
+	diagnostic::{Diagnostic, DiagnosticSeverity, WingSpan},
+	messages::{FIELD_NEVER_REASSIGNED, VARIABLE_NEVER_REASSIGNED},
+	type_check::{
+		resolve_super_member, resolve_user_defined_type, symbol_env::LookupResult, SymbolKind, Types,
+		CLASS_INFLIGHT_INIT_NAME, CLASS_INIT_NAME,
+	},
+	visit::{self, Visit},
+	visit_context::{VisitContext, VisitorWithContext},
+};
+
+/// This visitor warns about `var` variables and private `var` fields that are never reassigned, since they can
+/// be declared without `var`.
+///
+/// Assignments to a field in its class's constructor are initializations, so they don't count as reassignments.
+pub struct NeverReassignedVisitor<'a> {
+	types: &'a Types,
+	ctx: VisitContext,
+	/// Reassignable variables and fields in the order they're declared, and whether each is a field
+	declarations: Vec<(Symbol, bool)>,
+	/// The spans of the declarations of all variables and fields that are reassigned somewhere
+	reassigned: HashSet<WingSpan>,
+}
+
+impl<'a> NeverReassignedVisitor<'a> {
+	pub fn new(types: &'a Types) -> Self {
+		Self {
+			types,
+			ctx: VisitContext::new(),
+			declarations: vec![],
+			reassigned: HashSet::new(),
+		}
+	}
+
+	pub fn check(&mut self, scope: &Scope) {
+		self.visit_scope(scope);
+
+		for (name, is_field) in &self.declarations {
+			if self.reassigned.contains(&name.span) {
+				continue;
+			}
+			let message = if *is_field {
+				FIELD_NEVER_REASSIGNED
+			} else {
+				VARIABLE_NEVER_REASSIGNED
+			};
+			Diagnostic::from_message(message, &[("name", name)], name)
+				.severity(DiagnosticSeverity::Warning)
+				.report();
+		}
+	}
+
+	fn in_constructor(&self) -> bool {
+		self.ctx.current_function().is_some_and(|f| {
+			f.name
+				.is_some_and(|name| name.name == CLASS_INIT_NAME || name.name == CLASS_INFLIGHT_INIT_NAME)
+		})
+	}
+
+	/// Returns the span of the declaration of the variable or field being assigned to
+	fn assigned_declaration(&self, variable: &Reference) -> Option<WingSpan> {
+		let env = self.ctx.current_env()?;
+		let stmt_idx = self.ctx.current_stmt_idx();
+		let (class_type, property) = match variable {
+			Reference::Identifier(symbol) => {
+				let LookupResult::Found(kind, _) = env.lookup_ext(symbol, Some(stmt_idx)) else {
+					return None;
+				};
+				return kind.as_variable().map(|v| v.name.span.clone());
+			}
+			Reference::InstanceMember { object, property, .. } => {
+				// Assigning fields of `this` in the constructor initializes them
+				if self.in_constructor()
+					&& matches!(&object.kind, ExprKind::Reference(Reference::Identifier(s)) if s.name == "this")
+				{
+					return None;
+				}
+				(self.types.get_expr_type(object), property)
+			}
+			Reference::TypeMember { type_name, property } => {
+				(resolve_user_defined_type(type_name, env, stmt_idx).ok()?, property)
+			}
+			Reference::SuperMember { property } => {
+				return resolve_super_member(property, env, self.types)
+					.ok()
+					.map(|(_, field)| field.name.span.clone())
+			}
+			Reference::ElementAccess { .. } => return None,
+		};
+		let class = class_type.as_class()?;
+		match class.env.lookup(property, None)? {
+			SymbolKind::Variable(field) => Some(field.name.span.clone()),
+			_ => None,
+		}
+	}
+}
+
+impl<'a> Visit<'a> for NeverReassignedVisitor<'_> {
+	fn visit_scope(&mut self, node: &'a Scope) {
+		self.ctx.push_env(self.types.get_scope_env(node));
+		visit::visit_scope(self, node);
+		self.ctx.pop_env();
+	}
+
+	fn visit_stmt(&mut self, node: &'a Stmt) {
+		self.with_stmt(node, |v| {
+			match &node.kind {
+				StmtKind::Let {
+					reassignable: true,
+					var_name,
+					..
+				} => v.declarations.push((var_name.clone(), false)),
+				StmtKind::Assignment { variable, .. } => {
+					if let Some(span) = v.assigned_declaration(variable) {
+						v.reassigned.insert(span);
+					}
+				}
+				_ => {}
+			}
+			visit::visit_stmt(v, node);
+		});
+	}
+
+	fn visit_class(&mut self, node: &'a Class) {
+		// Non-private fields can be reassigned from other files, so only private ones are checked
+		for field in &node.fields {
+			if field.reassignable && field.access == AccessModifier::Private {
+				self.declarations.push((field.name.clone(), true));
+			}
+		}
+		self.with_class(node, |v| visit::visit_class(v, node));
+	}
+
+	fn visit_function_definition(&mut self, node: &'a FunctionDefinition) {
+		match &node.body {
+			FunctionBody::Statements(scope) => self.with_function_def(
+				node.name.as_ref(),
+				&node.signature,
+				node.is_static,
+				self.types.get_scope_env(scope),
+				&node.span,
+				|v| visit::visit_function_definition(v, node),
+			),
+			FunctionBody::External(_) => visit::visit_function_definition(self, node),
+		}
+	}
+}
+
+impl VisitorWithContext for NeverReassignedVisitor<'_> {
+	fn ctx(&mut self) -> &mut VisitContext {
+		&mut self.ctx
+	}
+}
@@ -0,0 +1,439 @@
+//! Generates an OpenAPI document from the routes a program adds to its `cloud.Api`s. Routes are only found when their
+//! paths are string literals, and request and response bodies are described by the structs their handlers parse them
+//! into and return, so the document may be incomplete. That's why it's only generated when it's asked for.
+
+use std::collections::HashMap;
+
+use camino::Utf8PathBuf;
+use const_format::formatcp;
+use indexmap::IndexMap;
+use serde_json::{json, Map, Value};
+
+use crate::ast::{
+	CalleeKind, Class, Expr, ExprId, ExprKind, FunctionBody, Literal, Reference, Scope, Stmt, StmtKind, Symbol,
+};
+use crate::json_schema_generator::JsonSchemaGenerator;
+use crate::type_check::{Type, TypeRef, Types, CLOSURE_CLASS_HANDLE_METHOD};
+use crate::visit::{self, Visit};
+use crate::{WINGSDK_ASSEMBLY_NAME, WINGSDK_CLOUD_MODULE, WINGSDK_JSON, WINGSDK_STD_MODULE};
+
+/// Name of the environment variable that turns on the generation of an OpenAPI document for the program's APIs
+/// (when set to "true")
+pub const OPENAPI_ENV: &str = "WING_OPENAPI";
+
+/// Emitted next to the preflight entrypoint when `OPENAPI_ENV` is set
+pub const OPENAPI_FILE_NAME: &str = "openapi.json";
+
+const API_CLASS_FQN: &str = formatcp!("{WINGSDK_ASSEMBLY_NAME}.{WINGSDK_CLOUD_MODULE}.Api");
+
+/// The methods of `cloud.Api` that add a route, which are named after its HTTP method
+const ROUTE_METHODS: [&str; 8] = ["get", "post", "put", "delete", "patch", "options", "head", "connect"];
+
+/// Static methods of structs that parse a request body into the struct
+const STRUCT_PARSE_METHODS: [&str; 2] = ["fromJson", "parseJson"];
+
+const JSON_STRINGIFY_METHOD: &str = "stringify";
+
+/// A route added to an API, with the structs its handler reads the request body into and writes responses from
+struct Route {
+	path: String,
+	method: String,
+	handler_doc: Option<String>,
+	request_body: Option<TypeRef>,
+	/// The schema type (if its body is written from a struct) of each response, by status code
+	responses: IndexMap<String, Option<TypeRef>>,
+}
+
+/// The OpenAPI 3 document of the routes the program adds to `cloud.Api`s, as JSON. The request and response bodies
+/// of a route are described by the structs its handler parses the request body into (`S.fromJson(req.body)`) and
+/// stringifies into the response bodies it returns (`{ status: 200, body: Json.stringify(s) }`).
+///
+/// Returns `None` if the program doesn't add any routes.
+pub fn openapi_document_json(types: &Types, asts: &IndexMap<Utf8PathBuf, Scope>, title: &str) -> Option<String> {
+	let mut routes = vec![];
+	for scope in asts.values() {
+		let mut collector = RouteCollector {
+			types,
+			closure_classes: HashMap::new(),
+			docs: HashMap::new(),
+			routes: vec![],
+		};
+		collector.visit_scope(scope);
+		routes.extend(collector.routes);
+	}
+	if routes.is_empty() {
+		return None;
+	}
+
+	let schema_generator = JsonSchemaGenerator::new();
+	let mut schemas = Map::new();
+	let mut schema_ref = |t: &Option<TypeRef>| -> Option<Value> {
+		let t = (*t)?;
+		Some(match t.as_struct() {
+			Some(s) => {
+				schemas
+					.entry(s.name.name.clone())
+					.or_insert_with(|| schema_generator.create_json_from_struct(s));
+				json!({ "$ref": format!("#/components/schemas/{}", s.name.name) })
+			}
+			None => schema_generator.get_struct_schema_field_json(&t),
+		})
+	};
+
+	let mut paths: IndexMap<String, Map<String, Value>> = IndexMap::new();
+	for route in &routes {
+		let mut operation = Map::new();
+		if let Some(doc) = &route.handler_doc {
+			operation.insert("summary".to_string(), json!(doc));
+		}
+		let parameters = path_parameters(&route.path)
+			.map(|name| json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } }))
+			.collect::<Vec<_>>();
+		if !parameters.is_empty() {
+			operation.insert("parameters".to_string(), json!(parameters));
+		}
+		if let Some(schema) = schema_ref(&route.request_body) {
+			operation.insert(
+				"requestBody".to_string(),
+				json!({ "required": true, "content": { "application/json": { "schema": schema } } }),
+			);
+		}
+		let mut responses = Map::new();
+		for (status, body) in &route.responses {
+			let mut response = Map::new();
+			response.insert("description".to_string(), json!(format!("{status} response")));
+			if let Some(schema) = schema_ref(body) {
+				response.insert(
+					"content".to_string(),
+					json!({ "application/json": { "schema": schema } }),
+				);
+			}
+			responses.insert(status.clone(), Value::Object(response));
+		}
+		if responses.is_empty() {
+			responses.insert("default".to_string(), json!({ "description": "The route's response" }));
+		}
+		operation.insert("responses".to_string(), Value::Object(responses));
+		paths
+			.entry(route.path.clone())
+			.or_default()
+			.insert(route.method.clone(), Value::Object(operation));
+	}
+
+	let mut document = json!({
+		"openapi": "3.1.0",
+		"info": { "title": title, "version": "0.0.0" },
+		"paths": paths,
+	});
+	if !schemas.is_empty() {
+		document["components"] = json!({ "schemas": schemas });
+	}
+	Some(serde_json::to_string_pretty(&document).expect("serialize OpenAPI document"))
+}
+
+/// The names of the `{parameters}` of a route's path
+fn path_parameters(path: &str) -> impl Iterator<Item = &str> {
+	path
+		.split('{')
+		.skip(1)
+		.filter_map(|part| part.split_once('}').map(|(name, _)| name))
+}
+
+struct RouteCollector<'a> {
+	types: &'a Types,
+	/// The classes inflight closures were transformed into, by name
+	closure_classes: HashMap<String, &'a Class>,
+	/// The doc comments of statements, by the id of the expression that's their value
+	docs: HashMap<ExprId, String>,
+	routes: Vec<Route>,
+}
+
+impl<'a> RouteCollector<'a> {
+	fn is_api(&self, object: &Expr) -> bool {
+		let mut class_type = Some(self.types.get_expr_type(object));
+		while let Some(class) = class_type.as_ref().and_then(|t| t.as_class()) {
+			if class.fqn.as_deref() == Some(API_CLASS_FQN) {
+				return true;
+			}
+			class_type = class.parent;
+		}
+		false
+	}
+
+	/// Adds the route added by a call to one of the route methods of an API, if the call is one
+	fn add_route(&mut self, expr: &'a Expr) {
+		let ExprKind::Call {
+			callee: CalleeKind::Expr(callee),
+			arg_list,
+			..
+		} = &expr.kind
+		else {
+			return;
+		};
+		let ExprKind::Reference(Reference::InstanceMember { object, property, .. }) = &callee.kind else {
+			return;
+		};
+		if !ROUTE_METHODS.contains(&property.name.as_str()) || !self.is_api(object) {
+			return;
+		}
+		let Some(ExprKind::Literal(Literal::String(path))) = arg_list.pos_args.first().map(|arg| &arg.kind) else {
+			return;
+		};
+		let Some(handler) = arg_list.pos_args.get(1) else {
+			return;
+		};
+		let Some(class) = self
+			.types
+			.get_expr_type(handler)
+			.as_class()
+			.and_then(|c| self.closure_classes.get(&c.name.name))
+			.copied()
+		else {
+			return;
+		};
+		let Some((_, handle)) = class
+			.methods
+			.iter()
+			.find(|(name, _)| name.name == CLOSURE_CLASS_HANDLE_METHOD)
+		else {
+			return;
+		};
+		let FunctionBody::Statements(body) = &handle.body else {
+			return;
+		};
+
+		let mut route = Route {
+			path: path.trim_matches('"').to_string(),
+			method: property.name.clone(),
+			handler_doc: self.docs.get(&expr.id).cloned(),
+			request_body: None,
+			responses: IndexMap::new(),
+		};
+		let mut handler_collector = HandlerCollector {
+			types: self.types,
+			request: handle.signature.parameters.first().map(|p| p.name.clone()),
+			route: &mut route,
+		};
+		handler_collector.visit_scope(body);
+		self.routes.push(route);
+	}
+}
+
+impl<'a> Visit<'a> for RouteCollector<'a> {
+	fn visit_stmt(&mut self, node: &'a Stmt) {
+		match &node.kind {
+			StmtKind::Class(class) => {
+				self.closure_classes.insert(class.name.name.clone(), class);
+			}
+			StmtKind::Expression(expr) => {
+				if let Some(doc) = &node.doc {
+					self.docs.insert(expr.id, doc.clone());
+				}
+			}
+			_ => {}
+		}
+		visit::visit_stmt(self, node);
+	}
+
+	fn visit_expr(&mut self, node: &'a Expr) {
+		self.add_route(node);
+		visit::visit_expr(self, node);
+	}
+}
+
+/// Finds the request and response bodies of a route in the body of its handler
+struct HandlerCollector<'a, 'b> {
+	types: &'a Types,
+	/// The request parameter of the handler
+	request: Option<Symbol>,
+	route: &'b mut Route,
+}
+
+impl HandlerCollector<'_, '_> {
+	/// The struct the expression parses the request body into, if it's a call like `S.fromJson(req.body)`
+	fn parsed_request_struct(&self, expr: &Expr) -> Option<TypeRef> {
+		let ExprKind::Call {
+			callee: CalleeKind::Expr(callee),
+			arg_list,
+			..
+		} = &expr.kind
+		else {
+			return None;
+		};
+		let ExprKind::Reference(Reference::TypeMember { property, .. }) = &callee.kind else {
+			return None;
+		};
+		if !STRUCT_PARSE_METHODS.contains(&property.name.as_str()) {
+			return None;
+		}
+		let request = self.request.as_ref()?;
+		if !arg_list.pos_args.iter().any(|arg| references(arg, request)) {
+			return None;
+		}
+		let parsed = self.types.get_expr_type(expr);
+		parsed.as_struct().map(|_| parsed)
+	}
+
+	/// The status code and the type of the body of a response the handler returns, if it's a literal like
+	/// `{ status: 200, body: Json.stringify(s) }`
+	fn response(&self, expr: &Expr) -> Option<(String, Option<TypeRef>)> {
+		let (ExprKind::StructLiteral { fields, .. } | ExprKind::JsonMapLiteral { fields }) = &expr.kind else {
+			return None;
+		};
+		let field = |name: &str| {
+			fields
+				.iter()
+				.find(|(field, _)| field.name == name)
+				.map(|(_, value)| value)
+		};
+		let status = match field("status").map(|status| &status.kind) {
+			Some(ExprKind::Literal(Literal::Number(status))) => status.to_string(),
+			_ => "default".to_string(),
+		};
+		let body = field("body").and_then(|body| self.stringified_type(body));
+		Some((status, body))
+	}
+
+	/// The type of the value stringified by a `Json.stringify(value)` call
+	fn stringified_type(&self, expr: &Expr) -> Option<TypeRef> {
+		let ExprKind::Call {
+			callee: CalleeKind::Expr(callee),
+			arg_list,
+			..
+		} = &expr.kind
+		else {
+			return None;
+		};
+		let ExprKind::Reference(Reference::TypeMember { type_name, property }) = &callee.kind else {
+			return None;
+		};
+		let json_type_names = [
+			WINGSDK_JSON,
+			WINGSDK_JSON.trim_start_matches(&format!("{WINGSDK_STD_MODULE}.")),
+		];
+		if property.name != JSON_STRINGIFY_METHOD || !json_type_names.contains(&type_name.full_path_str().as_str()) {
+			return None;
+		}
+		let value_type = self.types.get_expr_type(arg_list.pos_args.first()?);
+		(!matches!(*value_type, Type::Json(_) | Type::Anything)).then_some(value_type)
+	}
+}
+
+impl<'a, 'b> Visit<'a> for HandlerCollector<'a, 'b> {
+	fn visit_stmt(&mut self, node: &'a Stmt) {
+		if let StmtKind::Return(Some(value)) = &node.kind {
+			if let Some((status, body)) = self.response(value) {
+				let existing = self.route.responses.entry(status).or_default();
+				*existing = existing.or(body);
+			}
+		}
+		visit::visit_stmt(self, node);
+	}
+
+	fn visit_expr(&mut self, node: &'a Expr) {
+		if self.route.request_body.is_none() {
+			self.route.request_body = self.parsed_request_struct(node);
+		}
+		// Returns in nested functions aren't responses of the handler
+		if matches!(node.kind, ExprKind::FunctionClosure(_)) {
+			return;
+		}
+		visit::visit_expr(self, node);
+	}
+}
+
+/// Whether the expression refers to the given variable
+fn references(expr: &Expr, variable: &Symbol) -> bool {
+	struct ReferenceFinder<'a> {
+		variable: &'a Symbol,
+		found: bool,
+	}
+	impl<'a> Visit<'a> for ReferenceFinder<'_> {
+		fn visit_reference(&mut self, node: &'a Reference) {
+			if let Reference::Identifier(symbol) = node {
+				self.found |= symbol.name == self.variable.name;
+			}
+			visit::visit_reference(self, node);
+		}
+	}
+	let mut finder = ReferenceFinder { variable, found: false };
+	finder.visit_expr(expr);
+	finder.found
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::{json, Value};
+
+	use super::OPENAPI_FILE_NAME;
+	use crate::test_utils::compile_source_with_options;
+	use crate::CompileOptions;
+
+	#[test]
+	fn documents_api_routes_with_their_structs() {
+		let source = r#"
+bring cloud;
+
+struct Order {
+  /// The ordered item
+  item: str;
+  quantity: num?;
+}
+
+struct Receipt {
+  id: str;
+}
+
+let api = new cloud.Api();
+
+/// Places an order
+api.post("/orders/{store}", inflight (req) => {
+  let order = Order.fromJson(Json.parse(req.body!));
+  if order.item == "" {
+    return { status: 400, body: "missing item" };
+  }
+  return { status: 201, body: Json.stringify(Receipt { id: order.item }) };
+});
+
+api.get("/health", inflight () => {
+  return { status: 200 };
+});
+"#;
+		let document = compile_source_with_options(
+			source,
+			&CompileOptions {
+				openapi: true,
+				..Default::default()
+			},
+		)
+		.read_json(OPENAPI_FILE_NAME);
+
+		let post = &document["paths"]["/orders/{store}"]["post"];
+		assert_eq!(post["summary"], "Places an order");
+		assert_eq!(post["parameters"][0]["name"], "store");
+		assert_eq!(
+			post["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+			"#/components/schemas/Order"
+		);
+		assert_eq!(post["responses"]["400"]["content"], Value::Null);
+		assert_eq!(
+			post["responses"]["201"]["content"]["application/json"]["schema"]["$ref"],
+			"#/components/schemas/Receipt"
+		);
+		assert_eq!(
+			document["components"]["schemas"]["Order"],
+			json!({
+				"type": "object",
+				"properties": {
+					"item": { "type": "string", "description": "The ordered item" },
+					"quantity": { "type": "number" },
+				},
+				"required": ["item"],
+			})
+		);
+
+		let health = &document["paths"]["/health"]["get"];
+		assert_eq!(health["requestBody"], Value::Null);
+		assert!(health["responses"]["200"].is_object());
+	}
+}
@@ -1,4 +1,4 @@
-use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use camino::{Utf8Path, Utf8PathBuf};
 use indexmap::IndexMap;
 use itertools::Itertools;
 use phf::{phf_map, phf_set};
@@ -10,23 +10,24 @@ use std::{fs, str, vec};
 use tree_sitter::Node;
 
 use crate::ast::{
-	AccessModifier, ArgList, AssignmentKind, BinaryOperator, BringSource, CalleeKind, CatchBlock, Class, ClassField,
-	ElseIfBlock, ElseIfLetBlock, ElseIfs, Enum, ExplicitLift, Expr, ExprKind, FunctionBody, FunctionDefinition,
-	FunctionParameter, FunctionSignature, IfLet, Interface, InterpolatedString, InterpolatedStringPart, Intrinsic,
-	IntrinsicKind, LiftQualification, Literal, New, Phase, Reference, Scope, Spanned, Stmt, StmtKind, Struct,
-	StructField, Symbol, TypeAnnotation, TypeAnnotationKind, UnaryOperator, UserDefinedType,
+	AccessModifier, ArgList, AssignmentKind, AstContext, BinaryOperator, BringSource, CalleeKind, CatchBlock, Class,
+	ClassField, ElseIfBlock, ElseIfLetBlock, ElseIfs, Enum, ExplicitLift, Expr, ExprKind, FunctionBody,
+	FunctionDefinition, FunctionParameter, FunctionSignature, IfLet, IfLetCondition, Interface, InterpolatedString,
+	InterpolatedStringPart, Intrinsic, IntrinsicKind, LiftQualification, Literal, LiteralTypeValue, New, Phase,
+	Reference, Scope, Spanned, Stmt, StmtKind, Struct, StructField, Symbol, TypeAnnotation, TypeAnnotationKind,
+	UnaryOperator, UserDefinedType,
 };
 use crate::comp_ctx::{CompilationContext, CompilationPhase};
-use crate::diagnostic::{
-	report_diagnostic, Diagnostic, DiagnosticResult, DiagnosticSeverity, WingLocation, WingSpan, ERR_EXPECTED_SEMICOLON,
-};
+use crate::compiler_version::check_library_compiler_version;
+use crate::diagnostic::{report_diagnostic, Diagnostic, DiagnosticResult, DiagnosticSeverity, WingLocation, WingSpan};
+use crate::events::{emit_event, CacheKind, CompilerEvent};
 use crate::file_graph::{File, FileGraph};
 use crate::files::Files;
+use crate::messages::EXPECTED_SEMICOLON;
+use crate::paths::{is_absolute_path, normalize_path, path_key, resolve_symlinks};
 use crate::type_check::{CLASS_INFLIGHT_INIT_NAME, CLASS_INIT_NAME};
-use crate::{
-	is_absolute_path, TRUSTED_LIBRARY_NPM_NAMESPACE, WINGSDK_BRINGABLE_MODULES, WINGSDK_STD_MODULE,
-	WINGSDK_TEST_CLASS_NAME,
-};
+use crate::workspace::WorkspaceCache;
+use crate::{TRUSTED_LIBRARY_NPM_NAMESPACE, WINGSDK_BRINGABLE_MODULES, WINGSDK_STD_MODULE, WINGSDK_TEST_CLASS_NAME};
 
 // A custom struct could be used to better maintain metadata and issue tracking, though ideally
 // this is meant to serve as a bandaide to be removed once wing is further developed.
@@ -138,14 +139,65 @@ static RESERVED_WORDS: phf::Set<&'static str> = phf_set! {
 	"Object",
 };
 
+/// Prefix of bring sources that are plain npm packages rather than JSII or Wing libraries (e.g. `bring "npm:lodash" as _`)
+const NPM_MODULE_PREFIX: &str = "npm:";
+
+/// Values of a flags enum are distinct bits of a (positive) 32 bit integer
+const MAX_FLAGS_ENUM_VALUES: usize = 31;
+
+/// Prefix of the class holding the static methods declared in a struct's body
+pub const STRUCT_STATICS_CLASS_PREFIX: &str = "$Statics_";
+
+/// Name of the class holding the static methods declared in the body of the struct `struct_name`
+pub fn struct_statics_class_name(struct_name: &str) -> String {
+	format!("{}{}", STRUCT_STATICS_CLASS_PREFIX, struct_name)
+}
+
+/// In-memory contents of Wing files that are used instead of the contents on disk, e.g. the unsaved changes of an
+/// editor, or the changes of a speculative compilation. Only the contents of files are replaced, so the files still
+/// have to exist for `bring` statements to find them.
+#[derive(Debug, Default, Clone)]
+pub struct SourceOverlays {
+	/// The contents of the files, by their path keys (see `path_key`)
+	contents: HashMap<String, String>,
+}
+
+impl SourceOverlays {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Replaces the contents of a file, overwriting any previous overlay of it
+	pub fn insert(&mut self, path: &Utf8Path, text: String) {
+		self.contents.insert(path_key(path), text);
+	}
+
+	/// The contents of a file, read from disk unless it has an overlay
+	pub fn read(&self, path: &Utf8Path) -> std::io::Result<String> {
+		match self.contents.get(&path_key(path)) {
+			Some(text) => Ok(text.clone()),
+			None => fs::read_to_string(path),
+		}
+	}
+}
+
+impl<P: AsRef<Utf8Path>> FromIterator<(P, String)> for SourceOverlays {
+	fn from_iter<I: IntoIterator<Item = (P, String)>>(iter: I) -> Self {
+		let mut overlays = Self::new();
+		for (path, text) in iter {
+			overlays.insert(path.as_ref(), text);
+		}
+		overlays
+	}
+}
+
 /// Parses a Wing file and the transitive closure of all files it depends on.
 ///
 /// Expects an initial Wing file to be parsed. For Wing's CLI, this is usually
 /// the file the user asked to compile, and in the case of the LSP, the file that was
 /// just opened or changed.
 ///
-/// If `init_text` is `None`, then the file will be read from disk. Otherwise, the
-/// provided text will be used as the source text for the file. This is useful for
+/// Files are read from disk, unless they have an overlay in `overlays`. This is useful for
 /// the LSP, where the text may not be saved to the disk yet, and for unit tests.
 ///
 /// Internally it parses the initial file, and then recursively parses all of the files that
@@ -153,22 +205,36 @@ static RESERVED_WORDS: phf::Set<&'static str> = phf_set! {
 /// and `asts` parameters. It skips re-parsing any files that have already been parsed. function assumes all of these collections are kept in sync
 /// with each other.
 ///
+/// If `tree_sitter_trees` already has a tree for the initial file, it's reused for incremental parsing,
+/// so it must have been edited to match the new text (see `tree_sitter::Tree::edit`).
+///
+/// The identifiers of all AST nodes are allocated from `ast_ctx`, which must be the same context
+/// that was used to create any ASTs already in `asts`.
+///
 /// Returns a topological ordering of all known Wing files, where each file only depends on
 /// files that come before it in the ordering.
+#[allow(clippy::too_many_arguments)]
 pub fn parse_wing_project(
 	init_file: &File,
-	init_text: Option<String>,
+	ast_ctx: &AstContext,
+	overlays: &SourceOverlays,
 	files: &mut Files,
 	file_graph: &mut FileGraph,
 	library_roots: &mut IndexMap<String, Utf8PathBuf>,
 	tree_sitter_trees: &mut IndexMap<Utf8PathBuf, tree_sitter::Tree>,
 	asts: &mut IndexMap<Utf8PathBuf, Scope>,
 ) -> Vec<File> {
+	// Where each library was first brought from, to explain conflicts between versions of a library
+	let mut library_brought_at = HashMap::new();
+	// The workspaces the project's files belong to, found once for the whole project
+	let workspaces = WorkspaceCache::default();
+
 	// Parse the initial path (even if we have already seen it before)
 	let dependent_wing_paths = match init_file.path.is_dir() {
 		true => parse_wing_directory(
 			&init_file,
 			&WingSpan::for_file(init_file.to_string()),
+			ast_ctx,
 			files,
 			file_graph,
 			tree_sitter_trees,
@@ -176,10 +242,13 @@ pub fn parse_wing_project(
 		),
 		false => parse_wing_file(
 			&init_file,
-			init_text,
+			overlays,
+			ast_ctx,
 			files,
 			file_graph,
 			library_roots,
+			&mut library_brought_at,
+			&workspaces,
 			tree_sitter_trees,
 			asts,
 		),
@@ -192,6 +261,10 @@ pub fn parse_wing_project(
 	while let Some((file_or_dir, source_ref)) = unparsed_files.pop() {
 		// Skip files that we have already seen before (they should already be parsed)
 		if files.contains_file(&file_or_dir.path) {
+			emit_event(CompilerEvent::CacheHit {
+				cache: CacheKind::ParsedFile,
+				key: file_or_dir.path.as_str(),
+			});
 			assert!(
 				tree_sitter_trees.contains_key(&file_or_dir.path),
 				"files is not in sync with tree_sitter_trees"
@@ -206,13 +279,24 @@ pub fn parse_wing_project(
 
 		// Parse the file or directory
 		let dependent_wing_paths = match file_or_dir.path.is_dir() {
-			true => parse_wing_directory(&file_or_dir, &source_ref, files, file_graph, tree_sitter_trees, asts),
+			true => parse_wing_directory(
+				&file_or_dir,
+				&source_ref,
+				ast_ctx,
+				files,
+				file_graph,
+				tree_sitter_trees,
+				asts,
+			),
 			false => parse_wing_file(
 				&file_or_dir,
-				None,
+				overlays,
+				ast_ctx,
 				files,
 				file_graph,
 				library_roots,
+				&mut library_brought_at,
+				&workspaces,
 				tree_sitter_trees,
 				asts,
 			),
@@ -238,6 +322,7 @@ pub fn parse_wing_project(
 				annotations: vec![],
 				hints: vec![],
 				severity: DiagnosticSeverity::Error,
+				code: None,
 			});
 
 			// return a list of all files just so we can continue type-checking
@@ -246,19 +331,70 @@ pub fn parse_wing_project(
 	}
 }
 
+/// Reports a library that's brought from two different directories with different versions (e.g. when two libraries
+/// depend on different versions of it). Types from the two copies wouldn't be compatible with each other.
+fn report_library_version_conflict(
+	name: &str,
+	existing_path: &Utf8Path,
+	existing_bring: Option<&WingSpan>,
+	path: &Utf8Path,
+	bring_span: &WingSpan,
+) {
+	let (Some(existing_version), Some(version)) = (package_version(existing_path), package_version(path)) else {
+		return;
+	};
+	if existing_version == version {
+		return;
+	}
+
+	let mut diagnostic = Diagnostic::new(
+		format!("Library \"{name}\" is brought in two different versions: {existing_version} and {version}"),
+		bring_span,
+	)
+	.hint(format!(
+		"Types from different versions of \"{name}\" are not compatible with each other. Make sure all the libraries that depend on it use the same version (e.g. by updating them, or by running `npm dedupe`)."
+	));
+	if let Some(existing_bring) = existing_bring {
+		diagnostic.add_anotation(
+			format!("version {existing_version} is brought here (from {existing_path})"),
+			existing_bring.clone(),
+		);
+	}
+	diagnostic.add_anotation(
+		format!("version {version} is brought here (from {path})"),
+		bring_span.clone(),
+	);
+	diagnostic.report();
+}
+
+/// Returns the version in the package.json of a package's root directory
+fn package_version(package_dir: &Utf8Path) -> Option<String> {
+	package_name_and_version(package_dir).map(|(_, version)| version)
+}
+
+/// Returns the name and version in the package.json of a package's root directory
+pub fn package_name_and_version(package_dir: &Utf8Path) -> Option<(String, String)> {
+	let package_json = fs::read_to_string(package_dir.join("package.json")).ok()?;
+	let package_json: serde_json::Value = serde_json::from_str(&package_json).ok()?;
+	let name = package_json.get("name")?.as_str()?;
+	let version = package_json.get("version")?.as_str()?;
+	Some((name.to_string(), version.to_string()))
+}
+
+#[allow(clippy::too_many_arguments)]
 fn parse_wing_file(
 	source_file: &File,
-	source_text: Option<String>,
+	overlays: &SourceOverlays,
+	ast_ctx: &AstContext,
 	files: &mut Files,
 	file_graph: &mut FileGraph,
 	library_roots: &mut IndexMap<String, Utf8PathBuf>,
+	library_brought_at: &mut HashMap<String, WingSpan>,
+	workspaces: &WorkspaceCache,
 	tree_sitter_trees: &mut IndexMap<Utf8PathBuf, tree_sitter::Tree>,
 	asts: &mut IndexMap<Utf8PathBuf, Scope>,
 ) -> Vec<(File, WingSpan)> {
-	let source_text = match source_text {
-		Some(text) => text,
-		None => fs::read_to_string(&source_file.path).expect("read_to_string call failed"),
-	};
+	let source_text = overlays.read(&source_file.path).expect("read_to_string call failed");
 
 	// Update our files collection with the new source text. On a fresh compilation,
 	// this will be the first time we've seen this file. In the LSP we might already have
@@ -269,7 +405,16 @@ fn parse_wing_file(
 	let mut tree_sitter_parser = tree_sitter::Parser::new();
 	tree_sitter_parser.set_language(&language).unwrap();
 
-	let tree_sitter_tree = match tree_sitter_parser.parse(&source_text.as_bytes(), None) {
+	// If the file was parsed before, the LSP has already edited its old tree to match the new text (see
+	// `on_document_did_change`), so tree-sitter can reuse the parts of it that didn't change
+	let old_tree = tree_sitter_trees.get(&source_file.path);
+	if old_tree.is_some() {
+		emit_event(CompilerEvent::CacheHit {
+			cache: CacheKind::SyntaxTree,
+			key: source_file.path.as_str(),
+		});
+	}
+	let tree_sitter_tree = match tree_sitter_parser.parse(&source_text.as_bytes(), old_tree) {
 		Some(tree) => tree,
 		None => {
 			panic!("Error parsing source file with tree-sitter: {}", source_file);
@@ -279,9 +424,16 @@ fn parse_wing_file(
 	let tree_sitter_root = tree_sitter_tree.root_node();
 
 	// Parse the source text into an AST
-	let parser = Parser::new(&source_text.as_bytes(), source_file.to_owned());
+	let parser = Parser::new(&source_text.as_bytes(), source_file.to_owned(), ast_ctx, workspaces);
 	let (scope, dependent_wing_paths, found_library_roots) = parser.parse(&tree_sitter_root);
-	for (name, path) in found_library_roots {
+	for (name, (path, bring_span)) in found_library_roots {
+		if let Some(existing_path) = library_roots.get(&name) {
+			if *existing_path != path {
+				report_library_version_conflict(&name, existing_path, library_brought_at.get(&name), &path, &bring_span);
+				continue;
+			}
+		}
+		library_brought_at.entry(name.clone()).or_insert(bring_span);
 		library_roots.insert(name, path);
 	}
 
@@ -293,9 +445,40 @@ fn parse_wing_file(
 	dependent_wing_paths
 }
 
+/// Parses a single Wing file only to report its syntax errors, without parsing the files it depends on or
+/// running any of the later compiler phases. This is much cheaper than `parse_wing_project`, so the LSP
+/// can run it on every keystroke.
+///
+/// The AST is discarded, and the file's tree-sitter tree is returned so it can be reused for incremental
+/// parsing. If `old_tree` is provided, it must have been edited to match `source_text`.
+pub fn parse_wing_file_syntax(
+	source_file: &File,
+	source_text: &str,
+	old_tree: Option<&tree_sitter::Tree>,
+) -> tree_sitter::Tree {
+	let language = tree_sitter_wing::language();
+	let mut tree_sitter_parser = tree_sitter::Parser::new();
+	tree_sitter_parser.set_language(&language).unwrap();
+
+	let tree_sitter_tree = match tree_sitter_parser.parse(source_text.as_bytes(), old_tree) {
+		Some(tree) => tree,
+		None => {
+			panic!("Error parsing source file with tree-sitter: {}", source_file);
+		}
+	};
+
+	// Building the AST is what reports most syntax errors. Its node ids don't matter since it's thrown away.
+	let ast_ctx = AstContext::new();
+	let workspaces = WorkspaceCache::default();
+	let parser = Parser::new(source_text.as_bytes(), source_file.to_owned(), &ast_ctx, &workspaces);
+	parser.parse(&tree_sitter_tree.root_node());
+
+	tree_sitter_tree
+}
+
 /// Returns true if the directory contains any Wing source files (.w), either directly
 /// in the directory or in any subdirectories.
-fn dir_contains_wing_file_recursive(dir_path: &Utf8Path) -> bool {
+pub(crate) fn dir_contains_wing_file_recursive(dir_path: &Utf8Path) -> bool {
 	for entry in fs::read_dir(dir_path).unwrap() {
 		let entry = entry.unwrap().path();
 		let path = Utf8Path::from_path(&entry).unwrap();
@@ -327,6 +510,7 @@ fn check_valid_wing_dir_name(dir_path: &Utf8Path) -> bool {
 fn parse_wing_directory(
 	source_file: &File,
 	source_ref: &WingSpan,
+	ast_ctx: &AstContext,
 	files: &mut Files,
 	file_graph: &mut FileGraph,
 	tree_sitter_trees: &mut IndexMap<Utf8PathBuf, tree_sitter::Tree>,
@@ -348,6 +532,7 @@ fn parse_wing_directory(
 	for entry in fs::read_dir(&source_file.path).expect("read_dir call failed") {
 		let entry = entry.unwrap();
 		let path = Utf8PathBuf::from_path_buf(entry.path()).expect("invalid utf8 path");
+		let path = resolve_symlinks(&path, &source_file.path);
 
 		// If it's a directory and its name is not node_modules or .git or ending in .tmp, add it
 		// or if it's a file and its extension is .w, add it
@@ -381,7 +566,7 @@ fn parse_wing_directory(
 	let mut tree_sitter_parser = tree_sitter::Parser::new();
 	tree_sitter_parser.set_language(&tree_sitter_wing::language()).unwrap();
 	let tree_sitter_tree = tree_sitter_parser.parse("", None).unwrap();
-	let scope = Scope::empty();
+	let scope = Scope::empty(ast_ctx);
 	let dependent_wing_paths = files_and_dirs;
 
 	// Update our collections of trees and ASTs and our file graph
@@ -393,11 +578,18 @@ fn parse_wing_directory(
 	dependent_wing_paths
 }
 
+/// The roots of the libraries found by a file's bring statements, with the spans of the statements
+pub type FoundLibraryRoots = HashMap<String, (Utf8PathBuf, WingSpan)>;
+
 /// Parses a single Wing source file.
 pub struct Parser<'a> {
 	/// Source code of the file being parsed
 	pub source: &'a [u8],
 	pub source_file: File,
+	/// Allocates the identifiers of the AST nodes created by the parser
+	ast_ctx: &'a AstContext,
+	/// The workspaces of the project, shared by the parsers of all its files
+	workspaces: &'a WorkspaceCache,
 
 	pub error_nodes: RefCell<HashSet<usize>>,
 	// Nesting level within JSON literals, a value larger than 0 means we're currently in a JSON literal
@@ -409,8 +601,9 @@ pub struct Parser<'a> {
 	/// These will need to be eventually parsed (or diagnostics will be reported if they don't exist)
 	referenced_wing_files: RefCell<Vec<(File, WingSpan)>>,
 
-	/// Track the roots of all libraries that have been found while parsing the current file
-	found_library_roots: RefCell<HashMap<String, Utf8PathBuf>>,
+	/// Track the roots of all libraries that have been found while parsing the current file, along with the spans of the
+	/// bring statements that found them
+	found_library_roots: RefCell<FoundLibraryRoots>,
 }
 
 struct ParseErrorBuilder<'s> {
@@ -433,6 +626,11 @@ impl<'a> ParseErrorBuilder<'a> {
 		self
 	}
 
+	fn with_hint(mut self, hint: impl ToString) -> Self {
+		self.diag.add_hint(hint);
+		self
+	}
+
 	fn report(self) {
 		self.diag.report();
 		self.parser.error_nodes.borrow_mut().insert(self.node.id());
@@ -440,10 +638,12 @@ impl<'a> ParseErrorBuilder<'a> {
 }
 
 impl<'s> Parser<'s> {
-	pub fn new(source: &'s [u8], source_file: File) -> Self {
+	pub fn new(source: &'s [u8], source_file: File, ast_ctx: &'s AstContext, workspaces: &'s WorkspaceCache) -> Self {
 		Self {
 			source,
 			source_file,
+			ast_ctx,
+			workspaces,
 			error_nodes: RefCell::new(HashSet::new()),
 			is_in_loop: RefCell::new(false),
 			// This is similar to what we do in the type_checker, but we need to know 2 things when
@@ -457,10 +657,10 @@ impl<'s> Parser<'s> {
 		}
 	}
 
-	pub fn parse(self, root: &Node) -> (Scope, Vec<(File, WingSpan)>, HashMap<String, Utf8PathBuf>) {
+	pub fn parse(self, root: &Node) -> (Scope, Vec<(File, WingSpan)>, FoundLibraryRoots) {
 		let scope = match root.kind() {
 			"source" => self.build_scope(&root, Phase::Preflight),
-			_ => Scope::empty(),
+			_ => Scope::empty(self.ast_ctx),
 		};
 
 		// Module files can only have certain kinds of statements
@@ -468,7 +668,7 @@ impl<'s> Parser<'s> {
 			for stmt in &scope.statements {
 				if !is_valid_module_statement(&stmt) {
 					Diagnostic::new(
-						"Module files cannot have statements besides classes, interfaces, enums, and structs. Rename the file to end with `.main.w` or `.test.w` to make this an entrypoint file.",
+						"Module files cannot have statements besides classes, interfaces, enums, structs, public or internal variables and functions. Rename the file to end with `.main.w` or `.test.w` to make this an entrypoint file.",
 						stmt,
 					).report();
 				}
@@ -492,6 +692,39 @@ impl<'s> Parser<'s> {
 		ParseErrorBuilder::new(message, node, self)
 	}
 
+	fn report_package_not_found(&self, message: String, source_dir: &Utf8Path, node: &Node) {
+		let mut error = self.build_error(message, node);
+		// Packages installed with yarn Plug'n'Play are kept in zip archives we can't read
+		if wingii::node_resolve::is_yarn_pnp_project(source_dir) {
+			error = error.with_hint(
+				"This project is installed with yarn Plug'n'Play. Wing libraries must be unplugged (with `dependenciesMeta` in package.json), or installed with `nodeLinker: node-modules` or `nodeLinker: pnpm` in .yarnrc.yml.",
+			);
+		}
+		error.report();
+	}
+
+	/// Reports an error if a brought Wing library doesn't support the current compiler, so it isn't parsed and
+	/// type checked (which would fail with errors that are harder to make sense of)
+	fn check_library_compiler_version(&self, library_name: &str, library_dir: &Utf8Path, node: &Node) -> Result<(), ()> {
+		let Err(incompatible) = check_library_compiler_version(library_dir) else {
+			return Ok(());
+		};
+		Diagnostic::new(
+			format!(
+				"Library \"{library_name}\" requires Wing compiler version \"{}\", but this is version {}",
+				incompatible.range, incompatible.compiler_version
+			),
+			&self.node_span(node),
+		)
+		.hint(format!(
+			"The requirement comes from {}. Upgrade the compiler with `npm i -g winglang`, or install a version of the library that supports this compiler.",
+			incompatible.declared_in
+		))
+		.report();
+		self.error_nodes.borrow_mut().insert(node.id());
+		Err(())
+	}
+
 	fn with_error<T>(&self, message: impl ToString, node: &Node) -> Result<T, ()> {
 		self.with_error_builder::<T>(self.build_error(message, node))
 	}
@@ -578,10 +811,13 @@ impl<'s> Parser<'s> {
 		let span = self.node_span(node);
 		// represent duration literals as the AST equivalent of `duration.fromSeconds(value)`
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::Call {
 				callee: CalleeKind::Expr(Box::new(Expr::new(
+					self.ast_ctx,
 					ExprKind::Reference(Reference::InstanceMember {
 						object: Box::new(Expr::new(
+							self.ast_ctx,
 							ExprKind::Reference(Reference::Identifier(Symbol {
 								name: "duration".to_string(),
 								span: span.clone(),
@@ -596,8 +832,14 @@ impl<'s> Parser<'s> {
 					}),
 					span.clone(),
 				))),
+				optional_accessor: false,
 				arg_list: ArgList::new(
-					vec![Expr::new(ExprKind::Literal(Literal::Number(seconds)), span.clone())],
+					self.ast_ctx,
+					vec![Expr::new(
+						self.ast_ctx,
+						ExprKind::Literal(Literal::Number(seconds)),
+						span.clone(),
+					)],
 					IndexMap::new(),
 					span.clone(),
 				),
@@ -636,8 +878,13 @@ impl<'s> Parser<'s> {
 			if let Ok(stmt) = self.build_statement(&child, statements.len(), phase, doc) {
 				statements.push(stmt);
 			}
+			if child.kind() == "struct_definition" {
+				if let Some(stmt) = self.build_struct_statics_class(&child, statements.len()) {
+					statements.push(stmt);
+				}
+			}
 		}
-		Scope::new(statements, span)
+		Scope::new(self.ast_ctx, statements, span)
 	}
 
 	fn build_statement(
@@ -653,6 +900,7 @@ impl<'s> Parser<'s> {
 			"import_statement" => self.build_bring_statement(statement_node)?,
 
 			"variable_definition_statement" => self.build_variable_def_statement(statement_node, phase)?,
+			"function_definition" => self.build_function_statement(statement_node, phase, doc.clone())?,
 			"variable_assignment_statement" => {
 				let kind = match self.node_text(&statement_node.child_by_field_name("operator").unwrap()) {
 					"=" => AssignmentKind::Assign,
@@ -671,6 +919,7 @@ impl<'s> Parser<'s> {
 			"if_let_statement" => self.build_if_let_statement(statement_node, phase)?,
 			"for_in_loop" => self.build_for_statement(statement_node, phase)?,
 			"while_statement" => self.build_while_statement(statement_node, phase)?,
+			"while_let_statement" => self.build_while_let_statement(statement_node, phase)?,
 			"break_statement" => self.build_break_statement(statement_node)?,
 			"continue_statement" => self.build_continue_statement(statement_node)?,
 			"return_statement" => self.build_return_statement(statement_node, phase)?,
@@ -805,9 +1054,23 @@ impl<'s> Parser<'s> {
 		})
 	}
 
+	fn build_while_let_statement(&self, statement_node: &Node, phase: Phase) -> DiagnosticResult<StmtKind> {
+		Ok(StmtKind::WhileLet {
+			reassignable: statement_node.child_by_field_name("reassignable").is_some(),
+			var_name: self.check_reserved_symbol(&statement_node.child_by_field_name("name").unwrap())?,
+			value: self.build_expression(&statement_node.child_by_field_name("value").unwrap(), phase)?,
+			statements: self.build_in_loop_scope(&statement_node.child_by_field_name("block").unwrap(), phase),
+		})
+	}
+
 	fn build_for_statement(&self, statement_node: &Node, phase: Phase) -> DiagnosticResult<StmtKind> {
 		Ok(StmtKind::ForLoop {
 			iterator: self.check_reserved_symbol(&statement_node.child_by_field_name("iterator").unwrap())?,
+			second_iterator: if let Some(second_iterator) = statement_node.child_by_field_name("second_iterator") {
+				Some(self.check_reserved_symbol(&second_iterator)?)
+			} else {
+				None
+			},
 			iterable: self.build_expression(&statement_node.child_by_field_name("iterable").unwrap(), phase)?,
 			statements: self.build_in_loop_scope(&statement_node.child_by_field_name("block").unwrap(), phase),
 		})
@@ -839,6 +1102,21 @@ impl<'s> Parser<'s> {
 		let value = self.build_expression(&statement_node.child_by_field_name("value").unwrap(), phase)?;
 		let name = self.check_reserved_symbol(&statement_node.child_by_field_name("name").unwrap())?;
 
+		let mut conditions = vec![];
+		let mut cursor = statement_node.walk();
+		for node in statement_node.children_by_field_name("condition", &mut cursor) {
+			let condition = if node.kind() == "if_let_binding" {
+				IfLetCondition::Let {
+					reassignable: node.child_by_field_name("reassignable").is_some(),
+					var_name: self.check_reserved_symbol(&node.child_by_field_name("name").unwrap())?,
+					value: self.build_expression(&node.child_by_field_name("value").unwrap(), phase)?,
+				}
+			} else {
+				IfLetCondition::Guard(self.build_expression(&node, phase)?)
+			};
+			conditions.push(condition);
+		}
+
 		let mut else_if_vec = vec![];
 		let mut cursor = statement_node.walk();
 		for node in statement_node.children(&mut cursor) {
@@ -877,6 +1155,7 @@ impl<'s> Parser<'s> {
 			var_name: name,
 			reassignable,
 			value,
+			conditions,
 			statements: if_block,
 			else_if_statements: else_if_vec,
 			else_statements: else_block,
@@ -989,17 +1268,149 @@ impl<'s> Parser<'s> {
 		}))
 	}
 
+	/// Static methods declared in a struct's body are desugared into a phase independent class named after the struct
+	/// (see `struct_statics_class_name`). References to `MyStruct.method` are resolved against this class by the
+	/// type checker, so struct statics are compiled and lifted just like class statics. Methods without a phase
+	/// specifier are phase independent and can be called from both preflight and inflight code. The class itself is an
+	/// implementation detail and is hidden from completions, type declarations and docs.
+	fn build_struct_statics_class(&self, statement_node: &Node, idx: usize) -> Option<Stmt> {
+		let method_nodes = get_actual_children_by_field_name(*statement_node, "method");
+		if method_nodes.is_empty() {
+			return None;
+		}
+		let struct_name = self.node_symbol(&statement_node.child_by_field_name("name")?).ok()?;
+		let name = Symbol::new(struct_statics_class_name(&struct_name.name), struct_name.span());
+
+		let mut cursor = statement_node.walk();
+		let mut methods = vec![];
+		let mut doc_builder = DocBuilder::new(self);
+
+		for member_node in statement_node.named_children(&mut cursor) {
+			let DocBuilderResult::Done(doc) = doc_builder.process_node(&member_node) else {
+				continue;
+			};
+			if member_node.kind() != "method_definition" {
+				continue;
+			}
+			let Ok(method_name) = self.node_symbol(&member_node.child_by_field_name("name").unwrap()) else {
+				continue;
+			};
+			// Struct methods are phase independent unless marked `inflight`
+			let Ok(phase) = self.get_phase_specifier(&member_node.child_by_field_name("modifiers")) else {
+				continue;
+			};
+			let Ok(func_def) = self.build_function_definition_in_phase(
+				Some(method_name.clone()),
+				&member_node,
+				phase.unwrap_or(Phase::Independent),
+				false,
+				doc,
+			) else {
+				continue;
+			};
+
+			if !func_def.is_static {
+				Diagnostic::new("Struct methods must be static", &method_name).report();
+			}
+			if func_def.access == AccessModifier::Protected {
+				Diagnostic::new(
+					"Struct methods must be public (\"pub\"), internal or private",
+					&method_name,
+				)
+				.report();
+			}
+			// make sure all the parameters have type annotations
+			for param in &func_def.signature.parameters {
+				if matches!(param.type_annotation.kind, TypeAnnotationKind::Inferred) {
+					Diagnostic::new("Missing required type annotation for method signature", &param.name).report();
+				}
+			}
+
+			methods.push((method_name, func_def));
+		}
+
+		let access_modifier_node = statement_node.child_by_field_name("access_modifier");
+		let access = match self.build_access_modifier(&access_modifier_node) {
+			// an error was already reported for the struct itself
+			AccessModifier::Protected => AccessModifier::Private,
+			access => access,
+		};
+
+		Some(Stmt {
+			kind: StmtKind::Class(Class {
+				name: name.clone(),
+				span: self.node_span(statement_node),
+				fields: vec![],
+				methods,
+				initializer: self.build_default_initializer(&name, Phase::Preflight),
+				inflight_initializer: self.build_default_initializer(&name, Phase::Inflight),
+				parent: None,
+				implements: vec![],
+				phase: Phase::Independent,
+				access,
+				auto_id: false,
+			}),
+			span: self.node_span(statement_node),
+			idx,
+			doc: None,
+		})
+	}
+
 	fn build_variable_def_statement(&self, statement_node: &Node, phase: Phase) -> DiagnosticResult<StmtKind> {
 		let type_ = if let Some(type_node) = get_actual_child_by_field_name(*statement_node, "type") {
 			Some(self.build_type_annotation(Some(type_node), phase)?)
 		} else {
 			None
 		};
+		let access_modifier_node = statement_node.child_by_field_name("access_modifier");
+		let access = self.build_access_modifier(&access_modifier_node);
+		if access == AccessModifier::Protected {
+			self.with_error::<Node>(
+				"Variables must be public (\"pub\"), internal or private",
+				&access_modifier_node.expect("access modifier node"),
+			)?;
+		}
 		Ok(StmtKind::Let {
 			reassignable: statement_node.child_by_field_name("reassignable").is_some(),
 			var_name: self.check_reserved_symbol(&statement_node.child_by_field_name("name").unwrap())?,
 			initial_value: self.build_expression(&statement_node.child_by_field_name("value").unwrap(), phase)?,
 			type_,
+			access,
+		})
+	}
+
+	/// Builds a `fn name() {}` statement, which is sugar for defining an immutable variable holding a closure
+	fn build_function_statement(
+		&self,
+		statement_node: &Node,
+		phase: Phase,
+		doc: Option<String>,
+	) -> DiagnosticResult<StmtKind> {
+		let var_name = self.check_reserved_symbol(&statement_node.child_by_field_name("name").unwrap())?;
+		let mut func_def = self.build_function_definition(None, statement_node, phase, false, doc)?;
+
+		// The visibility belongs to the variable, the closure itself is always private
+		let access = func_def.access;
+		func_def.access = AccessModifier::Private;
+		if access == AccessModifier::Protected {
+			self.with_error::<Node>(
+				"Functions must be public (\"pub\"), internal or private",
+				&self
+					.get_modifier("access_modifier", &statement_node.child_by_field_name("modifiers"))?
+					.expect("access modifier node"),
+			)?;
+		}
+
+		Ok(StmtKind::Let {
+			reassignable: false,
+			var_name,
+			initial_value: Expr::new(
+				self.ast_ctx,
+				ExprKind::FunctionClosure(func_def),
+				self.node_span(statement_node),
+			),
+			type_: None,
+			access,
 		})
 	}
 
@@ -1035,6 +1446,8 @@ impl<'s> Parser<'s> {
 
 		if module_name.name.starts_with("\".") && module_name.name.ends_with("\"") {
 			let source_path = normalize_path(module_path, Some(&Utf8Path::new(&self.source_file.path)));
+			let source_dir = Utf8Path::new(&self.source_file.path).parent().unwrap();
+			let source_path = resolve_symlinks(&source_path, source_dir);
 			if source_path == Utf8Path::new(&self.source_file.path) {
 				return self.with_error("Cannot bring a module into itself", &module_name_node);
 			}
@@ -1115,31 +1528,64 @@ impl<'s> Parser<'s> {
 			);
 		}
 
+		if module_name.name.starts_with(&format!("\"{NPM_MODULE_PREFIX}")) && module_name.name.ends_with("\"") {
+			let package_name = module_name.name[NPM_MODULE_PREFIX.len() + 1..module_name.name.len() - 1].to_string();
+			let source_dir = Utf8Path::new(&self.source_file.path).parent().unwrap();
+			if wingii::util::package_json::find_dependency_directory(&package_name, &source_dir).is_none() {
+				return self.with_error(
+					format!(
+						"Unable to load \"{}\": Module not found in \"{}\"",
+						package_name, self.source_file
+					),
+					&statement_node,
+				);
+			}
+
+			return if let Some(alias) = alias {
+				Ok(StmtKind::Bring {
+					source: BringSource::NpmModule(Symbol {
+						name: package_name,
+						span: module_name.span,
+					}),
+					identifier: Some(alias),
+				})
+			} else {
+				self.with_error::<StmtKind>(
+					format!(
+						"bring {} must be assigned to an identifier (e.g. bring \"foo\" as foo)",
+						module_name
+					),
+					statement_node,
+				)
+			};
+		}
+
 		if module_name.name.starts_with("\"") && module_name.name.ends_with("\"") {
 			// we need to inspect the npm dependency to figure out if it's a JSII library or a Wing library
 			// first, find where the package.json is located
 			let module_name_parsed = module_name.name[1..module_name.name.len() - 1].to_string();
 			let source_dir = Utf8Path::new(&self.source_file.path).parent().unwrap();
-			let module_dir = wingii::util::package_json::find_dependency_directory(&module_name_parsed, &source_dir)
-				.ok_or_else(|| {
-					self
-						.with_error::<Node>(
-							format!(
-								"Unable to load {}: Module not found in \"{}\"",
-								module_name, self.source_file
-							),
-							&statement_node,
-						)
-						.err();
-				})?;
+			let module_dir = find_package_directory(&module_name_parsed, &source_dir, self.workspaces).ok_or_else(|| {
+				self.report_package_not_found(
+					format!(
+						"Unable to load {}: Module not found in \"{}\"",
+						module_name, self.source_file
+					),
+					&source_dir,
+					&statement_node,
+				);
+			})?;
+			// Packages linked into node_modules (e.g. `file:` dependencies) are known by the path they're linked from
+			let module_dir = resolve_symlinks(&module_dir, source_dir);
 
-			self
-				.found_library_roots
-				.borrow_mut()
-				.insert(module_name_parsed.clone(), module_dir.clone());
+			self.found_library_roots.borrow_mut().insert(
+				module_name_parsed.clone(),
+				(module_dir.clone(), self.node_span(statement_node)),
+			);
 
 			// If the package.json has a `wing` field, then we treat it as a Wing library
 			if let Some(libname) = as_wing_library(&Utf8Path::new(&module_dir), true) {
+				self.check_library_compiler_version(&module_name_parsed, &module_dir, statement_node)?;
 				return if let Some(alias) = alias {
 					// make sure the Wing library is also parsed
 					let module_file = File::new(&module_dir, &libname);
@@ -1199,26 +1645,24 @@ impl<'s> Parser<'s> {
 		// check if a trusted library exists with this name
 		let source_dir = Utf8Path::new(&self.source_file.path).parent().unwrap();
 		let package_name = format!("{}/{}", TRUSTED_LIBRARY_NPM_NAMESPACE, module_name.name);
-		let module_dir = wingii::util::package_json::find_dependency_directory(
-			&format!("{}/{}", TRUSTED_LIBRARY_NPM_NAMESPACE, module_name.name),
-			&source_dir,
-		)
-		.ok_or_else(|| {
-			self
-				.with_error::<Node>(
-					format!(
-						"Could not find a trusted library \"{}/{}\" installed. Did you mean to run `npm i {}/{}`?",
-						TRUSTED_LIBRARY_NPM_NAMESPACE, module_name, TRUSTED_LIBRARY_NPM_NAMESPACE, module_name
-					),
-					&statement_node,
-				)
-				.err();
+		let module_dir = find_package_directory(&package_name, &source_dir, self.workspaces).ok_or_else(|| {
+			self.report_package_not_found(
+				format!(
+					"Could not find a trusted library \"{}/{}\" installed. Did you mean to run `npm i {}/{}`?",
+					TRUSTED_LIBRARY_NPM_NAMESPACE, module_name, TRUSTED_LIBRARY_NPM_NAMESPACE, module_name
+				),
+				&source_dir,
+				&statement_node,
+			);
 		})?;
+		let module_dir = resolve_symlinks(&module_dir, source_dir);
+
+		self.check_library_compiler_version(&package_name, &module_dir, statement_node)?;
 
 		self
 			.found_library_roots
 			.borrow_mut()
-			.insert(package_name, module_dir.clone());
+			.insert(package_name, (module_dir.clone(), self.node_span(statement_node)));
 
 		// make sure the trusted library is also parsed
 		let module_file = File::new(
@@ -1250,8 +1694,11 @@ impl<'s> Parser<'s> {
 				.err();
 		}
 
+		let is_flags = statement_node.child_by_field_name("flags").is_some();
+
 		let mut cursor = statement_node.walk();
 		let mut values = IndexMap::<Symbol, Option<String>>::new();
+		let mut backing_values = IndexMap::<Symbol, String>::new();
 		let mut doc_builder = DocBuilder::new(self);
 
 		for node in statement_node.named_children(&mut cursor) {
@@ -1262,7 +1709,7 @@ impl<'s> Parser<'s> {
 				continue;
 			}
 
-			let diagnostic = self.node_symbol(&node);
+			let diagnostic = self.node_symbol(&node.child_by_field_name("name").unwrap());
 			if diagnostic.is_err() {
 				self.with_error::<Node>(String::from("Invalid enum value"), &node).err();
 				continue;
@@ -1274,6 +1721,46 @@ impl<'s> Parser<'s> {
 					.with_error::<Node>(format!("Duplicated enum value {}", symbol.name), &node)
 					.err();
 			}
+
+			if let Some(value_node) = node.child_by_field_name("value") {
+				if is_flags {
+					self
+						.with_error::<Node>("Values of a flags enum can't have backing values", &value_node)
+						.err();
+					continue;
+				}
+				if value_node.named_child_count() > 0 {
+					self
+						.with_error::<Node>("String interpolation is not allowed in enum values", &value_node)
+						.err();
+					continue;
+				}
+				let text = self.node_text(&value_node);
+				backing_values.insert(symbol, text[1..text.len() - 1].to_string());
+			}
+		}
+
+		// Values of a flags enum are backed by a bit each, and JavaScript's bitwise operators work on 32 bit integers
+		if is_flags && values.len() > MAX_FLAGS_ENUM_VALUES {
+			self
+				.with_error::<Node>(
+					format!("A flags enum can't have more than {MAX_FLAGS_ENUM_VALUES} values"),
+					statement_node,
+				)
+				.err();
+		}
+
+		// Values without an explicit backing value are backed by their name, so they can clash too
+		let mut seen_backing_values = HashSet::new();
+		for value in values.keys() {
+			let backing_value = backing_values.get(value).unwrap_or(&value.name);
+			if !seen_backing_values.insert(backing_value) {
+				Diagnostic::new(
+					format!("Duplicated enum backing value \"{backing_value}\""),
+					&value.span,
+				)
+				.report();
+			}
 		}
 
 		let access_modifier_node = statement_node.child_by_field_name("access_modifier");
@@ -1288,6 +1775,8 @@ impl<'s> Parser<'s> {
 		Ok(StmtKind::Enum(Enum {
 			name: name.unwrap(),
 			values,
+			backing_values,
+			is_flags,
 			access,
 		}))
 	}
@@ -1490,56 +1979,10 @@ impl<'s> Parser<'s> {
 			}
 		}
 
-		let initializer = match initializer {
-			Some(init) => init,
-			// add a default initializer if none is defined
-			None => FunctionDefinition {
-				name: Some(CLASS_INIT_NAME.into()),
-				signature: FunctionSignature {
-					parameters: vec![],
-					return_type: Box::new(TypeAnnotation {
-						kind: TypeAnnotationKind::UserDefined(UserDefinedType {
-							root: name.clone(),
-							fields: vec![],
-							span: name.span(),
-						}),
-						span: name.span(),
-					}),
-					phase: Phase::Preflight,
-				},
-				body: FunctionBody::Statements(Scope::new(vec![], name.span())),
-				is_static: false,
-				span: name.span(),
-				access: AccessModifier::Public,
-				doc: None,
-			},
-		};
-
-		let inflight_initializer = match inflight_initializer {
-			Some(init) => init,
-
-			// add a default inflight initializer if none is defined
-			None => FunctionDefinition {
-				name: Some(CLASS_INFLIGHT_INIT_NAME.into()),
-				signature: FunctionSignature {
-					parameters: vec![],
-					return_type: Box::new(TypeAnnotation {
-						kind: TypeAnnotationKind::UserDefined(UserDefinedType {
-							root: name.clone(),
-							fields: vec![],
-							span: name.span(),
-						}),
-						span: name.span(),
-					}),
-					phase: Phase::Inflight,
-				},
-				body: FunctionBody::Statements(Scope::new(vec![], name.span())),
-				is_static: false,
-				span: name.span(),
-				access: AccessModifier::Public,
-				doc: None,
-			},
-		};
+		// add default initializers if none are defined
+		let initializer = initializer.unwrap_or_else(|| self.build_default_initializer(&name, Phase::Preflight));
+		let inflight_initializer =
+			inflight_initializer.unwrap_or_else(|| self.build_default_initializer(&name, Phase::Inflight));
 
 		let parent = if let Some(parent_node) = get_actual_child_by_field_name(*statement_node, "parent") {
 			let parent_type = self.build_type_annotation(Some(parent_node), class_phase)?;
@@ -1599,6 +2042,34 @@ impl<'s> Parser<'s> {
 		}))
 	}
 
+	/// Builds an empty constructor for a class that doesn't define one
+	fn build_default_initializer(&self, class_name: &Symbol, phase: Phase) -> FunctionDefinition {
+		let name = if phase == Phase::Inflight {
+			CLASS_INFLIGHT_INIT_NAME
+		} else {
+			CLASS_INIT_NAME
+		};
+		FunctionDefinition {
+			name: Some(name.into()),
+			signature: FunctionSignature {
+				parameters: vec![],
+				return_type: Box::new(TypeAnnotation {
+					kind: TypeAnnotationKind::UserDefined(UserDefinedType {
+						root: class_name.clone(),
+						fields: vec![],
+						span: class_name.span(),
+					}),
+					span: class_name.span(),
+				}),
+				phase,
+			},
+			body: FunctionBody::Statements(Scope::new(self.ast_ctx, vec![], class_name.span())),
+			is_static: false,
+			span: class_name.span(),
+			access: AccessModifier::Public,
+			doc: None,
+		}
+	}
 	fn build_class_field(
 		&self,
 		class_element: Node<'_>,
@@ -1614,6 +2085,7 @@ impl<'s> Parser<'s> {
 				annotations: vec![],
 				hints: vec![],
 				severity: DiagnosticSeverity::Error,
+				code: None,
 			});
 		}
 
@@ -1687,8 +2159,9 @@ impl<'s> Parser<'s> {
 							.with_error::<Node>("Unphased methods on interfaces are not yet supported - see https://github.com/winglang/wing/issues/435", &statement_node)
 							.err();
 					}
+					let is_static = self.get_modifier("static", &modifiers)?.is_some();
 					if let Ok((method_name, func_sig)) = self.build_interface_method(interface_element, method_phase) {
-						methods.push((method_name, func_sig, doc))
+						methods.push((method_name, func_sig, doc, is_static))
 					}
 				}
 				"class_field" => {
@@ -1815,6 +2288,7 @@ impl<'s> Parser<'s> {
 
 	fn build_anonymous_closure(&self, anon_closure_node: &Node, scope_phase: Phase) -> DiagnosticResult<Expr> {
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::FunctionClosure(self.build_function_definition(None, anon_closure_node, scope_phase, false, None)?),
 			self.node_span(&anon_closure_node),
 		))
@@ -1843,10 +2317,38 @@ impl<'s> Parser<'s> {
 				.err();
 		}
 
+		self.build_function_definition_in_phase(name, func_def_node, phase, require_annotations, doc)
+	}
+
+	/// Builds a function definition whose phase was already determined by the caller
+	fn build_function_definition_in_phase(
+		&self,
+		name: Option<Symbol>,
+		func_def_node: &Node,
+		phase: Phase,
+		require_annotations: bool,
+		doc: Option<String>,
+	) -> DiagnosticResult<FunctionDefinition> {
+		let modifiers = func_def_node.child_by_field_name("modifiers");
 		let is_static = self.get_modifier("static", &modifiers)?.is_some();
 
-		let signature = self.build_function_signature(func_def_node, phase, require_annotations)?;
-		let statements = if let Some(external) = self.get_modifier("extern_modifier", &modifiers)? {
+		let access = self.get_access_modifier(&modifiers)?;
+		let mut signature = self.build_function_signature(func_def_node, phase, require_annotations)?;
+		let external = self.get_modifier("extern_modifier", &modifiers)?;
+
+		// Private methods and functions may omit their return type, in which case it's inferred from their body.
+		// Anything visible outside of its scope must have an explicit return type to keep its API stable.
+		if access == AccessModifier::Private
+			&& external.is_none()
+			&& get_actual_child_by_field_name(*func_def_node, "type").is_none()
+		{
+			signature.return_type = Box::new(TypeAnnotation {
+				kind: TypeAnnotationKind::Inferred,
+				span: Default::default(),
+			});
+		}
+
+		let statements = if let Some(external) = external {
 			let node_text = self.node_text(&external.named_child(0).unwrap());
 			let file_path = Utf8Path::new(&node_text[1..node_text.len() - 1]);
 			let file_path = normalize_path(file_path, Some(&Utf8Path::new(&self.source_file.path)));
@@ -1873,7 +2375,7 @@ impl<'s> Parser<'s> {
 			signature,
 			is_static,
 			span: self.node_span(func_def_node),
-			access: self.get_access_modifier(&modifiers)?,
+			access,
 			doc,
 		})
 	}
@@ -2036,6 +2538,14 @@ impl<'s> Parser<'s> {
 					kind: TypeAnnotationKind::Void,
 					span,
 				}),
+				"never" => Ok(TypeAnnotation {
+					kind: TypeAnnotationKind::Never,
+					span,
+				}),
+				"unknown" => Ok(TypeAnnotation {
+					kind: TypeAnnotationKind::Unknown,
+					span,
+				}),
 				"ERROR" => self.with_error("Expected builtin type", type_node),
 				other => return self.report_unimplemented_grammar(other, "builtin", type_node),
 			},
@@ -2047,6 +2557,29 @@ impl<'s> Parser<'s> {
 				})
 			}
 			"custom_type" => Ok(self.build_udt_annotation(&type_node)?),
+			"literal_type" => {
+				let mut cursor = type_node.walk();
+				let mut values = vec![];
+				for value_node in type_node.children_by_field_name("value", &mut cursor) {
+					match value_node.kind() {
+						"string" => {
+							if value_node.named_child_count() > 0 {
+								self
+									.with_error::<Node>("String interpolation is not allowed in literal types", &value_node)
+									.err();
+								continue;
+							}
+							values.push(LiteralTypeValue::String(self.node_text(&value_node).into()));
+						}
+						"number" => values.push(LiteralTypeValue::Number(parse_number(self.node_text(&value_node)))),
+						other => return self.report_unimplemented_grammar(other, "literal type", &value_node),
+					}
+				}
+				Ok(TypeAnnotation {
+					kind: TypeAnnotationKind::Literal(values),
+					span,
+				})
+			}
 			"function_type" => {
 				let param_type_list_node = type_node.child_by_field_name("parameter_types").unwrap();
 				let mut cursor = param_type_list_node.walk();
@@ -2148,8 +2681,9 @@ impl<'s> Parser<'s> {
 		let object_expr = self.get_child_field(nested_node, "object")?;
 
 		if let Some(property) = nested_node.child_by_field_name("property") {
-			if object_expr.kind() == "json_container_type" {
+			if matches!(object_expr.kind(), "json_container_type" | "container_static_type") {
 				Ok(Expr::new(
+					self.ast_ctx,
 					ExprKind::Reference(Reference::TypeMember {
 						type_name: UserDefinedType {
 							root: Symbol::global(WINGSDK_STD_MODULE),
@@ -2168,6 +2702,7 @@ impl<'s> Parser<'s> {
 					_ => false,
 				};
 				Ok(Expr::new(
+					self.ast_ctx,
 					ExprKind::Reference(Reference::InstanceMember {
 						object: Box::new(object_expr),
 						property: self.node_symbol(&property)?,
@@ -2217,12 +2752,24 @@ impl<'s> Parser<'s> {
 		let actual_node = reference_node.named_child(0).unwrap();
 		let actual_node_span = self.node_span(&actual_node);
 		match actual_node.kind() {
+			// The missing identifier is reported as a syntax error, so it shouldn't also be reported as an unknown symbol
+			"reference_identifier" if actual_node.is_missing() => {
+				Ok(Expr::new(self.ast_ctx, ExprKind::Error, actual_node_span))
+			}
 			"reference_identifier" => Ok(Expr::new(
+				self.ast_ctx,
 				ExprKind::Reference(Reference::Identifier(self.node_symbol(&actual_node)?)),
 				actual_node_span,
 			)),
 			"nested_identifier" => Ok(self.build_nested_identifier(&actual_node, phase)?),
 			"structured_access_expression" => Ok(self.build_structured_access_expression(&actual_node, phase)?),
+			"super_member" => Ok(Expr::new(
+				self.ast_ctx,
+				ExprKind::Reference(Reference::SuperMember {
+					property: self.node_symbol(&actual_node.child_by_field_name("member").unwrap())?,
+				}),
+				actual_node_span,
+			)),
 			other => self.with_error(format!("Expected reference, got {other}"), &actual_node),
 		}
 	}
@@ -2235,9 +2782,13 @@ impl<'s> Parser<'s> {
 		let index_expr = self.build_expression(&index_expr, phase)?;
 
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::Reference(Reference::ElementAccess {
 				object: Box::new(object_expr),
 				index: Box::new(index_expr),
+				optional_accessor: structured_access_node
+					.child_by_field_name("optional_accessor")
+					.is_some(),
 			}),
 			self.node_span(structured_access_node),
 		))
@@ -2278,12 +2829,20 @@ impl<'s> Parser<'s> {
 			}
 		}
 
-		Ok(ArgList::new(pos_args, named_args, span))
+		Ok(ArgList::new(self.ast_ctx, pos_args, named_args, span))
 	}
 
 	fn build_expression(&self, exp_node: &Node, phase: Phase) -> DiagnosticResult<Expr> {
 		let expression_span = self.node_span(exp_node);
 		CompilationContext::set(CompilationPhase::Parsing, &expression_span);
+		// The error was already reported, so instead of dropping the whole statement we keep a placeholder
+		// in place of the broken expression. This way the rest of the statement is still type checked (and its
+		// declarations are still known to the code after it) while the code is being edited.
+		let expression = self.build_expression_kind(exp_node, phase);
+		Ok(expression.unwrap_or_else(|()| Expr::new(self.ast_ctx, ExprKind::Error, expression_span)))
+	}
+
+	fn build_expression_kind(&self, exp_node: &Node, phase: Phase) -> DiagnosticResult<Expr> {
 		let expression_node = &self.check_error(*exp_node, "expression")?;
 		match expression_node.kind() {
 			"new_expression" => self.build_new_expression(&expression_node, phase),
@@ -2296,6 +2855,7 @@ impl<'s> Parser<'s> {
 			"nil_value" => self.build_nil_expression(&expression_node, phase),
 			"bool" => self.build_bool_expression(&expression_node, phase),
 			"intrinsic" => self.build_intrinsic_expression(&expression_node, phase),
+			"inline_js" => self.build_inline_js_expression(&expression_node, phase),
 			"duration" => self.build_duration(&expression_node),
 			"reference" => self.build_reference(&expression_node, phase),
 			"positional_argument" => self.build_expression(&expression_node.named_child(0).unwrap(), phase),
@@ -2316,10 +2876,25 @@ impl<'s> Parser<'s> {
 	fn build_new_expression(&self, expression_node: &Node, phase: Phase) -> Result<Expr, ()> {
 		let class_udt = self.build_udt(&expression_node.child_by_field_name("class").unwrap())?;
 
+		// `inflight new Foo(...)` is sugar for `inflight (): Foo => { return new Foo(...); }`, so the arguments are
+		// evaluated inflight every time the factory is called
+		let factory_phase_node = expression_node.child_by_field_name("phase");
+		let is_factory = match self.build_phase(&factory_phase_node) {
+			Some(Phase::Inflight) => true,
+			Some(_) => {
+				return self.with_error(
+					"Only inflight classes can be instantiated by a factory, use \"inflight new\" instead",
+					&factory_phase_node.unwrap(),
+				)
+			}
+			None => false,
+		};
+		let phase = if is_factory { Phase::Inflight } else { phase };
+
 		let arg_list = if let Ok(args_node) = self.get_child_field(expression_node, "args") {
 			self.build_arg_list(&args_node, phase)
 		} else {
-			Ok(ArgList::new_empty(WingSpan::default()))
+			Ok(ArgList::new_empty(self.ast_ctx, WingSpan::default()))
 		};
 
 		let obj_id = if let Some(id_node) = expression_node.child_by_field_name("id") {
@@ -2333,19 +2908,52 @@ impl<'s> Parser<'s> {
 			None
 		};
 
-		Ok(Expr::new(
+		let span = self.node_span(expression_node);
+		let new_expr = Expr::new(
+			self.ast_ctx,
 			ExprKind::New(New {
-				class: class_udt,
+				class: class_udt.clone(),
 				obj_id,
 				arg_list: arg_list?,
 				obj_scope,
 			}),
-			self.node_span(expression_node),
+			span.clone(),
+		);
+		if !is_factory {
+			return Ok(new_expr);
+		}
+
+		let return_stmt = Stmt {
+			kind: StmtKind::Return(Some(new_expr)),
+			span: span.clone(),
+			idx: 0,
+			doc: None,
+		};
+		Ok(Expr::new(
+			self.ast_ctx,
+			ExprKind::FunctionClosure(FunctionDefinition {
+				name: None,
+				body: FunctionBody::Statements(Scope::new(self.ast_ctx, vec![return_stmt], span.clone())),
+				signature: FunctionSignature {
+					parameters: vec![],
+					return_type: Box::new(TypeAnnotation {
+						span: class_udt.span.clone(),
+						kind: TypeAnnotationKind::UserDefined(class_udt),
+					}),
+					phase: Phase::Inflight,
+				},
+				is_static: true,
+				span: span.clone(),
+				access: AccessModifier::Public,
+				doc: None,
+			}),
+			span,
 		))
 	}
 
 	fn build_binary_expression(&self, expression_node: &Node, phase: Phase) -> Result<Expr, ()> {
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::Binary {
 				left: Box::new(self.build_expression(&expression_node.child_by_field_name("left").unwrap(), phase)?),
 				right: Box::new(self.build_expression(&expression_node.child_by_field_name("right").unwrap(), phase)?),
@@ -2366,6 +2974,9 @@ impl<'s> Parser<'s> {
 					"\\" => BinaryOperator::FloorDiv,
 					"**" => BinaryOperator::Power,
 					"??" => BinaryOperator::UnwrapOr,
+					"in" => BinaryOperator::In,
+					"|" => BinaryOperator::BitwiseOr,
+					"&" => BinaryOperator::BitwiseAnd,
 					"ERROR" => self.with_error::<BinaryOperator>("Expected binary operator", expression_node)?,
 					other => return self.report_unimplemented_grammar(other, "binary operator", expression_node),
 				},
@@ -2376,6 +2987,7 @@ impl<'s> Parser<'s> {
 
 	fn build_unary_expression(&self, expression_node: &Node, phase: Phase) -> Result<Expr, ()> {
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::Unary {
 				op: match self.node_text(&expression_node.child_by_field_name("op").unwrap()) {
 					"-" => UnaryOperator::Minus,
@@ -2393,6 +3005,7 @@ impl<'s> Parser<'s> {
 		// skipping the first #
 		let byte_range = (expression_node.start_byte() + 1)..expression_node.end_byte();
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::Literal(Literal::NonInterpolatedString(
 				self.node_text_from_range(byte_range).into(),
 			)),
@@ -2404,6 +3017,7 @@ impl<'s> Parser<'s> {
 		let span = self.node_span(&expression_node);
 		if expression_node.named_child_count() == 0 {
 			Ok(Expr::new(
+				self.ast_ctx,
 				ExprKind::Literal(Literal::String(self.node_text(&expression_node).into())),
 				span,
 			))
@@ -2450,6 +3064,7 @@ impl<'s> Parser<'s> {
 			));
 
 			Ok(Expr::new(
+				self.ast_ctx,
 				ExprKind::Literal(Literal::InterpolatedString(InterpolatedString { parts })),
 				span,
 			))
@@ -2463,6 +3078,7 @@ impl<'s> Parser<'s> {
 			Some(false)
 		};
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::Range {
 				start: Box::new(
 					self.build_expression(
@@ -2488,6 +3104,7 @@ impl<'s> Parser<'s> {
 
 	fn build_number_expression(&self, expression_node: &Node, _phase: Phase) -> Result<Expr, ()> {
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::Literal(Literal::Number(parse_number(self.node_text(&expression_node)))),
 			self.node_span(&expression_node),
 		))
@@ -2495,6 +3112,7 @@ impl<'s> Parser<'s> {
 
 	fn build_nil_expression(&self, expression_node: &Node, _phase: Phase) -> Result<Expr, ()> {
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::Literal(Literal::Nil),
 			self.node_span(&expression_node),
 		))
@@ -2502,6 +3120,7 @@ impl<'s> Parser<'s> {
 
 	fn build_bool_expression(&self, expression_node: &Node, _phase: Phase) -> Result<Expr, ()> {
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::Literal(Literal::Boolean(match self.node_text(&expression_node) {
 				"true" => true,
 				"false" => false,
@@ -2526,22 +3145,62 @@ impl<'s> Parser<'s> {
 		}
 
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::Intrinsic(Intrinsic { name, arg_list, kind }),
 			self.node_span(&expression_node),
 		))
 	}
 
+	fn build_inline_js_expression(&self, expression_node: &Node, phase: Phase) -> Result<Expr, ()> {
+		let mut inputs = vec![];
+		if let Some(inputs_node) = expression_node.child_by_field_name("inputs") {
+			let mut cursor = inputs_node.walk();
+			for input_node in inputs_node.named_children(&mut cursor) {
+				inputs.push(Expr::new(
+					self.ast_ctx,
+					ExprKind::Reference(Reference::Identifier(self.node_symbol(&input_node)?)),
+					self.node_span(&input_node),
+				));
+			}
+		}
+		let return_type = if let Some(type_node) = expression_node.child_by_field_name("type") {
+			Some(self.build_type_annotation(Some(type_node), phase)?)
+		} else {
+			None
+		};
+		let code = self
+			.node_text(&self.get_child_field(expression_node, "code")?)
+			.to_string();
+
+		Ok(Expr::new(
+			self.ast_ctx,
+			ExprKind::InlineJs {
+				inputs,
+				return_type,
+				code,
+			},
+			self.node_span(&expression_node),
+		))
+	}
+
 	fn build_call_expression(&self, expression_node: &Node, phase: Phase) -> Result<Expr, ()> {
 		let caller_node = expression_node.child_by_field_name("caller").unwrap();
-		let callee = if caller_node.kind() == "super_call" {
-			CalleeKind::SuperCall(self.node_symbol(&caller_node.child_by_field_name("method").unwrap())?)
+		// Calls of `super.x` are kept apart from other calls since they're dispatched to the parent class
+		let super_member = Some(caller_node)
+			.filter(|node| node.kind() == "reference")
+			.and_then(|node| node.named_child(0))
+			.filter(|node| node.kind() == "super_member");
+		let callee = if let Some(super_member) = super_member {
+			CalleeKind::SuperCall(self.node_symbol(&super_member.child_by_field_name("member").unwrap())?)
 		} else {
 			CalleeKind::Expr(Box::new(self.build_expression(&caller_node, phase)?))
 		};
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::Call {
 				callee,
 				arg_list: self.build_arg_list(&expression_node.child_by_field_name("args").unwrap(), phase)?,
+				optional_accessor: expression_node.child_by_field_name("optional_accessor").is_some(),
 			},
 			self.node_span(&expression_node),
 		))
@@ -2569,6 +3228,7 @@ impl<'s> Parser<'s> {
 		}
 
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::ArrayLiteral {
 				items,
 				type_: array_type,
@@ -2598,7 +3258,11 @@ impl<'s> Parser<'s> {
 			let value = if let Some(value_node) = field_node.named_child(1) {
 				self.build_expression(&value_node, phase)?
 			} else {
-				Expr::new(ExprKind::Reference(Reference::Identifier(key.clone())), key.span())
+				Expr::new(
+					self.ast_ctx,
+					ExprKind::Reference(Reference::Identifier(key.clone())),
+					key.span(),
+				)
 			};
 			if fields.contains_key(&key) {
 				self.add_error(format!("Duplicate key {} in json object literal", key), &key_node);
@@ -2608,6 +3272,7 @@ impl<'s> Parser<'s> {
 		}
 
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::JsonMapLiteral { fields },
 			self.node_span(&expression_node),
 		))
@@ -2623,6 +3288,7 @@ impl<'s> Parser<'s> {
 		let fields = self.build_map_fields(expression_node, phase)?;
 
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::MapLiteral {
 				fields,
 				type_: map_type,
@@ -2658,7 +3324,11 @@ impl<'s> Parser<'s> {
 				.is_missing()
 		{
 			self.add_error("Json literal must have an element", &named_element_child.unwrap());
-			Expr::new(ExprKind::Literal(Literal::Number(0.0)), self.node_span(&element_node))
+			Expr::new(
+				self.ast_ctx,
+				ExprKind::Literal(Literal::Number(0.0)),
+				self.node_span(&element_node),
+			)
 		} else {
 			self.build_expression(&element_node, phase)?
 		};
@@ -2677,6 +3347,7 @@ impl<'s> Parser<'s> {
 
 		let element = Box::new(exp);
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::JsonLiteral { is_mut, element },
 			self.node_span(&expression_node),
 		))
@@ -2696,6 +3367,7 @@ impl<'s> Parser<'s> {
 			} else {
 				if let Ok(field_name) = &field_name {
 					Ok(Expr::new(
+						self.ast_ctx,
 						ExprKind::Reference(Reference::Identifier(field_name.clone())),
 						self.node_span(&field),
 					))
@@ -2714,6 +3386,7 @@ impl<'s> Parser<'s> {
 			}
 		}
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::StructLiteral { type_: type_?, fields },
 			self.node_span(&expression_node),
 		))
@@ -2722,6 +3395,7 @@ impl<'s> Parser<'s> {
 	fn build_optional_unwrap_expression(&self, expression_node: &Node, phase: Phase) -> Result<Expr, ()> {
 		let expression = self.build_expression(&expression_node.named_child(0).unwrap(), phase);
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::Unary {
 				op: UnaryOperator::OptionalUnwrap,
 				exp: Box::new(expression?),
@@ -2761,6 +3435,7 @@ impl<'s> Parser<'s> {
 			items.push(self.build_expression(&element_node, phase)?);
 		}
 		Ok(Expr::new(
+			self.ast_ctx,
 			ExprKind::SetLiteral { items, type_: set_type },
 			expression_span,
 		))
@@ -2799,7 +3474,7 @@ impl<'s> Parser<'s> {
 				let end_point: WingLocation = target_node.end_point.into();
 
 				let diag = Diagnostic {
-					message: ERR_EXPECTED_SEMICOLON.to_string(),
+					message: EXPECTED_SEMICOLON.text(),
 					span: Some(WingSpan {
 						start: end_point,
 						end: end_point,
@@ -2810,6 +3485,7 @@ impl<'s> Parser<'s> {
 					annotations: vec![],
 					hints: vec![],
 					severity: DiagnosticSeverity::Error,
+					code: Some(EXPECTED_SEMICOLON.code),
 				};
 				report_diagnostic(diag);
 			} else if node.kind() == "AUTOMATIC_BLOCK" {
@@ -2829,6 +3505,7 @@ impl<'s> Parser<'s> {
 					annotations: vec![],
 					hints: vec![],
 					severity: DiagnosticSeverity::Error,
+					code: None,
 				};
 				report_diagnostic(diag);
 			} else if !self.error_nodes.borrow().contains(&node.id()) {
@@ -2850,6 +3527,7 @@ impl<'s> Parser<'s> {
 						annotations: vec![],
 						hints: vec![],
 						severity: DiagnosticSeverity::Error,
+						code: None,
 					};
 					report_diagnostic(diag);
 				}
@@ -2868,6 +3546,7 @@ impl<'s> Parser<'s> {
 		let name_node = statement_node.child_by_field_name("name").unwrap();
 		let name_text = self.node_text(&name_node);
 		let test_id = Box::new(Expr::new(
+			self.ast_ctx,
 			ExprKind::Literal(Literal::String(format!(
 				"\"test:{}\"",
 				&name_text[1..name_text.len() - 1]
@@ -2879,6 +3558,7 @@ impl<'s> Parser<'s> {
 		let span = self.node_span(statement_node);
 
 		let inflight_closure = Expr::new(
+			self.ast_ctx,
 			ExprKind::FunctionClosure(FunctionDefinition {
 				name: None,
 				body: FunctionBody::Statements(statements),
@@ -2900,6 +3580,7 @@ impl<'s> Parser<'s> {
 
 		let type_span = self.node_span(&statement_node.child(0).unwrap());
 		Ok(StmtKind::Expression(Expr::new(
+			self.ast_ctx,
 			ExprKind::New(New {
 				class: UserDefinedType {
 					root: Symbol::global(WINGSDK_STD_MODULE),
@@ -2908,7 +3589,7 @@ impl<'s> Parser<'s> {
 				},
 				obj_id: Some(test_id),
 				obj_scope: None,
-				arg_list: ArgList::new(vec![inflight_closure], IndexMap::new(), type_span.clone()),
+				arg_list: ArgList::new(self.ast_ctx, vec![inflight_closure], IndexMap::new(), type_span.clone()),
 			}),
 			span,
 		)))
@@ -2987,6 +3668,22 @@ fn get_actual_children_by_field_name<'a>(node: Node<'a>, field_name: &str) -> Ve
 	children
 }
 
+/// Finds the root directory of a package brought by a file in `source_dir`. Packages of the file's workspace (see
+/// `Workspace`) are found by name without having to be installed, other packages are looked up in `node_modules`.
+fn find_package_directory(
+	package_name: &str,
+	source_dir: &Utf8Path,
+	workspaces: &WorkspaceCache,
+) -> Option<Utf8PathBuf> {
+	if let Some(package_dir) = workspaces
+		.find(source_dir)
+		.and_then(|workspace| workspace.package_dir(package_name).cloned())
+	{
+		return Some(package_dir);
+	}
+	wingii::util::package_json::find_dependency_directory(package_name, source_dir)
+}
+
 /// Check if the package.json in the given directory has a `wing` field.
 /// If so, return the name of the library.
 pub fn as_wing_library(module_dir: &Utf8Path, require_wing_field: bool) -> Option<String> {
@@ -3038,11 +3735,18 @@ fn is_valid_module_statement(stmt: &Stmt) -> bool {
 		StmtKind::Interface(_) => true,
 		StmtKind::Struct { .. } => true,
 		StmtKind::Enum { .. } => true,
+		// only exported constants and functions, anything else would just be code running when the module is brought
+		StmtKind::Let {
+			access,
+			ref initial_value,
+			..
+		} => access != AccessModifier::Private || matches!(initial_value.kind, ExprKind::FunctionClosure(_)),
 		// --- these are all uncool ---
 		StmtKind::SuperConstructor { .. } => false,
 		StmtKind::If { .. } => false,
 		StmtKind::ForLoop { .. } => false,
 		StmtKind::While { .. } => false,
+		StmtKind::WhileLet { .. } => false,
 		StmtKind::IfLet { .. } => false,
 		StmtKind::Break => false,
 		StmtKind::Continue => false,
@@ -3053,60 +3757,8 @@ fn is_valid_module_statement(stmt: &Stmt) -> bool {
 		StmtKind::Scope(_) => false,
 		StmtKind::TryCatch { .. } => false,
 		StmtKind::ExplicitLift(_) => false,
-		// TODO: support constants https://github.com/winglang/wing/issues/3606
 		// TODO: support test statements https://github.com/winglang/wing/issues/3571
-		StmtKind::Let { .. } => false,
-	}
-}
-
-// TODO: this function seems fragile
-// use inodes as source of truth instead https://github.com/winglang/wing/issues/3627
-pub fn normalize_path(path: &Utf8Path, relative_to: Option<&Utf8Path>) -> Utf8PathBuf {
-	let path = if path.is_absolute() {
-		// if the path is absolute, we ignore "relative_to"
-		path.to_path_buf()
-	} else {
-		relative_to
-			.map(|p| p.parent().unwrap_or_else(|| Utf8Path::new(".")).join(path))
-			.unwrap_or_else(|| path.to_path_buf())
-	};
-
-	// Remove excess components like `/./` and `/../`.
-	// This is tricky because ".." usually means we pop the last component
-	// but if a path starts with ".." or looks like "a/../../b" we need to track
-	// how many components we've popped and add them later.
-	let mut normalized = Utf8PathBuf::new();
-	let mut extra_pops = Utf8PathBuf::new();
-	for part in path.components() {
-		match part {
-			Utf8Component::Prefix(ref prefix) => {
-				normalized.push(prefix.as_str());
-			}
-			Utf8Component::RootDir => {
-				normalized.push("/");
-			}
-			Utf8Component::ParentDir => {
-				let popped = normalized.pop();
-				if !popped {
-					extra_pops.push("..");
-				}
-			}
-			Utf8Component::CurDir => {
-				// Nothing
-			}
-			Utf8Component::Normal(name) => {
-				if extra_pops.components().next().is_some() {
-					normalized = extra_pops;
-					extra_pops = Utf8PathBuf::new();
-					normalized.push(name);
-				} else {
-					normalized.push(name);
-				}
-			}
-		}
 	}
-
-	normalized
 }
 
 fn parse_number(s: &str) -> f64 {
@@ -3142,59 +3794,185 @@ mod tests {
 	}
 
 	#[test]
-	fn normalize_path_relative_to_nothing() {
-		let file_path = Utf8Path::new("/a/b/c/d/e.f");
-		assert_eq!(normalize_path(file_path, None), file_path);
+	fn broken_expressions_are_replaced_with_placeholders() {
+		let source = "let x = 1 +;\nlet y = x;\n";
+		let mut tree_sitter_parser = tree_sitter::Parser::new();
+		tree_sitter_parser.set_language(&tree_sitter_wing::language()).unwrap();
+		let tree_sitter_tree = tree_sitter_parser.parse(source.as_bytes(), None).unwrap();
+
+		let ast_ctx = AstContext::new();
+		let workspaces = WorkspaceCache::default();
+		let parser = Parser::new(
+			source.as_bytes(),
+			File::new("test.main.w", "test"),
+			&ast_ctx,
+			&workspaces,
+		);
+		let (scope, _, _) = parser.parse(&tree_sitter_tree.root_node());
 
-		let file_path = Utf8Path::new("/a/b/./c/../d/e.f");
-		assert_eq!(normalize_path(file_path, None), Utf8Path::new("/a/b/d/e.f"));
+		// The statement with the syntax error is kept, so "x" is still defined for the statement after it
+		assert_eq!(scope.statements.len(), 2);
+		let StmtKind::Let { initial_value, .. } = &scope.statements[0].kind else {
+			panic!("expected a let statement");
+		};
+		let ExprKind::Binary { right, .. } = &initial_value.kind else {
+			panic!("expected a binary expression");
+		};
+		assert!(matches!(right.kind, ExprKind::Error));
+	}
 
-		let file_path = Utf8Path::new("a/b/c/d/e.f");
-		assert_eq!(normalize_path(file_path, None), Utf8Path::new("a/b/c/d/e.f"));
+	#[test]
+	fn inline_js_code_can_contain_regular_expressions() {
+		let code = r#" const re = /[}"]/g; return x / 2 > 1 ? "a}b".replace(re, "") : typeof /{/; "#;
+		let source = format!("let y = unsafe js (x): str {{{code}}};\nlet z = 1;\n");
+		let mut tree_sitter_parser = tree_sitter::Parser::new();
+		tree_sitter_parser.set_language(&tree_sitter_wing::language()).unwrap();
+		let tree_sitter_tree = tree_sitter_parser.parse(source.as_bytes(), None).unwrap();
+		assert!(!tree_sitter_tree.root_node().has_error());
+
+		let ast_ctx = AstContext::new();
+		let workspaces = WorkspaceCache::default();
+		let parser = Parser::new(
+			source.as_bytes(),
+			File::new("test.main.w", "test"),
+			&ast_ctx,
+			&workspaces,
+		);
+		let (scope, _, _) = parser.parse(&tree_sitter_tree.root_node());
+		assert_eq!(scope.statements.len(), 2);
+		let StmtKind::Let { initial_value, .. } = &scope.statements[0].kind else {
+			panic!("expected a let statement");
+		};
+		let ExprKind::InlineJs { code: parsed_code, .. } = &initial_value.kind else {
+			panic!("expected inline JavaScript");
+		};
+		assert_eq!(parsed_code, code);
+	}
 
-		let file_path = Utf8Path::new("a/b/./c/../d/e.f");
-		assert_eq!(normalize_path(file_path, None), Utf8Path::new("a/b/d/e.f"));
+	#[test]
+	fn reports_libraries_brought_in_different_versions() {
+		let write = |path: &Utf8Path, content: &str| {
+			fs::create_dir_all(path.parent().unwrap()).unwrap();
+			fs::write(path, content).unwrap();
+		};
+		let write_library = |dir: &Utf8Path, name: &str, version: &str, code: &str| {
+			write(
+				&dir.join("package.json"),
+				&format!(r#"{{ "name": "{name}", "version": "{version}", "wing": true }}"#),
+			);
+			write(&dir.join("lib.w"), code);
+		};
 
-		let file_path = Utf8Path::new("a/../e.f");
-		assert_eq!(normalize_path(file_path, None), Utf8Path::new("e.f"));
+		let project_dir = tempfile::tempdir().unwrap();
+		let project_dir = Utf8Path::from_path(project_dir.path()).unwrap();
+		let bring_shared = "bring \"shared\" as shared;\n";
+		write_library(&project_dir.join("node_modules/lib-a"), "lib-a", "1.0.0", bring_shared);
+		write_library(&project_dir.join("node_modules/lib-b"), "lib-b", "1.0.0", bring_shared);
+		let shared_v1 = project_dir.join("node_modules/lib-a/node_modules/shared");
+		let shared_v2 = project_dir.join("node_modules/lib-b/node_modules/shared");
+		write_library(&shared_v1, "shared", "1.0.0", "pub class Shared {}\n");
+		write_library(&shared_v2, "shared", "2.0.0", "pub class Shared {}\n");
+		let main = project_dir.join("main.w");
+		write(&main, "bring \"lib-a\" as a;\nbring \"lib-b\" as b;\n");
+
+		crate::diagnostic::reset_diagnostics();
+		let mut library_roots = IndexMap::new();
+		parse_wing_project(
+			&File::new(&main, "root"),
+			&AstContext::new(),
+			&SourceOverlays::new(),
+			&mut Files::new(),
+			&mut FileGraph::default(),
+			&mut library_roots,
+			&mut IndexMap::new(),
+			&mut IndexMap::new(),
+		);
 
-		let file_path = Utf8Path::new("a/../../../e.f");
-		assert_eq!(normalize_path(file_path, None), Utf8Path::new("../../e.f"));
+		let diagnostics = crate::diagnostic::get_diagnostics();
+		let conflict = diagnostics
+			.iter()
+			.find(|d| d.message.starts_with("Library \"shared\""))
+			.expect("a version conflict diagnostic");
+		assert!(
+			conflict.message.ends_with("two different versions: 1.0.0 and 2.0.0")
+				|| conflict.message.ends_with("two different versions: 2.0.0 and 1.0.0")
+		);
+		assert_eq!(conflict.annotations.len(), 2);
+		assert!(conflict.annotations.iter().all(|a| a.span.file_id.ends_with("lib.w")));
+		// The library root found first is kept
+		assert!(library_roots["shared"] == shared_v1 || library_roots["shared"] == shared_v2);
+	}
 
-		let file_path = Utf8Path::new("./e.f");
-		assert_eq!(normalize_path(file_path, None), Utf8Path::new("e.f"));
+	#[cfg(unix)]
+	#[test]
+	fn files_and_libraries_reached_through_symlinks_are_parsed_once() {
+		let write = |path: &Utf8Path, content: &str| {
+			fs::create_dir_all(path.parent().unwrap()).unwrap();
+			fs::write(path, content).unwrap();
+		};
 
-		let file_path = Utf8Path::new("../e.f");
-		assert_eq!(normalize_path(file_path, None), Utf8Path::new("../e.f"));
+		let project_dir = tempfile::tempdir().unwrap();
+		let project_dir = Utf8Path::from_path(project_dir.path()).unwrap();
+		write(&project_dir.join("lib/util.w"), "pub class Util {}\n");
+		std::os::unix::fs::symlink(project_dir.join("lib"), project_dir.join("linked")).unwrap();
+		let shared = project_dir.join("shared");
+		write(
+			&shared.join("package.json"),
+			r#"{ "name": "shared", "version": "1.0.0", "wing": true }"#,
+		);
+		write(&shared.join("shared.w"), "pub class Shared {}\n");
+		fs::create_dir_all(project_dir.join("node_modules")).unwrap();
+		std::os::unix::fs::symlink(&shared, project_dir.join("node_modules/shared")).unwrap();
+		let main = project_dir.join("main.w");
+		write(
+			&main,
+			"bring \"./lib/util.w\" as a;\nbring \"./linked/util.w\" as b;\nbring \"shared\" as c;\nbring \"./shared\" as d;\n",
+		);
+
+		crate::diagnostic::reset_diagnostics();
+		let mut library_roots = IndexMap::new();
+		let mut asts = IndexMap::new();
+		parse_wing_project(
+			&File::new(&main, "root"),
+			&AstContext::new(),
+			&SourceOverlays::new(),
+			&mut Files::new(),
+			&mut FileGraph::default(),
+			&mut library_roots,
+			&mut IndexMap::new(),
+			&mut asts,
+		);
 
-		let file_path = Utf8Path::new("../foo/.././e.f");
-		assert_eq!(normalize_path(file_path, None), Utf8Path::new("../e.f"));
+		assert!(asts.contains_key(&project_dir.join("lib/util.w")));
+		assert!(!asts.contains_key(&project_dir.join("linked/util.w")));
+		assert_eq!(library_roots["shared"], shared);
+		assert!(asts.contains_key(&shared.join("shared.w")));
+		assert!(!asts.contains_key(&project_dir.join("node_modules/shared/shared.w")));
 	}
 
 	#[test]
-	fn normalize_path_relative_to_something() {
-		// If the path is absolute, we ignore "relative_to"
-		let file_path = Utf8Path::new("/a/b/c/d/e.f");
-		let relative_to = Utf8Path::new("/g/h/i");
-		assert_eq!(normalize_path(file_path, Some(relative_to)), file_path);
-
-		let file_path = Utf8Path::new("a/b/c/d/e.f");
-		let relative_to = Utf8Path::new("/g/h/i");
-		assert_eq!(
-			normalize_path(file_path, Some(relative_to)),
-			Utf8Path::new("/g/h/a/b/c/d/e.f")
-		);
-
-		let file_path = Utf8Path::new("a/b/c/d/e.f");
-		let relative_to = Utf8Path::new("g/h/i");
-		assert_eq!(
-			normalize_path(file_path, Some(relative_to)),
-			Utf8Path::new("g/h/a/b/c/d/e.f")
+	fn overlays_replace_the_contents_of_brought_files() {
+		let project_dir = tempfile::tempdir().unwrap();
+		let project_dir = Utf8Path::from_path(project_dir.path()).unwrap();
+		let main = project_dir.join("main.w");
+		let util = project_dir.join("util.w");
+		fs::write(&main, "bring \"./util.w\" as util;\n").unwrap();
+		fs::write(&util, "pub class Saved {}\n").unwrap();
+
+		let mut files = Files::new();
+		parse_wing_project(
+			&File::new(&main, "root"),
+			&AstContext::new(),
+			&SourceOverlays::from_iter([(&util, "pub class Unsaved {}\n".to_string())]),
+			&mut files,
+			&mut FileGraph::default(),
+			&mut IndexMap::new(),
+			&mut IndexMap::new(),
+			&mut IndexMap::new(),
 		);
 
-		let file_path = Utf8Path::new("../foo.w");
-		let relative_to = Utf8Path::new("subdir/bar.w");
-		assert_eq!(normalize_path(file_path, Some(relative_to)), Utf8Path::new("foo.w"));
+		assert_eq!(files.get_file(&main).unwrap(), "bring \"./util.w\" as util;\n");
+		assert_eq!(files.get_file(&util).unwrap(), "pub class Unsaved {}\n");
 	}
 
 	#[test]
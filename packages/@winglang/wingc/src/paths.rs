@@ -0,0 +1,317 @@
+//! Handling of the paths of source files. The compiler runs both natively and as a WASI module, where the paths it's
+//! given by a Windows host are just strings (`std::path` only knows the paths of the platform it's built for), so
+//! Windows paths are parsed here instead: drive paths (`C:\dir`), UNC paths (`\\server\share\dir`) and paths mixing
+//! `\` and `/` separators are handled the same on every host.
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+const VERBATIM_PREFIX: &str = r"\\?\";
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+
+/// A path split into its root and components
+struct ParsedPath<'a> {
+	/// The drive (`C:`) or UNC share (`//server/share`) of a Windows path, normalized to use `/` separators
+	prefix: Option<String>,
+	/// Whether the path starts at the root (of its drive, if it has one)
+	rooted: bool,
+	components: Vec<&'a str>,
+	/// Whether `\` is a separator in the path
+	windows: bool,
+}
+
+impl<'a> ParsedPath<'a> {
+	fn new(path: &'a str) -> Self {
+		let is_separator = |c: char| c == '/' || c == '\\';
+
+		// Verbatim paths (`\\?\C:\dir`, `\\?\UNC\server\share`) are the regular paths behind a prefix
+		let (path, verbatim_unc) = if let Some(rest) = path.strip_prefix(VERBATIM_UNC_PREFIX) {
+			(rest, true)
+		} else {
+			(path.strip_prefix(VERBATIM_PREFIX).unwrap_or(path), false)
+		};
+
+		// A UNC path starts with two separators, at least one of them a `\` (`//server/share` is a POSIX path, unless
+		// the compiler runs on Windows)
+		let unc_rest = if verbatim_unc {
+			Some(path)
+		} else {
+			match path.get(..2) {
+				Some(r"\\") | Some(r"\/") | Some(r"/\") => Some(&path[2..]),
+				Some("//") if cfg!(windows) => Some(&path[2..]),
+				_ => None,
+			}
+		};
+		if let Some(rest) = unc_rest {
+			let mut parts = rest.split(is_separator).filter(|part| !part.is_empty());
+			if let (Some(server), Some(share)) = (parts.next(), parts.next()) {
+				return Self {
+					prefix: Some(format!("//{server}/{share}")),
+					rooted: true,
+					components: parts.collect(),
+					windows: true,
+				};
+			}
+		}
+
+		let mut chars = path.chars();
+		let (prefix, rest) = match (chars.next(), chars.next()) {
+			(Some(drive), Some(':')) if drive.is_ascii_alphabetic() => (Some(format!("{drive}:")), &path[2..]),
+			_ => (None, path),
+		};
+		let windows = prefix.is_some() || cfg!(windows);
+		let components = if windows {
+			rest.split(is_separator).filter(|part| !part.is_empty()).collect()
+		} else {
+			rest.split('/').filter(|part| !part.is_empty()).collect()
+		};
+		Self {
+			prefix,
+			rooted: rest.starts_with('/') || (windows && rest.starts_with('\\')),
+			components,
+			windows,
+		}
+	}
+
+	/// Removes `.` components, and `..` components along with the components they cancel out. `..` components at the
+	/// start of a relative path are kept, and the ones at the root of an absolute path are dropped.
+	fn normalize(&mut self) {
+		let mut normalized: Vec<&str> = vec![];
+		for component in self.components.drain(..) {
+			match component {
+				"." => {}
+				".." => match normalized.last() {
+					Some(&last) if last != ".." => {
+						normalized.pop();
+					}
+					_ if self.rooted || self.prefix.is_some() => {}
+					_ => normalized.push(".."),
+				},
+				_ => normalized.push(component),
+			}
+		}
+		self.components = normalized;
+	}
+
+	fn to_path_buf(&self) -> Utf8PathBuf {
+		let mut path = self.prefix.clone().unwrap_or_default();
+		if self.rooted {
+			path.push('/');
+		}
+		path.push_str(&self.components.join("/"));
+		Utf8PathBuf::from(path)
+	}
+}
+
+/// Whether the path is absolute, on POSIX (`/dir`) or Windows (`C:\dir`, `\\server\share`). Unlike
+/// `Utf8Path::is_absolute`, this works with Windows paths when the compiler runs on WASI.
+pub fn is_absolute_path(path: &Utf8Path) -> bool {
+	let parsed = ParsedPath::new(path.as_str());
+	parsed.rooted || parsed.prefix.is_some()
+}
+
+/// Removes the `.` and `..` components of a path, resolving it relative to the directory of `relative_to` if it isn't
+/// absolute. Windows paths are returned with `/` separators (e.g. `C:/dir/file.w` or `//server/share/file.w`), so
+/// they can be handled by `Utf8Path` on every host.
+// TODO: use inodes as source of truth instead https://github.com/winglang/wing/issues/3627
+pub fn normalize_path(path: &Utf8Path, relative_to: Option<&Utf8Path>) -> Utf8PathBuf {
+	let mut parsed = ParsedPath::new(path.as_str());
+	if let (false, Some(relative_to)) = (is_absolute_path(path), relative_to) {
+		let mut base = ParsedPath::new(relative_to.as_str());
+		// The path is relative to the directory of `relative_to`
+		base.components.pop();
+		if base.windows {
+			// A relative path on its own doesn't tell which separators it uses, but relative to a Windows path it's a
+			// Windows path too
+			base.components.extend(
+				parsed
+					.components
+					.iter()
+					.flat_map(|component| component.split('\\'))
+					.filter(|component| !component.is_empty()),
+			);
+		} else {
+			base.components.append(&mut parsed.components);
+		}
+		parsed = base;
+	}
+	parsed.normalize();
+	parsed.to_path_buf()
+}
+
+/// Resolves the symlinks in a path, so a file that can be reached through different paths (e.g. directly and through a
+/// symlinked package in `node_modules`) is always known by the same path. The resolved path keeps the spelling of the
+/// nearest ancestor of `base_dir` that contains it, so the paths of a project don't change when the project itself is
+/// in a symlinked directory (like `/var` on macOS). Paths that can't be resolved (e.g. files that don't exist, or when
+/// running on WASI) are returned as they are.
+pub fn resolve_symlinks(path: &Utf8Path, base_dir: &Utf8Path) -> Utf8PathBuf {
+	let canonicalize = |path: &Utf8Path| {
+		std::fs::canonicalize(path)
+			.ok()
+			.and_then(|resolved| Utf8PathBuf::from_path_buf(resolved).ok())
+			.map(|resolved| normalize_path(&resolved, None))
+	};
+	let Some(resolved) = canonicalize(path) else {
+		return path.to_path_buf();
+	};
+	for ancestor in base_dir.ancestors() {
+		let Some(resolved_ancestor) = canonicalize(ancestor) else {
+			continue;
+		};
+		if let Ok(rest) = resolved.strip_prefix(&resolved_ancestor) {
+			return normalize_path(&ancestor.join(rest), None);
+		}
+	}
+	resolved
+}
+
+/// The key paths are compared by: the normalized path, ignoring case for Windows paths since Windows file systems are
+/// case insensitive
+pub fn path_key(path: &Utf8Path) -> String {
+	let mut parsed = ParsedPath::new(path.as_str());
+	parsed.normalize();
+	let normalized = parsed.to_path_buf().into_string();
+	if parsed.windows {
+		normalized.to_lowercase()
+	} else {
+		normalized
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn normalize_path_relative_to_nothing() {
+		let file_path = Utf8Path::new("/a/b/c/d/e.f");
+		assert_eq!(normalize_path(file_path, None), file_path);
+
+		let file_path = Utf8Path::new("/a/b/./c/../d/e.f");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("/a/b/d/e.f"));
+
+		let file_path = Utf8Path::new("a/b/c/d/e.f");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("a/b/c/d/e.f"));
+
+		let file_path = Utf8Path::new("a/b/./c/../d/e.f");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("a/b/d/e.f"));
+
+		let file_path = Utf8Path::new("a/../e.f");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("e.f"));
+
+		let file_path = Utf8Path::new("a/../../../e.f");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("../../e.f"));
+
+		let file_path = Utf8Path::new("./e.f");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("e.f"));
+
+		let file_path = Utf8Path::new("../e.f");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("../e.f"));
+
+		let file_path = Utf8Path::new("../foo/.././e.f");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("../e.f"));
+	}
+
+	#[test]
+	fn normalize_path_relative_to_something() {
+		// If the path is absolute, we ignore "relative_to"
+		let file_path = Utf8Path::new("/a/b/c/d/e.f");
+		let relative_to = Utf8Path::new("/g/h/i");
+		assert_eq!(normalize_path(file_path, Some(relative_to)), file_path);
+
+		let file_path = Utf8Path::new("a/b/c/d/e.f");
+		let relative_to = Utf8Path::new("/g/h/i");
+		assert_eq!(
+			normalize_path(file_path, Some(relative_to)),
+			Utf8Path::new("/g/h/a/b/c/d/e.f")
+		);
+
+		let file_path = Utf8Path::new("a/b/c/d/e.f");
+		let relative_to = Utf8Path::new("g/h/i");
+		assert_eq!(
+			normalize_path(file_path, Some(relative_to)),
+			Utf8Path::new("g/h/a/b/c/d/e.f")
+		);
+
+		let file_path = Utf8Path::new("../foo.w");
+		let relative_to = Utf8Path::new("subdir/bar.w");
+		assert_eq!(normalize_path(file_path, Some(relative_to)), Utf8Path::new("foo.w"));
+	}
+
+	#[test]
+	fn normalize_path_stays_at_the_root() {
+		let file_path = Utf8Path::new("/a/../../b.w");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("/b.w"));
+
+		let file_path = Utf8Path::new(r"C:\a\..\..\b.w");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("C:/b.w"));
+
+		let file_path = Utf8Path::new(r"\\server\share\..\b.w");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("//server/share/b.w"));
+	}
+
+	#[test]
+	fn normalize_windows_paths() {
+		let file_path = Utf8Path::new(r"C:\Users\me\project\main.w");
+		assert_eq!(
+			normalize_path(file_path, None),
+			Utf8Path::new("C:/Users/me/project/main.w")
+		);
+
+		// Mixed separators, as passed by WASI hosts
+		let file_path = Utf8Path::new(r"C:\Users\me/project/.\lib\..\main.w");
+		assert_eq!(
+			normalize_path(file_path, None),
+			Utf8Path::new("C:/Users/me/project/main.w")
+		);
+
+		let file_path = Utf8Path::new(r"lib\util.w");
+		let relative_to = Utf8Path::new(r"C:\project\main.w");
+		assert_eq!(
+			normalize_path(file_path, Some(relative_to)),
+			Utf8Path::new("C:/project/lib/util.w")
+		);
+
+		let file_path = Utf8Path::new(r"..\shared\util.w");
+		let relative_to = Utf8Path::new(r"\\server\share\project\main.w");
+		assert_eq!(
+			normalize_path(file_path, Some(relative_to)),
+			Utf8Path::new("//server/share/shared/util.w")
+		);
+
+		let file_path = Utf8Path::new(r"\\?\C:\project\main.w");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("C:/project/main.w"));
+
+		let file_path = Utf8Path::new(r"\\?\UNC\server\share\main.w");
+		assert_eq!(normalize_path(file_path, None), Utf8Path::new("//server/share/main.w"));
+	}
+
+	#[test]
+	fn absolute_paths() {
+		assert!(is_absolute_path(Utf8Path::new("/a/b.w")));
+		assert!(is_absolute_path(Utf8Path::new(r"C:\a\b.w")));
+		assert!(is_absolute_path(Utf8Path::new("c:/a/b.w")));
+		assert!(is_absolute_path(Utf8Path::new(r"\\server\share\b.w")));
+		assert!(is_absolute_path(Utf8Path::new(r"\\?\C:\a\b.w")));
+		assert!(!is_absolute_path(Utf8Path::new("a/b.w")));
+		assert!(!is_absolute_path(Utf8Path::new("../b.w")));
+		assert!(!is_absolute_path(Utf8Path::new("1:/b.w")));
+	}
+
+	#[test]
+	fn path_keys_ignore_case_of_windows_paths() {
+		assert_eq!(
+			path_key(Utf8Path::new(r"C:\Project\Main.w")),
+			path_key(Utf8Path::new("c:/project/main.w"))
+		);
+		assert_eq!(
+			path_key(Utf8Path::new(r"\\Server\Share\main.w")),
+			path_key(Utf8Path::new(r"\\server\share\.\main.w"))
+		);
+		if !cfg!(windows) {
+			assert_ne!(
+				path_key(Utf8Path::new("/project/Main.w")),
+				path_key(Utf8Path::new("/project/main.w"))
+			);
+		}
+	}
+}
@@ -0,0 +1,181 @@
+//! A summary of the operations every inflight host (inflight closures and classes with inflight methods) performs on
+//! the preflight objects it lifts. These operations are what the objects grant their hosts permissions for when the
+//! program is deployed (e.g. IAM policies), so the summary lets the program's access be reviewed before deployment.
+
+use std::fmt::Write;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::diagnostic::WingSpan;
+use crate::ir::IrProgram;
+
+/// The summary for tools
+pub const PERMISSIONS_JSON_FILE_NAME: &str = "permissions.json";
+
+/// The summary for reviewers, as a Markdown table per host
+pub const PERMISSIONS_MARKDOWN_FILE_NAME: &str = "permissions.md";
+
+#[derive(Serialize)]
+pub struct PermissionSummary {
+	hosts: Vec<InflightHost>,
+}
+
+/// A class whose inflight code lifts preflight objects
+#[derive(Serialize)]
+struct InflightHost {
+	/// The readable name of the host (for closures, derived from where they're defined)
+	name: String,
+	/// The name of the host's class
+	class: String,
+	is_closure: bool,
+	span: WingSpan,
+	grants: Vec<Grant>,
+}
+
+/// The operations a host performs on a lifted object
+#[derive(Serialize)]
+struct Grant {
+	/// The preflight code of the lifted object
+	resource: String,
+	/// The operations performed on the object, in the order they were first used
+	operations: Vec<String>,
+	/// The host's methods that use the object
+	methods: Vec<String>,
+}
+
+impl PermissionSummary {
+	pub fn new(program: &IrProgram) -> Self {
+		let hosts = program
+			.files
+			.iter()
+			.flat_map(|file| &file.classes)
+			.filter(|class| !class.lifts.is_empty())
+			.map(|class| {
+				let mut grants: IndexMap<&str, Grant> = IndexMap::new();
+				for (method, qualifications) in &class.lifts {
+					for (resource, ops) in qualifications {
+						let grant = grants.entry(resource.as_str()).or_insert_with(|| Grant {
+							resource: resource.clone(),
+							operations: vec![],
+							methods: vec![],
+						});
+						for op in ops {
+							if !grant.operations.contains(op) {
+								grant.operations.push(op.clone());
+							}
+						}
+						// Every lift is also recorded on the inflight initializer, which isn't interesting unless it
+						// uses the object itself
+						if !ops.is_empty() || !method.starts_with('$') {
+							grant.methods.push(method.clone());
+						}
+					}
+				}
+				InflightHost {
+					name: class.display_name.clone().unwrap_or_else(|| class.name.clone()),
+					class: class.name.clone(),
+					is_closure: class.is_closure,
+					span: class.span.clone(),
+					grants: grants.into_values().collect(),
+				}
+			})
+			.collect();
+		Self { hosts }
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).expect("serialize permission summary")
+	}
+
+	pub fn to_markdown(&self) -> String {
+		let mut markdown = String::new();
+		_ = writeln!(markdown, "# Permissions");
+		_ = writeln!(markdown);
+		_ = writeln!(
+			markdown,
+			"The operations each inflight host performs on the preflight objects it uses."
+		);
+		if self.hosts.is_empty() {
+			_ = writeln!(markdown);
+			_ = writeln!(markdown, "No inflight code uses preflight objects.");
+		}
+
+		for host in &self.hosts {
+			let kind = if host.is_closure { "inflight closure" } else { "class" };
+			_ = writeln!(markdown);
+			_ = writeln!(markdown, "## `{}` ({kind}, {})", host.name, host.span);
+			_ = writeln!(markdown);
+			_ = writeln!(markdown, "| Resource | Operations | Used by |");
+			_ = writeln!(markdown, "| --- | --- | --- |");
+			for grant in &host.grants {
+				let operations = if grant.operations.is_empty() {
+					"-".to_string()
+				} else {
+					code_list(&grant.operations)
+				};
+				_ = writeln!(
+					markdown,
+					"| `{}` | {operations} | {} |",
+					grant.resource.replace('|', "\\|"),
+					code_list(&grant.methods)
+				);
+			}
+		}
+		markdown
+	}
+}
+
+fn code_list(items: &[String]) -> String {
+	items
+		.iter()
+		.map(|item| format!("`{item}`"))
+		.collect::<Vec<_>>()
+		.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{PERMISSIONS_JSON_FILE_NAME, PERMISSIONS_MARKDOWN_FILE_NAME};
+	use crate::test_utils::compile_source;
+
+	#[test]
+	fn summarizes_the_operations_of_inflight_closures() {
+		let source = r#"
+bring cloud;
+
+let bucket = new cloud.Bucket();
+let counter = new cloud.Counter();
+
+let upload = inflight (key: str) => {
+  bucket.put(key, "");
+  bucket.get(key);
+  counter.inc();
+};
+"#;
+		let project = compile_source(source);
+
+		let summary = project.read_json(PERMISSIONS_JSON_FILE_NAME);
+		let hosts = summary["hosts"].as_array().unwrap();
+		assert_eq!(hosts.len(), 1);
+		let upload = &hosts[0];
+		assert_eq!(upload["name"], "upload");
+		assert_eq!(upload["is_closure"], true);
+		let grant = |resource: &str| {
+			upload["grants"]
+				.as_array()
+				.unwrap()
+				.iter()
+				.find(|grant| grant["resource"] == resource)
+				.unwrap()
+				.clone()
+		};
+		assert_eq!(grant("bucket")["operations"], serde_json::json!(["get", "put"]));
+		assert_eq!(grant("bucket")["methods"], serde_json::json!(["handle"]));
+		assert_eq!(grant("counter")["operations"], serde_json::json!(["inc"]));
+
+		let markdown = project.read(PERMISSIONS_MARKDOWN_FILE_NAME);
+		assert!(markdown.contains("## `upload` (inflight closure, "));
+		assert!(markdown.contains("| `bucket` | `get`, `put` | `handle` |"));
+	}
+}
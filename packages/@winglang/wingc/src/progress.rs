@@ -0,0 +1,178 @@
+use std::cell::RefCell;
+
+use serde::Serialize;
+
+use crate::comp_ctx::CompilationPhase;
+use crate::events::{CompilerEvent, EventSink};
+
+/// An update on the progress of a compilation, sent to the WASM host
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Progress {
+	/// The current phase of the compilation (e.g. "type-checking")
+	pub phase: String,
+	/// The file the current phase is processing, if it processes files one by one
+	pub file: Option<String>,
+	/// How much of the compilation is done, from 0 to 100
+	pub percent: u8,
+}
+
+/// Turns the events of a compilation into progress updates. Each phase gets a share of the progress bar according to
+/// how long it usually takes, which it fills as it goes through the files of the project.
+pub struct ProgressReporter {
+	send: Box<dyn Fn(&Progress)>,
+	/// The innermost phase that has a share of the progress bar, and the range of its share
+	current: RefCell<Option<(CompilationPhase, u8, u8)>>,
+	last_sent: RefCell<Option<Progress>>,
+}
+
+impl ProgressReporter {
+	pub fn new(send: impl Fn(&Progress) + 'static) -> Self {
+		Self {
+			send: Box::new(send),
+			current: RefCell::new(None),
+			last_sent: RefCell::new(None),
+		}
+	}
+
+	fn report(&self, progress: Progress) {
+		let mut last_sent = self.last_sent.borrow_mut();
+		if last_sent.as_ref() == Some(&progress) {
+			return;
+		}
+		(self.send)(&progress);
+		*last_sent = Some(progress);
+	}
+}
+
+/// The range of the progress bar each phase fills
+fn phase_share(phase: CompilationPhase) -> Option<(u8, u8)> {
+	match phase {
+		CompilationPhase::Compiling => None,
+		CompilationPhase::Parsing => Some((0, 10)),
+		CompilationPhase::TypeChecking => Some((10, 60)),
+		CompilationPhase::Lifting => Some((60, 70)),
+		CompilationPhase::Jsifying => Some((70, 95)),
+		CompilationPhase::Dtsifying => Some((95, 100)),
+	}
+}
+
+impl EventSink for ProgressReporter {
+	fn on_event(&self, event: &CompilerEvent<'_>) {
+		match event {
+			CompilerEvent::PhaseStarted { phase } => {
+				let Some((start, end)) = phase_share(*phase) else {
+					return;
+				};
+				*self.current.borrow_mut() = Some((*phase, start, end));
+				self.report(Progress {
+					phase: phase.to_string(),
+					file: None,
+					percent: start,
+				});
+			}
+			CompilerEvent::FileStarted { path, index, total } => {
+				let Some((phase, start, end)) = *self.current.borrow() else {
+					return;
+				};
+				let done = (end - start) as usize * index / (*total).max(1);
+				self.report(Progress {
+					phase: phase.to_string(),
+					file: Some(path.to_string()),
+					percent: start + done as u8,
+				});
+			}
+			CompilerEvent::PhaseFinished {
+				phase: CompilationPhase::Compiling,
+				..
+			} => {
+				self.report(Progress {
+					phase: CompilationPhase::Compiling.to_string(),
+					file: None,
+					percent: 100,
+				});
+			}
+			_ => {}
+		}
+	}
+}
+
+/// Sends a progress update to the WASM host, which can show it while the compiler runs
+pub fn send_progress_to_host(progress: &Progress) {
+	#[cfg(target_arch = "wasm32")]
+	{
+		use crate::diagnostic::{report_diagnostic, Diagnostic, DiagnosticSeverity};
+
+		match serde_json::to_string(progress) {
+			Ok(json) => {
+				let bytes = json.as_bytes();
+				unsafe {
+					send_progress(bytes.as_ptr(), bytes.len() as u32);
+				}
+			}
+			// Progress is only informational, so a failure to send it shouldn't fail the compilation
+			Err(err) => report_diagnostic(Diagnostic {
+				message: format!("Error serializing progress: {err}"),
+				span: None,
+				annotations: vec![],
+				hints: vec![],
+				severity: DiagnosticSeverity::Warning,
+				code: None,
+			}),
+		}
+	}
+	#[cfg(not(target_arch = "wasm32"))]
+	let _ = progress;
+}
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+	pub fn send_progress(data: *const u8, data_length: u32);
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::RefCell;
+	use std::rc::Rc;
+
+	use camino::Utf8Path;
+
+	use super::ProgressReporter;
+	use crate::comp_ctx::CompilationPhase;
+	use crate::events::{emit_event, set_event_sink, start_phase, CompilerEvent};
+
+	#[test]
+	fn reports_phases_and_files() {
+		let updates = Rc::new(RefCell::new(vec![]));
+		let sent = updates.clone();
+		set_event_sink(Some(Rc::new(ProgressReporter::new(move |progress| {
+			sent
+				.borrow_mut()
+				.push(format!("{} {:?} {}", progress.phase, progress.file, progress.percent));
+		}))));
+
+		let compiling = start_phase(CompilationPhase::Compiling);
+		start_phase(CompilationPhase::Parsing).finish();
+		let type_checking = start_phase(CompilationPhase::TypeChecking);
+		for (index, path) in ["a.w", "b.w"].iter().enumerate() {
+			emit_event(CompilerEvent::FileStarted {
+				path: Utf8Path::new(path),
+				index,
+				total: 2,
+			});
+		}
+		type_checking.finish();
+		compiling.finish();
+		set_event_sink(None);
+
+		assert_eq!(
+			*updates.borrow(),
+			vec![
+				"parsing None 0",
+				"type-checking None 10",
+				"type-checking Some(\"a.w\") 10",
+				"type-checking Some(\"b.w\") 35",
+				"compiling None 100",
+			]
+		);
+	}
+}
@@ -48,7 +48,7 @@ impl<'a> Visit<'a> for StructSchemaVisitor<'a> {
 			Reference::Identifier(_) => {
 				visit::visit_reference(self, node);
 			}
-			Reference::ElementAccess { .. } => {
+			Reference::ElementAccess { .. } | Reference::SuperMember { .. } => {
 				visit::visit_reference(self, node);
 			}
 		}
@@ -0,0 +1,351 @@
+use regex::Regex;
+use serde::Serialize;
+
+use crate::ast::{Expr, ExprKind, Literal, Phase, Scope};
+use crate::diagnostic::WingSpan;
+use crate::visit::{self, Visit};
+use crate::{WINGSDK_STD_MODULE, WINGSDK_TEST_CLASS_NAME};
+
+/// Name of the environment variable with a regex selecting the tests to compile (e.g. "^bucket"). It's the filter
+/// passed to `wing test --test-filter`, so it's a JavaScript regular expression (see `TestFilter`).
+pub const TEST_FILTER_ENV: &str = "WING_TEST_FILTER";
+
+/// A `test` block of a Wing program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TestBlock {
+	/// The name of the test (e.g. "put" for `test "put" { ... }`)
+	pub name: String,
+	/// The file the test is defined in
+	pub file: String,
+	/// The span of the whole test block
+	pub span: WingSpan,
+	/// Whether the test's body runs inflight
+	pub inflight: bool,
+}
+
+/// Returns the name of the test defined by an expression, if it's a `test "name" { ... }` block
+/// (which the parser turns into `new std.Test(...) as "test:name"`)
+pub fn test_name(expr: &Expr) -> Option<String> {
+	let ExprKind::New(new_expr) = &expr.kind else {
+		return None;
+	};
+	let is_test_class = new_expr.class.root.name == WINGSDK_STD_MODULE
+		&& new_expr.class.fields.len() == 1
+		&& new_expr.class.fields[0].name == WINGSDK_TEST_CLASS_NAME;
+	if !is_test_class {
+		return None;
+	}
+	let ExprKind::Literal(Literal::String(id)) = &new_expr.obj_id.as_ref()?.kind else {
+		return None;
+	};
+	id.trim_matches('"').strip_prefix("test:").map(|name| name.to_string())
+}
+
+/// Finds all the test blocks of a parsed file (before inflight closures are transformed into classes)
+pub fn find_test_blocks(scope: &Scope) -> Vec<TestBlock> {
+	let mut finder = TestBlockFinder { tests: vec![] };
+	finder.visit_scope(scope);
+	finder.tests
+}
+
+/// Selects the tests that are compiled into the program. Tests whose names don't match are still type checked (so a
+/// filter never hides errors), but they're left out of the generated code, so their resources are never created.
+///
+/// The filter is a JavaScript regular expression, like the one the test runner matches test names with. Its syntax is
+/// translated to the syntax of the `regex` crate, except for look-arounds and backreferences, which the `regex` crate
+/// doesn't support.
+#[derive(Debug, Clone)]
+pub struct TestFilter {
+	regex: Regex,
+}
+
+impl TestFilter {
+	pub fn new(pattern: &str) -> Result<Self, String> {
+		let translated = js_regex_to_rust(pattern)
+			.map_err(|feature| format!("Test filter \"{pattern}\" uses {feature}, which can't be used in test filters"))?;
+		let regex = Regex::new(&translated).map_err(|err| format!("Invalid test filter \"{pattern}\": {err}"))?;
+		Ok(Self { regex })
+	}
+
+	pub fn is_match(&self, test_name: &str) -> bool {
+		self.regex.is_match(test_name)
+	}
+
+	/// Whether an expression is a test block whose name doesn't match the filter
+	pub fn excludes(&self, expr: &Expr) -> bool {
+		test_name(expr).map_or(false, |name| !self.is_match(&name))
+	}
+}
+
+/// Translates a JavaScript regular expression (without flags) to the syntax of the `regex` crate. The two mostly agree,
+/// but JavaScript's `\d`, `\w` and `\b` are ASCII only, escaping a letter without a special meaning matches the letter,
+/// `[^]` matches any character, and `[`, `&&`, `--` and `~~` aren't special inside character classes.
+///
+/// Returns the name of the feature if the pattern uses one that has no equivalent.
+fn js_regex_to_rust(pattern: &str) -> Result<String, &'static str> {
+	let mut translated = String::new();
+	let mut in_class = false;
+	let mut chars = pattern.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' => {
+				let Some(escaped) = chars.next() else {
+					// a trailing backslash is an error in both syntaxes
+					translated.push(c);
+					break;
+				};
+				match escaped {
+					'd' | 'D' | 'w' | 'W' => {
+						let class = match escaped {
+							'd' => "[:digit:]",
+							'D' => "[:^digit:]",
+							'w' => "[:word:]",
+							_ => "[:^word:]",
+						};
+						if in_class {
+							translated.push_str(class);
+						} else {
+							translated.push_str(&format!("[{class}]"));
+						}
+					}
+					'b' | 'B' if !in_class => translated.push_str(&format!("(?-u:\\{escaped})")),
+					// a backspace inside a character class
+					'b' => translated.push_str("\\x08"),
+					'1'..='9' => return Err("backreferences"),
+					'k' if chars.peek() == Some(&'<') => return Err("backreferences"),
+					'0' => translated.push_str("\\x00"),
+					's' | 'S' | 'f' | 'n' | 'r' | 't' | 'v' | 'x' | 'u' => {
+						translated.push(c);
+						translated.push(escaped);
+					}
+					'c' => match chars.next_if(char::is_ascii_alphabetic) {
+						Some(letter) => translated.push_str(&format!("\\x{:02X}", letter as u8 % 32)),
+						None => translated.push_str("\\\\c"),
+					},
+					_ => translated.push_str(&regex::escape(&escaped.to_string())),
+				}
+			}
+			'[' if in_class => translated.push_str("\\["),
+			'&' | '~' if in_class => {
+				translated.push('\\');
+				translated.push(c);
+			}
+			'-' if in_class && chars.peek() == Some(&'-') => translated.push_str("\\-"),
+			'[' => {
+				in_class = true;
+				if chars.next_if_eq(&'^').is_some() {
+					if chars.next_if_eq(&']').is_some() {
+						// `[^]` matches any character
+						translated.push_str("(?s:.)");
+						in_class = false;
+					} else {
+						translated.push_str("[^");
+					}
+				} else {
+					// `[]` matches nothing (a `]` right after `[` isn't a literal in JavaScript)
+					if chars.next_if_eq(&']').is_some() {
+						translated.push_str("[^\\s\\S]");
+						in_class = false;
+					} else {
+						translated.push('[');
+					}
+				}
+			}
+			']' if in_class => {
+				in_class = false;
+				translated.push(c);
+			}
+			'(' if !in_class && chars.peek() == Some(&'?') => {
+				let rest = chars.clone().skip(1).take(2).collect::<String>();
+				if rest.starts_with('=') || rest.starts_with('!') || rest == "<=" || rest == "<!" {
+					return Err("look-arounds");
+				}
+				translated.push(c);
+			}
+			'{' if !in_class && starts_quantifier(chars.clone()) => {
+				translated.push(c);
+				for c in chars.by_ref() {
+					translated.push(c);
+					if c == '}' {
+						break;
+					}
+				}
+			}
+			// JavaScript treats braces that aren't part of a quantifier as literals
+			'{' if !in_class => translated.push_str("\\{"),
+			'}' | ']' if !in_class => {
+				translated.push('\\');
+				translated.push(c);
+			}
+			_ => translated.push(c),
+		}
+	}
+	Ok(translated)
+}
+
+/// Whether the characters after a `{` make it a quantifier, like `{2}`, `{2,}` or `{2,5}`
+fn starts_quantifier(rest: impl Iterator<Item = char>) -> bool {
+	let mut quantifier = String::new();
+	let mut closed = false;
+	for c in rest {
+		if c == '}' {
+			closed = true;
+			break;
+		}
+		quantifier.push(c);
+	}
+	if !closed {
+		return false;
+	}
+	let mut bounds = quantifier.splitn(2, ',');
+	let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+	match (bounds.next(), bounds.next()) {
+		(Some(min), None) => is_number(min),
+		(Some(min), Some(max)) => is_number(min) && (max.is_empty() || is_number(max)),
+		_ => false,
+	}
+}
+
+struct TestBlockFinder {
+	tests: Vec<TestBlock>,
+}
+
+impl<'a> Visit<'a> for TestBlockFinder {
+	fn visit_expr(&mut self, node: &'a Expr) {
+		if let (Some(name), ExprKind::New(new_expr)) = (test_name(node), &node.kind) {
+			let inflight = match new_expr.arg_list.pos_args.first().map(|handler| &handler.kind) {
+				Some(ExprKind::FunctionClosure(def)) => def.signature.phase == Phase::Inflight,
+				_ => false,
+			};
+			self.tests.push(TestBlock {
+				name,
+				file: node.span.file_id.clone(),
+				span: node.span.clone(),
+				inflight,
+			});
+		}
+		visit::visit_expr(self, node);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use camino::Utf8Path;
+
+	use super::{find_test_blocks, js_regex_to_rust, TestFilter};
+	use crate::ast::{AstContext, StmtKind};
+	use crate::test_utils::{compile_source_with_errors, compile_source_with_options, parse_source};
+	use crate::CompileOptions;
+
+	#[test]
+	fn finds_and_filters_test_blocks() {
+		let main = Utf8Path::new("/project/main.test.w");
+		let source = r#"
+bring cloud;
+let b = new cloud.Bucket();
+test "put" {
+  b.put("a", "b");
+}
+if true {
+  test "nested" {}
+}
+new std.Test(inflight () => {}) as "not a test block";
+"#;
+		let ast_ctx = AstContext::new();
+		let ast = parse_source(main, source, &ast_ctx);
+
+		let tests = find_test_blocks(&ast);
+		assert_eq!(
+			tests.iter().map(|t| (t.name.as_str(), t.inflight)).collect::<Vec<_>>(),
+			vec![("put", true), ("nested", true)]
+		);
+		assert_eq!(tests[0].file, main.as_str());
+		assert_eq!((tests[0].span.start.line, tests[0].span.end.line), (3, 5));
+
+		let filter = TestFilter::new("^nest").unwrap();
+		let excluded = ast
+			.statements
+			.iter()
+			.filter(|stmt| matches!(&stmt.kind, StmtKind::Expression(expr) if filter.excludes(expr)))
+			.count();
+		// only the "put" test is excluded, "not a test block" isn't a test
+		assert_eq!(excluded, 1);
+		assert!(filter.is_match("nested"));
+	}
+
+	#[test]
+	fn compiles_only_the_tests_matching_the_filter() {
+		let source = r#"
+test "put" {}
+test "get" {}
+"#;
+		let with_filter = |test_filter: &str| CompileOptions {
+			test_filter: Some(test_filter.to_string()),
+			..Default::default()
+		};
+
+		let preflight = compile_source_with_options(source, &with_filter("^pu")).read("preflight.cjs");
+		assert!(preflight.contains("\"test:put\""));
+		assert!(!preflight.contains("\"test:get\""));
+
+		// tests that are filtered out are still type checked
+		let errors = compile_source_with_errors(
+			r#"
+test "put" {}
+test "get" { let x: num = "not a number"; }
+"#,
+			&with_filter("^put$"),
+		);
+		assert_eq!(errors.len(), 1);
+		assert!(
+			errors[0].message.contains("Expected type to be \"num\""),
+			"{}",
+			errors[0]
+		);
+
+		let errors = compile_source_with_errors(source, &with_filter("put(?!s)"));
+		assert_eq!(
+			errors.iter().map(|e| e.message.as_str()).collect::<Vec<_>>(),
+			vec!["Test filter \"put(?!s)\" uses look-arounds, which can't be used in test filters"]
+		);
+	}
+
+	#[test]
+	fn translates_javascript_test_filters() {
+		let matches = |pattern: &str, name: &str| TestFilter::new(pattern).unwrap().is_match(name);
+
+		// the same in both syntaxes
+		assert!(matches("^put (a|b)$", "put a"));
+		assert!(!matches("^put (a|b)$", "put c"));
+		assert!(matches("(?<verb>get|put)", "get"));
+		// ASCII only in JavaScript
+		assert!(matches("^\\d+$", "42"));
+		assert!(!matches("^\\d+$", "٤٢"));
+		assert!(!matches("^\\w+$", "café"));
+		assert!(matches("\\bput\\b", "can put it"));
+		// escaped letters without a special meaning are literals
+		assert!(matches("^\\A\\z$", "Az"));
+		// `[^]` is any character and brackets aren't special inside classes
+		assert!(matches("^a[^]c$", "a\nc"));
+		assert!(matches("^[[]+$", "[["));
+		assert!(matches("^[a&&b]+$", "a&b"));
+		assert!(!matches("a[]b", "ab"));
+		// braces that aren't quantifiers are literals
+		assert!(matches("^{x}$", "{x}"));
+		assert!(matches("^a{2}$", "aa"));
+		assert!(matches("^a{2,}$", "aaa"));
+		assert!(matches("^f\\(\\)$", "f()"));
+
+		assert_eq!(js_regex_to_rust("a(?=b)"), Err("look-arounds"));
+		assert_eq!(js_regex_to_rust("(?<!a)b"), Err("look-arounds"));
+		assert_eq!(js_regex_to_rust("(a)\\1"), Err("backreferences"));
+		assert_eq!(js_regex_to_rust("(?<x>a)\\k<x>"), Err("backreferences"));
+		assert_eq!(
+			TestFilter::new("(a)\\1").unwrap_err(),
+			"Test filter \"(a)\\1\" uses backreferences, which can't be used in test filters"
+		);
+		assert!(TestFilter::new("(a")
+			.unwrap_err()
+			.starts_with("Invalid test filter \"(a\""));
+	}
+}
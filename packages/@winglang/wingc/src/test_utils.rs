@@ -1,15 +1,40 @@
 use std::env;
 use std::fs::read_dir;
 
-use camino::Utf8Path;
+use camino::{Utf8Path, Utf8PathBuf};
+use indexmap::IndexMap;
 use itertools::Itertools;
-use tempfile;
+use serde_json::Value;
+use tempfile::{self, TempDir};
 
 use crate::{
-	compile,
-	diagnostic::{found_errors, get_diagnostics},
+	ast::{AstContext, Scope},
+	compile, compile_with_options,
+	construct_tree::CONSTRUCT_TREE_METADATA_FILE_NAME,
+	diagnostic::{found_errors, get_diagnostics, Diagnostic, DiagnosticSeverity},
+	env_manifest::ENV_MANIFEST_FILE_NAME,
+	file_graph::{File, FileGraph},
+	files::{Files, EMITTED_FILES_MANIFEST},
+	jsify::inflight_deps::INFLIGHT_DEPENDENCIES_FILE_NAME,
+	parser::{parse_wing_project, SourceOverlays},
+	permissions::{PERMISSIONS_JSON_FILE_NAME, PERMISSIONS_MARKDOWN_FILE_NAME},
+	CompileOptions,
 };
 
+pub mod ast_gen;
+pub mod ast_printer;
+
+/// Metadata files every compilation emits, which are left out of snapshots since they have their own tests (and most of
+/// them have the absolute paths of the temporary project)
+const METADATA_FILE_NAMES: [&str; 6] = [
+	CONSTRUCT_TREE_METADATA_FILE_NAME,
+	EMITTED_FILES_MANIFEST,
+	ENV_MANIFEST_FILE_NAME,
+	INFLIGHT_DEPENDENCIES_FILE_NAME,
+	PERMISSIONS_JSON_FILE_NAME,
+	PERMISSIONS_MARKDOWN_FILE_NAME,
+];
+
 #[macro_export]
 macro_rules! assert_compile_dir {
   ($code:literal) => {
@@ -46,6 +71,87 @@ macro_rules! assert_compile_fail {
   };
 }
 
+/// Parses `source` as the file at `path` (which doesn't have to exist) and returns its AST, for tests of passes that
+/// only need the AST. Files it brings are parsed too, but only its own AST is returned.
+pub fn parse_source(path: &Utf8Path, source: &str, ast_ctx: &AstContext) -> Scope {
+	let mut asts = IndexMap::new();
+	parse_wing_project(
+		&File::new(path, "root"),
+		ast_ctx,
+		&SourceOverlays::from_iter([(path, source.to_string())]),
+		&mut Files::new(),
+		&mut FileGraph::default(),
+		&mut IndexMap::new(),
+		&mut IndexMap::new(),
+		&mut asts,
+	);
+	asts.swap_remove(path).expect("parsed file")
+}
+
+/// A program compiled from a single `main.w` in a temporary project, for tests that check the files a compilation
+/// emits. The project is removed when it's dropped.
+pub struct CompiledProject {
+	_project_dir: TempDir,
+	pub out_dir: Utf8PathBuf,
+}
+
+impl CompiledProject {
+	/// Reads a file from the output directory
+	pub fn read(&self, file_name: &str) -> String {
+		std::fs::read_to_string(self.out_dir.join(file_name)).unwrap()
+	}
+
+	/// Reads and parses a JSON file from the output directory
+	pub fn read_json(&self, file_name: &str) -> Value {
+		serde_json::from_str(&self.read(file_name)).unwrap()
+	}
+}
+
+/// Compiles `source` as the `main.w` of a temporary project, panicking if the compilation fails
+pub fn compile_source(source: &str) -> CompiledProject {
+	compile_source_with_options(source, &CompileOptions::default())
+}
+
+/// Compiles `source` like `compile_source`, with the given options
+pub fn compile_source_with_options(source: &str, options: &CompileOptions) -> CompiledProject {
+	// NOTE: this is needed for debugging to work regardless of where you run the test
+	env::set_current_dir(env!("CARGO_MANIFEST_DIR")).unwrap();
+
+	let project_dir = tempfile::tempdir().unwrap();
+	let project_path = Utf8Path::from_path(project_dir.path()).unwrap();
+	let out_dir = project_path.join("target/main.out/.wing");
+	let main = project_path.join("main.w");
+	let overlays = SourceOverlays::from_iter([(&main, source.to_string())]);
+	if compile_with_options(&main, &overlays, &out_dir, options).is_err() {
+		get_diagnostics().iter().for_each(|d| println!("{}", d));
+		panic!("expected no errors");
+	}
+
+	CompiledProject {
+		_project_dir: project_dir,
+		out_dir,
+	}
+}
+
+/// Compiles `source` like `compile_source_with_options`, expecting the compilation to fail, and returns its errors
+pub fn compile_source_with_errors(source: &str, options: &CompileOptions) -> Vec<Diagnostic> {
+	env::set_current_dir(env!("CARGO_MANIFEST_DIR")).unwrap();
+
+	let project_dir = tempfile::tempdir().unwrap();
+	let project_path = Utf8Path::from_path(project_dir.path()).unwrap();
+	let out_dir = project_path.join("target/main.out/.wing");
+	let main = project_path.join("main.w");
+	let overlays = SourceOverlays::from_iter([(&main, source.to_string())]);
+	assert!(
+		compile_with_options(&main, &overlays, &out_dir, options).is_err(),
+		"expected errors"
+	);
+	get_diagnostics()
+		.into_iter()
+		.filter(|d| d.severity == DiagnosticSeverity::Error)
+		.collect()
+}
+
 pub fn compile_dir(code: &str) -> String {
 	let snap = compile_code(code, true);
 	if found_errors() {
@@ -110,6 +216,11 @@ fn compile_code(code: &str, as_dir: bool) -> String {
 				.filter(|f| f.is_ok())
 				.map(|f| f.unwrap().path())
 				.filter(|f| f.extension().unwrap_or_default() != "map")
+				.filter(|f| {
+					!METADATA_FILE_NAMES
+						.iter()
+						.any(|name| f.file_name().unwrap_or_default() == *name)
+				})
 				.sorted_by_key(|f| f.as_os_str().to_string_lossy().to_string())
 				.collect::<Vec<_>>();
 
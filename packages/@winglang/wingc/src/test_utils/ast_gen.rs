@@ -0,0 +1,526 @@
+use indexmap::IndexMap;
+
+use crate::ast::{
+	AccessModifier, ArgList, AssignmentKind, AstContext, BinaryOperator, CalleeKind, CatchBlock, ElseIfBlock, Expr,
+	ExprKind, FunctionBody, FunctionDefinition, FunctionParameter, FunctionSignature, Literal, Phase, Reference, Scope,
+	Stmt, StmtKind, Symbol, TypeAnnotation, TypeAnnotationKind, UnaryOperator,
+};
+use crate::diagnostic::WingSpan;
+
+/// How deep blocks are nested in generated programs
+const MAX_BLOCK_DEPTH: usize = 3;
+/// How deep expressions are nested in generated programs
+const MAX_EXPR_DEPTH: usize = 3;
+
+const VARIABLES: &[&str] = &["v0", "v1", "v2", "v3"];
+const FUNCTIONS: &[&str] = &["f0", "f1", "f2"];
+const PROPERTIES: &[&str] = &["p0", "p1", "p2"];
+
+/// A small deterministic pseudo random number generator (splitmix64), so a failing seed can be replayed
+pub struct Rng(u64);
+
+impl Rng {
+	pub fn new(seed: u64) -> Self {
+		Self(seed)
+	}
+
+	fn next_u64(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+		z ^ (z >> 31)
+	}
+
+	/// A number in `0..n`
+	pub fn below(&mut self, n: usize) -> usize {
+		(self.next_u64() % n as u64) as usize
+	}
+
+	/// True with a probability of `percent`%
+	pub fn chance(&mut self, percent: usize) -> bool {
+		self.below(100) < percent
+	}
+
+	pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+		&items[self.below(items.len())]
+	}
+}
+
+/// Generates random syntactically valid programs, for checking that compiler passes keep the trees they're given
+/// intact. The programs aren't meant to type check: names are picked from small pools so they're often reused.
+/// Nodes get default spans, so trees are compared by printing them (see `ast_printer`).
+pub struct AstGenerator<'a> {
+	ctx: &'a AstContext,
+	rng: Rng,
+	block_depth: usize,
+	in_loop: bool,
+	in_function: bool,
+}
+
+impl<'a> AstGenerator<'a> {
+	pub fn new(ctx: &'a AstContext, seed: u64) -> Self {
+		Self {
+			ctx,
+			rng: Rng::new(seed),
+			block_depth: 0,
+			in_loop: false,
+			in_function: false,
+		}
+	}
+
+	/// Generates the root scope of a program
+	pub fn program(&mut self) -> Scope {
+		let count = 1 + self.rng.below(6);
+		self.statements(count)
+	}
+
+	fn statements(&mut self, count: usize) -> Scope {
+		let statements = (0..count).map(|idx| self.stmt(idx)).collect();
+		Scope::new(self.ctx, statements, WingSpan::default())
+	}
+
+	/// Generates a nested block
+	fn block(&mut self) -> Scope {
+		self.block_depth += 1;
+		let count = self.rng.below(4);
+		let scope = self.statements(count);
+		self.block_depth -= 1;
+		scope
+	}
+
+	fn loop_block(&mut self) -> Scope {
+		let outer = std::mem::replace(&mut self.in_loop, true);
+		let scope = self.block();
+		self.in_loop = outer;
+		scope
+	}
+
+	fn stmt(&mut self, idx: usize) -> Stmt {
+		let can_nest = self.block_depth < MAX_BLOCK_DEPTH;
+		let kind = loop {
+			let kind = match self.rng.below(11) {
+				0 | 1 => StmtKind::Let {
+					reassignable: self.rng.chance(30),
+					var_name: self.symbol(VARIABLES),
+					type_: self.rng.chance(30).then(|| self.type_annotation()),
+					initial_value: self.expr(0),
+					access: AccessModifier::Private,
+				},
+				2 => StmtKind::Expression(self.call(0)),
+				3 => StmtKind::Assignment {
+					kind: match self.rng.below(3) {
+						0 => AssignmentKind::Assign,
+						1 => AssignmentKind::AssignIncr,
+						_ => AssignmentKind::AssignDecr,
+					},
+					variable: if self.rng.chance(70) {
+						Reference::Identifier(self.symbol(VARIABLES))
+					} else {
+						Reference::InstanceMember {
+							object: Box::new(self.identifier(VARIABLES)),
+							property: self.symbol(PROPERTIES),
+							optional_accessor: false,
+						}
+					},
+					value: self.expr(0),
+				},
+				4 if can_nest => StmtKind::If {
+					condition: self.block_head(),
+					statements: self.block(),
+					else_if_statements: (0..self.rng.below(3))
+						.map(|_| ElseIfBlock {
+							condition: self.block_head(),
+							statements: self.block(),
+						})
+						.collect(),
+					else_statements: self.rng.chance(50).then(|| self.block()),
+				},
+				5 if can_nest => StmtKind::While {
+					condition: self.block_head(),
+					statements: self.loop_block(),
+				},
+				6 if can_nest => StmtKind::ForLoop {
+					iterator: self.symbol(VARIABLES),
+					second_iterator: self.rng.chance(30).then(|| self.symbol(VARIABLES)),
+					iterable: self.block_head(),
+					statements: self.loop_block(),
+				},
+				// bare blocks (`StmtKind::Scope`) aren't generated, the grammar can't tell them apart from map literals
+				7 if can_nest => {
+					let has_catch = self.rng.chance(70);
+					StmtKind::TryCatch {
+						try_statements: self.block(),
+						catch_block: has_catch.then(|| CatchBlock {
+							exception_var: self.rng.chance(50).then(|| self.symbol(VARIABLES)),
+							statements: self.block(),
+						}),
+						finally_statements: (!has_catch || self.rng.chance(30)).then(|| self.block()),
+					}
+				}
+				// `throw (x)` is parsed as a call to a function named `throw`
+				8 => StmtKind::Throw(
+					self.expr_where(|kind| !matches!(kind, ExprKind::Binary { .. } | ExprKind::FunctionClosure(_))),
+				),
+				9 if self.in_loop => {
+					if self.rng.chance(50) {
+						StmtKind::Break
+					} else {
+						StmtKind::Continue
+					}
+				}
+				10 if self.in_function => StmtKind::Return(self.rng.chance(70).then(|| self.expr(0))),
+				_ => continue,
+			};
+			break kind;
+		};
+		Stmt {
+			kind,
+			span: WingSpan::default(),
+			idx,
+			doc: None,
+		}
+	}
+
+	fn expr(&mut self, depth: usize) -> Expr {
+		if depth >= MAX_EXPR_DEPTH || self.rng.chance(30) {
+			return self.leaf();
+		}
+		let kind = match self.rng.below(9) {
+			0 => ExprKind::Binary {
+				op: self.binary_operator(),
+				left: Box::new(self.expr(depth + 1)),
+				right: Box::new(self.expr(depth + 1)),
+			},
+			1 => ExprKind::Unary {
+				op: if self.rng.chance(50) {
+					UnaryOperator::Minus
+				} else {
+					UnaryOperator::Not
+				},
+				exp: Box::new(self.expr(depth + 1)),
+			},
+			2 | 3 => return self.postfix(depth),
+			4 => ExprKind::ArrayLiteral {
+				type_: None,
+				items: (0..self.rng.below(4)).map(|_| self.expr(depth + 1)).collect(),
+			},
+			5 => ExprKind::MapLiteral {
+				type_: None,
+				fields: (0..1 + self.rng.below(3))
+					.map(|_| (self.string(), self.expr(depth + 1)))
+					.collect(),
+			},
+			6 => {
+				let fields: IndexMap<Symbol, Expr> = (0..1 + self.rng.below(3))
+					.map(|_| (self.symbol(PROPERTIES), self.expr(depth + 1)))
+					.collect();
+				ExprKind::JsonLiteral {
+					is_mut: false,
+					element: Box::new(self.new_expr(ExprKind::JsonMapLiteral { fields })),
+				}
+			}
+			7 if self.block_depth < MAX_BLOCK_DEPTH => ExprKind::FunctionClosure(self.closure()),
+			_ => return self.leaf(),
+		};
+		self.new_expr(kind)
+	}
+
+	/// An expression followed by a block (e.g. an `if` condition), which can't start with a brace
+	fn block_head(&mut self) -> Expr {
+		self.expr_where(|kind| !matches!(kind, ExprKind::MapLiteral { .. } | ExprKind::JsonLiteral { .. }))
+	}
+
+	fn expr_where(&mut self, allowed: impl Fn(&ExprKind) -> bool) -> Expr {
+		loop {
+			let expr = self.expr(0);
+			if allowed(&expr.kind) {
+				return expr;
+			}
+		}
+	}
+
+	/// Expressions that can be the object of a member access or the callee of a call without parentheses
+	fn postfix(&mut self, depth: usize) -> Expr {
+		if depth >= MAX_EXPR_DEPTH {
+			return self.identifier(VARIABLES);
+		}
+		let kind = match self.rng.below(3) {
+			0 => ExprKind::Reference(Reference::InstanceMember {
+				object: Box::new(self.postfix(depth + 1)),
+				property: self.symbol(PROPERTIES),
+				optional_accessor: self.rng.chance(20),
+			}),
+			1 => ExprKind::Reference(Reference::ElementAccess {
+				object: Box::new(self.postfix(depth + 1)),
+				index: Box::new(self.expr(depth + 1)),
+				optional_accessor: false,
+			}),
+			_ => return self.call(depth),
+		};
+		self.new_expr(kind)
+	}
+
+	fn call(&mut self, depth: usize) -> Expr {
+		let callee = if depth + 1 < MAX_EXPR_DEPTH && self.rng.chance(30) {
+			let object = self.postfix(depth + 1);
+			let property = self.symbol(FUNCTIONS);
+			self.new_expr(ExprKind::Reference(Reference::InstanceMember {
+				object: Box::new(object),
+				property,
+				optional_accessor: false,
+			}))
+		} else {
+			self.identifier(FUNCTIONS)
+		};
+		let pos_args = (0..self.rng.below(3)).map(|_| self.expr(depth + 1)).collect();
+		let named_args = (0..self.rng.below(2))
+			.map(|_| (self.symbol(PROPERTIES), self.expr(depth + 1)))
+			.collect();
+		self.new_expr(ExprKind::Call {
+			callee: CalleeKind::Expr(Box::new(callee)),
+			arg_list: ArgList::new(self.ctx, pos_args, named_args, WingSpan::default()),
+			optional_accessor: false,
+		})
+	}
+
+	fn closure(&mut self) -> FunctionDefinition {
+		let parameters = (0..self.rng.below(3))
+			.map(|i| FunctionParameter {
+				name: Symbol::global(format!("a{i}")),
+				type_annotation: self.type_annotation(),
+				reassignable: self.rng.chance(20),
+				variadic: false,
+			})
+			.collect();
+		let return_type = if self.rng.chance(50) {
+			self.type_annotation()
+		} else {
+			TypeAnnotation {
+				kind: TypeAnnotationKind::Inferred,
+				span: WingSpan::default(),
+			}
+		};
+
+		let outer_in_loop = std::mem::replace(&mut self.in_loop, false);
+		let outer_in_function = std::mem::replace(&mut self.in_function, true);
+		let body = self.block();
+		self.in_loop = outer_in_loop;
+		self.in_function = outer_in_function;
+
+		FunctionDefinition {
+			name: None,
+			body: FunctionBody::Statements(body),
+			signature: FunctionSignature {
+				parameters,
+				return_type: Box::new(return_type),
+				phase: Phase::Preflight,
+			},
+			is_static: false,
+			access: AccessModifier::Private,
+			doc: None,
+			span: WingSpan::default(),
+		}
+	}
+
+	fn leaf(&mut self) -> Expr {
+		let kind = match self.rng.below(6) {
+			0 => {
+				// whole numbers and halves print the same way they're parsed
+				let n = self.rng.below(1000) as f64 / if self.rng.chance(20) { 2.0 } else { 1.0 };
+				ExprKind::Literal(Literal::Number(n))
+			}
+			1 => return self.string(),
+			2 => ExprKind::Literal(Literal::Boolean(self.rng.chance(50))),
+			3 => ExprKind::Literal(Literal::Nil),
+			_ => return self.identifier(VARIABLES),
+		};
+		self.new_expr(kind)
+	}
+
+	fn string(&mut self) -> Expr {
+		let text: String = (0..self.rng.below(6))
+			.map(|_| *self.rng.pick(&['a', 'b', 'z', ' ']))
+			.collect();
+		// string literals keep their quotes in the AST
+		self.new_expr(ExprKind::Literal(Literal::String(format!("\"{text}\""))))
+	}
+
+	fn identifier(&mut self, names: &[&str]) -> Expr {
+		let symbol = self.symbol(names);
+		self.new_expr(ExprKind::Reference(Reference::Identifier(symbol)))
+	}
+
+	fn symbol(&mut self, names: &[&str]) -> Symbol {
+		Symbol::global(*self.rng.pick(names))
+	}
+
+	fn type_annotation(&mut self) -> TypeAnnotation {
+		let simple = |kind| {
+			Box::new(TypeAnnotation {
+				kind,
+				span: WingSpan::default(),
+			})
+		};
+		let kind = match self.rng.below(5) {
+			0 => TypeAnnotationKind::Number,
+			1 => TypeAnnotationKind::String,
+			2 => TypeAnnotationKind::Bool,
+			3 => TypeAnnotationKind::Optional(simple(TypeAnnotationKind::Number)),
+			_ => TypeAnnotationKind::Array(simple(TypeAnnotationKind::String)),
+		};
+		TypeAnnotation {
+			kind,
+			span: WingSpan::default(),
+		}
+	}
+
+	fn binary_operator(&mut self) -> BinaryOperator {
+		match self.rng.below(19) {
+			0 => BinaryOperator::AddOrConcat,
+			1 => BinaryOperator::Sub,
+			2 => BinaryOperator::Mul,
+			3 => BinaryOperator::Div,
+			4 => BinaryOperator::FloorDiv,
+			5 => BinaryOperator::Mod,
+			6 => BinaryOperator::Power,
+			7 => BinaryOperator::Greater,
+			8 => BinaryOperator::GreaterOrEqual,
+			9 => BinaryOperator::Less,
+			10 => BinaryOperator::LessOrEqual,
+			11 => BinaryOperator::Equal,
+			12 => BinaryOperator::NotEqual,
+			13 => BinaryOperator::LogicalAnd,
+			14 => BinaryOperator::LogicalOr,
+			15 => BinaryOperator::UnwrapOr,
+			16 => BinaryOperator::BitwiseOr,
+			17 => BinaryOperator::BitwiseAnd,
+			_ => BinaryOperator::In,
+		}
+	}
+
+	fn new_expr(&self, kind: ExprKind) -> Expr {
+		Expr::new(self.ctx, kind, WingSpan::default())
+	}
+}
+
+mod tests {
+	use camino::Utf8Path;
+
+	use super::AstGenerator;
+	use crate::ast::{AstContext, Expr, ExprId, Scope, Stmt};
+	use crate::diagnostic::{get_diagnostics, reset_diagnostics, WingSpan};
+	use crate::fold::Fold;
+	use crate::test_utils::ast_printer::print_program;
+	use crate::test_utils::parse_source;
+	use crate::visit::{self, Visit};
+
+	/// How many random programs each property is checked with
+	const SEEDS: u64 = 300;
+
+	struct IdentityFold;
+
+	impl Fold for IdentityFold {}
+
+	/// Collects the ids of all the expressions of a tree, in visiting order
+	#[derive(Default)]
+	struct ExprIds(Vec<ExprId>);
+
+	impl<'a> Visit<'a> for ExprIds {
+		fn visit_expr(&mut self, node: &'a Expr) {
+			self.0.push(node.id);
+			visit::visit_expr(self, node);
+		}
+	}
+
+	fn expr_ids(scope: &Scope) -> Vec<ExprId> {
+		let mut ids = ExprIds::default();
+		ids.visit_scope(scope);
+		ids.0
+	}
+
+	/// Checks that the span of every statement and expression is within the span of the node it's nested in
+	#[derive(Default)]
+	struct SpanNesting {
+		enclosing: Vec<WingSpan>,
+		errors: Vec<String>,
+	}
+
+	impl SpanNesting {
+		fn enter(&mut self, kind: &str, span: &WingSpan) {
+			if let Some(outer) = self.enclosing.last() {
+				if span.start_offset < outer.start_offset || span.end_offset > outer.end_offset {
+					self
+						.errors
+						.push(format!("{kind} at {span} is outside of its enclosing node at {outer}"));
+				}
+			}
+			self.enclosing.push(span.clone());
+		}
+	}
+
+	impl<'a> Visit<'a> for SpanNesting {
+		fn visit_scope(&mut self, node: &'a Scope) {
+			self.enter("scope", &node.span);
+			visit::visit_scope(self, node);
+			self.enclosing.pop();
+		}
+
+		fn visit_stmt(&mut self, node: &'a Stmt) {
+			self.enter("statement", &node.span);
+			visit::visit_stmt(self, node);
+			self.enclosing.pop();
+		}
+
+		fn visit_expr(&mut self, node: &'a Expr) {
+			self.enter("expression", &node.span);
+			visit::visit_expr(self, node);
+			self.enclosing.pop();
+		}
+	}
+
+	fn parse(source: &str, ast_ctx: &AstContext) -> Scope {
+		parse_source(Utf8Path::new("/project/main.w"), source, ast_ctx)
+	}
+
+	#[test]
+	fn identity_fold_keeps_generated_trees() {
+		for seed in 0..SEEDS {
+			let ast_ctx = AstContext::new();
+			let program = AstGenerator::new(&ast_ctx, seed).program();
+			let source = print_program(&program);
+			let ids = expr_ids(&program);
+
+			let folded = IdentityFold.fold_scope(program);
+			assert_eq!(print_program(&folded), source, "seed {seed}");
+			assert_eq!(expr_ids(&folded), ids, "seed {seed}");
+		}
+	}
+
+	#[test]
+	fn printed_trees_parse_back_to_the_same_tree() {
+		for seed in 0..SEEDS {
+			let ast_ctx = AstContext::new();
+			let source = print_program(&AstGenerator::new(&ast_ctx, seed).program());
+
+			reset_diagnostics();
+			let parsed = parse(&source, &ast_ctx);
+			let diagnostics = get_diagnostics();
+			assert!(
+				diagnostics.is_empty(),
+				"seed {seed}: {}\n{source}",
+				diagnostics.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("\n")
+			);
+			assert_eq!(print_program(&parsed), source, "seed {seed}");
+
+			let mut spans = SpanNesting::default();
+			spans.visit_scope(&parsed);
+			assert!(spans.errors.is_empty(), "seed {seed}: {:#?}\n{source}", spans.errors);
+
+			let ids = expr_ids(&parsed);
+			let folded = IdentityFold.fold_scope(parsed);
+			assert_eq!(print_program(&folded), source, "seed {seed}");
+			assert_eq!(expr_ids(&folded), ids, "seed {seed}");
+		}
+	}
+}
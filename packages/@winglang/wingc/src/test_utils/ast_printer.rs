@@ -0,0 +1,265 @@
+use itertools::Itertools;
+
+use crate::ast::{
+	ArgList, AssignmentKind, BinaryOperator, CalleeKind, Expr, ExprKind, FunctionBody, FunctionDefinition, Literal,
+	Phase, Reference, Scope, Stmt, StmtKind, TypeAnnotationKind, UnaryOperator,
+};
+
+/// Prints an AST back to Wing source in a canonical form: every binary and unary operation is parenthesized and
+/// every statement is on its own line, so two trees print the same only if they have the same structure (spans and
+/// ids aside). Only the kinds of nodes `ast_gen` generates are supported.
+pub fn print_program(scope: &Scope) -> String {
+	scope.statements.iter().map(|stmt| print_stmt(stmt, 0)).collect()
+}
+
+fn print_stmt(stmt: &Stmt, indent: usize) -> String {
+	let code = match &stmt.kind {
+		StmtKind::Let {
+			reassignable,
+			var_name,
+			initial_value,
+			type_,
+			..
+		} => {
+			let var = if *reassignable { "var " } else { "" };
+			let type_ = type_.as_ref().map(|t| format!(": {t}")).unwrap_or_default();
+			format!("let {var}{var_name}{type_} = {};", print_expr(initial_value, indent))
+		}
+		StmtKind::Expression(expr) => format!("{};", print_expr(expr, indent)),
+		StmtKind::Assignment { kind, variable, value } => {
+			let op = match kind {
+				AssignmentKind::Assign => "=",
+				AssignmentKind::AssignIncr => "+=",
+				AssignmentKind::AssignDecr => "-=",
+			};
+			format!(
+				"{} {op} {};",
+				print_reference(variable, indent),
+				print_expr(value, indent)
+			)
+		}
+		StmtKind::If {
+			condition,
+			statements,
+			else_if_statements,
+			else_statements,
+		} => {
+			let mut code = format!(
+				"if {} {}",
+				print_expr(condition, indent),
+				print_scope(statements, indent)
+			);
+			for else_if in else_if_statements {
+				code.push_str(&format!(
+					" else if {} {}",
+					print_expr(&else_if.condition, indent),
+					print_scope(&else_if.statements, indent)
+				));
+			}
+			if let Some(else_statements) = else_statements {
+				code.push_str(&format!(" else {}", print_scope(else_statements, indent)));
+			}
+			code
+		}
+		StmtKind::While { condition, statements } => format!(
+			"while {} {}",
+			print_expr(condition, indent),
+			print_scope(statements, indent)
+		),
+		StmtKind::ForLoop {
+			iterator,
+			second_iterator,
+			iterable,
+			statements,
+		} => format!(
+			"for {iterator}{} in {} {}",
+			second_iterator.as_ref().map(|s| format!(", {s}")).unwrap_or_default(),
+			print_expr(iterable, indent),
+			print_scope(statements, indent)
+		),
+		StmtKind::Break => "break;".to_string(),
+		StmtKind::Continue => "continue;".to_string(),
+		StmtKind::Return(None) => "return;".to_string(),
+		StmtKind::Return(Some(value)) => format!("return {};", print_expr(value, indent)),
+		StmtKind::Throw(value) => format!("throw {};", print_expr(value, indent)),
+		StmtKind::Scope(scope) => print_scope(scope, indent),
+		StmtKind::TryCatch {
+			try_statements,
+			catch_block,
+			finally_statements,
+		} => {
+			let mut code = format!("try {}", print_scope(try_statements, indent));
+			if let Some(catch_block) = catch_block {
+				let var = catch_block
+					.exception_var
+					.as_ref()
+					.map(|var| format!("{var} "))
+					.unwrap_or_default();
+				code.push_str(&format!(" catch {var}{}", print_scope(&catch_block.statements, indent)));
+			}
+			if let Some(finally_statements) = finally_statements {
+				code.push_str(&format!(" finally {}", print_scope(finally_statements, indent)));
+			}
+			code
+		}
+		other => panic!("printing {other:?} is not supported"),
+	};
+	format!("{}{code}\n", "  ".repeat(indent))
+}
+
+fn print_scope(scope: &Scope, indent: usize) -> String {
+	let statements: String = scope
+		.statements
+		.iter()
+		.map(|stmt| print_stmt(stmt, indent + 1))
+		.collect();
+	format!("{{\n{statements}{}}}", "  ".repeat(indent))
+}
+
+fn print_expr(expr: &Expr, indent: usize) -> String {
+	match &expr.kind {
+		ExprKind::Literal(literal) => match literal {
+			Literal::Number(n) => n.to_string(),
+			// strings keep their quotes in the AST
+			Literal::String(s) => s.clone(),
+			Literal::NonInterpolatedString(s) => format!("#{s}"),
+			Literal::Boolean(b) => b.to_string(),
+			Literal::Nil => "nil".to_string(),
+			Literal::InterpolatedString(_) => panic!("printing interpolated strings is not supported"),
+		},
+		ExprKind::Reference(reference) => print_reference(reference, indent),
+		ExprKind::Call {
+			callee,
+			arg_list,
+			optional_accessor,
+		} => {
+			let callee = match callee {
+				CalleeKind::Expr(callee) => print_expr(callee, indent),
+				CalleeKind::SuperCall(method) => format!("super.{method}"),
+			};
+			let optional = if *optional_accessor { "?" } else { "" };
+			format!("{callee}{optional}({})", print_args(arg_list, indent))
+		}
+		ExprKind::Unary { op, exp } => match op {
+			// `-(x) ** y` would be parsed as `-((x) ** y)`
+			UnaryOperator::Minus => format!("(-({}))", print_expr(exp, indent)),
+			UnaryOperator::Not => format!("(!({}))", print_expr(exp, indent)),
+			UnaryOperator::OptionalUnwrap => format!("(({})!)", print_expr(exp, indent)),
+		},
+		ExprKind::Binary { op, left, right } => {
+			let op = match op {
+				BinaryOperator::AddOrConcat => "+",
+				BinaryOperator::Sub => "-",
+				BinaryOperator::Mul => "*",
+				BinaryOperator::Div => "/",
+				BinaryOperator::FloorDiv => "\\",
+				BinaryOperator::Mod => "%",
+				BinaryOperator::Power => "**",
+				BinaryOperator::Greater => ">",
+				BinaryOperator::GreaterOrEqual => ">=",
+				BinaryOperator::Less => "<",
+				BinaryOperator::LessOrEqual => "<=",
+				BinaryOperator::Equal => "==",
+				BinaryOperator::NotEqual => "!=",
+				BinaryOperator::LogicalAnd => "&&",
+				BinaryOperator::LogicalOr => "||",
+				BinaryOperator::UnwrapOr => "??",
+				BinaryOperator::In => "in",
+				BinaryOperator::BitwiseOr => "|",
+				BinaryOperator::BitwiseAnd => "&",
+			};
+			format!("({} {op} {})", print_expr(left, indent), print_expr(right, indent))
+		}
+		ExprKind::ArrayLiteral { type_, items } => format!(
+			"{}[{}]",
+			type_.as_ref().map(|t| t.to_string()).unwrap_or_default(),
+			items.iter().map(|item| print_expr(item, indent)).join(", ")
+		),
+		ExprKind::MapLiteral { type_, fields } => format!(
+			"{}{{{}}}",
+			type_.as_ref().map(|t| t.to_string()).unwrap_or_default(),
+			fields
+				.iter()
+				.map(|(key, value)| format!("{} => {}", print_expr(key, indent), print_expr(value, indent)))
+				.join(", ")
+		),
+		ExprKind::JsonLiteral { is_mut, element } => {
+			let is_map = matches!(element.kind, ExprKind::JsonMapLiteral { .. });
+			match (is_mut, is_map) {
+				(false, true) => print_expr(element, indent),
+				(false, false) => format!("Json {}", print_expr(element, indent)),
+				(true, _) => format!("MutJson {}", print_expr(element, indent)),
+			}
+		}
+		ExprKind::JsonMapLiteral { fields } => format!(
+			"{{{}}}",
+			fields
+				.iter()
+				.map(|(key, value)| format!("{key}: {}", print_expr(value, indent)))
+				.join(", ")
+		),
+		ExprKind::FunctionClosure(def) => print_closure(def, indent),
+		other => panic!("printing {other:?} is not supported"),
+	}
+}
+
+fn print_reference(reference: &Reference, indent: usize) -> String {
+	match reference {
+		Reference::Identifier(symbol) => symbol.name.clone(),
+		Reference::InstanceMember {
+			object,
+			property,
+			optional_accessor,
+		} => {
+			let accessor = if *optional_accessor { "?." } else { "." };
+			format!("{}{accessor}{property}", print_expr(object, indent))
+		}
+		Reference::SuperMember { property } => format!("super.{property}"),
+		Reference::ElementAccess {
+			object,
+			index,
+			optional_accessor,
+		} => {
+			let optional = if *optional_accessor { "?" } else { "" };
+			format!(
+				"{}{optional}[{}]",
+				print_expr(object, indent),
+				print_expr(index, indent)
+			)
+		}
+		Reference::TypeMember { type_name, property } => format!("{type_name}.{property}"),
+	}
+}
+
+fn print_args(arg_list: &ArgList, indent: usize) -> String {
+	let positional = arg_list.pos_args.iter().map(|arg| print_expr(arg, indent));
+	let named = arg_list
+		.named_args
+		.iter()
+		.map(|(name, arg)| format!("{name}: {}", print_expr(arg, indent)));
+	positional.chain(named).join(", ")
+}
+
+fn print_closure(def: &FunctionDefinition, indent: usize) -> String {
+	let phase = match def.signature.phase {
+		Phase::Inflight => "inflight ",
+		Phase::Preflight | Phase::Independent => "",
+	};
+	let parameters = def
+		.signature
+		.parameters
+		.iter()
+		.map(|p| {
+			let var = if p.reassignable { "var " } else { "" };
+			format!("{var}{}: {}", p.name, p.type_annotation)
+		})
+		.join(", ");
+	let return_type = match def.signature.return_type.kind {
+		TypeAnnotationKind::Inferred => String::new(),
+		_ => format!(": {}", def.signature.return_type),
+	};
+	let FunctionBody::Statements(body) = &def.body else {
+		panic!("printing extern functions is not supported");
+	};
+	format!("{phase}({parameters}){return_type} => {}", print_scope(body, indent))
+}
@@ -3,12 +3,14 @@ mod has_type_stmt;
 mod inference_visitor;
 pub(crate) mod jsii_importer;
 pub mod lifts;
+pub(crate) mod suggestions;
 pub mod symbol_env;
+pub(crate) mod type_diff;
 pub(crate) mod type_reference_transform;
 
 use crate::ast::{
 	self, AccessModifier, ArgListId, AssignmentKind, BringSource, CalleeKind, ClassField, ExplicitLift, ExprId,
-	FunctionDefinition, IfLet, Intrinsic, IntrinsicKind, New, TypeAnnotationKind,
+	FunctionDefinition, IfLet, IfLetCondition, Intrinsic, IntrinsicKind, LiteralTypeValue, New, TypeAnnotationKind,
 };
 use crate::ast::{
 	ArgList, BinaryOperator, Class as AstClass, ElseIfs, Enum as AstEnum, Expr, ExprKind, FunctionBody,
@@ -17,22 +19,30 @@ use crate::ast::{
 	UserDefinedType,
 };
 use crate::comp_ctx::{CompilationContext, CompilationPhase};
+use crate::content_hash::content_hash;
 use crate::diagnostic::{report_diagnostic, Diagnostic, DiagnosticAnnotation, DiagnosticSeverity, TypeError, WingSpan};
 use crate::docs::Docs;
+use crate::events::{emit_event, CacheKind, CompilerEvent};
 use crate::file_graph::{File, FileGraph};
-use crate::parser::normalize_path;
+use crate::messages::{
+	CANNOT_CALL_INTO_PHASE, INFLIGHT_OBJECT_ID, INVALID_OBJECT_SCOPE, NOT_REASSIGNABLE, TYPE_MISMATCH, UNKNOWN_SYMBOL,
+};
+use crate::parser::{is_entrypoint_file, package_name_and_version, struct_statics_class_name};
+use crate::paths::normalize_path;
 use crate::type_check::has_type_stmt::HasStatementVisitor;
 use crate::type_check::symbol_env::SymbolEnvKind;
+use crate::unreachable_code_visitor::scope_completes;
 use crate::visit::Visit;
 use crate::visit_context::{VisitContext, VisitorWithContext};
 use crate::visit_stmt_before_super::{CheckSuperCtorLocationVisitor, CheckValidBeforeSuperVisitor};
 use crate::visit_types::{VisitType, VisitTypeMut};
 use crate::{
 	debug, CONSTRUCT_BASE_CLASS, CONSTRUCT_BASE_INTERFACE, CONSTRUCT_NODE_PROPERTY, DEFAULT_PACKAGE_NAME,
-	UTIL_CLASS_NAME, WINGSDK_APP, WINGSDK_ARRAY, WINGSDK_ASSEMBLY_NAME, WINGSDK_BRINGABLE_MODULES, WINGSDK_DATETIME,
-	WINGSDK_DURATION, WINGSDK_GENERIC, WINGSDK_IRESOURCE, WINGSDK_JSON, WINGSDK_MAP, WINGSDK_MUT_ARRAY, WINGSDK_MUT_JSON,
-	WINGSDK_MUT_MAP, WINGSDK_MUT_SET, WINGSDK_NODE, WINGSDK_REGEX, WINGSDK_RESOURCE, WINGSDK_SET,
-	WINGSDK_SIM_IRESOURCE_FQN, WINGSDK_STD_MODULE, WINGSDK_STRING, WINGSDK_STRUCT,
+	INTERNAL_DIRECTORY_NAME, UTIL_CLASS_NAME, WINGSDK_APP, WINGSDK_ARRAY, WINGSDK_ASSEMBLY_NAME,
+	WINGSDK_BRINGABLE_MODULES, WINGSDK_DATETIME, WINGSDK_DURATION, WINGSDK_GENERIC, WINGSDK_HASHABLE_FQN,
+	WINGSDK_IRESOURCE, WINGSDK_JSON, WINGSDK_MAP, WINGSDK_MUT_ARRAY, WINGSDK_MUT_JSON, WINGSDK_MUT_MAP, WINGSDK_MUT_SET,
+	WINGSDK_NODE, WINGSDK_REGEX, WINGSDK_RESOURCE, WINGSDK_SET, WINGSDK_SIM_IRESOURCE_FQN, WINGSDK_STD_MODULE,
+	WINGSDK_STRING, WINGSDK_STRUCT,
 };
 use camino::{Utf8Path, Utf8PathBuf};
 use derivative::Derivative;
@@ -40,10 +50,12 @@ use indexmap::IndexMap;
 use itertools::{izip, Itertools};
 use jsii_importer::JsiiImporter;
 
+use std::cell::RefCell;
 use std::cmp;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Debug, Display};
 use std::iter::FilterMap;
+use std::mem::{discriminant, Discriminant};
 use symbol_env::{StatementIdx, SymbolEnv};
 use wingii::fqn::FQN;
 use wingii::type_system::TypeSystem;
@@ -52,7 +64,11 @@ use self::class_fields_init::VisitClassInit;
 use self::inference_visitor::{InferenceCounterVisitor, InferenceVisitor};
 use self::jsii_importer::JsiiImportSpec;
 use self::lifts::Lifts;
+use self::suggestions::{
+	access_modifier_hint, did_you_mean_hint, minimal_access_modifier, missing_fields_hint, similar_names,
+};
 use self::symbol_env::{LookupResult, LookupResultMut, SymbolEnvIter, SymbolEnvRef};
+use self::type_diff::diff_types;
 
 pub struct UnsafeRef<T>(*const T);
 
@@ -233,6 +249,12 @@ pub enum Type {
 	Regex,
 	Boolean,
 	Void,
+	/// The type of expressions that never produce a value, such as calls to functions that always throw.
+	/// It's a subtype of every other type.
+	Never,
+	/// A type-safe counterpart of `any`: every type is a subtype of it, but a value of this type must be
+	/// narrowed (e.g. with `unsafeCast`) before it can be used.
+	Unknown,
 	/// Immutable Json literals may store extra information about their known data
 	Json(Option<JsonData>),
 	MutJson,
@@ -253,6 +275,8 @@ pub enum Type {
 	Interface(Interface),
 	Struct(Struct),
 	Enum(Enum),
+	/// A union of string and/or number literal values, e.g. `"GET" | "POST"`
+	Literal(Vec<LiteralTypeValue>),
 	/// A special type for parameters that accept any stringable value.
 	/// If you have a value of this type, the only thing you know you can do
 	/// for sure is that you can stringify it.
@@ -264,6 +288,26 @@ pub const CLASS_INFLIGHT_INIT_NAME: &'static str = "$inflight_init";
 
 pub const CLOSURE_CLASS_HANDLE_METHOD: &'static str = "handle";
 
+/// A public method with this name makes instances of a class (or interface) comparable with `<`, `<=`, `>` and `>=`
+pub const COMPARE_TO_METHOD: &'static str = "compareTo";
+
+/// The method of `std.IHashable` that gives instances of a class (or interface) the hash they're `Set` members by
+pub const HASH_METHOD: &'static str = "hash";
+
+/// The property of an enum value that's its backing string (or number, for flags enums)
+pub const ENUM_VALUE_PROPERTY: &'static str = "value";
+
+/// How values are hashed as members of a `Set`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SetMemberHashing {
+	/// By their identity, for primitives and objects that are compared by reference
+	Identity,
+	/// By their contents, for structs, Json, immutable collections and the standard library's value types
+	Value,
+	/// By the result of their `hash` method, for classes and interfaces that implement `std.IHashable`
+	Method,
+}
+
 #[derive(Debug)]
 pub enum JsonDataKind {
 	Type(SpannedTypeInfo),
@@ -377,6 +421,26 @@ impl Class {
 			.find(|(name, v)| name == CLOSURE_CLASS_HANDLE_METHOD && v.type_.is_inflight_function())
 			.map(|(_, v)| v.type_)
 	}
+
+	/// Returns the secondary constructors of this class: public static methods named `of` or `ofXxx` which
+	/// return an instance of the class itself. A `new` expression whose arguments don't fit the class's
+	/// constructor is routed to the first secondary constructor they do fit.
+	pub fn secondary_constructors(&self) -> Vec<VariableInfo> {
+		self
+			.methods(false)
+			.filter(|(name, v)| {
+				is_secondary_constructor_name(name)
+					&& matches!(v.kind, VariableKind::StaticMember)
+					&& v.access == AccessModifier::Public
+					&& v
+						.type_
+						.as_function_sig()
+						.and_then(|sig| sig.return_type.as_class())
+						.map_or(false, |c| std::ptr::eq(c, self))
+			})
+			.map(|(_, v)| v)
+			.collect()
+	}
 }
 
 #[derive(Derivative)]
@@ -516,6 +580,22 @@ pub struct Enum {
 	pub docs: Docs,
 	/// Variant name and optional documentation
 	pub values: IndexMap<Symbol, Option<String>>,
+	/// Variants with an explicit backing string, the rest are backed by their name
+	pub backing_values: IndexMap<Symbol, String>,
+	/// Variants of a flags enum are backed by a distinct bit each (see `flag_value`) instead of a string
+	pub is_flags: bool,
+}
+
+impl Enum {
+	/// The string a variant is represented by at runtime (and in Json)
+	pub fn backing_value<'a>(&'a self, value: &'a Symbol) -> &'a str {
+		self.backing_values.get(value).unwrap_or(&value.name)
+	}
+
+	/// The bit a variant of a flags enum is represented by at runtime (and in Json)
+	pub fn flag_value(&self, value: &Symbol) -> u32 {
+		1 << self.values.get_index_of(value).expect("an enum value")
+	}
 }
 
 #[derive(Debug)]
@@ -635,6 +715,14 @@ impl Subtype for Type {
 					true
 				}
 			}
+			(Self::Never, _) => {
+				// A value of type never can't exist, so it can stand in for any type
+				true
+			}
+			(_, Self::Unknown) => {
+				// Any value can be stored in an unknown, but it must be narrowed before it can be used
+				true
+			}
 			(Self::Function(l0), Self::Interface(r0)) => {
 				// TODO: Hack to make functions compatible with interfaces
 				// Remove this after https://github.com/winglang/wing/issues/1448
@@ -729,7 +817,9 @@ impl Subtype for Type {
 				// method type (aka "closure classes").
 
 				// First, check if there is exactly one inflight method in the interface
-				let mut inflight_methods = iface.methods(true).filter(|(_name, v)| v.type_.is_inflight_function());
+				let mut inflight_methods = iface
+					.methods(true)
+					.filter(|(_name, v)| v.kind != VariableKind::StaticMember && v.type_.is_inflight_function());
 				let handler_method = inflight_methods.next();
 				if handler_method.is_none() || inflight_methods.next().is_some() {
 					return false;
@@ -788,10 +878,9 @@ impl Subtype for Type {
 				l.is_subtype_of(r)
 			}
 			(Self::MutArray(l0), Self::MutArray(r0)) => {
-				// An Array type is a subtype of another Array type if the value type is a subtype of the other value type
-				let l: &Type = l0;
-				let r: &Type = r0;
-				l.is_subtype_of(r)
+				// Mutable collections are invariant in their value type: if a `MutArray<Cat>` were a subtype of
+				// `MutArray<Animal>` then a `Dog` could be pushed into it through the supertype.
+				l0.is_same_type_as(r0)
 			}
 			(Self::Map(l0), Self::Map(r0)) => {
 				// A Map type is a subtype of another Map type if the value type is a subtype of the other value type
@@ -800,10 +889,8 @@ impl Subtype for Type {
 				l.is_subtype_of(r)
 			}
 			(Self::MutMap(l0), Self::MutMap(r0)) => {
-				// A Map type is a subtype of another Map type if the value type is a subtype of the other value type
-				let l: &Type = l0;
-				let r: &Type = r0;
-				l.is_subtype_of(r)
+				// Mutable collections are invariant in their value type (see `MutArray`)
+				l0.is_same_type_as(r0)
 			}
 			(Self::Set(l0), Self::Set(r0)) => {
 				// A Set type is a subtype of another Set type if the value type is a subtype of the other value type
@@ -812,10 +899,8 @@ impl Subtype for Type {
 				l.is_subtype_of(r)
 			}
 			(Self::MutSet(l0), Self::MutSet(r0)) => {
-				// A Set type is a subtype of another Set type if the value type is a subtype of the other value type
-				let l: &Type = l0;
-				let r: &Type = r0;
-				l.is_subtype_of(r)
+				// Mutable collections are invariant in their value type (see `MutArray`)
+				l0.is_same_type_as(r0)
 			}
 			(Self::Enum(e0), Self::Enum(e1)) => {
 				// An enum type is a subtype of another Enum type only if they are the exact same
@@ -850,6 +935,13 @@ impl Subtype for Type {
 			(Self::Json(_), Self::Json(_)) => true,
 			(Self::MutJson, Self::Stringable) => true,
 			(Self::Enum(_), Self::Stringable) => true,
+			(Self::Literal(l0), Self::Literal(r0)) => {
+				// A literal type is a subtype of another if all of its values are allowed by the other
+				l0.iter().all(|v| r0.contains(v))
+			}
+			(Self::Literal(l0), Self::String) => l0.iter().all(|v| matches!(v, LiteralTypeValue::String(_))),
+			(Self::Literal(l0), Self::Number) => l0.iter().all(|v| matches!(v, LiteralTypeValue::Number(_))),
+			(Self::Literal(_), Self::Stringable) => true,
 			(Self::Struct(s), Self::Stringable) => s.fields(true).map(|(_, v)| v.type_).all(|t| t.is_stringable()),
 			_ => false,
 		}
@@ -957,6 +1049,8 @@ impl Display for Type {
 			Type::Regex => write!(f, "regex"),
 			Type::Boolean => write!(f, "bool"),
 			Type::Void => write!(f, "void"),
+			Type::Never => write!(f, "never"),
+			Type::Unknown => write!(f, "unknown"),
 			Type::Json(_) => write!(f, "Json"),
 			Type::MutJson => write!(f, "MutJson"),
 			Type::Nil => write!(f, "nil"),
@@ -981,6 +1075,7 @@ impl Display for Type {
 			Type::Set(v) => write!(f, "Set<{}>", v),
 			Type::MutSet(v) => write!(f, "MutSet<{}>", v),
 			Type::Enum(s) => write!(f, "{}", s.name.name),
+			Type::Literal(values) => write!(f, "{}", values.iter().join(" | ")),
 		}
 	}
 }
@@ -1150,6 +1245,53 @@ impl TypeRef {
 		matches!(**self, Type::Unresolved)
 	}
 
+	/// Returns true if this is the placeholder for a generic type argument (`T1`)
+	pub fn is_generic_type_argument(&self) -> bool {
+		matches!(**self, Type::Class(ref c) if c.fqn.as_ref().is_some_and(|fqn| fqn.contains(WINGSDK_GENERIC)))
+	}
+
+	/// Returns true if this type is, or refers anywhere within it to, a type we failed to resolve
+	pub fn contains_unresolved(&self) -> bool {
+		match **self {
+			Type::Unresolved => true,
+			Type::Optional(t)
+			| Type::Array(t)
+			| Type::MutArray(t)
+			| Type::Map(t)
+			| Type::MutMap(t)
+			| Type::Set(t)
+			| Type::MutSet(t) => t.contains_unresolved(),
+			Type::Function(ref sig) => {
+				sig.parameters.iter().any(|p| p.typeref.contains_unresolved()) || sig.return_type.contains_unresolved()
+			}
+			_ => false,
+		}
+	}
+
+	/// Returns true if this type refers to a generic type argument (`T1`) anywhere within it
+	pub fn contains_generic_type_argument(&self) -> bool {
+		if self.is_generic_type_argument() {
+			return true;
+		}
+		match **self {
+			Type::Optional(t)
+			| Type::Array(t)
+			| Type::MutArray(t)
+			| Type::Map(t)
+			| Type::MutMap(t)
+			| Type::Set(t)
+			| Type::MutSet(t) => t.contains_generic_type_argument(),
+			Type::Function(ref sig) => {
+				sig
+					.parameters
+					.iter()
+					.any(|p| p.typeref.contains_generic_type_argument())
+					|| sig.return_type.contains_generic_type_argument()
+			}
+			_ => false,
+		}
+	}
+
 	pub fn is_json(&self) -> bool {
 		if let Type::Json(_) | Type::MutJson = **self {
 			return true;
@@ -1166,6 +1308,141 @@ impl TypeRef {
 		return false;
 	}
 
+	/// Returns true if values of this type are compared by reference with `==`. This is the case for functions and
+	/// for instances of classes and interfaces, except for the standard library's value classes (like `datetime`)
+	/// which are compared structurally like structs and Json. Optionals follow their inner type.
+	pub fn is_compared_by_reference(&self) -> bool {
+		match **self {
+			Type::Class(ref class) => {
+				!self.is_generic_type_argument()
+					&& (class.phase == Phase::Preflight
+						|| !class
+							.fqn
+							.as_ref()
+							.is_some_and(|fqn| fqn.starts_with(&format!("{WINGSDK_ASSEMBLY_NAME}.{WINGSDK_STD_MODULE}."))))
+			}
+			Type::Interface(_) | Type::Function(_) => true,
+			Type::Optional(t) => t.is_compared_by_reference(),
+			_ => false,
+		}
+	}
+
+	/// Returns the signature of the method that makes instances of this class or interface comparable with `<`, `<=`,
+	/// `>` and `>=`, if it has one. This is a public `compareTo` instance method which takes a single argument and
+	/// returns a `num` that's negative, zero or positive if the object is less than, equal to or greater than it.
+	pub fn compare_to_sig(&self) -> Option<&FunctionSignature> {
+		let method = match **self {
+			Type::Class(ref class) => class.get_method(&COMPARE_TO_METHOD.into()),
+			Type::Interface(ref iface) => iface.get_method(&COMPARE_TO_METHOD.into()),
+			_ => None,
+		}?;
+		let sig = method.type_.as_function_sig()?;
+		let is_protocol_method = method.access == AccessModifier::Public
+			&& matches!(method.kind, VariableKind::InstanceMember)
+			&& sig.parameters.len() == 1
+			&& sig.return_type.is_number();
+		is_protocol_method.then_some(sig)
+	}
+
+	/// Whether this class or interface implements `std.IHashable`, directly or through its parents
+	pub fn implements_hashable(&self) -> bool {
+		match **self {
+			Type::Class(ref class) => class
+				.implements
+				.iter()
+				.chain(class.parent.iter())
+				.any(|t| t.implements_hashable()),
+			Type::Interface(ref iface) => {
+				iface.fqn == WINGSDK_HASHABLE_FQN || iface.extends.iter().any(|t| t.implements_hashable())
+			}
+			_ => false,
+		}
+	}
+
+	/// Returns the signature of the `hash` method of a class or interface that implements `std.IHashable`
+	pub fn hash_sig(&self) -> Option<&FunctionSignature> {
+		if !self.implements_hashable() {
+			return None;
+		}
+		let method = match **self {
+			Type::Class(ref class) => class.get_method(&HASH_METHOD.into()),
+			Type::Interface(ref iface) => iface.get_method(&HASH_METHOD.into()),
+			_ => None,
+		}?;
+		method.type_.as_function_sig()
+	}
+
+	/// Returns how values of this type are hashed as members of a `Set`, or why they can't be members of one.
+	/// Values which are compared structurally with `==` are hashed by their contents, so they can only be members if
+	/// they can't change.
+	pub fn set_member_hashing(&self) -> Result<SetMemberHashing, String> {
+		match **self {
+			Type::Class(_) | Type::Interface(_) => {
+				if self.implements_hashable() {
+					Ok(SetMemberHashing::Method)
+				} else if self.is_compared_by_reference() {
+					Ok(SetMemberHashing::Identity)
+				} else {
+					Ok(SetMemberHashing::Value)
+				}
+			}
+			Type::Struct(ref s) => {
+				for (name, field) in s.fields(true) {
+					if let Err(reason) = field.type_.set_member_hashing() {
+						return Err(format!("its field \"{name}\" can't be hashed since {reason}"));
+					}
+				}
+				Ok(SetMemberHashing::Value)
+			}
+			Type::Array(t) | Type::Map(t) | Type::Set(t) => t.set_member_hashing().map(|_| SetMemberHashing::Value),
+			Type::Json(_) | Type::Duration => Ok(SetMemberHashing::Value),
+			Type::MutJson | Type::MutArray(_) | Type::MutMap(_) | Type::MutSet(_) => {
+				Err("it's mutable, so its hash could change while it's in the set".to_string())
+			}
+			Type::Optional(t) => t.set_member_hashing(),
+			_ => Ok(SetMemberHashing::Identity),
+		}
+	}
+
+	/// Code for a value of this type to initialize a missing field with, which the user is expected to replace. Types
+	/// without an obvious empty value get `unsafeCast(nil)`, which type checks as any type.
+	pub fn placeholder_value(&self) -> String {
+		self.placeholder_value_with_depth(0)
+	}
+
+	fn placeholder_value_with_depth(&self, depth: usize) -> String {
+		// Nested structs are filled in too, up to a point (a struct that contains itself can't be filled in)
+		const MAX_DEPTH: usize = 3;
+		match **self {
+			Type::Number => "0".to_string(),
+			Type::String => "\"\"".to_string(),
+			Type::Boolean => "false".to_string(),
+			Type::Duration => "0s".to_string(),
+			Type::Array(_) => "[]".to_string(),
+			Type::Map(_) => "{}".to_string(),
+			Type::MutArray(_) | Type::Set(_) | Type::MutSet(_) => format!("{self}[]"),
+			Type::MutMap(_) => format!("{self}{{}}"),
+			Type::Json(_) => "Json {}".to_string(),
+			Type::MutJson => "MutJson {}".to_string(),
+			Type::Literal(ref values) => values[0].to_string(),
+			Type::Enum(ref e) => format!("{}.{}", e.name, e.values.first().expect("an enum value").0),
+			Type::Optional(_) => "nil".to_string(),
+			Type::Struct(ref s) if depth < MAX_DEPTH => {
+				let fields = s
+					.fields(true)
+					.filter(|(_, field)| !field.type_.is_option())
+					.map(|(name, field)| format!("{name}: {}", field.type_.placeholder_value_with_depth(depth + 1)))
+					.collect_vec();
+				if fields.is_empty() {
+					format!("{} {{}}", s.name)
+				} else {
+					format!("{} {{ {} }}", s.name, fields.join(", "))
+				}
+			}
+			_ => "unsafeCast(nil)".to_string(),
+		}
+	}
+
 	pub fn is_preflight_object_type(&self) -> bool {
 		if let Type::Class(ref class) = **self {
 			return class.phase == Phase::Preflight;
@@ -1219,6 +1496,10 @@ impl TypeRef {
 		matches!(**self, Type::Void)
 	}
 
+	pub fn is_never(&self) -> bool {
+		matches!(**self, Type::Never)
+	}
+
 	pub fn is_option(&self) -> bool {
 		// "any" can also be `nil`
 		matches!(**self, Type::Optional(_) | Type::Anything)
@@ -1252,7 +1533,14 @@ impl TypeRef {
 	pub fn is_stringable(&self) -> bool {
 		matches!(
 			**self,
-			Type::String | Type::Number | Type::Boolean | Type::Json(_) | Type::MutJson | Type::Enum(_) | Type::Anything
+			Type::String
+				| Type::Number
+				| Type::Boolean
+				| Type::Json(_)
+				| Type::MutJson
+				| Type::Enum(_)
+				| Type::Literal(_)
+				| Type::Anything
 		) || self.is_stringable_struct()
 	}
 
@@ -1315,6 +1603,8 @@ impl TypeRef {
 			Type::String => true,
 			Type::Boolean => true,
 			Type::Void => true,
+			Type::Never => true,
+			Type::Literal(_) => true,
 			Type::Json(_) => true,
 			Type::MutJson => true,
 			Type::Nil => true,
@@ -1337,6 +1627,7 @@ impl TypeRef {
 			Type::Class(_) => false,
 			Type::Interface(_) => false,
 			Type::Stringable => false,
+			Type::Unknown => false,
 		}
 	}
 
@@ -1358,6 +1649,9 @@ impl TypeRef {
 			Type::Number => true,
 			Type::String => true,
 			Type::Boolean => true,
+			Type::Literal(_) => true,
+			// Enum values are represented by their backing string
+			Type::Enum(_) => true,
 			Type::MutJson | Type::Json(None) => true,
 			Type::Inferred(..) => true,
 			Type::Array(v) => v.is_json_legal_value(),
@@ -1482,6 +1776,8 @@ pub struct Types {
 	regex_idx: usize,
 	anything_idx: usize,
 	void_idx: usize,
+	never_idx: usize,
+	unknown_idx: usize,
 	json_idx: usize,
 	mut_json_idx: usize,
 	nil_idx: usize,
@@ -1500,8 +1796,20 @@ pub struct Types {
 	type_expressions: IndexMap<ExprId, Reference>,
 	/// Append empty struct to end of arg list
 	pub append_empty_struct_to_arglist: HashSet<ArgListId>,
-	/// Class counter, used to generate unique ids for class types
-	pub class_counter: usize,
+	/// Lookup table from a `new` expression's id to the name of the secondary constructor it was routed to
+	pub secondary_constructor_calls: HashMap<ExprId, String>,
+	/// Lookup table from the id of an expression that's added to, looked up in or removed from a `Set` to how it's hashed
+	/// (only for members which aren't hashed by identity)
+	pub set_members: HashMap<ExprId, SetMemberHashing>,
+	/// The unique ids given to class types so far
+	class_uids: HashSet<usize>,
+	/// Indices of the optional and collection types added so far, keyed by their kind and element type.
+	/// Primitive types don't need this since each of them is only allocated once, in `Types::new`.
+	interned_types: HashMap<(Discriminant<Type>, *const Type), usize>,
+	/// The directory of the first copy of each Wing library that was brought, keyed by package name and version
+	library_dirs: IndexMap<(String, String), Utf8PathBuf>,
+	/// A map from the directories of other copies of Wing libraries to the directory of their first copy
+	library_copies: HashMap<Utf8PathBuf, Utf8PathBuf>,
 }
 
 impl Types {
@@ -1523,6 +1831,10 @@ impl Types {
 		let anything_idx = types.len() - 1;
 		types.push(Box::new(Type::Void));
 		let void_idx = types.len() - 1;
+		types.push(Box::new(Type::Never));
+		let never_idx = types.len() - 1;
+		types.push(Box::new(Type::Unknown));
+		let unknown_idx = types.len() - 1;
 		types.push(Box::new(Type::Json(None)));
 		let json_idx = types.len() - 1;
 		types.push(Box::new(Type::MutJson));
@@ -1547,6 +1859,8 @@ impl Types {
 			regex_idx,
 			anything_idx,
 			void_idx,
+			never_idx,
+			unknown_idx,
 			json_idx,
 			mut_json_idx,
 			nil_idx,
@@ -1558,6 +1872,8 @@ impl Types {
 			inferences: Vec::new(),
 			type_expressions: IndexMap::new(),
 			append_empty_struct_to_arglist: HashSet::new(),
+			secondary_constructor_calls: HashMap::new(),
+			set_members: HashMap::new(),
 			libraries: SymbolEnv::new(
 				None,
 				SymbolEnvKind::Scope,
@@ -1572,10 +1888,50 @@ impl Types {
 				0,
 				DEFAULT_PACKAGE_NAME.to_string(),
 			),
-			// 1 based to avoid conflict with imported JSII classes. This isn't strictly needed since brought JSII classes are never accessed
-			// through their unique ID, but still good to avoid confusion.
-			class_counter: 1,
+			class_uids: HashSet::new(),
+			interned_types: HashMap::new(),
+			library_dirs: IndexMap::new(),
+			library_copies: HashMap::new(),
+		}
+	}
+
+	/// Returns the directory whose types are used for the Wing library brought from `module_dir`. All copies of the
+	/// same version of a library (e.g. in different `node_modules` directories) share the types of the first copy that
+	/// was brought, so values can be passed between them.
+	pub fn unify_library_dir(&mut self, module_dir: &Utf8Path) -> Utf8PathBuf {
+		let Some(identity) = package_name_and_version(module_dir) else {
+			return module_dir.to_owned();
+		};
+		let library_dir = self
+			.library_dirs
+			.entry(identity)
+			.or_insert_with(|| module_dir.to_owned())
+			.clone();
+		if library_dir != module_dir {
+			self.library_copies.insert(module_dir.to_owned(), library_dir.clone());
+		}
+		library_dir
+	}
+
+	/// Returns the directory of the copy of a Wing library whose types are used for the library in `module_dir`
+	/// (see `unify_library_dir`)
+	pub fn library_dir<'a>(&'a self, module_dir: &'a Utf8Path) -> &'a Utf8Path {
+		self
+			.library_copies
+			.get(module_dir)
+			.map_or(module_dir, |dir| dir.as_path())
+	}
+
+	/// Returns a unique id for a class type. The id is derived from a hash of the given key (identifying the class
+	/// by its file and name) so it's the same in every compilation of unchanged source code.
+	pub fn class_uid(&mut self, key: &str) -> usize {
+		let hash = content_hash(key);
+		// 0 is used for imported JSII classes
+		let mut uid = ((hash ^ (hash >> 32)) as u32).max(1) as usize;
+		while !self.class_uids.insert(uid) {
+			uid = uid.wrapping_add(1).max(1);
 		}
+		uid
 	}
 
 	pub fn number(&self) -> TypeRef {
@@ -1586,6 +1942,137 @@ impl Types {
 		self.get_typeref(self.string_idx)
 	}
 
+	/// If the given type is a literal type whose values share a primitive type, returns that type.
+	/// Otherwise returns the given type.
+	pub fn maybe_widen_literal(&self, type_: TypeRef) -> TypeRef {
+		match &*type_ {
+			Type::Literal(values) => self.literal_base_type(values).unwrap_or(type_),
+			_ => type_,
+		}
+	}
+
+	/// Returns the primitive type (`str` or `num`) shared by all values of a literal type, if there is one.
+	pub fn literal_base_type(&self, values: &[LiteralTypeValue]) -> Option<TypeRef> {
+		if values.iter().all(|v| matches!(v, LiteralTypeValue::String(_))) {
+			Some(self.string())
+		} else if values.iter().all(|v| matches!(v, LiteralTypeValue::Number(_))) {
+			Some(self.number())
+		} else {
+			None
+		}
+	}
+
+	/// Returns the least upper bound of two types: the most specific type both of them are subtypes of.
+	/// This is either one of the types, their optional version (when one of them is `nil` or optional),
+	/// a common base class or interface, or an immutable collection of the least upper bound of their elements.
+	/// Returns None if there's no such type (or if there are several equally specific ones).
+	pub fn least_upper_bound(&mut self, a: TypeRef, b: TypeRef) -> Option<TypeRef> {
+		if a.is_subtype_of(&b) {
+			return Some(b);
+		}
+		if b.is_subtype_of(&a) {
+			return Some(a);
+		}
+
+		if a.is_nil() {
+			return Some(self.make_option(b));
+		}
+		if b.is_nil() {
+			return Some(self.make_option(a));
+		}
+		if a.is_option() || b.is_option() {
+			let inner = self.least_upper_bound(*a.maybe_unwrap_option(), *b.maybe_unwrap_option())?;
+			return Some(self.make_option(inner));
+		}
+
+		if let (Type::Literal(l), Type::Literal(r)) = (&*a, &*b) {
+			let mut values = l.clone();
+			values.extend(r.iter().filter(|v| !l.contains(v)).cloned());
+			return Some(self.add_type(Type::Literal(values)));
+		}
+		if matches!(*a, Type::Literal(_)) || matches!(*b, Type::Literal(_)) {
+			let (wide_a, wide_b) = (self.maybe_widen_literal(a), self.maybe_widen_literal(b));
+			if wide_a.is_same_type_as(&a) && wide_b.is_same_type_as(&b) {
+				return None;
+			}
+			return self.least_upper_bound(wide_a, wide_b);
+		}
+
+		match (&*a, &*b) {
+			(Type::Array(l) | Type::MutArray(l), Type::Array(r) | Type::MutArray(r)) => {
+				let inner = self.least_upper_bound(*l, *r)?;
+				Some(self.add_type(Type::Array(inner)))
+			}
+			(Type::Map(l) | Type::MutMap(l), Type::Map(r) | Type::MutMap(r)) => {
+				let inner = self.least_upper_bound(*l, *r)?;
+				Some(self.add_type(Type::Map(inner)))
+			}
+			(Type::Set(l) | Type::MutSet(l), Type::Set(r) | Type::MutSet(r)) => {
+				let inner = self.least_upper_bound(*l, *r)?;
+				Some(self.add_type(Type::Set(inner)))
+			}
+			(Type::Class(_) | Type::Interface(_), Type::Class(_) | Type::Interface(_)) => self.common_supertype(a, b),
+			_ => None,
+		}
+	}
+
+	/// Finds the most specific class, or otherwise interface, that two class-like types both derive from.
+	/// The implicit `std.Resource` base of preflight classes (and the interfaces it implements) isn't considered.
+	fn common_supertype(&self, a: TypeRef, b: TypeRef) -> Option<TypeRef> {
+		let mut ancestor = a.as_class().and_then(|c| c.parent);
+		while let Some(class) = ancestor.filter(|t| !self.is_resource_base_type(t)) {
+			if b.is_subtype_of(&class) {
+				return Some(class);
+			}
+			ancestor = class.as_class().and_then(|c| c.parent);
+		}
+
+		let mut candidates = vec![];
+		self.collect_interfaces(a, &mut candidates);
+		candidates.retain(|iface| b.is_subtype_of(iface));
+		// Drop interfaces that are extended by another candidate, leaving only the most specific ones
+		let most_specific = candidates
+			.iter()
+			.filter(|iface| !candidates.iter().any(|other| other.is_strict_subtype_of(iface)))
+			.fold(vec![], |mut acc: Vec<TypeRef>, iface| {
+				if !acc.iter().any(|t| t.is_same_type_as(iface)) {
+					acc.push(*iface);
+				}
+				acc
+			});
+		match most_specific.as_slice() {
+			[iface] => Some(*iface),
+			_ => None,
+		}
+	}
+
+	/// Collects all interfaces a class-like type implements or extends, directly or through its ancestors.
+	fn collect_interfaces(&self, t: TypeRef, interfaces: &mut Vec<TypeRef>) {
+		match &*t {
+			Type::Class(c) => {
+				for iface in &c.implements {
+					interfaces.push(*iface);
+					self.collect_interfaces(*iface, interfaces);
+				}
+				if let Some(parent) = c.parent.filter(|p| !self.is_resource_base_type(p)) {
+					self.collect_interfaces(parent, interfaces);
+				}
+			}
+			Type::Interface(i) => {
+				for iface in &i.extends {
+					interfaces.push(*iface);
+					self.collect_interfaces(*iface, interfaces);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn is_resource_base_type(&self, t: &TypeRef) -> bool {
+		let resource_fqn = format!("{}.{}", WINGSDK_ASSEMBLY_NAME, WINGSDK_RESOURCE);
+		t.as_class().and_then(|c| c.fqn.as_ref()) == Some(&resource_fqn)
+	}
+
 	pub fn stringable(&self) -> TypeRef {
 		self.get_typeref(self.stringable_idx)
 	}
@@ -1622,9 +2109,39 @@ impl Types {
 		self.get_typeref(self.void_idx)
 	}
 
+	pub fn never(&self) -> TypeRef {
+		self.get_typeref(self.never_idx)
+	}
+
+	pub fn unknown(&self) -> TypeRef {
+		self.get_typeref(self.unknown_idx)
+	}
+
+	/// Adds a type to the collection and returns a reference to it.
+	///
+	/// Optional and collection types are hash-consed, so e.g. all `Array<str>` types share a single reference and
+	/// can be compared by pointer. This means types returned from here must never be modified in place.
 	pub fn add_type(&mut self, t: Type) -> TypeRef {
+		let intern_key = match &t {
+			Type::Optional(inner)
+			| Type::Array(inner)
+			| Type::MutArray(inner)
+			| Type::Map(inner)
+			| Type::MutMap(inner)
+			| Type::Set(inner)
+			| Type::MutSet(inner) => Some((discriminant(&t), inner.0)),
+			_ => None,
+		};
+		if let Some(idx) = intern_key.and_then(|key| self.interned_types.get(&key)) {
+			return self.get_typeref(*idx);
+		}
+
 		self.types.push(Box::new(t));
-		self.get_typeref(self.types.len() - 1)
+		let idx = self.types.len() - 1;
+		if let Some(key) = intern_key {
+			self.interned_types.insert(key, idx);
+		}
+		self.get_typeref(idx)
 	}
 
 	/// If the type is inferred and the real type is known, return it.
@@ -1689,6 +2206,7 @@ impl Types {
 					annotations: vec![],
 					hints: vec![],
 					severity: DiagnosticSeverity::Error,
+					code: None,
 				});
 				existing_type_option.replace(error);
 				return;
@@ -1698,6 +2216,12 @@ impl Types {
 		existing_type_option.replace(new_type);
 	}
 
+	/// Replaces the type of an already linked inference with a supertype of it (see `least_upper_bound`)
+	pub fn widen_inferred_type(&mut self, id: InferenceId, new_type: TypeRef) {
+		let existing_type_option = self.inferences.get_mut(id).expect("Inference id out of bounds");
+		existing_type_option.replace(new_type);
+	}
+
 	pub fn make_inference(&mut self) -> TypeRef {
 		let id = self.inferences.len();
 		self.inferences.push(None);
@@ -1842,6 +2366,12 @@ impl Types {
 		self.scope_envs[scope_id].expect("Scope should have an env")
 	}
 
+	/// Whether the expression is the object of an instance member reference that actually refers to a type, and
+	/// which `TypeReferenceTransformer` hasn't replaced yet
+	pub fn is_type_expression(&self, expr_id: ExprId) -> bool {
+		self.type_expressions.contains_key(&expr_id)
+	}
+
 	/// Obtain the type of a given expression id. Returns None if the expression has not been type checked yet. If
 	/// this is called after type checking, it should always return Some.
 	pub fn try_get_expr_type(&self, expr_id: ExprId) -> Option<TypeRef> {
@@ -1890,6 +2420,7 @@ impl Types {
 			Type::Struct(_) => "Struct",
 
 			Type::Optional(t) => return self.get_std_class(t),
+			Type::Literal(values) => return self.get_std_class(&self.literal_base_type(values)?),
 
 			Type::Function(_)
 			| Type::Class(_)
@@ -1897,6 +2428,8 @@ impl Types {
 			| Type::Enum(_)
 			| Type::Stringable
 			| Type::Void
+			| Type::Never
+			| Type::Unknown
 			| Type::Nil
 			| Type::Anything
 			| Type::Unresolved
@@ -1967,6 +2500,18 @@ pub struct TypeChecker<'a> {
 
 	is_in_mut_json: bool,
 
+	/// Lengths of immutable arrays bound to `let` variables from array literals, keyed by the span of the
+	/// variable's definition. Used to report constant indices that are provably out of bounds.
+	literal_array_lengths: HashMap<WingSpan, usize>,
+
+	/// Values of string and number literal expressions, keyed by the span of the expression. Used to
+	/// allow literals wherever a literal type that contains their value is expected.
+	literal_expr_values: HashMap<WingSpan, LiteralTypeValue>,
+
+	/// Messages of the unknown symbol errors reported in this file. An unknown symbol is usually a single mistake (a
+	/// typo or a missing `bring`) that shows up wherever the symbol is used, so only its first use is reported.
+	reported_unknown_symbols: RefCell<HashSet<String>>,
+
 	ctx: VisitContext,
 }
 
@@ -1989,6 +2534,9 @@ impl<'a> TypeChecker<'a> {
 			jsii_imports,
 			generated_fqns: HashSet::new(),
 			is_in_mut_json: false,
+			literal_array_lengths: HashMap::new(),
+			literal_expr_values: HashMap::new(),
+			reported_unknown_symbols: RefCell::new(HashSet::new()),
 			ctx: VisitContext::new(),
 		}
 	}
@@ -2058,6 +2606,7 @@ impl<'a> TypeChecker<'a> {
 			annotations: vec![],
 			hints: vec![],
 			severity: DiagnosticSeverity::Error,
+			code: None,
 		});
 
 		(self.make_error_variable_info(), Phase::Independent)
@@ -2070,6 +2619,7 @@ impl<'a> TypeChecker<'a> {
 			annotations: vec![],
 			hints: vec![],
 			severity: DiagnosticSeverity::Error,
+			code: None,
 		});
 	}
 
@@ -2080,6 +2630,7 @@ impl<'a> TypeChecker<'a> {
 			annotations: vec![],
 			hints: hints.iter().map(|h| h.to_string()).collect(),
 			severity: DiagnosticSeverity::Error,
+			code: None,
 		});
 	}
 
@@ -2095,6 +2646,7 @@ impl<'a> TypeChecker<'a> {
 			annotations,
 			hints: vec![],
 			severity: DiagnosticSeverity::Error,
+			code: None,
 		});
 	}
 
@@ -2105,6 +2657,7 @@ impl<'a> TypeChecker<'a> {
 			annotations: vec![],
 			hints: vec![],
 			severity: DiagnosticSeverity::Error,
+			code: None,
 		});
 	}
 
@@ -2114,13 +2667,20 @@ impl<'a> TypeChecker<'a> {
 			span,
 			annotations,
 			hints,
+			code,
 		} = type_error;
+
+		if code == Some(UNKNOWN_SYMBOL.code) && !self.reported_unknown_symbols.borrow_mut().insert(message.clone()) {
+			return self.types.error();
+		}
+
 		report_diagnostic(Diagnostic {
 			message,
 			span: Some(span),
 			annotations,
 			hints,
 			severity: DiagnosticSeverity::Error,
+			code,
 		});
 
 		self.types.error()
@@ -2338,6 +2898,17 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 
 	// Validates types in the expression make sense and returns the expression's inferred type
 	fn type_check_exp(&mut self, exp: &Expr, env: &mut SymbolEnv) -> (TypeRef, Phase) {
+		self.type_check_exp_with_expected(exp, env, None)
+	}
+
+	/// Like `type_check_exp`, but with the type the expression is expected to have in its context (e.g. the type
+	/// of the parameter it's passed to). This is used to infer the parameter types of closures.
+	fn type_check_exp_with_expected(
+		&mut self,
+		exp: &Expr,
+		env: &mut SymbolEnv,
+		expected_type: Option<TypeRef>,
+	) -> (TypeRef, Phase) {
 		CompilationContext::set(CompilationPhase::TypeChecking, &exp.span);
 
 		let (mut t, phase) = match &exp.kind {
@@ -2348,14 +2919,23 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			ExprKind::Reference(_ref) => self.type_check_reference(_ref, env),
 			ExprKind::Intrinsic(intrinsic) => self.type_check_intrinsic(intrinsic, env, exp),
 			ExprKind::New(new_expr) => self.type_check_new(new_expr, env, exp),
-			ExprKind::Call { callee, arg_list } => self.type_check_call(arg_list, env, callee, exp),
+			ExprKind::Call {
+				callee,
+				arg_list,
+				optional_accessor,
+			} => self.type_check_call(arg_list, env, callee, *optional_accessor, exp),
 			ExprKind::ArrayLiteral { type_, items } => self.type_check_array_lit(type_, env, exp, items),
 			ExprKind::MapLiteral { fields, type_ } => self.type_check_map_lit(type_, env, fields, exp),
 			ExprKind::SetLiteral { type_, items } => self.type_check_set_lit(type_, env, items, exp),
 			ExprKind::StructLiteral { type_, fields } => self.type_check_struct_lit(type_, env, fields, exp),
 			ExprKind::JsonLiteral { is_mut, element } => self.type_check_json_lit(is_mut, element, env, exp),
 			ExprKind::JsonMapLiteral { fields } => self.type_check_json_map_lit(fields, env, exp),
-			ExprKind::FunctionClosure(func_def) => self.type_check_closure(func_def, env),
+			ExprKind::FunctionClosure(func_def) => self.type_check_closure(func_def, env, expected_type),
+			ExprKind::InlineJs {
+				inputs, return_type, ..
+			} => self.type_check_inline_js(inputs, return_type, env, exp),
+			// An error was already reported when parsing the expression
+			ExprKind::Error => self.resolved_error(),
 		};
 
 		// If we're inflight but the expression is a lifted (preflight) expression then make it immutable
@@ -2365,12 +2945,55 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 
 		self.types.assign_type_to_expr(exp, t, phase);
 
+		if let Some(value) = literal_type_value(exp) {
+			self.literal_expr_values.insert(exp.span.clone(), value);
+		}
+
 		// In case any type inferences were updated during this check, ensure all related inferences are updated
 		self.update_known_inferences(&mut t, &exp.span);
 
 		(t, phase)
 	}
 
+	fn type_check_inline_js(
+		&mut self,
+		inputs: &Vec<Expr>,
+		return_type: &Option<TypeAnnotation>,
+		env: &mut SymbolEnv,
+		exp: &Expr,
+	) -> (TypeRef, Phase) {
+		if env.phase != Phase::Inflight {
+			self.spanned_error(exp, "Inline JavaScript can only be used in inflight code");
+		}
+
+		let mut input_names = HashSet::new();
+		for input in inputs {
+			let (input_type, _) = self.type_check_exp(input, env);
+			// The code could call any of the object's inflight methods, so we can't qualify which ones it needs
+			if input_type.is_preflight_object_type() {
+				self.spanned_error(
+					input,
+					format!("Cannot pass preflight object of type \"{input_type}\" to inline JavaScript"),
+				);
+			}
+			if let ExprKind::Reference(Reference::Identifier(name)) = &input.kind {
+				if !input_names.insert(name.name.as_str()) {
+					self.spanned_error(
+						input,
+						format!("\"{name}\" is already an input of this inline JavaScript"),
+					);
+				}
+			}
+		}
+
+		// The code's return value can only be checked at runtime (see `jsify::inline_js_type`)
+		let t = match return_type {
+			Some(return_type) => self.resolve_type_annotation(return_type, env),
+			None => self.types.void(),
+		};
+		(t, Phase::Inflight)
+	}
+
 	fn type_check_lit(&mut self, lit: &Literal, env: &mut SymbolEnv) -> (TypeRef, Phase) {
 		match lit {
 			Literal::String(_) => (self.types.string(), Phase::Independent),
@@ -2382,7 +3005,14 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 					if let InterpolatedStringPart::Expr(interpolated_expr) = part {
 						let (exp_type, p) = self.type_check_exp(interpolated_expr, env);
 						phase = combine_phases(phase, p);
-						self.validate_type_in(exp_type, &[self.types.stringable()], interpolated_expr, None, None);
+						self.validate_type_in(
+							exp_type,
+							&[self.types.stringable()],
+							interpolated_expr,
+							None,
+							None,
+							vec![],
+						);
 					}
 				});
 				(self.types.string(), phase)
@@ -2454,10 +3084,52 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				(self.types.number(), phase)
 			}
 			BinaryOperator::Equal | BinaryOperator::NotEqual => {
-				self.validate_type_binary_equality(rtype, ltype, exp, None, None);
+				// Literal types can be compared with any value of their primitive type
+				let ltype = self.types.maybe_widen_literal(ltype);
+				let rtype = self.types.maybe_widen_literal(rtype);
+				if ltype.is_compared_by_reference() && rtype.is_compared_by_reference() {
+					// Objects are compared by reference, so either side can be the more specific type. Objects of unrelated
+					// classes can never be the same object (interfaces can still be implemented by a subclass of the other side)
+					let (lbase, rbase) = (ltype.maybe_unwrap_option(), rtype.maybe_unwrap_option());
+					let related = lbase.is_subtype_of(rbase)
+						|| rbase.is_subtype_of(lbase)
+						|| lbase.as_interface().is_some()
+						|| rbase.as_interface().is_some();
+					if !related {
+						self.spanned_error(
+							exp,
+							format!(
+								"Cannot compare \"{ltype}\" with \"{rtype}\", objects of unrelated types are compared by reference and are never equal"
+							),
+						);
+					}
+				} else {
+					self.validate_type_binary_equality(rtype, ltype, exp, None, None);
+				}
 				(self.types.bool(), phase)
 			}
 			BinaryOperator::Less | BinaryOperator::LessOrEqual | BinaryOperator::Greater | BinaryOperator::GreaterOrEqual => {
+				// Objects that define an ordering are compared using their `compareTo` method
+				if let Some(compare_to_sig) = ltype.compare_to_sig() {
+					let param_type = compare_to_sig.parameters[0].typeref;
+					let method_phase = compare_to_sig.phase;
+					if method_phase != Phase::Independent && method_phase != env.phase {
+						self.spanned_error(
+							exp,
+							format!(
+								"Cannot compare \"{ltype}\" in {} phase because its \"{COMPARE_TO_METHOD}\" method is {method_phase}",
+								env.phase
+							),
+						);
+					}
+					self.validate_type(rtype, param_type, right);
+					let phase = if method_phase == Phase::Inflight {
+						Phase::Inflight
+					} else {
+						phase
+					};
+					return (self.types.bool(), phase);
+				}
 				self.validate_type(ltype, self.types.number(), left);
 				self.validate_type(rtype, self.types.number(), right);
 				(self.types.bool(), phase)
@@ -2468,33 +3140,98 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 					self.spanned_error(left, format!("Expected optional type, found \"{}\"", ltype));
 					(ltype, phase)
 				} else {
-					// Right argument must be a subtype of the inner type of the left argument
+					// Right argument must be a subtype of the inner type of the left argument, or share a common
+					// supertype with it (in which case that's the type of the expression)
 					let inner_type = *ltype.maybe_unwrap_option();
+					if !rtype.is_subtype_of(&inner_type) {
+						if let Some(lub) = self
+							.types
+							.least_upper_bound(inner_type, rtype)
+							.filter(|t| !t.is_option())
+						{
+							return (lub, phase);
+						}
+					}
 					self.validate_type(rtype, inner_type, right);
 					(inner_type, phase)
 				}
 			}
-		}
-	}
-
-	fn type_check_unary_op(&mut self, unary_exp: &Expr, env: &mut SymbolEnv, op: &UnaryOperator) -> (TypeRef, Phase) {
-		let (type_, phase) = self.type_check_exp(unary_exp, env);
-
-		match op {
-			UnaryOperator::Not => (self.validate_type(type_, self.types.bool(), unary_exp), phase),
-			UnaryOperator::Minus => (self.validate_type(type_, self.types.number(), unary_exp), phase),
-			UnaryOperator::OptionalUnwrap => {
-				if !type_.is_option() {
-					self.spanned_error(unary_exp, format!("'!' expects an optional type, found \"{}\"", type_));
-					(type_, phase)
+			BinaryOperator::BitwiseOr | BinaryOperator::BitwiseAnd => {
+				// Values of a flags enum are combined into a value of the same enum
+				let ltype = self.types.maybe_unwrap_inference(ltype);
+				if matches!(*ltype, Type::Enum(Enum { is_flags: true, .. })) {
+					self.validate_type(rtype, ltype, right);
+					(ltype, phase)
 				} else {
-					let inner_type = *type_.maybe_unwrap_option();
-					(inner_type, phase)
-				}
-			}
-		}
-	}
-
+					if !ltype.is_unresolved() {
+						self.spanned_error(
+							left,
+							format!(
+								"Operator '{}' expects values of a flags enum, found \"{ltype}\"",
+								if matches!(op, BinaryOperator::BitwiseOr) {
+									"|"
+								} else {
+									"&"
+								}
+							),
+						);
+					}
+					(self.types.error(), phase)
+				}
+			}
+			BinaryOperator::In => {
+				// The left argument is checked against the collection's key (maps) or element (sets, arrays) type
+				let rtype = self.types.maybe_unwrap_inference(rtype);
+				match *rtype {
+					Type::Map(_) | Type::MutMap(_) | Type::String => {
+						self.validate_type(ltype, self.types.string(), left);
+					}
+					Type::Set(t) | Type::MutSet(t) => {
+						self.validate_type(ltype, t, left);
+						if let Some(hashing) = self.check_set_member_type(t, left, env) {
+							if hashing != SetMemberHashing::Identity {
+								self.types.set_members.insert(left.id, hashing);
+							}
+						}
+					}
+					Type::Array(t) | Type::MutArray(t) => {
+						self.validate_type(ltype, t, left);
+					}
+					_ => {
+						if !rtype.is_unresolved() {
+							self.spanned_error(
+								right,
+								format!(
+									"Operator 'in' expects a Map, Set, Array or str on its right side, found \"{}\"",
+									rtype
+								),
+							);
+						}
+					}
+				}
+				(self.types.bool(), phase)
+			}
+		}
+	}
+
+	fn type_check_unary_op(&mut self, unary_exp: &Expr, env: &mut SymbolEnv, op: &UnaryOperator) -> (TypeRef, Phase) {
+		let (type_, phase) = self.type_check_exp(unary_exp, env);
+
+		match op {
+			UnaryOperator::Not => (self.validate_type(type_, self.types.bool(), unary_exp), phase),
+			UnaryOperator::Minus => (self.validate_type(type_, self.types.number(), unary_exp), phase),
+			UnaryOperator::OptionalUnwrap => {
+				if !type_.is_option() {
+					self.spanned_error(unary_exp, format!("'!' expects an optional type, found \"{}\"", type_));
+					(type_, phase)
+				} else {
+					let inner_type = *type_.maybe_unwrap_option();
+					(inner_type, phase)
+				}
+			}
+		}
+	}
+
 	fn type_check_json_map_lit(
 		&mut self,
 		fields: &IndexMap<Symbol, Expr>,
@@ -2622,6 +3359,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			.expect(&format!("Expected \"{}\" to be a struct type", struct_type));
 
 		// Verify that all expected fields are present and are the right type
+		let mut missing_fields = vec![];
 		for (name, v) in st.fields(true) {
 			let field_type = v.type_;
 			match fields.get(name.as_str()) {
@@ -2631,16 +3369,42 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				}
 				None => {
 					if !field_type.is_option() {
-						self.spanned_error(exp, format!("\"{}\" is not initialized", name));
+						missing_fields.push((name, field_type));
 					}
 				}
 			}
 		}
 
+		// All missing fields are reported together, with a hint that has placeholders for all of them
+		if let Some(((last_name, _), rest)) = missing_fields.split_last() {
+			let message = if rest.is_empty() {
+				format!("\"{last_name}\" is not initialized")
+			} else {
+				format!(
+					"{} and \"{last_name}\" are not initialized",
+					rest.iter().map(|(name, _)| format!("\"{name}\"")).join(", ")
+				)
+			};
+			let initializers = missing_fields
+				.iter()
+				.map(|(name, field_type)| format!("{name}: {}", field_type.placeholder_value()))
+				.collect_vec();
+			self.spanned_error_with_hints(
+				exp,
+				message,
+				&missing_fields_hint(&initializers).into_iter().collect_vec(),
+			);
+		}
+
 		// Verify that no unexpected fields are present
 		for (name, _t) in field_types.iter() {
 			if st.env.lookup(name, Some(self.ctx.current_stmt_idx())).is_none() {
-				self.spanned_error(exp, format!("\"{}\" is not a field of \"{}\"", name.name, st.name.name));
+				let suggestions = similar_symbol_names(&st.env, &name.name, false);
+				self.spanned_error_with_hints(
+					exp,
+					format!("\"{}\" is not a field of \"{}\"", name.name, st.name.name),
+					&did_you_mean_hint(&suggestions).into_iter().collect_vec(),
+				);
 			}
 		}
 
@@ -2655,7 +3419,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		exp: &Expr,
 	) -> (TypeRef, Phase) {
 		// Infer type based on either the explicit type or the value in one of the items
-		let (mut container_type, mut element_type) = if let Some(type_) = type_ {
+		let (container_type, mut element_type) = if let Some(type_) = type_ {
 			let container_type = self.resolve_type_annotation(type_, env);
 			let element_type = match *container_type {
 				Type::Set(t) | Type::MutSet(t) => t,
@@ -2700,13 +3464,84 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			element_type = self.types.maybe_unwrap_inference(element_type);
 		}
 
-		if let Type::Set(ref mut inner) | Type::MutSet(ref mut inner) = &mut *container_type {
-			*inner = element_type;
+		if let Some(hashing) = self.check_set_member_type(element_type, exp, env) {
+			if hashing != SetMemberHashing::Identity {
+				for item in items {
+					self.types.set_members.insert(item.id, hashing);
+				}
+			}
 		}
 
+		// Container types are shared, so a new one is created for the element type instead of updating it in place
+		let container_type = match *container_type {
+			Type::Set(_) => self.types.add_type(Type::Set(element_type)),
+			Type::MutSet(_) => self.types.add_type(Type::MutSet(element_type)),
+			_ => container_type,
+		};
+
 		(container_type, env.phase)
 	}
 
+	/// Verifies values of the given type can be members of a `Set`, and returns how they're hashed
+	fn check_set_member_type(
+		&mut self,
+		element_type: TypeRef,
+		span: &impl Spanned,
+		env: &SymbolEnv,
+	) -> Option<SetMemberHashing> {
+		match element_type.set_member_hashing() {
+			Ok(SetMemberHashing::Method) => {
+				// A class that's missing its `hash` method has already been reported
+				let hash_phase = element_type.maybe_unwrap_option().hash_sig().map(|sig| sig.phase);
+				if let Some(hash_phase) = hash_phase.filter(|phase| !env.phase.can_call_to(phase)) {
+					self.spanned_error(
+						span,
+						format!(
+							"Cannot use \"{element_type}\" as a Set member in {} phase because its \"{HASH_METHOD}\" method is {hash_phase}",
+							env.phase
+						),
+					);
+					return None;
+				}
+				Some(SetMemberHashing::Method)
+			}
+			Ok(hashing) => Some(hashing),
+			Err(reason) => {
+				self.spanned_error(
+					span,
+					format!("\"{element_type}\" can't be a Set member because {reason}"),
+				);
+				None
+			}
+		}
+	}
+
+	/// Values which are added to, looked up in or removed from a `Set` are hashed like its members
+	fn record_set_member_arg(&mut self, callee: &CalleeKind, arg_list: &ArgList, env: &SymbolEnv) {
+		let CalleeKind::Expr(callee_expr) = callee else {
+			return;
+		};
+		let ExprKind::Reference(Reference::InstanceMember { object, property, .. }) = &callee_expr.kind else {
+			return;
+		};
+		if !matches!(property.name.as_str(), "add" | "has" | "delete") {
+			return;
+		}
+		let element_type = match **self.types.get_expr_type(object).maybe_unwrap_option() {
+			Type::Set(t) | Type::MutSet(t) => t,
+			_ => return,
+		};
+		let Some(member) = arg_list.pos_args.first() else {
+			return;
+		};
+		match self.check_set_member_type(element_type, member, env) {
+			Some(hashing) if hashing != SetMemberHashing::Identity => {
+				self.types.set_members.insert(member.id, hashing);
+			}
+			_ => {}
+		}
+	}
+
 	fn type_check_map_lit(
 		&mut self,
 		type_: &Option<TypeAnnotation>,
@@ -2715,7 +3550,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		exp: &Expr,
 	) -> (TypeRef, Phase) {
 		// Infer type based on either the explicit type or the value in one of the fields
-		let (mut container_type, mut element_type) = if let Some(type_) = type_ {
+		let (container_type, mut element_type) = if let Some(type_) = type_ {
 			let container_type = self.resolve_type_annotation(type_, env);
 			let element_type = match *container_type {
 				Type::Map(t) | Type::MutMap(t) => t,
@@ -2753,9 +3588,12 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			self.validate_type(key_type, self.types.string(), key);
 		}
 
-		if let Type::Map(ref mut inner) | Type::MutMap(ref mut inner) = &mut *container_type {
-			*inner = element_type;
-		}
+		// Container types are shared, so a new one is created for the element type instead of updating it in place
+		let container_type = match *container_type {
+			Type::Map(_) => self.types.add_type(Type::Map(element_type)),
+			Type::MutMap(_) => self.types.add_type(Type::MutMap(element_type)),
+			_ => container_type,
+		};
 
 		(container_type, env.phase)
 	}
@@ -2768,7 +3606,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		items: &Vec<Expr>,
 	) -> (TypeRef, Phase) {
 		// Infer type based on either the explicit type or the value in one of the items
-		let (mut container_type, mut element_type) = if let Some(type_) = type_ {
+		let (container_type, mut element_type) = if let Some(type_) = type_ {
 			let container_type = self.resolve_type_annotation(type_, env);
 			let element_type = match *container_type {
 				Type::Array(t) | Type::MutArray(t) => t,
@@ -2826,6 +3664,12 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			}
 
 			if !self.ctx.in_json() && !t.is_json() {
+				// Without an explicit type, the element type is widened to fit elements of different but compatible types
+				if type_.is_none() && !element_type.is_inferred() && !t.is_subtype_of(&element_type) {
+					if let Some(lub) = self.types.least_upper_bound(element_type, t) {
+						element_type = lub;
+					}
+				}
 				// If we're not in a Json literal, validate the type of each element
 				self.validate_type(t, element_type, item);
 				element_type = self.types.maybe_unwrap_inference(element_type);
@@ -2838,23 +3682,102 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			}
 		}
 
-		if let Type::Array(ref mut inner) | Type::MutArray(ref mut inner) = &mut *container_type {
-			*inner = element_type;
-		}
+		// Container types are shared, so a new one is created for the element type instead of updating it in place
+		let container_type = match *container_type {
+			Type::Array(_) => self.types.add_type(Type::Array(element_type)),
+			Type::MutArray(_) => self.types.add_type(Type::MutArray(element_type)),
+			_ => container_type,
+		};
 
 		(container_type, phase)
 	}
 
+	/// Reports a call to a function of a phase the calling code can't call, pointing at where the phases of both the
+	/// caller and the callee come from
+	fn report_cannot_call_into_phase(&self, call: &Expr, callee: &CalleeKind, callee_phase: Phase, env: &SymbolEnv) {
+		let mut diagnostic = Diagnostic::from_message(
+			CANNOT_CALL_INTO_PHASE,
+			&[("callee_phase", &callee_phase), ("phase", &env.phase)],
+			call,
+		)
+		.annotate(
+			match callee_phase {
+				Phase::Preflight => "this is a preflight function".to_string(),
+				_ => format!("this is an {callee_phase} function"),
+			},
+			callee.span(),
+		);
+
+		// Where the callee was declared with its phase
+		let callee_definition = self
+			.callee_definition(callee, env)
+			.filter(|definition| !definition.name.span.is_default());
+		if let Some(definition) = &callee_definition {
+			diagnostic = diagnostic.annotate(
+				format!("\"{}\" is declared {callee_phase} here", definition.name),
+				definition.name.span(),
+			);
+		}
+
+		// Where the calling code got its phase from
+		if let Some(function) = self.ctx.current_function() {
+			diagnostic = match &function.name {
+				Some(name) => diagnostic.annotate(format!("\"{name}\" is {}", env.phase), name.span()),
+				None => diagnostic.annotate(format!("this closure is {}", env.phase), function.span),
+			};
+		}
+
+		let hint = match (env.phase, callee_phase) {
+			(Phase::Preflight, Phase::Inflight) => Some(
+				"inflight functions can only be called from inflight code, try calling it from an inflight closure (`inflight () => { ... }`)"
+					.to_string(),
+			),
+			(Phase::Inflight, Phase::Preflight) => Some(match &callee_definition {
+				Some(definition) => format!(
+					"preflight functions run before the application is deployed, call it in preflight and use its result, or mark \"{}\" inflight if it doesn't need to run in preflight",
+					definition.name
+				),
+				None => "preflight functions run before the application is deployed, call it in preflight and use its result"
+					.to_string(),
+			}),
+			(Phase::Independent, _) => {
+				Some("phase-independent functions can only call other phase-independent functions".to_string())
+			}
+			_ => None,
+		};
+		if let Some(hint) = hint {
+			diagnostic = diagnostic.hint(hint);
+		}
+		diagnostic.report();
+	}
+
+	/// The variable or member a callee refers to, if it's a plain reference (e.g. `foo` or `bar.foo`)
+	fn callee_definition(&self, callee: &CalleeKind, env: &SymbolEnv) -> Option<VariableInfo> {
+		let CalleeKind::Expr(callee) = callee else {
+			return None;
+		};
+		let ExprKind::Reference(reference) = &callee.kind else {
+			return None;
+		};
+		match reference {
+			Reference::Identifier(name) => env.lookup(name, None)?.as_variable().cloned(),
+			Reference::InstanceMember { object, property, .. } => {
+				let object_type = self.types.try_get_expr_type(object.id)?;
+				let object_env = object_type.maybe_unwrap_option().as_env()?;
+				object_env.lookup(property, None)?.as_variable().cloned()
+			}
+			_ => None,
+		}
+	}
+
 	fn type_check_call(
 		&mut self,
 		arg_list: &ArgList,
 		env: &mut SymbolEnv,
 		callee: &CalleeKind,
+		optional_accessor: bool,
 		exp: &Expr,
 	) -> (TypeRef, Phase) {
-		// Type check the call arguments
-		let arg_list_types = self.type_check_arg_list(arg_list, env);
-
 		// Resolve the function's reference (either a method in the class's env or a function in the current env)
 		let (func_type, callee_phase) = match callee {
 			CalleeKind::Expr(expr) => self.type_check_exp(expr, env),
@@ -2863,6 +3786,16 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				self.resolved_error()
 			}),
 		};
+
+		// Type check the call arguments, the callee's signature gives the expected types of closures passed to it
+		// (unless it's generic, since its type argument is only known once the arguments are type checked)
+		let expected_sig = func_type
+			.maybe_unwrap_option()
+			.as_deep_function_sig()
+			.filter(|_| !func_type.maybe_unwrap_option().contains_generic_type_argument())
+			.cloned();
+		let arg_list_types = self.type_check_arg_list_with_expected(arg_list, env, expected_sig.as_ref());
+
 		let is_option = func_type.is_option();
 		let func_type = func_type.maybe_unwrap_option();
 
@@ -2890,11 +3823,16 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			return self.resolved_error();
 		};
 
+		// Generic functions that aren't bound to an instance of a generic type get their type argument from the
+		// types of the arguments they're called with
+		let func_sig = if func_sig.this_type.is_none() && func_type.contains_generic_type_argument() {
+			self.infer_generic_function_sig(&func_sig, &arg_list_types, env, exp)
+		} else {
+			func_sig
+		};
+
 		if !env.phase.can_call_to(&func_sig.phase) {
-			self.spanned_error(
-				exp,
-				format!("Cannot call into {} phase while {}", func_sig.phase, env.phase),
-			);
+			self.report_cannot_call_into_phase(exp, callee, func_sig.phase, env);
 		}
 
 		// If the function is phase independent, then inherit from the callee
@@ -2908,6 +3846,9 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			return (value, func_phase);
 		}
 
+		self.validate_json_literal_from_json(callee, arg_list, env);
+		self.record_set_member_arg(callee, arg_list, env);
+
 		// If the function is "wingc_env", then print out the current environment
 		if let CalleeKind::Expr(call_expr) = callee {
 			if let ExprKind::Reference(Reference::Identifier(ident)) = &call_expr.kind {
@@ -2918,7 +3859,10 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			}
 		}
 
-		if is_option {
+		if optional_accessor {
+			// `f?()` evaluates to nil if `f` is nil, so the return type is always optional
+			(self.types.make_option(func_sig.return_type), func_phase)
+		} else if is_option {
 			// When calling a an optional function, the return type is always optional
 			// To allow this to be both safe and unsurprising,
 			// the callee must be a reference with an optional accessor
@@ -2931,12 +3875,14 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 						(self.types.error(), func_phase)
 					}
 				} else {
-					// TODO do we want syntax for this? e.g. `foo?.()`
-					self.spanned_error(callee, "Cannot call an optional function");
+					self.spanned_error_with_hints(
+						callee,
+						"Cannot call an optional function",
+						&["Use optional chaining to call it only if it is defined: \"f?()\""],
+					);
 					(self.types.error(), func_phase)
 				}
 			} else {
-				// TODO do we want syntax for this? e.g. `foo?.()`
 				self.spanned_error(callee, "Cannot call an optional function");
 				(self.types.error(), func_phase)
 			}
@@ -2945,6 +3891,44 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		}
 	}
 
+	/// `S.fromJson(json)` validates `json` against the struct `S` at runtime. When `json` is a literal object its fields
+	/// are already known, so they're validated against the struct here instead (unless the call opts out of validation
+	/// with `unsafe`).
+	fn validate_json_literal_from_json(&mut self, callee: &CalleeKind, arg_list: &ArgList, env: &SymbolEnv) {
+		let CalleeKind::Expr(callee) = callee else {
+			return;
+		};
+		let ExprKind::Reference(Reference::TypeMember { type_name, property }) = &callee.kind else {
+			return;
+		};
+		if property.name != "fromJson" || arg_list.named_args.keys().any(|name| name.name == "unsafe") {
+			return;
+		}
+		let Some(json) = arg_list.pos_args.first().filter(|json| {
+			matches!(
+				json.kind,
+				ExprKind::JsonLiteral { .. } | ExprKind::JsonMapLiteral { .. }
+			)
+		}) else {
+			return;
+		};
+		let json_type = self.types.get_expr_type(json);
+		if !matches!(
+			&*json_type,
+			Type::Json(Some(JsonData {
+				kind: JsonDataKind::Fields(_),
+				..
+			}))
+		) {
+			return;
+		}
+		if let Ok(struct_type) = self.resolve_user_defined_type(type_name, env, self.ctx.current_stmt_idx()) {
+			if struct_type.is_struct() {
+				self.validate_type_json(json_type, struct_type, json);
+			}
+		}
+	}
+
 	fn type_check_intrinsic(&mut self, intrinsic: &Intrinsic, env: &mut SymbolEnv, exp: &Expr) -> (TypeRef, Phase) {
 		if !intrinsic.kind.is_valid_phase(&env.phase) {
 			self.spanned_error(exp, format!("{} cannot be used in {}", intrinsic.kind, env.phase));
@@ -3005,7 +3989,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			.resolve_user_defined_type(class, env, self.ctx.current_stmt_idx())
 			.unwrap_or_else(|e| self.type_error(e));
 		let obj_scope_type = obj_scope.as_ref().map(|x| self.type_check_exp(x, env).0);
-		let obj_id_type = obj_id.as_ref().map(|x| self.type_check_exp(x, env).0);
+		let obj_id_type = obj_id.as_ref().map(|x| self.type_check_exp(x, env));
 		let arg_list_types = self.type_check_arg_list(arg_list, env);
 
 		// Lookup the class's type in the env
@@ -3018,13 +4002,22 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				if class.phase == Phase::Independent || env.phase == class.phase {
 					(&class.env, &class.name)
 				} else {
-					self.spanned_error(
-						exp,
-						format!(
-							"Cannot create {} class \"{}\" in {} phase",
-							class.phase, class.name, env.phase
-						),
+					let message = format!(
+						"Cannot create {} class \"{}\" in {} phase",
+						class.phase, class.name, env.phase
 					);
+					if class.phase == Phase::Inflight {
+						self.spanned_error_with_hints(
+							exp,
+							message,
+							&[format!(
+								"use \"inflight new {}(...)\" to get an inflight function that creates it when called",
+								class.name
+							)],
+						);
+					} else {
+						self.spanned_error(exp, message);
+					}
 					return (self.types.error(), Phase::Independent);
 				}
 			}
@@ -3078,6 +4071,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 						name: CLASS_INIT_NAME.into(),
 						span: class_symbol.span.clone(),
 					},
+					env,
 				));
 				return self.resolved_error();
 			}
@@ -3089,6 +4083,47 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		// Verify return type (This should never fail since we define the constructors return type during AST building)
 		self.validate_type(constructor_sig.return_type, class_type, exp);
 
+		// If the arguments don't fit the constructor, route the expression to the first secondary constructor they fit
+		if !self.arg_list_fits_sig(arg_list, &arg_list_types, constructor_sig) {
+			let secondary_constructor = class_type
+				.as_class()
+				.expect("a class")
+				.secondary_constructors()
+				.into_iter()
+				.find(|v| {
+					let sig = v.type_.as_function_sig().expect("a function signature");
+					(sig.phase == Phase::Independent || sig.phase == env.phase)
+						&& self.arg_list_fits_sig(arg_list, &arg_list_types, sig)
+				});
+			if let Some(secondary_constructor) = secondary_constructor {
+				let secondary_constructor_name = format!("{}.{}", class_symbol.name, secondary_constructor.name.name);
+				if let Some(obj_scope) = obj_scope {
+					self.spanned_error(
+						obj_scope,
+						format!("Cannot set scope of an object created by secondary constructor \"{secondary_constructor_name}\" using `in`"),
+					);
+				}
+				if let Some(obj_id) = obj_id {
+					self.spanned_error(
+						obj_id,
+						format!(
+							"Cannot set id of an object created by secondary constructor \"{secondary_constructor_name}\" using `as`"
+						),
+					);
+				}
+				self
+					.types
+					.secondary_constructor_calls
+					.insert(exp.id, secondary_constructor.name.name.clone());
+				let sig = secondary_constructor
+					.type_
+					.as_function_sig()
+					.expect("a function signature");
+				self.type_check_arg_list_against_function_sig(arg_list, sig, exp, arg_list_types);
+				return (class_type, env.phase);
+			}
+		}
+
 		self.type_check_arg_list_against_function_sig(&arg_list, &constructor_sig, exp, arg_list_types);
 
 		let non_std_args = !class_type.as_class().unwrap().std_construct_args;
@@ -3116,22 +4151,20 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				obj_scope_type
 			};
 
-			// Verify the object scope is a construct
+			// Verify the object scope is a construct (an explicit scope is reported where it's set)
 			if let Some(obj_scope_type) = obj_scope_type {
 				if !obj_scope_type.is_subtype_of(&self.types.construct_interface()) {
-					self.spanned_error(
-						exp,
-						format!(
-							"Expected scope to be a preflight object, instead found \"{}\"",
-							obj_scope_type
-						),
-					);
+					let span = obj_scope.as_ref().map_or(exp.span(), |obj_scope| obj_scope.span());
+					Diagnostic::from_message(INVALID_OBJECT_SCOPE, &[("actual", &obj_scope_type)], &span).report();
 				}
 			}
 
-			// Verify the object id is a string
-			if let Some(obj_id_type) = obj_id_type {
+			// Verify the object id is a string that can be computed in preflight
+			if let Some((obj_id_type, obj_id_phase)) = obj_id_type {
 				self.validate_type(obj_id_type, self.types.string(), obj_id.as_ref().unwrap());
+				if obj_id_phase == Phase::Inflight {
+					Diagnostic::from_message(INFLIGHT_OBJECT_ID, &[], obj_id.as_ref().unwrap()).report();
+				}
 				// If this is a non-standard preflight class, make sure the object's id isn't explicitly set (using the `as` keywords)
 				if non_std_args {
 					self.spanned_error(
@@ -3290,12 +4323,180 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		None
 	}
 
-	fn type_check_closure(&mut self, func_def: &ast::FunctionDefinition, env: &SymbolEnv) -> (TypeRef, Phase) {
+	/// Checks whether an argument list can be passed to a function without reporting any errors. This mirrors the
+	/// checks of `type_check_arg_list_against_function_sig`, and is used to pick the constructor a `new` expression
+	/// is routed to. Arguments whose type is unknown are assumed to fit.
+	fn arg_list_fits_sig(&self, arg_list: &ArgList, arg_list_types: &ArgListTypes, func_sig: &FunctionSignature) -> bool {
+		let fits = |arg_type: TypeRef, param_type: TypeRef| {
+			arg_type.is_unresolved()
+				|| arg_type.is_inferred()
+				|| arg_type.is_anything()
+				|| arg_type.is_subtype_of(&param_type)
+		};
+
+		// Verify named args
+		let last_param = func_sig.parameters.last();
+		let is_last_param_struct = last_param.map_or(false, |p| p.typeref.maybe_unwrap_option().is_struct());
+		if !arg_list.named_args.is_empty() {
+			let Some(last_param_struct) = last_param.and_then(|p| p.typeref.maybe_unwrap_option().as_struct()) else {
+				return false;
+			};
+			for (name, arg) in arg_list_types.named_args.iter() {
+				let Some(field) = last_param_struct.env.lookup(name, None).and_then(|f| f.as_variable()) else {
+					return false;
+				};
+				if !fits(arg.type_, field.type_) {
+					return false;
+				}
+			}
+			let missing_field = last_param_struct
+				.fields(true)
+				.any(|(name, v)| !v.type_.is_option() && !arg_list_types.named_args.keys().any(|k| k.name == name));
+			if missing_field {
+				return false;
+			}
+		}
+
+		// Verify arity
+		let variadic_index = func_sig.parameters.iter().position(|o| o.variadic);
+		let pos_args_len = cmp::min(
+			arg_list.pos_args.len(),
+			variadic_index.unwrap_or(arg_list.pos_args.len()),
+		);
+		let non_variadic_args_len = pos_args_len
+			+ if is_last_param_struct && !arg_list.named_args.is_empty() {
+				1
+			} else {
+				0
+			};
+		let mut min_args = func_sig.min_parameters()
+			+ if last_param.map_or(false, |p| p.typeref.is_struct()) {
+				1
+			} else {
+				0
+			};
+		let max_args = func_sig.parameters.len() - if variadic_index.is_some() { 1 } else { 0 };
+		if last_param.map_or(false, |p| Self::all_optional_struct(p.typeref)) && non_variadic_args_len + 1 == min_args {
+			min_args -= 1;
+		}
+		if non_variadic_args_len < min_args || pos_args_len > max_args || non_variadic_args_len > max_args {
+			return false;
+		}
+
+		// Verify positioned and variadic args
+		let positional_fit = izip!(
+			arg_list_types.pos_args.iter().take(pos_args_len),
+			func_sig.parameters.iter().take(pos_args_len)
+		)
+		.all(|(arg_type, param)| fits(*arg_type, param.typeref));
+		let variadic_fit = variadic_index.map_or(true, |variadic_index| {
+			let variadic_args_inner_type = func_sig.parameters[variadic_index]
+				.typeref
+				.collection_item_type()
+				.expect("variadic parameter should be a collection");
+			arg_list_types
+				.pos_args
+				.iter()
+				.skip(variadic_index)
+				.all(|arg_type| fits(*arg_type, variadic_args_inner_type))
+		});
+		positional_fit && variadic_fit
+	}
+
+	/// Infers the type argument of a generic function from the types of the (positional) arguments it's called with,
+	/// and returns the function's signature with the type argument replaced by the inferred type.
+	fn infer_generic_function_sig(
+		&mut self,
+		func_sig: &FunctionSignature,
+		arg_list_types: &ArgListTypes,
+		env: &SymbolEnv,
+		exp: &Expr,
+	) -> FunctionSignature {
+		let mut inferred = None;
+		for (idx, arg_type) in arg_list_types.pos_args.iter().enumerate() {
+			if let Some(param_type) = self.expected_positional_arg_type(func_sig, idx) {
+				self.unify_generic_type_argument(param_type, *arg_type, &mut inferred);
+			}
+		}
+
+		let Some(inferred) = inferred else {
+			self.spanned_error(
+				exp,
+				"Unable to infer the type argument of a generic function from the arguments it was called with",
+			);
+			return func_sig.clone();
+		};
+
+		let t1 = lookup_known_type(WINGSDK_GENERIC, env);
+		let types_map = HashMap::from([(format!("{t1}"), (t1, inferred))]);
+		let func_type = self.types.add_type(Type::Function(func_sig.clone()));
+		let hydrated = self.get_concrete_type_for_generic(env, func_type, &types_map);
+		hydrated
+			.as_function_sig()
+			.expect("hydrated generic function should be a function")
+			.clone()
+	}
+
+	/// Matches the type of an argument against the generic parameter type it was passed for, narrowing down the type
+	/// argument wherever the parameter refers to it. The first type found is used unless a later argument is a
+	/// supertype of it, any other mismatches are reported when the arguments are validated against the inferred
+	/// signature.
+	fn unify_generic_type_argument(&self, param_type: TypeRef, arg_type: TypeRef, inferred: &mut Option<TypeRef>) {
+		if arg_type.is_unresolved() || arg_type.is_inferred() {
+			return;
+		}
+
+		if param_type.is_generic_type_argument() {
+			match inferred {
+				Some(t) if !t.is_subtype_of(&arg_type) => {}
+				_ => *inferred = Some(arg_type),
+			}
+			return;
+		}
+
+		match (&*param_type, &*arg_type) {
+			(Type::Optional(p), Type::Optional(a)) => self.unify_generic_type_argument(*p, *a, inferred),
+			(Type::Optional(p), _) => self.unify_generic_type_argument(*p, arg_type, inferred),
+			(Type::Array(p), Type::Array(a) | Type::MutArray(a))
+			| (Type::MutArray(p), Type::MutArray(a))
+			| (Type::Map(p), Type::Map(a) | Type::MutMap(a))
+			| (Type::MutMap(p), Type::MutMap(a))
+			| (Type::Set(p), Type::Set(a) | Type::MutSet(a))
+			| (Type::MutSet(p), Type::MutSet(a)) => self.unify_generic_type_argument(*p, *a, inferred),
+			(Type::Function(p), Type::Function(a)) => {
+				for (p, a) in p.parameters.iter().zip(a.parameters.iter()) {
+					self.unify_generic_type_argument(p.typeref, a.typeref, inferred);
+				}
+				self.unify_generic_type_argument(p.return_type, a.return_type, inferred);
+			}
+			_ => {}
+		}
+	}
+
+	fn type_check_closure(
+		&mut self,
+		func_def: &ast::FunctionDefinition,
+		env: &SymbolEnv,
+		expected_type: Option<TypeRef>,
+	) -> (TypeRef, Phase) {
 		// TODO: make sure this function returns on all control paths when there's a return type (can be done by recursively traversing the statements and making sure there's a "return" statements in all control paths)
 		// https://github.com/winglang/wing/issues/457
 
 		// Create a type_checker function signature from the AST function definition
-		let function_type = self.resolve_type_annotation(&func_def.signature.to_type_annotation(), env);
+		let mut function_type = self.resolve_type_annotation(&func_def.signature.to_type_annotation(), env);
+
+		// If we know the function type expected here, use it to infer any parameters (and the return type) that
+		// weren't annotated before the body is checked
+		if let Some(expected_type) = expected_type {
+			if expected_type.maybe_unwrap_option().as_deep_function_sig().is_some() {
+				self.add_new_inference(&function_type, &expected_type, &func_def.span);
+				self.update_known_inferences(&mut function_type, &func_def.span);
+			}
+		}
+		if let Some(doc) = &func_def.doc {
+			let docs = Docs::from_doc_comment(doc);
+			self.document_function_sig(function_type.as_function_sig_mut().unwrap(), &docs, &func_def.span);
+		}
 		let sig = function_type.as_function_sig().unwrap();
 
 		// Create an environment for the function
@@ -3311,18 +4512,25 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		));
 		self.add_arguments_to_env(&func_def.signature.parameters, &sig, &mut function_env);
 
-		self.with_function_def(None, &func_def.signature, func_def.is_static, function_env, |tc| {
-			// Type check the function body
-			if let FunctionBody::Statements(scope) = &func_def.body {
-				tc.types.set_scope_env(scope, function_env);
+		self.with_function_def(
+			None,
+			&func_def.signature,
+			func_def.is_static,
+			function_env,
+			&func_def.span,
+			|tc| {
+				// Type check the function body
+				if let FunctionBody::Statements(scope) = &func_def.body {
+					tc.types.set_scope_env(scope, function_env);
 
-				tc.inner_scopes.push((scope, tc.ctx.clone()));
+					tc.inner_scopes.push((scope, tc.ctx.clone()));
 
-				(function_type, sig.phase)
-			} else {
-				(function_type, sig.phase)
-			}
-		})
+					(function_type, sig.phase)
+				} else {
+					(function_type, sig.phase)
+				}
+			},
+		)
 	}
 
 	/// Validate that a given map can be assigned to a variable of given struct type
@@ -3351,7 +4559,12 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 					.type_;
 				field_map.insert(k.name.clone(), (k, field_type));
 			} else {
-				self.spanned_error(value, format!("\"{}\" is not a field of \"{}\"", k.name, expected_type));
+				let suggestions = similar_symbol_names(&expected_struct.env, &k.name, false);
+				self.spanned_error_with_hints(
+					value,
+					format!("\"{}\" is not a field of \"{}\"", k.name, expected_type),
+					&did_you_mean_hint(&suggestions).into_iter().collect_vec(),
+				);
 			}
 		}
 
@@ -3530,7 +4743,19 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 	///
 	/// Returns the given type on success, otherwise returns the expected type.
 	fn validate_type(&mut self, actual_type: TypeRef, expected_type: TypeRef, span: &impl Spanned) -> TypeRef {
-		self.validate_type_in(actual_type, &[expected_type], span, None, None)
+		self.validate_type_in(actual_type, &[expected_type], span, None, None, vec![])
+	}
+
+	/// Like `validate_type`, but labels where the expected type comes from (e.g. the type annotation of a variable)
+	/// in the error
+	fn validate_type_expected_due_to(
+		&mut self,
+		actual_type: TypeRef,
+		expected_type: TypeRef,
+		span: &impl Spanned,
+		expected_due_to: DiagnosticAnnotation,
+	) -> TypeRef {
+		self.validate_type_in(actual_type, &[expected_type], span, None, None, vec![expected_due_to])
 	}
 
 	/// Validate that the given type is a subtype (or same) as the expected type. If not, add an error
@@ -3552,14 +4777,15 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				span,
 				actual_original_type,
 				Some(&[expected_original_t]),
+				vec![],
 			)
 		} else {
-			self.validate_type_in(actual_type, &[expected_type], span, actual_original_type, None)
+			self.validate_type_in(actual_type, &[expected_type], span, actual_original_type, None, vec![])
 		}
 	}
 
 	/// Validate that the given type is a subtype (or same) as the one of the expected types. If not, add
-	/// an error to the diagnostics.
+	/// an error to the diagnostics, with the given annotations (e.g. where the expected types come from).
 	/// Returns the given type on success, otherwise returns one of the expected types.
 	fn validate_type_in(
 		&mut self,
@@ -3568,6 +4794,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		span: &impl Spanned,
 		actual_original_type: Option<TypeRef>,
 		expected_original_types: Option<&[TypeRef]>,
+		mut annotations: Vec<DiagnosticAnnotation>,
 	) -> TypeRef {
 		assert!(expected_types.len() > 0);
 		let first_expected_type = expected_types[0];
@@ -3591,15 +4818,25 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			return return_type;
 		}
 
-		// If the actual type is an error (a type we failed to resolve) then we silently ignore it assuming
-		// the error was already reported.
-		if return_type.is_unresolved() {
+		// If the actual value is a literal allowed by one of the expected literal types then we're good
+		if let Some(value) = self.literal_expr_values.get(&span.span()) {
+			if expected_types
+				.iter()
+				.any(|t| matches!(&**t.maybe_unwrap_option(), Type::Literal(values) if values.contains(value)))
+			{
+				return return_type;
+			}
+		}
+
+		// If the actual type is (or contains, like `Array<Unknown>`) an error (a type we failed to resolve) then we
+		// silently ignore it assuming the error was already reported.
+		if return_type.contains_unresolved() {
 			return return_type;
 		}
 
-		// If any of the expected types are errors (types we failed to resolve) then we silently ignore it
+		// If any of the expected types are (or contain) errors (types we failed to resolve) then we silently ignore it
 		// assuming the error was already reported.
-		if expected_types.iter().any(|t| t.is_unresolved()) {
+		if expected_types.iter().any(|t| t.contains_unresolved()) {
 			return return_type;
 		}
 
@@ -3632,7 +4869,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		};
 
 		let return_type_str = actual_original_type.unwrap_or(return_type);
-		let message = format!("Expected type to be {expected_type_str}, but got \"{return_type_str}\" instead");
+		let message = TYPE_MISMATCH.format(&[("expected", &expected_type_str), ("actual", &return_type_str)]);
 		let mut hints: Vec<String> = vec![];
 		if return_type.is_nil() && expected_types.len() == 1 {
 			hints.push(format!(
@@ -3670,6 +4907,14 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			}
 		}
 
+		// Spell out how struct and function types differ instead of leaving it to the user to compare their names
+		if expected_types.len() == 1 {
+			if let Some(diff) = diff_types(return_type, first_expected_type) {
+				hints.extend(diff.hints);
+				annotations.extend(diff.annotations);
+			}
+		}
+
 		if expected_types.len() == 1 && matches!(*expected_types[0], Type::Stringable) {
 			if actual_type.maybe_unwrap_option().is_stringable() {
 				hints.push(format!(
@@ -3681,7 +4926,14 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			};
 		}
 
-		self.spanned_error_with_hints(span, message, &hints);
+		report_diagnostic(Diagnostic {
+			message,
+			span: Some(span.span()),
+			annotations,
+			hints,
+			severity: DiagnosticSeverity::Error,
+			code: Some(TYPE_MISMATCH.code),
+		});
 
 		// Evaluate to one of the expected types
 		first_expected_type
@@ -3718,6 +4970,13 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 					child_envs.push(*env);
 				}
 				Some(SymbolEnvOrNamespace::Namespace(ns)) => {
+					let dir_name = child_file.path.file_stem().unwrap();
+					// Directories named `internal` can be used within the package but aren't exported from it
+					let access = if dir_name == INTERNAL_DIRECTORY_NAME {
+						AccessModifier::Internal
+					} else {
+						AccessModifier::Public
+					};
 					let mut new_env = SymbolEnv::new(
 						None,
 						SymbolEnvKind::Scope,
@@ -3727,9 +4986,9 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 					);
 					new_env
 						.define(
-							&Symbol::global(child_file.path.file_stem().unwrap().to_string()),
+							&Symbol::global(dir_name.to_string()),
 							SymbolKind::Namespace(*ns),
-							AccessModifier::Public,
+							access,
 							StatementIdx::Top,
 						)
 						.unwrap();
@@ -3755,6 +5014,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 							annotations: vec![],
 							hints: vec![],
 							severity: DiagnosticSeverity::Error,
+							code: None,
 						}),
 					);
 					return;
@@ -3782,6 +5042,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 							annotations: vec![],
 							hints: vec![],
 							severity: DiagnosticSeverity::Error,
+							code: None,
 						}),
 					);
 					return;
@@ -3826,23 +5087,41 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		}
 
 		if let SymbolEnvKind::Function { sig, is_init, .. } = env.kind {
+			// iterate over the statements in the scope and check if there are any statements
+			// we care about
+			let mut has_stmt_visitor = HasStatementVisitor::default();
+			has_stmt_visitor.visit(&scope.statements);
+			// Whether the end of the function body can be reached, loops and branches that always return or
+			// throw don't fall through to it
+			let falls_through = scope_completes(self.types, scope);
+
 			let mut return_type = sig.as_function_sig().expect("a function type").return_type;
 			if let Type::Inferred(n) = &*return_type {
 				if self.types.get_inference_by_id(*n).is_none() {
-					// If function types don't return anything then we should set the return type to void
-					self.types.update_inferred_type(*n, self.types.void(), &scope.span);
+					// If function types don't return anything then we should set the return type to void,
+					// unless they always end by throwing in which case they never return
+					let inferred = if !falls_through && !has_stmt_visitor.seen_return {
+						self.types.never()
+					} else {
+						self.types.void()
+					};
+					self.types.update_inferred_type(*n, inferred, &scope.span);
 				}
 				self.update_known_inferences(&mut return_type, &scope.span);
 			}
 
-			// iterate over the statements in the scope and check if there are any statements
-			// we care about
-			let mut has_stmt_visitor = HasStatementVisitor::default();
-			has_stmt_visitor.visit(&scope.statements);
-
+			if return_type.is_never() {
+				if falls_through {
+					self.spanned_error(
+						scope,
+						"A function whose return type is \"never\" must end by throwing an error",
+					);
+				}
+			}
 			// If the scope doesn't contain any return statements and the return type isn't void or T? or
 			// the scope itself does not have a throw error, throw an error to the user
-			if !has_stmt_visitor.seen_throw
+			else if !has_stmt_visitor.seen_throw
+				&& falls_through
 				&& !has_stmt_visitor.seen_return
 				&& !return_type.is_void()
 				&& !return_type.is_option()
@@ -3921,6 +5200,20 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				namespace_filter = vec![];
 				alias = identifier.as_ref().unwrap();
 			}
+			BringSource::NpmModule(_) => {
+				// npm modules have no type information, so everything they export is `any`. They can be used in both
+				// phases since the module is required separately by preflight and inflight code.
+				let alias = identifier.as_ref().unwrap();
+				if let Err(e) = env.define(
+					alias,
+					SymbolKind::make_free_variable(alias.clone(), self.types.anything(), false, Phase::Independent),
+					AccessModifier::Private,
+					StatementIdx::Top,
+				) {
+					self.type_error(e);
+				}
+				return;
+			}
 			BringSource::WingFile(path) => {
 				let brought_env = match self.types.source_file_envs.get(path) {
 					Some(SymbolEnvOrNamespace::SymbolEnv(env)) => *env,
@@ -3962,7 +5255,8 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				return;
 			}
 			BringSource::WingLibrary(name, module_dir) | BringSource::TrustedModule(name, module_dir) => {
-				let brought_ns = match self.types.source_file_envs.get(module_dir) {
+				let module_dir = self.types.unify_library_dir(module_dir);
+				let brought_ns = match self.types.source_file_envs.get(&module_dir) {
 					Some(SymbolEnvOrNamespace::SymbolEnv(_)) => {
 						panic!("Expected a namespace to be associated with the library")
 					}
@@ -4076,7 +5370,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			fqn: format!("{}.{}", self.base_fqn_for_current_file(), st.name),
 			extends: extends_types.clone(),
 			env: dummy_env,
-			docs: doc.as_ref().map_or(Docs::default(), |s| Docs::with_summary(s)),
+			docs: doc.as_ref().map_or(Docs::default(), |s| Docs::from_doc_comment(s)),
 		}));
 
 		match env.define(name, SymbolKind::Type(struct_type), *access, StatementIdx::Top) {
@@ -4131,7 +5425,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		let interface_spec = Interface {
 			name: iface.name.clone(),
 			fqn: format!("{}.{}", self.base_fqn_for_current_file(), iface.name),
-			docs: doc.as_ref().map_or(Docs::default(), |s| Docs::with_summary(s)),
+			docs: doc.as_ref().map_or(Docs::default(), |s| Docs::from_doc_comment(s)),
 			env: dummy_env,
 			extends: extend_interfaces.clone(),
 			phase: iface.phase,
@@ -4156,7 +5450,9 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			name: enu.name.clone(),
 			fqn: format!("{}.{}", self.base_fqn_for_current_file(), enu.name),
 			values: enu.values.clone(),
-			docs: doc.as_ref().map_or(Docs::default(), |s| Docs::with_summary(s)),
+			backing_values: enu.backing_values.clone(),
+			is_flags: enu.is_flags,
+			docs: doc.as_ref().map_or(Docs::default(), |s| Docs::from_doc_comment(s)),
 		}));
 
 		// Enums can only be declared only at the top-level of a program
@@ -4191,6 +5487,9 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			TypeAnnotationKind::Datetime => self.types.datetime(),
 			TypeAnnotationKind::Regex => self.types.regex(),
 			TypeAnnotationKind::Void => self.types.void(),
+			TypeAnnotationKind::Never => self.types.never(),
+			TypeAnnotationKind::Unknown => self.types.unknown(),
+			TypeAnnotationKind::Literal(values) => self.types.add_type(Type::Literal(values.clone())),
 			TypeAnnotationKind::Json => self.types.json(),
 			TypeAnnotationKind::MutJson => self.types.mut_json(),
 			TypeAnnotationKind::Optional(v) => {
@@ -4291,6 +5590,17 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 	}
 
 	fn type_check_arg_list(&mut self, arg_list: &ArgList, env: &mut SymbolEnv) -> ArgListTypes {
+		self.type_check_arg_list_with_expected(arg_list, env, None)
+	}
+
+	/// Type checks the arguments of a call, using the signature of the called function (if known) as the expected
+	/// types of the arguments.
+	fn type_check_arg_list_with_expected(
+		&mut self,
+		arg_list: &ArgList,
+		env: &mut SymbolEnv,
+		func_sig: Option<&FunctionSignature>,
+	) -> ArgListTypes {
 		// By default assume there are no inflight expressions in the arg list
 		let mut inflight_args = false;
 
@@ -4298,19 +5608,30 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		let pos_arg_types = arg_list
 			.pos_args
 			.iter()
-			.map(|pos_arg| {
-				let (t, p) = self.type_check_exp(pos_arg, env);
+			.enumerate()
+			.map(|(idx, pos_arg)| {
+				let expected_type = func_sig.and_then(|sig| self.expected_positional_arg_type(sig, idx));
+				let (t, p) = self.type_check_exp_with_expected(pos_arg, env, expected_type);
 				inflight_args |= p == Phase::Inflight;
 				t
 			})
 			.collect();
 
+		// Named arguments are fields of the struct expected as the last parameter
+		let expected_struct = func_sig
+			.and_then(|sig| sig.parameters.last())
+			.and_then(|p| p.typeref.maybe_unwrap_option().as_struct());
+
 		// Type check the named arguments, e.g. fn(named_arg1: exp4, named_arg2: exp5)
 		let named_arg_types = arg_list
 			.named_args
 			.iter()
 			.map(|(sym, expr)| {
-				let (arg_type, p) = self.type_check_exp(&expr, env);
+				let expected_type = expected_struct
+					.and_then(|s| s.env.lookup(sym, None))
+					.and_then(|field| field.as_variable())
+					.map(|field| field.type_);
+				let (arg_type, p) = self.type_check_exp_with_expected(&expr, env, expected_type);
 				inflight_args |= p == Phase::Inflight;
 				(
 					sym.clone(),
@@ -4329,6 +5650,15 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		}
 	}
 
+	/// The type expected for the positional argument at the given index, taking variadic parameters into account.
+	fn expected_positional_arg_type(&self, func_sig: &FunctionSignature, idx: usize) -> Option<TypeRef> {
+		let last_param = func_sig.parameters.last()?;
+		if last_param.variadic && idx >= func_sig.parameters.len() - 1 {
+			return last_param.typeref.collection_item_type();
+		}
+		func_sig.parameters.get(idx).map(|p| p.typeref)
+	}
+
 	fn type_check_statement(&mut self, stmt: &Stmt, env: &mut SymbolEnv) {
 		CompilationContext::set(CompilationPhase::TypeChecking, &stmt.span);
 
@@ -4339,19 +5669,30 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				var_name,
 				initial_value,
 				type_,
+				access,
 			} => {
-				tc.type_check_let(type_, initial_value, var_name, reassignable, env);
+				tc.type_check_let(type_, initial_value, var_name, reassignable, access, env);
 			}
 			StmtKind::ForLoop {
 				iterator,
+				second_iterator,
 				iterable,
 				statements,
 			} => {
-				tc.type_check_for_loop(iterable, iterator, statements, env);
+				tc.type_check_for_loop(iterable, iterator, second_iterator, statements, env);
 			}
 			StmtKind::While { condition, statements } => {
 				tc.type_check_while(condition, statements, env);
 			}
+			StmtKind::WhileLet {
+				reassignable,
+				var_name,
+				value,
+				statements,
+			} => {
+				// The value is unwrapped into the loop's scope the same way as in `if let`
+				tc.type_check_if_let_statement(value, &[], statements, reassignable, var_name, env);
+			}
 			StmtKind::Break | StmtKind::Continue => {}
 			StmtKind::IfLet(iflet) => {
 				tc.type_check_iflet(iflet, env);
@@ -4525,7 +5866,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 					false,
 					Phase::Independent,
 					AccessModifier::Public,
-					field.doc.as_ref().map(|s| Docs::with_summary(s)),
+					field.doc.as_ref().map(|s| Docs::from_doc_comment(s)),
 				),
 				AccessModifier::Public,
 				StatementIdx::Top,
@@ -4568,14 +5909,18 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		);
 
 		// Add methods to the interface env
-		for (method_name, sig, doc) in ast_iface.methods.iter() {
+		for (method_name, sig, doc, is_static) in ast_iface.methods.iter() {
 			let mut method_type = self.resolve_type_annotation(&sig.to_type_annotation(), env);
-			// use the interface type as the function's "this" type
+			// use the interface type as the function's "this" type (static methods have no "this")
 			if let Type::Function(ref mut f) = *method_type {
-				f.this_type = Some(interface_type);
+				f.this_type = if *is_static { None } else { Some(interface_type) };
 			} else {
 				panic!("Expected method type to be a function");
 			}
+			let docs = doc.as_ref().map(|s| Docs::from_doc_comment(s));
+			if let Some(docs) = &docs {
+				self.document_function_sig(method_type.as_function_sig_mut().unwrap(), docs, method_name);
+			}
 
 			match interface_env.define(
 				method_name,
@@ -4583,10 +5928,10 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 					method_name.clone(),
 					method_type,
 					false,
-					false,
+					*is_static,
 					sig.phase,
 					AccessModifier::Public,
-					doc.as_ref().map(|s| Docs::with_summary(s)),
+					docs,
 				),
 				AccessModifier::Public,
 				StatementIdx::Top,
@@ -4731,12 +6076,19 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			is_abstract: false,
 			phase: ast_class.phase,
 			defined_in_phase: env.phase,
-			docs: stmt.doc.as_ref().map_or(Docs::default(), |s| Docs::with_summary(s)),
+			docs: stmt.doc.as_ref().map_or(Docs::default(), |s| Docs::from_doc_comment(s)),
 			std_construct_args: ast_class.phase == Phase::Preflight,
 			lifts: None,
-			uid: self.types.class_counter,
+			uid: self.types.class_uid(&format!(
+				"{}#{}",
+				self
+					.source_file
+					.path
+					.strip_prefix(self.current_package_root())
+					.unwrap_or(&self.source_file.path),
+				ast_class.name
+			)),
 		};
-		self.types.class_counter += 1;
 		let mut class_type = self.types.add_type(Type::Class(class_spec));
 		match env.define(
 			&ast_class.name,
@@ -4771,7 +6123,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 					field.is_static,
 					field.phase,
 					field.access,
-					field.doc.as_ref().map(|s| Docs::with_summary(s)),
+					field.doc.as_ref().map(|s| Docs::from_doc_comment(s)),
 				),
 				field.access,
 				StatementIdx::Top,
@@ -4967,6 +6319,20 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				{
 					let class_method_var = symbol.as_variable().expect("Expected method to be a variable");
 					let class_method_type = class_method_var.type_;
+					// Static interface methods must be implemented by static class methods and vice versa
+					let is_static = v.kind == VariableKind::StaticMember;
+					if is_static != (class_method_var.kind == VariableKind::StaticMember) {
+						self.spanned_error(
+							&class_method_var.name,
+							format!(
+								"Method \"{method_name}\" is {} in interface \"{interface_type}\" but its implementation in \"{}\" is {}",
+								if is_static { "static" } else { "not static" },
+								ast_class.name,
+								if is_static { "not static" } else { "static" },
+							),
+						);
+						continue;
+					}
 					self.validate_type(class_method_type, method_type, &class_method_var.name);
 					// Make sure the method is public (interface methods must be public)
 					if class_method_var.access != AccessModifier::Public {
@@ -5069,19 +6435,49 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			panic!("Expected function env");
 		};
 		let mut function_ret_type = cur_func_type.as_function_sig().expect("a function_type").return_type;
+		let return_type_inference = self.types.as_inference(function_ret_type);
 
 		let return_type_inferred = self.update_known_inferences(&mut function_ret_type, &stmt.span);
 
+		// If the return type was inferred from a previous return statement, widen it to fit this one as well
+		if let (Some(inference), Some((return_type, _))) = (return_type_inference, &return_type) {
+			if !function_ret_type.is_inferred() && !return_type.is_subtype_of(&function_ret_type) {
+				if let Some(lub) = self.types.least_upper_bound(function_ret_type, *return_type) {
+					self.types.widen_inferred_type(inference, lub);
+					function_ret_type = lub;
+				}
+			}
+		}
+
+		if function_ret_type.is_never() {
+			self.spanned_error(stmt, "Cannot return from a function whose return type is \"never\"");
+			return;
+		}
+
 		if let Some((return_type, return_expression)) = return_type {
 			if !function_ret_type.is_void() {
-				self.validate_type(return_type, function_ret_type, return_expression);
+				let declared_return_type = self
+					.ctx
+					.current_function()
+					.map(|function| *function.sig.return_type)
+					.filter(|return_type| !matches!(return_type.kind, TypeAnnotationKind::Inferred));
+				if let Some(declared_return_type) = declared_return_type {
+					self.validate_type_expected_due_to(
+						return_type,
+						function_ret_type,
+						return_expression,
+						DiagnosticAnnotation::new("expected due to this return type", &declared_return_type),
+					);
+				} else {
+					self.validate_type(return_type, function_ret_type, return_expression);
+				}
 			} else {
 				if return_type_inferred {
 					self.spanned_error(stmt, "Unexpected return value from void function");
 				} else {
 					self.spanned_error(
 						stmt,
-						"Unexpected return value from void function. Return type annotations are required for methods.",
+						"Unexpected return value from void function. Return type annotations are required for non-private methods and functions.",
 					);
 				}
 			}
@@ -5123,17 +6519,19 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		// let x = Array<num>[1, 2, 3];
 		// x[0] = 4; // error: cannot update elements of an immutable array
 		// ```
+		if let Reference::ElementAccess {
+			optional_accessor: true,
+			..
+		} = variable
+		{
+			self.spanned_error(variable, "Cannot assign to an optional element access (\"?[\")");
+		}
 		match &var {
 			ResolveReferenceResult::Variable(var) => {
 				if !var.reassignable && !var.type_.is_unresolved() {
-					self.spanned_error_with_annotations(
-						variable,
-						"Variable is not reassignable".to_string(),
-						vec![DiagnosticAnnotation {
-							message: "defined here (try adding \"var\" in front)".to_string(),
-							span: var.name.span(),
-						}],
-					);
+					Diagnostic::from_message(NOT_REASSIGNABLE, &[], variable)
+						.annotate("defined here (try adding \"var\" in front)", var.name.span())
+						.report();
 				} else if var_phase == Phase::Preflight && env.phase == Phase::Inflight {
 					self.spanned_error(variable, "Variable cannot be reassigned from inflight");
 				}
@@ -5172,6 +6570,9 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				| Type::Regex
 				| Type::Boolean
 				| Type::Void
+				| Type::Never
+				| Type::Unknown
+				| Type::Literal(_)
 				| Type::Nil
 				| Type::Optional(_)
 				| Type::Set(_)
@@ -5204,8 +6605,22 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		// ```
 		match kind {
 			AssignmentKind::AssignIncr => {
-				self.validate_type_in(exp_type, &[self.types.number(), self.types.string()], value, None, None);
-				self.validate_type_in(var_type, &[self.types.number(), self.types.string()], value, None, None);
+				self.validate_type_in(
+					exp_type,
+					&[self.types.number(), self.types.string()],
+					value,
+					None,
+					None,
+					vec![],
+				);
+				self.validate_type_in(
+					var_type,
+					&[self.types.number(), self.types.string()],
+					value,
+					None,
+					None,
+					vec![],
+				);
 			}
 			AssignmentKind::AssignDecr => {
 				self.validate_type(exp_type, self.types.number(), value);
@@ -5213,7 +6628,19 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			}
 			AssignmentKind::Assign => {}
 		}
-		self.validate_type(exp_type, var_type, value);
+		match &var {
+			ResolveReferenceResult::Variable(var) if !var.name.span.is_default() => {
+				self.validate_type_expected_due_to(
+					exp_type,
+					var_type,
+					value,
+					DiagnosticAnnotation::new(format!("expected due to the type of \"{}\"", var.name), &var.name),
+				);
+			}
+			_ => {
+				self.validate_type(exp_type, var_type, value);
+			}
+		}
 	}
 
 	fn type_check_if(
@@ -5246,6 +6673,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 	fn type_check_iflet(&mut self, iflet: &IfLet, env: &mut SymbolEnv) {
 		self.type_check_if_let_statement(
 			&iflet.value,
+			&iflet.conditions,
 			&iflet.statements,
 			&iflet.reassignable,
 			&iflet.var_name,
@@ -5260,6 +6688,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				ElseIfs::ElseIfLetBlock(else_if_let_block) => {
 					self.type_check_if_let_statement(
 						&else_if_let_block.value,
+						&[],
 						&else_if_let_block.statements,
 						&else_if_let_block.reassignable,
 						&else_if_let_block.var_name,
@@ -5298,22 +6727,48 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		self.inner_scopes.push((statements, self.ctx.clone()));
 	}
 
-	fn type_check_for_loop(&mut self, iterable: &Expr, iterator: &Symbol, statements: &Scope, env: &mut SymbolEnv) {
+	fn type_check_for_loop(
+		&mut self,
+		iterable: &Expr,
+		iterator: &Symbol,
+		second_iterator: &Option<Symbol>,
+		statements: &Scope,
+		env: &mut SymbolEnv,
+	) {
 		// TODO: Expression must be iterable
 		let (exp_type, _) = self.type_check_exp(iterable, env);
 
-		if !exp_type.is_iterable() {
-			self.spanned_error(iterable, format!("Unable to iterate over \"{}\"", &exp_type));
-		}
-
-		let iterator_type = match &*exp_type {
-			// These are builtin iterables that have a clear/direct iterable type
-			Type::Array(t) => *t,
-			Type::Set(t) => *t,
-			Type::MutArray(t) => *t,
-			Type::MutSet(t) => *t,
-			Type::Anything => exp_type,
-			_t => self.types.error(),
+		let (iterator_type, second_iterator_type) = if second_iterator.is_some() {
+			// Iterating with two variables yields item and index for arrays, and key and value for maps
+			match &*exp_type {
+				Type::Array(t) | Type::MutArray(t) => (*t, Some(self.types.number())),
+				Type::Map(t) | Type::MutMap(t) => (self.types.string(), Some(*t)),
+				_ => {
+					if !exp_type.is_unresolved() {
+						self.spanned_error_with_hints(
+							iterable,
+							format!("Unable to iterate over \"{}\" with two loop variables", &exp_type),
+							&["Use \"for item, i in arr\" to iterate over an array with indices, or \"for key, value in map\" to iterate over a map"],
+						);
+					}
+					(self.types.error(), Some(self.types.error()))
+				}
+			}
+		} else {
+			if !exp_type.is_iterable() {
+				self.spanned_error(iterable, format!("Unable to iterate over \"{}\"", &exp_type));
+			}
+
+			let iterator_type = match &*exp_type {
+				// These are builtin iterables that have a clear/direct iterable type
+				Type::Array(t) => *t,
+				Type::Set(t) => *t,
+				Type::MutArray(t) => *t,
+				Type::MutSet(t) => *t,
+				Type::Anything => exp_type,
+				_t => self.types.error(),
+			};
+			(iterator_type, None)
 		};
 
 		let mut scope_env = self.types.add_symbol_env(SymbolEnv::new(
@@ -5334,6 +6789,19 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			}
 			_ => {}
 		};
+		if let (Some(second_iterator), Some(second_iterator_type)) = (second_iterator, second_iterator_type) {
+			match scope_env.define(
+				second_iterator,
+				SymbolKind::make_free_variable(second_iterator.clone(), second_iterator_type, false, env.phase),
+				AccessModifier::Private,
+				StatementIdx::Top,
+			) {
+				Err(type_error) => {
+					self.type_error(type_error);
+				}
+				_ => {}
+			};
+		}
 		self.types.set_scope_env(statements, scope_env);
 
 		self.inner_scopes.push((statements, self.ctx.clone()));
@@ -5345,10 +6813,41 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		initial_value: &Expr,
 		var_name: &Symbol,
 		reassignable: &bool,
+		access: &AccessModifier,
 		env: &mut SymbolEnv,
 	) {
+		if *access != AccessModifier::Private {
+			if !env.is_root() {
+				self.spanned_error(
+					var_name,
+					"Only module-level variables and functions can have an access modifier",
+				);
+			} else if *reassignable {
+				self.spanned_error_with_hints(
+					var_name,
+					format!("Variable \"{var_name}\" cannot be reassignable because it is {access}"),
+					&["use a class with a public setter method to share mutable state"],
+				);
+			}
+		}
+
 		let explicit_type = type_.as_ref().map(|t| self.resolve_type_annotation(t, env));
-		let (mut inferred_type, _) = self.type_check_exp(initial_value, env);
+		let (mut inferred_type, _) = self.type_check_exp_with_expected(initial_value, env, explicit_type);
+		// Module files don't have a construct scope at their top level
+		if env.is_root() && !is_entrypoint_file(&self.source_file.path) {
+			let is_inflight_closure = inferred_type
+				.as_function_sig()
+				.map_or(false, |sig| sig.phase == Phase::Inflight);
+			if inferred_type.is_preflight_object_type() || is_inflight_closure {
+				self.spanned_error_with_hints(
+					initial_value,
+					format!(
+						"Module-level variables in library files cannot hold preflight objects or inflight closures, found \"{inferred_type}\""
+					),
+					&["create it inside a class constructor instead"],
+				);
+			}
+		}
 		if inferred_type.is_void() {
 			self.spanned_error(
 				var_name,
@@ -5361,8 +6860,13 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				"Cannot assign nil value to variables without explicit optional type",
 			);
 		}
-		if let Some(explicit_type) = explicit_type {
-			self.validate_type(inferred_type, explicit_type, initial_value);
+		if let (Some(explicit_type), Some(type_annotation)) = (explicit_type, type_) {
+			self.validate_type_expected_due_to(
+				inferred_type,
+				explicit_type,
+				initial_value,
+				DiagnosticAnnotation::new("expected due to this type annotation", type_annotation),
+			);
 			let final_type = if !*reassignable && explicit_type.is_json() && inferred_type.is_json() {
 				// If both types are Json, use the inferred type in case it has more information
 				inferred_type
@@ -5372,7 +6876,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			match env.define(
 				var_name,
 				SymbolKind::make_free_variable(var_name.clone(), final_type, *reassignable, env.phase),
-				AccessModifier::Private,
+				*access,
 				StatementIdx::Index(self.ctx.current_stmt_idx()),
 			) {
 				Err(type_error) => {
@@ -5390,7 +6894,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			match env.define(
 				var_name,
 				SymbolKind::make_free_variable(var_name.clone(), inferred_type, *reassignable, env.phase),
-				AccessModifier::Private,
+				*access,
 				StatementIdx::Index(self.ctx.current_stmt_idx()),
 			) {
 				Err(type_error) => {
@@ -5399,16 +6903,61 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				_ => {}
 			};
 		}
+
+		// Remember the length of immutable arrays initialized from literals so constant indices can be bounds checked
+		if let ExprKind::ArrayLiteral { items, .. } = &initial_value.kind {
+			let var_type = env
+				.lookup(var_name, Some(self.ctx.current_stmt_idx()))
+				.and_then(|v| v.as_variable())
+				.map(|v| v.type_);
+			if !*reassignable && matches!(var_type.as_deref(), Some(Type::Array(_))) {
+				self.literal_array_lengths.insert(var_name.span.clone(), items.len());
+			}
+		}
 	}
 
 	fn type_check_if_let_statement(
 		&mut self,
 		value: &Expr,
+		conditions: &[IfLetCondition],
 		statements: &Scope,
 		reassignable: &bool,
 		var_name: &Symbol,
 		env: &mut SymbolEnv,
 	) {
+		let mut stmt_env = self.type_check_if_let_binding(value, reassignable, var_name, env);
+
+		// Each chained binding gets its own nested scope so it can shadow earlier ones, guards can see
+		// all the bindings that precede them
+		for condition in conditions {
+			match condition {
+				IfLetCondition::Let {
+					reassignable,
+					var_name,
+					value,
+				} => {
+					stmt_env = self.type_check_if_let_binding(value, reassignable, var_name, &mut stmt_env);
+				}
+				IfLetCondition::Guard(guard) => {
+					let (guard_type, _) = self.type_check_exp(guard, &mut stmt_env);
+					self.validate_type(guard_type, self.types.bool(), guard);
+				}
+			}
+		}
+
+		self.types.set_scope_env(statements, stmt_env);
+		self.inner_scopes.push((statements, self.ctx.clone()));
+	}
+
+	/// Type checks the value of an `if let` binding and returns a new scope, nested in `env`, with
+	/// the unwrapped variable defined in it
+	fn type_check_if_let_binding(
+		&mut self,
+		value: &Expr,
+		reassignable: &bool,
+		var_name: &Symbol,
+		env: &mut SymbolEnv,
+	) -> SymbolEnvRef {
 		let (mut cond_type, _) = self.type_check_exp(value, env);
 
 		if let Type::Inferred(n) = *cond_type {
@@ -5455,8 +7004,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			_ => {}
 		}
 
-		self.types.set_scope_env(statements, stmt_env);
-		self.inner_scopes.push((statements, self.ctx.clone()));
+		stmt_env
 	}
 
 	fn type_check_if_statement(&mut self, condition: &Expr, statements: &Scope, env: &mut SymbolEnv) {
@@ -5565,6 +7113,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		let mut visit_init = VisitClassInit::default();
 		visit_init.analyze_statements(&scope.statements);
 		let initialized_fields = visit_init.fields;
+		let definitely_initialized = visit_init.definitely_initialized;
 
 		let (current_phase, forbidden_phase) = if phase == Phase::Inflight {
 			("Inflight", Phase::Preflight)
@@ -5599,6 +7148,15 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 					&field.name,
 					format!("{} field \"{}\" is not initialized", current_phase, field.name.name),
 				);
+			} else if field.phase == phase && !definitely_initialized.contains(&field.name.name) {
+				// It's initialized somewhere, but there's a path through the constructor that skips it
+				self.spanned_error(
+					&field.name,
+					format!(
+						"{} field \"{}\" is not initialized on all paths of the constructor",
+						current_phase, field.name.name
+					),
+				);
 			}
 		}
 	}
@@ -5653,6 +7211,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			&method_def.signature,
 			method_def.is_static,
 			method_env,
+			&method_def.span,
 			|tc| {
 				if let FunctionBody::Statements(scope) = &method_def.body {
 					tc.types.set_scope_env(scope, method_env);
@@ -5694,6 +7253,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 										}],
 										hints: vec![format!("Change type to match first declaration: {}", lookup.type_)],
 										severity: DiagnosticSeverity::Error,
+										code: None,
 									});
 								}
 							} else {
@@ -5765,6 +7325,11 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				&& !matches!(method_def.body, FunctionBody::External(_))
 		};
 
+		let docs = method_def.doc.as_ref().map(|doc| Docs::from_doc_comment(doc));
+		if let Some(docs) = &docs {
+			self.document_function_sig(method_sig, docs, method_name);
+		}
+
 		// If this method is overriding a parent method, check access modifiers allow it, note this is only relevant for instance methods
 		if instance_type.is_some() {
 			if let Some(parent_type_env) = class_env.parent {
@@ -5810,7 +7375,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				instance_type.is_none(),
 				method_phase,
 				access,
-				method_def.doc.as_ref().map(|s| Docs::with_summary(s)),
+				docs,
 			),
 			access,
 			StatementIdx::Top,
@@ -5818,8 +7383,30 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			Err(type_error) => {
 				self.type_error(type_error);
 			}
-			_ => {}
-		};
+			_ => {}
+		};
+	}
+
+	/// Documents a function's signature and parameters with its doc comment, and warns about `@param` tags that don't
+	/// match any of its parameters
+	fn document_function_sig(&mut self, sig: &mut FunctionSignature, docs: &Docs, function: &impl Spanned) {
+		for (name, description) in &docs.params {
+			if let Some(param) = sig.parameters.iter_mut().find(|param| &param.name == name) {
+				param.docs = Docs::with_summary(description);
+				continue;
+			}
+			let suggestions = similar_names(name, sig.parameters.iter().map(|param| param.name.as_str()));
+			let mut diagnostic = Diagnostic::new(
+				format!("Doc comment documents parameter \"{name}\", but the function has no such parameter"),
+				function,
+			)
+			.severity(DiagnosticSeverity::Warning);
+			if let Some(hint) = did_you_mean_hint(&suggestions) {
+				diagnostic = diagnostic.hint(hint);
+			}
+			diagnostic.report();
+		}
+		sig.docs = docs.clone();
 	}
 
 	pub fn add_jsii_module_to_env(
@@ -5837,6 +7424,10 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			.find(|j| j.assembly_name == library_name && j.alias.same(alias))
 		{
 			// This spec has already been pre-supplied to the typechecker, so we'll still use this to populate the symbol environment
+			emit_event(CompilerEvent::CacheHit {
+				cache: CacheKind::JsiiImport,
+				key: &jsii.assembly_name,
+			});
 			jsii
 		} else {
 			// Loading the SDK is handled different from loading any other jsii modules because with the SDK we provide an exact
@@ -5895,7 +7486,19 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 
 		// check if we've already defined the given alias in the current scope
 		if env.lookup(&jsii.alias.name.as_str().into(), None).is_some() {
-			self.spanned_error(alias, format!("\"{}\" is already defined", alias.name));
+			let previous_definition = env
+				.symbol_map
+				.get(&alias.name)
+				.map(|entry| entry.span.clone())
+				.filter(|span| !span.is_default());
+			self.spanned_error_with_annotations(
+				alias,
+				format!("\"{}\" is already defined", alias.name),
+				previous_definition
+					.map(|span| DiagnosticAnnotation::new("previous definition", &span))
+					.into_iter()
+					.collect(),
+			);
 		} else {
 			let mut importer = JsiiImporter::new(&jsii, self.types, self.jsii_types);
 
@@ -6275,8 +7878,8 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 					path.push(type_name.root.clone());
 					break;
 				}
-				// a[b] cannot be a type reference
-				Reference::ElementAccess { .. } => return None,
+				// a[b] and super.x cannot be type references
+				Reference::ElementAccess { .. } | Reference::SuperMember { .. } => return None,
 			}
 		}
 
@@ -6303,6 +7906,59 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		})
 	}
 
+	/// If `object` refers to a namespace and `property` is a variable defined in it (e.g. `ns.myFunc`),
+	/// returns the variable's info. The namespace expression itself doesn't evaluate to a value.
+	fn resolve_namespace_variable(&mut self, object: &Expr, property: &Symbol, env: &SymbolEnv) -> Option<VariableInfo> {
+		let ExprKind::Reference(reference) = &object.kind else {
+			return None;
+		};
+		let ns_path = self.reference_to_udt(reference)?.full_path();
+		let LookupResult::Found(SymbolKind::Namespace(_), _) =
+			env.lookup_nested(&ns_path.iter().collect_vec(), Some(self.ctx.current_stmt_idx()))
+		else {
+			return None;
+		};
+
+		let member_path = ns_path.iter().chain(std::iter::once(property)).collect_vec();
+		let variable = match env.lookup_nested(&member_path, Some(self.ctx.current_stmt_idx())) {
+			LookupResult::Found(SymbolKind::Variable(variable), _) => variable.clone(),
+			lookup_res @ LookupResult::NotPublic(SymbolKind::Variable(_), _) => {
+				self.type_error(lookup_result_to_type_error(lookup_res, property, env));
+				self.make_error_variable_info()
+			}
+			_ => return None,
+		};
+
+		// Give the namespace expression a type so later phases can traverse it like any other object expression
+		self
+			.types
+			.assign_type_to_expr(object, self.types.void(), Phase::Preflight);
+		Some(variable)
+	}
+
+	/// If `type_name` is a struct whose body declares a static method named `property`, returns the
+	/// class holding the struct's static methods (see `parser::struct_statics_class_name`).
+	fn struct_statics_class(
+		&mut self,
+		type_name: &UserDefinedType,
+		property: &Symbol,
+		env: &SymbolEnv,
+	) -> Option<UserDefinedType> {
+		let stmt_idx = self.ctx.current_stmt_idx();
+		self
+			.resolve_user_defined_type(type_name, env, stmt_idx)
+			.ok()?
+			.as_struct()?;
+
+		let mut statics_udt = type_name.clone();
+		let struct_name = statics_udt.fields.last_mut().unwrap_or(&mut statics_udt.root);
+		struct_name.name = struct_statics_class_name(&struct_name.name);
+
+		let statics_class = self.resolve_user_defined_type(&statics_udt, env, stmt_idx).ok()?;
+		statics_class.as_class()?.get_method(property)?;
+		Some(statics_udt)
+	}
+
 	/// Check if this expression is actually a reference to a type. The parser doesn't distinguish between a `some_expression.field` and `SomeType.field`.
 	/// This function checks if the expression is a reference to a user define type and if it is it returns it. If not it returns `None`.
 	fn expr_maybe_type(&mut self, expr: &Expr, env: &SymbolEnv) -> Option<UserDefinedType> {
@@ -6388,7 +8044,14 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 						self.spanned_error(symbol, "Unknown symbol \"print\", did you mean to use \"log\"?");
 					} else {
 						let lookup_res = env.lookup_ext(symbol, Some(self.ctx.current_stmt_idx()));
-						self.type_error(lookup_result_to_type_error(lookup_res, symbol));
+						let not_found = matches!(lookup_res, LookupResult::NotFound(..));
+						let mut error = lookup_result_to_type_error(lookup_res, symbol, env);
+						if not_found {
+							error
+								.hints
+								.extend(did_you_mean_hint(&similar_symbol_names(env, &symbol.name, true)));
+						}
+						self.type_error(error);
 					}
 					(
 						ResolveReferenceResult::Variable(self.make_error_variable_info()),
@@ -6401,10 +8064,21 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				property,
 				optional_accessor,
 			} => {
+				// Module-level variables and functions of a brought file are accessed directly through its namespace
+				if let Some(variable) = self.resolve_namespace_variable(object, property, env) {
+					let phase = variable.phase;
+					return (ResolveReferenceResult::Variable(variable), phase);
+				}
+
 				// There's a special case where the object is actually a type and the property is either a static member or an enum variant.
 				// In this case the type might even be namespaced (recursive nested reference). We need to detect this and transform this
 				// reference into a type reference.
 				if let Some(user_type_annotation) = self.expr_maybe_type(object, env) {
+					// Static methods declared in a struct's body live in a class generated alongside the struct
+					let user_type_annotation = self
+						.struct_statics_class(&user_type_annotation, property, env)
+						.unwrap_or(user_type_annotation);
+
 					// We can't get here twice, we can safely assume that if we're here the `object` part of the reference doesn't have and evaluated type yet.
 					// Create a type reference out of this nested reference and call ourselves again
 					let new_ref = Reference::TypeMember {
@@ -6502,6 +8176,28 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 
 				(ResolveReferenceResult::Variable(property_variable), property_phase)
 			}
+			Reference::SuperMember { property } => {
+				match resolve_super_member(property, env, &self.types) {
+					Ok((parent_type, _)) => {
+						// Checks the member is accessible from the current class
+						let variable = self.get_property_from_class_like(parent_type.as_class().unwrap(), property, false, env);
+						// Phase independent members get the env phase, like other instance members
+						let phase = if variable.phase == Phase::Independent {
+							env.phase
+						} else {
+							variable.phase
+						};
+						(ResolveReferenceResult::Variable(variable), phase)
+					}
+					Err(e) => {
+						self.type_error(e);
+						(
+							ResolveReferenceResult::Variable(self.make_error_variable_info()),
+							Phase::Independent,
+						)
+					}
+				}
+			}
 			Reference::TypeMember { type_name, property } => {
 				let type_ = self
 					.resolve_user_defined_type(type_name, env, self.ctx.current_stmt_idx())
@@ -6522,11 +8218,16 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 								Phase::Independent,
 							)
 						} else {
-							let err = self.spanned_error_with_var(
+							let suggestions = similar_names(&property.name, e.values.keys().map(|value| value.name.as_str()));
+							self.spanned_error_with_hints(
 								property,
 								format!("Enum \"{}\" does not contain value \"{}\"", type_, property.name),
+								&did_you_mean_hint(&suggestions).into_iter().collect_vec(),
 							);
-							(ResolveReferenceResult::Variable(err.0), err.1)
+							(
+								ResolveReferenceResult::Variable(self.make_error_variable_info()),
+								Phase::Independent,
+							)
 						}
 					}
 					Type::Struct(ref s) => {
@@ -6574,16 +8275,34 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 						};
 						(ResolveReferenceResult::Variable(v.clone()), phase)
 					}
+					Type::Interface(ref iface) if iface.get_method(property).is_some() => {
+						let err = self.spanned_error_with_var(
+							property,
+							format!("Cannot call method \"{property}\" of interface \"{iface}\" directly, it must be called on a class that implements the interface"),
+						);
+						(ResolveReferenceResult::Variable(err.0), err.1)
+					}
 					_ => {
 						let err = self.spanned_error_with_var(property, format!("\"{}\" not a valid reference", reference));
 						(ResolveReferenceResult::Variable(err.0), err.1)
 					}
 				}
 			}
-			Reference::ElementAccess { object, index } => {
+			Reference::ElementAccess {
+				object,
+				index,
+				optional_accessor,
+			} => {
 				let (instance_type, instance_phase) = self.type_check_exp(object, env);
 				let (index_type, index_phase) = self.type_check_exp(index, env);
 
+				// With optional chaining (`a?[b]`) we index into the unwrapped type
+				let instance_type = if *optional_accessor {
+					*instance_type.maybe_unwrap_option()
+				} else {
+					instance_type
+				};
+
 				// Given a[b], we type check the expression according to the type of a.
 				let res = match *instance_type {
 					// TODO: it might be possible to look at Type::Json's inner data to give a more specific type
@@ -6594,6 +8313,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 							index,
 							None,
 							None,
+							vec![],
 						);
 						ResolveReferenceResult::Location(instance_type, self.types.json()) // indexing into a Json object returns a Json object
 					}
@@ -6604,11 +8324,13 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 							index,
 							None,
 							None,
+							vec![],
 						);
 						ResolveReferenceResult::Location(instance_type, self.types.mut_json()) // indexing into a MutJson object returns a MutJson object
 					}
 					Type::Array(inner_type) | Type::MutArray(inner_type) => {
 						self.validate_type(index_type, self.types.number(), index);
+						self.check_constant_index_bounds(object, index, env);
 						ResolveReferenceResult::Location(instance_type, inner_type)
 					}
 					Type::Map(inner_type) | Type::MutMap(inner_type) => {
@@ -6632,8 +8354,10 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 					| Type::Regex
 					| Type::Boolean
 					| Type::Void
+					| Type::Never
+					| Type::Unknown
+					| Type::Literal(_)
 					| Type::Nil
-					| Type::Optional(_)
 					| Type::Set(_)
 					| Type::MutSet(_)
 					| Type::Function(_)
@@ -6645,6 +8369,22 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 						let err = self.spanned_error_with_var(object, format!("Type \"{}\" is not indexable", instance_type));
 						ResolveReferenceResult::Variable(err.0)
 					}
+					Type::Optional(_) => {
+						self.spanned_error_with_hints(
+							object,
+							format!("Type \"{}\" is not indexable", instance_type),
+							&["Use optional element access to index it only if it is defined: \"x?[i]\""],
+						);
+						ResolveReferenceResult::Variable(self.make_error_variable_info())
+					}
+				};
+
+				// If `a?[b]`, the element is nil when `a` is nil
+				let res = match res {
+					ResolveReferenceResult::Location(container_type, element_type) if *optional_accessor => {
+						ResolveReferenceResult::Location(container_type, self.types.make_option(element_type))
+					}
+					res => res,
 				};
 				(
 					res,
@@ -6663,6 +8403,53 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 		}
 	}
 
+	/// Reports an error if `index` is a constant that isn't an integer, or that is out of bounds for an array
+	/// with a length known at compile time. Negative indices count from the end of the array, so `-len..len` is
+	/// valid.
+	fn check_constant_index_bounds(&mut self, object: &Expr, index: &Expr, env: &SymbolEnv) {
+		let Some(i) = constant_number(index) else {
+			return;
+		};
+		if i.fract() != 0.0 {
+			self.spanned_error(index, format!("Array index must be an integer, found {}", i));
+			return;
+		}
+		let Some(len) = self.known_array_length(object, env) else {
+			return;
+		};
+		if i >= len as f64 || i < -(len as f64) {
+			self.spanned_error_with_hints(
+				index,
+				format!("Index {} is out of bounds for array of length {}", i, len),
+				&[if len == 0 {
+					"The array is empty".to_string()
+				} else {
+					format!(
+						"Valid indices are 0 to {}, or -{} to -1 to count from the end",
+						len - 1,
+						len
+					)
+				}],
+			);
+		}
+	}
+
+	/// Returns the length of an array expression if it can be determined at compile time: either an array
+	/// literal or a non-reassignable variable holding an immutable array that was initialized from one.
+	fn known_array_length(&self, object: &Expr, env: &SymbolEnv) -> Option<usize> {
+		match &object.kind {
+			ExprKind::ArrayLiteral { items, .. } => Some(items.len()),
+			ExprKind::Reference(Reference::Identifier(symbol)) => {
+				let var = env.lookup(symbol, Some(self.ctx.current_stmt_idx()))?.as_variable()?;
+				if var.reassignable {
+					return None;
+				}
+				self.literal_array_lengths.get(&var.name.span).copied()
+			}
+			_ => None,
+		}
+	}
+
 	/// Check if the given property on the given type with the given access modifier can be accessed from the current context
 	fn resolve_variable_from_instance_type(
 		&mut self,
@@ -6746,6 +8533,35 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				env,
 			),
 			Type::Struct(ref s) => self.get_property_from_class_like(s, property, true, env),
+			Type::Enum(ref e) if property.name == ENUM_VALUE_PROPERTY => VariableInfo {
+				name: property.clone(),
+				type_: if e.is_flags {
+					self.types.number()
+				} else {
+					self.types.string()
+				},
+				reassignable: false,
+				phase: Phase::Independent,
+				kind: VariableKind::InstanceMember,
+				access: AccessModifier::Public,
+				docs: None,
+			},
+			Type::Literal(ref values) => {
+				let Some(base_type) = self.types.literal_base_type(values) else {
+					return self.spanned_error_with_var(property, "Property not found").0;
+				};
+				self.resolve_variable_from_instance_type(base_type, property, env)
+			}
+			Type::Unknown => {
+				self
+					.spanned_error_with_var(
+						property,
+						format!(
+							"Cannot access \"{property}\" on a value of type \"unknown\", narrow it to a known type first (e.g. with unsafeCast())"
+						),
+					)
+					.0
+			}
 			_ => self.spanned_error_with_var(property, "Property not found").0,
 		}
 	}
@@ -6785,6 +8601,15 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 			}
 
 			let allow_internal_access = self.source_file.package == lookup_info.env.source_package;
+			let required_access_hint = || {
+				let other_package = &lookup_info.env.source_package;
+				access_modifier_hint(
+					&property.name,
+					var.access,
+					minimal_access_modifier(var.access, allow_protected_access, allow_internal_access),
+					(!allow_internal_access).then_some(other_package.as_str()),
+				)
+			};
 
 			// Compare the access type with what's allowed
 			match var.access {
@@ -6797,10 +8622,9 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 								message: "defined here".to_string(),
 								span: lookup_info.span,
 							}],
-							hints: vec![format!(
-								"the definition of \"{property}\" needs a broader access modifier like \"pub\" or \"protected\" to be used outside of \"{class}\"",
-							)],
+							hints: vec![required_access_hint()],
 							severity: DiagnosticSeverity::Error,
+							code: None,
 						});
 					}
 				}
@@ -6813,15 +8637,13 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 								message: "defined here".to_string(),
 								span: lookup_info.span,
 							}],
-							hints: vec![format!(
-								"the definition of \"{property}\" needs a broader access modifier like \"pub\" to be used outside of \"{class}\"",
-							)],
+							hints: vec![required_access_hint()],
 							severity: DiagnosticSeverity::Error,
+							code: None,
 						});
 					}
 				}
 				AccessModifier::Internal => {
-					let other_package = &lookup_info.env.source_package;
 					if !allow_internal_access {
 						report_diagnostic(Diagnostic {
 							message: format!("Cannot access internal member \"{property}\" of \"{class}\""),
@@ -6830,10 +8652,9 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 								message: "defined here".to_string(),
 								span: lookup_info.span,
 							}],
-							hints: vec![format!(
-								"the definition of \"{property}\" needs a broader access modifier like \"pub\" to be used outside of \"{other_package}\"",
-							)],
+							hints: vec![required_access_hint()],
 							severity: DiagnosticSeverity::Error,
+							code: None,
 						});
 					}
 				}
@@ -6855,7 +8676,7 @@ See https://www.winglang.io/docs/concepts/application-tree for more information.
 				var.clone()
 			}
 		} else {
-			self.type_error(lookup_result_to_type_error(lookup_res, property));
+			self.type_error(lookup_result_to_type_error(lookup_res, property, env));
 			self.make_error_variable_info()
 		}
 	}
@@ -7023,6 +8844,33 @@ fn combine_phases(phase1: Phase, phase2: Phase) -> Phase {
 	}
 }
 
+/// Secondary constructors are named `of`, or `of` followed by a capitalized suffix (e.g. `ofJson`).
+pub(crate) fn is_secondary_constructor_name(name: &str) -> bool {
+	name.strip_prefix("of").map_or(false, |rest| {
+		rest.chars().next().map_or(true, |c| c.is_ascii_uppercase())
+	})
+}
+
+/// Returns the value of a string or numeric literal expression, as it would appear in a literal type.
+fn literal_type_value(expr: &Expr) -> Option<LiteralTypeValue> {
+	match &expr.kind {
+		ExprKind::Literal(Literal::String(s)) => Some(LiteralTypeValue::String(s.clone())),
+		_ => constant_number(expr).map(LiteralTypeValue::Number),
+	}
+}
+
+/// Evaluates a numeric literal, optionally negated (e.g. `3` or `-1`), to its value.
+fn constant_number(expr: &Expr) -> Option<f64> {
+	match &expr.kind {
+		ExprKind::Literal(Literal::Number(n)) => Some(*n),
+		ExprKind::Unary {
+			op: UnaryOperator::Minus,
+			exp,
+		} => constant_number(exp).map(|n| -n),
+		_ => None,
+	}
+}
+
 fn add_parent_members_to_struct_env(
 	extends_types: &Vec<TypeRef>,
 	name: &Symbol,
@@ -7041,6 +8889,7 @@ fn add_parent_members_to_struct_env(
 				span: name.span.clone(),
 				annotations: vec![],
 				hints: vec![],
+				code: None,
 			});
 		};
 		// Add each member of current parent to the struct's environment (if it wasn't already added by a previous parent)
@@ -7065,6 +8914,7 @@ fn add_parent_members_to_struct_env(
 						),
 						annotations: vec![],
 						hints: vec![],
+						code: None,
 					});
 				}
 			} else {
@@ -7111,6 +8961,7 @@ fn add_parent_members_to_iface_env(
 				span: name.span.clone(),
 				annotations: vec![],
 				hints: vec![],
+				code: None,
 			});
 		};
 		// Add each member of current parent to the interface's environment (if it wasn't already added by a previous parent)
@@ -7132,6 +8983,7 @@ fn add_parent_members_to_iface_env(
 						span: name.span.clone(),
 						annotations: vec![],
 						hints: vec![],
+						code: None,
 					});
 				}
 			} else {
@@ -7159,26 +9011,29 @@ fn add_parent_members_to_iface_env(
 	Ok(())
 }
 
-fn lookup_result_to_type_error<T>(lookup_result: LookupResult, looked_up_object: &T) -> TypeError
+fn lookup_result_to_type_error<T>(lookup_result: LookupResult, looked_up_object: &T, env: &SymbolEnv) -> TypeError
 where
 	T: Spanned + Display,
 {
 	match lookup_result {
 		LookupResult::NotFound(s, maybe_t) => {
-			let message = if let Some(env_type) = maybe_t {
-				format!("Member \"{s}\" does not exist in \"{env_type}\"")
+			let (message, code) = if let Some(env_type) = maybe_t {
+				(format!("Member \"{s}\" does not exist in \"{env_type}\""), None)
 			} else {
-				format!("Unknown symbol \"{s}\"")
+				(UNKNOWN_SYMBOL.format(&[("name", &s)]), Some(UNKNOWN_SYMBOL.code))
 			};
 			let mut hints = vec![];
 			if s.name == CONSTRUCT_NODE_PROPERTY {
 				hints.push("use nodeof(x) to access the tree node on a preflight class".to_string());
+			} else if let Some(type_env) = maybe_t.as_ref().and_then(|t| t.as_env()) {
+				hints.extend(did_you_mean_hint(&similar_symbol_names(type_env, &s.name, false)));
 			}
 			TypeError {
 				message,
 				span: s.span(),
 				annotations: vec![],
 				hints,
+				code,
 			}
 		}
 		LookupResult::NotPublic(kind, lookup_info) => {
@@ -7211,14 +9066,16 @@ where
 					span: lookup_info.span,
 				}],
 				hints: {
-					let mut hints = vec![];
-					if access == AccessModifier::Internal {
-						hints.push(format!(
-						"the definition of \"{looked_up_object}\" needs a broader access modifier like \"pub\" to be used outside of \"{source_package}\"",
-					));
-					}
-					hints
+					let from_same_package = *source_package == env.source_package;
+					let required = minimal_access_modifier(access, false, from_same_package);
+					vec![access_modifier_hint(
+						&looked_up_object.to_string(),
+						access,
+						required,
+						(!from_same_package).then_some(source_package.as_str()),
+					)]
 				},
+				code: None,
 			}
 		}
 		LookupResult::MultipleFound => TypeError {
@@ -7226,6 +9083,7 @@ where
 			span: looked_up_object.span(),
 			annotations: vec![],
 			hints: vec![],
+			code: None,
 		},
 		LookupResult::DefinedLater(span) => TypeError {
 			message: format!("Symbol \"{looked_up_object}\" used before being defined"),
@@ -7235,12 +9093,14 @@ where
 				span,
 			}],
 			hints: vec![],
+			code: None,
 		},
 		LookupResult::ExpectedNamespace(ns_name) => TypeError {
 			message: format!("Expected \"{ns_name}\" in \"{looked_up_object}\" to be a namespace"),
 			span: ns_name.span(),
 			annotations: vec![],
 			hints: vec![],
+			code: None,
 		},
 		LookupResult::Found(..) => panic!("Expected a lookup error, but found a successful lookup"),
 	}
@@ -7276,11 +9136,57 @@ pub fn resolve_user_defined_type_ref<'a>(
 				span: symb.span.clone(),
 				annotations: vec![],
 				hints: vec![],
+				code: None,
 			})
 		}
 	} else {
-		Err(lookup_result_to_type_error(lookup_result, user_defined_type))
+		let suggestions = if let LookupResult::NotFound(missing, None) = &lookup_result {
+			similar_type_names(&nested_name, missing, env, statement_idx)
+		} else {
+			vec![]
+		};
+		let mut error = lookup_result_to_type_error(lookup_result, user_defined_type, env);
+		error.hints.extend(did_you_mean_hint(&suggestions));
+		Err(error)
+	}
+}
+
+/// Names close to a part of a user defined type that wasn't found: symbols in scope if it's the root of the type,
+/// otherwise public symbols of the namespace it was looked up in
+fn similar_type_names(nested_name: &[&Symbol], missing: &Symbol, env: &SymbolEnv, statement_idx: usize) -> Vec<String> {
+	let Some(position) = nested_name.iter().position(|part| part.span == missing.span) else {
+		return vec![];
+	};
+	if position == 0 {
+		return similar_symbol_names(env, &missing.name, true);
 	}
+	let LookupResult::Found(SymbolKind::Namespace(ns), _) =
+		env.lookup_nested(&nested_name[..position], Some(statement_idx))
+	else {
+		return vec![];
+	};
+	let names = ns
+		.envs
+		.iter()
+		.flat_map(|ns_env| {
+			ns_env
+				.iter(false)
+				.filter(|(_, _, info)| info.access != AccessModifier::Private)
+				.map(|(name, ..)| name)
+				.collect_vec()
+		})
+		.collect_vec();
+	similar_names(&missing.name, names.iter().map(String::as_str))
+}
+
+/// Names of the symbols defined in `env` (or its parents) that are close to `name`, see `similar_names`
+fn similar_symbol_names(env: &SymbolEnv, name: &str, include_private: bool) -> Vec<String> {
+	let names = env
+		.iter(true)
+		.filter(|(_, _, info)| include_private || info.access != AccessModifier::Private)
+		.map(|(name, ..)| name)
+		.collect_vec();
+	similar_names(name, names.iter().map(String::as_str))
 }
 
 pub fn get_udt_definition_phase(user_defined_type: &UserDefinedType, env: &SymbolEnv) -> Option<Phase> {
@@ -7305,6 +9211,58 @@ pub fn is_udt_struct_type(udt: &UserDefinedType, env: &SymbolEnv) -> bool {
 }
 
 pub fn resolve_super_method(method: &Symbol, env: &SymbolEnv, types: &Types) -> Result<(TypeRef, Phase), TypeError> {
+	let parent_type = resolve_super_class(method, env, types, "call super method")?;
+	if let Some(method_info) = parent_type.as_class().unwrap().get_method(method) {
+		Ok((method_info.type_, method_info.phase))
+	} else {
+		Err(TypeError {
+			message: format!(
+				"super class \"{}\" does not have a method named \"{}\"",
+				parent_type, method
+			),
+			span: method.span.clone(),
+			annotations: vec![],
+			hints: vec![],
+			code: None,
+		})
+	}
+}
+
+/// Resolves a member of the parent class accessed with `super.<member>`, which can be a method or a field.
+/// Returns the parent class and the member.
+pub fn resolve_super_member(
+	member: &Symbol,
+	env: &SymbolEnv,
+	types: &Types,
+) -> Result<(TypeRef, VariableInfo), TypeError> {
+	let parent_type = resolve_super_class(member, env, types, "access super member")?;
+	match parent_type.as_class().unwrap().get_env().lookup_ext(member, None) {
+		LookupResult::Found(SymbolKind::Variable(variable), _) => Ok((parent_type, variable.clone())),
+		_ => Err(TypeError {
+			message: format!(
+				"super class \"{}\" does not have a member named \"{}\"",
+				parent_type, member
+			),
+			span: member.span.clone(),
+			annotations: vec![],
+			hints: vec![],
+			code: None,
+		}),
+	}
+}
+
+/// Whether a member accessed with `super.<member>` is a method of the parent class (as opposed to a field, which the
+/// class instance holds itself, so in JS it can only be accessed through `this`).
+pub fn is_super_method(member: &VariableInfo) -> bool {
+	member
+		.type_
+		.as_function_sig()
+		.is_some_and(|sig| sig.this_type.is_some())
+}
+
+/// Returns the parent class of the class in which `super` is used. `usage` describes what `super` is used for in
+/// error messages.
+fn resolve_super_class(member: &Symbol, env: &SymbolEnv, types: &Types, usage: &str) -> Result<TypeRef, TypeError> {
 	let this_type = env.lookup(&Symbol::global("this"), None);
 	if let Some(SymbolKind::Variable(VariableInfo {
 		type_,
@@ -7317,9 +9275,10 @@ pub fn resolve_super_method(method: &Symbol, env: &SymbolEnv, types: &Types) ->
 				message:
 					"`super` calls inside inflight closures not supported yet, see: https://github.com/winglang/wing/issues/3474"
 						.to_string(),
-				span: method.span.clone(),
+				span: member.span.clone(),
 				annotations: vec![],
 				hints: vec![],
+				code: None,
 			});
 		}
 		// Get the parent type of "this" (if it's a preflight class that's directly derived from `std.Resource` it's an implicit derive so we'll treat it as if there's no parent)
@@ -7328,39 +9287,24 @@ pub fn resolve_super_method(method: &Symbol, env: &SymbolEnv, types: &Types) ->
 			.expect("Expected \"this\" to be a class")
 			.parent
 			.filter(|t| !(t.is_preflight_class() && t.is_same_type_as(&types.resource_base_type())));
-		if let Some(parent_type) = parent_type {
-			if let Some(method_info) = parent_type.as_class().unwrap().get_method(method) {
-				Ok((method_info.type_, method_info.phase))
-			} else {
-				Err(TypeError {
-					message: format!(
-						"super class \"{}\" does not have a method named \"{}\"",
-						parent_type, method
-					),
-					span: method.span.clone(),
-					annotations: vec![],
-					hints: vec![],
-				})
-			}
-		} else {
-			Err(TypeError {
-				message: format!("Cannot call super method because class {} has no parent", type_),
-				span: method.span.clone(),
-				annotations: vec![],
-				hints: vec![],
-			})
-		}
+		parent_type.ok_or_else(|| TypeError {
+			message: format!("Cannot {usage} because class {} has no parent", type_),
+			span: member.span.clone(),
+			annotations: vec![],
+			hints: vec![],
+			code: None,
+		})
 	} else {
 		Err(TypeError {
-			message: (if matches!(env.kind, SymbolEnvKind::Function { .. }) {
-				"Cannot call super method inside of a static method"
+			message: if matches!(env.kind, SymbolEnvKind::Function { .. }) {
+				format!("Cannot {usage} inside of a static method")
 			} else {
-				"\"super\" can only be used inside of classes"
-			})
-			.to_string(),
-			span: method.span.clone(),
+				"\"super\" can only be used inside of classes".to_string()
+			},
+			span: member.span.clone(),
 			annotations: vec![],
 			hints: vec![],
+			code: None,
 		})
 	}
 }
@@ -7500,6 +9444,32 @@ enum ResolveReferenceResult {
 mod tests {
 	use super::*;
 
+	#[test]
+	fn copies_of_a_library_version_share_types() {
+		let project_dir = tempfile::tempdir().unwrap();
+		let project_dir = Utf8Path::from_path(project_dir.path()).unwrap();
+		let write_library = |dir: &str, version: &str| {
+			let dir = project_dir.join(dir);
+			std::fs::create_dir_all(&dir).unwrap();
+			std::fs::write(
+				dir.join("package.json"),
+				format!(r#"{{ "name": "shared", "version": "{version}", "wing": true }}"#),
+			)
+			.unwrap();
+			dir
+		};
+		let first = write_library("node_modules/shared", "1.0.0");
+		let copy = write_library("node_modules/lib/node_modules/shared", "1.0.0");
+		let other_version = write_library("node_modules/other/node_modules/shared", "2.0.0");
+
+		let mut types = Types::new();
+		assert_eq!(types.unify_library_dir(&first), first);
+		assert_eq!(types.unify_library_dir(&copy), first);
+		assert_eq!(types.unify_library_dir(&other_version), other_version);
+		assert_eq!(types.library_dir(&copy), first);
+		assert_eq!(types.library_dir(&other_version), other_version);
+	}
+
 	#[test]
 	fn phase_subtyping() {
 		// subtyping is reflexive
@@ -7533,6 +9503,29 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn unresolved_types_within_types() {
+		let string = UnsafeRef::<Type>(&Type::String as *const Type);
+		let unresolved = UnsafeRef::<Type>(&Type::Unresolved as *const Type);
+		let array_of_unresolved = UnsafeRef::<Type>(&Type::Array(unresolved) as *const Type);
+		let function = make_function(
+			vec![FunctionParameter {
+				name: "x".into(),
+				typeref: array_of_unresolved,
+				docs: Docs::default(),
+				variadic: false,
+			}],
+			string,
+			Phase::Inflight,
+		);
+		let function = UnsafeRef::<Type>(&function as *const Type);
+
+		assert!(unresolved.contains_unresolved());
+		assert!(array_of_unresolved.contains_unresolved());
+		assert!(function.contains_unresolved());
+		assert!(!string.contains_unresolved());
+	}
+
 	#[test]
 	fn optional_subtyping() {
 		let string = UnsafeRef::<Type>(&Type::String as *const Type);
@@ -7648,9 +9641,257 @@ mod tests {
 		assert!(!str_fn.is_subtype_of(&opt_str_fn));
 	}
 
+	#[test]
+	fn function_subtyping_return_type_covariance() {
+		let string = UnsafeRef::<Type>(&Type::String);
+		let opt_string_type = Type::Optional(string);
+		let opt_string = UnsafeRef::<Type>(&opt_string_type);
+		let str_array_type = Type::Array(string);
+		let str_array = UnsafeRef::<Type>(&str_array_type);
+		let opt_str_array_type = Type::Array(opt_string);
+		let opt_str_array = UnsafeRef::<Type>(&opt_str_array_type);
+		let returns_str_array = make_function(vec![], str_array, Phase::Inflight);
+		let returns_opt_str_array = make_function(vec![], opt_str_array, Phase::Inflight);
+
+		// a function returning "Array<str>" can be used where a function returning "Array<str?>" is expected
+		assert!(returns_str_array.is_subtype_of(&returns_opt_str_array));
+		assert!(!returns_opt_str_array.is_subtype_of(&returns_str_array));
+	}
+
+	#[test]
+	fn immutable_collection_subtyping_covariance() {
+		let string = UnsafeRef::<Type>(&Type::String);
+		let opt_string_type = Type::Optional(string);
+		let opt_string = UnsafeRef::<Type>(&opt_string_type);
+
+		// Array<str> can be used anywhere an Array<str?> is expected since it can't be modified
+		for (collection, opt_collection) in [
+			(Type::Array(string), Type::Array(opt_string)),
+			(Type::Map(string), Type::Map(opt_string)),
+			(Type::Set(string), Type::Set(opt_string)),
+		] {
+			assert!(collection.is_subtype_of(&opt_collection));
+			assert!(!opt_collection.is_subtype_of(&collection));
+		}
+	}
+
+	#[test]
+	fn never_and_unknown_subtyping() {
+		let string = UnsafeRef::<Type>(&Type::String);
+		let never = Type::Never;
+		let unknown = Type::Unknown;
+
+		// never is assignable to anything, and anything is assignable to unknown
+		for t in [Type::String, Type::Number, Type::Void, Type::Array(string), Type::Nil] {
+			assert!(never.is_subtype_of(&t));
+			assert!(!t.is_subtype_of(&never));
+			assert!(t.is_subtype_of(&unknown));
+			assert!(!unknown.is_subtype_of(&t));
+		}
+		assert!(never.is_subtype_of(&unknown));
+		assert!(!unknown.is_subtype_of(&never));
+	}
+
+	#[test]
+	fn literal_type_subtyping() {
+		let get = LiteralTypeValue::String("\"GET\"".to_string());
+		let post = LiteralTypeValue::String("\"POST\"".to_string());
+		let get_type = Type::Literal(vec![get.clone()]);
+		let get_or_post = Type::Literal(vec![get.clone(), post]);
+		let mixed = Type::Literal(vec![get, LiteralTypeValue::Number(200.0)]);
+
+		assert!(get_type.is_subtype_of(&get_or_post));
+		assert!(!get_or_post.is_subtype_of(&get_type));
+
+		// a literal type is a subtype of the primitive type of all its values
+		assert!(get_or_post.is_subtype_of(&Type::String));
+		assert!(!get_or_post.is_subtype_of(&Type::Number));
+		assert!(!Type::String.is_subtype_of(&get_or_post));
+		assert!(!mixed.is_subtype_of(&Type::String));
+		assert!(!mixed.is_subtype_of(&Type::Number));
+	}
+
+	#[test]
+	fn least_upper_bound() {
+		let mut types = Types::new();
+		let (num, string, nil) = (types.number(), types.string(), types.nil());
+		let opt_num = types.make_option(num);
+		let arr_num = types.add_type(Type::Array(num));
+		let mut_arr_num = types.add_type(Type::MutArray(num));
+		let arr_str = types.add_type(Type::Array(string));
+
+		// a type and its subtype, or a type and nil
+		assert!(types.least_upper_bound(num, opt_num).unwrap().is_same_type_as(&opt_num));
+		assert!(types.least_upper_bound(nil, num).unwrap().is_same_type_as(&opt_num));
+
+		// immutable collections of compatible elements
+		assert!(types
+			.least_upper_bound(mut_arr_num, arr_num)
+			.unwrap()
+			.is_same_type_as(&arr_num));
+		assert!(types.least_upper_bound(arr_num, arr_str).is_none());
+
+		// literal types are merged, or widened to their primitive type
+		let get = types.add_type(Type::Literal(vec![LiteralTypeValue::String("\"GET\"".to_string())]));
+		let post = types.add_type(Type::Literal(vec![LiteralTypeValue::String("\"POST\"".to_string())]));
+		let get_or_post = types.least_upper_bound(get, post).unwrap();
+		assert!(get.is_subtype_of(&get_or_post) && post.is_subtype_of(&get_or_post));
+		assert!(!get_or_post.is_subtype_of(&get));
+		assert!(types.least_upper_bound(get, string).unwrap().is_same_type_as(&string));
+		assert!(types.least_upper_bound(get, num).is_none());
+	}
+
+	#[test]
+	fn collection_types_are_interned() {
+		let mut types = Types::new();
+		let (num, string) = (types.number(), types.string());
+		let arr_str = types.add_type(Type::Array(string));
+
+		assert_eq!(types.add_type(Type::Array(string)).0, arr_str.0);
+		assert_eq!(types.make_option(arr_str).0, types.make_option(arr_str).0);
+		assert_ne!(types.add_type(Type::MutArray(string)).0, arr_str.0);
+		assert_ne!(types.add_type(Type::Array(num)).0, arr_str.0);
+	}
+
+	#[test]
+	fn mutable_collection_subtyping_invariance() {
+		let string = UnsafeRef::<Type>(&Type::String);
+		let opt_string_type = Type::Optional(string);
+		let opt_string = UnsafeRef::<Type>(&opt_string_type);
+
+		// MutArray<str> can't be used where a MutArray<str?> is expected, otherwise nil could be pushed into it
+		for (collection, opt_collection, same_collection) in [
+			(
+				Type::MutArray(string),
+				Type::MutArray(opt_string),
+				Type::MutArray(string),
+			),
+			(Type::MutMap(string), Type::MutMap(opt_string), Type::MutMap(string)),
+			(Type::MutSet(string), Type::MutSet(opt_string), Type::MutSet(string)),
+		] {
+			assert!(!collection.is_subtype_of(&opt_collection));
+			assert!(!opt_collection.is_subtype_of(&collection));
+			assert!(collection.is_subtype_of(&same_collection));
+		}
+	}
+
 	#[test]
 	fn any_is_optional() {
 		let any = UnsafeRef::<Type>(&Type::Anything);
 		assert!(any.is_option());
 	}
+
+	#[test]
+	fn resolved_inferences_are_interned() {
+		let mut types = Types::new();
+		let inference = types.make_inference();
+		let id = types.as_inference(inference).unwrap();
+		let mut array = types.add_type(Type::Array(inference));
+		let span = WingSpan::default();
+		let string = types.string();
+		types.update_inferred_type(id, string, &span);
+
+		let mut visitor = InferenceVisitor {
+			types: &mut types,
+			expected_type: None,
+			found_inference: false,
+			span: &span,
+		};
+		visitor.visit_typeref_mut(&mut array);
+
+		// `Array<str>` is the same type whether it was resolved from an inference or built directly
+		let str_array = types.add_type(Type::Array(string));
+		assert!(std::ptr::eq(&*array, &*str_array));
+		// the interned `Array<inferred>` is left as it was
+		let inferred_array = types.add_type(Type::Array(inference));
+		assert!(matches!(*inferred_array, Type::Array(element) if element.is_inferred()));
+	}
+
+	/// The messages of the annotations of each error
+	fn annotations(errors: &[Diagnostic]) -> Vec<Vec<&str>> {
+		errors
+			.iter()
+			.map(|error| error.annotations.iter().map(|a| a.message.as_str()).collect())
+			.collect()
+	}
+
+	#[test]
+	fn errors_point_at_related_code() {
+		let errors = crate::test_utils::compile_source_with_errors(
+			r#"
+let n: num = "hello";
+let f = (): str => {
+  return 1;
+};
+let x = 1;
+let x = 2;
+let y = "outer";
+() => {
+  log(y);
+  let y = "inner";
+};
+"#,
+			&crate::CompileOptions::default(),
+		);
+		assert_eq!(
+			annotations(&errors),
+			vec![
+				vec!["expected due to this type annotation"],
+				vec!["expected due to this return type"],
+				vec!["previous definition"],
+				vec!["shadowed by this definition"],
+			]
+		);
+		// the annotations point at the type annotation, the return type, the first `x` and the second `y`
+		let lines = errors
+			.iter()
+			.map(|error| error.annotations[0].span.start.line)
+			.collect::<Vec<_>>();
+		assert_eq!(lines, vec![1, 2, 5, 10]);
+	}
+
+	#[test]
+	fn phase_errors_point_at_where_phases_come_from() {
+		let errors = crate::test_utils::compile_source_with_errors(
+			r#"
+class Foo {
+  pub inflight do() {}
+  pub prepare() {}
+}
+class Bar {
+  foo: Foo;
+  new() {
+    this.foo = new Foo();
+  }
+  pub setup() {
+    this.foo.do();
+  }
+  pub inflight run() {
+    this.foo.prepare();
+  }
+}
+"#,
+			&crate::CompileOptions::default(),
+		);
+		assert_eq!(
+			annotations(&errors),
+			vec![
+				vec![
+					"this is an inflight function",
+					"\"do\" is declared inflight here",
+					"\"setup\" is preflight"
+				],
+				vec![
+					"this is a preflight function",
+					"\"prepare\" is declared preflight here",
+					"\"run\" is inflight"
+				],
+			]
+		);
+		assert_eq!(
+			errors[0].hints,
+			vec!["inflight functions can only be called from inflight code, try calling it from an inflight closure (`inflight () => { ... }`)"]
+		);
+		assert!(errors[1].hints[0].contains("or mark \"prepare\" inflight"));
+	}
 }
@@ -1,12 +1,19 @@
+use std::collections::HashSet;
+
 use crate::{
-	ast::{AssignmentKind, FunctionDefinition, Reference, Stmt, StmtKind, Symbol},
+	ast::{
+		AssignmentKind, ElseIfs, Expr, ExprKind, FunctionDefinition, Literal, Reference, Scope, Stmt, StmtKind, Symbol,
+	},
 	visit::{self, Visit},
 };
 
-/// Determine a list of all fields that are initialized in a class constructor.
+/// Determine a list of all fields that are initialized in a class constructor, and which of them are
+/// initialized on every path through it.
 #[derive(Default)]
 pub struct VisitClassInit {
 	pub fields: Vec<Symbol>,
+	/// Fields that are assigned on every path through the constructor that doesn't throw
+	pub definitely_initialized: HashSet<String>,
 }
 
 impl VisitClassInit {
@@ -14,6 +21,162 @@ impl VisitClassInit {
 		for stmt in statements {
 			self.visit_stmt(stmt);
 		}
+
+		// Each `return` leaves the constructor with the fields assigned up to that point, and so does reaching its end
+		let mut exits = vec![];
+		if let Some(assigned) = definitely_assigned(statements, HashSet::new(), &mut exits) {
+			exits.push(assigned);
+		}
+		self.definitely_initialized = match exits.split_first() {
+			Some((first, rest)) => rest
+				.iter()
+				.fold(first.clone(), |acc, exit| acc.intersection(exit).cloned().collect()),
+			// The constructor always throws, so no field is ever observed uninitialized
+			None => self.fields.iter().map(|f| f.name.clone()).collect(),
+		};
+	}
+}
+
+/// Returns the fields that are assigned after running the given statements, starting with the `assigned` fields,
+/// or None if the statements never complete normally (they always return, throw, break or continue).
+/// The fields assigned at every `return` along the way are added to `exits`.
+fn definitely_assigned(
+	statements: &[Stmt],
+	mut assigned: HashSet<String>,
+	exits: &mut Vec<HashSet<String>>,
+) -> Option<HashSet<String>> {
+	for stmt in statements {
+		match &stmt.kind {
+			StmtKind::Assignment {
+				kind: AssignmentKind::Assign,
+				variable: Reference::InstanceMember { property, .. },
+				..
+			} => {
+				assigned.insert(property.name.clone());
+			}
+			StmtKind::Return(_) => {
+				exits.push(assigned);
+				return None;
+			}
+			StmtKind::Throw(_) | StmtKind::Break | StmtKind::Continue => return None,
+			StmtKind::Scope(scope) => {
+				assigned = definitely_assigned(&scope.statements, assigned, exits)?;
+			}
+			StmtKind::ExplicitLift(lift) => {
+				assigned = definitely_assigned(&lift.statements.statements, assigned, exits)?;
+			}
+			StmtKind::If {
+				condition,
+				statements,
+				else_if_statements,
+				else_statements,
+			} => {
+				let mut branches = vec![];
+				let mut else_reachable = true;
+				let conditions = std::iter::once((condition, statements))
+					.chain(else_if_statements.iter().map(|b| (&b.condition, &b.statements)));
+				for (condition, statements) in conditions {
+					// Branches with a constant condition are either always taken or never taken
+					match constant_bool(condition) {
+						Some(false) => continue,
+						always_taken => {
+							branches.push(definitely_assigned(&statements.statements, assigned.clone(), exits));
+							if always_taken == Some(true) {
+								else_reachable = false;
+								break;
+							}
+						}
+					}
+				}
+				if else_reachable {
+					branches.push(else_branch(else_statements, &assigned, exits));
+				}
+				assigned = branches.into_iter().fold(None, meet)?;
+			}
+			StmtKind::IfLet(if_let) => {
+				let mut branches = std::iter::once(&if_let.statements)
+					.chain(if_let.else_if_statements.iter().map(|b| match b {
+						ElseIfs::ElseIfBlock(b) => &b.statements,
+						ElseIfs::ElseIfLetBlock(b) => &b.statements,
+					}))
+					.map(|s| definitely_assigned(&s.statements, assigned.clone(), exits))
+					.collect::<Vec<_>>();
+				branches.push(else_branch(&if_let.else_statements, &assigned, exits));
+				assigned = branches.into_iter().fold(None, meet)?;
+			}
+			// Loop bodies might not run at all, but a `return` inside them still leaves the constructor
+			StmtKind::ForLoop { statements, .. }
+			| StmtKind::While { statements, .. }
+			| StmtKind::WhileLet { statements, .. } => {
+				definitely_assigned(&statements.statements, assigned.clone(), exits);
+			}
+			StmtKind::TryCatch {
+				try_statements,
+				catch_block,
+				finally_statements,
+			} => {
+				let after_try = definitely_assigned(&try_statements.statements, assigned.clone(), exits);
+				// The try block may throw before any of its assignments, so the catch block starts from scratch
+				let after_catch = match catch_block {
+					Some(catch_block) => meet(
+						after_try,
+						definitely_assigned(&catch_block.statements.statements, assigned.clone(), exits),
+					),
+					None => after_try,
+				};
+				match (after_catch, finally_statements) {
+					(Some(after_catch), Some(finally)) => {
+						assigned = definitely_assigned(&finally.statements, after_catch, exits)?;
+					}
+					(Some(after_catch), None) => assigned = after_catch,
+					(None, Some(finally)) => {
+						definitely_assigned(&finally.statements, assigned.clone(), exits);
+						return None;
+					}
+					(None, None) => return None,
+				}
+			}
+			// Statements that can't assign fields or leave the constructor. Listed explicitly so new statements
+			// that can are handled above.
+			StmtKind::Bring { .. }
+			| StmtKind::SuperConstructor { .. }
+			| StmtKind::Let { .. }
+			| StmtKind::Expression(_)
+			| StmtKind::Assignment { .. }
+			| StmtKind::Class(_)
+			| StmtKind::Interface(_)
+			| StmtKind::Struct(_)
+			| StmtKind::Enum(_) => {}
+		}
+	}
+	Some(assigned)
+}
+
+/// The outcome of the `else` block of an `if` statement, without one the fields assigned so far are unchanged
+fn else_branch(
+	else_statements: &Option<Scope>,
+	assigned: &HashSet<String>,
+	exits: &mut Vec<HashSet<String>>,
+) -> Option<HashSet<String>> {
+	match else_statements {
+		Some(else_statements) => definitely_assigned(&else_statements.statements, assigned.clone(), exits),
+		None => Some(assigned.clone()),
+	}
+}
+
+fn constant_bool(condition: &Expr) -> Option<bool> {
+	match condition.kind {
+		ExprKind::Literal(Literal::Boolean(b)) => Some(b),
+		_ => None,
+	}
+}
+
+/// The fields assigned after either of two paths, where None means a path doesn't complete normally
+fn meet(a: Option<HashSet<String>>, b: Option<HashSet<String>>) -> Option<HashSet<String>> {
+	match (a, b) {
+		(Some(a), Some(b)) => Some(a.intersection(&b).cloned().collect()),
+		(Some(a), None) | (None, Some(a)) => Some(a),
+		(None, None) => None,
 	}
 }
 
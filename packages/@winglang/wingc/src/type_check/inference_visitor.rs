@@ -31,8 +31,25 @@ impl<'a> crate::visit_types::VisitTypeMut<'_> for InferenceVisitor<'a> {
 			if !node.is_inferred() {
 				self.visit_typeref_mut(node)
 			}
-		} else {
-			crate::visit_types::visit_typeref_mut(self, node);
+			return;
+		}
+
+		// Optional and collection types are interned (see `Types::add_type`), so they can't be modified in place: the
+		// resolved type is interned instead
+		let (inner, wrap): (TypeRef, fn(TypeRef) -> Type) = match **node {
+			Type::Optional(t) => (t, Type::Optional),
+			Type::Array(t) => (t, Type::Array),
+			Type::MutArray(t) => (t, Type::MutArray),
+			Type::Map(t) => (t, Type::Map),
+			Type::MutMap(t) => (t, Type::MutMap),
+			Type::Set(t) => (t, Type::Set),
+			Type::MutSet(t) => (t, Type::MutSet),
+			_ => return crate::visit_types::visit_typeref_mut(self, node),
+		};
+		let mut resolved = inner;
+		self.visit_typeref_mut(&mut resolved);
+		if !std::ptr::eq(&*resolved, &*inner) {
+			*node = self.types.add_type(wrap(resolved));
 		}
 	}
 
@@ -113,6 +130,9 @@ impl<'a> crate::visit_types::VisitType<'_> for InferenceVisitor<'a> {
 				| Type::Regex
 				| Type::Boolean
 				| Type::Void
+				| Type::Never
+				| Type::Unknown
+				| Type::Literal(_)
 				| Type::Json(_)
 				| Type::MutJson
 				| Type::Nil
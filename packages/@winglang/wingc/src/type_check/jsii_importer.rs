@@ -281,6 +281,8 @@ impl<'a> JsiiImporter<'a> {
 			fqn: enum_fqn.to_string(),
 			docs: Docs::from(&jsii_enum.docs),
 			values,
+			backing_values: IndexMap::new(),
+			is_flags: false,
 		}));
 
 		self.register_jsii_type(&enum_fqn, &enum_symbol, enum_type_ref)
@@ -1176,6 +1178,8 @@ impl From<&Option<jsii::Docs>> for Docs {
 			example: docs.example.clone(),
 			see: docs.see.clone(),
 			returns: docs.returns.clone(),
+			throws: None,
+			params: vec![],
 			stability: docs.stability.clone(),
 			subclassable: docs.subclassable,
 		}
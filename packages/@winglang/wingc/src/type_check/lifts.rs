@@ -17,7 +17,8 @@ pub struct Lifts {
 	/// Used for preflight setup of inflight captures.
 	pub captures: BTreeMap<String, Capture>,
 
-	/// Map between liftable AST element and a lift token (used for inflight jsification of captures)
+	/// Map between liftable AST element and a lift token (used for inflight jsification of captures), or the code of
+	/// its value if it's inlined
 	pub token_for_liftable: HashMap<Liftable, String>,
 }
 
@@ -31,7 +32,8 @@ pub enum Liftable {
 /// A record that describes a single lift from a method.
 #[derive(Debug)]
 pub struct LiftQualification {
-	/// The operations that qualify the lift (the property names)
+	/// The operations that qualify the lift (the property names). Each operation appears once, in the order it was
+	/// first used, no matter how many times the method uses it.
 	pub ops: IndexSet<String>,
 }
 
@@ -58,18 +60,19 @@ impl Lifts {
 		format!("${}", replace_non_alphanumeric(code))
 	}
 
-	/// Adds a lift for an expression.
-	pub fn lift(&mut self, method: Symbol, qualification: Option<String>, code: &str) {
-		self.add_lift(method.to_string(), code, qualification.clone());
+	/// Adds a lift for an expression, qualified with the operations the method performs on it. Repeated lifts of the
+	/// same preflight code by a method are merged into a single lift.
+	pub fn lift(&mut self, method: Symbol, ops: &[&str], code: &str) {
+		self.add_lift(method.to_string(), code, ops);
 
 		// Add a lift to the inflight initializer to signify this class requires access to that preflight object.
 		// "this" is a special case since it's already in scope and doesn't need to be lifted.
 		if code != "this" {
-			self.add_lift(CLASS_INFLIGHT_INIT_NAME.to_string(), code, None);
+			self.add_lift(CLASS_INFLIGHT_INIT_NAME.to_string(), code, &[]);
 		}
 	}
 
-	fn add_lift(&mut self, method: String, code: &str, qualification: Option<String>) {
+	fn add_lift(&mut self, method: String, code: &str, ops: &[&str]) {
 		let lift = self
 			.lifts_qualifications
 			.entry(method)
@@ -77,9 +80,7 @@ impl Lifts {
 			.entry(code.to_string())
 			.or_insert(LiftQualification { ops: IndexSet::new() });
 
-		if let Some(op) = qualification {
-			lift.ops.insert(op);
-		}
+		lift.ops.extend(ops.iter().map(|op| op.to_string()));
 	}
 
 	/// Returns the token for a liftable. Called by the jsifier when emitting inflight code.
@@ -124,6 +125,14 @@ impl Lifts {
 		});
 	}
 
+	/// Inlines the value of a liftable into inflight code instead of capturing it (used for preflight constants).
+	pub fn inline(&mut self, lifted_thing: &Liftable, code: &str) {
+		self
+			.token_for_liftable
+			.entry(lifted_thing.clone())
+			.or_insert(code.to_string());
+	}
+
 	/// List of all lifted fields in the class. (map from lift token to preflight code)
 	pub fn lifted_fields(&self) -> impl Iterator<Item = (String, String)> + '_ {
 		self
@@ -0,0 +1,213 @@
+use itertools::Itertools;
+
+use crate::{
+	ast::AccessModifier,
+	closure_transform::{CLOSURE_CLASS_PREFIX, PARENT_THIS_NAME},
+};
+
+/// Start of the hint listing the names a misspelled name is close to. The LSP offers to replace the misspelled name
+/// with each of them.
+pub const HINT_DID_YOU_MEAN: &str = "did you mean";
+
+/// Start of the hint suggesting a broader access modifier for a declaration that can't be accessed where it was. The
+/// LSP offers to change the declaration's access modifier to the suggested one.
+pub const HINT_DECLARE_AS: &str = "declare";
+
+/// Start of the hint listing placeholder initializers for the required fields missing from a struct literal. The LSP
+/// offers to add all of them to the literal.
+pub const HINT_ADD_MISSING_FIELDS: &str = "add the missing fields";
+
+/// At most this many close matches are suggested
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns the names among `candidates` that are close enough to `name` to be a likely typo of it, closest first.
+/// A name is close if it's at most a third of its length away (allowing one edit for names of 3 or more characters),
+/// counting swapped letters as a single edit and ignoring case. Very short names are only matched up to case, since
+/// one edit away from them is almost any other short name.
+pub fn similar_names<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+	let lowercase_name = name.to_lowercase();
+	let length = name.chars().count();
+	let max_distance = if length < 3 { 0 } else { (length / 3).max(1) };
+	candidates
+		.into_iter()
+		.filter(|candidate| *candidate != name && !is_compiler_generated(candidate))
+		.unique()
+		.filter_map(|candidate| {
+			let distance = strsim::damerau_levenshtein(&lowercase_name, &candidate.to_lowercase());
+			(distance <= max_distance).then_some((distance, candidate))
+		})
+		.sorted()
+		.take(MAX_SUGGESTIONS)
+		.map(|(_, candidate)| candidate.to_string())
+		.collect()
+}
+
+/// A hint suggesting the given names, if there are any (e.g. `did you mean "foo" or "fooBar"?`)
+pub fn did_you_mean_hint(suggestions: &[String]) -> Option<String> {
+	let (last, rest) = suggestions.split_last()?;
+	let quoted = |name: &String| format!("\"{name}\"");
+	if rest.is_empty() {
+		Some(format!("{HINT_DID_YOU_MEAN} {}?", quoted(last)))
+	} else {
+		Some(format!(
+			"{HINT_DID_YOU_MEAN} {} or {}?",
+			rest.iter().map(quoted).join(", "),
+			quoted(last)
+		))
+	}
+}
+
+/// Parses the names suggested by a hint made by `did_you_mean_hint`
+pub fn parse_did_you_mean_hint(hint: &str) -> Vec<String> {
+	let Some(names) = hint.strip_prefix(HINT_DID_YOU_MEAN) else {
+		return vec![];
+	};
+	names
+		.split('"')
+		.skip(1)
+		.step_by(2)
+		.map(|name| name.to_string())
+		.collect()
+}
+
+/// The narrowest access modifier, at least as broad as `current`, that allows accessing a declaration from a subclass
+/// of the class it's in (if `from_subclass`) and from the package it's in (if `from_same_package`). Protected and
+/// internal don't include each other, so a declaration that is already one of them can only be made public.
+pub fn minimal_access_modifier(
+	current: AccessModifier,
+	from_subclass: bool,
+	from_same_package: bool,
+) -> AccessModifier {
+	match current {
+		AccessModifier::Private if from_subclass => AccessModifier::Protected,
+		AccessModifier::Private if from_same_package => AccessModifier::Internal,
+		_ => AccessModifier::Public,
+	}
+}
+
+/// A hint stating the access modifier `name` is declared with and the one it needs to be accessed (e.g. `declare "foo"
+/// as "internal" to access it here (it's currently private)`). A declaration in another package can't be changed from
+/// here, so its hint only says what it would take (and can't be parsed by `parse_access_modifier_hint`).
+pub fn access_modifier_hint(
+	name: &str,
+	current: AccessModifier,
+	required: AccessModifier,
+	other_package: Option<&str>,
+) -> String {
+	let keyword = required.keyword().unwrap_or_default();
+	match other_package {
+		None => format!("{HINT_DECLARE_AS} \"{name}\" as \"{keyword}\" to access it here (it's currently {current})"),
+		Some(package) => format!(
+			"\"{name}\" is {current} in \"{package}\" and would have to be declared \"{keyword}\" there to be accessed here"
+		),
+	}
+}
+
+/// Parses the name and the access modifier keyword suggested by a hint made by `access_modifier_hint`
+pub fn parse_access_modifier_hint(hint: &str) -> Option<(String, String)> {
+	let rest = hint.strip_prefix(HINT_DECLARE_AS)?;
+	let (_, name, as_, keyword) = rest.split('"').next_tuple()?;
+	(as_ == " as ").then(|| (name.to_string(), keyword.to_string()))
+}
+
+/// A hint with the placeholder initializer of each missing field (e.g. ``add the missing fields `x: 0`, `y: ""` ``).
+/// Initializers are quoted with backticks, which Wing code doesn't use.
+pub fn missing_fields_hint(initializers: &[String]) -> Option<String> {
+	if initializers.is_empty() {
+		return None;
+	}
+	Some(format!(
+		"{HINT_ADD_MISSING_FIELDS} {}",
+		initializers.iter().map(|i| format!("`{i}`")).join(", ")
+	))
+}
+
+/// Parses the field initializers suggested by a hint made by `missing_fields_hint`
+pub fn parse_missing_fields_hint(hint: &str) -> Vec<String> {
+	let Some(initializers) = hint.strip_prefix(HINT_ADD_MISSING_FIELDS) else {
+		return vec![];
+	};
+	initializers
+		.split('`')
+		.skip(1)
+		.step_by(2)
+		.map(|initializer| initializer.to_string())
+		.collect()
+}
+
+/// Symbols the compiler defines for its own use, which users can't refer to
+fn is_compiler_generated(name: &str) -> bool {
+	name.starts_with(CLOSURE_CLASS_PREFIX) || name.starts_with(PARENT_THIS_NAME) || name.starts_with('$')
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn suggests_close_names() {
+		let candidates = ["public", "publish", "private", "$Closure1", "bucket"];
+		assert_eq!(similar_names("bublic", candidates), vec!["public"]);
+		assert_eq!(similar_names("pubilsh", candidates), vec!["publish"]);
+		assert_eq!(
+			similar_names("fod", ["foo", "food", "fo", "bar"]),
+			vec!["fo", "foo", "food"]
+		);
+		assert_eq!(similar_names("Bucket", candidates), vec!["bucket"]);
+		assert!(similar_names("queue", candidates).is_empty());
+		assert!(similar_names("$Closure2", candidates).is_empty());
+		assert_eq!(similar_names("x", ["y", "X"]), vec!["X"]);
+	}
+
+	#[test]
+	fn did_you_mean_hint_round_trip() {
+		let names = vec!["foo".to_string(), "fooBar".to_string(), "food".to_string()];
+		let hint = did_you_mean_hint(&names).unwrap();
+		assert_eq!(hint, "did you mean \"foo\", \"fooBar\" or \"food\"?");
+		assert_eq!(parse_did_you_mean_hint(&hint), names);
+		assert_eq!(did_you_mean_hint(&[]), None);
+	}
+
+	#[test]
+	fn access_modifier_hint_round_trip() {
+		let required = minimal_access_modifier(AccessModifier::Private, false, true);
+		assert_eq!(required, AccessModifier::Internal);
+		let hint = access_modifier_hint("foo", AccessModifier::Private, required, None);
+		assert_eq!(
+			hint,
+			"declare \"foo\" as \"internal\" to access it here (it's currently private)"
+		);
+		assert_eq!(
+			parse_access_modifier_hint(&hint),
+			Some(("foo".to_string(), "internal".to_string()))
+		);
+
+		assert_eq!(
+			minimal_access_modifier(AccessModifier::Private, true, false),
+			AccessModifier::Protected
+		);
+		assert_eq!(
+			minimal_access_modifier(AccessModifier::Protected, false, true),
+			AccessModifier::Public
+		);
+		let hint = access_modifier_hint("foo", AccessModifier::Internal, AccessModifier::Public, Some("lib"));
+		assert_eq!(parse_access_modifier_hint(&hint), None);
+	}
+
+	#[test]
+	fn missing_fields_hint_round_trip() {
+		let initializers = vec![
+			"x: 0".to_string(),
+			"y: \"\"".to_string(),
+			"p: Point { x: 0, y: 0 }".to_string(),
+		];
+		let hint = missing_fields_hint(&initializers).unwrap();
+		assert_eq!(
+			hint,
+			"add the missing fields `x: 0`, `y: \"\"`, `p: Point { x: 0, y: 0 }`"
+		);
+		assert_eq!(parse_missing_fields_hint(&hint), initializers);
+		assert_eq!(missing_fields_hint(&[]), None);
+		assert!(parse_missing_fields_hint("did you mean \"x\"?").is_empty());
+	}
+}
@@ -281,6 +281,7 @@ impl SymbolEnv {
 					span: self.symbol_map[&symbol.name].span.clone(),
 				}],
 				hints: vec![],
+				code: None,
 			});
 		}
 
@@ -386,6 +387,7 @@ impl SymbolEnv {
 
 		let symb = *it.next().unwrap();
 		let caller_source_package = self.source_package.clone();
+		let caller_env = self.get_ref();
 
 		let res = self.lookup_ext(symb, statement_idx);
 		let mut res = if let LookupResult::Found(k, i) = res {
@@ -433,8 +435,11 @@ impl SymbolEnv {
 
 				// We're looking up a symbol in a namespace other than our own, so we need to
 				// check if the symbol is public or not. If it's not, replace a "Found" result
-				// with a "NotPublic" result.
-				let partial_result = match env.lookup_ext(next_symb, statement_idx) {
+				// with a "NotPublic" result. Statement ordering of the caller only applies to
+				// symbols defined in the caller's own file (an environment it's nested in), so
+				// symbols of other files are looked up without a statement index.
+				let in_caller_file = env.is_same(&caller_env) || env.is_parent_of(&caller_env);
+				let partial_result = match env.lookup_ext(next_symb, statement_idx.filter(|_| in_caller_file)) {
 					LookupResult::Found(kind, info) => match info.access {
 						AccessModifier::Public => LookupResult::Found(kind, info),
 						AccessModifier::Internal => {
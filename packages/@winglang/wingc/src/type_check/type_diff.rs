@@ -0,0 +1,208 @@
+use itertools::Itertools;
+
+use crate::diagnostic::DiagnosticAnnotation;
+
+use super::{ClassLike, FunctionSignature, Struct, Subtype, TypeRef, VariableInfo};
+
+/// At most this many differences are listed, the rest are summarized in a single hint
+const MAX_DIFFERENCES: usize = 5;
+
+/// Why a struct or function type can't be used where another one is expected, broken down into the parts that differ
+/// (fields, parameters, return type), so errors don't have to make the user compare two long type names.
+#[derive(Default)]
+pub struct TypeDiff {
+	/// One line per difference
+	pub hints: Vec<String>,
+	/// The definitions of the differing struct fields
+	pub annotations: Vec<DiagnosticAnnotation>,
+}
+
+impl TypeDiff {
+	fn push(&mut self, hint: String) {
+		self.hints.push(hint);
+	}
+
+	fn annotate(&mut self, message: String, field: &VariableInfo) {
+		// Fields of structs imported from JSII have no location to point at
+		if !field.name.span.is_default() {
+			self.annotations.push(DiagnosticAnnotation::new(message, &field.name));
+		}
+	}
+
+	fn truncate(mut self) -> Self {
+		if self.hints.len() > MAX_DIFFERENCES {
+			let more = self.hints.len() - MAX_DIFFERENCES;
+			self.hints.truncate(MAX_DIFFERENCES);
+			self.hints.push(format!(
+				"and {more} more difference{}",
+				if more == 1 { "" } else { "s" }
+			));
+		}
+		self
+	}
+}
+
+/// Compares two mismatching struct types, or two mismatching function types. Returns `None` for other types, which
+/// are short enough to be compared by name.
+pub fn diff_types(actual: TypeRef, expected: TypeRef) -> Option<TypeDiff> {
+	let (actual, expected) = (*actual.maybe_unwrap_option(), *expected.maybe_unwrap_option());
+	if let (Some(actual_struct), Some(expected_struct)) = (actual.as_struct(), expected.as_struct()) {
+		return Some(diff_structs(actual_struct, expected_struct).truncate());
+	}
+	if let (Some(actual_sig), Some(expected_sig)) = (actual.as_deep_function_sig(), expected.as_deep_function_sig()) {
+		return Some(diff_function_signatures(actual_sig, expected_sig).truncate());
+	}
+	None
+}
+
+fn struct_fields(s: &Struct) -> Vec<(String, VariableInfo)> {
+	s.get_env()
+		.iter(true)
+		.filter_map(|(name, kind, ..)| Some((name, kind.as_variable()?.clone())))
+		.sorted_by(|(a, _), (b, _)| a.cmp(b))
+		.collect()
+}
+
+fn diff_structs(actual: &Struct, expected: &Struct) -> TypeDiff {
+	let mut diff = TypeDiff::default();
+	let actual_fields = struct_fields(actual);
+	let expected_fields = struct_fields(expected);
+	let find = |fields: &[(String, VariableInfo)], name: &str| {
+		fields
+			.iter()
+			.find(|(field_name, _)| field_name == name)
+			.map(|(_, field)| field.clone())
+	};
+
+	for (name, expected_field) in &expected_fields {
+		match find(&actual_fields, name) {
+			None if !expected_field.type_.is_option() => {
+				diff.push(format!(
+					"\"{actual}\" is missing the field \"{name}: {}\" of \"{expected}\"",
+					expected_field.type_
+				));
+				diff.annotate(format!("\"{name}\" is required by \"{expected}\""), expected_field);
+			}
+			Some(actual_field) if !actual_field.type_.is_subtype_of(&expected_field.type_) => {
+				diff.push(format!(
+					"field \"{name}\" is \"{}\" in \"{actual}\", but \"{}\" in \"{expected}\"",
+					actual_field.type_, expected_field.type_
+				));
+				diff.annotate(format!("\"{name}\" is \"{}\" here", actual_field.type_), &actual_field);
+				diff.annotate(
+					format!("\"{name}\" is expected to be \"{}\"", expected_field.type_),
+					expected_field,
+				);
+			}
+			_ => {}
+		}
+	}
+
+	for (name, actual_field) in &actual_fields {
+		if find(&expected_fields, name).is_none() {
+			diff.push(format!(
+				"\"{actual}\" has the field \"{name}: {}\", which isn't in \"{expected}\"",
+				actual_field.type_
+			));
+		}
+	}
+
+	if diff.hints.is_empty() {
+		diff.push(format!(
+			"\"{actual}\" has the same fields as \"{expected}\", but structs are only compatible when one extends the other"
+		));
+	}
+	diff
+}
+
+fn diff_function_signatures(actual: &FunctionSignature, expected: &FunctionSignature) -> TypeDiff {
+	let mut diff = TypeDiff::default();
+
+	// Phase differences are already hinted by the type mismatch error itself
+
+	let (actual_required, expected_required) = (actual.min_parameters(), expected.min_parameters());
+	if actual_required > expected_required {
+		diff.push(format!(
+			"the function requires {actual_required} parameter{}, but may be called with only {expected_required}",
+			if actual_required == 1 { "" } else { "s" }
+		));
+	}
+
+	for (index, (actual_param, expected_param)) in actual.parameters.iter().zip(&expected.parameters).enumerate() {
+		// Parameter types are contravariant: the function must accept any value it may be passed
+		if !expected_param.typeref.is_subtype_of(&actual_param.typeref) {
+			diff.push(format!(
+				"parameter {} (\"{}\") is \"{}\", but the function must accept \"{}\"",
+				index + 1,
+				actual_param.name,
+				actual_param.typeref,
+				expected_param.typeref
+			));
+		}
+	}
+
+	if !expected.return_type.is_void() && !actual.return_type.is_subtype_of(&expected.return_type) {
+		diff.push(format!(
+			"the function returns \"{}\", but \"{}\" is expected",
+			actual.return_type, expected.return_type
+		));
+	}
+	diff
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		docs::Docs,
+		type_check::{FunctionParameter, Phase, Type, UnsafeRef},
+	};
+
+	use super::*;
+
+	fn param(name: &str, typeref: TypeRef) -> FunctionParameter {
+		FunctionParameter {
+			name: name.into(),
+			typeref,
+			docs: Docs::default(),
+			variadic: false,
+		}
+	}
+
+	fn function(parameters: Vec<FunctionParameter>, return_type: TypeRef) -> Type {
+		Type::Function(FunctionSignature {
+			this_type: None,
+			parameters,
+			return_type,
+			phase: Phase::Inflight,
+			js_override: None,
+			is_macro: false,
+			docs: Docs::default(),
+			implicit_scope_param: false,
+		})
+	}
+
+	#[test]
+	fn diffs_function_parameters_and_return_types() {
+		let num = UnsafeRef::<Type>(&Type::Number as *const Type);
+		let string = UnsafeRef::<Type>(&Type::String as *const Type);
+		let actual = function(vec![param("x", num), param("y", num)], num);
+		let expected = function(vec![param("a", string)], string);
+
+		let diff = diff_types(
+			UnsafeRef::<Type>(&actual as *const Type),
+			UnsafeRef::<Type>(&expected as *const Type),
+		)
+		.unwrap();
+		assert_eq!(
+			diff.hints,
+			vec![
+				"the function requires 2 parameters, but may be called with only 1",
+				"parameter 1 (\"x\") is \"num\", but the function must accept \"str\"",
+				"the function returns \"num\", but \"str\" is expected",
+			]
+		);
+		assert!(diff.annotations.is_empty());
+
+		assert!(diff_types(num, string).is_none());
+	}
+}
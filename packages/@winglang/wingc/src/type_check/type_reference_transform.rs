@@ -25,9 +25,10 @@ impl<'a> Fold for TypeReferenceTransformer<'a> {
 					fold::fold_reference(self, node)
 				}
 			}
-			Reference::Identifier(..) | Reference::TypeMember { .. } | Reference::ElementAccess { .. } => {
-				fold::fold_reference(self, node)
-			}
+			Reference::Identifier(..)
+			| Reference::TypeMember { .. }
+			| Reference::ElementAccess { .. }
+			| Reference::SuperMember { .. } => fold::fold_reference(self, node),
 		}
 	}
 }
@@ -0,0 +1,167 @@
+use crate::{
+	ast::{ElseIfs, Expr, ExprKind, Literal, Scope, Stmt, StmtKind},
+	diagnostic::{Diagnostic, DiagnosticSeverity},
+	type_check::Types,
+	visit::{self, Visit},
+};
+
+/// This visitor warns about statements that can never run because a previous statement in the same scope
+/// never completes: a `return`, `throw`, `break` or `continue`, a call to a function returning `never`, a
+/// `while true` loop without a `break`, or a compound statement all of whose branches end in one of these.
+///
+/// It runs after type checking so the types of expressions are known.
+pub struct UnreachableCodeVisitor<'a> {
+	types: &'a Types,
+}
+
+impl<'a> UnreachableCodeVisitor<'a> {
+	pub fn new(types: &'a Types) -> Self {
+		Self { types }
+	}
+
+	pub fn check(&mut self, scope: &Scope) {
+		self.visit_scope(scope);
+	}
+}
+
+/// Whether execution can continue to the statement following the given one
+pub fn completes(types: &Types, stmt: &Stmt) -> bool {
+	match &stmt.kind {
+		StmtKind::Return(_) | StmtKind::Throw(_) | StmtKind::Break | StmtKind::Continue => false,
+		StmtKind::Expression(expr) => !types
+			.try_get_expr_type(expr.id)
+			.is_some_and(|t| types.maybe_unwrap_inference(t).is_never()),
+		StmtKind::Scope(scope) => scope_completes(types, scope),
+		StmtKind::ExplicitLift(lift) => scope_completes(types, &lift.statements),
+		StmtKind::If {
+			statements,
+			else_if_statements,
+			else_statements,
+			..
+		} => match else_statements {
+			Some(else_statements) => {
+				scope_completes(types, statements)
+					|| else_if_statements.iter().any(|b| scope_completes(types, &b.statements))
+					|| scope_completes(types, else_statements)
+			}
+			None => true,
+		},
+		StmtKind::IfLet(if_let) => match &if_let.else_statements {
+			Some(else_statements) => {
+				scope_completes(types, &if_let.statements)
+					|| if_let.else_if_statements.iter().any(|b| match b {
+						ElseIfs::ElseIfBlock(b) => scope_completes(types, &b.statements),
+						ElseIfs::ElseIfLetBlock(b) => scope_completes(types, &b.statements),
+					}) || scope_completes(types, else_statements)
+			}
+			None => true,
+		},
+		// A `while true` loop only ends through a `break`
+		StmtKind::While { condition, statements } => !is_constant_true(condition) || contains_break(statements),
+		StmtKind::TryCatch {
+			try_statements,
+			catch_block,
+			finally_statements,
+		} => {
+			if finally_statements.as_ref().is_some_and(|f| !scope_completes(types, f)) {
+				return false;
+			}
+			scope_completes(types, try_statements)
+				|| catch_block
+					.as_ref()
+					.is_some_and(|catch_block| scope_completes(types, &catch_block.statements))
+		}
+		// Listed explicitly so the reachability of new kinds of statements is considered
+		StmtKind::Bring { .. }
+		| StmtKind::SuperConstructor { .. }
+		| StmtKind::Let { .. }
+		| StmtKind::ForLoop { .. }
+		| StmtKind::WhileLet { .. }
+		| StmtKind::Assignment { .. }
+		| StmtKind::Class(_)
+		| StmtKind::Interface(_)
+		| StmtKind::Struct(_)
+		| StmtKind::Enum(_) => true,
+	}
+}
+
+/// Whether execution can continue past the end of the scope
+pub fn scope_completes(types: &Types, scope: &Scope) -> bool {
+	scope.statements.iter().all(|stmt| completes(types, stmt))
+}
+
+fn is_constant_true(condition: &Expr) -> bool {
+	matches!(condition.kind, ExprKind::Literal(Literal::Boolean(true)))
+}
+
+/// Whether the scope contains a `break` that exits the loop it's the body of (not an inner loop)
+fn contains_break(scope: &Scope) -> bool {
+	scope.statements.iter().any(|stmt| match &stmt.kind {
+		StmtKind::Break => true,
+		StmtKind::Scope(scope) => contains_break(scope),
+		StmtKind::ExplicitLift(lift) => contains_break(&lift.statements),
+		StmtKind::If {
+			statements,
+			else_if_statements,
+			else_statements,
+			..
+		} => {
+			contains_break(statements)
+				|| else_if_statements.iter().any(|b| contains_break(&b.statements))
+				|| else_statements.as_ref().is_some_and(contains_break)
+		}
+		StmtKind::IfLet(if_let) => {
+			contains_break(&if_let.statements)
+				|| if_let.else_if_statements.iter().any(|b| match b {
+					ElseIfs::ElseIfBlock(b) => contains_break(&b.statements),
+					ElseIfs::ElseIfLetBlock(b) => contains_break(&b.statements),
+				}) || if_let.else_statements.as_ref().is_some_and(contains_break)
+		}
+		StmtKind::TryCatch {
+			try_statements,
+			catch_block,
+			finally_statements,
+		} => {
+			contains_break(try_statements)
+				|| catch_block.as_ref().is_some_and(|c| contains_break(&c.statements))
+				|| finally_statements.as_ref().is_some_and(contains_break)
+		}
+		// A `break` inside an inner loop only exits that loop
+		StmtKind::ForLoop { .. } | StmtKind::While { .. } | StmtKind::WhileLet { .. } => false,
+		StmtKind::Bring { .. }
+		| StmtKind::SuperConstructor { .. }
+		| StmtKind::Let { .. }
+		| StmtKind::Continue
+		| StmtKind::Return(_)
+		| StmtKind::Throw(_)
+		| StmtKind::Expression(_)
+		| StmtKind::Assignment { .. }
+		| StmtKind::Class(_)
+		| StmtKind::Interface(_)
+		| StmtKind::Struct(_)
+		| StmtKind::Enum(_) => false,
+	})
+}
+
+impl<'a> Visit<'_> for UnreachableCodeVisitor<'a> {
+	fn visit_scope(&mut self, node: &Scope) {
+		if let Some(idx) = node.statements.iter().position(|stmt| !completes(self.types, stmt)) {
+			// Type definitions are hoisted, so they're usable even if they come after the end of the scope's execution
+			let unreachable = node.statements[idx + 1..]
+				.iter()
+				.filter(|stmt| !stmt.kind.is_type_def())
+				.collect::<Vec<_>>();
+			if let (Some(first), Some(last)) = (unreachable.first(), unreachable.last()) {
+				Diagnostic::new("Unreachable code", &first.span.merge(&last.span))
+					.annotate(
+						"execution never continues past this statement",
+						node.statements[idx].span.clone(),
+					)
+					.severity(DiagnosticSeverity::Warning)
+					.report();
+			}
+		}
+
+		visit::visit_scope(self, node);
+	}
+}
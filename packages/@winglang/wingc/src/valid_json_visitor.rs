@@ -33,6 +33,7 @@ impl<'a> ValidJsonVisitor<'a> {
 				annotations: vec![],
 				hints: vec![],
 				severity: DiagnosticSeverity::Error,
+				code: None,
 			})
 		}
 	}
@@ -1,7 +1,7 @@
 use crate::ast::{
 	ArgList, BringSource, CalleeKind, Class, ElseIfs, Enum, Expr, ExprKind, FunctionBody, FunctionDefinition,
-	FunctionParameter, FunctionSignature, IfLet, Interface, InterpolatedStringPart, Literal, New, Reference, Scope, Stmt,
-	StmtKind, Struct, Symbol, TypeAnnotation, TypeAnnotationKind, UserDefinedType,
+	FunctionParameter, FunctionSignature, IfLet, IfLetCondition, Interface, InterpolatedStringPart, Literal, New,
+	Reference, Scope, Stmt, StmtKind, Struct, Symbol, TypeAnnotation, TypeAnnotationKind, UserDefinedType,
 };
 
 /// Visitor pattern inspired by implementation from https://docs.rs/syn/latest/syn/visit/index.html
@@ -26,6 +26,10 @@ use crate::ast::{
 /// }
 /// ```
 ///
+/// The default implementations match on every kind of node without a wildcard (`_`) or rest (`..`) pattern, so adding
+/// a new `ExprKind` or `StmtKind` variant doesn't compile until it's visited here. Passes that match on the kind of a
+/// node to decide how to handle it should list every kind explicitly as well, so they can't silently skip new ones.
+///
 /// TODO: Can we code-generate this based on data in `ast.rs`?
 /// TODO: Provide a VisitMut trait that allows for mutation of the AST nodes
 /// (each method would accept a `&mut node` instead of `&node`)
@@ -103,6 +107,7 @@ where
 				BringSource::TrustedModule(name, _module_dir) => v.visit_symbol(name),
 				BringSource::WingLibrary(name, _module_dir) => v.visit_symbol(name),
 				BringSource::JsiiModule(name) => v.visit_symbol(name),
+				BringSource::NpmModule(name) => v.visit_symbol(name),
 				BringSource::WingFile(_) | BringSource::Directory(_) => {}
 			}
 			if let Some(identifier) = identifier {
@@ -115,6 +120,7 @@ where
 			var_name,
 			initial_value,
 			type_,
+			access: _,
 		} => {
 			v.visit_symbol(var_name);
 			if let Some(type_) = type_ {
@@ -124,10 +130,14 @@ where
 		}
 		StmtKind::ForLoop {
 			iterator,
+			second_iterator,
 			iterable,
 			statements,
 		} => {
 			v.visit_symbol(iterator);
+			if let Some(second_iterator) = second_iterator {
+				v.visit_symbol(second_iterator);
+			}
 			v.visit_expr(iterable);
 			v.visit_scope(statements);
 		}
@@ -135,9 +145,20 @@ where
 			v.visit_expr(condition);
 			v.visit_scope(statements);
 		}
+		StmtKind::WhileLet {
+			reassignable: _,
+			var_name,
+			value,
+			statements,
+		} => {
+			v.visit_symbol(var_name);
+			v.visit_expr(value);
+			v.visit_scope(statements);
+		}
 		StmtKind::Break | StmtKind::Continue => {}
 		StmtKind::IfLet(IfLet {
 			value,
+			conditions,
 			statements,
 			reassignable: _,
 			var_name,
@@ -146,6 +167,19 @@ where
 		}) => {
 			v.visit_symbol(var_name);
 			v.visit_expr(value);
+			for condition in conditions {
+				match condition {
+					IfLetCondition::Let {
+						reassignable: _,
+						var_name,
+						value,
+					} => {
+						v.visit_symbol(var_name);
+						v.visit_expr(value);
+					}
+					IfLetCondition::Guard(guard) => v.visit_expr(guard),
+				}
+			}
 			v.visit_scope(statements);
 			for else_if in else_if_statements {
 				match else_if {
@@ -339,7 +373,21 @@ where
 				v.visit_args(arg_list);
 			}
 		}
-		ExprKind::Call { callee, arg_list } => {
+		ExprKind::InlineJs {
+			inputs, return_type, ..
+		} => {
+			for input in inputs {
+				v.visit_expr(input);
+			}
+			if let Some(return_type) = return_type {
+				v.visit_type_annotation(return_type);
+			}
+		}
+		ExprKind::Call {
+			callee,
+			arg_list,
+			optional_accessor: _,
+		} => {
 			match callee {
 				CalleeKind::Expr(expr) => v.visit_expr(expr),
 				CalleeKind::SuperCall(method) => v.visit_symbol(method),
@@ -361,7 +409,7 @@ where
 				v.visit_expr(item);
 			}
 		}
-		ExprKind::JsonLiteral { element, .. } => {
+		ExprKind::JsonLiteral { is_mut: _, element } => {
 			v.visit_expr(element);
 		}
 		ExprKind::StructLiteral { type_, fields } => {
@@ -397,6 +445,7 @@ where
 		ExprKind::FunctionClosure(def) => {
 			v.visit_function_definition(def);
 		}
+		ExprKind::Error => {}
 	}
 }
 
@@ -440,10 +489,17 @@ where
 			v.visit_user_defined_type(type_name);
 			v.visit_symbol(property);
 		}
-		Reference::ElementAccess { object, index } => {
+		Reference::ElementAccess {
+			object,
+			index,
+			optional_accessor: _,
+		} => {
 			v.visit_expr(object);
 			v.visit_expr(index);
 		}
+		Reference::SuperMember { property } => {
+			v.visit_symbol(property);
+		}
 	}
 }
 
@@ -500,6 +556,9 @@ where
 		TypeAnnotationKind::Duration => {}
 		TypeAnnotationKind::Datetime => {}
 		TypeAnnotationKind::Regex => {}
+		TypeAnnotationKind::Never => {}
+		TypeAnnotationKind::Unknown => {}
+		TypeAnnotationKind::Literal(_) => {}
 		TypeAnnotationKind::Void => {}
 		TypeAnnotationKind::Json => {}
 		TypeAnnotationKind::MutJson => {}
@@ -2,6 +2,7 @@ use itertools::Itertools;
 
 use crate::{
 	ast::{Class, Expr, ExprId, FunctionSignature, Phase, Stmt, StmtKind, Symbol, UserDefinedType},
+	diagnostic::WingSpan,
 	type_check::symbol_env::SymbolEnvRef,
 };
 
@@ -10,6 +11,8 @@ pub struct FunctionContext {
 	pub name: Option<Symbol>,
 	pub sig: FunctionSignature,
 	pub is_static: bool,
+	/// The span of the function's definition
+	pub span: WingSpan,
 }
 
 #[derive(Clone)]
@@ -103,6 +106,11 @@ impl VisitContext {
 		self.expression.last().map(|id| *id)
 	}
 
+	/// The expression that contains the current expression, if any
+	pub fn parent_expr(&self) -> Option<ExprId> {
+		self.expression.iter().rev().nth(1).copied()
+	}
+
 	// --
 
 	pub fn push_class(&mut self, class: &Class) {
@@ -129,12 +137,14 @@ impl VisitContext {
 		sig: &FunctionSignature,
 		is_static: bool,
 		env: SymbolEnvRef,
+		span: &WingSpan,
 	) {
 		self.push_phase(sig.phase);
 		self.function.push(FunctionContext {
 			name: function_name.cloned(),
 			sig: sig.clone(),
 			is_static,
+			span: span.clone(),
 		});
 		self.function_env.push(env);
 	}
@@ -255,9 +265,12 @@ pub trait VisitorWithContext {
 		sig: &FunctionSignature,
 		is_static: bool,
 		env: SymbolEnvRef,
+		span: &WingSpan,
 		f: impl FnOnce(&mut Self) -> T,
 	) -> T {
-		self.ctx().push_function_definition(function_name, sig, is_static, env);
+		self
+			.ctx()
+			.push_function_definition(function_name, sig, is_static, env, span);
 		let res = f(self);
 		self.ctx().pop_function_definition();
 		res
@@ -51,6 +51,9 @@ where
 		| Type::Regex
 		| Type::Boolean
 		| Type::Void
+		| Type::Never
+		| Type::Unknown
+		| Type::Literal(_)
 		| Type::Json(None)
 		| Type::MutJson
 		| Type::Nil
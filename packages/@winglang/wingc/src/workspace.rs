@@ -0,0 +1,274 @@
+use std::{cell::RefCell, collections::HashMap, fs, rc::Rc};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use globset::Glob;
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::parser::as_wing_library;
+
+/// The package.json field listing the packages of a workspace (the same field npm and yarn use)
+const WORKSPACES_FIELD: &str = "workspaces";
+
+/// Directories that are never searched for workspace packages
+const IGNORED_DIRS: [&str; 2] = ["node_modules", "target"];
+
+/// A workspace (or monorepo) with several local Wing packages that can bring each other by package name,
+/// without being published or installed.
+///
+/// A workspace is declared by a package.json with a `workspaces` field, listing the directories of its packages
+/// (glob patterns like "packages/*" are supported). Only packages that are Wing libraries (packages with a `wing`
+/// field) are part of the Wing workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+	/// The directory of the workspace's manifest
+	pub root: Utf8PathBuf,
+	/// A map from package names to their root directories
+	pub packages: IndexMap<String, Utf8PathBuf>,
+}
+
+impl Workspace {
+	/// Finds the workspace the given directory belongs to, by searching it and its parents for a workspace manifest.
+	pub fn find(dir: &Utf8Path) -> Option<Self> {
+		let (root, patterns) = find_workspace_manifest(dir)?;
+		let workspace = Self {
+			root: root.to_owned(),
+			packages: find_workspace_packages(root, &patterns),
+		};
+		workspace.contains(dir).then_some(workspace)
+	}
+
+	/// Directories that aren't part of any of the workspace's packages belong to some other project
+	fn contains(&self, dir: &Utf8Path) -> bool {
+		dir == self.root || self.package_of(dir).is_some()
+	}
+
+	/// Returns the root directory of a package of the workspace
+	pub fn package_dir(&self, name: &str) -> Option<&Utf8PathBuf> {
+		self.packages.get(name)
+	}
+
+	/// Returns the name of the workspace package that contains the given path
+	pub fn package_of(&self, path: &Utf8Path) -> Option<&str> {
+		self
+			.packages
+			.iter()
+			.filter(|(_, package_dir)| path.starts_with(package_dir))
+			// with nested packages, the innermost package contains the path
+			.max_by_key(|(_, package_dir)| package_dir.as_str().len())
+			.map(|(name, _)| name.as_str())
+	}
+}
+
+/// The workspaces found while parsing a project. Every `bring` of a package looks for the workspace of the bringing
+/// file, so this keeps the file system from being searched (and the workspace's package patterns from being expanded)
+/// over and over. It's meant to live for a single compilation, so packages added to a workspace are found next time.
+#[derive(Debug, Default)]
+pub struct WorkspaceCache {
+	/// The workspace of each directory that was looked up, if it has one
+	dirs: RefCell<HashMap<Utf8PathBuf, Option<Rc<Workspace>>>>,
+	/// The workspaces found so far, by the directories of their manifests
+	roots: RefCell<HashMap<Utf8PathBuf, Rc<Workspace>>>,
+}
+
+impl WorkspaceCache {
+	/// Same as `Workspace::find`, but each directory is only searched once and each workspace is only expanded once
+	pub fn find(&self, dir: &Utf8Path) -> Option<Rc<Workspace>> {
+		if let Some(workspace) = self.dirs.borrow().get(dir) {
+			return workspace.clone();
+		}
+		let workspace = find_workspace_manifest(dir).and_then(|(root, patterns)| {
+			let workspace = self
+				.roots
+				.borrow_mut()
+				.entry(root.to_owned())
+				.or_insert_with(|| {
+					Rc::new(Workspace {
+						root: root.to_owned(),
+						packages: find_workspace_packages(root, &patterns),
+					})
+				})
+				.clone();
+			workspace.contains(dir).then_some(workspace)
+		});
+		self.dirs.borrow_mut().insert(dir.to_owned(), workspace.clone());
+		workspace
+	}
+}
+
+/// Searches the given directory and its parents for a workspace manifest, and returns its directory and package patterns
+fn find_workspace_manifest(dir: &Utf8Path) -> Option<(&Utf8Path, Vec<String>)> {
+	dir
+		.ancestors()
+		.find_map(|ancestor| workspace_patterns(ancestor).map(|patterns| (ancestor, patterns)))
+}
+
+/// Reads the package patterns of the workspace manifest in the given directory, if there is one.
+/// Both the npm format (`"workspaces": [...]`) and the yarn format (`"workspaces": { "packages": [...] }`) are supported.
+fn workspace_patterns(dir: &Utf8Path) -> Option<Vec<String>> {
+	let package_json = fs::read_to_string(dir.join("package.json")).ok()?;
+	let package_json: Value = serde_json::from_str(&package_json).ok()?;
+	let workspaces = package_json.get(WORKSPACES_FIELD)?;
+	let patterns = match workspaces {
+		Value::Array(patterns) => patterns,
+		Value::Object(workspaces) => workspaces.get("packages")?.as_array()?,
+		_ => return None,
+	};
+	Some(patterns.iter().filter_map(|p| p.as_str()).map(str::to_string).collect())
+}
+
+/// Expands the package patterns of a workspace into the Wing libraries they match
+fn find_workspace_packages(root: &Utf8Path, patterns: &[String]) -> IndexMap<String, Utf8PathBuf> {
+	let mut packages = IndexMap::new();
+	for pattern in patterns {
+		// Negated patterns exclude packages matched by other patterns
+		if pattern.starts_with('!') {
+			continue;
+		}
+		let components = pattern.trim_start_matches("./").split('/').collect::<Vec<_>>();
+		for dir in expand_pattern(root, &components) {
+			let is_excluded = patterns
+				.iter()
+				.filter_map(|pattern| pattern.strip_prefix('!'))
+				.any(|excluded| matches_pattern(excluded, root, &dir));
+			if is_excluded {
+				continue;
+			}
+			if let Some(name) = as_wing_library(&dir, true) {
+				packages.entry(name).or_insert(dir);
+			}
+		}
+	}
+	packages
+}
+
+/// Returns all directories under `dir` that match the given path components (which may be glob patterns)
+fn expand_pattern(dir: &Utf8Path, components: &[&str]) -> Vec<Utf8PathBuf> {
+	let Some((component, rest)) = components.split_first() else {
+		return vec![dir.to_owned()];
+	};
+	if component.is_empty() || *component == "." {
+		return expand_pattern(dir, rest);
+	}
+	if *component == "**" {
+		// "**" matches the directory itself and any of its subdirectories
+		let mut dirs = expand_pattern(dir, rest);
+		for subdir in subdirectories(dir) {
+			dirs.extend(expand_pattern(&subdir, components));
+		}
+		return dirs;
+	}
+	let Ok(glob) = Glob::new(component) else {
+		return vec![];
+	};
+	let matcher = glob.compile_matcher();
+	subdirectories(dir)
+		.into_iter()
+		.filter(|subdir| subdir.file_name().map_or(false, |name| matcher.is_match(name)))
+		.flat_map(|subdir| expand_pattern(&subdir, rest))
+		.collect()
+}
+
+fn matches_pattern(pattern: &str, root: &Utf8Path, dir: &Utf8Path) -> bool {
+	let Ok(relative) = dir.strip_prefix(root) else {
+		return false;
+	};
+	Glob::new(pattern.trim_start_matches("./"))
+		.map(|glob| glob.compile_matcher().is_match(relative))
+		.unwrap_or(false)
+}
+
+fn subdirectories(dir: &Utf8Path) -> Vec<Utf8PathBuf> {
+	let Ok(entries) = dir.read_dir_utf8() else {
+		return vec![];
+	};
+	let mut subdirs = entries
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| entry.file_type().map_or(false, |t| t.is_dir()))
+		.filter(|entry| !entry.file_name().starts_with('.') && !IGNORED_DIRS.contains(&entry.file_name()))
+		.map(|entry| entry.into_path())
+		.collect::<Vec<_>>();
+	// Keep the order of the packages deterministic
+	subdirs.sort();
+	subdirs
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use camino::{Utf8Path, Utf8PathBuf};
+
+	use super::{Workspace, WorkspaceCache};
+
+	fn write_package(dir: &Utf8Path, package_json: &str) {
+		fs::create_dir_all(dir).unwrap();
+		fs::write(dir.join("package.json"), package_json).unwrap();
+	}
+
+	#[test]
+	fn finds_the_wing_packages_of_a_workspace() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+		write_package(
+			&root,
+			r#"{ "workspaces": ["packages/*", "apps/web", "!packages/ignored"] }"#,
+		);
+		write_package(&root.join("packages/lib-a"), r#"{ "name": "lib-a", "wing": true }"#);
+		write_package(&root.join("packages/lib-b"), r#"{ "name": "@acme/lib-b", "wing": {} }"#);
+		write_package(&root.join("packages/not-wing"), r#"{ "name": "not-wing" }"#);
+		write_package(&root.join("packages/ignored"), r#"{ "name": "ignored", "wing": true }"#);
+		write_package(&root.join("apps/web"), r#"{ "name": "web", "wing": true }"#);
+
+		let workspace = Workspace::find(&root.join("apps/web")).unwrap();
+		assert_eq!(workspace.root, root);
+		assert_eq!(
+			workspace.packages.keys().collect::<Vec<_>>(),
+			vec!["lib-a", "@acme/lib-b", "web"]
+		);
+		assert_eq!(workspace.package_dir("@acme/lib-b"), Some(&root.join("packages/lib-b")));
+		assert_eq!(
+			workspace.package_of(&root.join("packages/lib-a/src/main.w")),
+			Some("lib-a")
+		);
+		assert_eq!(workspace.package_of(&root.join("README.md")), None);
+	}
+
+	#[test]
+	fn ignores_directories_outside_of_a_workspace() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+		write_package(&root, r#"{ "workspaces": ["packages/*"] }"#);
+		write_package(&root.join("packages/lib"), r#"{ "name": "lib", "wing": true }"#);
+		write_package(&root.join("examples/app"), r#"{ "name": "app", "wing": true }"#);
+
+		assert!(Workspace::find(&root.join("examples/app")).is_none());
+		assert!(Workspace::find(&root.join("packages/lib")).is_some());
+	}
+
+	#[test]
+	fn caches_the_workspace_of_each_directory() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf()).unwrap();
+		write_package(&root, r#"{ "workspaces": ["packages/*"] }"#);
+		write_package(&root.join("packages/lib-a"), r#"{ "name": "lib-a", "wing": true }"#);
+		write_package(&root.join("packages/lib-b"), r#"{ "name": "lib-b", "wing": true }"#);
+		write_package(&root.join("examples/app"), r#"{ "name": "app", "wing": true }"#);
+
+		let cache = WorkspaceCache::default();
+		let workspace = cache.find(&root.join("packages/lib-a")).unwrap();
+		assert_eq!(*workspace, Workspace::find(&root.join("packages/lib-a")).unwrap());
+		assert!(cache.find(&root.join("examples/app")).is_none());
+
+		// packages added after the workspace was found are only seen by a new cache
+		write_package(&root.join("packages/lib-c"), r#"{ "name": "lib-c", "wing": true }"#);
+		let same_workspace = cache.find(&root.join("packages/lib-b")).unwrap();
+		assert!(std::rc::Rc::ptr_eq(&workspace, &same_workspace));
+		assert_eq!(same_workspace.package_dir("lib-c"), None);
+		assert!(WorkspaceCache::default()
+			.find(&root.join("packages/lib-b"))
+			.unwrap()
+			.package_dir("lib-c")
+			.is_some());
+	}
+}
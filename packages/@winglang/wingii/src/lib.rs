@@ -161,7 +161,7 @@ pub mod spec {
 pub mod type_system {
 	type AssemblyName = String;
 
-	use camino::Utf8Path;
+	use camino::{Utf8Path, Utf8PathBuf};
 	use serde_json::Value;
 
 	use crate::fqn::FQN;
@@ -174,6 +174,8 @@ pub mod type_system {
 
 	pub struct TypeSystem {
 		assemblies: HashMap<String, Assembly>,
+		/// The `package.json` and assembly files of the loaded modules, in the order they were loaded
+		manifest_files: Vec<Utf8PathBuf>,
 	}
 
 	pub trait QueryableType {}
@@ -185,6 +187,7 @@ pub mod type_system {
 		pub fn new() -> TypeSystem {
 			TypeSystem {
 				assemblies: HashMap::new(),
+				manifest_files: Vec::new(),
 			}
 		}
 
@@ -194,6 +197,10 @@ pub mod type_system {
 		pub fn find_assembly(&self, name: &str) -> Option<&Assembly> {
 			self.assemblies.get(name)
 		}
+		/// The files the loaded assemblies were read from (each module's `package.json` and JSII manifest)
+		pub fn manifest_files(&self) -> &[Utf8PathBuf] {
+			&self.manifest_files
+		}
 		fn find_type(&self, fqn: &FQN) -> Option<&jsii::Type> {
 			let assembly = self.assemblies.get(fqn.assembly())?;
 
@@ -243,7 +250,7 @@ pub mod type_system {
 
 		pub fn load_module(&mut self, module_directory: &Utf8Path) -> Result<AssemblyName> {
 			let file_path = Utf8Path::new(module_directory).join("package.json");
-			let package_json = std::fs::read_to_string(file_path)?;
+			let package_json = std::fs::read_to_string(&file_path)?;
 			let package: serde_json::Value = serde_json::from_str(&package_json)?;
 			let _ = package
 				.get("jsii")
@@ -267,6 +274,8 @@ pub mod type_system {
 			};
 
 			let asm = spec::load_assembly_from_file(name, &assembly_file, None, &module_version)?;
+			self.manifest_files.push(file_path);
+			self.manifest_files.push(assembly_file);
 			let root = self.add_assembly(asm)?;
 			let bundled = package_json::bundled_dependencies_of(&package);
 			let deps = package_json::dependencies_of(&package);
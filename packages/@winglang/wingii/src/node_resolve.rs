@@ -15,6 +15,14 @@ static ROOT: &str = "/";
 static NODE_WING_FIELD: &str = "wing";
 static NODE_EXTENSIONS: [&str; 5] = [".js", ".cjs", ".mjs", "json", ".node"];
 static NODE_MAIN_FIELDS: [&str; 1] = ["main"];
+/// The directory where pnpm hoists all the packages of its virtual store (`node_modules/.pnpm`)
+static PNPM_HOISTED_DIR: &str = "node_modules/.pnpm/node_modules";
+/// The files yarn creates for projects installed with Plug'n'Play
+static YARN_PNP_FILES: [&str; 2] = [".pnp.cjs", ".pnp.js"];
+/// The directory where yarn extracts the Plug'n'Play packages that must exist on disk
+static YARN_UNPLUGGED_DIR: &str = ".yarn/unplugged";
+/// How many symbolic links we follow when resolving a path before giving up
+static MAX_SYMLINKS: usize = 40;
 static NODE_BUILTINS: [&str; 33] = [
 	"assert",
 	"buffer",
@@ -74,7 +82,7 @@ pub fn resolve_from(target: &str, basedir: &Utf8Path) -> Result<Utf8PathBuf, Box
 		return resolve_as_file(&path).or_else(|_| resolve_as_directory(&path));
 	}
 
-	resolve_node_modules(target, basedir)
+	resolve_node_modules(target, basedir).or_else(|err| resolve_package_manager_layouts(target, basedir).ok_or(err))
 }
 
 /// Resolve a path as a file. If `path` refers to a file, it is returned;
@@ -121,6 +129,96 @@ fn resolve_node_modules(target: &str, basedir: &Utf8Path) -> Result<Utf8PathBuf,
 	}
 }
 
+/// Resolve a package installed by a package manager that doesn't lay out `node_modules` the way npm does.
+/// This is only tried if the package can't be found by walking up `node_modules` folders.
+fn resolve_package_manager_layouts(target: &str, basedir: &Utf8Path) -> Option<Utf8PathBuf> {
+	// pnpm links the packages of a project to its virtual store (`node_modules/.pnpm/<package>@<version>/node_modules`),
+	// and puts their dependencies next to them there. So the dependencies of a package are found from its real path,
+	// not from the link in the project's `node_modules`.
+	if let Some(real_basedir) = resolve_symlinks(basedir).filter(|real_basedir| real_basedir != basedir) {
+		if let Ok(path) = resolve_node_modules(target, &real_basedir) {
+			return Some(path);
+		}
+	}
+
+	for dir in basedir.ancestors() {
+		// pnpm also hoists every package of the virtual store to `node_modules/.pnpm/node_modules`, which is how
+		// Node.js finds packages that are used without being declared as dependencies
+		let path = dir.join(PNPM_HOISTED_DIR).join(target);
+		if let Ok(path) = resolve_as_file(&path).or_else(|_| resolve_as_directory(&path)) {
+			return Some(path);
+		}
+
+		// yarn Plug'n'Play keeps packages in zip archives, except the ones that are unplugged
+		// (see `dependenciesMeta` in yarn's documentation)
+		if is_yarn_pnp_root(dir) {
+			return resolve_yarn_unplugged(target, &dir.join(YARN_UNPLUGGED_DIR));
+		}
+	}
+	None
+}
+
+/// Check if a directory is part of a project installed with yarn Plug'n'Play
+pub fn is_yarn_pnp_project(dir: &Utf8Path) -> bool {
+	dir.ancestors().any(is_yarn_pnp_root)
+}
+
+fn is_yarn_pnp_root(dir: &Utf8Path) -> bool {
+	YARN_PNP_FILES.iter().any(|pnp_file| dir.join(pnp_file).is_file())
+}
+
+/// Resolve a package unplugged by yarn. Unplugged packages are extracted to
+/// `.yarn/unplugged/<package>-npm-<version>-<hash>/node_modules/<package>`.
+fn resolve_yarn_unplugged(target: &str, unplugged_dir: &Utf8Path) -> Option<Utf8PathBuf> {
+	let mut entries = unplugged_dir
+		.read_dir_utf8()
+		.ok()?
+		.filter_map(|entry| entry.ok())
+		.map(|entry| entry.into_path())
+		.collect::<Vec<_>>();
+	// If several versions of the package are unplugged, prefer the latest one
+	entries.sort();
+	entries.into_iter().rev().find_map(|entry| {
+		let path = entry.join("node_modules").join(target);
+		resolve_as_file(&path).or_else(|_| resolve_as_directory(&path)).ok()
+	})
+}
+
+/// Returns the path with all the symbolic links in it resolved, or `None` if it can't be resolved.
+/// We can't use `fs::canonicalize` since it isn't supported by WASI.
+pub fn resolve_symlinks(path: &Utf8Path) -> Option<Utf8PathBuf> {
+	let mut resolved = Utf8PathBuf::new();
+	let mut followed_links = 0;
+	let mut components = path.components().map(|c| c.as_str().to_string()).collect::<Vec<_>>();
+	components.reverse();
+	while let Some(component) = components.pop() {
+		match component.as_str() {
+			"." => continue,
+			".." => {
+				resolved.pop();
+				continue;
+			}
+			_ => {}
+		}
+		let next = resolved.join(&component);
+		if !fs::symlink_metadata(&next).ok()?.is_symlink() {
+			resolved = next;
+			continue;
+		}
+		followed_links += 1;
+		if followed_links > MAX_SYMLINKS {
+			return None;
+		}
+		let link = Utf8PathBuf::try_from(fs::read_link(&next).ok()?).ok()?;
+		if link.is_absolute() {
+			resolved = Utf8PathBuf::new();
+		}
+		// Resolve the link's target (which may contain links too) before the rest of the path
+		components.extend(link.components().rev().map(|c| c.as_str().to_string()));
+	}
+	Some(resolved)
+}
+
 /// Resolve a path as a directory, using the "main" key from a package.json file if it
 /// exists, or resolving to the index.EXT file if it exists.
 fn resolve_as_directory(path: &Utf8Path) -> Result<Utf8PathBuf, Box<dyn Error>> {
@@ -192,3 +290,88 @@ fn is_core_module(target: &str) -> bool {
 pub fn is_path_dependency(dependency_name: &str) -> bool {
 	dependency_name.starts_with("./") || dependency_name.starts_with("../") || dependency_name.starts_with("/")
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+	use std::{fs, os::unix::fs::symlink};
+
+	use camino::{Utf8Path, Utf8PathBuf};
+	use rand::Rng;
+
+	use super::{resolve_from, resolve_symlinks};
+
+	fn temp_project() -> Utf8PathBuf {
+		let dir = std::env::temp_dir().join(format!("wingii-resolve-{}", rand::thread_rng().gen::<u64>()));
+		let dir = Utf8PathBuf::try_from(dir).unwrap();
+		fs::create_dir_all(&dir).unwrap();
+		resolve_symlinks(&dir).unwrap()
+	}
+
+	fn write_package(dir: &Utf8Path, name: &str) {
+		fs::create_dir_all(dir).unwrap();
+		fs::write(
+			dir.join("package.json"),
+			format!(r#"{{ "name": "{name}", "wing": true }}"#),
+		)
+		.unwrap();
+	}
+
+	#[test]
+	fn resolves_dependencies_from_the_pnpm_virtual_store() {
+		let project = temp_project();
+		let store = project.join("node_modules/.pnpm");
+		write_package(&store.join("lib-a@1.0.0/node_modules/lib-a"), "lib-a");
+		write_package(&store.join("lib-b@1.0.0/node_modules/lib-b"), "lib-b");
+		// lib-a depends on lib-b, which isn't a dependency of the project
+		symlink(
+			"../../lib-b@1.0.0/node_modules/lib-b",
+			store.join("lib-a@1.0.0/node_modules/lib-b"),
+		)
+		.unwrap();
+		symlink(
+			".pnpm/lib-a@1.0.0/node_modules/lib-a",
+			project.join("node_modules/lib-a"),
+		)
+		.unwrap();
+
+		let lib_a = resolve_from("lib-a", &project).unwrap();
+		assert_eq!(lib_a, project.join("node_modules/lib-a"));
+		assert_eq!(
+			resolve_symlinks(&resolve_from("lib-b", &lib_a).unwrap()).unwrap(),
+			store.join("lib-b@1.0.0/node_modules/lib-b")
+		);
+
+		fs::remove_dir_all(project).unwrap();
+	}
+
+	#[test]
+	fn resolves_packages_hoisted_by_pnpm() {
+		let project = temp_project();
+		write_package(&project.join("node_modules/.pnpm/node_modules/lib-c"), "lib-c");
+
+		assert_eq!(
+			resolve_from("lib-c", &project.join("src")).unwrap(),
+			project.join("node_modules/.pnpm/node_modules/lib-c")
+		);
+
+		fs::remove_dir_all(project).unwrap();
+	}
+
+	#[test]
+	fn resolves_packages_unplugged_by_yarn() {
+		let project = temp_project();
+		fs::write(project.join(".pnp.cjs"), "").unwrap();
+		write_package(
+			&project.join(".yarn/unplugged/@winglibs-lib-d-npm-0.1.0-abc123/node_modules/@winglibs/lib-d"),
+			"@winglibs/lib-d",
+		);
+
+		assert_eq!(
+			resolve_from("@winglibs/lib-d", &project).unwrap(),
+			project.join(".yarn/unplugged/@winglibs-lib-d-npm-0.1.0-abc123/node_modules/@winglibs/lib-d")
+		);
+		assert!(resolve_from("@winglibs/missing", &project).is_err());
+
+		fs::remove_dir_all(project).unwrap();
+	}
+}
@@ -0,0 +1,7 @@
+// Files in an `internal` directory can be used by the library itself,
+// but are not exported to its consumers.
+pub class Keys {
+  pub static dataPrefix(): str {
+    return "data/";
+  }
+}
@@ -1,4 +1,5 @@
 bring "./store.w" as store;
+bring "./internal/keys.w" as keys;
 
 let s = new store.Store();
 
@@ -33,3 +34,6 @@ struct FakeStruct extends store.InternalStruct {}
 let x1 = store.PublicStruct {};
 let x2 = store.InternalStruct {};
 let x3 = FakeStruct {};
+
+// internal files can be used within the package
+assert(keys.Keys.dataPrefix() == "data/");
@@ -196,6 +196,12 @@ const contributes: VSCodeExtensionContributions = {
           description:
             "Path to the Wing binary. Will be `wing` from PATH by default.",
         },
+        "wing.syntaxOnlyOnType": {
+          type: "boolean",
+          default: false,
+          description:
+            "Only check for syntax errors while typing, and type check once typing pauses. Useful for very large projects.",
+        },
       },
     },
   ],
@@ -239,6 +245,8 @@ project.tryRemoveFile(".npmrc");
 
 project.addTask("dev").exec("node scripts/dev.mjs");
 
+project.testTask.exec("tsx --test src/*.test.ts");
+
 project.deps.addDependency("@types/node@^20.11.0", DependencyType.DEVENV);
 
 project.synth();
@@ -0,0 +1,42 @@
+import { deepStrictEqual, ok } from "node:assert";
+import { describe, test } from "node:test";
+import {
+  openConsoleArgs,
+  runTestArgs,
+  showLiftsArgs,
+  testFilter,
+} from "./commands";
+
+describe("runTestArgs", () => {
+  test("passes paths and test names as single arguments", () => {
+    const filePath = `/tmp/my "app"/$(touch pwned).test.w`;
+    const testName = `it's "ok"; rm -rf ~`;
+    deepStrictEqual(runTestArgs(filePath, testName), [
+      "test",
+      filePath,
+      "--test-filter",
+      testName,
+    ]);
+  });
+
+  test("escapes the test name in the test filter", () => {
+    const name = "adds (1 + 2) * 3 [again]?";
+    const filter = testFilter(name);
+    deepStrictEqual(filter, "adds \\(1 \\+ 2\\) \\* 3 \\[again\\]\\?");
+    ok(new RegExp(filter).test(name));
+    ok(!new RegExp(filter).test("adds 1 + 2 * 3 again"));
+  });
+});
+
+describe("other commands", () => {
+  test("pass the file path as a single argument", () => {
+    const filePath = "/tmp/a b/`whoami`.main.w";
+    deepStrictEqual(showLiftsArgs(filePath), [
+      "compile",
+      filePath,
+      "--emit",
+      "lifts",
+    ]);
+    deepStrictEqual(openConsoleArgs(filePath), ["it", filePath]);
+  });
+});
@@ -0,0 +1,27 @@
+/**
+ * Arguments of the wing CLI commands run by the extension's commands. The CLI is run directly, without a shell, so
+ * file paths and test names are passed as they are and don't need any quoting.
+ */
+
+/** Arguments for running a single test of a file */
+export function runTestArgs(filePath: string, testName: string): string[] {
+  return ["test", filePath, "--test-filter", testFilter(testName)];
+}
+
+/** Arguments for showing the lifts of the classes of a file */
+export function showLiftsArgs(filePath: string): string[] {
+  return ["compile", filePath, "--emit", "lifts"];
+}
+
+/** Arguments for opening a file in the Wing Console */
+export function openConsoleArgs(filePath: string): string[] {
+  return ["it", filePath];
+}
+
+/**
+ * The test filter is a regular expression, so the characters of the test's name that have a special meaning in
+ * regular expressions are escaped.
+ */
+export function testFilter(testName: string): string {
+  return testName.replace(/[.*+?^${}()|[\]\\]/g, "\\$&");
+}
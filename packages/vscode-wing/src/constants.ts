@@ -1,6 +1,8 @@
 export const LANGUAGE_SERVER_NAME = "Wing Language Server";
 export const LANGUAGE_SERVER_ID = "wing-language-server";
 export const COMMAND_OPEN_CONSOLE = "wing.openConsole";
+export const COMMAND_RUN_TEST = "wing.runTest";
+export const COMMAND_SHOW_LIFTS = "wing.showLifts";
 
 export const CFG_WING = "wing";
 export const CFG_WING_BIN = "bin";
@@ -1,6 +1,6 @@
 import { FSWatcher, watch } from "fs";
 import { readFile } from "fs/promises";
-import { join } from "path";
+import { basename, join } from "path";
 import {
   commands,
   ExtensionContext,
@@ -9,9 +9,17 @@ import {
   window,
   debug,
   DebugConfiguration,
+  Uri,
 } from "vscode";
 import { getWingBin, updateStatusBar } from "./bin-helper";
-import { CFG_WING, CFG_WING_BIN, COMMAND_OPEN_CONSOLE } from "./constants";
+import { openConsoleArgs, runTestArgs, showLiftsArgs } from "./commands";
+import {
+  CFG_WING,
+  CFG_WING_BIN,
+  COMMAND_OPEN_CONSOLE,
+  COMMAND_RUN_TEST,
+  COMMAND_SHOW_LIFTS,
+} from "./constants";
 import { Loggers } from "./logging";
 import { LanguageServerManager } from "./lsp";
 
@@ -144,22 +152,52 @@ export async function activate(context: ExtensionContext) {
     }
   };
 
-  const wingIt = async () => {
-    const filePath = window.activeTextEditor?.document.fileName;
-    if (!filePath) {
+  // wing is run without a shell, so its arguments are never interpreted by one
+  const runInTerminal = async (terminalName: string, args: string[]) => {
+    const wingBin = await getWingBin();
+    if (!wingBin) {
       return;
     }
-    const terminalName = `Wing it: ${filePath.split("/").pop()}`;
-
     const existingTerminal = window.terminals.find(
       (t) => t.name === terminalName
     );
     if (existingTerminal) {
       existingTerminal.dispose();
     }
-    const terminal = window.createTerminal(terminalName);
+    const terminal = window.createTerminal({
+      name: terminalName,
+      shellPath: wingBin,
+      shellArgs: args,
+    });
     terminal.show();
-    terminal.sendText(`wing it "${filePath}"`);
+  };
+
+  const wingIt = async () => {
+    const filePath = window.activeTextEditor?.document.fileName;
+    if (!filePath) {
+      return;
+    }
+    await runInTerminal(
+      `Wing it: ${basename(filePath)}`,
+      openConsoleArgs(filePath)
+    );
+  };
+
+  // commands used by the code lenses of the language server
+  const runTest = async (uri: string, testName: string) => {
+    const filePath = Uri.parse(uri).fsPath;
+    await runInTerminal(
+      `Wing test: ${testName}`,
+      runTestArgs(filePath, testName)
+    );
+  };
+
+  const showLifts = async (uri: string) => {
+    const filePath = Uri.parse(uri).fsPath;
+    await runInTerminal(
+      `Wing lifts: ${basename(filePath)}`,
+      showLiftsArgs(filePath)
+    );
   };
 
   //watch for config changes
@@ -172,7 +210,9 @@ export async function activate(context: ExtensionContext) {
 
   // add command to preview wing files
   context.subscriptions.push(
-    commands.registerCommand(COMMAND_OPEN_CONSOLE, wingIt)
+    commands.registerCommand(COMMAND_OPEN_CONSOLE, wingIt),
+    commands.registerCommand(COMMAND_RUN_TEST, runTest),
+    commands.registerCommand(COMMAND_SHOW_LIFTS, showLifts)
   );
 
   await wingBinChanged();
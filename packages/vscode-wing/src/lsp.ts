@@ -47,10 +47,19 @@ export class LanguageServerManager {
             languageId: "wing",
           })
           .get<boolean>("enabled", true),
+        syntaxOnlyOnType: workspace
+          .getConfiguration("wing")
+          .get<boolean>("syntaxOnlyOnType", false),
       },
       documentSelector: [
         { scheme: "file", language: "wing", pattern: "**/*.w" },
       ],
+      synchronize: {
+        // the language server indexes the types exported by Wing files and installed libraries for auto imports
+        fileEvents: workspace.createFileSystemWatcher(
+          "**/{*.w,package.json}"
+        ),
+      },
     };
 
     // Create the language client and start the client.
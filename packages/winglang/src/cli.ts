@@ -187,6 +187,23 @@ async function main() {
     )
     .option("-v, --value <value>", "Platform-specific value in the form KEY=VALUE", addValue, [])
     .option("--values <file>", "File with platform-specific values (TOML|YAML|JSON)")
+    .option(
+      "--emit <kind>",
+      "Write a human-readable compiler dump to the output directory (ast, typed-ast, lifts, ir). Can be repeated",
+      collectVariadic,
+      []
+    )
+    .option("--bundle", "Emit the preflight code as a single self-contained file")
+    .option(
+      "--target-node <version>",
+      "Node.js version the inflight code must run on (e.g. 16). Defaults to the latest"
+    )
+    .option("--strict", "Report compiler warnings as errors")
+    .option(
+      "--api-lock",
+      "Report breaking changes in the public API of a library since it was locked in wing.api.json"
+    )
+    .option("--update-api-lock", "Lock the current public API of a library in wing.api.json")
     .hook("preAction", progressHook)
     .hook("preAction", collectAnalyticsHook)
     .action(runSubCommand("compile"));
@@ -233,6 +250,11 @@ async function main() {
     )
     .option("--no-clean", "Keep build output")
     .option("--no-stream", "Do not stream logs")
+    .option("--coverage", "Collect code coverage of the tests and report it after they run")
+    .option(
+      "-u, --update-snapshots",
+      "Overwrite the stored values of expect.snapshot calls with the values of this run"
+    )
     .option(
       "-o, --output-file <outputFile>",
       "File name to write test results to (file extension is required, supports only .json at the moment)"
@@ -7,7 +7,7 @@ import chalk from "chalk";
 import debug from "debug";
 import { glob } from "glob";
 import { formatDiagnostics } from "./diagnostics";
-import { COLORING } from "../util";
+import { COLORING, formatCompileProgress, withSpinner } from "../util";
 
 // increase the stack trace limit to 50, useful for debugging Rust panics
 // (not setting the limit too high in case of infinite recursion)
@@ -59,6 +59,51 @@ export interface CompileOptions {
    * copies of the application resources in order to run tests in parallel.
    */
   readonly testing?: boolean;
+  /**
+   * Compiler dumps to write into the output directory, for debugging the compiler
+   * (`ast`, `typed-ast`, `lifts` or `ir`)
+   */
+  readonly emit?: string[];
+  /**
+   * Whether to emit the preflight code as a single self-contained file
+   */
+  readonly bundle?: boolean;
+  /**
+   * Node.js version the inflight code must run on
+   * @default - the latest Node.js version
+   */
+  readonly targetNode?: string;
+  /**
+   * Whether to report compiler warnings as errors
+   */
+  readonly strict?: boolean;
+  /**
+   * String representing a regex pattern used to selectively filter which tests to run.
+   * Tests whose names don't match are left out of the compiled program.
+   */
+  readonly testFilter?: string;
+  /**
+   * Whether to instrument the generated code with coverage counters
+   */
+  readonly coverage?: boolean;
+  /**
+   * Whether `expect.snapshot` calls record the values they're passed for the test runner
+   */
+  readonly expectSnapshots?: "record" | "update";
+  /**
+   * Whether to report the breaking changes in the public API of a library since it was locked
+   * in `wing.api.json`
+   */
+  readonly apiLock?: boolean;
+  /**
+   * Whether to lock the current public API of a library in `wing.api.json`, whatever changed
+   */
+  readonly updateApiLock?: boolean;
+  /**
+   * Called with updates on the progress of the compiler. If it isn't set, the progress is shown
+   * next to a spinner (when progress is enabled).
+   */
+  readonly onProgress?: (progress: wingCompiler.WingCompileProgress) => void;
 }
 
 /**
@@ -88,12 +133,21 @@ export async function compile(entrypoint?: string, options?: CompileOptions): Pr
     modes: options?.testing ? ["test"] : ["compile"],
     cwd: resolve(dirname(entrypoint)),
   });
-  const compileOutput = await wingCompiler.compile(entrypoint, {
-    ...options,
-    log,
-    color: COLORING,
-    platform: options?.platform ?? ["sim"],
-  });
+  const compileWing = (onProgress?: (progress: wingCompiler.WingCompileProgress) => void) =>
+    wingCompiler.compile(entrypoint!, {
+      ...options,
+      log,
+      color: COLORING,
+      platform: options?.platform ?? ["sim"],
+      target: options?.targetNode ? { node: options.targetNode } : undefined,
+      apiLockMode: options?.updateApiLock ? "update" : options?.apiLock ? "check" : undefined,
+      onProgress,
+    });
+  const compileOutput = options?.onProgress
+    ? await compileWing(options.onProgress)
+    : await withSpinner(`Compiling ${entrypoint}...`, (update) =>
+        compileWing((progress) => update(formatCompileProgress(progress)))
+      );
   if (compileOutput.wingcErrors.length > 0) {
     // Print any errors or warnings from the compiler.
     const diagnostics = compileOutput.wingcErrors;
@@ -9,8 +9,12 @@ import {
   Range,
   DocumentUri,
   Location,
+  TextDocumentContentChangeEvent,
 } from "vscode-languageserver/node";
 
+/** How long to wait after the last change before fully checking a document in syntax-only mode */
+const CHECK_DEBOUNCE_MS = 500;
+
 export async function lsp() {
   let wingc = await wingCompiler.load({
     imports: {
@@ -23,6 +27,15 @@ export async function lsp() {
 
   const seenFiles = new Set<DocumentUri>();
 
+  // wingc only receives the changes made to open documents, so we keep their full text around to be able to
+  // restore its state after reloading it
+  const openDocuments = new Map<DocumentUri, { text: string; version: number; languageId: string }>();
+
+  // In syntax-only mode, changes are only checked for syntax errors while typing, and the full compilation of a
+  // document runs once typing pauses, or before answering a request that needs its results
+  let syntaxOnlyOnType = false;
+  const pendingChecks = new Map<DocumentUri, NodeJS.Timeout>();
+
   const raw_diagnostics: wingCompiler.WingDiagnostic[] = [];
 
   function send_diagnostic(data_ptr: number, data_len: number) {
@@ -52,6 +65,12 @@ export async function lsp() {
         return JSON.parse(result);
       }
     } catch (e) {
+      badState = true;
+      // notifications that aren't about a document have nowhere to report the crash
+      if (!args.textDocument) {
+        return null;
+      }
+
       // set status in ide
       void connection.sendDiagnostics({
         uri: args.textDocument.uri,
@@ -74,7 +93,6 @@ export async function lsp() {
         ],
       });
 
-      badState = true;
       return null;
     }
   };
@@ -89,9 +107,11 @@ export async function lsp() {
           }
         : undefined;
 
+    syntaxOnlyOnType = params.initializationOptions?.syntaxOnlyOnType ?? false;
+
     const result: InitializeResult = {
       capabilities: {
-        textDocumentSync: TextDocumentSyncKind.Full,
+        textDocumentSync: TextDocumentSyncKind.Incremental,
         completionProvider: {
           triggerCharacters: [".", ":"],
         },
@@ -99,6 +119,7 @@ export async function lsp() {
         codeActionProvider: true,
         hoverProvider: true,
         documentSymbolProvider: true,
+        codeLensProvider: { resolveProvider: false },
         definitionProvider: true,
         renameProvider: { prepareProvider: true },
       },
@@ -109,8 +130,9 @@ export async function lsp() {
   async function handle_event_and_update_diagnostics(
     wingc_handler_name: wingCompiler.WingCompilerFunction,
     params: any,
-    _uri: DocumentUri
+    uri: DocumentUri
   ) {
+    let handled = false;
     if (badState) {
       wingc = await wingCompiler.load({
         imports: {
@@ -120,18 +142,44 @@ export async function lsp() {
         },
       });
       badState = false;
+
+      // The new instance doesn't know about any of the open documents yet. The event's own document is restored
+      // last, and since its text already includes the event's changes, the event doesn't need to be handled again.
+      const documents = [...openDocuments].filter(([documentUri]) => documentUri !== uri);
+      if (openDocuments.has(uri)) {
+        documents.push([uri, openDocuments.get(uri)!]);
+        handled = true;
+      }
+      for (const [documentUri, document] of documents) {
+        raw_diagnostics.length = 0;
+        callWing("wingc_on_did_open_text_document", {
+          textDocument: { uri: documentUri, ...document },
+        });
+      }
+    }
+    if (!handled) {
+      // Reset diagnostics list
+      raw_diagnostics.length = 0;
+      // Call wingc handler
+      const result = callWing(wingc_handler_name, params);
+      // wingc couldn't apply the changes since it doesn't know the document's text, so it's opened again with its
+      // full text (which already includes the changes)
+      const document = openDocuments.get(uri);
+      if (result?.needsFullText && document) {
+        raw_diagnostics.length = 0;
+        callWing("wingc_on_did_open_text_document", { textDocument: { uri, ...document } });
+        handled = true;
+      }
     }
-    // Reset diagnostics list
-    raw_diagnostics.length = 0;
-    // Call wingc handler
-    callWing(wingc_handler_name, params);
 
     const allDiagnostics = new Map<DocumentUri, Diagnostic[]>();
 
     // set empty list of diagnostics for files that have been seen before
-    // this way even if we don't get a diagnostic for a file, we clear out the old ones
-    for (const uri of seenFiles) {
-      allDiagnostics.set(uri, []);
+    // this way even if we don't get a diagnostic for a file, we clear out the old ones.
+    // A syntax-only pass doesn't know about the other files, so their diagnostics are left alone.
+    const syntaxOnly = !handled && wingc_handler_name === "wingc_on_did_change_text_document_syntax";
+    for (const seenUri of syntaxOnly ? [uri] : seenFiles) {
+      allDiagnostics.set(seenUri, []);
     }
 
     for (const rd of raw_diagnostics) {
@@ -146,7 +194,7 @@ export async function lsp() {
           Range.create(rd.span.start.line, rd.span.start.col, rd.span.end.line, rd.span.end.col),
           message,
           undefined,
-          undefined,
+          rd.code,
           undefined,
           rd.annotations.map((a) => ({
             location: Location.create(
@@ -190,7 +238,33 @@ export async function lsp() {
     }
   }
 
+  function scheduleCheck(uri: DocumentUri) {
+    clearTimeout(pendingChecks.get(uri));
+    pendingChecks.set(uri, setTimeout(() => void flushCheck(uri), CHECK_DEBOUNCE_MS));
+  }
+
+  async function flushCheck(uri: DocumentUri) {
+    const timer = pendingChecks.get(uri);
+    if (timer === undefined) {
+      return;
+    }
+    clearTimeout(timer);
+    pendingChecks.delete(uri);
+    await handle_event_and_update_diagnostics(
+      "wingc_on_check_text_document",
+      { textDocument: { uri } },
+      uri
+    );
+  }
+
+  // Requests are answered from the results of the last full compilation, so it must include all changes
+  async function flushChecks() {
+    await Promise.all([...pendingChecks.keys()].map(flushCheck));
+  }
+
   connection.onDidOpenTextDocument((params) => {
+    const { uri, text, version, languageId } = params.textDocument;
+    openDocuments.set(uri, { text, version, languageId });
     void handle_event_and_update_diagnostics(
       "wingc_on_did_open_text_document",
       params,
@@ -198,6 +272,20 @@ export async function lsp() {
     );
   });
   connection.onDidChangeTextDocument((params) => {
+    const document = openDocuments.get(params.textDocument.uri);
+    if (document) {
+      document.text = applyChanges(document.text, params.contentChanges);
+      document.version = params.textDocument.version;
+    }
+    if (syntaxOnlyOnType) {
+      void handle_event_and_update_diagnostics(
+        "wingc_on_did_change_text_document_syntax",
+        params,
+        params.textDocument.uri
+      );
+      scheduleCheck(params.textDocument.uri);
+      return;
+    }
     void handle_event_and_update_diagnostics(
       "wingc_on_did_change_text_document",
       params,
@@ -205,29 +293,87 @@ export async function lsp() {
     );
   });
   connection.onCompletion(async (params) => {
+    await flushChecks();
     return callWing("wingc_on_completion", params);
   });
   connection.onSignatureHelp(async (params) => {
+    await flushChecks();
     return callWing("wingc_on_signature_help", params);
   });
   connection.onDefinition(async (params) => {
+    await flushChecks();
     return callWing("wingc_on_goto_definition", params);
   });
   connection.onDocumentSymbol(async (params) => {
+    await flushChecks();
     return callWing("wingc_on_document_symbol", params);
   });
+  connection.onCodeLens(async (params) => {
+    await flushChecks();
+    return callWing("wingc_on_code_lens", params);
+  });
   connection.onRenameRequest(async (params) => {
+    await flushChecks();
     return callWing("wingc_on_rename", params);
   });
   connection.onPrepareRename(async (params) => {
+    await flushChecks();
     return callWing("wingc_on_prepare_rename", params);
   });
   connection.onHover(async (params) => {
+    await flushChecks();
     return callWing("wingc_on_hover", params);
   });
   connection.onCodeAction(async (params) => {
+    await flushChecks();
     return callWing("wingc_on_code_action", params);
   });
 
+  // keeps the index of the types that can be auto imported up to date
+  connection.onDidChangeWatchedFiles((params) => {
+    callWing("wingc_on_did_change_watched_files", params);
+  });
+
+  connection.onDidCloseTextDocument((params) => {
+    clearTimeout(pendingChecks.get(params.textDocument.uri));
+    pendingChecks.delete(params.textDocument.uri);
+    openDocuments.delete(params.textDocument.uri);
+    void handle_event_and_update_diagnostics(
+      "wingc_on_did_close_text_document",
+      params,
+      params.textDocument.uri
+    );
+  });
+
   connection.listen();
 }
+
+/**
+ * Applies incremental changes to the text of a document.
+ * LSP positions count characters in UTF-16 code units, just like JavaScript strings do.
+ */
+function applyChanges(text: string, changes: TextDocumentContentChangeEvent[]): string {
+  for (const change of changes) {
+    if (!("range" in change)) {
+      text = change.text;
+      continue;
+    }
+    const lineStarts = [0];
+    for (let i = 0; i < text.length; i++) {
+      if (text[i] === "\n") {
+        lineStarts.push(i + 1);
+      }
+    }
+    const offsetAt = ({ line, character }: { line: number; character: number }) => {
+      if (line >= lineStarts.length) {
+        return text.length;
+      }
+      const lineEnd = line + 1 < lineStarts.length ? lineStarts[line + 1] - 1 : text.length;
+      return Math.min(lineStarts[line] + character, lineEnd);
+    };
+    const start = offsetAt(change.range.start);
+    const end = Math.max(start, offsetAt(change.range.end));
+    text = text.slice(0, start) + change.text + text.slice(end);
+  }
+  return text;
+}
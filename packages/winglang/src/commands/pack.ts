@@ -31,6 +31,7 @@ const defaultGlobs = [
 ];
 const compilerOutputFolder = "$lib";
 const dotWingDir = ".wing";
+const apiLockFile = "wing.api.json";
 
 export interface PackageOptions {
   /**
@@ -105,12 +106,25 @@ export async function pack(options: PackageOptions = {}): Promise<string> {
     const nodeModulesPath = path.join(workdir, "node_modules");
     await fs.symlink(path.join(userDir, "node_modules"), nodeModulesPath);
 
+    // if the library's public API is locked, check it for breaking changes while compiling
+    const apiLockPath = path.join(userDir, apiLockFile);
+    const apiLock = await exists(apiLockPath);
+    if (apiLock) {
+      await fs.copyFile(apiLockPath, path.join(workdir, apiLockFile));
+    }
+
     // check that the library compiles to the "sim" target
     console.log('Compiling to the "sim" target...');
     const compilerOutputDir = await compile(workdir, {
       platform: [BuiltinPlatform.SIM],
+      apiLock,
     });
 
+    // keep the API lock up to date with the API that's being packaged
+    if (apiLock) {
+      await fs.copyFile(path.join(workdir, apiLockFile), apiLockPath);
+    }
+
     const pkgJsonPath = path.join(workdir, "package.json");
     const pkgJson = JSON.parse(await fs.readFile(pkgJsonPath, "utf8"));
 
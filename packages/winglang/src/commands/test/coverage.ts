@@ -0,0 +1,165 @@
+import { existsSync, mkdirSync, readdirSync, readFileSync, writeFileSync } from "fs";
+import { dirname, join, relative } from "path";
+import chalk from "chalk";
+
+/**
+ * The file the compiler maps the coverage counters to the Wing code they count in.
+ */
+const COVERAGE_MAP_FILE = join(".wing", "coverage-map.json");
+
+/**
+ * The directory instrumented programs write their counters to, one file per process.
+ */
+const COVERAGE_HITS_DIR = join(".wing", "coverage");
+
+interface CoverageCounter {
+  readonly kind: "statement" | "branch";
+  readonly span: {
+    readonly file_id: string;
+    readonly start: { readonly line: number; readonly col: number };
+    readonly end: { readonly line: number; readonly col: number };
+  };
+}
+
+interface CoveredCode {
+  /** 1-based line the code starts at */
+  readonly line: number;
+  hits: number;
+}
+
+interface FileCoverage {
+  readonly statements: Map<string, CoveredCode>;
+  readonly branches: Map<string, CoveredCode>;
+}
+
+/**
+ * Merges the coverage of the programs compiled by `wing test --coverage`. A source file
+ * brought by several test files is reported once, with the counts of all of them.
+ */
+export class CoverageCollector {
+  private readonly files = new Map<string, FileCoverage>();
+
+  /**
+   * Adds the counts recorded while testing the program compiled to `synthDir`.
+   */
+  public collect(synthDir: string) {
+    const mapFile = join(synthDir, COVERAGE_MAP_FILE);
+    if (!existsSync(mapFile)) {
+      return;
+    }
+    const counters: CoverageCounter[] = JSON.parse(readFileSync(mapFile, "utf-8"));
+
+    const hits = new Array<number>(counters.length).fill(0);
+    const hitsDir = join(synthDir, COVERAGE_HITS_DIR);
+    if (existsSync(hitsDir)) {
+      for (const file of readdirSync(hitsDir)) {
+        const counts: Record<string, number> = JSON.parse(
+          readFileSync(join(hitsDir, file), "utf-8")
+        );
+        for (const [id, count] of Object.entries(counts)) {
+          hits[Number(id)] += count;
+        }
+      }
+    }
+
+    counters.forEach((counter, id) => {
+      const { file_id, start, end } = counter.span;
+      let file = this.files.get(file_id);
+      if (!file) {
+        file = { statements: new Map(), branches: new Map() };
+        this.files.set(file_id, file);
+      }
+      const code = counter.kind === "statement" ? file.statements : file.branches;
+      const key = `${start.line}:${start.col}-${end.line}:${end.col}`;
+      const covered = code.get(key) ?? { line: start.line + 1, hits: 0 };
+      covered.hits += hits[id];
+      code.set(key, covered);
+    });
+  }
+
+  /**
+   * Renders a table with the percentage of statements and branches each file covers,
+   * and the lines that never ran.
+   */
+  public renderReport(): string {
+    const rows = [...this.files.entries()]
+      .sort(([a], [b]) => a.localeCompare(b))
+      .map(([file, coverage]) => {
+        const statements = [...coverage.statements.values()];
+        const branches = [...coverage.branches.values()];
+        const uncoveredLines = [
+          ...new Set(statements.filter((s) => s.hits === 0).map((s) => s.line)),
+        ].sort((a, b) => a - b);
+        return [
+          relative(process.cwd(), file),
+          percentage(statements),
+          percentage(branches),
+          renderLineRanges(uncoveredLines),
+        ];
+      });
+
+    const header = ["File", "Stmts", "Branches", "Uncovered lines"];
+    const widths = header.map((h, i) => Math.max(h.length, ...rows.map((row) => row[i].length)));
+    const renderRow = (row: string[]) => row.map((cell, i) => cell.padEnd(widths[i])).join("  ");
+
+    return [
+      chalk.bold("Coverage"),
+      chalk.gray(renderRow(header)),
+      ...rows.map((row) => {
+        const text = renderRow(row);
+        return row[3] === "" ? chalk.green(text) : text;
+      }),
+    ].join("\n");
+  }
+
+  /**
+   * Writes the coverage in the lcov format, which most coverage tools and editors can display.
+   */
+  public writeLcov(file: string) {
+    const out = new Array<string>();
+    for (const [source, coverage] of this.files) {
+      out.push("TN:", `SF:${source}`);
+
+      const lines = new Map<number, number>();
+      for (const statement of coverage.statements.values()) {
+        lines.set(statement.line, Math.max(lines.get(statement.line) ?? 0, statement.hits));
+      }
+      const branches = [...coverage.branches.values()];
+      branches.forEach((branch, i) => {
+        out.push(`BRDA:${branch.line},0,${i},${branch.hits > 0 ? branch.hits : "-"}`);
+      });
+      out.push(`BRF:${branches.length}`, `BRH:${branches.filter((b) => b.hits > 0).length}`);
+      for (const [line, hits] of [...lines].sort(([a], [b]) => a - b)) {
+        out.push(`DA:${line},${hits}`);
+      }
+      out.push(`LF:${lines.size}`, `LH:${[...lines.values()].filter((h) => h > 0).length}`);
+      out.push("end_of_record");
+    }
+
+    mkdirSync(dirname(file), { recursive: true });
+    writeFileSync(file, out.join("\n") + "\n");
+  }
+}
+
+function percentage(code: CoveredCode[]): string {
+  if (code.length === 0) {
+    return "-";
+  }
+  const covered = code.filter((c) => c.hits > 0).length;
+  return `${((covered / code.length) * 100).toFixed(2)}%`;
+}
+
+/**
+ * Renders sorted line numbers as ranges, e.g. "3, 7-9".
+ */
+function renderLineRanges(lines: number[]): string {
+  const ranges = new Array<string>();
+  let start = 0;
+  for (let i = 1; i <= lines.length; i++) {
+    if (i === lines.length || lines[i] !== lines[i - 1] + 1) {
+      ranges.push(start === i - 1 ? `${lines[start]}` : `${lines[start]}-${lines[i - 1]}`);
+      start = i;
+    }
+  }
+  return ranges.join(", ");
+}
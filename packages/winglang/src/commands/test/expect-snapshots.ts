@@ -0,0 +1,81 @@
+import { existsSync, readFileSync, writeFileSync } from "fs";
+import { join, relative } from "path";
+import chalk from "chalk";
+
+/**
+ * The file the compiler lists the `expect.snapshot` calls of a program in, in the order of their indices.
+ */
+const SNAPSHOT_MANIFEST_FILE = join(".wing", "snapshots.json");
+
+/**
+ * The directory the program records the value passed to each `expect.snapshot` call in (`<index>.json`).
+ */
+const SNAPSHOT_VALUES_DIR = join(".wing", "snapshots");
+
+interface SnapshotCall {
+  /** The key of the snapshot in its snapshot file */
+  readonly id: string;
+  /** The file the snapshot is stored in */
+  readonly snapshot_file: string;
+}
+
+export interface StoreExpectSnapshotsOptions {
+  /** Overwrite the snapshots that are already stored */
+  readonly update: boolean;
+  /**
+   * Remove the stored snapshots of calls that no longer exist. Only safe when the program
+   * includes all the tests of its files.
+   */
+  readonly prune: boolean;
+}
+
+/**
+ * Stores the values recorded by the `expect.snapshot` calls of the program compiled to `synthDir`
+ * in their snapshot files. New snapshots are always added.
+ */
+export function storeExpectSnapshots(synthDir: string, options: StoreExpectSnapshotsOptions) {
+  const manifestFile = join(synthDir, SNAPSHOT_MANIFEST_FILE);
+  if (!existsSync(manifestFile)) {
+    return;
+  }
+  const calls: SnapshotCall[] = JSON.parse(readFileSync(manifestFile, "utf-8"));
+
+  const stored = new Map<string, Record<string, unknown>>();
+  const files = new Map<string, Record<string, unknown>>();
+  calls.forEach((call, index) => {
+    if (!stored.has(call.snapshot_file)) {
+      const snapshots = readSnapshotFile(call.snapshot_file);
+      stored.set(call.snapshot_file, snapshots);
+      files.set(call.snapshot_file, options.prune ? {} : { ...snapshots });
+    }
+    const storedSnapshots = stored.get(call.snapshot_file)!;
+    const snapshots = files.get(call.snapshot_file)!;
+
+    const valueFile = join(synthDir, SNAPSHOT_VALUES_DIR, `${index}.json`);
+    if (existsSync(valueFile) && (options.update || !(call.id in storedSnapshots))) {
+      snapshots[call.id] = JSON.parse(readFileSync(valueFile, "utf-8"));
+    } else if (call.id in storedSnapshots) {
+      // keep the stored snapshot (also when the call didn't run, e.g. because its test failed early)
+      snapshots[call.id] = storedSnapshots[call.id];
+    }
+  });
+
+  for (const [file, snapshots] of files) {
+    const sorted = Object.fromEntries(
+      Object.entries(snapshots).sort(([a], [b]) => a.localeCompare(b))
+    );
+    const content = JSON.stringify(sorted, undefined, 2) + "\n";
+    if (existsSync(file) && readFileSync(file, "utf-8") === content) {
+      continue;
+    }
+    writeFileSync(file, content);
+    console.log(chalk.gray(`Wrote snapshots to ${relative(process.cwd(), file)}`));
+  }
+}
+
+function readSnapshotFile(file: string): Record<string, unknown> {
+  if (!existsSync(file)) {
+    return {};
+  }
+  return JSON.parse(readFileSync(file, "utf-8"));
+}
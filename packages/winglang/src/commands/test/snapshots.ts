@@ -84,7 +84,8 @@ export async function captureSnapshot(
   return withSpinner(`Snapshot ${renderTestName(snapshotFile)}...`, async () => {
     // we need to compile again in order for snapshots because we can't afford the snapshot to be
     // based on a random root id (which is the default for tests)
-    const snapshotDir = await compile(entrypoint, options);
+    // (and the snapshot mustn't depend on whether coverage is collected)
+    const snapshotDir = await compile(entrypoint, { ...options, coverage: false });
 
     // take a snapshot of the synthesis output
     const newSnapshot = await createMarkdownSnapshot(basename(snapshotFile), snapshotDir);
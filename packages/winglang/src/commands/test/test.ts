@@ -12,12 +12,14 @@ import chalk from "chalk";
 import debug from "debug";
 import { glob } from "glob";
 import { nanoid } from "nanoid";
+import { CoverageCollector } from "./coverage";
+import { storeExpectSnapshots } from "./expect-snapshots";
 import { printResults, validateOutputFilePath, writeResultsToFile } from "./results";
 import { SnapshotMode, SnapshotResult, captureSnapshot, determineSnapshotMode } from "./snapshots";
 import { SNAPSHOT_ERROR_PREFIX } from "./snapshots-help";
 import { TraceProcessor } from "./trace-processor";
 import { renderTestName } from "./util";
-import { withSpinner } from "../../util";
+import { formatCompileProgress, withSpinner } from "../../util";
 import { compile, CompileOptions } from "../compile";
 import { SpinnerStream } from "../spinner-stream";
 
@@ -40,10 +42,6 @@ export interface TestOptions extends CompileOptions {
    * The name of the output file.
    */
   readonly outputFile?: string;
-  /**
-   * String representing a regex pattern used to selectively filter which tests to run.
-   */
-  readonly testFilter?: string;
   /**
    * How many times failed tests should be retried. default is one
    */
@@ -62,6 +60,17 @@ export interface TestOptions extends CompileOptions {
    * Number of tests to be run in parallel. 0 or undefined will run all at once.
    */
   readonly parallel?: number;
+
+  /**
+   * Whether to collect code coverage of the tests (only supported on the sim platform).
+   */
+  readonly coverage?: boolean;
+
+  /**
+   * Whether to overwrite the stored values of `expect.snapshot` calls with the values of this run
+   * (only supported on the sim platform).
+   */
+  readonly updateSnapshots?: boolean;
 }
 
 const TEST_FILE_PATTERNS = ["**/*.test.w", "**/{main,*.main}.{w,ts}"];
@@ -115,13 +124,24 @@ export async function test(entrypoints: string[], options: TestOptions): Promise
   const startTime = Date.now();
   const results: SingleTestResult[] = [];
   process.env.WING_TARGET = determineTargetFromPlatforms(options.platform ?? []);
+  let coverage: CoverageCollector | undefined;
+  if (options.coverage) {
+    if (process.env.WING_TARGET === BuiltinPlatform.SIM) {
+      coverage = new CoverageCollector();
+    } else {
+      console.warn("Coverage is only collected when testing on the sim platform.");
+    }
+  }
+  if (options.updateSnapshots && process.env.WING_TARGET !== BuiltinPlatform.SIM) {
+    console.warn("Snapshots of expect.snapshot calls are only updated when testing on the sim platform.");
+  }
   const testFile = async (
     entrypoint: string,
     retries: number = options.retry || 1
   ): Promise<void> => {
     const testName = renderTestName(entrypoint);
     try {
-      const singleTestResults = await testOne(testName, entrypoint, options);
+      const singleTestResults = await testOne(testName, entrypoint, options, coverage);
       if (singleTestResults.results.some((t) => !t.pass) && retries > 1) {
         console.log(`Retrying failed tests. ${retries - 1} retries left.`);
         return await testFile(entrypoint, retries - 1);
@@ -160,6 +180,12 @@ export async function test(entrypoints: string[], options: TestOptions): Promise
 
   const testDuration = Date.now() - startTime;
   printResults(results, testDuration);
+  if (coverage) {
+    console.log(coverage.renderReport());
+    const lcovFile = join(options.targetDir ?? "target", "coverage", "lcov.info");
+    coverage.writeLcov(lcovFile);
+    console.log(chalk.gray(`Coverage written to ${lcovFile}`));
+  }
   if (options.outputFile) {
     await writeResultsToFile(results, testDuration, options.outputFile, options.platform);
   }
@@ -185,7 +211,8 @@ export type SingleTestResult = {
 async function testOne(
   testName: string,
   entrypoint: string,
-  options: TestOptions
+  options: TestOptions,
+  coverage?: CoverageCollector
 ): Promise<SingleTestResult> {
   const target = determineTargetFromPlatforms(options.platform);
 
@@ -195,20 +222,29 @@ async function testOne(
   const testOptions = {
     ...options,
     rootId: options.rootId ?? target === BuiltinPlatform.SIM ? "root" : `Test.${nanoid(10)}`,
+    coverage: coverage !== undefined,
+    // the values of `expect.snapshot` calls can only be recorded when tests run locally
+    expectSnapshots:
+      target === BuiltinPlatform.SIM
+        ? options.updateSnapshots
+          ? ("update" as const)
+          : ("record" as const)
+        : undefined,
   };
 
   let results: std.TestResult[] = [];
   if (shouldExecute) {
     const synthDir = await withSpinner(
       `Compiling ${renderTestName(entrypoint)} to ${target}...`,
-      async () =>
+      async (update) =>
         compile(entrypoint, {
           ...testOptions,
           testing: true,
+          onProgress: (progress) => update(formatCompileProgress(progress)),
         })
     );
 
-    results = await executeTest(synthDir, target, testOptions);
+    results = await executeTest(synthDir, target, testOptions, coverage);
   }
 
   // if one of the tests failed, return the results without updating any snapshots.
@@ -230,11 +266,12 @@ async function testOne(
 async function executeTest(
   synthDir: string,
   target: string | undefined,
-  options: TestOptions
+  options: TestOptions,
+  coverage?: CoverageCollector
 ): Promise<std.TestResult[]> {
   switch (target) {
     case BuiltinPlatform.SIM:
-      return testSimulator(synthDir, options);
+      return testSimulator(synthDir, options, coverage);
     case BuiltinPlatform.TF_AZURE:
     case BuiltinPlatform.TF_AWS:
     case BuiltinPlatform.TF_GCP:
@@ -442,7 +479,11 @@ function shouldSkipTrace(trace: std.Trace): boolean {
   }
 }
 
-async function testSimulator(synthDir: string, options: TestOptions) {
+async function testSimulator(
+  synthDir: string,
+  options: TestOptions,
+  coverage?: CoverageCollector
+) {
   const s = new simulator.Simulator({ simfile: synthDir });
   const { clean, testFilter } = options;
 
@@ -527,6 +568,17 @@ async function testSimulator(synthDir: string, options: TestOptions) {
     args = { methods: JSON.parse(readFileSync(join(synthDir, "usage_context.json")).toString()) };
   }
 
+  // the simulator has stopped, so all the processes running inflight code have written their counts
+  coverage?.collect(synthDir);
+
+  if (options.expectSnapshots) {
+    storeExpectSnapshots(synthDir, {
+      update: options.expectSnapshots === "update",
+      // tests that don't match the filter aren't compiled, so their snapshots must be kept
+      prune: options.expectSnapshots === "update" && !testFilter,
+    });
+  }
+
   if (clean) {
     try {
       rmSync(synthDir, { recursive: true, force: true });
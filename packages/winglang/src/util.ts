@@ -2,8 +2,9 @@ import * as cp from "child_process";
 import { readdirSync } from "fs";
 import * as fs from "fs/promises";
 import { tmpdir } from "os";
-import { join } from "path";
+import { join, relative } from "path";
 import { promisify } from "util";
+import type { WingCompileProgress } from "@winglang/compiler";
 import chalk from "chalk";
 
 export const DEFAULT_PARALLEL_SIZE = 10;
@@ -25,10 +26,17 @@ export function normalPath(path: string) {
   }
 }
 
-export async function withSpinner<T>(message: string, fn: () => Promise<T>): Promise<T> {
+/**
+ * Runs a function while showing a spinner with a message, if progress is enabled. The function
+ * can show its progress next to the message with the `update` callback it's passed.
+ */
+export async function withSpinner<T>(
+  message: string,
+  fn: (update: (status: string) => void) => Promise<T>
+): Promise<T> {
   // if progress is disabled, just run the function
   if (!process.env.PROGRESS) {
-    return fn();
+    return fn(() => {});
   }
 
   const ora = await import("ora").then((m) => m.default);
@@ -38,7 +46,9 @@ export async function withSpinner<T>(message: string, fn: () => Promise<T>): Pro
     text: `${message}\n`,
   }).start();
   try {
-    const result = await fn();
+    const result = await fn((status) => {
+      spinner.text = `${message} ${status}\n`;
+    });
     spinner.succeed();
     return result;
   } catch (e) {
@@ -47,6 +57,14 @@ export async function withSpinner<T>(message: string, fn: () => Promise<T>): Pro
   }
 }
 
+/**
+ * Describes the progress of the compiler, e.g. "42% (type-checking src/bucket.w)".
+ */
+export function formatCompileProgress(progress: WingCompileProgress): string {
+  const file = progress.file ? ` ${relative(process.cwd(), progress.file)}` : "";
+  return chalk.gray(`${progress.percent}% (${progress.phase}${file})`);
+}
+
 /**
  * Execute a command and return its stdout.
  */
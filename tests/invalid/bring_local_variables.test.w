@@ -1,3 +1,6 @@
 bring "./file_with_variables.w" as stuff;
 
 new stuff.Bar();
+
+log("{stuff.x}");
+//         ^ Symbol "x" is private
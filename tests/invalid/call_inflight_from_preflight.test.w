@@ -11,3 +11,15 @@ let foo = new Foo();
 // Call an inflight method
 foo.do();
 //^^^^^^ Cannot call into inflight phase while preflight
+
+class Bar {
+  foo: Foo;
+  new() {
+    this.foo = new Foo();
+  }
+  pub setup() {
+    // Call an inflight method from a preflight method
+    this.foo.do();
+//  ^^^^^^^^^^^^^ Cannot call into inflight phase while preflight
+  }
+}
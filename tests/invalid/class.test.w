@@ -236,3 +236,32 @@ if true {
     // ^ internal class "PublicClass" must be declared at the top-level of the file or marked private
   }
 }
+
+class PartiallyInitialized {
+  a: num;
+//^ Preflight field "a" is not initialized on all paths of the constructor
+  b: num;
+//^ Preflight field "b" is not initialized on all paths of the constructor
+  c: num;
+  inflight d: str;
+         //^ Inflight field "d" is not initialized on all paths of the constructor
+  new(flag: bool) {
+    if flag {
+      this.a = 1;
+    }
+    for i in 0..3 {
+      this.b = i;
+    }
+    if flag {
+      this.c = 1;
+    } else {
+      throw "no c";
+    }
+  }
+  inflight new() {
+    if true {
+      return;
+    }
+    this.d = "d";
+  }
+}
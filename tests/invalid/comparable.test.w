@@ -0,0 +1,22 @@
+class Task {
+  pub inflight compareTo(other: Task): num {
+    return 0;
+  }
+}
+
+let t1 = new Task() as "t1";
+let t2 = new Task() as "t2";
+
+let a = t1 < t2;
+//      ^^^^^^^ Cannot compare "Task" in preflight phase because its "compareTo" method is inflight
+
+inflight () => {
+  let b = t1 < 3;
+//             ^ Expected type to be "Task", but got "num" instead
+};
+
+class Point {}
+
+let p = new Point();
+let c = p < 1;
+//      ^ Expected type to be "num", but got "Point" instead
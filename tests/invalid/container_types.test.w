@@ -45,3 +45,8 @@ let ss2: MutSet<num> = MutSet<num>[true];
 let ss3 = MutSet<bool> [false, true];
 let ss4 = ss3.copyMut();
 //            ^^^^^^^ Unknown symbol "copyMut"
+
+let ofMixed = Array.of(1, "a");
+//                        ^^^ Expected type to be "num", but got "str" instead
+let ofNothing = Array.of();
+//              ^^^^^^^^^^ Unable to infer the type argument of a generic function from the arguments it was called with
@@ -0,0 +1,21 @@
+class Greeter {
+  /// Greets someone
+  /// @param name the name of the person to greet
+  /// @param greting the greeting to use
+  /// @returns the greeting
+  pub greet(name: str, greeting: str): str {
+//    ^^^^^ Doc comment documents parameter "greting", but the function has no such parameter
+    return "{greeting}, {name}!";
+  }
+
+  /// @param times how many times to wave
+  /// @throws if `times` is negative
+  pub wave(count: num) {}
+//    ^^^^ Doc comment documents parameter "times", but the function has no such parameter
+}
+
+interface IGreeter {
+  /// @param nmae the name of the person to greet
+  greet(name: str): str;
+//^^^^^ Doc comment documents parameter "nmae", but the function has no such parameter
+}
@@ -14,3 +14,34 @@ if true {
       FOUR, FIVE, SIX
   }
 }
+
+enum Color {
+  Red = "red",
+  Crimson = "red",
+//^^^^^^^ Duplicated enum backing value "red"
+  Blue = "Green",
+  Green
+//^^^^^ Duplicated enum backing value "Green"
+}
+
+let name = "x";
+enum Letter {
+  X = "{name}"
+//    ^^^^^^^^ String interpolation is not allowed in enum values
+}
+
+flags enum Permission {
+  Read = "read",
+//       ^^^^^^ Values of a flags enum can't have backing values
+  Write
+}
+
+flags enum Mode {
+  Fast, Safe
+}
+
+let both = SomeEnum.ONE | SomeEnum.TWO;
+//         ^^^^^^^^^^^^ Operator '|' expects values of a flags enum, found "SomeEnum"
+
+let mixed = Permission.Write & Mode.Fast;
+//                             ^^^^^^^^^ Expected type to be "Permission", but got "Mode" instead
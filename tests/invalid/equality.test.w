@@ -76,4 +76,18 @@ let f1 = Set<num>[1,2,3,3,3,2];
 let f2 = MutSet<num>[1,2,3,3,3,2];
 
 assert(f1 == f2);
-// this is ok
\ No newline at end of file
+// this is ok
+
+class Cat {}
+class Dog {}
+interface IPet {}
+
+let cat = new Cat();
+let dog = new Dog();
+let pet: IPet? = nil;
+
+assert(cat == dog);
+//     ^^^^^^^^^^ Cannot compare "Cat" with "Dog", objects of unrelated types are compared by reference and are never equal
+
+assert(cat != pet);
+// this is ok, a subclass of Cat could implement IPet
@@ -2,9 +2,20 @@
 
 bring cloud;
 
-let x = 5;
+let x = (): num => { return 5; };
 let y = ["hello", "world"];
-let z = new cloud.Bucket();
+//^^^^^^^^^^^^^^^^^^^^^^^^^ Module files cannot have statements besides classes, interfaces, enums, structs, public or internal variables and functions. Rename the file to end with `.main.w` or `.test.w` to make this an entrypoint file.
+internal let z = new cloud.Bucket();
+//               ^^^^^^^^^^^^^^^^^^ Module-level variables in library files cannot hold preflight objects or inflight closures, found "Bucket"
+pub let handler = inflight () => {};
+//                ^^^^^^^^^^^^^^^^^ Module-level variables in library files cannot hold preflight objects or inflight closures, found "inflight (): void"
+pub let var counter = 0;
+//          ^^^^^^^ Variable "counter" cannot be reassignable because it is public
+
+pub fn helper() {
+  pub let inner = 1;
+  //      ^^^^^ Only module-level variables and functions can have an access modifier
+}
 
 pub class Bar {
   x: num;
@@ -6,3 +6,14 @@ for test in bucket {
         //  ^^^^^^ Unable to iterate over \\"Bucket\\"
   log(test);
 }
+
+for item, i in Set<num>[1, 2] {
+//             ^^^^^^^^^^^^^^ Unable to iterate over "Set<num>" with two loop variables
+}
+
+for key, value in { "a" => 1 } {
+  let k: num = key;
+//             ^^^ Expected type to be "num", but got "str" instead
+  let v: str = value;
+//             ^^^^^ Expected type to be "str", but got "num" instead
+}
@@ -31,4 +31,30 @@ class r impl I3 {
       // ^ Resource "r" does not implement method "method1" of interface "I3"
       // ^ Resource "r" does not implement method "method2" of interface "I3"
       // ^ Resource "r" does not implement method "method3" of interface "I3"
-}
\ No newline at end of file
+}
+
+interface IFactory {
+  static create(): IFactory;
+  describe(): str;
+}
+
+class NotStatic impl IFactory {
+  pub create(): IFactory {
+  //  ^^^^^^ Method "create" is static in interface "IFactory" but its implementation in "NotStatic" is not static
+    return new NotStatic();
+  }
+  pub static describe(): str {
+  //         ^^^^^^^^ Method "describe" is not static in interface "IFactory" but its implementation in "NotStatic" is static
+    return "";
+  }
+}
+
+class MissingStatic impl IFactory {
+  //  ^^^^^^^^^^^^^ Class "MissingStatic" does not implement method "create" of interface "IFactory"
+  pub describe(): str {
+    return "";
+  }
+}
+
+IFactory.create();
+//       ^^^^^^ Cannot call method "create" of interface "IFactory" directly, it must be called on a class that implements the interface
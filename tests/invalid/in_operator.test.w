@@ -0,0 +1,10 @@
+let m = {"a" => 1};
+let found = 1 in m;
+//          ^ Expected type to be "str", but got "num" instead
+
+let arr = [1, 2, 3];
+let found2 = "a" in arr;
+//           ^^^ Expected type to be "num", but got "str" instead
+
+let found3 = 1 in 5;
+//                ^ Operator 'in' expects a Map, Set, Array or str on its right side, found "num"
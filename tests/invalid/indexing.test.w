@@ -9,6 +9,22 @@ arr[0] = 5;
 arr["key"];
 // ^ error: Expected type to be "num", but got "str" instead
 
+arr[3];
+//  ^ error: Index 3 is out of bounds for array of length 3
+
+arr[-4];
+//  ^^ error: Index -4 is out of bounds for array of length 3
+
+arr[1.5];
+//  ^^^ error: Array index must be an integer, found 1.5
+
+[1, 2][2];
+//     ^ error: Index 2 is out of bounds for array of length 2
+
+let growing = MutArray<num>[1, 2, 3];
+growing[0.5];
+//      ^^^ error: Array index must be an integer, found 0.5
+
 let b = new cloud.Bucket();
 b[0] = 5;
 // ^ error: Type "Bucket" is not indexable
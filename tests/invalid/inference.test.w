@@ -71,9 +71,9 @@ let anotherEmptyArray = [];
 Json { cool: anotherEmptyArray };
 
 class NeedAnnotations {
-  returns() {
+  pub returns() {
     return true;
-//  ^^^^^^^^^^^^ Unexpected return value from void function. Return type annotations are required for methods.
+//  ^^^^^^^^^^^^ Unexpected return value from void function. Return type annotations are required for non-private methods and functions.
   }
   args(nice) {
 //     ^^^^ Missing required type annotation for method signature
@@ -121,3 +121,11 @@ takesOptionalClosure((a) => {
   //     ^^ Expected type to be "str", but got "num" instead
 });
 
+
+let noCommonType = (flag: bool) => {
+  if flag {
+    return 1;
+  }
+  return "one";
+//       ^^^^^ Expected type to be "num", but got "str" instead
+};
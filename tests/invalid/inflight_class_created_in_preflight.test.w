@@ -18,4 +18,7 @@ class PreflightClass {
 inflight () => {
   new PreflightClass();
 //    ^^^^^^^^^^^^^^^^^^^^ Cannot create preflight class "PreflightClass" in inflight phase
-};
\ No newline at end of file
+};
+
+unphased new Foo();
+// ^^^^^^^^ Only inflight classes can be instantiated by a factory, use "inflight new" instead
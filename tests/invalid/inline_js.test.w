@@ -0,0 +1,18 @@
+bring cloud;
+
+let bucket = new cloud.Bucket();
+
+let x = unsafe js: num { return 1; };
+//      ^^^^^^^^^^^^^^^^^^^^^^^^^^^^ Inline JavaScript can only be used in inflight code
+
+test "inline JavaScript" {
+  unsafe js (bucket) { bucket.put("a", "b"); };
+//           ^^^^^^ Cannot pass preflight object of type "Bucket" to inline JavaScript
+
+  let a = 1;
+  unsafe js (a, a) { };
+//              ^ "a" is already an input of this inline JavaScript
+
+  unsafe js (b) { };
+//           ^ Unknown symbol "b"
+}
@@ -21,3 +21,6 @@ p.internalMethod();
 
 let s = fixture.InternalStruct {};
 // ^ Error: Cannot access internal struct 'InternalStruct'
+
+fixture.internal.Keys.dataPrefix();
+// ^ Error: Cannot access internal namespace 'internal'
@@ -126,7 +126,7 @@ let objInsteadOfArray: StructyJson = {
 // Unkonwn variable in json object punning
 {"x":1, y: 2, unknownVar};
 // Unknown variable in explicitly typed json object punning
-Json {"x":1, y: 2, unknownVar};
+Json {"x":1, y: 2, otherUnknownVar};
 // Duplicate field in punned json
 let numField = 1;
 {numField, numField};
@@ -0,0 +1,19 @@
+bring cloud;
+
+let buckets = [new cloud.Bucket() as "b1", new cloud.Bucket() as "b2"];
+
+test "only reads the length" {
+  log("{buckets.length}");
+//      ^^^^^^^ "buckets" is lifted only to read "length", which also lifts every preflight object it contains
+}
+
+test "uses the buckets" {
+  assert(buckets.length == 2);
+  buckets.at(0).put("a", "b");
+}
+
+let names = ["a", "b"];
+
+test "collections without preflight objects" {
+  assert(names.length == 2);
+}
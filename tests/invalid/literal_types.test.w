@@ -0,0 +1,18 @@
+let send = (method: "GET" | "POST") => {};
+
+send("DELETE");
+//   ^^^^^^^^ Expected type to be ""GET" | "POST"", but got "str" instead
+
+let method = "GET";
+send(method);
+//   ^^^^^^ Expected type to be ""GET" | "POST"", but got "str" instead
+
+let m: "GET" | "POST" | "PUT" = "PUT";
+send(m);
+//   ^ Expected type to be ""GET" | "POST"", but got ""GET" | "POST" | "PUT"" instead
+
+let code: 200 | 404 = 500;
+//                    ^^^ Expected type to be "200 | 404", but got "num" instead
+
+let x: "a{method}" = "a";
+//     ^^^^^^^^^^^ String interpolation is not allowed in literal types
@@ -23,3 +23,11 @@ let m3: MutMap<num> = {"hello" => "world"};
 let m4 = MutMap<num>{ "hello" => 123 };
 let m5: MutMap<str> = m4;
 //                    ^^ Expected type to be "MutMap<str>", but got "MutMap<num>" instead
+
+// Mutable collections are invariant in their element type
+let m6 = MutMap<str>{"hello" => "world"};
+let m7: MutMap<str?> = m6;
+//                     ^^ Expected type to be "MutMap<str?>", but got "MutMap<str>" instead
+let arr6 = MutArray<str>["a"];
+let arr7: MutArray<str?> = arr6;
+//                         ^^^^ Expected type to be "MutArray<str?>", but got "MutArray<str>" instead
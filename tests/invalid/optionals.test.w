@@ -51,6 +51,20 @@ if let hi = hi {
 //^^^^^^^^^^^ Variable hi is not reassignable 
 }
 
+let maybeNum: num? = 1;
+if let n = maybeNum, let m = n {
+//                           ^ Expected type to be optional, but got "num" instead
+}
+
+if let n = maybeNum, n {
+//                   ^ Expected type to be "bool", but got "num" instead
+}
+
+if let n = maybeNum, let m = maybeNum {
+  m = 2;
+//^^^^^^ Variable m is not reassignable
+}
+
 struct A {
   val: num?;
 }
@@ -113,3 +127,15 @@ let nonOptionalFn = (): num => {
 let unwrapValueFn = nonOptionalFn()!;
 //                  ^^^^^^^^^^^^^^^ '!' expects an optional type, found "num"
 
+
+let maybeArr: Array<num>? = [1, 2];
+let first: num = maybeArr?[0];
+//               ^^^^^^^^^^^^ Expected type to be "num", but got "num?" instead
+
+let maybeMutArr: MutArray<num>? = MutArray<num>[1];
+maybeMutArr?[0] = 2;
+//^^^^^^^^^^^^^ Cannot assign to an optional element access ("?[")
+
+let maybeFunc: ((): num)? = (): num => { return 1; };
+let res: num = maybeFunc?();
+//             ^^^^^^^^^^^^ Expected type to be "num", but got "num?" instead
@@ -0,0 +1,21 @@
+let var count = 1;
+let var label = "before";
+
+let handler = inflight () => {
+  log("{count} {label}");
+};
+
+if true {
+  count = 2;
+//^^^^^ Variable "count" is reassigned after it was captured by inflight code
+
+  label += " and after";
+//^^^^^ Variable "label" is reassigned after it was captured by inflight code
+}
+
+// Reassigning before the variable is captured is fine
+let var prefix = "a";
+prefix = "b";
+test "uses prefix" {
+  log(prefix);
+}
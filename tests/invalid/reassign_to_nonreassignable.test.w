@@ -43,3 +43,25 @@ let f = (arg: num):num => {
 //^^^^^^^^ Variable arg is not reassignable
   return arg;
 };
+
+// Reassignable variables and private fields that are never reassigned
+let var neverChanged = 1;
+//      ^^^^^^^^^^^^ Variable "neverChanged" is never reassigned, consider removing "var"
+log("{neverChanged}");
+
+class Counter {
+  var count: num;
+  var step: num;
+//    ^^^^ Field "step" is never reassigned, consider removing "var"
+  pub var label: str;
+
+  new() {
+    this.count = 0;
+    this.step = 1;
+    this.label = "counter";
+  }
+
+  pub inc() {
+    this.count += this.step;
+  }
+}
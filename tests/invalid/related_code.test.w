@@ -0,0 +1,22 @@
+let n: num = "hello";
+//     ^^^ expected due to this type annotation
+//           ^^^^^^^ Expected type to be "num", but got "str" instead
+
+let f = (): str => {
+//          ^^^ expected due to this return type
+  return 1;
+//       ^ Expected type to be "str", but got "num" instead
+};
+
+let x = 1;
+//  ^ previous definition
+let x = 2;
+//  ^ Symbol "x" already defined in this scope
+
+let y = "outer";
+() => {
+  log(y);
+//    ^ Cannot access "y" because it is shadowed by another symbol with the same name
+  let y = "inner";
+//    ^ shadowed by this definition
+};
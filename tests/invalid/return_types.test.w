@@ -15,8 +15,76 @@ inflight (): void => {
 };
 
 class C {
-  func() {
+  pub func() {
     return 9;
 //  ^^^^^^^^^ Unexpected return value from void function
   }
 }
+
+let neverReturns = (): never => {
+  return;
+//^^^^^^^ Cannot return from a function whose return type is "never"
+};
+
+let neverFallsThrough = (): never => {
+  log("hi");
+};
+//^ A function whose return type is "never" must end by throwing an error
+
+let neverOnOneBranch = (x: bool): never => {
+  if x {
+    throw "x";
+  }
+};
+//^ A function whose return type is "never" must end by throwing an error
+
+class D {
+  inconsistent(x: num) {
+    if x > 0 {
+      return 1;
+    }
+    return "negative";
+//         ^^^^^^^^^^ Expected type to be "num", but got "str" instead
+  }
+}
+
+let afterReturn = (): num => {
+  return 1;
+  log("unreachable");
+//^^^^^^^^^^^^^^^^^^^ Unreachable code
+};
+
+let afterThrow = () => {
+  throw "oops";
+  log("unreachable");
+//^^^^^^^^^^^^^^^^^^^ Unreachable code
+};
+
+for i in 0..10 {
+  if i > 5 {
+    break;
+    log("unreachable");
+  //^^^^^^^^^^^^^^^^^^^ Unreachable code
+  }
+  continue;
+  log("unreachable");
+//^^^^^^^^^^^^^^^^^^^ Unreachable code
+}
+
+let bothBranchesReturn = (x: bool): str => {
+  if x {
+    return "yes";
+  } else {
+    return "no";
+  }
+  return "maybe";
+//^^^^^^^^^^^^^^^ Unreachable code
+};
+
+let forever = () => {
+  while true {
+    log("spinning");
+  }
+  log("done");
+//^^^^^^^^^^^^ Unreachable code
+};
@@ -16,4 +16,15 @@ inflight () => {
                        //^ Inflight classes cannot have an id
   new InflightClass() in pc;
                         //^ Inflight classes cannot have a scope
-};
\ No newline at end of file
+};
+
+// Verify the id of a preflight object can't use inflight values
+class Named {
+  pub inflight name: str;
+  inflight new() {
+    this.name = "named";
+  }
+}
+let named = new Named();
+new PreflightClass() as named.name;
+                      //^^^^^^^^^^ The id of a preflight object must be known in preflight, it can't use inflight values
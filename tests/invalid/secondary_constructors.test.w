@@ -0,0 +1,20 @@
+class Point {
+  pub x: num;
+  new(x: num) {
+    this.x = x;
+  }
+
+  pub static ofStr(s: str): Point {
+    return new Point(num.fromStr(s));
+  }
+
+  static ofBool(b: bool): Point {
+    return new Point(0);
+  }
+}
+
+new Point("1") as "p1";
+//                ^^^^ Cannot set id of an object created by secondary constructor "Point.ofStr" using `as`
+
+new Point(true);
+//        ^^^^ Expected type to be "num", but got "bool" instead
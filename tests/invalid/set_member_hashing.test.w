@@ -0,0 +1,25 @@
+let lists = MutSet<MutArray<num>>[MutArray<num>[1]];
+//          ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ "MutArray<num>" can't be a Set member because it's mutable, so its hash could change while it's in the set
+
+struct Bag {
+  items: MutArray<str>;
+}
+
+let hasBag = (bags: Set<Bag>, bag: Bag): bool => {
+  return bags.has(bag);
+//                ^^^ "Bag" can't be a Set member because its field "items" can't be hashed since it's mutable, so its hash could change while it's in the set
+};
+
+class Key impl std.IHashable {
+  pub hash(): str {
+    return "key";
+  }
+}
+
+let keys = Set<Key>[new Key() as "k1"];
+let key = new Key() as "k2";
+
+test "hashing set members inflight" {
+  assert(keys.has(key));
+//                ^^^ Cannot use "Key" as a Set member in inflight phase because its "hash" method is preflight
+}
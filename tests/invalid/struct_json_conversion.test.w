@@ -27,3 +27,18 @@ C.fromJson({});
 //^^^^^^^^ Struct "C" contains field "b" which cannot be represented in Json
 C.schema();
 //^^^^^^^^ Struct "C" contains field "b" which cannot be represented in Json
+
+struct D {
+  name: str;
+  count: num?;
+}
+
+D.fromJson({ count: 1 });
+//         ^^^^^^^^^^^^ Missing required field "name" from "D"
+D.fromJson({ name: "d", cont: 1 });
+//         ^^^^^^^^^^^^^^^^^^^^^^ "cont" is not a field of "D"
+D.fromJson({ name: 1 });
+//                 ^ Expected type to be "str", but got "num" instead
+
+// Validation is skipped when parsing unsafely
+D.fromJson({ count: 1 }, unsafe: true);
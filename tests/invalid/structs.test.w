@@ -69,3 +69,38 @@ let numField = "hello";
 let noSuchField = 1;
 SomeStruct1 { numField }; // Wrong type when using punning
 SomeStruct1 { noSuchField }; // Wrong field when using punning
+
+struct WithMethods {
+  name: str;
+
+  pub static make(): WithMethods {
+    return WithMethods { name: "made" };
+  }
+
+  pub describe(): str {
+  //  ^^^^^^^^ Struct methods must be static
+    return "";
+  }
+
+  protected static prot() {}
+  //               ^^^^ Struct methods must be public ("pub"), internal or private
+
+  static secret() {}
+}
+
+WithMethods.secret();
+//          ^^^^^^ Cannot access private member
+WithMethods.unknown();
+//          ^^^^^^^ Member "unknown" does not exist
+
+struct Point3 {
+  x: num;
+  y: num;
+  z: num;
+  label: str?;
+}
+
+let p1 = Point3 { y: 1 };
+//       ^^^^^^^^^^^^^^^ "x" and "z" are not initialized
+let p2 = Point3 {};
+//       ^^^^^^^^^ "x", "y" and "z" are not initialized
@@ -60,3 +60,19 @@ class ExtendedClass extends BaseClass {
 // test "inflight closure accesses super" {
 //   assert(y.invoke("") == "this: extended inflight m1, super: base inflight m1");
 // }
+
+class C extends A {
+  method() {
+    // Access a field that doesn't exist in the parent class
+    log(super.missing);
+            //^^^^^^^ super class "A" does not have a member named "missing"
+  }
+}
+
+class D {
+  method() {
+    // Access a field with no base class
+    log(super.x);
+            //^ Cannot access super member because class D has no parent
+  }
+}
@@ -0,0 +1,9 @@
+let u: unknown = "hello";
+
+u.length;
+//^^^^^^ Cannot access "length" on a value of type "unknown", narrow it to a known type first (e.g. with unsafeCast())
+
+let s: str = u;
+//           ^ Expected type to be "str", but got "unknown" instead
+
+let n: num = unsafeCast(u);
@@ -0,0 +1,10 @@
+while let x = 1 {
+//            ^ Expected type to be optional, but got "num" instead
+}
+
+let maybeNum: num? = 1;
+while let x = maybeNum {
+  x = 2;
+//^^^^^^ Variable x is not reassignable
+  break;
+}
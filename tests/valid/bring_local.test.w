@@ -25,6 +25,15 @@ let s = file1.Point {
   x: 1,
   y: 2,
 };
+assert(file1.Point.origin().x == 0);
+
+// module-level variables and functions from other files can be used
+assert(file1.DEFAULT_DATA == "<empty>");
+assert(file1.describe(s) == "(1, 2) 3");
+
+test "module-level variables can be lifted" {
+  assert(file1.DEFAULT_DATA.length == 7);
+}
 
 // enums from other files can be used
 let c = file1.Color.BLUE;
@@ -0,0 +1,11 @@
+bring "npm:uuid" as uuid;
+
+// npm modules without JSII types can be used in both phases, their exports are `any`
+let preflightId: str = uuid.v4();
+assert(uuid.validate(preflightId));
+
+test "use an npm module inflight" {
+  let id: str = uuid.v4();
+  assert(uuid.validate(id));
+  assert(id != preflightId);
+}
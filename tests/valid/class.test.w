@@ -258,4 +258,28 @@ class DerivedClassWithInnerClass extends BaseClassWithCtorArg {
     }
     super(1);
   }
-}
\ No newline at end of file
+}
+
+// fields only need to be initialized on the paths through the constructor that don't throw
+class Branches {
+  pub kind: str;
+  pub size: num;
+  new(size: num) {
+    if size > 10 {
+      this.kind = "large";
+    } else if size > 0 {
+      this.kind = "small";
+    } else {
+      throw "size must be positive";
+    }
+    try {
+      this.size = size;
+    } catch {
+      this.size = 0;
+    }
+  }
+}
+
+let branches = new Branches(3);
+assert(branches.kind == "small");
+assert(branches.size == 3);
@@ -0,0 +1,60 @@
+bring expect;
+
+// Classes with a public `compareTo` method can be compared with `<`, `<=`, `>` and `>=`
+class Version {
+  pub major: num;
+  pub minor: num;
+  new(major: num, minor: num) {
+    this.major = major;
+    this.minor = minor;
+  }
+
+  pub compareTo(other: Version): num {
+    if this.major != other.major {
+      return this.major - other.major;
+    }
+    return this.minor - other.minor;
+  }
+}
+
+let v1 = new Version(1, 2) as "v1";
+let v2 = new Version(1, 10) as "v2";
+
+assert(v1 < v2);
+assert(v1 <= v2);
+assert(v2 > v1);
+assert(v2 >= v1);
+assert(v1 <= v1);
+assert(!(v1 < v1));
+
+class Priority {
+  pub level: num;
+  new(level: num) {
+    this.level = level;
+  }
+
+  pub inflight compareTo(other: Priority): num {
+    return this.level - other.level;
+  }
+}
+
+let low = new Priority(1) as "low";
+let high = new Priority(5) as "high";
+
+inflight class Money {
+  pub cents: num;
+  new(cents: num) {
+    this.cents = cents;
+  }
+
+  pub compareTo(other: Money): num {
+    return this.cents - other.cents;
+  }
+}
+
+test "inflight objects are compared using compareTo" {
+  expect.equal(new Money(100) < new Money(200), true);
+  expect.equal(new Money(100) >= new Money(200), false);
+  expect.equal(high > low, true);
+  expect.equal(low >= high, false);
+}
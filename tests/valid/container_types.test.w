@@ -155,3 +155,28 @@ assert(s6.has(bucket2));
 let s7: Set<num> = s2;
 assert(s7.size == 3);
 assert(s7.has(1));
+
+// Immutable collections are covariant in their element type
+let arrStrings = ["a", "b"];
+let arrOptionalStrings: Array<str?> = arrStrings;
+assert(arrOptionalStrings.length == 2);
+let mapStrings = {"a" => "b"};
+let mapOptionalStrings: Map<str?> = mapStrings;
+assert(mapOptionalStrings.size() == 1);
+
+// Function parameters are contravariant and return types are covariant
+let describe = (items: Array<str?>): Array<str> => {
+  return ["{items.length}"];
+};
+let describeStrings: (Array<str>): Array<str?> = describe;
+assert(describeStrings(arrStrings).length == 1);
+
+// Type arguments of generic functions are inferred from their arguments
+let ofNums = Array.of(1, 2, 3);
+let ofNumsCheck: Array<num> = ofNums;
+assert(ofNumsCheck.at(2) == 3);
+let ofStrings = MutArray.of("a", "b");
+ofStrings.push("c");
+assert(ofStrings.length == 3);
+let ofArrays = Array.of([1], [2, 3]);
+assert(ofArrays.at(1).at(0) == 2);
@@ -116,3 +116,46 @@ test "Struct with different values" {
   assert(cat1 != cat3);
   assert(!(cat1 != cat2));
 }
+
+//-----------------------------------------------------------------------------
+// Class
+//-----------------------------------------------------------------------------
+class Pet {
+  pub name: str;
+  new(name: str) {
+    this.name = name;
+  }
+}
+
+class Dog extends Pet {
+  new(name: str) {
+    super(name);
+  }
+}
+
+let pet1 = new Pet("Rex") as "pet1";
+let pet2 = new Pet("Rex") as "pet2";
+let dog: Pet = new Dog("Rex");
+let maybePet: Pet? = nil;
+
+// Class instances are compared by reference, even if their fields are equal
+assert(pet1 == pet1);
+assert(pet1 != pet2);
+assert(dog != pet1);
+assert(maybePet != pet1);
+assert(maybePet == nil);
+
+inflight class Point {
+  pub x: num;
+  new(x: num) {
+    this.x = x;
+  }
+}
+
+test "Class instances are compared by reference" {
+  let p1 = new Point(1);
+  let p2 = new Point(1);
+  assert(p1 == p1);
+  assert(p1 != p2);
+  assert(pet1 != pet2);
+}
@@ -40,3 +40,55 @@ enum DocumentedEnum {
     /// blah blah blah
     VARIANT
 }
+
+// Values can have explicit backing strings, the rest are backed by their name
+enum Status {
+  Active = "active",
+  Disabled = "disabled",
+  Unknown
+}
+
+struct Account {
+  status: Status;
+}
+
+assert(Status.Active.value == "active");
+assert(Status.Unknown.value == "Unknown");
+assert(SomeEnum.ONE.value == "ONE");
+assert("{Status.Disabled}" == "disabled");
+
+let account = Account { status: Status.Disabled };
+assert(Json.stringify(account) == "\{\"status\":\"disabled\"}");
+assert(Account.fromJson({ status: "active" }).status == Status.Active);
+assert(Account.tryFromJson({ status: "Active" }) == nil);
+
+test "backing values inflight" {
+  assert(Status.Active.value == "active");
+  assert(account.status.value == "disabled");
+}
+
+// Values of a flags enum are distinct bits, and can be combined with `|` and `&`
+flags enum Permission {
+  Read,
+  Write,
+  Execute
+}
+
+let readWrite = Permission.Read | Permission.Write;
+assert(Permission.Read.value == 1);
+assert(Permission.Execute.value == 4);
+assert(readWrite.value == 3);
+assert((readWrite & Permission.Write) == Permission.Write);
+assert((readWrite & Permission.Execute).value == 0);
+
+struct Grant {
+  permission: Permission;
+}
+
+assert(Grant.fromJson({ permission: 5 }).permission == (Permission.Read | Permission.Execute));
+assert(Grant.tryFromJson({ permission: 8 }) == nil);
+
+test "flags inflight" {
+  assert((readWrite & Permission.Read) == Permission.Read);
+  assert((readWrite | Permission.Execute).value == 7);
+}
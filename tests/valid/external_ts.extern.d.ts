@@ -69,15 +69,15 @@ export class Resource extends Construct implements IResource {
 }
 /** The request's method. */
 export enum HttpMethod {
-  GET = 0,
-  PUT = 1,
-  DELETE = 2,
-  PATCH = 3,
-  POST = 4,
-  OPTIONS = 5,
-  HEAD = 6,
-  CONNECT = 7,
-  TRACE = 8,
+  GET = "GET",
+  PUT = "PUT",
+  DELETE = "DELETE",
+  PATCH = "PATCH",
+  POST = "POST",
+  OPTIONS = "OPTIONS",
+  HEAD = "HEAD",
+  CONNECT = "CONNECT",
+  TRACE = "TRACE",
 }
 /** Represents a length of time. */
 export class Duration implements ILiftable {
@@ -148,6 +148,14 @@ export interface IBucketEventHandler extends IInflight {
   one or more of its methods are called. */
   readonly onLift: (host: IInflightHost, ops: (readonly (string)[])) => void;
 }
+/** Bucket events to subscribe to */
+export enum BucketEventType {
+  CREATE = "CREATE",
+  DELETE = "DELETE",
+  UPDATE = "UPDATE",
+}
+/** The signature of the inflight `handle` method of `IBucketEventHandler` */
+export type IBucketEventHandler$Handle = (key: string, type: BucketEventType) => Promise<void>;
 /** `onCreate` event options. */
 export interface BucketOnCreateOptions {
 }
@@ -175,3 +175,53 @@ class Foo {
     }
   }
 }
+
+// iterating with indices and entries
+let var indexSum = 0;
+let var joined = "";
+for item, i in ["a", "b", "c"] {
+  indexSum = indexSum + i;
+  joined = joined + "{i}{item}";
+}
+assert(indexSum == 3);
+assert(joined == "0a1b2c");
+
+for x, i in 5..8 {
+  assert(x == i + 5);
+}
+
+// sets aren't indexable, so they're collected first
+let var setJoined = "";
+for item, i in Set<str>["x", "y"] {
+  setJoined = setJoined + "{i}{item}";
+}
+assert(setJoined == "0x1y");
+
+// ranges stored in variables are arrays, so they can be iterated more than once
+let digits = 0..3;
+let var digitCount = 0;
+for d, i in digits {
+  assert(d == i);
+  digitCount = digitCount + 1;
+}
+for d in digits {
+  digitCount = digitCount + 1;
+}
+assert(digitCount == 6);
+assert(digits.length == 3);
+
+let scores = { "alice" => 1, "bob" => 2 };
+let var total = 0;
+let var names = "";
+for name, score in scores {
+  total = total + score;
+  names = names + name;
+}
+assert(total == 3);
+assert(names == "alicebob");
+
+let mutScores = MutMap<num>{ "carol" => 3 };
+for name, score in mutScores {
+  assert(name == "carol");
+  assert(score == 3);
+}
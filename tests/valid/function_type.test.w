@@ -24,3 +24,61 @@ interface IFace {
   my_method(x: num): void;
   inflight my_method2(x: num): void;
 }
+
+// never is the return type of functions that always throw
+let fail = (message: str): never => {
+  throw message;
+};
+
+// calling a never function satisfies any return type
+let unimplemented = (): str => {
+  fail("not implemented");
+};
+
+// the return type of a closure that always throws is inferred as never
+let alwaysFails = () => {
+  throw "oops";
+};
+let asNumFn: (): num = alwaysFails;
+
+// functions whose every path ends by throwing don't need a final throw or return
+let failEither = (fatal: bool): never => {
+  if fatal {
+    throw "fatal";
+  } else {
+    fail("not fatal either");
+  }
+};
+
+let unwrapOrFail = (x: str?): never => {
+  if let x = x {
+    throw "unexpected {x}";
+  } else {
+    throw "missing";
+  }
+};
+
+let retryForever = (attempt: (): bool): never => {
+  while true {
+    if attempt() {
+      throw "done";
+    }
+  }
+};
+
+let firstEven = (values: Array<num>): num => {
+  while true {
+    for v in values {
+      if v % 2 == 0 {
+        return v;
+      }
+    }
+    throw "no even number";
+  }
+};
+assert(firstEven([1, 2, 3]) == 2);
+
+// any value can be stored in an unknown, but it must be narrowed before it's used
+let u: unknown = 5;
+let n: num = unsafeCast(u);
+let values = Array<unknown>[1, "two", true];
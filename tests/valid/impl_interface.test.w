@@ -133,3 +133,37 @@ class ImplPreflightIfaceInPreflightClass impl IPreflight {
     return;
   }
 }
+
+// Interfaces can require implementing classes to provide static methods, e.g. factories
+struct ConnectionOptions {
+  host: str;
+}
+
+interface IConnection {
+  host(): str;
+  static connect(options: ConnectionOptions): IConnection;
+  static inflight defaultHost(): str;
+}
+
+class Connection impl IConnection {
+  options: ConnectionOptions;
+  new(options: ConnectionOptions) {
+    this.options = options;
+  }
+  pub host(): str {
+    return this.options.host;
+  }
+  pub static connect(options: ConnectionOptions): IConnection {
+    return new Connection(options);
+  }
+  pub static inflight defaultHost(): str {
+    return "localhost";
+  }
+}
+
+let connection = Connection.connect(host: "example.com");
+assert(connection.host() == "example.com");
+
+test "static interface methods" {
+  assert(Connection.defaultHost() == "localhost");
+}
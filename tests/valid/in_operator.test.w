@@ -0,0 +1,24 @@
+let m = {"a" => 1, "b" => 2};
+assert("a" in m);
+assert(!("c" in m));
+
+let mm = MutMap<num>{};
+mm.set("x", 1);
+assert("x" in mm);
+
+let s = Set<str>["hello", "world"];
+assert("hello" in s);
+assert(!("bye" in s));
+
+let arr = [1, 2, 3];
+assert(2 in arr);
+assert(!(4 in arr));
+
+assert("ell" in "hello");
+assert(!("xyz" in "hello"));
+
+test "in operator inflight" {
+  assert("b" in m);
+  assert("world" in s);
+  assert(3 in arr);
+}
@@ -5,17 +5,23 @@ let arr = Array<num>[1, 2, 3];
 
 assert(arr[0] == 1);
 assert(arr[2 - 5] == 1);
+assert(arr[-1] == 3);
+assert(arr[-3] == 1);
 assert(arr[0] != arr[1]);
 
 
+// constant out-of-bounds indices are reported at compile time, so use variables to test the runtime checks
+let tooLow = -5;
+let tooHigh = 5;
+
 try {
-  arr[-5];
+  arr[tooLow];
 } catch e {
   assert(e == "Index -5 out of bounds for array of length 3");
 }
 
 try {
-  arr[5];
+  arr[tooHigh];
 } catch e {
   assert(e == "Index 5 out of bounds for array of length 3");
 }
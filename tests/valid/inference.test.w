@@ -54,3 +54,96 @@ let takesOptionalClosure = (fn: ((num): str)?) => {};
 takesOptionalClosure((a) => {
   return "";
 });
+
+// closure parameters are inferred from the function type expected by the context
+let applyTwice = (f: (num): num, x: num): num => {
+  return f(f(x));
+};
+assert(applyTwice((n) => { return n * 2; }, 3) == 12);
+
+let toLength: (str): num = (s) => {
+  return s.length;
+};
+assert(toLength("abc") == 3);
+
+struct Handlers {
+  onValue: (str): str;
+}
+let handle = (value: str, handlers: Handlers): str => {
+  return handlers.onValue(value);
+};
+assert(handle("hi", onValue: (v) => { return "{v}!"; }) == "hi!");
+
+let sumResults = (...fns: Array<(num): num>): num => {
+  let var sum = 0;
+  for f in fns {
+    sum += f(1);
+  }
+  return sum;
+};
+assert(sumResults((a) => { return a + 1; }, (b) => { return b * 10; }) == 12);
+
+class Greeter {
+  prefix: str;
+  new() {
+    this.prefix = "hello";
+  }
+  greeting(name: str) {
+    if name == "" {
+      return this.prefix;
+    }
+    return "{this.prefix} {name}";
+  }
+  nothing() {
+    log("no return value");
+  }
+  pub greet(name: str): str {
+    this.nothing();
+    return this.greeting(name);
+  }
+}
+assert(new Greeter().greet("wing") == "hello wing");
+
+// Different but compatible types are unified to their common supertype
+interface Shape {
+  area(): num;
+}
+inflight class Square impl Shape {
+  side: num;
+  new(side: num) {
+    this.side = side;
+  }
+  pub area(): num {
+    return this.side * this.side;
+  }
+}
+inflight class Circle impl Shape {
+  pub area(): num {
+    return 3;
+  }
+}
+test "common supertypes are inferred" {
+  let pick = (square: bool) => {
+    if square {
+      return new Square(2);
+    } else {
+      return new Circle();
+    }
+  };
+  assert(pick(true).area() == 4);
+
+  let shapes = [new Square(1), new Circle()];
+  assert(shapes.at(1).area() == 3);
+
+  let maybeSquare: Square? = nil;
+  let shape = maybeSquare ?? new Circle();
+  assert(shape.area() == 3);
+
+  let firstOrNil = (items: Array<num>) => {
+    if items.length == 0 {
+      return nil;
+    }
+    return items.at(0);
+  };
+  assert(firstOrNil([]) == nil);
+}
@@ -0,0 +1,35 @@
+// Preflight constants are inlined into inflight code instead of being lifted
+let greeting = "hello, \"world\"\nand more";
+let count = 42;
+let fraction = 0.5;
+let enabled = true;
+let config = Json { name: "app", ports: [80, 443], debug: false, nested: { level: 1 } };
+let items = Json ["a", "b"];
+
+// Not constants, these are still lifted
+let var reassigned = "before";
+reassigned = "after";
+let mutable = MutJson { value: 1 };
+
+class Reader {
+  pub inflight read(): str {
+    return "{greeting} {count}";
+  }
+}
+
+let reader = new Reader();
+
+test "constants are inlined" {
+  assert(greeting == "hello, \"world\"\nand more");
+  assert(count + 1 == 43);
+  assert(fraction * 2 == 1);
+  assert(enabled);
+  assert(config.get("name").asStr() == "app");
+  assert(config.get("ports").getAt(1).asNum() == 443);
+  assert(config.get("nested").get("level").asNum() == 1);
+  assert(!config.get("debug").asBool());
+  assert(items.getAt(0).asStr() == "a");
+  assert(reassigned == "after");
+  assert(mutable.get("value").asNum() == 1);
+  assert(reader.read() == "hello, \"world\"\nand more 42");
+}
@@ -0,0 +1,46 @@
+bring cloud;
+bring expect;
+
+inflight class Counter {
+  pub count: num;
+  new(start: num) {
+    this.count = start;
+  }
+
+  pub inc() {
+    this.count += 1;
+  }
+}
+
+let start = 10;
+let makeCounter = inflight new Counter(start);
+
+// The factory can be passed around like any other inflight closure
+let callFactory = (factory: inflight (): Counter): inflight (): num => {
+  return inflight () => {
+    let c = factory();
+    c.inc();
+    return c.count;
+  };
+};
+
+let counterValue = callFactory(makeCounter);
+
+class Holder {
+  pub factory: inflight (): Counter;
+  new() {
+    this.factory = inflight new Counter(100);
+  }
+}
+
+let holder = new Holder();
+
+test "inflight factory creates a new instance each time" {
+  let c1 = makeCounter();
+  let c2 = makeCounter();
+  c1.inc();
+  expect.equal(c1.count, 11);
+  expect.equal(c2.count, 10);
+  expect.equal(counterValue(), 11);
+  expect.equal(holder.factory().count, 100);
+}
@@ -0,0 +1,40 @@
+let greeting = "hello";
+
+test "inline JavaScript" {
+  let a = 1;
+  let b = 2;
+  let sum = unsafe js (a, b): num {
+    return a + b;
+  };
+  assert(sum == 3);
+
+  // lifted values can be passed as inputs too
+  let upper = unsafe js (greeting): str { return greeting.toUpperCase(); };
+  assert(upper == "HELLO");
+
+  // braces in strings, template literals and comments don't end the block
+  let braces = unsafe js: str {
+    const open = "{"; // }
+    return `${open}}`;
+  };
+  assert(braces.length == 2);
+
+  // nor do braces in regular expressions
+  let stripped = unsafe js: str { return "a}b".replace(/[}"]/g, ""); };
+  assert(stripped == "ab");
+
+  unsafe js { console.log("no inputs or result"); };
+}
+
+test "the result of inline JavaScript is checked against its type" {
+  let var error = "";
+  try {
+    let n = unsafe js: num { return "one"; };
+  } catch e {
+    error = e;
+  }
+  assert(error.contains("Inline JavaScript returned 'one', which is not a value of type \"num\""));
+
+  let nums = unsafe js: Array<num?> { return [1, undefined]; };
+  assert(nums.length == 2);
+}
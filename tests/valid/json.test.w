@@ -290,4 +290,17 @@ struct StructToPun {
 }
 let structToPunFromJson: StructToPun = Json {numVar, strVar};
 assert(structToPunFromJson.numVar == 1);
-assert(structToPunFromJson.strVar == "s");
\ No newline at end of file
+assert(structToPunFromJson.strVar == "s");
+// Literals copied from JSON config blocks may have comments and trailing commas, but stringify to strict JSON
+let copiedConfig = {
+  // how many times to retry
+  retries: 3,
+  "log-level": "debug", /* or "info" */
+  endpoints: ["a", "b",],
+};
+assert(Json.stringify(copiedConfig) == "\{\"retries\":3,\"log-level\":\"debug\",\"endpoints\":[\"a\",\"b\"]}");
+let copiedStruct: StructToPun = {
+  numVar: 1,
+  strVar: "s", // the last field
+};
+assert(copiedStruct.strVar == "s");
@@ -0,0 +1,40 @@
+struct Request {
+  method: "GET" | "POST";
+  retries: (0 | 1 | 3)?;
+}
+
+let send = (method: "GET" | "POST", path: str): str => {
+  return "{method} {path}";
+};
+
+assert(send("GET", "/") == "GET /");
+
+let r = Request { method: "POST", retries: 3 };
+assert(r.method == "POST");
+assert(r.method.length == 4);
+
+// literal types can be used wherever a value of their primitive type is expected
+let m: "GET" | "POST" = r.method;
+let s: str = m;
+assert(send(m, "/users") == "POST /users");
+
+// a literal type is a subtype of any literal type that allows all of its values
+let methods = Array<"GET" | "POST" | "PUT">["PUT", m];
+assert(methods.length == 2);
+
+let code: 200 | 404 = 404;
+assert(code + 1 == 405);
+
+test "struct schema only accepts the allowed literals" {
+  let req = Request.fromJson({ method: "GET" });
+  assert(req.method == "GET");
+
+  let var error = "";
+  try {
+    // a literal would be rejected at compile time, so the Json is parsed at runtime
+    Request.fromJson(Json.parse("\{\"method\": \"DELETE\"}"));
+  } catch e {
+    error = e;
+  }
+  assert(error != "");
+}
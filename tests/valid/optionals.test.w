@@ -133,6 +133,36 @@ let fun = (a: str?): str => {
 assert(fun("hello") == "hello");
 assert(fun(nil) == "default");
 
+// if let chains with guards
+let chain = (x: num?, y: num?): str => {
+  if let a = x, let b = y, a > b {
+    return "{a} > {b}";
+  } else if let a = x {
+    return "only {a}";
+  } else {
+    return "none";
+  }
+};
+
+assert(chain(2, 1) == "2 > 1");
+assert(chain(1, 2) == "only 1");
+assert(chain(1, nil) == "only 1");
+assert(chain(nil, 1) == "none");
+
+// chained bindings can shadow and use earlier ones
+let chainedName: Name? = Name { first: "John", last: "Doe" };
+if let n = chainedName, let var n = n.last, n.length > 0 {
+  n = "{n}!";
+  assert(n == "Doe!");
+} else {
+  assert(false);
+}
+
+let noLast: Name? = Name { first: "John" };
+if let n = noLast, let last = n.last {
+  assert(false);
+}
+
 class Node {
   pub value: num;
   pub left: Node?;
@@ -256,3 +286,17 @@ assert(maybeX! == 0);
 
 let maybeY: str? = "";
 assert(maybeY! == "");
+
+// optional element access and calls
+let maybeMap: Map<num>? = {"a" => 1};
+let noMap: Map<num>? = nil;
+assert(maybeMap?["a"] == 1);
+assert(noMap?["a"] == nil);
+
+let maybeArr: Array<str>? = ["x", "y"];
+assert(maybeArr?[-1] ?? "" == "y");
+
+let maybeAdd: ((num, num): num)? = (a: num, b: num): num => { return a + b; };
+let noAdd: ((num, num): num)? = nil;
+assert(maybeAdd?(1, 2) == 3);
+assert(noAdd?(1, 2) == nil);
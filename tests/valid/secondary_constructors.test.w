@@ -0,0 +1,54 @@
+bring expect;
+
+class Point {
+  pub x: num;
+  pub y: num;
+  new(x: num, y: num) {
+    this.x = x;
+    this.y = y;
+  }
+
+  pub static of(xy: Array<num>): Point {
+    return new Point(xy.at(0), xy.at(1));
+  }
+
+  pub static ofJson(j: Json): Point {
+    return new Point(num.fromJson(j.get("x")), num.fromJson(j.get("y")));
+  }
+
+  // Not a secondary constructor since it doesn't return a `Point`
+  pub static ofString(s: str): str {
+    return s;
+  }
+}
+
+let p1 = new Point(1, 2);
+let p2 = new Point([3, 4]);
+let p3 = new Point({ x: 5, y: 6 });
+
+expect.equal(p1.x + p1.y, 3);
+expect.equal(p2.x + p2.y, 7);
+expect.equal(p3.x + p3.y, 11);
+
+inflight class Duration {
+  pub ms: num;
+  new(ms: num) {
+    this.ms = ms;
+  }
+
+  pub static ofSeconds(seconds: num, extraMs: num?): Duration {
+    return new Duration(seconds * 1000 + (extraMs ?? 0));
+  }
+
+  pub static ofDuration(d: duration): Duration {
+    return new Duration(d.milliseconds);
+  }
+}
+
+test "new is routed to secondary constructors" {
+  expect.equal(new Duration(1500).ms, 1500);
+  expect.equal(new Duration(1, 500).ms, 1500);
+  expect.equal(new Duration(2s).ms, 2000);
+  expect.equal(p2.y, 4);
+  expect.equal(p3.x, 5);
+}
@@ -0,0 +1,108 @@
+// Structs are hashed by their contents, so equal structs are the same set member
+struct Point {
+  x: num;
+  y: num;
+}
+
+let points = MutSet<Point>[];
+points.add(Point { x: 1, y: 2 });
+points.add(Point { x: 1, y: 2 });
+points.add(Point { x: 2, y: 1 });
+assert(points.size == 2);
+assert(points.has(Point { x: 1, y: 2 }));
+assert(points.delete(Point { x: 2, y: 1 }));
+assert(points.size == 1);
+
+let pointSet = Set<Point>[Point { x: 0, y: 0 }, Point { x: 0, y: 0 }];
+assert(pointSet.size == 1);
+
+// Immutable collections and Json are hashed by their contents too
+let lists = Set<Array<num>>[[1, 2], [1, 2], [2, 1]];
+assert(lists.size == 2);
+assert(lists.has([2, 1]));
+
+let docs = Set<Json>[{ "a": 1 }, { "a": 1 }];
+assert(docs.size == 1);
+
+// Class instances are hashed by reference
+class Tag {
+  pub name: str;
+  new(name: str) {
+    this.name = name;
+  }
+}
+
+let t1 = new Tag("a") as "t1";
+let t2 = new Tag("a") as "t2";
+let tags = Set<Tag>[t1, t2, t1];
+assert(tags.size == 2);
+
+// ...even if they have a `hash` method
+class Label {
+  pub name: str;
+  new(name: str) {
+    this.name = name;
+  }
+
+  pub hash(): str {
+    return this.name;
+  }
+}
+
+let labels = Set<Label>[new Label("a") as "l1", new Label("a") as "l2"];
+assert(labels.size == 2);
+
+// ...unless they implement `std.IHashable`
+class Email impl std.IHashable {
+  pub address: str;
+  new(address: str) {
+    this.address = address;
+  }
+
+  pub hash(): str {
+    return this.address.lowercase();
+  }
+}
+
+let emails = MutSet<Email>[new Email("me@example.com") as "e1", new Email("ME@example.com") as "e2"];
+assert(emails.size == 1);
+assert(emails.has(new Email("Me@Example.com") as "e3"));
+let e4 = new Email("me@EXAMPLE.com") as "e4";
+assert(e4 in emails);
+
+// Objects of different classes are never the same member, even with the same hash
+class Username impl std.IHashable {
+  pub name: str;
+  new(name: str) {
+    this.name = name;
+  }
+
+  pub hash(): str {
+    return "me@example.com";
+  }
+}
+
+let ids = MutSet<std.IHashable>[new Email("me@example.com") as "e5"];
+ids.add(new Username("me") as "u1");
+assert(ids.size == 2);
+
+// Members are found in sets that weren't created with a set literal
+let copied = pointSet.copyMut();
+copied.add(Point { x: 0, y: 0 });
+assert(copied.size == 1);
+assert(copied.delete(Point { x: 0, y: 0 }));
+
+test "set members are hashed inflight" {
+  assert(points.has(Point { x: 1, y: 2 }));
+  assert(pointSet.has(Point { x: 0, y: 0 }));
+  assert(lists.has([1, 2]));
+  assert(tags.has(t1));
+
+  let inflightPoints = MutSet<Point>[Point { x: 3, y: 3 }];
+  inflightPoints.add(Point { x: 3, y: 3 });
+  assert(inflightPoints.size == 1);
+
+  let inflightCopy = pointSet.copyMut();
+  assert(inflightCopy.delete(Point { x: 0, y: 0 }));
+  assert(inflightCopy.size == 0);
+}
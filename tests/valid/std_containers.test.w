@@ -63,3 +63,16 @@ let heterogeneousMap = Map<Animal>{
   "cat" => new Cat() as "C5",
   "dog" => new Dog() as "D4",
 };
+
+// Immutable containers are covariant in their element type
+let cats = [new Cat() as "C6"];
+let animals: Array<Animal> = cats;
+assert(animals.length == 1);
+let animalSet: Set<Animal> = Set<Cat>[new Cat() as "C8"];
+assert(animalSet.size == 1);
+
+// Mutable containers are invariant, so they're copied to widen their element type
+let mutAnimals: MutArray<Animal> = animals.copyMut();
+mutAnimals.push(new Dog() as "D5");
+assert(mutAnimals.length == 2);
+assert(cats.length == 1);
@@ -30,8 +30,21 @@ pub enum Color {
 pub struct Point {
   x: num;
   y: num;
+
+  /// The point at the origin
+  pub static origin(): Point {
+    return Point { x: 0, y: 0 };
+  }
 }
 
 pub interface Shape {
   area(): num;
 }
+
+pub let DEFAULT_DATA = "<empty>";
+internal let secret = "shh";
+
+/// Describes a point
+pub fn describe(p: Point): str {
+  return "({p.x}, {p.y}) {secret.length}";
+}
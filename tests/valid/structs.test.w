@@ -136,4 +136,44 @@ let someStruct3 = SomeStruct3 {
 assert(someStruct3.boolField == true);
 assert(someStruct3.strField == "leet");
 assert(someStruct3.structField.numField == 1337);
-assert(someStruct3.otherField == "good");
\ No newline at end of file
+assert(someStruct3.otherField == "good");
+
+// Structs can declare static methods to encapsulate their construction logic
+struct Opts {
+  name: str;
+  retries: num;
+
+  pub static default(): Opts {
+    return Opts { name: "default", retries: Opts.maxRetries() };
+  }
+
+  pub static withName(name: str): Opts {
+    return Opts { name, retries: Opts.default().retries };
+  }
+
+  pub static inflight inflightDefault(): Opts {
+    return Opts { name: "inflight", retries: 1 };
+  }
+
+  static maxRetries(): num {
+    return 3;
+  }
+}
+
+let defaultOpts = Opts.default();
+assert(defaultOpts.name == "default");
+assert(defaultOpts.retries == 3);
+assert(Opts.withName("custom").name == "custom");
+
+// struct static methods don't shadow the builtin ones
+let parsedOpts = Opts.fromJson({ name: "parsed", retries: 2 });
+assert(parsedOpts.name == "parsed");
+
+test "struct static methods" {
+  assert(defaultOpts.retries == 3);
+  assert(Opts.inflightDefault().name == "inflight");
+
+  // phase independent struct statics can be called inflight as well
+  assert(Opts.default().name == "default");
+  assert(Opts.withName("inflight custom").retries == 3);
+}
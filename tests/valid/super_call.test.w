@@ -71,4 +71,47 @@ class ExtendedClass extends BaseClass {
 let extended = new ExtendedClass();
 test "super call sets binding permissions" {
   expect.equal(extended.do(), "value");
-}
\ No newline at end of file
+}
+// Test access to fields of the parent class through `super`
+class Greeter {
+  pub greeting: str;
+  pub format: (str): str;
+  pub bucket: cloud.Bucket;
+
+  new() {
+    this.greeting = "hello";
+    this.format = (name: str): str => {
+      return "{this.greeting}, {name}";
+    };
+    this.bucket = new cloud.Bucket() as "greeter bucket";
+  }
+
+  pub greet(name: str): str {
+    return this.format(name);
+  }
+}
+
+class LoudGreeter extends Greeter {
+  pub greet(name: str): str {
+    // Delegate to the parent's implementation and read its fields
+    return "{super.greet(name)}! ({super.greeting})";
+  }
+
+  pub shout(name: str): str {
+    // Closure fields of the parent class can be called through `super` too
+    return super.format(name).uppercase();
+  }
+
+  pub inflight store(name: str) {
+    super.bucket.put(name, super.greeting);
+  }
+}
+
+let loud = new LoudGreeter();
+expect.equal(loud.greet("world"), "hello, world! (hello)");
+expect.equal(loud.shout("world"), "HELLO, WORLD");
+
+test "super field access inflight" {
+  loud.store("world");
+  expect.equal(loud.bucket.get("world"), "hello");
+}
@@ -31,3 +31,40 @@ while i < 10 {
 }
 assert(i == 10);
 assert(v == 5);
+
+// while let
+let queue = MutArray<num>[1, 2, 3, 4];
+let tryPop = (): num? => {
+  if queue.length > 0 {
+    return queue.pop();
+  }
+  return nil;
+};
+
+let var sum = 0;
+while let item = tryPop() {
+  if item == 3 {
+    continue;
+  }
+  sum = sum + item;
+}
+assert(sum == 7);
+assert(queue.length == 0);
+
+let stack = MutArray<str>["a", "b", "c"];
+let var popped = "";
+while let var s = stack.tryAt(stack.length - 1) {
+  stack.pop();
+  s = s.uppercase();
+  if s == "A" {
+    break;
+  }
+  popped = popped + s;
+}
+assert(popped == "CB");
+assert(stack.length == 0);
+
+let none: str? = nil;
+while let n = none {
+  assert(false);
+}
@@ -5,7 +5,7 @@ import { mkdtemp, writeFile, copyFile } from "fs/promises";
 import { tmpdir } from "os";
 import { runWingCommand } from "./utils";
 
-test("warning is emitted when an unsupported package manager is used", async () => {
+test("no warning is emitted when a pnpm/yarn/bun lockfile is found", async () => {
   const wingFile = "hello.test.w";
   const tmpDir = await mkdtemp(path.join(tmpdir(), "wing-test-"));
   await copyFile(